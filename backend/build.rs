@@ -79,6 +79,7 @@ fn main() {
     let text_detection_model = dir.join("text_detection.onnx");
     let text_recognition_model = dir.join("text_recognition.onnx");
     let text_alphabet_txt = dir.join("alphabet_94.txt");
+    let dashboard_html = dir.join("dashboard.html");
 
     tonic_build::compile_protos("proto/input.proto").unwrap();
     println!(
@@ -418,4 +419,10 @@ fn main() {
         "cargo:rustc-env=TEXT_RECOGNITION_ALPHABET={}",
         text_alphabet_txt.to_str().unwrap()
     );
+
+    // Control server
+    println!(
+        "cargo:rustc-env=DASHBOARD_HTML={}",
+        dashboard_html.to_str().unwrap()
+    );
 }
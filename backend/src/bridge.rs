@@ -1,31 +1,35 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, VecDeque};
 use std::fmt::Debug;
-use std::{any::Any, cell::RefCell};
+use std::{
+    any::Any,
+    cell::{Cell, RefCell},
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
 
 use anyhow::Result;
 #[cfg(test)]
 use mockall::automock;
 use platforms::windows::{
-    self, BitBltCapture, Frame, Handle, KeyInputKind, KeyKind, Keys, WgcCapture, WindowBoxCapture,
+    self, BitBltCapture, DesktopDuplicationCapture, Frame, Handle, KeyInputKind, KeyKind, Keys,
+    KmBoxNet, SerialPort, WgcCapture, WindowBoxCapture,
 };
 
-use crate::context::MS_PER_TICK_F32;
+use crate::context::ms_per_tick_f32;
 use crate::database::Seeds;
+use crate::mouse_path::MousePath;
 use crate::rng::Rng;
 use crate::rpc;
-use crate::{CaptureMode, context::MS_PER_TICK, rpc::KeysService};
-
-/// Base mean in milliseconds to generate a pair from.
-const BASE_MEAN_MS_DELAY: f32 = 100.0;
-
-/// Base standard deviation in milliseconds to generate a pair from.
-const BASE_STD_MS_DELAY: f32 = 20.0;
+use crate::{
+    Bound, CaptureMode, InputEvent, InputEventKind, InputHumanization, InputMethod,
+    JitterDistribution, KeyRemap, Settings, context::ms_per_tick, rpc::KeysService,
+};
+use crate::{frame_recorder::FrameReplay, frame_stream::FrameStream};
 
-/// The rate at which generated standard deviation will revert to the base [`BASE_STD_MS_DELAY`]
-/// over time.
+/// The rate at which generated mean and standard deviation will revert to the base
+/// [`InputHumanization::hold_mean_millis`] and [`InputHumanization::hold_std_millis`] over time.
 const MEAN_STD_REVERSION_RATE: f32 = 0.2;
 
-/// The rate at which generated mean will revert to the base [`BASE_MEAN_MS_DELAY`] over time.
+/// The volatility of generated mean and standard deviation over time.
 const MEAN_STD_VOLATILITY: f32 = 3.0;
 
 /// The input method to use for the key sender.
@@ -34,6 +38,36 @@ const MEAN_STD_VOLATILITY: f32 = 3.0;
 pub enum KeySenderMethod {
     Rpc(Handle, String),
     Default(Handle, KeyInputKind),
+    Interception(Handle, KeyInputKind),
+    /// Sends to the serial port and baud rate.
+    Serial(Handle, KeyInputKind, String, u32),
+    /// Sends to the KMBox Net address and UUID.
+    KmBoxNet(Handle, KeyInputKind, String, String),
+}
+
+impl KeySenderMethod {
+    /// Creates the method for [`Settings::input_method`] sending to `handle` with `kind`.
+    pub fn from_settings(settings: &Settings, handle: Handle, kind: KeyInputKind) -> Self {
+        match settings.input_method {
+            InputMethod::Default => KeySenderMethod::Default(handle, kind),
+            InputMethod::Rpc => {
+                KeySenderMethod::Rpc(handle, settings.input_method_rpc_server_url.clone())
+            }
+            InputMethod::Interception => KeySenderMethod::Interception(handle, kind),
+            InputMethod::Serial => KeySenderMethod::Serial(
+                handle,
+                kind,
+                settings.input_method_serial_port.clone(),
+                settings.input_method_serial_baud_rate,
+            ),
+            InputMethod::KmBoxNet => KeySenderMethod::KmBoxNet(
+                handle,
+                kind,
+                settings.input_method_kmbox_address.clone(),
+                settings.input_method_kmbox_uuid.clone(),
+            ),
+        }
+    }
 }
 
 /// The inner kind of the key sender.
@@ -53,6 +87,16 @@ pub enum MouseAction {
     Scroll,
 }
 
+/// How the frame used for detection maps to the client area of the captured window.
+#[derive(Clone, Copy, PartialEq, Eq, Default, Debug)]
+pub struct MouseFrame {
+    /// The top-left of the detection frame inside the captured frame (e.g. from cropping).
+    pub offset: (i32, i32),
+    /// The size of the captured frame to scale to the client area or [`None`] if it is not
+    /// scaled.
+    pub size: Option<(i32, i32)>,
+}
+
 /// A trait for sending keys.
 #[cfg_attr(test, automock)]
 pub trait KeySender: Debug {
@@ -68,12 +112,26 @@ pub trait KeySender: Debug {
     /// TODO: Unfortunate name and location...
     fn send_mouse(&self, x: i32, y: i32, action: MouseAction) -> Result<()>;
 
+    /// Sets the frame `(x, y)` of [`Self::send_mouse`] is relative to.
+    ///
+    /// This keeps the mouse on the right position when the frame is cropped or captured at a
+    /// different size than the client area (e.g. with display scaling).
+    fn set_mouse_frame(&mut self, frame: MouseFrame);
+
     fn send_up(&self, kind: KeyKind) -> Result<()>;
 
     fn send_down(&self, kind: KeyKind) -> Result<()>;
 
+    /// Releases all held down keys including the ones pending from input delay.
+    fn release_all(&self) -> Result<()>;
+
     fn all_keys_cleared(&self) -> bool;
 
+    /// Whether the game window is in the foreground so that keys can be sent.
+    ///
+    /// This is always `true` for the RPC input method since its keys are sent remotely.
+    fn is_foreground(&self) -> bool;
+
     fn as_any_mut(&mut self) -> &mut dyn Any;
 }
 
@@ -83,6 +141,24 @@ pub struct DefaultKeySender {
     delay_rng: Rng,
     delay_mean_std_pair: (f32, f32),
     delay_map: RefCell<HashMap<KeyKind, u32>>,
+    humanization: InputHumanization,
+    /// Key presses waiting for the inter-key delay.
+    key_queue: RefCell<VecDeque<KeyKind>>,
+    /// Remaining ticks before the next key press can be sent.
+    inter_key_delay: Cell<u32>,
+    /// Keys sent or held down since the last [`Self::take_sent_keys`].
+    sent_keys: RefCell<Vec<KeyKind>>,
+    /// Inputs sent since the last [`Self::take_input_events`].
+    input_events: RefCell<Vec<InputEvent>>,
+    mouse_frame: MouseFrame,
+    /// The last position sent or queued by [`KeySender::send_mouse`] or [`None`] if unknown.
+    mouse_position: Cell<Option<(i32, i32)>>,
+    /// Mouse inputs of a humanized movement waiting to be sent one per tick.
+    mouse_queue: RefCell<VecDeque<(i32, i32, MouseAction)>>,
+    /// Keys to send in place of the keys sent by the bot.
+    key_remaps: HashMap<KeyKind, KeyKind>,
+    /// The window that must be in the foreground for inputs to be sent.
+    focus_guard: Option<Handle>,
 }
 
 #[derive(Debug)]
@@ -94,28 +170,177 @@ enum InputDelay {
 
 impl DefaultKeySender {
     pub fn new(method: KeySenderMethod, seeds: Seeds) -> Self {
+        let humanization = InputHumanization::default();
         Self {
             kind: to_key_sender_kind_from(method, &seeds.seed),
             delay_rng: Rng::new(seeds.seed),
-            delay_mean_std_pair: (BASE_MEAN_MS_DELAY, BASE_STD_MS_DELAY),
+            delay_mean_std_pair: base_mean_std_pair(&humanization),
             delay_map: RefCell::new(HashMap::new()),
+            humanization,
+            key_queue: RefCell::new(VecDeque::new()),
+            inter_key_delay: Cell::new(0),
+            sent_keys: RefCell::new(Vec::new()),
+            input_events: RefCell::new(Vec::new()),
+            mouse_frame: MouseFrame::default(),
+            mouse_position: Cell::new(None),
+            mouse_queue: RefCell::new(VecDeque::new()),
+            key_remaps: HashMap::new(),
+            focus_guard: None,
+        }
+    }
+
+    /// Only sends inputs when `handle` is the foreground window.
+    ///
+    /// Does not apply to [`KeySenderMethod::Rpc`] since the game usually runs on another PC.
+    pub fn set_focus_guard(&mut self, handle: Option<Handle>) {
+        self.focus_guard = handle;
+        if let KeySenderKind::Default(keys) = &mut self.kind {
+            keys.set_focus_guard(handle);
         }
     }
 
+    /// Sets the keys to send in place of the keys sent by the bot.
+    pub fn set_key_remaps(&mut self, remaps: &[KeyRemap]) {
+        self.key_remaps = remaps
+            .iter()
+            .map(|remap| (remap.from.into(), remap.to.into()))
+            .collect();
+    }
+
+    #[inline]
+    fn remap(&self, kind: KeyKind) -> KeyKind {
+        self.key_remaps.get(&kind).copied().unwrap_or(kind)
+    }
+
+    /// Sets how keys are pressed.
+    pub fn set_humanization(&mut self, humanization: InputHumanization) {
+        self.delay_mean_std_pair = base_mean_std_pair(&humanization);
+        self.humanization = humanization;
+    }
+
+    /// Takes the keys sent or held down since the last call.
+    #[inline]
+    pub fn take_sent_keys(&self) -> Vec<KeyKind> {
+        self.sent_keys.take()
+    }
+
+    /// Takes the inputs sent since the last call.
+    ///
+    /// The tick, state and action of the events are left for the caller to fill.
+    #[inline]
+    pub fn take_input_events(&self) -> Vec<InputEvent> {
+        self.input_events.take()
+    }
+
+    fn record_input(&self, kind: InputEventKind, input: String) {
+        let millis = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|duration| duration.as_millis() as u64)
+            .unwrap_or_default();
+        self.input_events.borrow_mut().push(InputEvent {
+            millis,
+            tick: 0,
+            kind,
+            input,
+            state: String::new(),
+            action_id: None,
+            action: None,
+        });
+    }
+
     #[inline]
     fn send_inner(&self, kind: KeyKind) -> Result<()> {
+        if self.inter_key_delay.get() > 0
+            || !self.key_queue.borrow().is_empty()
+            || self.has_input_delay(kind)
+        {
+            self.key_queue.borrow_mut().push_back(kind);
+            return Ok(());
+        }
+
+        self.inter_key_delay
+            .set(self.random_inter_key_delay_tick_count());
+        self.send_key_press(kind)
+    }
+
+    /// Sends the next queued key press.
+    ///
+    /// For [`KeySenderKind::Rpc`], all queued key presses are sent in one batch with the
+    /// inter-key delays in between so that the timings are not affected by the network.
+    fn send_queued_key_presses(&self) -> Result<()> {
+        if let KeySenderKind::Rpc(_, Some(service)) = &self.kind
+            && service.borrow().supports_batch()
+            && self.key_queue.borrow().len() > 1
+        {
+            let kinds = self.key_queue.borrow_mut().drain(..).collect::<Vec<_>>();
+            let last = kinds.len() - 1;
+            let strokes = kinds
+                .into_iter()
+                .enumerate()
+                .map(|(i, kind)| {
+                    let down_ms = self.random_input_delay_tick_count(kind).0;
+                    let delay_ms = if i == last {
+                        0.0
+                    } else {
+                        self.random_inter_key_delay_tick_count() as f32 * ms_per_tick_f32()
+                    };
+                    (kind, down_ms, delay_ms)
+                })
+                .collect::<Vec<_>>();
+            self.inter_key_delay
+                .set(self.random_inter_key_delay_tick_count());
+            service.borrow_mut().send_batch(&strokes)?;
+            for (kind, down_ms, _) in strokes {
+                self.record_input(
+                    InputEventKind::KeyPress,
+                    format!("{kind:?} ({down_ms:.0} ms)"),
+                );
+            }
+            return Ok(());
+        }
+
+        // The same key still held down from the previous press is sent once released
+        if self
+            .key_queue
+            .borrow()
+            .front()
+            .is_some_and(|kind| self.has_input_delay(*kind))
+        {
+            return Ok(());
+        }
+        let kind = self.key_queue.borrow_mut().pop_front();
+        if let Some(kind) = kind {
+            self.inter_key_delay
+                .set(self.random_inter_key_delay_tick_count());
+            self.send_key_press(kind)?;
+        }
+        Ok(())
+    }
+
+    #[inline]
+    fn send_key_press(&self, kind: KeyKind) -> Result<()> {
         match &self.kind {
             KeySenderKind::Rpc(_, service) => {
                 if let Some(cell) = service {
-                    cell.borrow_mut()
-                        .send(kind, self.random_input_delay_tick_count().0)?;
+                    let down_ms = self.random_input_delay_tick_count(kind).0;
+                    cell.borrow_mut().send(kind, down_ms)?;
+                    self.record_input(
+                        InputEventKind::KeyPress,
+                        format!("{kind:?} ({down_ms:.0} ms)"),
+                    );
                 }
                 Ok(())
             }
             KeySenderKind::Default(keys) => {
                 match self.track_input_delay(kind) {
-                    InputDelay::Untracked => keys.send(kind)?,
-                    InputDelay::Tracked => keys.send_down(kind)?,
+                    InputDelay::Untracked => {
+                        keys.send(kind)?;
+                        self.record_input(InputEventKind::KeyPress, format!("{kind:?}"));
+                    }
+                    InputDelay::Tracked => {
+                        keys.send_down(kind)?;
+                        self.record_input(InputEventKind::KeyDown, format!("{kind:?}"));
+                    }
                     InputDelay::AlreadyTracked => (),
                 }
                 Ok(())
@@ -129,12 +354,14 @@ impl DefaultKeySender {
             KeySenderKind::Rpc(_, service) => {
                 if let Some(cell) = service {
                     cell.borrow_mut().send_up(kind)?;
+                    self.record_input(InputEventKind::KeyUp, format!("{kind:?}"));
                 }
                 Ok(())
             }
             KeySenderKind::Default(keys) => {
                 if forced || !self.has_input_delay(kind) {
                     keys.send_up(kind)?;
+                    self.record_input(InputEventKind::KeyUp, format!("{kind:?}"));
                 }
                 Ok(())
             }
@@ -147,12 +374,14 @@ impl DefaultKeySender {
             KeySenderKind::Rpc(_, service) => {
                 if let Some(cell) = service {
                     cell.borrow_mut().send_down(kind)?;
+                    self.record_input(InputEventKind::KeyDown, format!("{kind:?}"));
                 }
                 Ok(())
             }
             KeySenderKind::Default(keys) => {
                 if !self.has_input_delay(kind) {
                     keys.send_down(kind)?;
+                    self.record_input(InputEventKind::KeyDown, format!("{kind:?}"));
                 }
                 Ok(())
             }
@@ -179,7 +408,7 @@ impl DefaultKeySender {
             return InputDelay::AlreadyTracked;
         }
 
-        let (_, delay_tick_count) = self.random_input_delay_tick_count();
+        let (_, delay_tick_count) = self.random_input_delay_tick_count(kind);
         if delay_tick_count > 0 {
             let _ = map.insert(kind, delay_tick_count);
             InputDelay::Tracked
@@ -188,23 +417,39 @@ impl DefaultKeySender {
         }
     }
 
-    /// Updates the input delay (key up timing) for held down keys and delay std/mean pair.
+    /// Updates the input delay (key up timing) for held down keys, delay std/mean pair, queued
+    /// key presses and queued mouse movement.
     #[inline]
     pub fn update_input_delay(&mut self, game_tick: u64) {
         const UPDATE_MEAN_STD_PAIR_INTERVAL: u64 = 200;
 
         if game_tick > 0 && game_tick.is_multiple_of(UPDATE_MEAN_STD_PAIR_INTERVAL) {
+            let (base_mean, base_std) = base_mean_std_pair(&self.humanization);
             let (mean, std) = self.delay_mean_std_pair;
             self.delay_mean_std_pair = self.delay_rng.random_mean_std_pair(
-                BASE_MEAN_MS_DELAY,
+                base_mean,
                 mean,
-                BASE_STD_MS_DELAY,
+                base_std,
                 std,
                 MEAN_STD_REVERSION_RATE,
                 MEAN_STD_VOLATILITY,
             )
         }
 
+        if let KeySenderKind::Rpc(_, Some(service)) = &self.kind {
+            service.borrow_mut().check_health();
+        }
+
+        if let Some((x, y, action)) = self.mouse_queue.borrow_mut().pop_front() {
+            let _ = self.send_mouse_inner(x, y, action);
+        }
+
+        let inter_key_delay = self.inter_key_delay.get().saturating_sub(1);
+        self.inter_key_delay.set(inter_key_delay);
+        if inter_key_delay == 0 {
+            let _ = self.send_queued_key_presses();
+        }
+
         let mut map = self.delay_map.borrow_mut();
         if map.is_empty() {
             return;
@@ -218,40 +463,8 @@ impl DefaultKeySender {
         });
     }
 
-    fn random_input_delay_tick_count(&self) -> (f32, u32) {
-        let (mean, std) = self.delay_mean_std_pair;
-        self.delay_rng
-            .random_delay_tick_count(mean, std, MS_PER_TICK_F32, 80.0, 120.0)
-    }
-}
-
-impl KeySender for DefaultKeySender {
-    fn set_method(&mut self, method: KeySenderMethod) {
-        match &method {
-            KeySenderMethod::Rpc(handle, url) => {
-                if let KeySenderKind::Rpc(ref cur_handle, ref option) = self.kind {
-                    let service = option.as_ref();
-                    let service_borrow = service.map(|service| service.borrow_mut());
-                    if let Some(mut borrow) = service_borrow
-                        && borrow.url() == url
-                        && handle == cur_handle
-                    {
-                        let _ = borrow.init(self.delay_rng.seed());
-                        borrow.reset();
-                        return;
-                    }
-                }
-            }
-            KeySenderMethod::Default(_, _) => (),
-        }
-        self.kind = to_key_sender_kind_from(method, self.delay_rng.seed());
-    }
-
-    fn send(&self, kind: KeyKind) -> Result<()> {
-        self.send_inner(kind)
-    }
-
-    fn send_mouse(&self, x: i32, y: i32, action: MouseAction) -> Result<()> {
+    fn send_mouse_inner(&self, x: i32, y: i32, action: MouseAction) -> Result<()> {
+        let input = format!("{action:?} ({x}, {y})");
         match &self.kind {
             KeySenderKind::Rpc(handle, service) => {
                 if let Some(cell) = service {
@@ -260,6 +473,7 @@ impl KeySender for DefaultKeySender {
                         *handle,
                         x,
                         y,
+                        self.mouse_frame.size,
                         matches!(borrow.mouse_coordinate(), rpc::Coordinate::Screen),
                     )?;
                     let action = match action {
@@ -275,6 +489,7 @@ impl KeySender for DefaultKeySender {
                         coordinates.y,
                         action,
                     )?;
+                    self.record_input(InputEventKind::Mouse, input);
                 }
                 Ok(())
             }
@@ -284,23 +499,181 @@ impl KeySender for DefaultKeySender {
                     MouseAction::Click => windows::MouseAction::Click,
                     MouseAction::Scroll => windows::MouseAction::Scroll,
                 };
-                keys.send_mouse(x, y, action)?;
+                keys.send_mouse(x, y, self.mouse_frame.size, action)?;
+                self.record_input(InputEventKind::Mouse, input);
                 Ok(())
             }
         }
     }
 
+    /// Samples a random `(delay, tick count)` pair of how long `kind` is held down.
+    fn random_input_delay_tick_count(&self, kind: KeyKind) -> (f32, u32) {
+        let humanization = &self.humanization;
+        let key_override = humanization
+            .key_overrides
+            .iter()
+            .find(|key_override| KeyKind::from(key_override.key) == kind);
+        let mut ms = if let Some(key_override) = key_override {
+            let mean = key_override.hold_mean_millis as f32;
+            let std = key_override.hold_std_millis as f32;
+            let min = (mean - 2.0 * std).max(0.0);
+            self.delay_rng
+                .random_delay_tick_count(mean, std, ms_per_tick_f32(), min, mean + 2.0 * std)
+                .0
+        } else {
+            let min = humanization.hold_min_millis as f32;
+            let max = (humanization.hold_max_millis as f32).max(min);
+            match humanization.jitter_distribution {
+                JitterDistribution::Normal => {
+                    let (mean, std) = self.delay_mean_std_pair;
+                    self.delay_rng
+                        .random_delay_tick_count(mean, std, ms_per_tick_f32(), min, max)
+                        .0
+                }
+                JitterDistribution::Uniform => self.delay_rng.random_range(min..=max),
+            }
+        };
+        if humanization.fumble_probability > 0.0
+            && self
+                .delay_rng
+                .random_bool(humanization.fumble_probability.min(1.0) as f64)
+        {
+            ms *= 2.0;
+        }
+
+        (ms, (ms / ms_per_tick_f32()).round() as u32)
+    }
+
+    /// Samples a random tick count to wait before sending the next key press.
+    fn random_inter_key_delay_tick_count(&self) -> u32 {
+        let min = self.humanization.inter_key_delay_min_millis;
+        let max = self.humanization.inter_key_delay_max_millis.max(min);
+        if max == 0 {
+            return 0;
+        }
+
+        let ms = self.delay_rng.random_range(min..=max);
+        (ms as f32 / ms_per_tick_f32()).round() as u32
+    }
+}
+
+#[inline]
+fn base_mean_std_pair(humanization: &InputHumanization) -> (f32, f32) {
+    (
+        humanization.hold_mean_millis as f32,
+        humanization.hold_std_millis as f32,
+    )
+}
+
+impl KeySender for DefaultKeySender {
+    fn set_method(&mut self, method: KeySenderMethod) {
+        match &method {
+            KeySenderMethod::Rpc(handle, url) => {
+                if let KeySenderKind::Rpc(ref cur_handle, ref option) = self.kind {
+                    let service = option.as_ref();
+                    let service_borrow = service.map(|service| service.borrow_mut());
+                    if let Some(mut borrow) = service_borrow
+                        && borrow.url() == url
+                        && handle == cur_handle
+                    {
+                        let _ = borrow.init(self.delay_rng.seed());
+                        borrow.reset();
+                        return;
+                    }
+                }
+            }
+            KeySenderMethod::Default(_, _)
+            | KeySenderMethod::Interception(_, _)
+            | KeySenderMethod::Serial(_, _, _, _)
+            | KeySenderMethod::KmBoxNet(_, _, _, _) => (),
+        }
+        self.kind = to_key_sender_kind_from(method, self.delay_rng.seed());
+        self.set_focus_guard(self.focus_guard);
+    }
+
+    fn send(&self, kind: KeyKind) -> Result<()> {
+        let kind = self.remap(kind);
+        self.sent_keys.borrow_mut().push(kind);
+        self.send_inner(kind)
+    }
+
+    fn send_mouse(&self, x: i32, y: i32, action: MouseAction) -> Result<()> {
+        let (x, y) = (x + self.mouse_frame.offset.0, y + self.mouse_frame.offset.1);
+        let from = self.mouse_position.replace(Some((x, y)));
+        let mut queue = self.mouse_queue.borrow_mut();
+        // The first movement jumps to the target since the cursor position is not known
+        if self.humanization.humanize_mouse_movement
+            && let Some(from) = from
+        {
+            let step = Duration::from_secs_f32(ms_per_tick_f32() / 1000.0);
+            let path = MousePath::generate(&self.delay_rng, from, (x, y), step);
+            let count = path.points.len() - 1;
+            queue.extend(
+                path.points
+                    .into_iter()
+                    .take(count)
+                    .map(|(x, y)| (x, y, MouseAction::Move)),
+            );
+            queue.push_back((x, y, action));
+            return Ok(());
+        }
+        // Keeps the order behind a movement still in progress
+        if !queue.is_empty() {
+            queue.push_back((x, y, action));
+            return Ok(());
+        }
+        drop(queue);
+        self.send_mouse_inner(x, y, action)
+    }
+
+    fn set_mouse_frame(&mut self, frame: MouseFrame) {
+        if self.mouse_frame != frame {
+            self.mouse_position.set(None);
+        }
+        self.mouse_frame = frame;
+    }
+
     fn send_up(&self, kind: KeyKind) -> Result<()> {
-        self.send_up_inner(kind, false)
+        self.send_up_inner(self.remap(kind), false)
     }
 
     fn send_down(&self, kind: KeyKind) -> Result<()> {
+        let kind = self.remap(kind);
+        self.sent_keys.borrow_mut().push(kind);
         self.send_down_inner(kind)
     }
 
+    fn release_all(&self) -> Result<()> {
+        self.delay_map.borrow_mut().clear();
+        self.key_queue.borrow_mut().clear();
+        self.mouse_queue.borrow_mut().clear();
+        match &self.kind {
+            KeySenderKind::Rpc(_, service) => {
+                if let Some(cell) = service {
+                    cell.borrow_mut().reset();
+                }
+                Ok(())
+            }
+            KeySenderKind::Default(keys) => {
+                keys.release_all()?;
+                Ok(())
+            }
+        }
+    }
+
     #[inline]
     fn all_keys_cleared(&self) -> bool {
         self.delay_map.borrow().is_empty()
+            && self.key_queue.borrow().is_empty()
+            && self.mouse_queue.borrow().is_empty()
+    }
+
+    #[inline]
+    fn is_foreground(&self) -> bool {
+        match &self.kind {
+            KeySenderKind::Rpc(_, _) => true,
+            KeySenderKind::Default(keys) => keys.is_foreground(),
+        }
     }
 
     #[inline]
@@ -314,7 +687,10 @@ impl KeySender for DefaultKeySender {
 pub enum ImageCaptureKind {
     BitBlt(BitBltCapture),
     Wgc(Option<WgcCapture>),
+    DesktopDuplication(DesktopDuplicationCapture),
     BitBltArea(WindowBoxCapture),
+    Replay(FrameReplay),
+    Stream(FrameStream),
 }
 
 /// A struct for managing different capture modes.
@@ -324,9 +700,9 @@ pub struct ImageCapture {
 }
 
 impl ImageCapture {
-    pub fn new(handle: Handle, mode: CaptureMode) -> Self {
+    pub fn new(handle: Handle, mode: CaptureMode, stream_url: &str) -> Self {
         Self {
-            kind: to_image_capture_kind_from(handle, mode),
+            kind: to_image_capture_kind_from(handle, mode, stream_url),
         }
     }
 
@@ -340,12 +716,51 @@ impl ImageCapture {
             ImageCaptureKind::Wgc(capture) => {
                 capture.as_mut().and_then(|capture| capture.grab().ok())
             }
+            ImageCaptureKind::DesktopDuplication(capture) => capture.grab().ok(),
             ImageCaptureKind::BitBltArea(capture) => capture.grab().ok(),
+            ImageCaptureKind::Replay(replay) => replay.grab(),
+            ImageCaptureKind::Stream(stream) => stream.grab(),
         }
     }
 
-    pub fn set_mode(&mut self, handle: Handle, mode: CaptureMode) {
-        self.kind = to_image_capture_kind_from(handle, mode);
+    pub fn set_mode(&mut self, handle: Handle, mode: CaptureMode, stream_url: &str) {
+        self.kind = to_image_capture_kind_from(handle, mode, stream_url);
+    }
+
+    /// Gets the top-left of the captured area inside the client area if only an area of the
+    /// window is captured.
+    pub fn area_offset(&self) -> Option<(i32, i32)> {
+        match &self.kind {
+            ImageCaptureKind::BitBlt(capture) => capture.area().map(|(x, y, _, _)| (x, y)),
+            _ => None,
+        }
+    }
+
+    /// Recreates the capture resources for the current window after it moved or resized.
+    pub fn reset(&mut self) {
+        match &mut self.kind {
+            ImageCaptureKind::BitBlt(capture) => capture.reset(),
+            ImageCaptureKind::Wgc(capture) => {
+                if let Some(capture) = capture.as_mut() {
+                    capture.stop_capture();
+                }
+            }
+            ImageCaptureKind::DesktopDuplication(capture) => capture.stop_capture(),
+            ImageCaptureKind::BitBltArea(_)
+            | ImageCaptureKind::Replay(_)
+            | ImageCaptureKind::Stream(_) => (),
+        }
+    }
+
+    /// Captures `area` inside the client area of `handle` as seen on screen.
+    pub fn set_area(&mut self, handle: Handle, area: Bound) {
+        self.kind = ImageCaptureKind::BitBlt(BitBltCapture::new_area(
+            handle,
+            area.x,
+            area.y,
+            area.width,
+            area.height,
+        ));
     }
 }
 
@@ -360,17 +775,36 @@ fn to_key_sender_kind_from(method: KeySenderMethod, seed: &[u8]) -> KeySenderKin
             KeySenderKind::Rpc(handle, service.ok().map(RefCell::new))
         }
         KeySenderMethod::Default(handle, kind) => KeySenderKind::Default(Keys::new(handle, kind)),
+        // Shares the same key states and delays as the default method
+        KeySenderMethod::Interception(handle, kind) => {
+            KeySenderKind::Default(Keys::new_interception(handle, kind))
+        }
+        KeySenderMethod::Serial(handle, kind, port, baud_rate) => KeySenderKind::Default(
+            Keys::new_serial(handle, kind, SerialPort::new(port, baud_rate)),
+        ),
+        KeySenderMethod::KmBoxNet(handle, kind, address, uuid) => {
+            KeySenderKind::Default(Keys::new_kmbox(handle, kind, KmBoxNet::new(address, &uuid)))
+        }
     }
 }
 
 #[inline]
-fn to_image_capture_kind_from(handle: Handle, mode: CaptureMode) -> ImageCaptureKind {
+fn to_image_capture_kind_from(
+    handle: Handle,
+    mode: CaptureMode,
+    stream_url: &str,
+) -> ImageCaptureKind {
     match mode {
         CaptureMode::BitBlt => ImageCaptureKind::BitBlt(BitBltCapture::new(handle, false)),
         CaptureMode::WindowsGraphicsCapture => {
-            ImageCaptureKind::Wgc(WgcCapture::new(handle, MS_PER_TICK).ok())
+            ImageCaptureKind::Wgc(WgcCapture::new(handle, ms_per_tick()).ok())
         }
+        CaptureMode::DesktopDuplication => ImageCaptureKind::DesktopDuplication(
+            DesktopDuplicationCapture::new(handle, ms_per_tick()),
+        ),
         CaptureMode::BitBltArea => ImageCaptureKind::BitBltArea(WindowBoxCapture::default()),
+        CaptureMode::Replay => ImageCaptureKind::Replay(FrameReplay::load()),
+        CaptureMode::Stream => ImageCaptureKind::Stream(FrameStream::new(stream_url)),
     }
 }
 
@@ -379,6 +813,7 @@ mod tests {
     use std::assert_matches::assert_matches;
 
     use super::*;
+    use crate::{KeyBinding, KeyHoldOverride, KeyRemap};
 
     const SEED: [u8; 32] = [
         64, 241, 206, 219, 49, 21, 218, 145, 254, 152, 68, 176, 242, 238, 152, 14, 176, 241, 153,
@@ -441,4 +876,110 @@ mod tests {
         sender.update_input_delay(200);
         assert_ne!(sender.delay_mean_std_pair, original_pair);
     }
+
+    #[test]
+    fn send_inner_queue_key_press_until_inter_key_delay_elapsed() {
+        let mut sender = test_key_sender();
+        sender.set_humanization(InputHumanization {
+            inter_key_delay_min_millis: 100,
+            inter_key_delay_max_millis: 100,
+            ..InputHumanization::default()
+        });
+        let delay_tick_count = (100.0 / ms_per_tick_f32()).round() as u32;
+        sender.inter_key_delay.set(delay_tick_count);
+
+        // Repeated presses of the same key are all queued
+        assert!(sender.send_inner(KeyKind::A).is_ok());
+        assert!(sender.send_inner(KeyKind::A).is_ok());
+        assert_eq!(sender.key_queue.borrow().len(), 2);
+        assert!(!sender.all_keys_cleared());
+
+        for _ in 0..delay_tick_count - 1 {
+            sender.update_input_delay(0);
+        }
+        assert_eq!(sender.key_queue.borrow().len(), 2);
+        sender.update_input_delay(0);
+        assert_eq!(sender.key_queue.borrow().len(), 1);
+        assert_eq!(sender.inter_key_delay.get(), delay_tick_count);
+
+        // The second press waits for the first one to be released
+        while sender.has_input_delay(KeyKind::A) {
+            assert_eq!(sender.key_queue.borrow().len(), 1);
+            sender.update_input_delay(0);
+        }
+        for _ in 0..delay_tick_count {
+            sender.update_input_delay(0);
+        }
+        assert!(sender.key_queue.borrow().is_empty());
+    }
+
+    #[test]
+    fn send_mouse_queue_humanized_movement_one_per_tick() {
+        let mut sender = test_key_sender();
+        sender.set_humanization(InputHumanization {
+            humanize_mouse_movement: true,
+            ..InputHumanization::default()
+        });
+        sender.mouse_position.set(Some((0, 0)));
+
+        assert!(sender.send_mouse(500, 300, MouseAction::Click).is_ok());
+        let count = sender.mouse_queue.borrow().len();
+        assert!(count > 1);
+        assert_matches!(
+            sender.mouse_queue.borrow().back(),
+            Some((500, 300, MouseAction::Click))
+        );
+        assert_eq!(sender.mouse_position.get(), Some((500, 300)));
+        assert!(!sender.all_keys_cleared());
+
+        for remaining in (0..count).rev() {
+            sender.update_input_delay(0);
+            assert_eq!(sender.mouse_queue.borrow().len(), remaining);
+        }
+    }
+
+    #[test]
+    fn send_down_remap_key() {
+        let mut sender = test_key_sender();
+        sender.set_key_remaps(&[KeyRemap {
+            from: KeyBinding::Up,
+            to: KeyBinding::W,
+        }]);
+
+        let _ = sender.send_down(KeyKind::Up);
+        let _ = sender.send_down(KeyKind::Down);
+        assert_eq!(sender.take_sent_keys(), vec![KeyKind::W, KeyKind::Down]);
+    }
+
+    #[test]
+    fn random_input_delay_tick_count_key_override() {
+        let mut sender = test_key_sender();
+        sender.set_humanization(InputHumanization {
+            key_overrides: vec![KeyHoldOverride {
+                key: KeyBinding::A,
+                hold_mean_millis: 300,
+                hold_std_millis: 0,
+            }],
+            ..InputHumanization::default()
+        });
+
+        let (ms, _) = sender.random_input_delay_tick_count(KeyKind::A);
+        assert_eq!(ms, 300.0);
+        let (ms, _) = sender.random_input_delay_tick_count(KeyKind::B);
+        assert!((80.0..=120.0).contains(&ms));
+    }
+
+    #[test]
+    fn random_input_delay_tick_count_fumble() {
+        let mut sender = test_key_sender();
+        sender.set_humanization(InputHumanization {
+            hold_min_millis: 100,
+            hold_max_millis: 100,
+            fumble_probability: 1.0,
+            ..InputHumanization::default()
+        });
+
+        let (ms, _) = sender.random_input_delay_tick_count(KeyKind::A);
+        assert_eq!(ms, 200.0);
+    }
 }
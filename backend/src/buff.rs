@@ -1,13 +1,14 @@
 use std::{
     mem,
     ops::{Index, IndexMut},
+    time::Instant,
 };
 
 use anyhow::Result;
-use strum::EnumIter;
+use strum::{Display, EnumIter};
 
 use crate::{
-    Character, Settings,
+    BuffDuration, Character, RequiredBuff, Settings,
     context::{Context, Contextual, ControlFlow},
     player::Player,
     task::{Task, Update, update_detection_task},
@@ -29,6 +30,22 @@ pub struct BuffState {
     max_fail_count: u32,
     /// Whether a buff is enabled.
     enabled: bool,
+    /// Whether a buff is required by the current preset.
+    ///
+    /// A required buff is still detected when not enabled to verify it before farming.
+    required: bool,
+    /// The [`Instant`] the buff was detected as started.
+    ///
+    /// Resets when the buff is missing or becomes [`Buff::Expiring`].
+    started_at: Option<Instant>,
+    /// The duration of the buff or zero if unknown.
+    duration_millis: u64,
+    /// The remaining duration at or below which the buff becomes [`Buff::Expiring`].
+    ///
+    /// Zero disables expiring.
+    expiring_remaining_millis: u64,
+    /// Whether the buff has been detected at least once since the last [`BuffState::reset`].
+    detected: bool,
 }
 
 impl BuffState {
@@ -54,9 +71,53 @@ impl BuffState {
                 | BuffKind::ExtremeGoldPotion => BUFF_FAIL_MAX_COUNT,
             },
             enabled: true,
+            required: false,
+            started_at: None,
+            duration_millis: 0,
+            expiring_remaining_millis: 0,
+            detected: false,
         }
     }
 
+    /// Resets the detection so that the buff is detected again as soon as possible.
+    ///
+    /// This is used when the buffs are lost (e.g. after dying).
+    pub fn reset(&mut self) {
+        self.task = None;
+        self.fail_count = 0;
+        self.started_at = None;
+        self.detected = false;
+    }
+
+    /// Whether the buff has been detected at least once since the last [`BuffState::reset`].
+    ///
+    /// Always `true` if the buff is not detected at all.
+    #[inline]
+    pub fn is_detected(&self) -> bool {
+        self.detected || (!self.enabled && !self.required)
+    }
+
+    /// The estimated remaining duration of the buff since it was detected.
+    ///
+    /// Returns [`None`] if the buff duration is unknown or the buff has not been detected.
+    pub fn remaining_millis(&self) -> Option<u64> {
+        if self.duration_millis == 0 {
+            return None;
+        }
+        let elapsed = self.started_at?.elapsed().as_millis() as u64;
+        Some(self.duration_millis.saturating_sub(elapsed))
+    }
+
+    #[inline]
+    pub fn kind(&self) -> BuffKind {
+        self.kind
+    }
+
+    #[inline]
+    pub fn set_required(&mut self, required: bool) {
+        self.required = required;
+    }
+
     /// Updates the enabled states of each buff to only detect if enabled.
     pub fn update_enabled_state(&mut self, character: &Character, settings: &Settings) {
         self.enabled = match self.kind {
@@ -75,9 +136,27 @@ impl BuffState {
             BuffKind::ExtremeGreenPotion => character.extreme_green_potion_key.enabled,
             BuffKind::ExtremeGoldPotion => character.extreme_gold_potion_key.enabled,
         };
+        let duration = match self.kind {
+            BuffKind::Rune | BuffKind::Familiar => BuffDuration::default(),
+            BuffKind::SayramElixir => character.sayram_elixir_duration,
+            BuffKind::AureliaElixir => character.aurelia_elixir_duration,
+            BuffKind::ExpCouponX3 => character.exp_x3_duration,
+            BuffKind::BonusExpCoupon => character.bonus_exp_duration,
+            BuffKind::LegionWealth => character.legion_wealth_duration,
+            BuffKind::LegionLuck => character.legion_luck_duration,
+            BuffKind::WealthAcquisitionPotion => character.wealth_acquisition_potion_duration,
+            BuffKind::ExpAccumulationPotion => character.exp_accumulation_potion_duration,
+            BuffKind::ExtremeRedPotion => character.extreme_red_potion_duration,
+            BuffKind::ExtremeBluePotion => character.extreme_blue_potion_duration,
+            BuffKind::ExtremeGreenPotion => character.extreme_green_potion_duration,
+            BuffKind::ExtremeGoldPotion => character.extreme_gold_potion_duration,
+        };
+        self.duration_millis = duration.duration_millis;
+        self.expiring_remaining_millis = duration.recast_remaining_millis;
         if !self.enabled {
             self.fail_count = 0;
             self.task = None;
+            self.started_at = None;
         }
     }
 }
@@ -91,10 +170,13 @@ pub enum Buff {
     Yes,
     /// Player did have this [`BuffKind`] but currently unsure.
     Volatile,
+    /// Player has this [`BuffKind`] but its remaining duration is low enough to re-cast.
+    ///
+    /// This lasts until the next detection to allow re-casting once.
+    Expiring,
 }
 
-#[derive(Clone, Copy, Debug, EnumIter)]
-#[cfg_attr(test, derive(PartialEq))]
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Display, EnumIter)]
 #[repr(usize)]
 pub enum BuffKind {
     // NOTE: Upon failing to solving rune, there is a cooldown
@@ -119,6 +201,26 @@ impl BuffKind {
     pub const COUNT: usize = mem::variant_count::<BuffKind>();
 }
 
+impl From<RequiredBuff> for BuffKind {
+    fn from(value: RequiredBuff) -> Self {
+        match value {
+            RequiredBuff::Familiar => BuffKind::Familiar,
+            RequiredBuff::SayramElixir => BuffKind::SayramElixir,
+            RequiredBuff::AureliaElixir => BuffKind::AureliaElixir,
+            RequiredBuff::ExpCouponX3 => BuffKind::ExpCouponX3,
+            RequiredBuff::BonusExpCoupon => BuffKind::BonusExpCoupon,
+            RequiredBuff::LegionWealth => BuffKind::LegionWealth,
+            RequiredBuff::LegionLuck => BuffKind::LegionLuck,
+            RequiredBuff::WealthAcquisitionPotion => BuffKind::WealthAcquisitionPotion,
+            RequiredBuff::ExpAccumulationPotion => BuffKind::ExpAccumulationPotion,
+            RequiredBuff::ExtremeRedPotion => BuffKind::ExtremeRedPotion,
+            RequiredBuff::ExtremeBluePotion => BuffKind::ExtremeBluePotion,
+            RequiredBuff::ExtremeGreenPotion => BuffKind::ExtremeGreenPotion,
+            RequiredBuff::ExtremeGoldPotion => BuffKind::ExtremeGoldPotion,
+        }
+    }
+}
+
 impl Index<BuffKind> for [Buff; BuffKind::COUNT] {
     type Output = Buff;
 
@@ -137,7 +239,7 @@ impl Contextual for Buff {
     type Persistent = BuffState;
 
     fn update(self, context: &Context, state: &mut BuffState) -> ControlFlow<Self> {
-        if !state.enabled {
+        if !state.enabled && !state.required {
             return ControlFlow::Next(Buff::No);
         }
         let next = if matches!(context.player, Player::CashShopThenExit(_, _)) {
@@ -159,15 +261,18 @@ fn update_context(contextual: Buff, context: &Context, state: &mut BuffState) ->
     else {
         return contextual;
     };
+    state.detected = true;
     state.fail_count = if matches!(contextual, Buff::Volatile) && !has_buff {
         state.fail_count + 1
     } else {
         0
     };
-    match (has_buff, contextual) {
-        (true, Buff::Volatile) | (true, Buff::Yes) | (true, Buff::No) => Buff::Yes,
+    let next = match (has_buff, contextual) {
+        (true, Buff::Volatile) | (true, Buff::Yes) | (true, Buff::No) | (true, Buff::Expiring) => {
+            Buff::Yes
+        }
         (false, Buff::No) => Buff::No,
-        (false, Buff::Yes) => {
+        (false, Buff::Yes) | (false, Buff::Expiring) => {
             if state.max_fail_count > 1 {
                 Buff::Volatile
             } else {
@@ -181,6 +286,29 @@ fn update_context(contextual: Buff, context: &Context, state: &mut BuffState) ->
                 Buff::Volatile
             }
         }
+    };
+    match next {
+        Buff::No => {
+            state.started_at = None;
+            Buff::No
+        }
+        Buff::Yes => {
+            if state.started_at.is_none() {
+                state.started_at = Some(Instant::now());
+            }
+            if state.expiring_remaining_millis > 0
+                && state
+                    .remaining_millis()
+                    .is_some_and(|millis| millis <= state.expiring_remaining_millis)
+            {
+                // Restarts on the next detection assuming the buff is re-casted
+                state.started_at = None;
+                Buff::Expiring
+            } else {
+                Buff::Yes
+            }
+        }
+        Buff::Volatile | Buff::Expiring => next,
     }
 }
 
@@ -290,6 +418,59 @@ mod tests {
         assert!(state.task.is_none());
     }
 
+    #[test]
+    fn update_enabled_state_duration_per_buff() {
+        let character = Character {
+            legion_wealth_duration: BuffDuration {
+                duration_millis: 1800000,
+                recast_remaining_millis: 60000,
+            },
+            legion_luck_duration: BuffDuration {
+                duration_millis: 900000,
+                recast_remaining_millis: 30000,
+            },
+            ..Character::default()
+        };
+        let settings = Settings::default();
+        let mut wealth = BuffState::new(BuffKind::LegionWealth);
+        let mut luck = BuffState::new(BuffKind::LegionLuck);
+        let mut sayram = BuffState::new(BuffKind::SayramElixir);
+
+        wealth.update_enabled_state(&character, &settings);
+        luck.update_enabled_state(&character, &settings);
+        sayram.update_enabled_state(&character, &settings);
+
+        assert_eq!(wealth.duration_millis, 1800000);
+        assert_eq!(wealth.expiring_remaining_millis, 60000);
+        assert_eq!(luck.duration_millis, 900000);
+        assert_eq!(luck.expiring_remaining_millis, 30000);
+        assert_eq!(sayram.duration_millis, 0);
+        assert_eq!(sayram.expiring_remaining_millis, 0);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn buff_yes_to_expiring() {
+        let kind = BuffKind::LegionWealth;
+        let detector = detector_with_kind(kind, true);
+        let context = Context::new(None, Some(detector));
+        let mut state = BuffState::new(kind);
+        state.duration_millis = 60000;
+        state.expiring_remaining_millis = 30000;
+        state.started_at = Instant::now().checked_sub(Duration::from_secs(40));
+
+        let buff = advance_task(Buff::Yes, &context, &mut state).await;
+        assert_matches!(buff, Buff::Expiring);
+        assert!(state.started_at.is_none());
+
+        let buff = advance_task(buff, &context, &mut state).await;
+        assert_matches!(buff, Buff::Yes);
+        assert!(
+            state
+                .remaining_millis()
+                .is_some_and(|millis| millis > 30000)
+        );
+    }
+
     #[tokio::test(start_paused = true)]
     async fn buff_volatile_stay_before_threshold() {
         for kind in BuffKind::iter() {
@@ -0,0 +1,124 @@
+use std::time::{Duration, Instant};
+
+/// How the capture should be recovered after no frame has been captured for a while.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum CaptureRecovery {
+    /// Recreates the capture with the current capture mode.
+    Reinitialize,
+    /// Switches to the next capture mode.
+    Fallback,
+}
+
+/// Monitors whether frames are being captured.
+///
+/// Without frames, there is no detector and the bot silently does nothing. This usually means the
+/// game is minimized or the capture device was lost (e.g. after a driver reset or display change).
+#[derive(Debug, Default)]
+pub struct CaptureMonitor {
+    /// The instant of the last captured frame or the last recovery.
+    last_frame_at: Option<Instant>,
+    /// Number of recoveries since the last captured frame.
+    recoveries: u32,
+}
+
+impl CaptureMonitor {
+    /// Updates the monitor with whether a frame is `captured` in the current tick.
+    ///
+    /// Returns the recovery to perform when no frame has been captured for `timeout`. The first
+    /// recovery always reinitializes the capture and the next ones fall back if `fallback` is
+    /// `true`.
+    pub fn update(
+        &mut self,
+        captured: bool,
+        timeout: Duration,
+        fallback: bool,
+    ) -> Option<CaptureRecovery> {
+        self.track(Instant::now(), captured, timeout, fallback)
+    }
+
+    /// Whether the last recovery returned by [`Self::update`] is the first since frames stopped.
+    pub fn is_first_recovery(&self) -> bool {
+        self.recoveries == 1
+    }
+
+    fn track(
+        &mut self,
+        now: Instant,
+        captured: bool,
+        timeout: Duration,
+        fallback: bool,
+    ) -> Option<CaptureRecovery> {
+        if captured {
+            *self = CaptureMonitor {
+                last_frame_at: Some(now),
+                recoveries: 0,
+            };
+            return None;
+        }
+
+        let last_frame_at = *self.last_frame_at.get_or_insert(now);
+        if now.duration_since(last_frame_at) < timeout {
+            return None;
+        }
+
+        self.last_frame_at = Some(now);
+        self.recoveries += 1;
+        if fallback && self.recoveries > 1 {
+            Some(CaptureRecovery::Fallback)
+        } else {
+            Some(CaptureRecovery::Reinitialize)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TIMEOUT: Duration = Duration::from_secs(10);
+
+    #[test]
+    fn track_reinitialize_then_fallback_until_captured() {
+        let mut monitor = CaptureMonitor::default();
+        let now = Instant::now();
+
+        assert_eq!(monitor.track(now, true, TIMEOUT, true), None);
+        assert_eq!(monitor.track(now + TIMEOUT / 2, false, TIMEOUT, true), None);
+        assert_eq!(
+            monitor.track(now + TIMEOUT, false, TIMEOUT, true),
+            Some(CaptureRecovery::Reinitialize)
+        );
+        assert!(monitor.is_first_recovery());
+        assert_eq!(
+            monitor.track(now + TIMEOUT * 3 / 2, false, TIMEOUT, true),
+            None
+        );
+        assert_eq!(
+            monitor.track(now + TIMEOUT * 2, false, TIMEOUT, true),
+            Some(CaptureRecovery::Fallback)
+        );
+        assert!(!monitor.is_first_recovery());
+
+        assert_eq!(monitor.track(now + TIMEOUT * 2, true, TIMEOUT, true), None);
+        assert_eq!(
+            monitor.track(now + TIMEOUT * 3, false, TIMEOUT, true),
+            Some(CaptureRecovery::Reinitialize)
+        );
+    }
+
+    #[test]
+    fn track_reinitialize_without_fallback() {
+        let mut monitor = CaptureMonitor::default();
+        let now = Instant::now();
+
+        assert_eq!(monitor.track(now, false, TIMEOUT, false), None);
+        assert_eq!(
+            monitor.track(now + TIMEOUT, false, TIMEOUT, false),
+            Some(CaptureRecovery::Reinitialize)
+        );
+        assert_eq!(
+            monitor.track(now + TIMEOUT * 2, false, TIMEOUT, false),
+            Some(CaptureRecovery::Reinitialize)
+        );
+    }
+}
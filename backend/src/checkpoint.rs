@@ -0,0 +1,81 @@
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use log::debug;
+
+use crate::database::{Checkpoint, delete_checkpoint, query_checkpoint, upsert_checkpoint};
+
+/// The interval between each saved checkpoint while the actions are running.
+const CHECKPOINT_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Periodically persists a [`Checkpoint`] of the running actions.
+///
+/// The checkpoint is deleted once the actions are halted so that only an unexpected exit (e.g.
+/// a crash or restart) leaves a checkpoint to resume from.
+#[derive(Debug, Default)]
+pub struct CheckpointTracker {
+    /// The id of the checkpoint stored in the database.
+    id: Option<i64>,
+    last_saved_at: Option<Instant>,
+    /// The checkpoint left from the last run that has not been resumed or discarded.
+    resumable: Option<Checkpoint>,
+}
+
+impl CheckpointTracker {
+    /// Loads the checkpoint left from the last run if any.
+    pub fn load() -> Self {
+        let resumable = query_checkpoint();
+        Self {
+            id: resumable.as_ref().and_then(|checkpoint| checkpoint.id),
+            last_saved_at: None,
+            resumable,
+        }
+    }
+
+    /// Whether a checkpoint should be saved now given whether the actions are `running`.
+    ///
+    /// When the actions are not running, the stored checkpoint is deleted unless it is still
+    /// resumable.
+    pub fn should_save(&mut self, running: bool) -> bool {
+        if !running {
+            self.last_saved_at = None;
+            if self.resumable.is_none()
+                && let Some(id) = self.id.take()
+            {
+                let _ = delete_checkpoint(&Checkpoint {
+                    id: Some(id),
+                    ..Checkpoint::default()
+                });
+            }
+            return false;
+        }
+
+        self.last_saved_at
+            .is_none_or(|instant| instant.elapsed() >= CHECKPOINT_INTERVAL)
+    }
+
+    /// Saves `checkpoint` replacing the stored one.
+    pub fn save(&mut self, mut checkpoint: Checkpoint) {
+        checkpoint.id = self.id;
+        checkpoint.saved_at_millis = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|duration| duration.as_millis() as u64)
+            .unwrap_or_default();
+        if upsert_checkpoint(&mut checkpoint).is_ok() {
+            debug!(target: "checkpoint", "saved checkpoint {checkpoint:?}");
+            self.id = checkpoint.id;
+        }
+        self.last_saved_at = Some(Instant::now());
+    }
+
+    /// The checkpoint left from the last run that can be resumed from.
+    #[inline]
+    pub fn resumable(&self) -> Option<&Checkpoint> {
+        self.resumable.as_ref()
+    }
+
+    /// Takes the resumable checkpoint so that it is no longer offered.
+    #[inline]
+    pub fn take_resumable(&mut self) -> Option<Checkpoint> {
+        self.resumable.take()
+    }
+}
@@ -1,8 +1,11 @@
 use std::{
     cell::RefCell,
-    env,
+    env, panic,
     rc::Rc,
-    sync::atomic::{AtomicBool, Ordering},
+    sync::{
+        Arc, Mutex,
+        atomic::{AtomicBool, AtomicU32, Ordering},
+    },
     thread,
     time::{Duration, Instant},
 };
@@ -14,34 +17,109 @@ use opencv::{
     core::{Vector, VectorToVec},
     imgcodecs::imencode_def,
 };
-use platforms::windows::{self, Handle, KeyInputKind, KeyReceiver};
-use strum::IntoEnumIterator;
+use platforms::windows::{self, Handle, HotkeyReceiver, KeyInputKind, KeyReceiver};
+use strum::{Display, IntoEnumIterator};
 use tokio::sync::broadcast;
 
 use crate::{
-    Action,
-    bridge::{DefaultKeySender, ImageCapture, ImageCaptureKind, KeySender, KeySenderMethod},
+    Action, InputEvent, MAX_UNSTUCK_STRATEGIES, StateSnapshot,
+    bridge::{
+        DefaultKeySender, ImageCapture, ImageCaptureKind, KeySender, KeySenderMethod, MouseFrame,
+    },
     buff::{Buff, BuffKind, BuffState},
-    database::{CaptureMode, InputMethod, KeyBinding, query_seeds, query_settings},
+    capture_monitor::CaptureMonitor,
+    checkpoint::CheckpointTracker,
+    control_server::ControlServer,
+    database::{
+        CaptureMode, GmBehavior, InventoryFullBehavior, KeyBinding, MAX_CHANNEL_COUNT, query_seeds,
+        query_settings,
+    },
     database_event_receiver,
-    detect::{CachedDetector, Detector},
+    death_recovery::DeathRecovery,
+    detect::{
+        CachedDetector, DetectionCache, Detector, execution_providers, set_detection_scale,
+        update_models,
+    },
+    frame_recorder::FrameRecorder,
+    gm::GmWatcher,
+    input_audit::InputAuditLog,
+    input_monitor::InputMonitor,
+    key_macro_recorder::KeyMacroRecorder,
+    key_watchdog::KeyWatchdog,
+    lie_detector::LieDetectorWatcher,
     mat::OwnedMat,
     minimap::{Minimap, MinimapState},
+    minimap_selector::MinimapSelector,
+    movement_test::MovementTester,
     navigation::Navigator,
-    network::{DiscordNotification, NotificationKind},
+    navigation_recorder::NavigationRecorder,
+    network::{Notification, NotificationKind},
+    platform_recorder::PlatformRecorder,
     player::{PanicTo, Panicking, Player, PlayerState},
-    request_handler::DefaultRequestHandler,
+    profiler::{TickProfiler, TickStage},
+    recorder::FlightRecorder,
+    remote_control::RemoteControl,
+    request_handler::{DefaultRequestHandler, register_global_hotkeys},
     rng::Rng,
     rotator::Rotator,
+    scheduler::{ScheduleTransition, Scheduler},
+    screenshot::{EventScreenshots, ScreenshotEvent},
+    session::{SessionStopReason, SessionTracker},
     skill::{Skill, SkillKind, SkillState},
+    whisper::WhisperWatcher,
+    window_tracker::WindowTracker,
 };
 #[cfg(test)]
 use crate::{Settings, bridge::MockKeySender, detect::MockDetector};
 
-const FPS: u32 = 30;
+const MIN_FPS: u32 = 10;
+const MAX_FPS: u32 = 60;
 const PENDING_HALT_SECS: u64 = 12;
-pub const MS_PER_TICK: u64 = MS_PER_TICK_F32 as u64;
-pub const MS_PER_TICK_F32: f32 = 1000.0 / FPS as f32;
+
+/// The current number of ticks per second of the update loop.
+static FPS: AtomicU32 = AtomicU32::new(30);
+
+/// The measured number of ticks per second stored as [`f32`] bits.
+static MEASURED_FPS: AtomicU32 = AtomicU32::new(0);
+
+/// Retrieves the current number of ticks per second.
+#[inline]
+pub fn fps() -> u32 {
+    FPS.load(Ordering::Relaxed)
+}
+
+/// Sets the number of ticks per second, clamped between [`MIN_FPS`] and [`MAX_FPS`].
+///
+/// Takes effect from the next tick.
+pub fn set_fps(fps: u32) {
+    FPS.store(fps.clamp(MIN_FPS, MAX_FPS), Ordering::Relaxed);
+}
+
+/// Retrieves the measured number of ticks per second of the update loop.
+#[inline]
+pub fn measured_fps() -> f32 {
+    f32::from_bits(MEASURED_FPS.load(Ordering::Relaxed))
+}
+
+/// Retrieves the duration of a tick in whole milliseconds rounded down.
+///
+/// Used as the minimum frame interval of the captures so that a frame is available every tick.
+#[inline]
+pub fn ms_per_tick() -> u64 {
+    ms_per_tick_f32() as u64
+}
+
+/// Retrieves the duration of a tick in milliseconds.
+#[inline]
+pub fn ms_per_tick_f32() -> f32 {
+    1000.0 / fps() as f32
+}
+
+/// Converts `millis` to the nearest number of ticks at the current tick rate.
+#[inline]
+pub fn millis_to_ticks(millis: u64) -> u32 {
+    (millis as f32 / ms_per_tick_f32()).round() as u32
+}
 
 /// A control flow to use after a contextual state update.
 #[derive(Debug)]
@@ -80,7 +158,9 @@ pub struct Context {
     pub keys: Box<dyn KeySender>,
     pub rng: Rng,
     /// A struct for sending notifications through web hook.
-    pub notification: DiscordNotification,
+    pub notification: Notification,
+    /// A struct for saving screenshots of notable events.
+    pub screenshots: EventScreenshots,
     /// A struct to detect game information.
     ///
     /// This is [`None`] when no frame as ever been captured.
@@ -110,7 +190,8 @@ impl Context {
             handle: Handle::new(""),
             keys: Box::new(keys.unwrap_or_default()),
             rng: Rng::new(rand::random()),
-            notification: DiscordNotification::new(Rc::new(RefCell::new(Settings::default()))),
+            notification: Notification::new(Rc::new(RefCell::new(Settings::default()))),
+            screenshots: EventScreenshots::new(Rc::new(RefCell::new(Settings::default()))),
             detector: detector.map(|detector| Box::new(detector) as Box<dyn Detector>),
             minimap: Minimap::Detecting,
             player: Player::Detecting,
@@ -136,12 +217,16 @@ impl Context {
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Display)]
 pub enum Operation {
     HaltUntil(Instant),
     Halting,
     Running,
     RunUntil(Instant),
+    /// Paused from running with the remaining run duration if run/stop cycle is enabled.
+    ///
+    /// The queued actions and the player's current action are kept while paused.
+    Paused(Option<Duration>),
 }
 
 impl Operation {
@@ -149,6 +234,18 @@ impl Operation {
     pub fn halting(&self) -> bool {
         matches!(self, Operation::Halting | Operation::HaltUntil(_))
     }
+
+    #[inline]
+    pub fn paused(&self) -> bool {
+        matches!(self, Operation::Paused(_))
+    }
+}
+
+/// Samples a run/stop cycle duration from `millis` plus or minus `random_range` milliseconds.
+pub fn cycle_duration(rng: &Rng, millis: u64, random_range: u64) -> Duration {
+    let min = millis.saturating_sub(random_range);
+    let max = millis.saturating_add(random_range);
+    Duration::from_millis(rng.random_range(min..=max))
 }
 
 pub fn init() {
@@ -164,8 +261,17 @@ pub fn init() {
             .unwrap()
             .join("onnxruntime.dll");
 
-        ort::init_from(dll.to_str().unwrap()).commit().unwrap();
+        ort::init_from(dll.to_str().unwrap())
+            .with_execution_providers(execution_providers(query_settings().execution_provider))
+            .commit()
+            .unwrap();
         windows::init();
+        // Keys held down by the crashed update loop must not stay held
+        let panic_hook = panic::take_hook();
+        panic::set_hook(Box::new(move |info| {
+            windows::release_held_keys();
+            panic_hook(info);
+        }));
         thread::spawn(|| {
             let tokio_rt = tokio::runtime::Builder::new_multi_thread()
                 .enable_all()
@@ -193,30 +299,40 @@ fn update_loop() {
     let mut character = None; // Override by UI
     let mut buffs = vec![];
     let settings = query_settings();
+    set_fps(settings.fps);
     let seeds = query_seeds(); // Fixed, unchanged
     let rng = Rng::new(seeds.seed); // Create one for Context
 
-    let key_sender_method = if let InputMethod::Rpc = settings.input_method {
-        KeySenderMethod::Rpc(handle, settings.input_method_rpc_server_url.clone())
-    } else {
-        match settings.capture_mode {
-            CaptureMode::BitBlt | CaptureMode::WindowsGraphicsCapture => {
-                KeySenderMethod::Default(handle, KeyInputKind::Fixed)
-            }
-            // This shouldn't matter because we have to get the Handle from the box capture anyway
-            CaptureMode::BitBltArea => KeySenderMethod::Default(handle, KeyInputKind::Foreground),
-        }
+    let key_input_kind = match settings.capture_mode {
+        CaptureMode::BitBlt
+        | CaptureMode::WindowsGraphicsCapture
+        | CaptureMode::DesktopDuplication
+        | CaptureMode::Replay
+        | CaptureMode::Stream => KeyInputKind::Fixed,
+        // This shouldn't matter because we have to get the Handle from the box capture anyway
+        CaptureMode::BitBltArea => KeyInputKind::Foreground,
     };
+    let key_sender_method = KeySenderMethod::from_settings(&settings, handle, key_input_kind);
     let mut keys = DefaultKeySender::new(key_sender_method, seeds);
+    keys.set_humanization(settings.input_humanization.clone());
+    keys.set_focus_guard(settings.input_focus_guard.then_some(handle));
     let key_sender = broadcast::channel::<KeyBinding>(1).0; // Callback to UI
     let mut key_receiver = KeyReceiver::new(handle, KeyInputKind::Fixed);
+    let mut hotkey_receiver = HotkeyReceiver::default();
+    let mut remote_control = RemoteControl::new(&settings);
+    let mut control_server = ControlServer::new(&settings);
+    register_global_hotkeys(&settings);
+    let _ = update_models(&settings.models, false);
+    set_detection_scale(settings.detection_scale);
 
     let mut capture_handles = Vec::<(String, Handle)>::new();
     let mut selected_capture_handle = None;
-    let mut image_capture = ImageCapture::new(handle, settings.capture_mode);
+    let mut image_capture =
+        ImageCapture::new(handle, settings.capture_mode, &settings.capture_stream_url);
     if let ImageCaptureKind::BitBltArea(capture) = image_capture.kind() {
         key_receiver = KeyReceiver::new(capture.handle(), KeyInputKind::Foreground);
-        keys.set_method(KeySenderMethod::Default(
+        keys.set_method(KeySenderMethod::from_settings(
+            &settings,
             capture.handle(),
             KeyInputKind::Foreground,
         ));
@@ -227,7 +343,8 @@ fn update_loop() {
         handle,
         keys: Box::new(keys),
         rng,
-        notification: DiscordNotification::new(settings.clone()),
+        notification: Notification::new(settings.clone()),
+        screenshots: EventScreenshots::new(settings.clone()),
         detector: None,
         minimap: Minimap::Detecting,
         player: Player::Idle,
@@ -238,7 +355,53 @@ fn update_loop() {
         did_minimap_changed: false,
     };
     let mut player_state = PlayerState::default();
+    player_state.config.rune_fallback_enlarged_crop = settings.borrow().rune_fallback_enlarged_crop;
+    player_state.config.rune_fallback_halt = settings.borrow().rune_fallback_halt;
+    player_state.config.rune_fallback_remote = settings.borrow().rune_fallback_remote;
+    player_state.config.rune_verify_frame_count = settings.borrow().rune_verify_frame_count;
+    player_state.config.maintenance_halt = settings.borrow().enable_maintenance_halt;
+    player_state.config.maintenance_halt_margin_millis =
+        settings.borrow().maintenance_halt_margin_millis;
+    player_state.config.action_position_max_platform_distance =
+        settings.borrow().action_position_max_platform_distance;
+    player_state.config.priority_action_preemption = settings.borrow().priority_action_preemption;
+    player_state.config.unstuck_strategies_mid_air = settings
+        .borrow()
+        .unstuck_strategies_mid_air
+        .iter()
+        .copied()
+        .take(MAX_UNSTUCK_STRATEGIES)
+        .collect();
+    player_state.config.unstuck_strategies_on_ground = settings
+        .borrow()
+        .unstuck_strategies_on_ground
+        .iter()
+        .copied()
+        .take(MAX_UNSTUCK_STRATEGIES)
+        .collect();
+    player_state.config.channel_selection = settings.borrow().channel_selection;
+    player_state.config.channel_count = settings.borrow().channel_count;
+    player_state.config.channel_blacklist = settings
+        .borrow()
+        .channel_blacklist
+        .iter()
+        .copied()
+        .take(MAX_CHANNEL_COUNT)
+        .collect();
+    player_state.config.channel_whitelist = settings
+        .borrow()
+        .channel_whitelist
+        .iter()
+        .copied()
+        .take(MAX_CHANNEL_COUNT)
+        .collect();
+    player_state
+        .set_channel(Some(settings.borrow().current_channel).filter(|channel| *channel > 0));
     let mut minimap_state = MinimapState::default();
+    minimap_state.set_ignored_other_players(
+        settings.borrow().ignore_guildie_players,
+        settings.borrow().ignore_friend_players,
+    );
     let mut skill_states = SkillKind::iter()
         .map(SkillState::new)
         .collect::<Vec<SkillState>>();
@@ -250,19 +413,91 @@ fn update_loop() {
     // specified threshold to pass before determining panicking is needed. This can be beneficial
     // when navigator falsely navigates to a wrong unknown location.
     let mut pending_halt = None;
+    let mut death_recovery = DeathRecovery::default();
     let mut database_event_receiver = database_event_receiver();
+    let mut panic_key_triggered_at = None;
+    let mut movement_tester = MovementTester::default();
+    let mut platform_recorder = PlatformRecorder::default();
+    let mut navigation_recorder = NavigationRecorder::default();
+    let mut key_macro_recorder = KeyMacroRecorder::default();
+    let mut input_monitor = InputMonitor::default();
+    let mut capture_monitor = CaptureMonitor::default();
+    let mut minimap_selector = MinimapSelector::default();
+    let mut session_tracker = SessionTracker::default();
+    let mut checkpoint_tracker = CheckpointTracker::load();
+    let mut scheduler = Scheduler::default();
+    // Whether the system is currently prevented from sleeping by this thread
+    let mut sleep_prevented = false;
+    let mut flight_recorder = FlightRecorder::default();
+    let mut input_audit_log = InputAuditLog::default();
+    let mut frame_recorder = FrameRecorder::default();
+    let mut tick_profiler = TickProfiler::default();
+    let mut key_watchdog = KeyWatchdog::default();
+    let mut whisper_watcher = WhisperWatcher::default();
+    let mut lie_detector_watcher = LieDetectorWatcher::default();
+    let mut gm_watcher = GmWatcher::default();
+    let mut window_tracker = WindowTracker::default();
+    let detection_cache = Arc::new(Mutex::new(DetectionCache::default()));
 
     #[cfg(debug_assertions)]
     let mut recording_images_id = None;
     #[cfg(debug_assertions)]
     let mut infering_rune = None;
 
-    loop_with_fps(FPS, || {
-        let mat = image_capture.grab().map(OwnedMat::new_from_frame);
+    loop_with_fps(|| {
+        tick_profiler.begin_tick();
+        let capture_crop = settings.borrow().capture_crop;
+        // Replayed and streamed frames do not come from the window
+        if !matches!(
+            settings.borrow().capture_mode,
+            CaptureMode::Replay | CaptureMode::Stream
+        ) && window_tracker.update(selected_capture_handle.unwrap_or(handle))
+        {
+            debug!(target: "context", "game window moved or resized, detecting minimap again");
+            image_capture.reset();
+            context.minimap = Minimap::Detecting;
+            player_state.reset_health_bar();
+        }
+        // Recording while replaying would overwrite the frames being replayed
+        frame_recorder.set_enabled(
+            settings.borrow().enable_frame_recorder
+                && !matches!(settings.borrow().capture_mode, CaptureMode::Replay),
+        );
+        let area_offset = image_capture.area_offset();
+        let mat = tick_profiler.measure(TickStage::Capture, || {
+            image_capture.grab().map(|frame| {
+                frame_recorder.record(&frame);
+                let crop_offset = capture_crop
+                    .map(|crop| (crop.x, crop.y))
+                    .unwrap_or_default();
+                // An area capture is already in client coordinates and is not scaled
+                context.keys.set_mouse_frame(match area_offset {
+                    Some((x, y)) => MouseFrame {
+                        offset: (x + crop_offset.0, y + crop_offset.1),
+                        size: None,
+                    },
+                    None => MouseFrame {
+                        offset: crop_offset,
+                        size: Some((frame.width, frame.height)),
+                    },
+                });
+                match capture_crop {
+                    Some(crop) => OwnedMat::new_from_frame_cropped(frame, crop.into()),
+                    None => OwnedMat::new_from_frame(frame),
+                }
+            })
+        });
         let was_player_alive = !player_state.is_dead();
         let was_player_navigating = navigator.was_last_point_available_or_completed();
         let mut was_cycled_to_stop = false;
-        let detector = mat.map(CachedDetector::new);
+        let captured = mat.is_some();
+        let detector = mat.map(|mat| {
+            if settings.borrow().enable_performance_mode {
+                CachedDetector::new_with_cache(mat, detection_cache.clone())
+            } else {
+                CachedDetector::new(mat)
+            }
+        });
 
         context.tick += 1;
         context.operation = match context.operation {
@@ -272,13 +507,19 @@ fn update_loop() {
                 if now < instant {
                     Operation::HaltUntil(instant)
                 } else {
+                    let settings = settings.borrow();
                     Operation::RunUntil(
-                        now + Duration::from_millis(settings.borrow().cycle_run_duration_millis),
+                        now + cycle_duration(
+                            &context.rng,
+                            settings.cycle_run_duration_millis,
+                            settings.cycle_run_duration_millis_random_range,
+                        ),
                     )
                 }
             }
             Operation::Halting => Operation::Halting,
             Operation::Running => Operation::Running,
+            Operation::Paused(remaining) => Operation::Paused(remaining),
             // Imply run/stop cycle enabled
             Operation::RunUntil(instant) => {
                 let now = Instant::now();
@@ -286,8 +527,13 @@ fn update_loop() {
                     Operation::RunUntil(instant)
                 } else {
                     was_cycled_to_stop = true;
+                    let settings = settings.borrow();
                     Operation::HaltUntil(
-                        now + Duration::from_millis(settings.borrow().cycle_stop_duration_millis),
+                        now + cycle_duration(
+                            &context.rng,
+                            settings.cycle_stop_duration_millis,
+                            settings.cycle_stop_duration_millis_random_range,
+                        ),
                     )
                 }
             }
@@ -296,10 +542,18 @@ fn update_loop() {
             let was_minimap_idle = matches!(context.minimap, Minimap::Idle(_));
 
             context.detector = Some(Box::new(detector));
-            context.minimap = fold_context(&context, context.minimap, &mut minimap_state);
+            context.minimap = tick_profiler.measure(TickStage::Minimap, || {
+                fold_context(&context, context.minimap, &mut minimap_state)
+            });
             context.did_minimap_changed =
                 was_minimap_idle && matches!(context.minimap, Minimap::Detecting);
-            context.player = fold_context(&context, context.player, &mut player_state);
+            // Player is frozen while paused so its current action continues on resume
+            if !context.operation.paused() {
+                context.player = tick_profiler.measure(TickStage::Player, || {
+                    fold_context(&context, context.player, &mut player_state)
+                });
+            }
+            let detection_started_at = Instant::now();
             for (i, state) in skill_states
                 .iter_mut()
                 .enumerate()
@@ -310,26 +564,72 @@ fn update_loop() {
             for (i, state) in buff_states.iter_mut().enumerate().take(context.buffs.len()) {
                 context.buffs[i] = fold_context(&context, context.buffs[i], state);
             }
+            tick_profiler.record(TickStage::Detection, detection_started_at.elapsed());
 
             // This must always be done last
-            navigator.update(&context);
-            if navigator.navigate_player(&context, &mut player_state) {
-                rotator.rotate_action(&context, &mut player_state);
-            }
+            tick_profiler.measure(TickStage::Rotator, || {
+                navigator.update(&context);
+                let navigated = !player_state.is_dead()
+                    && !player_state.has_maintenance_halt()
+                    && navigator.navigate_player(&context, &mut player_state);
+                // Death recovery holds the rotation until the player is back and re-buffed
+                if death_recovery.update(&mut context, &mut buff_states, navigated) && navigated {
+                    rotator.rotate_action(&context, &mut player_state);
+                }
+            });
+            movement_tester.update(&context, &mut player_state);
+            platform_recorder.update(&player_state);
+            input_monitor.update(&context, &player_state);
         }
         // TODO: Maybe should not downcast but really don't want to public update_input_delay
         // method
-        context
+        let keys = context
             .keys
             .as_any_mut()
             .downcast_mut::<DefaultKeySender>()
-            .unwrap()
-            .update_input_delay(context.tick);
+            .unwrap();
+        keys.update_input_delay(context.tick);
+        let sent_keys = keys.take_sent_keys();
+        let action_id = player_state
+            .priority_action_id()
+            .or(player_state.normal_action_id());
+        let action = player_state
+            .priority_action_name()
+            .or(player_state.normal_action_name());
+        input_audit_log.record(
+            keys.take_input_events()
+                .into_iter()
+                .map(|event| InputEvent {
+                    tick: context.tick,
+                    state: context.player.to_string(),
+                    action_id,
+                    action: action.clone(),
+                    ..event
+                }),
+        );
+        flight_recorder.set_enabled(settings.borrow().enable_flight_recorder);
+        flight_recorder.record(|millis| StateSnapshot {
+            tick: context.tick,
+            millis,
+            operation: context.operation.to_string(),
+            state: context.player.to_string(),
+            position: player_state.last_known_pos.map(|pos| (pos.x, pos.y)),
+            normal_action: player_state.normal_action_name(),
+            priority_action: player_state.priority_action_name(),
+            keys: sent_keys
+                .into_iter()
+                .map(|key| format!("{key:?}"))
+                .collect(),
+        });
         context.notification.update_scheduled_frames(|| {
             to_png(context.detector.as_ref().map(|detector| detector.mat()))
         });
+        context
+            .screenshots
+            .update(context.detector.as_deref(), context.minimap);
 
         // Poll requests, keys and update scheduled notifications frames
+        let request_handling_started_at = Instant::now();
         let mut settings_borrow_mut = settings.borrow_mut();
         // I know what you are thinking...
         let mut handler = DefaultRequestHandler {
@@ -347,26 +647,135 @@ fn update_loop() {
             minimap_data_preset: &mut minimap_preset,
             key_sender: &key_sender,
             key_receiver: &mut key_receiver,
+            hotkey_receiver: &mut hotkey_receiver,
+            remote_control: &mut remote_control,
+            control_server: &mut control_server,
             image_capture: &mut image_capture,
             capture_handles: &mut capture_handles,
             selected_capture_handle: &mut selected_capture_handle,
             database_event_receiver: &mut database_event_receiver,
+            panic_key_triggered_at: &mut panic_key_triggered_at,
+            movement_tester: &mut movement_tester,
+            platform_recorder: &mut platform_recorder,
+            navigation_recorder: &mut navigation_recorder,
+            key_macro_recorder: &mut key_macro_recorder,
+            input_audit_log: &mut input_audit_log,
+            session_tracker: &mut session_tracker,
+            checkpoint_tracker: &mut checkpoint_tracker,
+            capture_monitor: &mut capture_monitor,
+            tick_profiler: &tick_profiler,
             #[cfg(debug_assertions)]
             recording_images_id: &mut recording_images_id,
             #[cfg(debug_assertions)]
             infering_rune: &mut infering_rune,
         };
         handler.poll_request();
+        handler.update_navigation_recorder();
+        handler.update_checkpoint();
+        handler.update_capture_monitor(captured);
+
+        // Select the saved minimap matching the detected minimap after the map changes
+        if let Some(minimap) = minimap_selector.update(
+            handler.context,
+            handler.settings.auto_select_minimap,
+            handler.minimap_data.as_ref(),
+            handler.navigator.current_path_id(),
+        ) {
+            handler.select_minimap(minimap);
+        }
+
+        // Start or halt the actions when a scheduled time window starts or ends
+        match scheduler.update(
+            handler.settings.enable_schedule,
+            &handler.settings.schedules,
+            windows::local_time(),
+        ) {
+            Some(ScheduleTransition::Start(schedule)) => handler.start_by_schedule(schedule),
+            Some(ScheduleTransition::Halt) => handler.halt_by_schedule(),
+            None => (),
+        }
 
         // Go to town on stop cycle
         if was_cycled_to_stop {
+            handler
+                .session_tracker
+                .set_stop_reason(SessionStopReason::Cycle);
             handler.rotator.reset_queue();
             handler.player.clear_actions_aborted(false);
             handler.context.player = Player::Panicking(Panicking::new(PanicTo::Town));
         }
+        if handler.player.take_rune_halt() {
+            handler
+                .session_tracker
+                .set_stop_reason(SessionStopReason::RuneFailed);
+            handler.update_context_halting(true, true);
+        }
+        if handler.player.take_maintenance_halt() {
+            handler
+                .session_tracker
+                .set_stop_reason(SessionStopReason::Maintenance);
+            handler.update_context_halting(true, false);
+            handler.context.player = Player::Panicking(Panicking::new(PanicTo::Town));
+        }
+        if handler.player.take_potion_halt() {
+            handler
+                .session_tracker
+                .set_stop_reason(SessionStopReason::PotionNoEffect);
+            handler.update_context_halting(true, false);
+            handler.context.player = Player::Panicking(Panicking::new(PanicTo::Town));
+        }
+        if !handler.context.operation.halting()
+            && !handler.context.operation.paused()
+            && handler.character.as_ref().is_some_and(|character| {
+                matches!(
+                    character.inventory_full_behavior,
+                    InventoryFullBehavior::Stop
+                )
+            })
+            && matches!(handler.context.minimap, Minimap::Idle(idle) if idle.has_inventory_full())
+        {
+            handler
+                .session_tracker
+                .set_stop_reason(SessionStopReason::InventoryFull);
+            handler.update_context_halting(true, false);
+            handler.context.player = Player::Panicking(Panicking::new(PanicTo::Town));
+        }
+        // Stops sending keys while the lie detector is answered manually
+        if lie_detector_watcher.update(handler.context, handler.settings.enable_lie_detector_pause)
+        {
+            handler.update_context_paused(true);
+        }
+        if gm_watcher.update(handler.context, handler.settings.enable_gm_detection) {
+            match handler.settings.gm_behavior {
+                GmBehavior::Halt => {
+                    handler
+                        .session_tracker
+                        .set_stop_reason(SessionStopReason::GmDetected);
+                    handler.update_context_halting(true, true);
+                }
+                GmBehavior::CycleChannel => {
+                    handler.rotator.reset_queue();
+                    handler.player.clear_actions_aborted(false);
+                    handler.context.player = Player::Panicking(Panicking::new(PanicTo::Channel));
+                }
+                GmBehavior::CashShop => {
+                    handler.rotator.reset_queue();
+                    handler.player.clear_actions_aborted(false);
+                    handler.player.set_cash_shop_then_exit();
+                }
+            }
+        }
+        if handler.context.operation.halting() {
+            death_recovery.reset();
+        }
         // Upon accidental or white roomed causing map to change,
         // abort actions and send notification
-        if handler.minimap_data.is_some() && !handler.context.operation.halting() {
+        let mut notify_fail_or_map_change = false;
+        // Map changes while paused are ignored since the player can be moved manually
+        if handler.minimap_data.is_some()
+            && !handler.context.operation.halting()
+            && !handler.context.operation.paused()
+        {
             if was_player_navigating {
                 pending_halt = None;
             }
@@ -390,26 +799,70 @@ fn update_loop() {
                 handler.settings.stop_on_fail_or_change_map,
             ) {
                 (true, _, _) => {
-                    handler.update_context_halting(true, true);
+                    handler.session_tracker.track_death();
+                    if handler.settings.enable_death_recovery
+                        && death_recovery
+                            .start(handler.context, handler.settings.death_recovery_max_count)
+                    {
+                        // Abort current actions and let the navigator bring the player back to
+                        // the selected map from the respawn town
+                        handler.rotator.reset_queue();
+                        handler.player.clear_actions_aborted(true);
+                        handler.navigator.mark_dirty();
+                    } else {
+                        handler
+                            .session_tracker
+                            .set_stop_reason(SessionStopReason::PlayerDied);
+                        handler.update_context_halting(true, true);
+                    }
                 }
                 (_, true, true) => {
                     if pending_halt.is_none() {
                         pending_halt = Some(Instant::now());
                     } else {
                         pending_halt = None;
+                        handler
+                            .session_tracker
+                            .set_stop_reason(SessionStopReason::FailOrMapChange);
                         handler.update_context_halting(true, false);
                         handler.context.player = Player::Panicking(Panicking::new(PanicTo::Town));
                     }
                 }
                 _ => (),
             }
-            if can_halt_or_notify && pending_halt.is_none() {
-                drop(settings_borrow_mut); // For notification to borrow immutably
-                let _ = context
-                    .notification
-                    .schedule_notification(NotificationKind::FailOrMapChange);
+            notify_fail_or_map_change = can_halt_or_notify && pending_halt.is_none();
+        }
+
+        drop(settings_borrow_mut); // For notification to borrow immutably
+        tick_profiler.record(
+            TickStage::RequestHandling,
+            request_handling_started_at.elapsed(),
+        );
+        if notify_fail_or_map_change {
+            let _ = context
+                .notification
+                .schedule_notification(NotificationKind::FailOrMapChange);
+            context.screenshots.schedule(ScreenshotEvent::MapChanged);
+        }
+        session_tracker.update(&context, &mut player_state, &settings.borrow());
+        key_watchdog.update(&context);
+        whisper_watcher.update(
+            &context,
+            settings.borrow().notifications.notify_on_whisper_received,
+        );
+
+        // The stop phase of run/stop cycle still counts as running since it resumes by itself
+        let prevent_sleep = settings.borrow().prevent_sleep_while_running
+            && !matches!(context.operation, Operation::Halting);
+        if prevent_sleep != sleep_prevented {
+            sleep_prevented = prevent_sleep;
+            if prevent_sleep {
+                windows::prevent_sleep();
+            } else {
+                windows::allow_sleep();
             }
         }
+        tick_profiler.end_tick();
     });
 }
 
@@ -434,16 +887,29 @@ where
 }
 
 #[inline]
-fn loop_with_fps(fps: u32, mut on_tick: impl FnMut()) {
+fn loop_with_fps(mut on_tick: impl FnMut()) {
     #[cfg(debug_assertions)]
     const LOG_INTERVAL_SECS: u64 = 5;
+    /// The weight of the latest tick interval in the measured FPS.
+    const MEASURED_FPS_WEIGHT: f32 = 0.1;
 
-    let nanos_per_frame = (1_000_000_000 / fps) as u128;
     #[cfg(debug_assertions)]
     let mut last_logged_instant = Instant::now();
+    let mut last_start = None;
+    let mut fps_measured = 0.0;
 
     loop {
         let start = Instant::now();
+        let nanos_per_frame = (1_000_000_000 / fps()) as u128;
+        if let Some(last_start) = last_start.replace(start) {
+            let fps_current = 1.0 / start.duration_since(last_start).as_secs_f32();
+            fps_measured = if fps_measured == 0.0 {
+                fps_current
+            } else {
+                fps_measured + (fps_current - fps_measured) * MEASURED_FPS_WEIGHT
+            };
+            MEASURED_FPS.store(fps_measured.to_bits(), Ordering::Relaxed);
+        }
 
         on_tick();
 
@@ -463,7 +929,7 @@ fn loop_with_fps(fps: u32, mut on_tick: impl FnMut()) {
 }
 
 #[inline]
-fn to_png(frame: Option<&OwnedMat>) -> Option<Vec<u8>> {
+pub fn to_png(frame: Option<&OwnedMat>) -> Option<Vec<u8>> {
     frame.and_then(|image| {
         let mut bytes = Vector::new();
         imencode_def(".png", image, &mut bytes).ok()?;
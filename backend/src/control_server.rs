@@ -0,0 +1,527 @@
+use std::{
+    net::{Ipv4Addr, SocketAddr},
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+use axum::{
+    Json, Router,
+    body::Bytes,
+    extract::{
+        Query, Request, State,
+        ws::{Message, WebSocket, WebSocketUpgrade},
+    },
+    http::{StatusCode, Uri, header, uri::Authority},
+    middleware::{Next, from_fn_with_state},
+    response::{Html, IntoResponse, Response},
+    routing::{get, post},
+};
+use log::{debug, error, info};
+use serde::{Deserialize, Serialize};
+use tokio::{
+    net::TcpListener,
+    select, spawn,
+    sync::{
+        mpsc::{UnboundedReceiver, UnboundedSender, unbounded_channel},
+        oneshot, watch,
+    },
+    task::JoinHandle,
+    time::timeout,
+};
+
+use crate::{SessionStats, Settings};
+
+/// The minimum interval between two annotated frames.
+const FRAME_INTERVAL: Duration = Duration::from_millis(200);
+
+/// The maximum duration to wait for the next status or frame from the update loop.
+const NEXT_VALUE_TIMEOUT: Duration = Duration::from_secs(1);
+
+static DASHBOARD_HTML: &str = include_str!(env!("DASHBOARD_HTML"));
+
+/// The bot status exposed by the control server.
+#[derive(Clone, Default, Debug, Serialize)]
+pub struct ControlStatus {
+    pub operation: String,
+    pub state: String,
+    pub position: Option<(i32, i32)>,
+    pub health: Option<(u32, u32)>,
+    pub normal_action: Option<String>,
+    pub priority_action: Option<String>,
+    pub minimap: Option<String>,
+    pub preset: Option<String>,
+    /// The presets of the current minimap.
+    pub presets: Vec<String>,
+    pub character: Option<String>,
+    /// The statistics of the current session if the actions are running.
+    pub session: Option<SessionStats>,
+}
+
+/// A command received by the control server to be handled in the update loop.
+#[derive(Debug)]
+pub enum ControlCommand {
+    Start,
+    Halt,
+    Pause,
+    Resume,
+    SelectPreset(String),
+    /// Types the rune arrows (e.g. `ULDR`) when asked because they failed to be detected.
+    RuneArrows(String),
+}
+
+/// The result of a [`ControlCommand`] with an error message if it failed.
+pub type ControlCommandResult = Result<(), String>;
+
+type ControlRequest = (ControlCommand, oneshot::Sender<ControlCommandResult>);
+
+#[derive(Clone)]
+struct ServerState {
+    /// The token required in each request, empty if not required
+    token: String,
+    status: Arc<watch::Sender<ControlStatus>>,
+    frame: Arc<watch::Sender<Option<Bytes>>>,
+    minimap: Arc<watch::Sender<Option<Bytes>>>,
+    commands: UnboundedSender<ControlRequest>,
+}
+
+#[derive(Clone, PartialEq, Debug)]
+struct ServerConfig {
+    port: u16,
+    allow_lan: bool,
+    token: String,
+}
+
+impl ServerConfig {
+    fn from_settings(settings: &Settings) -> Option<ServerConfig> {
+        settings.enable_control_server.then(|| ServerConfig {
+            port: settings.control_server_port,
+            allow_lan: settings.control_server_allow_lan,
+            token: settings.control_server_token.trim().to_string(),
+        })
+    }
+}
+
+/// A PNG image produced by the update loop at most once every [`FRAME_INTERVAL`].
+#[derive(Debug)]
+struct FrameChannel {
+    sender: Arc<watch::Sender<Option<Bytes>>>,
+    updated_at: Option<Instant>,
+}
+
+impl Default for FrameChannel {
+    fn default() -> Self {
+        Self {
+            sender: Arc::new(watch::Sender::new(None)),
+            updated_at: None,
+        }
+    }
+}
+
+impl FrameChannel {
+    fn wants(&self) -> bool {
+        self.sender.receiver_count() > 0
+            && self
+                .updated_at
+                .is_none_or(|instant| instant.elapsed() >= FRAME_INTERVAL)
+    }
+
+    fn update(&mut self, frame: Vec<u8>) {
+        self.updated_at = Some(Instant::now());
+        self.sender.send_replace(Some(Bytes::from(frame)));
+    }
+}
+
+#[derive(Deserialize)]
+struct SelectPresetBody {
+    preset: String,
+}
+
+#[derive(Deserialize)]
+struct RuneArrowsBody {
+    arrows: String,
+}
+
+/// An optional HTTP and WebSocket server for controlling the bot with an embedded dashboard.
+///
+/// The status and the annotated frames are only produced by the update loop while there is at
+/// least one request waiting for them.
+#[derive(Debug)]
+pub struct ControlServer {
+    /// The current server config, [`None`] if the server is disabled
+    config: Option<ServerConfig>,
+    task: Option<JoinHandle<()>>,
+    status: Arc<watch::Sender<ControlStatus>>,
+    frame: FrameChannel,
+    minimap: FrameChannel,
+    commands: Option<UnboundedReceiver<ControlRequest>>,
+}
+
+impl ControlServer {
+    pub fn new(settings: &Settings) -> Self {
+        let mut server = Self {
+            config: None,
+            task: None,
+            status: Arc::new(watch::Sender::new(ControlStatus::default())),
+            frame: FrameChannel::default(),
+            minimap: FrameChannel::default(),
+            commands: None,
+        };
+        server.update(settings);
+        server
+    }
+
+    /// Starts, restarts or stops the server if the control server settings changed.
+    pub fn update(&mut self, settings: &Settings) {
+        let config = ServerConfig::from_settings(settings);
+        if config == self.config {
+            return;
+        }
+        if let Some(task) = self.task.take() {
+            task.abort();
+        }
+        self.commands = None;
+        self.config = config.clone();
+
+        let Some(config) = config else {
+            return;
+        };
+        if config.allow_lan && config.token.is_empty() {
+            error!(target: "control_server", "a token is required to allow access from LAN");
+            return;
+        }
+        let (sender, receiver) = unbounded_channel();
+        let state = ServerState {
+            token: config.token.clone(),
+            status: self.status.clone(),
+            frame: self.frame.sender.clone(),
+            minimap: self.minimap.sender.clone(),
+            commands: sender,
+        };
+        self.task = Some(spawn(serve(config, state)));
+        self.commands = Some(receiver);
+    }
+
+    pub fn try_recv(&mut self) -> Option<(ControlCommand, oneshot::Sender<ControlCommandResult>)> {
+        self.commands.as_mut()?.try_recv().ok()
+    }
+
+    #[inline]
+    pub fn wants_status(&self) -> bool {
+        self.status.receiver_count() > 0
+    }
+
+    pub fn update_status(&self, status: ControlStatus) {
+        self.status.send_replace(status);
+    }
+
+    #[inline]
+    pub fn wants_frame(&self) -> bool {
+        self.frame.wants()
+    }
+
+    /// Updates the annotated frame encoded as PNG.
+    pub fn update_frame(&mut self, frame: Vec<u8>) {
+        self.frame.update(frame);
+    }
+
+    #[inline]
+    pub fn wants_minimap(&self) -> bool {
+        self.minimap.wants()
+    }
+
+    /// Updates the annotated minimap cropped from the frame encoded as PNG.
+    pub fn update_minimap(&mut self, minimap: Vec<u8>) {
+        self.minimap.update(minimap);
+    }
+}
+
+impl Drop for ControlServer {
+    fn drop(&mut self) {
+        if let Some(task) = self.task.take() {
+            task.abort();
+        }
+    }
+}
+
+async fn serve(config: ServerConfig, state: ServerState) {
+    let ip = if config.allow_lan {
+        Ipv4Addr::UNSPECIFIED
+    } else {
+        Ipv4Addr::LOCALHOST
+    };
+    let address = SocketAddr::from((ip, config.port));
+    let listener = match TcpListener::bind(address).await {
+        Ok(listener) => listener,
+        Err(err) => {
+            error!(target: "control_server", "binding {address} failed {err}");
+            return;
+        }
+    };
+    info!(target: "control_server", "listening on {address}");
+
+    let router = Router::new()
+        .route("/", get(get_dashboard))
+        .route("/status", get(get_status))
+        .route("/frame", get(get_frame))
+        .route("/minimap", get(get_minimap))
+        .route("/stream", get(get_stream))
+        .route("/start", post(post_start))
+        .route("/halt", post(post_halt))
+        .route("/pause", post(post_pause))
+        .route("/resume", post(post_resume))
+        .route("/preset", post(post_preset))
+        .route("/rune", post(post_rune))
+        .layer(from_fn_with_state(state.clone(), authorize))
+        .with_state(state);
+    if let Err(err) = axum::serve(listener, router).await {
+        error!(target: "control_server", "serving failed {err}");
+    }
+}
+
+#[derive(Deserialize)]
+struct TokenQuery {
+    token: Option<String>,
+}
+
+/// Rejects the request if it does not have the required token.
+///
+/// Without a token, only requests made to and from localhost are accepted so that other websites
+/// opened in the browser cannot send commands.
+async fn authorize(State(state): State<ServerState>, request: Request, next: Next) -> Response {
+    let authorized = if state.token.is_empty() {
+        is_local_request(&request)
+    } else {
+        request_token(&request)
+            .is_some_and(|token| constant_time_eq(token.as_bytes(), state.token.as_bytes()))
+    };
+    if authorized {
+        next.run(request).await
+    } else {
+        StatusCode::UNAUTHORIZED.into_response()
+    }
+}
+
+/// Extracts the token from the `token` query parameter or the `Authorization: Bearer` header.
+fn request_token(request: &Request) -> Option<String> {
+    let query_token = Query::<TokenQuery>::try_from_uri(request.uri())
+        .ok()
+        .and_then(|Query(query)| query.token);
+    let header_token = || {
+        request
+            .headers()
+            .get(header::AUTHORIZATION)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.strip_prefix("Bearer "))
+            .map(|token| token.to_string())
+    };
+    query_token.or_else(header_token)
+}
+
+/// Whether the request `Host` and `Origin` (if any) headers are both localhost.
+///
+/// Checking `Host` prevents DNS rebinding and checking `Origin` prevents cross-site requests.
+fn is_local_request(request: &Request) -> bool {
+    let headers = request.headers();
+    let host = headers
+        .get(header::HOST)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.parse::<Authority>().ok());
+    let is_local_host = host.is_some_and(|host| is_localhost(host.host()));
+    let is_local_origin = headers.get(header::ORIGIN).is_none_or(|value| {
+        value
+            .to_str()
+            .ok()
+            .and_then(|value| value.parse::<Uri>().ok())
+            .is_some_and(|origin| origin.host().is_some_and(is_localhost))
+    });
+
+    is_local_host && is_local_origin
+}
+
+#[inline]
+fn is_localhost(host: &str) -> bool {
+    matches!(host, "localhost" | "127.0.0.1")
+}
+
+/// Compares `a` and `b` without short-circuiting on the first different byte.
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0, |acc, (a, b)| acc | (a ^ b)) == 0
+}
+
+async fn get_dashboard() -> Html<&'static str> {
+    Html(DASHBOARD_HTML)
+}
+
+/// Waits for the next value sent by the update loop.
+async fn next_value<T: Clone>(sender: &watch::Sender<T>) -> Option<T> {
+    let mut receiver = sender.subscribe();
+    timeout(NEXT_VALUE_TIMEOUT, receiver.changed())
+        .await
+        .ok()?
+        .ok()?;
+    let value = receiver.borrow().clone();
+    Some(value)
+}
+
+async fn get_status(State(state): State<ServerState>) -> Response {
+    match next_value(&state.status).await {
+        Some(status) => Json(status).into_response(),
+        None => StatusCode::SERVICE_UNAVAILABLE.into_response(),
+    }
+}
+
+async fn get_frame(State(state): State<ServerState>) -> Response {
+    png_response(next_value(&state.frame).await.flatten())
+}
+
+async fn get_minimap(State(state): State<ServerState>) -> Response {
+    png_response(next_value(&state.minimap).await.flatten())
+}
+
+fn png_response(png: Option<Bytes>) -> Response {
+    match png {
+        Some(png) => ([(header::CONTENT_TYPE, "image/png")], png).into_response(),
+        None => StatusCode::SERVICE_UNAVAILABLE.into_response(),
+    }
+}
+
+async fn get_stream(State(state): State<ServerState>, upgrade: WebSocketUpgrade) -> Response {
+    upgrade.on_upgrade(move |socket| stream_frames(socket, state.frame))
+}
+
+/// Sends each annotated frame as a binary PNG message until the socket is closed.
+async fn stream_frames(mut socket: WebSocket, frame: Arc<watch::Sender<Option<Bytes>>>) {
+    let mut receiver = frame.subscribe();
+    loop {
+        select! {
+            changed = receiver.changed() => {
+                if changed.is_err() {
+                    break;
+                }
+                let frame = receiver.borrow_and_update().clone();
+                if let Some(frame) = frame
+                    && socket.send(Message::Binary(frame)).await.is_err()
+                {
+                    break;
+                }
+            }
+            message = socket.recv() => {
+                if matches!(message, None | Some(Err(_)) | Some(Ok(Message::Close(_)))) {
+                    break;
+                }
+            }
+        }
+    }
+    debug!(target: "control_server", "frame stream closed");
+}
+
+async fn post_start(State(state): State<ServerState>) -> Response {
+    send_command(&state, ControlCommand::Start).await
+}
+
+async fn post_halt(State(state): State<ServerState>) -> Response {
+    send_command(&state, ControlCommand::Halt).await
+}
+
+async fn post_pause(State(state): State<ServerState>) -> Response {
+    send_command(&state, ControlCommand::Pause).await
+}
+
+async fn post_resume(State(state): State<ServerState>) -> Response {
+    send_command(&state, ControlCommand::Resume).await
+}
+
+async fn post_preset(
+    State(state): State<ServerState>,
+    Json(body): Json<SelectPresetBody>,
+) -> Response {
+    send_command(&state, ControlCommand::SelectPreset(body.preset)).await
+}
+
+async fn post_rune(State(state): State<ServerState>, Json(body): Json<RuneArrowsBody>) -> Response {
+    send_command(&state, ControlCommand::RuneArrows(body.arrows)).await
+}
+
+async fn send_command(state: &ServerState, command: ControlCommand) -> Response {
+    let (sender, receiver) = oneshot::channel();
+    if state.commands.send((command, sender)).is_err() {
+        return StatusCode::SERVICE_UNAVAILABLE.into_response();
+    }
+    match receiver.await {
+        Ok(Ok(())) => StatusCode::NO_CONTENT.into_response(),
+        Ok(Err(err)) => (StatusCode::CONFLICT, err).into_response(),
+        Err(_) => StatusCode::SERVICE_UNAVAILABLE.into_response(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::{body::Body, extract::Request, http::header};
+
+    use super::{ControlServer, constant_time_eq, is_local_request, request_token};
+    use crate::Settings;
+
+    #[test]
+    fn request_token_from_query_or_header() {
+        let request = Request::builder()
+            .uri("/status?foo=bar&token=a%2Bb%20c")
+            .body(Body::empty())
+            .unwrap();
+        assert_eq!(request_token(&request).as_deref(), Some("a+b c"));
+
+        let request = Request::builder()
+            .uri("/status")
+            .header(header::AUTHORIZATION, "Bearer def")
+            .body(Body::empty())
+            .unwrap();
+        assert_eq!(request_token(&request).as_deref(), Some("def"));
+
+        let request = Request::builder()
+            .uri("/status")
+            .body(Body::empty())
+            .unwrap();
+        assert_eq!(request_token(&request), None);
+    }
+
+    #[test]
+    fn local_request_requires_localhost_host_and_origin() {
+        let request = |host: &str, origin: Option<&str>| {
+            let mut builder = Request::builder().uri("/start").header(header::HOST, host);
+            if let Some(origin) = origin {
+                builder = builder.header(header::ORIGIN, origin);
+            }
+            builder.body(Body::empty()).unwrap()
+        };
+
+        assert!(is_local_request(&request("127.0.0.1:8420", None)));
+        assert!(is_local_request(&request(
+            "localhost:8420",
+            Some("http://localhost:8420")
+        )));
+        assert!(!is_local_request(&request(
+            "127.0.0.1:8420",
+            Some("https://example.com")
+        )));
+        assert!(!is_local_request(&request("127.0.0.1:8420", Some("null"))));
+        assert!(!is_local_request(&request("example.com:8420", None)));
+    }
+
+    #[test]
+    fn constant_time_eq_compares_bytes() {
+        assert!(constant_time_eq(b"token", b"token"));
+        assert!(!constant_time_eq(b"token", b"tokex"));
+        assert!(!constant_time_eq(b"token", b"tok"));
+    }
+
+    #[test]
+    fn wants_frame_only_with_receiver_and_after_interval() {
+        let mut server = ControlServer::new(&Settings::default());
+        assert!(!server.wants_frame());
+
+        let _receiver = server.frame.sender.subscribe();
+        assert!(server.wants_frame());
+
+        server.update_frame(vec![]);
+        assert!(!server.wants_frame());
+    }
+}
@@ -10,16 +10,32 @@ use platforms::windows::KeyKind;
 use rusqlite::{Connection, Params, Statement, types::Null};
 use serde::{Deserialize, Deserializer, Serialize, de::DeserializeOwned};
 use serde_json::Value;
-use strum::{Display, EnumIter, EnumString};
+use strum::{Display, EnumIter, EnumString, IntoEnumIterator};
 use tokio::sync::broadcast::{Receiver, Sender, channel};
 
-use crate::pathing;
+use crate::{network::NotificationKind, pathing};
 
 const MAPS: &str = "maps";
 const NAVIGATION_PATHS: &str = "navigation_paths";
 const CHARACTERS: &str = "characters";
 const SETTINGS: &str = "settings";
 const SEEDS: &str = "seeds";
+const AUTO_MOB_DATA: &str = "auto_mob_data";
+const CHECKPOINTS: &str = "checkpoints";
+const SESSIONS: &str = "sessions";
+
+/// The maximum number of recent revisions kept per minimap.
+const MAX_MINIMAP_REVISIONS: usize = 8;
+
+/// The maximum number of recent sessions kept in the database.
+const MAX_SESSIONS: usize = 500;
+
+/// Recently written revisions of each minimap keyed by minimap id.
+///
+/// A revision is used as the common base when merging a write made from an outdated
+/// [`Minimap::version`]. The lock also serializes minimap writes.
+static MINIMAP_REVISIONS: LazyLock<Mutex<HashMap<i64, Vec<Minimap>>>> =
+    LazyLock::new(|| Mutex::new(HashMap::new()));
 
 static CONNECTION: LazyLock<Mutex<Connection>> = LazyLock::new(|| {
     let path = env::current_exe()
@@ -51,6 +67,18 @@ static CONNECTION: LazyLock<Mutex<Connection>> = LazyLock::new(|| {
             id INTEGER PRIMARY KEY,
             data TEXT NOT NULL
         );
+        CREATE TABLE IF NOT EXISTS auto_mob_data (
+            id INTEGER PRIMARY KEY,
+            data TEXT NOT NULL
+        );
+        CREATE TABLE IF NOT EXISTS checkpoints (
+            id INTEGER PRIMARY KEY,
+            data TEXT NOT NULL
+        );
+        CREATE TABLE IF NOT EXISTS sessions (
+            id INTEGER PRIMARY KEY,
+            data TEXT NOT NULL
+        );
         "#,
     )
     .unwrap();
@@ -67,6 +95,7 @@ pub enum DatabaseEvent {
     SettingsUpdated(Settings),
     CharacterUpdated(Character),
     CharacterDeleted(i64),
+    SessionStatsUpdated,
 }
 
 trait Identifiable {
@@ -106,6 +135,84 @@ impl Default for Seeds {
 
 impl_identifiable!(Seeds);
 
+/// Auto-mobbing positions and portal links learned by the player on a [`Minimap`].
+///
+/// The id is the same as the [`Minimap`] id.
+#[derive(Clone, Debug, PartialEq, Default, Serialize, Deserialize)]
+pub struct AutoMobData {
+    #[serde(skip_serializing, default)]
+    pub id: Option<i64>,
+    /// Reachable y to the number of times it has been reached.
+    pub reachable_y_map: HashMap<i32, u32>,
+    /// Reachable y to the ignored x ranges and their ignored counts.
+    pub ignore_xs_map: HashMap<i32, Vec<((i32, i32), u32)>>,
+    /// Entry and exit positions of the in-map portals used by the player.
+    #[serde(default)]
+    pub portal_links: Vec<((i32, i32), (i32, i32))>,
+}
+
+impl_identifiable!(AutoMobData);
+
+/// A snapshot of the running actions persisted periodically to resume after a crash or restart.
+#[derive(Clone, Debug, PartialEq, Default, Serialize, Deserialize)]
+pub struct Checkpoint {
+    #[serde(skip_serializing, default)]
+    pub id: Option<i64>,
+    /// The milliseconds since the Unix epoch when the checkpoint was saved.
+    pub saved_at_millis: u64,
+    pub character_id: Option<i64>,
+    pub minimap_id: Option<i64>,
+    pub minimap_preset: Option<String>,
+    /// The remaining milliseconds of the current run/stop cycle phase if enabled.
+    pub cycle_remaining_millis: Option<u64>,
+    /// Whether [`Self::cycle_remaining_millis`] is for the stop phase of run/stop cycle.
+    pub cycle_stopping: bool,
+    /// The index of the next normal action to rotate.
+    pub normal_action_index: usize,
+    /// Whether the normal actions are rotated backward.
+    pub normal_actions_backward: bool,
+}
+
+impl_identifiable!(Checkpoint);
+
+/// Statistics of a single run from when the actions start until they halt.
+#[derive(Clone, Debug, PartialEq, Default, Serialize, Deserialize)]
+pub struct SessionStats {
+    #[serde(skip_serializing, default)]
+    pub id: Option<i64>,
+    /// The milliseconds since the Unix epoch when the session started.
+    pub started_at_millis: u64,
+    pub runtime_millis: u64,
+    /// The reason the session ended or empty if it is still running.
+    pub stop_reason: String,
+    pub completed_actions: u32,
+    pub solved_runes: u32,
+    pub failed_runes: u32,
+    pub deaths: u32,
+    pub channel_changes: u32,
+    pub unstuck_events: u32,
+    /// The estimated EXP percentage gained per hour if EXP is tracked.
+    #[serde(default)]
+    pub exp_percent_per_hour: Option<f32>,
+    /// The estimated mesos gained per hour if mesos are tracked.
+    #[serde(default)]
+    pub mesos_per_hour: Option<f32>,
+    /// The estimated EXP and mesos per hour sampled periodically during the session.
+    #[serde(default)]
+    pub income_samples: Vec<IncomeSample>,
+}
+
+/// The estimated EXP and mesos per hour at a point of a session.
+#[derive(Clone, Copy, Debug, PartialEq, Default, Serialize, Deserialize)]
+pub struct IncomeSample {
+    /// The milliseconds since the session started.
+    pub elapsed_millis: u64,
+    pub exp_percent_per_hour: Option<f32>,
+    pub mesos_per_hour: Option<f32>,
+}
+
+impl_identifiable!(SessionStats);
+
 #[derive(
     Clone, Copy, PartialEq, Default, Debug, Serialize, Deserialize, EnumIter, Display, EnumString,
 )]
@@ -113,6 +220,215 @@ pub enum InputMethod {
     #[default]
     Default,
     Rpc,
+    /// Sends keys through the Interception driver for clients that ignore `SendInput`.
+    Interception,
+    /// Sends keys to a microcontroller (e.g. Arduino or Raspberry Pi Pico) acting as a USB
+    /// keyboard through a serial port.
+    Serial,
+    /// Sends keys through a KMBox Net device over the network.
+    #[strum(to_string = "KMBox Net")]
+    KmBoxNet,
+}
+
+/// How the key hold durations are sampled around the mean.
+#[derive(
+    Clone, Copy, PartialEq, Default, Debug, Serialize, Deserialize, EnumIter, Display, EnumString,
+)]
+pub enum JitterDistribution {
+    /// Samples from a normal distribution with the mean and standard deviation.
+    #[default]
+    Normal,
+    /// Samples uniformly between the minimum and maximum.
+    Uniform,
+}
+
+/// The key hold duration of a specific key that overrides [`InputHumanization`].
+#[derive(Clone, Copy, PartialEq, Debug, Serialize, Deserialize)]
+pub struct KeyHoldOverride {
+    pub key: KeyBinding,
+    pub hold_mean_millis: u64,
+    pub hold_std_millis: u64,
+}
+
+impl Default for KeyHoldOverride {
+    fn default() -> Self {
+        Self {
+            key: KeyBinding::default(),
+            hold_mean_millis: 100,
+            hold_std_millis: 20,
+        }
+    }
+}
+
+pub const MAX_KEY_HOLD_OVERRIDES: usize = 16;
+
+/// How keys are pressed to trade off between speed and human-likeness.
+///
+/// Only applies to key presses and not to keys held down (e.g. walking).
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
+pub struct InputHumanization {
+    /// The mean milliseconds a key is held down before being released.
+    ///
+    /// The actual mean slowly drifts around this value over time.
+    pub hold_mean_millis: u64,
+    /// The standard deviation milliseconds of the hold duration for
+    /// [`JitterDistribution::Normal`].
+    pub hold_std_millis: u64,
+    pub hold_min_millis: u64,
+    pub hold_max_millis: u64,
+    pub jitter_distribution: JitterDistribution,
+    /// The minimum milliseconds between two key presses.
+    ///
+    /// Key presses sent earlier are queued and sent in order.
+    pub inter_key_delay_min_millis: u64,
+    pub inter_key_delay_max_millis: u64,
+    /// The probability in `0.0..=1.0` that a key is fumbled and held twice as long.
+    pub fumble_probability: f32,
+    /// The hold durations of specific keys.
+    pub key_overrides: Vec<KeyHoldOverride>,
+    /// Whether the mouse moves along a curved path to the target instead of jumping to it.
+    #[serde(default)]
+    pub humanize_mouse_movement: bool,
+}
+
+impl Default for InputHumanization {
+    fn default() -> Self {
+        Self {
+            hold_mean_millis: 100,
+            hold_std_millis: 20,
+            hold_min_millis: 80,
+            hold_max_millis: 120,
+            jitter_distribution: JitterDistribution::default(),
+            inter_key_delay_min_millis: 0,
+            inter_key_delay_max_millis: 0,
+            fumble_probability: 0.0,
+            key_overrides: vec![],
+            humanize_mouse_movement: false,
+        }
+    }
+}
+
+#[derive(
+    Clone, Copy, PartialEq, Default, Debug, Serialize, Deserialize, EnumIter, Display, EnumString,
+)]
+pub enum UnstuckStrategy {
+    #[default]
+    Jump,
+    RandomArrowSpam,
+    UpJump,
+    UseTownScroll,
+    ChangeChannel,
+}
+
+pub const MAX_UNSTUCK_STRATEGIES: usize = 8;
+
+/// How the channel to change to is selected.
+#[derive(
+    Clone, Copy, PartialEq, Default, Debug, Serialize, Deserialize, EnumIter, Display, EnumString,
+)]
+pub enum ChannelSelection {
+    #[default]
+    Next,
+    Random,
+}
+
+pub const MAX_CHANNEL_COUNT: usize = 40;
+
+#[derive(
+    Clone, Copy, PartialEq, Eq, Debug, Serialize, Deserialize, EnumIter, Display, EnumString,
+)]
+pub enum ScheduleDay {
+    Sunday,
+    Monday,
+    Tuesday,
+    Wednesday,
+    Thursday,
+    Friday,
+    Saturday,
+}
+
+/// A time window of the day in which the actions are automatically started.
+///
+/// The window spans to the next day when the end time is before the start time.
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
+pub struct Schedule {
+    pub enabled: bool,
+    /// Days of the week the window starts on.
+    pub days: Vec<ScheduleDay>,
+    pub start_hour: u32,
+    pub start_minute: u32,
+    pub end_hour: u32,
+    pub end_minute: u32,
+    /// The minimap to select when the window starts, keeping the current one if [`None`].
+    pub minimap_id: Option<i64>,
+    /// The preset of [`Self::minimap_id`] to select, using the first one if [`None`].
+    pub minimap_preset: Option<String>,
+}
+
+impl Default for Schedule {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            days: ScheduleDay::iter().collect(),
+            start_hour: 0,
+            start_minute: 0,
+            end_hour: 0,
+            end_minute: 0,
+            minimap_id: None,
+            minimap_preset: None,
+        }
+    }
+}
+
+/// What happens when a priority action is queued while the player is executing a normal action.
+#[derive(
+    Clone, Copy, PartialEq, Default, Debug, Serialize, Deserialize, EnumIter, Display, EnumString,
+)]
+pub enum PriorityActionPreemption {
+    /// Overrides the normal action as soon as the player can be interrupted.
+    #[default]
+    #[strum(to_string = "Abort immediately")]
+    Abort,
+    /// Waits for the player to finish moving to the normal action's position.
+    #[strum(to_string = "Finish movement first")]
+    FinishMovement,
+    /// Waits for the normal action to complete.
+    #[strum(to_string = "Finish whole action")]
+    FinishAction,
+}
+
+/// Per-map override of changing channel when a stranger stays in the map.
+#[derive(
+    Clone, Copy, PartialEq, Default, Debug, Serialize, Deserialize, EnumIter, Display, EnumString,
+)]
+pub enum StrangerChannelHop {
+    /// Uses [`Settings::enable_stranger_channel_hop`].
+    #[default]
+    #[strum(to_string = "Same as settings")]
+    Settings,
+    Enabled,
+    Disabled,
+}
+
+/// A buff or consumable a preset requires before farming.
+#[derive(
+    Clone, Copy, PartialEq, Default, Debug, Serialize, Deserialize, EnumIter, Display, EnumString,
+)]
+pub enum RequiredBuff {
+    #[default]
+    Familiar,
+    SayramElixir,
+    AureliaElixir,
+    ExpCouponX3,
+    BonusExpCoupon,
+    LegionWealth,
+    LegionLuck,
+    WealthAcquisitionPotion,
+    ExpAccumulationPotion,
+    ExtremeRedPotion,
+    ExtremeBluePotion,
+    ExtremeGreenPotion,
+    ExtremeGoldPotion,
 }
 
 #[derive(
@@ -139,6 +455,12 @@ pub struct Familiars {
     pub swap_check_millis: u64,
     pub swappable_familiars: SwappableFamiliars,
     pub swappable_rarities: HashSet<FamiliarRarity>,
+    /// The remaining familiar essence percentage below which it is considered low.
+    #[serde(default = "familiars_essence_low_percentage")]
+    pub essence_low_percentage: f32,
+    /// Whether to unsummon and resummon the familiars when the essence is low.
+    #[serde(default)]
+    pub resummon_on_essence_low: bool,
 }
 
 impl Default for Familiars {
@@ -148,6 +470,8 @@ impl Default for Familiars {
             swap_check_millis: familiars_swap_check_millis(),
             swappable_familiars: SwappableFamiliars::default(),
             swappable_rarities: HashSet::default(),
+            essence_low_percentage: familiars_essence_low_percentage(),
+            resummon_on_essence_low: false,
         }
     }
 }
@@ -156,6 +480,10 @@ fn familiars_swap_check_millis() -> u64 {
     300000
 }
 
+fn familiars_essence_low_percentage() -> f32 {
+    20.0
+}
+
 #[derive(
     Clone, Copy, PartialEq, Default, Debug, Serialize, Deserialize, EnumIter, Display, EnumString,
 )]
@@ -165,10 +493,141 @@ pub enum EliteBossBehavior {
     UseKey,
 }
 
+/// The response when a GM is detected.
+#[derive(
+    Clone, Copy, PartialEq, Default, Debug, Serialize, Deserialize, EnumIter, Display, EnumString,
+)]
+pub enum GmBehavior {
+    /// Stops the actions immediately.
+    #[default]
+    Halt,
+    /// Changes to a different channel and continues the actions.
+    CycleChannel,
+    /// Enters the cash shop, exits after a while and continues the actions.
+    CashShop,
+}
+
+/// The response when the inventory is detected to be full.
+#[derive(
+    Clone, Copy, PartialEq, Default, Debug, Serialize, Deserialize, EnumIter, Display, EnumString,
+)]
+pub enum InventoryFullBehavior {
+    /// Only sends a notification if enabled.
+    #[default]
+    NotifyOnly,
+    /// Stops the actions and goes to town.
+    Stop,
+    /// Runs the actions with [`ActionCondition::InventoryFull`].
+    RunActions,
+}
+
+/// The ONNX models used for detection.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Models {
+    /// The path to a user-provided rune arrows model or empty to use the compiled-in model.
+    #[serde(default)]
+    pub rune_model_path: String,
+    /// The minimum confidence for a rune arrow prediction to be accepted.
+    #[serde(default = "models_rune_threshold")]
+    pub rune_threshold: f32,
+    /// The path to a user-provided mob detection model or empty to use the compiled-in model.
+    #[serde(default)]
+    pub mob_model_path: String,
+    /// The minimum confidence for a mob prediction to be accepted.
+    #[serde(default = "models_mob_threshold")]
+    pub mob_threshold: f32,
+}
+
+impl Default for Models {
+    fn default() -> Self {
+        Self {
+            rune_model_path: String::default(),
+            rune_threshold: models_rune_threshold(),
+            mob_model_path: String::default(),
+            mob_threshold: models_mob_threshold(),
+        }
+    }
+}
+
+fn models_rune_threshold() -> f32 {
+    0.2
+}
+
+fn models_mob_threshold() -> f32 {
+    0.5
+}
+
+/// The options of a specific [`NotificationKind`].
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct NotificationKindOptions {
+    /// The minimum seconds between two notifications of the same kind, `0` to disable.
+    #[serde(default)]
+    pub cooldown_secs: u32,
+    /// Whether game snapshots are attached.
+    #[serde(default = "attach_snapshots_default")]
+    pub attach_snapshots: bool,
+    /// Whether the notification is critical.
+    ///
+    /// A critical notification always mentions the Discord user and is sent to the critical
+    /// Discord webhook instead if provided.
+    #[serde(default)]
+    pub critical: bool,
+}
+
+impl Default for NotificationKindOptions {
+    fn default() -> Self {
+        Self {
+            cooldown_secs: 0,
+            attach_snapshots: attach_snapshots_default(),
+            critical: false,
+        }
+    }
+}
+
+fn attach_snapshots_default() -> bool {
+    true
+}
+
 #[derive(Clone, Debug, PartialEq, Default, Serialize, Deserialize)]
 pub struct Notifications {
     pub discord_webhook_url: String,
     pub discord_user_id: String,
+    /// The Discord webhook URL for critical notifications, the normal one is used if empty.
+    #[serde(default)]
+    pub discord_critical_webhook_url: String,
+    /// Only mentions the Discord user for critical notifications.
+    #[serde(default)]
+    pub discord_ping_critical_only: bool,
+    /// Listens for commands sent by the Discord user in [`Self::discord_channel_id`].
+    #[serde(default)]
+    pub enable_discord_remote_control: bool,
+    /// The token of the Discord bot that reads commands and replies.
+    #[serde(default)]
+    pub discord_bot_token: String,
+    /// The Discord channel to read commands from.
+    #[serde(default)]
+    pub discord_channel_id: String,
+    /// The token of the Telegram bot created with `@BotFather`.
+    #[serde(default)]
+    pub telegram_bot_token: String,
+    /// The Telegram chat the bot sends notifications to.
+    #[serde(default)]
+    pub telegram_chat_id: String,
+    /// The URL of a generic HTTP webhook to post a JSON body to.
+    #[serde(default)]
+    pub webhook_url: String,
+    /// The JSON body template of the generic webhook.
+    ///
+    /// `{message}` and `{kind}` are replaced with the notification text and kind. A default
+    /// template is used if it is empty.
+    #[serde(default)]
+    pub webhook_template: String,
+    /// The ntfy topic URL (e.g. `https://ntfy.sh/<topic>`) to publish push notifications to.
+    #[serde(default)]
+    pub ntfy_url: String,
+    /// The access token for a protected ntfy topic.
+    #[serde(default)]
+    pub ntfy_access_token: String,
     pub notify_on_fail_or_change_map: bool,
     pub notify_on_rune_appear: bool,
     pub notify_on_spam_appear: bool,
@@ -177,6 +636,33 @@ pub struct Notifications {
     pub notify_on_player_guildie_appear: bool,
     pub notify_on_player_stranger_appear: bool,
     pub notify_on_player_friend_appear: bool,
+    #[serde(default)]
+    pub notify_on_rune_fail: bool,
+    #[serde(default)]
+    pub notify_on_familiar_essence_low: bool,
+    #[serde(default)]
+    pub notify_on_maintenance_countdown: bool,
+    #[serde(default)]
+    pub notify_on_invalid_action_position: bool,
+    #[serde(default)]
+    pub notify_on_capture_or_input_broken: bool,
+    #[serde(default)]
+    pub notify_on_session_summary: bool,
+    #[serde(default)]
+    pub notify_on_required_buffs_missing: bool,
+    #[serde(default)]
+    pub notify_on_inventory_full: bool,
+    #[serde(default)]
+    pub notify_on_whisper_received: bool,
+    #[serde(default)]
+    pub notify_on_lie_detector_appear: bool,
+    #[serde(default)]
+    pub notify_on_gm_appear: bool,
+    #[serde(default)]
+    pub notify_on_potion_no_effect: bool,
+    /// The options of each kind, [`NotificationKindOptions::default`] if missing.
+    #[serde(default)]
+    pub kind_options: HashMap<NotificationKind, NotificationKindOptions>,
 }
 
 #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
@@ -184,8 +670,90 @@ pub struct Settings {
     #[serde(skip_serializing, default)]
     pub id: Option<i64>,
     pub capture_mode: CaptureMode,
+    /// The area of the game screen inside the captured frame.
+    ///
+    /// Frames are cropped to this area before detection. Useful when the capture source is not
+    /// the game window itself (e.g. a streaming client).
+    #[serde(default)]
+    pub capture_crop: Option<Bound>,
+    /// The number of frames captured and ticks updated per second.
+    ///
+    /// It is clamped between 10 and 60. Timings configured in milliseconds are converted to ticks
+    /// with the current tick rate and the player internal timeouts are scaled to it.
+    #[serde(default = "fps_default")]
+    pub fps: u32,
+    /// The source URL of [`CaptureMode::Stream`].
+    ///
+    /// Supports `camera://<index>` (e.g. OBS virtual camera), `udp://<bind address>`,
+    /// `tcp://<address>` and `http://<address>/<path>` for MJPEG streams.
+    #[serde(default)]
+    pub capture_stream_url: String,
+    /// The areas to capture inside each handle by name in [`CaptureMode::BitBltArea`].
+    ///
+    /// The capture area window is used instead if the selected handle has no area.
+    #[serde(default)]
+    pub capture_areas: HashMap<String, Bound>,
+    /// The duration without any captured frame before the capture is recreated, `0` to disable.
+    #[serde(default = "capture_recovery_timeout_millis_default")]
+    pub capture_recovery_timeout_millis: u64,
+    /// Falls back to the next capture mode if recreating the capture does not help.
+    #[serde(default)]
+    pub capture_recovery_fallback: bool,
+    /// The execution provider for running the ONNX models.
+    ///
+    /// It is only applied on startup and falls back to CPU if the provider is not available.
+    #[serde(default = "execution_provider_default")]
+    pub execution_provider: ExecutionProvider,
     #[serde(default = "enable_rune_solving_default")]
     pub enable_rune_solving: bool,
+    #[serde(default)]
+    pub rune_solving_only_when_safe: bool,
+    #[serde(default)]
+    pub rune_fallback_enlarged_crop: bool,
+    #[serde(default)]
+    pub rune_fallback_halt: bool,
+    #[serde(default)]
+    pub rune_fallback_remote: bool,
+    #[serde(default = "rune_verify_frame_count_default")]
+    pub rune_verify_frame_count: u32,
+    #[serde(default)]
+    pub enable_flight_recorder: bool,
+    /// Records the captured frames to disk so they can be replayed with [`CaptureMode::Replay`].
+    #[serde(default)]
+    pub enable_frame_recorder: bool,
+    /// Saves annotated screenshots when notable events occur (e.g. rune failed, player died or
+    /// map changed).
+    #[serde(default)]
+    pub enable_event_screenshots: bool,
+    /// The folder to save the event screenshots to.
+    ///
+    /// The `recordings/screenshots` folder next to the executable is used if empty.
+    #[serde(default)]
+    pub event_screenshots_dir: String,
+    /// Maximum number of event screenshots kept before the oldest is removed.
+    #[serde(default = "event_screenshots_max_count_default")]
+    pub event_screenshots_max_count: u32,
+    /// Maximum total size in megabytes of event screenshots kept before the oldest is removed.
+    #[serde(default = "event_screenshots_max_size_mb_default")]
+    pub event_screenshots_max_size_mb: u32,
+    /// Reuses the detection results of static screen regions (e.g. buffs and HP bar) while the
+    /// regions stay unchanged between frames.
+    #[serde(default)]
+    pub enable_performance_mode: bool,
+    /// The scale of the frame used for template matching.
+    ///
+    /// Downscaling uses less CPU at the cost of some accuracy.
+    #[serde(default)]
+    pub detection_scale: DetectionScale,
+    /// Reads the EXP bar and the meso counter periodically to estimate EXP and mesos per hour.
+    #[serde(default)]
+    pub enable_income_tracking: bool,
+    /// The area of the meso counter inside the captured frame.
+    ///
+    /// Mesos are only tracked when this is set and the counter stays visible (e.g. the inventory
+    /// is kept opened).
+    #[serde(default)]
+    pub mesos_region: Option<Bound>,
     pub enable_panic_mode: bool,
     pub notify_on_spam_appear: bool,
     pub stop_on_fail_or_change_map: bool,
@@ -195,18 +763,158 @@ pub struct Settings {
     pub cycle_run_duration_millis: u64,
     #[serde(default = "cycle_stop_duration_millis_default")]
     pub cycle_stop_duration_millis: u64,
+    #[serde(default)]
+    pub cycle_run_duration_millis_random_range: u64,
+    #[serde(default)]
+    pub cycle_stop_duration_millis_random_range: u64,
+    /// Starts and halts the actions at the time windows in [`Self::schedules`].
+    #[serde(default)]
+    pub enable_schedule: bool,
+    #[serde(default)]
+    pub schedules: Vec<Schedule>,
+    #[serde(default)]
+    pub enable_death_recovery: bool,
+    #[serde(default = "death_recovery_max_count_default")]
+    pub death_recovery_max_count: u32,
+    #[serde(default)]
+    pub enable_maintenance_halt: bool,
+    #[serde(default = "maintenance_halt_margin_millis_default")]
+    pub maintenance_halt_margin_millis: u64,
+    /// Pauses the running actions while the lie detector window is visible.
+    #[serde(default = "enable_lie_detector_pause_default")]
+    pub enable_lie_detector_pause: bool,
+    /// Detects GM presence from the chat box while the actions are running.
+    #[serde(default)]
+    pub enable_gm_detection: bool,
+    #[serde(default)]
+    pub gm_behavior: GmBehavior,
+    /// Prevents the system from sleeping and the display from turning off while the actions are
+    /// running, including the stop phase of run/stop cycle.
+    #[serde(default)]
+    pub prevent_sleep_while_running: bool,
+    /// Automatically selects the saved minimap matching the detected minimap when the map changes.
+    #[serde(default)]
+    pub auto_select_minimap: bool,
+    /// The maximum distance a positioned action can be from the nearest platform.
+    ///
+    /// A value of `0` disables the check.
+    #[serde(default = "action_position_max_platform_distance_default")]
+    pub action_position_max_platform_distance: u32,
+    #[serde(default)]
+    pub priority_action_preemption: PriorityActionPreemption,
+    /// Strategies to try in order when stuck mid-air.
+    ///
+    /// An empty list uses the default random unstucking.
+    #[serde(default)]
+    pub unstuck_strategies_mid_air: Vec<UnstuckStrategy>,
+    /// Strategies to try in order when stuck on ground.
+    ///
+    /// An empty list uses the default random unstucking.
+    #[serde(default)]
+    pub unstuck_strategies_on_ground: Vec<UnstuckStrategy>,
+    /// How the channel to change to is selected.
+    #[serde(default)]
+    pub channel_selection: ChannelSelection,
+    /// Number of channels in the change channel menu.
+    #[serde(default = "channel_count_default")]
+    pub channel_count: u32,
+    /// The channel the character is in with `0` meaning unknown.
+    ///
+    /// The channel is tracked after each change so that blacklisted channels can be avoided and
+    /// whitelisted channels preferred.
+    #[serde(default)]
+    pub current_channel: u32,
+    /// Channels to never change to.
+    #[serde(default)]
+    pub channel_blacklist: Vec<u32>,
+    /// Channels to prefer when changing channel.
+    #[serde(default)]
+    pub channel_whitelist: Vec<u32>,
+    /// Changes channel when a stranger stays in the map for
+    /// [`Self::stranger_channel_hop_after_millis`].
+    #[serde(default)]
+    pub enable_stranger_channel_hop: bool,
+    #[serde(default = "stranger_channel_hop_after_millis_default")]
+    pub stranger_channel_hop_after_millis: u64,
+    /// Minimum duration between two channel changes caused by strangers.
+    #[serde(default = "stranger_channel_hop_cooldown_millis_default")]
+    pub stranger_channel_hop_cooldown_millis: u64,
+    /// Ignores guildie players in the minimap (e.g. known alts or mules in the same guild).
+    ///
+    /// Ignored players are neither notified nor counted as other players in the map.
+    #[serde(default)]
+    pub ignore_guildie_players: bool,
+    /// Ignores friend players in the minimap.
+    #[serde(default)]
+    pub ignore_friend_players: bool,
+    /// Enters the cash shop for [`Self::cash_shop_anti_idle_stay_millis`] every
+    /// [`Self::cash_shop_anti_idle_every_millis`] while the actions are running.
+    #[serde(default)]
+    pub enable_cash_shop_anti_idle: bool,
+    #[serde(default = "cash_shop_anti_idle_every_millis_default")]
+    pub cash_shop_anti_idle_every_millis: u64,
+    #[serde(default = "cash_shop_anti_idle_stay_millis_default")]
+    pub cash_shop_anti_idle_stay_millis: u64,
     pub input_method: InputMethod,
     pub input_method_rpc_server_url: String,
+    /// The serial port of [`InputMethod::Serial`] (e.g. `COM3`).
+    #[serde(default)]
+    pub input_method_serial_port: String,
+    #[serde(default = "input_method_serial_baud_rate_default")]
+    pub input_method_serial_baud_rate: u32,
+    /// The address of [`InputMethod::KmBoxNet`] (e.g. `192.168.2.188:8808`).
+    #[serde(default)]
+    pub input_method_kmbox_address: String,
+    /// The UUID shown on the screen of the KMBox Net device.
+    #[serde(default)]
+    pub input_method_kmbox_uuid: String,
+    /// Suppresses inputs when the selected game window is not in the foreground.
+    ///
+    /// Mostly for [`CaptureMode::BitBltArea`] where inputs are otherwise sent to any window on top
+    /// of the capture area.
+    #[serde(default)]
+    pub input_focus_guard: bool,
+    #[serde(default)]
+    pub input_humanization: InputHumanization,
     pub notifications: Notifications,
+    #[serde(default)]
+    pub models: Models,
     pub familiars: Familiars,
     #[serde(default = "toggle_actions_key_default")]
     pub toggle_actions_key: KeyBindingConfiguration,
+    #[serde(default = "pause_actions_key_default")]
+    pub pause_actions_key: KeyBindingConfiguration,
     #[serde(default = "platform_start_key_default")]
     pub platform_start_key: KeyBindingConfiguration,
     #[serde(default = "platform_end_key_default")]
     pub platform_end_key: KeyBindingConfiguration,
     #[serde(default = "platform_add_key_default")]
     pub platform_add_key: KeyBindingConfiguration,
+    #[serde(default = "panic_key_default")]
+    pub panic_key: KeyBindingConfiguration,
+    /// System-wide hotkey to start or halt the actions in any window.
+    #[serde(default = "global_toggle_actions_hotkey_default")]
+    pub global_toggle_actions_hotkey: GlobalHotkey,
+    /// System-wide hotkey to pause or resume the actions in any window.
+    #[serde(default = "global_pause_actions_hotkey_default")]
+    pub global_pause_actions_hotkey: GlobalHotkey,
+    /// System-wide hotkey to release all held down keys and halt the actions in any window.
+    #[serde(default = "global_emergency_halt_hotkey_default")]
+    pub global_emergency_halt_hotkey: GlobalHotkey,
+    /// Serves the local HTTP and WebSocket control API on [`Self::control_server_port`].
+    #[serde(default)]
+    pub enable_control_server: bool,
+    /// The localhost port of the control server.
+    #[serde(default = "control_server_port_default")]
+    pub control_server_port: u16,
+    /// Listens on all network interfaces so the dashboard can be accessed from LAN.
+    ///
+    /// Requires [`Self::control_server_token`].
+    #[serde(default)]
+    pub control_server_allow_lan: bool,
+    /// The token required in each control server request, empty if not required.
+    #[serde(default)]
+    pub control_server_token: String,
 }
 
 impl Default for Settings {
@@ -214,27 +922,99 @@ impl Default for Settings {
         Self {
             id: None,
             capture_mode: CaptureMode::default(),
+            capture_crop: None,
+            fps: fps_default(),
+            capture_stream_url: String::default(),
+            capture_areas: HashMap::default(),
+            capture_recovery_timeout_millis: capture_recovery_timeout_millis_default(),
+            capture_recovery_fallback: false,
+            execution_provider: execution_provider_default(),
             enable_rune_solving: enable_rune_solving_default(),
+            rune_solving_only_when_safe: false,
+            rune_fallback_enlarged_crop: false,
+            rune_fallback_halt: false,
+            rune_fallback_remote: false,
+            rune_verify_frame_count: rune_verify_frame_count_default(),
+            enable_flight_recorder: false,
+            enable_frame_recorder: false,
+            enable_event_screenshots: false,
+            event_screenshots_dir: String::default(),
+            event_screenshots_max_count: event_screenshots_max_count_default(),
+            event_screenshots_max_size_mb: event_screenshots_max_size_mb_default(),
+            enable_performance_mode: false,
+            detection_scale: DetectionScale::default(),
+            enable_income_tracking: false,
+            mesos_region: None,
             enable_panic_mode: false,
             notify_on_spam_appear: true,
             input_method: InputMethod::default(),
             input_method_rpc_server_url: String::default(),
+            input_method_serial_port: String::default(),
+            input_method_serial_baud_rate: input_method_serial_baud_rate_default(),
+            input_method_kmbox_address: String::default(),
+            input_method_kmbox_uuid: String::default(),
+            input_focus_guard: false,
+            input_humanization: InputHumanization::default(),
             stop_on_fail_or_change_map: false,
             cycle_run_stop: false,
             cycle_run_duration_millis: cycle_run_duration_millis_default(),
             cycle_stop_duration_millis: cycle_stop_duration_millis_default(),
+            cycle_run_duration_millis_random_range: 0,
+            cycle_stop_duration_millis_random_range: 0,
+            enable_schedule: false,
+            schedules: vec![],
+            enable_death_recovery: false,
+            death_recovery_max_count: death_recovery_max_count_default(),
+            enable_maintenance_halt: false,
+            maintenance_halt_margin_millis: maintenance_halt_margin_millis_default(),
+            enable_lie_detector_pause: enable_lie_detector_pause_default(),
+            enable_gm_detection: false,
+            gm_behavior: GmBehavior::default(),
+            prevent_sleep_while_running: false,
+            auto_select_minimap: false,
+            action_position_max_platform_distance: action_position_max_platform_distance_default(),
+            priority_action_preemption: PriorityActionPreemption::default(),
+            unstuck_strategies_mid_air: vec![],
+            unstuck_strategies_on_ground: vec![],
+            channel_selection: ChannelSelection::default(),
+            channel_count: channel_count_default(),
+            current_channel: 0,
+            channel_blacklist: vec![],
+            channel_whitelist: vec![],
+            enable_stranger_channel_hop: false,
+            stranger_channel_hop_after_millis: stranger_channel_hop_after_millis_default(),
+            stranger_channel_hop_cooldown_millis: stranger_channel_hop_cooldown_millis_default(),
+            ignore_guildie_players: false,
+            ignore_friend_players: false,
+            enable_cash_shop_anti_idle: false,
+            cash_shop_anti_idle_every_millis: cash_shop_anti_idle_every_millis_default(),
+            cash_shop_anti_idle_stay_millis: cash_shop_anti_idle_stay_millis_default(),
             notifications: Notifications::default(),
+            models: Models::default(),
             familiars: Familiars::default(),
             toggle_actions_key: toggle_actions_key_default(),
+            pause_actions_key: pause_actions_key_default(),
             platform_start_key: platform_start_key_default(),
             platform_end_key: platform_end_key_default(),
             platform_add_key: platform_add_key_default(),
+            panic_key: panic_key_default(),
+            global_toggle_actions_hotkey: global_toggle_actions_hotkey_default(),
+            global_pause_actions_hotkey: global_pause_actions_hotkey_default(),
+            global_emergency_halt_hotkey: global_emergency_halt_hotkey_default(),
+            enable_control_server: false,
+            control_server_port: control_server_port_default(),
+            control_server_allow_lan: false,
+            control_server_token: String::default(),
         }
     }
 }
 
 impl_identifiable!(Settings);
 
+fn fps_default() -> u32 {
+    30
+}
+
 fn cycle_run_duration_millis_default() -> u64 {
     14400000 // 4 hours
 }
@@ -243,10 +1023,74 @@ fn cycle_stop_duration_millis_default() -> u64 {
     3600000 // 1 hour
 }
 
+fn death_recovery_max_count_default() -> u32 {
+    3
+}
+
+fn input_method_serial_baud_rate_default() -> u32 {
+    115200
+}
+
+fn capture_recovery_timeout_millis_default() -> u64 {
+    10000
+}
+
+fn maintenance_halt_margin_millis_default() -> u64 {
+    120000 // 2 minutes
+}
+
+fn action_position_max_platform_distance_default() -> u32 {
+    20
+}
+
+fn channel_count_default() -> u32 {
+    30
+}
+
+fn stranger_channel_hop_after_millis_default() -> u64 {
+    30000 // 30 seconds
+}
+
+fn stranger_channel_hop_cooldown_millis_default() -> u64 {
+    300000 // 5 minutes
+}
+
+fn event_screenshots_max_count_default() -> u32 {
+    200
+}
+
+fn event_screenshots_max_size_mb_default() -> u32 {
+    500
+}
+
+fn cash_shop_anti_idle_every_millis_default() -> u64 {
+    7200000 // 2 hours
+}
+
+fn cash_shop_anti_idle_stay_millis_default() -> u64 {
+    60000 // 1 minute
+}
+
+fn rune_verify_frame_count_default() -> u32 {
+    1
+}
+
 fn enable_rune_solving_default() -> bool {
     true
 }
 
+fn execution_provider_default() -> ExecutionProvider {
+    if cfg!(feature = "gpu") {
+        ExecutionProvider::Cuda
+    } else {
+        ExecutionProvider::Cpu
+    }
+}
+
+fn enable_lie_detector_pause_default() -> bool {
+    true
+}
+
 fn toggle_actions_key_default() -> KeyBindingConfiguration {
     KeyBindingConfiguration {
         key: KeyBinding::Comma,
@@ -254,6 +1098,54 @@ fn toggle_actions_key_default() -> KeyBindingConfiguration {
     }
 }
 
+fn pause_actions_key_default() -> KeyBindingConfiguration {
+    KeyBindingConfiguration {
+        key: KeyBinding::Period,
+        enabled: false,
+    }
+}
+
+fn panic_key_default() -> KeyBindingConfiguration {
+    KeyBindingConfiguration {
+        key: KeyBinding::End,
+        enabled: false,
+    }
+}
+
+fn global_toggle_actions_hotkey_default() -> GlobalHotkey {
+    GlobalHotkey {
+        key: KeyBinding::F9,
+        ctrl: true,
+        alt: false,
+        shift: true,
+        enabled: false,
+    }
+}
+
+fn global_pause_actions_hotkey_default() -> GlobalHotkey {
+    GlobalHotkey {
+        key: KeyBinding::F10,
+        ctrl: true,
+        alt: false,
+        shift: true,
+        enabled: false,
+    }
+}
+
+fn control_server_port_default() -> u16 {
+    8420
+}
+
+fn global_emergency_halt_hotkey_default() -> GlobalHotkey {
+    GlobalHotkey {
+        key: KeyBinding::F12,
+        ctrl: true,
+        alt: false,
+        shift: true,
+        enabled: false,
+    }
+}
+
 fn platform_start_key_default() -> KeyBindingConfiguration {
     KeyBindingConfiguration {
         key: KeyBinding::J,
@@ -283,7 +1175,52 @@ pub enum CaptureMode {
     BitBlt,
     #[strum(to_string = "Windows 10 (1903 and up)")] // Thanks OBS
     WindowsGraphicsCapture,
+    /// Captures the monitor the game is on with DXGI desktop duplication and crops to the game.
+    #[strum(to_string = "Desktop Duplication")]
+    DesktopDuplication,
     BitBltArea,
+    /// Replays the frames recorded by the frame recorder instead of capturing the game.
+    #[strum(to_string = "Replay recorded frames")]
+    Replay,
+    /// Receives the frames from an external source at [`Settings::capture_stream_url`].
+    #[strum(to_string = "External stream")]
+    Stream,
+}
+
+#[derive(
+    Clone, Copy, PartialEq, Default, Debug, Serialize, Deserialize, EnumIter, Display, EnumString,
+)]
+pub enum ExecutionProvider {
+    #[default]
+    #[strum(to_string = "CPU")]
+    Cpu,
+    #[strum(to_string = "CUDA")]
+    Cuda,
+    #[strum(to_string = "DirectML")]
+    DirectMl,
+}
+
+#[derive(
+    Clone, Copy, PartialEq, Default, Debug, Serialize, Deserialize, EnumIter, Display, EnumString,
+)]
+pub enum DetectionScale {
+    #[default]
+    #[strum(to_string = "100%")]
+    Full,
+    #[strum(to_string = "75%")]
+    ThreeQuarters,
+    #[strum(to_string = "50%")]
+    Half,
+}
+
+impl DetectionScale {
+    pub fn percent(self) -> u32 {
+        match self {
+            DetectionScale::Full => 100,
+            DetectionScale::ThreeQuarters => 75,
+            DetectionScale::Half => 50,
+        }
+    }
 }
 
 #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
@@ -296,6 +1233,8 @@ pub struct Character {
     #[serde(default = "jump_key_default")]
     pub jump_key: KeyBindingConfiguration,
     pub up_jump_key: Option<KeyBindingConfiguration>,
+    #[serde(default)]
+    pub up_jump_style: UpJumpStyle,
     #[serde(default = "key_default")]
     pub interact_key: KeyBindingConfiguration,
     #[serde(default = "key_default")]
@@ -313,23 +1252,65 @@ pub struct Character {
     pub potion_key: KeyBindingConfiguration,
     pub potion_mode: PotionMode,
     pub health_update_millis: u64,
+    /// The key to use when health is below [`Self::emergency_health_percent`] (e.g. a town
+    /// return scroll or an invincibility skill).
+    ///
+    /// Only used with [`PotionMode::Percentage`].
+    #[serde(default)]
+    pub emergency_key: KeyBindingConfiguration,
+    #[serde(default = "emergency_health_percent_default")]
+    pub emergency_health_percent: f32,
+    /// Stops the actions and goes to town when using potion does not increase health several
+    /// times in a row (e.g. out of potions).
+    ///
+    /// Only used with [`PotionMode::Percentage`].
+    #[serde(default)]
+    pub halt_on_potion_no_effect: bool,
     pub familiar_buff_key: KeyBindingConfiguration,
     #[serde(default = "key_default")]
     pub familiar_essence_key: KeyBindingConfiguration,
     pub sayram_elixir_key: KeyBindingConfiguration,
+    #[serde(default)]
+    pub sayram_elixir_duration: BuffDuration,
     pub aurelia_elixir_key: KeyBindingConfiguration,
+    #[serde(default)]
+    pub aurelia_elixir_duration: BuffDuration,
     pub exp_x3_key: KeyBindingConfiguration,
+    #[serde(default)]
+    pub exp_x3_duration: BuffDuration,
     pub bonus_exp_key: KeyBindingConfiguration,
+    #[serde(default)]
+    pub bonus_exp_duration: BuffDuration,
     pub legion_wealth_key: KeyBindingConfiguration,
+    #[serde(default)]
+    pub legion_wealth_duration: BuffDuration,
     pub legion_luck_key: KeyBindingConfiguration,
+    #[serde(default)]
+    pub legion_luck_duration: BuffDuration,
     pub wealth_acquisition_potion_key: KeyBindingConfiguration,
+    #[serde(default)]
+    pub wealth_acquisition_potion_duration: BuffDuration,
     pub exp_accumulation_potion_key: KeyBindingConfiguration,
+    #[serde(default)]
+    pub exp_accumulation_potion_duration: BuffDuration,
     pub extreme_red_potion_key: KeyBindingConfiguration,
+    #[serde(default)]
+    pub extreme_red_potion_duration: BuffDuration,
     pub extreme_blue_potion_key: KeyBindingConfiguration,
+    #[serde(default)]
+    pub extreme_blue_potion_duration: BuffDuration,
     pub extreme_green_potion_key: KeyBindingConfiguration,
+    #[serde(default)]
+    pub extreme_green_potion_duration: BuffDuration,
     pub extreme_gold_potion_key: KeyBindingConfiguration,
+    #[serde(default)]
+    pub extreme_gold_potion_duration: BuffDuration,
     pub class: Class,
     pub disable_adjusting: bool,
+    #[serde(default)]
+    pub double_jump_key_timing: DoubleJumpKeyTiming,
+    #[serde(default)]
+    pub double_jump_key_delay_millis: u64,
     pub actions: Vec<ActionConfiguration>,
     #[serde(default)]
     pub elite_boss_behavior_enabled: bool,
@@ -337,8 +1318,27 @@ pub struct Character {
     pub elite_boss_behavior: EliteBossBehavior,
     #[serde(default)]
     pub elite_boss_behavior_key: KeyBinding,
+    #[serde(default)]
+    pub inventory_full_behavior: InventoryFullBehavior,
+    /// Remaps the keys sent by the bot to the keys bound in-game.
+    ///
+    /// Used when the in-game key bindings differ from the keys the bot assumes (e.g. arrow keys).
+    #[serde(default)]
+    pub key_remaps: Vec<KeyRemap>,
+    /// Overrides [`Settings::input_humanization`] for this character if set.
+    #[serde(default)]
+    pub input_humanization: Option<InputHumanization>,
+}
+
+/// Sends [`Self::to`] whenever the bot sends [`Self::from`].
+#[derive(Clone, Copy, PartialEq, Default, Debug, Serialize, Deserialize)]
+pub struct KeyRemap {
+    pub from: KeyBinding,
+    pub to: KeyBinding,
 }
 
+pub const MAX_KEY_REMAPS: usize = 16;
+
 fn num_pets_default() -> u32 {
     3
 }
@@ -351,6 +1351,10 @@ fn jump_key_default() -> KeyBindingConfiguration {
     }
 }
 
+fn emergency_health_percent_default() -> f32 {
+    20.0
+}
+
 fn key_default() -> KeyBindingConfiguration {
     // Enabled is not neccessary but for semantic purpose
     KeyBindingConfiguration {
@@ -368,6 +1372,7 @@ impl Default for Character {
             teleport_key: None,
             jump_key: jump_key_default(),
             up_jump_key: None,
+            up_jump_style: UpJumpStyle::default(),
             interact_key: key_default(),
             cash_shop_key: key_default(),
             familiar_menu_key: key_default(),
@@ -379,26 +1384,46 @@ impl Default for Character {
             potion_key: KeyBindingConfiguration::default(),
             potion_mode: PotionMode::EveryMillis(180000),
             health_update_millis: 1000,
+            emergency_key: KeyBindingConfiguration::default(),
+            emergency_health_percent: emergency_health_percent_default(),
+            halt_on_potion_no_effect: false,
             familiar_buff_key: KeyBindingConfiguration::default(),
             familiar_essence_key: key_default(),
             sayram_elixir_key: KeyBindingConfiguration::default(),
+            sayram_elixir_duration: BuffDuration::default(),
             aurelia_elixir_key: KeyBindingConfiguration::default(),
+            aurelia_elixir_duration: BuffDuration::default(),
             exp_x3_key: KeyBindingConfiguration::default(),
+            exp_x3_duration: BuffDuration::default(),
             bonus_exp_key: KeyBindingConfiguration::default(),
+            bonus_exp_duration: BuffDuration::default(),
             legion_wealth_key: KeyBindingConfiguration::default(),
+            legion_wealth_duration: BuffDuration::default(),
             legion_luck_key: KeyBindingConfiguration::default(),
+            legion_luck_duration: BuffDuration::default(),
             wealth_acquisition_potion_key: KeyBindingConfiguration::default(),
+            wealth_acquisition_potion_duration: BuffDuration::default(),
             exp_accumulation_potion_key: KeyBindingConfiguration::default(),
+            exp_accumulation_potion_duration: BuffDuration::default(),
             extreme_red_potion_key: KeyBindingConfiguration::default(),
+            extreme_red_potion_duration: BuffDuration::default(),
             extreme_blue_potion_key: KeyBindingConfiguration::default(),
+            extreme_blue_potion_duration: BuffDuration::default(),
             extreme_green_potion_key: KeyBindingConfiguration::default(),
+            extreme_green_potion_duration: BuffDuration::default(),
             extreme_gold_potion_key: KeyBindingConfiguration::default(),
+            extreme_gold_potion_duration: BuffDuration::default(),
             class: Class::default(),
             disable_adjusting: false,
+            double_jump_key_timing: DoubleJumpKeyTiming::default(),
+            double_jump_key_delay_millis: 0,
             actions: vec![],
             elite_boss_behavior_enabled: false,
             elite_boss_behavior_key: KeyBinding::default(),
             elite_boss_behavior: EliteBossBehavior::default(),
+            inventory_full_behavior: InventoryFullBehavior::default(),
+            key_remaps: vec![],
+            input_humanization: None,
         }
     }
 }
@@ -475,6 +1500,7 @@ impl From<ActionConfiguration> for Action {
             direction: ActionKeyDirection::Any,
             with: value.with,
             queue_to_front: Some(true),
+            burst: false,
             wait_before_use_millis: value.wait_before_millis,
             wait_before_use_millis_random_range: value.wait_before_millis_random_range,
             wait_after_use_millis: value.wait_after_millis,
@@ -483,12 +1509,33 @@ impl From<ActionConfiguration> for Action {
     }
 }
 
+/// The duration of a buff used to estimate its remaining duration.
+#[derive(Clone, Copy, Default, PartialEq, Debug, Serialize, Deserialize)]
+pub struct BuffDuration {
+    /// The duration of the buff or zero if unknown.
+    pub duration_millis: u64,
+    /// Re-casts the buff when its estimated remaining duration is below this.
+    ///
+    /// Zero disables re-casting before the buff runs out.
+    pub recast_remaining_millis: u64,
+}
+
 #[derive(Clone, Copy, Default, PartialEq, Debug, Serialize, Deserialize)]
 pub struct KeyBindingConfiguration {
     pub key: KeyBinding,
     pub enabled: bool,
 }
 
+/// A key combined with modifier keys that is registered as a system-wide hotkey.
+#[derive(Clone, Copy, Default, PartialEq, Debug, Serialize, Deserialize)]
+pub struct GlobalHotkey {
+    pub key: KeyBinding,
+    pub ctrl: bool,
+    pub alt: bool,
+    pub shift: bool,
+    pub enabled: bool,
+}
+
 #[derive(Clone, Copy, PartialEq, Default, Debug, Serialize, Deserialize)]
 pub struct Bound {
     pub x: i32,
@@ -526,6 +1573,41 @@ pub struct MobbingKey {
     pub wait_before_millis_random_range: u64,
     pub wait_after_millis: u64,
     pub wait_after_millis_random_range: u64,
+    /// Holds the key down for the milliseconds instead of pressing [`Self::count`] times.
+    #[serde(default)]
+    pub hold_millis: Option<u64>,
+    /// Keys to use in order after the mobbing key.
+    #[serde(default)]
+    pub linked_keys: [Option<MobbingLinkedKey>; MAX_MOBBING_LINKED_KEYS],
+}
+
+/// The maximum number of [`MobbingLinkedKey`] in a [`MobbingKey`].
+pub const MAX_MOBBING_LINKED_KEYS: usize = 2;
+
+/// A key used after the [`MobbingKey`] as part of a linked chain.
+#[derive(Clone, Copy, PartialEq, Debug, Serialize, Deserialize)]
+pub struct MobbingLinkedKey {
+    pub key: KeyBinding,
+    #[serde(default = "key_count_default")]
+    pub count: u32,
+    pub wait_after_millis: u64,
+}
+
+impl Default for MobbingLinkedKey {
+    fn default() -> Self {
+        Self {
+            key: KeyBinding::default(),
+            count: key_count_default(),
+            wait_after_millis: 0,
+        }
+    }
+}
+
+/// A [`MobbingKey`] that can only be used once per cooldown.
+#[derive(Clone, Copy, PartialEq, Default, Debug, Serialize, Deserialize)]
+pub struct MobbingCooldownKey {
+    pub key: MobbingKey,
+    pub cooldown_millis: u64,
 }
 
 impl Default for MobbingKey {
@@ -539,6 +1621,8 @@ impl Default for MobbingKey {
             wait_before_millis_random_range: 0,
             wait_after_millis: 0,
             wait_after_millis_random_range: 0,
+            hold_millis: None,
+            linked_keys: [None; MAX_MOBBING_LINKED_KEYS],
         }
     }
 }
@@ -556,6 +1640,7 @@ pub enum RotationMode {
     StartToEndThenReverse,
     AutoMobbing,
     PingPong,
+    Bossing,
 }
 
 impl_identifiable!(Character);
@@ -571,11 +1656,29 @@ pub struct Minimap {
     pub rotation_mode: RotationMode,
     #[serde(default)]
     pub rotation_ping_pong_bound: Bound,
+    /// Whether ping pong only walks within the bound instead of double jumping.
+    #[serde(default)]
+    pub rotation_ping_pong_walk_only: bool,
+    /// Whether auto mobbing prefers the quadrant with more detected mobs instead of going
+    /// clockwise.
+    #[serde(default)]
+    pub rotation_auto_mob_density_pathing: bool,
+    /// Whether to unstuck when several mobbing attacks in a row show no damage numbers.
+    #[serde(default)]
+    pub rotation_mobbing_damage_check: bool,
     #[serde(default)]
     pub rotation_auto_mob_bound: Bound,
     #[serde(default)]
     pub rotation_mobbing_key: MobbingKey,
+    /// Mobbing keys tried in order before [`Self::rotation_mobbing_key`] when off cooldown.
+    #[serde(default)]
+    pub rotation_mobbing_cooldown_keys: Vec<MobbingCooldownKey>,
     pub platforms: Vec<Platform>,
+    /// Confirmed portals of this minimap in player-relative coordinate.
+    ///
+    /// When not empty, portals are no longer detected and these are used instead.
+    #[serde(default)]
+    pub portals: Vec<Bound>,
     pub rune_platforms_pathing: bool,
     pub rune_platforms_pathing_up_jump_only: bool,
     pub auto_mob_platforms_pathing: bool,
@@ -584,7 +1687,23 @@ pub struct Minimap {
     pub actions_any_reset_on_erda_condition: bool,
     pub actions: HashMap<String, Vec<Action>>,
     #[serde(default)]
+    pub home_positions: HashMap<String, Position>,
+    #[serde(default)]
+    pub home_platforms_pathing: bool,
+    #[serde(default)]
+    pub home_platforms_pathing_up_jump_only: bool,
+    /// Buffs required by each preset before the first normal action is rotated.
+    #[serde(default)]
+    pub required_buffs: HashMap<String, Vec<RequiredBuff>>,
+    #[serde(default)]
+    pub stranger_channel_hop: StrangerChannelHop,
+    #[serde(default)]
     pub path_id: Option<i64>, // Not FK, loose coupling to another path
+    /// The revision of this minimap, incremented on each write.
+    ///
+    /// Used to detect and merge concurrent writes from an outdated copy.
+    #[serde(default)]
+    pub version: u64,
 }
 
 impl_identifiable!(Minimap);
@@ -608,14 +1727,26 @@ pub struct NavigationPoint {
     pub x: i32,
     pub y: i32,
     pub transition: NavigationTransition,
+    /// The key used by [`NavigationTransition::Key`] and [`NavigationTransition::Npc`].
+    #[serde(default)]
+    pub key: KeyBinding,
+    /// Number of times down is pressed to select the destination in the NPC dialog.
+    #[serde(default)]
+    pub npc_option: u32,
 }
 
 #[derive(
     Clone, Copy, PartialEq, Default, Debug, Serialize, Deserialize, EnumIter, Display, EnumString,
 )]
 pub enum NavigationTransition {
+    /// Presses up at the point to enter a portal.
     #[default]
     Portal,
+    /// Uses [`NavigationPoint::key`] at the point (e.g. a teleport item).
+    Key,
+    /// Talks to an NPC with [`NavigationPoint::key`] at the point and selects the destination
+    /// (e.g. a taxi).
+    Npc,
 }
 
 fn deserialize_with_ok_or_default<'a, T, D>(deserializer: D) -> Result<T, D::Error>
@@ -632,12 +1763,21 @@ pub struct Platform {
     pub x_start: i32,
     pub x_end: i32,
     pub y: i32,
+    /// The maximum x displacement of a moving or swaying platform from its resting position.
+    ///
+    /// A value of `0` means the platform does not move.
+    #[serde(default)]
+    pub moving_x_range: i32,
+    /// The duration of one full movement cycle of a moving or swaying platform.
+    #[serde(default)]
+    pub moving_period_millis: u64,
 }
 
 // TODO: Should be part of pathing logics, not here
 impl From<Platform> for pathing::Platform {
     fn from(value: Platform) -> Self {
         Self::new(value.x_start..value.x_end, value.y)
+            .moving(value.moving_x_range.max(0), value.moving_period_millis)
     }
 }
 
@@ -671,6 +1811,13 @@ pub struct ActionKey {
     pub wait_after_use_millis: u64,
     pub wait_after_use_millis_random_range: u64,
     pub queue_to_front: Option<bool>,
+    /// Whether this action is a burst member.
+    ///
+    /// Burst members are held until all of them are ready and then queued back-to-back to the
+    /// front. Only applies to actions with [`ActionCondition::EveryMillis`] or
+    /// [`ActionCondition::ErdaShowerOffCooldown`].
+    #[serde(default)]
+    pub burst: bool,
 }
 
 impl Default for ActionKey {
@@ -688,6 +1835,7 @@ impl Default for ActionKey {
             wait_after_use_millis: 0,
             wait_after_use_millis_random_range: 0,
             queue_to_front: None,
+            burst: false,
         }
     }
 }
@@ -741,6 +1889,36 @@ pub enum Class {
     Generic,
 }
 
+/// When to use the key of an action with [`ActionKeyWith::DoubleJump`] after double jumped.
+#[derive(
+    Clone, Copy, Display, Default, EnumString, EnumIter, PartialEq, Debug, Serialize, Deserialize,
+)]
+pub enum DoubleJumpKeyTiming {
+    /// Uses the key while the player is still ascending.
+    #[default]
+    Ascent,
+    /// Waits for the player to start descending before using the key.
+    Descent,
+}
+
+/// The key combination used to perform an up jump.
+#[derive(
+    Clone, Copy, Display, Default, EnumString, EnumIter, PartialEq, Debug, Serialize, Deserialize,
+)]
+pub enum UpJumpStyle {
+    /// Infers the style from the up jump and teleport keys.
+    #[default]
+    Auto,
+    /// Up arrow and the up jump key.
+    SingleKey,
+    /// Up arrow and the jump key pressed twice.
+    DoubleJumpUp,
+    /// Up arrow and the jump key held down until up jumped.
+    HoldUpJump,
+    /// Up arrow, the jump key and the teleport key.
+    TeleportUp,
+}
+
 #[derive(Clone, Copy, PartialEq, Debug, Serialize, Deserialize, EnumIter, Display, EnumString)]
 pub enum Action {
     Move(ActionMove),
@@ -777,6 +1955,9 @@ pub enum ActionCondition {
     Any,
     EveryMillis(u64),
     ErdaShowerOffCooldown,
+    /// Queued when the inventory is detected to be full and the character's
+    /// [`InventoryFullBehavior`] is [`InventoryFullBehavior::RunActions`].
+    InventoryFull,
     Linked,
 }
 
@@ -1087,20 +2268,150 @@ pub fn query_minimaps() -> Result<Vec<Minimap>> {
     query_from_table(MAPS)
 }
 
+/// Upserts `minimap` and increments its [`Minimap::version`].
+///
+/// If `minimap` was modified from an outdated version, only the fields changed since that
+/// version are written on top of the stored minimap so that concurrent changes are kept. When
+/// the outdated version is no longer known, the write falls back to last-write-wins.
 pub fn upsert_minimap(minimap: &mut Minimap) -> Result<()> {
+    let mut revisions = MINIMAP_REVISIONS.lock().unwrap();
+    if let Some(id) = minimap.id
+        && let Some(stored) = query_minimap(id)
+    {
+        let id_revisions = revisions.entry(id).or_default();
+        if !id_revisions
+            .iter()
+            .any(|revision| revision.version == stored.version)
+        {
+            push_minimap_revision(id_revisions, stored.clone());
+        }
+        if stored.version != minimap.version {
+            let base = id_revisions
+                .iter()
+                .find(|revision| revision.version == minimap.version);
+            if let Some(base) = base {
+                *minimap = merge_minimap(base, &stored, minimap)?;
+            }
+            minimap.version = stored.version;
+        }
+    }
+
+    minimap.version += 1;
     upsert_to_table(MAPS, minimap).inspect(|_| {
+        let id = minimap.id.expect("valid id if upserted");
+        push_minimap_revision(revisions.entry(id).or_default(), minimap.clone());
         let _ = EVENT.send(DatabaseEvent::MinimapUpdated(minimap.clone()));
     })
 }
 
+pub fn query_minimap(id: i64) -> Option<Minimap> {
+    let conn = CONNECTION.lock().unwrap();
+    let stmt = format!("SELECT id, data FROM {MAPS} WHERE id = ?1;");
+    let stmt = conn.prepare(&stmt).unwrap();
+    map_data(stmt, [id]).ok()?.into_iter().next()
+}
+
+#[inline]
+fn push_minimap_revision(revisions: &mut Vec<Minimap>, minimap: Minimap) {
+    if revisions.len() >= MAX_MINIMAP_REVISIONS {
+        revisions.remove(0);
+    }
+    revisions.push(minimap);
+}
+
+/// Merges `ours` modified from `base` into `stored`.
+///
+/// Each top-level field changed in `ours` compared to `base` takes precedence. Otherwise, the
+/// field from `stored` is used.
+fn merge_minimap(base: &Minimap, stored: &Minimap, ours: &Minimap) -> Result<Minimap> {
+    let base = serde_json::to_value(base)?;
+    let ours = serde_json::to_value(ours)?;
+    let Value::Object(mut merged) = serde_json::to_value(stored)? else {
+        bail!("minimap is not an object");
+    };
+    if let Value::Object(ours) = ours {
+        for (key, value) in ours {
+            if base.get(&key) != Some(&value) {
+                merged.insert(key, value);
+            }
+        }
+    }
+
+    let mut minimap = serde_json::from_value::<Minimap>(Value::Object(merged))?;
+    minimap.id = stored.id;
+    Ok(minimap)
+}
+
 pub fn delete_minimap(minimap: &Minimap) -> Result<()> {
     delete_from_table(MAPS, minimap).inspect(|_| {
+        if let Some(id) = minimap.id {
+            MINIMAP_REVISIONS.lock().unwrap().remove(&id);
+        }
+        let _ = delete_from_table(
+            AUTO_MOB_DATA,
+            &AutoMobData {
+                id: minimap.id,
+                ..AutoMobData::default()
+            },
+        );
         let _ = EVENT.send(DatabaseEvent::MinimapDeleted(
             minimap.id.expect("valid id if deleted"),
         ));
     })
 }
 
+pub fn query_auto_mob_data(minimap_id: i64) -> Option<AutoMobData> {
+    let conn = CONNECTION.lock().unwrap();
+    let stmt = format!("SELECT id, data FROM {AUTO_MOB_DATA} WHERE id = ?1;");
+    let stmt = conn.prepare(&stmt).unwrap();
+    map_data(stmt, [minimap_id]).ok()?.into_iter().next()
+}
+
+pub fn upsert_auto_mob_data(data: &mut AutoMobData) -> Result<()> {
+    upsert_to_table(AUTO_MOB_DATA, data)
+}
+
+pub fn query_checkpoint() -> Option<Checkpoint> {
+    query_from_table::<Checkpoint>(CHECKPOINTS)
+        .ok()?
+        .into_iter()
+        .next()
+}
+
+pub fn upsert_checkpoint(checkpoint: &mut Checkpoint) -> Result<()> {
+    upsert_to_table(CHECKPOINTS, checkpoint)
+}
+
+pub fn delete_checkpoint(checkpoint: &Checkpoint) -> Result<()> {
+    delete_from_table(CHECKPOINTS, checkpoint)
+}
+
+pub fn query_session_stats() -> Result<Vec<SessionStats>> {
+    query_from_table(SESSIONS)
+}
+
+/// Inserts `stats` of a completed session.
+///
+/// Only the [`MAX_SESSIONS`] most recent sessions are kept.
+pub fn insert_session_stats(stats: &mut SessionStats) -> Result<()> {
+    stats.id = None;
+    upsert_to_table(SESSIONS, stats)?;
+    let sessions = query_session_stats()?;
+    for session in sessions.iter().rev().skip(MAX_SESSIONS) {
+        let _ = delete_from_table(SESSIONS, session);
+    }
+    let _ = EVENT.send(DatabaseEvent::SessionStatsUpdated);
+    Ok(())
+}
+
+pub fn delete_session_stats() -> Result<()> {
+    for session in query_session_stats()? {
+        let _ = delete_from_table(SESSIONS, &session);
+    }
+    let _ = EVENT.send(DatabaseEvent::SessionStatsUpdated);
+    Ok(())
+}
+
 pub fn query_navigation_paths() -> Result<Vec<NavigationPath>> {
     query_from_table(NAVIGATION_PATHS)
 }
@@ -1187,3 +2498,34 @@ fn delete_from_table<T: Identifiable>(table: &str, data: &T) -> Result<()> {
 
     inner(table, data.id())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn merge_minimap_keeps_concurrent_changes() {
+        let base = Minimap {
+            id: Some(1),
+            name: "map".to_string(),
+            version: 1,
+            ..Minimap::default()
+        };
+        let stored = Minimap {
+            path_id: Some(2),
+            version: 2,
+            ..base.clone()
+        };
+        let ours = Minimap {
+            auto_mob_platforms_pathing: true,
+            ..base.clone()
+        };
+
+        let merged = merge_minimap(&base, &stored, &ours).unwrap();
+
+        assert_eq!(merged.id, Some(1));
+        assert_eq!(merged.path_id, Some(2));
+        assert_eq!(merged.version, 2);
+        assert!(merged.auto_mob_platforms_pathing);
+    }
+}
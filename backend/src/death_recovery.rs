@@ -0,0 +1,238 @@
+use log::info;
+
+use crate::{
+    buff::{Buff, BuffState},
+    context::{Context, millis_to_ticks},
+};
+
+/// The maximum duration to wait for the player to respawn in another map.
+///
+/// The player is assumed to be revived in the same map (e.g. with a safety charm) afterward.
+const RESPAWN_TIMEOUT_MILLIS: u64 = 10000;
+
+/// The maximum duration to wait for the buffs to be detected again before resuming.
+const REBUFF_TIMEOUT_MILLIS: u64 = 15000;
+
+/// Stages of recovering the player after death.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Stage {
+    /// Waiting for the player to respawn in town since the given tick.
+    Respawning(u64),
+    /// Waiting for the navigator to bring the player back to the selected map.
+    Navigating,
+    /// Waiting for the buffs to be detected again since the given tick so that the missing ones
+    /// are re-casted before the rotation resumes.
+    Rebuffing(u64),
+}
+
+/// Recovers the player after death and resumes the rotation.
+#[derive(Debug, Default)]
+pub struct DeathRecovery {
+    /// Number of times the player has died and recovered since the actions were last started.
+    ///
+    /// Once it reaches the configured limit, the next death halts the actions instead.
+    count: u32,
+    stage: Option<Stage>,
+}
+
+impl DeathRecovery {
+    /// Starts recovering the player who just died.
+    ///
+    /// Returns `false` if the player has already recovered `max_count` times and the actions
+    /// should be halted instead.
+    pub fn start(&mut self, context: &Context, max_count: u32) -> bool {
+        if self.count >= max_count {
+            return false;
+        }
+        self.count += 1;
+        self.stage = Some(Stage::Respawning(context.tick));
+        true
+    }
+
+    /// Resets the recovery count and stops recovering.
+    pub fn reset(&mut self) {
+        self.count = 0;
+        self.stage = None;
+    }
+
+    /// Updates the recovery from the current map and whether the player has been `navigated` back.
+    ///
+    /// The buffs in `context` and `buff_states` are reset once the player is back so that the
+    /// missing ones are re-casted. Returns `true` if the rotation can resume.
+    pub fn update(
+        &mut self,
+        context: &mut Context,
+        buff_states: &mut [BuffState],
+        navigated: bool,
+    ) -> bool {
+        let Some(stage) = self.stage else {
+            return true;
+        };
+        self.stage = match stage {
+            Stage::Respawning(started_tick) => {
+                if context.did_minimap_changed {
+                    info!(target: "death_recovery", "player respawned in town");
+                    Some(Stage::Navigating)
+                } else if has_elapsed(context, started_tick, RESPAWN_TIMEOUT_MILLIS) {
+                    info!(target: "death_recovery", "player revived in the same map");
+                    Some(Stage::Navigating)
+                } else {
+                    Some(stage)
+                }
+            }
+            Stage::Navigating => {
+                if navigated {
+                    info!(target: "death_recovery", "re-buffing before resuming");
+                    for state in buff_states.iter_mut() {
+                        state.reset();
+                        context.buffs[state.kind()] = Buff::No;
+                    }
+                    Some(Stage::Rebuffing(context.tick))
+                } else {
+                    Some(stage)
+                }
+            }
+            Stage::Rebuffing(started_tick) => {
+                if buff_states.iter().all(BuffState::is_detected)
+                    || has_elapsed(context, started_tick, REBUFF_TIMEOUT_MILLIS)
+                {
+                    info!(target: "death_recovery", "resuming actions");
+                    None
+                } else {
+                    Some(stage)
+                }
+            }
+        };
+        self.stage.is_none()
+    }
+}
+
+#[inline]
+fn has_elapsed(context: &Context, started_tick: u64, millis: u64) -> bool {
+    context.tick.saturating_sub(started_tick) >= millis_to_ticks(millis) as u64
+}
+
+#[cfg(test)]
+mod tests {
+    use std::assert_matches::assert_matches;
+
+    use super::*;
+    use crate::buff::BuffKind;
+
+    #[test]
+    fn start_until_max_count() {
+        let context = Context::new(None, None);
+        let mut recovery = DeathRecovery::default();
+
+        assert!(recovery.start(&context, 2));
+        assert!(recovery.start(&context, 2));
+        assert!(!recovery.start(&context, 2));
+        assert_eq!(recovery.count, 2);
+    }
+
+    #[test]
+    fn start_halt_when_max_count_zero() {
+        let context = Context::new(None, None);
+        let mut recovery = DeathRecovery::default();
+
+        assert!(!recovery.start(&context, 0));
+        assert_matches!(recovery.stage, None);
+    }
+
+    #[test]
+    fn reset_count_and_stage() {
+        let context = Context::new(None, None);
+        let mut recovery = DeathRecovery::default();
+        recovery.start(&context, 1);
+
+        recovery.reset();
+
+        assert_eq!(recovery.count, 0);
+        assert_matches!(recovery.stage, None);
+        assert!(recovery.start(&context, 1));
+    }
+
+    #[test]
+    fn update_resume_when_not_recovering() {
+        let mut context = Context::new(None, None);
+        let mut recovery = DeathRecovery::default();
+
+        assert!(recovery.update(&mut context, &mut [], false));
+    }
+
+    #[test]
+    fn update_respawning_to_navigating_on_minimap_changed() {
+        let mut context = Context::new(None, None);
+        let mut recovery = DeathRecovery::default();
+        recovery.start(&context, 1);
+
+        assert!(!recovery.update(&mut context, &mut [], true));
+        assert_matches!(recovery.stage, Some(Stage::Respawning(0)));
+
+        context.did_minimap_changed = true;
+        assert!(!recovery.update(&mut context, &mut [], false));
+        assert_matches!(recovery.stage, Some(Stage::Navigating));
+    }
+
+    #[test]
+    fn update_respawning_to_navigating_on_timeout() {
+        let mut context = Context::new(None, None);
+        let mut recovery = DeathRecovery::default();
+        recovery.start(&context, 1);
+
+        context.tick = millis_to_ticks(RESPAWN_TIMEOUT_MILLIS) as u64;
+        assert!(!recovery.update(&mut context, &mut [], false));
+        assert_matches!(recovery.stage, Some(Stage::Navigating));
+    }
+
+    #[test]
+    fn update_navigating_to_rebuffing_reset_buffs() {
+        let mut context = Context::new(None, None);
+        let mut buff_states = vec![BuffState::new(BuffKind::SayramElixir)];
+        let mut recovery = DeathRecovery {
+            count: 1,
+            stage: Some(Stage::Navigating),
+        };
+        context.buffs[BuffKind::SayramElixir] = Buff::Yes;
+
+        assert!(!recovery.update(&mut context, &mut buff_states, false));
+        assert_matches!(recovery.stage, Some(Stage::Navigating));
+
+        context.tick = 5;
+        assert!(!recovery.update(&mut context, &mut buff_states, true));
+        assert_matches!(recovery.stage, Some(Stage::Rebuffing(5)));
+        assert_matches!(context.buffs[BuffKind::SayramElixir], Buff::No);
+        assert!(!buff_states[0].is_detected());
+    }
+
+    #[test]
+    fn update_rebuffing_to_resumed_when_buffs_detected() {
+        let mut context = Context::new(None, None);
+        let mut recovery = DeathRecovery {
+            count: 1,
+            stage: Some(Stage::Rebuffing(0)),
+        };
+
+        assert!(recovery.update(&mut context, &mut [], false));
+        assert_matches!(recovery.stage, None);
+        // Count is kept until the actions are halted
+        assert_eq!(recovery.count, 1);
+    }
+
+    #[test]
+    fn update_rebuffing_to_resumed_on_timeout() {
+        let mut context = Context::new(None, None);
+        let mut buff_states = vec![BuffState::new(BuffKind::SayramElixir)];
+        let mut recovery = DeathRecovery {
+            count: 1,
+            stage: Some(Stage::Rebuffing(0)),
+        };
+
+        assert!(!recovery.update(&mut context, &mut buff_states, true));
+        assert_matches!(recovery.stage, Some(Stage::Rebuffing(0)));
+
+        context.tick = millis_to_ticks(REBUFF_TIMEOUT_MILLIS) as u64;
+        assert!(recovery.update(&mut context, &mut buff_states, true));
+        assert_matches!(recovery.stage, None);
+    }
+}
@@ -3,10 +3,13 @@ use std::{
     collections::HashMap,
     env,
     fmt::Debug,
+    fs,
+    path::Path,
     sync::{
         Arc, LazyLock, Mutex,
-        atomic::{AtomicBool, Ordering},
+        atomic::{AtomicBool, AtomicU32, Ordering},
     },
+    time::Duration,
 };
 
 use anyhow::{Result, anyhow, bail};
@@ -17,11 +20,12 @@ use mockall::mock;
 use opencv::{
     boxed_ref::BoxedRef,
     core::{
-        BORDER_CONSTANT, CMP_EQ, CMP_GT, CV_8U, CV_32FC3, CV_32S, Mat, MatExprTraitConst, MatTrait,
-        MatTraitConst, MatTraitConstManual, ModifyInplace, Point, Point2f, Range, Rect, Scalar,
-        Size, ToInputArray, Vec3b, Vec4b, Vector, add, add_weighted_def, bitwise_and_def, compare,
-        copy_make_border, divide2_def, extract_channel, find_non_zero, min_max_loc, no_array,
-        subtract_def, transpose_nd,
+        _InputArrayTraitConst, BORDER_CONSTANT, CMP_EQ, CMP_GT, CV_8U, CV_32FC3, CV_32S, Mat,
+        MatExprTraitConst, MatTrait, MatTraitConst, MatTraitConstManual, ModifyInplace, Point,
+        Point2f, Range, Rect, Scalar, Size, ToInputArray, Vec3b, Vec4b, Vector, absdiff, add,
+        add_weighted_def, bitwise_and_def, bitwise_or_def, compare, copy_make_border, divide2_def,
+        extract_channel, find_non_zero, in_range, mean_def, min_max_loc, no_array, subtract_def,
+        transpose_nd,
     },
     dnn::{
         ModelTrait, TextRecognitionModel, TextRecognitionModelTrait,
@@ -31,14 +35,16 @@ use opencv::{
     imgproc::{
         CC_STAT_AREA, CC_STAT_HEIGHT, CC_STAT_LEFT, CC_STAT_TOP, CC_STAT_WIDTH,
         CHAIN_APPROX_SIMPLE, COLOR_BGR2HSV_FULL, COLOR_BGRA2BGR, COLOR_BGRA2GRAY, COLOR_BGRA2RGB,
-        INTER_CUBIC, INTER_LINEAR, MORPH_RECT, RETR_EXTERNAL, THRESH_BINARY, TM_CCOEFF_NORMED,
-        TM_SQDIFF_NORMED, bounding_rect, connected_components_with_stats, cvt_color_def,
-        dilate_def, find_contours_def, get_structuring_element_def, match_template, min_area_rect,
-        resize, threshold,
+        INTER_AREA, INTER_CUBIC, INTER_LINEAR, INTER_NEAREST, MORPH_RECT, RETR_EXTERNAL,
+        THRESH_BINARY, TM_CCOEFF_NORMED, TM_SQDIFF_NORMED, bounding_rect,
+        connected_components_with_stats, cvt_color_def, dilate_def, find_contours_def,
+        get_structuring_element_def, match_template, median_blur, min_area_rect, resize, threshold,
     },
 };
 use ort::{
-    execution_providers::CUDAExecutionProvider,
+    execution_providers::{
+        CUDAExecutionProvider, DirectMLExecutionProvider, ExecutionProviderDispatch,
+    },
     session::{Session, SessionInputValue, SessionOutputs},
     value::TensorRef,
 };
@@ -46,11 +52,34 @@ use platforms::windows::KeyKind;
 
 #[cfg(debug_assertions)]
 use crate::debug::{debug_mat, debug_spinning_arrows};
-use crate::{array::Array, buff::BuffKind, mat::OwnedMat};
+use crate::{
+    DetectionScale, ExecutionProvider, Models, array::Array, buff::BuffKind, mat::OwnedMat,
+};
 
 const MAX_ARROWS: usize = 4;
 const MAX_SPIN_ARROWS: usize = 2; // PRAY
 
+/// The minimum width and height of a downscaled template for it to be matched on the downscaled
+/// frame.
+///
+/// Smaller templates lose too much detail when downscaled and are matched at full size instead.
+const MIN_DOWNSCALED_TEMPLATE_SIZE: i32 = 10;
+
+/// The scale in percent of the frame used for template matching.
+static DETECTION_SCALE_PERCENT: AtomicU32 = AtomicU32::new(100);
+
+/// The folder next to the executable containing the user-provided templates.
+const USER_TEMPLATES_DIR: &str = "templates";
+
+/// Decodes the compiled-in template at the path of environment variable `$name` with `$flags`.
+///
+/// See [`decode_template`] for overriding the compiled-in template.
+macro_rules! template {
+    ($name:literal, $flags:expr) => {
+        decode_template(env!($name), include_bytes!(env!($name)), $flags)
+    };
+}
+
 /// Struct for storing information about the spinning arrows
 #[derive(Debug, Copy, Clone)]
 struct SpinArrow {
@@ -79,11 +108,27 @@ pub struct ArrowsCalibrating {
     spin_arrows_calibrated: bool,
     rune_region: Option<Rect>,
     normal_arrows: Option<Array<(Rect, KeyKind), MAX_ARROWS>>,
+    /// Whether to retry with an enlarged and denoised crop when normal detection fails.
+    enlarged_crop_fallback: bool,
     #[cfg(debug_assertions)]
     is_spin_testing: bool,
 }
 
 impl ArrowsCalibrating {
+    pub fn enable_enlarged_crop_fallback(&mut self) {
+        self.enlarged_crop_fallback = true;
+    }
+
+    #[cfg(test)]
+    pub fn is_enlarged_crop_fallback_enabled(&self) -> bool {
+        self.enlarged_crop_fallback
+    }
+
+    /// Clears the cached normal arrows so that the next detection uses the current frame.
+    pub fn clear_cached_arrows(&mut self) {
+        self.normal_arrows = None;
+    }
+
     #[cfg(debug_assertions)]
     pub fn enable_spin_test(&mut self) {
         self.is_spin_testing = true;
@@ -119,6 +164,9 @@ pub trait Detector: 'static + Send + DynClone + Debug {
     /// Returns a list of mobs coordinate relative to minimap coordinate.
     fn detect_mobs(&self, minimap: Rect, bound: Rect, player: Point) -> Result<Vec<Point>>;
 
+    /// Detects a list of mobs bounding boxes on the screen.
+    fn detect_mob_bboxes(&self) -> Vec<Rect>;
+
     /// Detects whether to press ESC for unstucking.
     fn detect_esc_settings(&self) -> bool;
 
@@ -160,6 +208,11 @@ pub trait Detector: 'static + Send + DynClone + Debug {
     /// Returns `Rect` relative to `minimap` coordinate.
     fn detect_minimap_rune(&self, minimap: Rect) -> Result<Rect>;
 
+    /// Detects the platforms from the walkable line pixels of the given `minimap` rectangle.
+    ///
+    /// Returns a list of one pixel height `Rect` relative to `minimap` coordinate.
+    fn detect_minimap_platforms(&self, minimap: Rect) -> Vec<Rect>;
+
     /// Detects the player in the provided `minimap` rectangle.
     ///
     /// Returns `Rect` relative to `minimap` coordinate.
@@ -227,11 +280,41 @@ pub trait Detector: 'static + Send + DynClone + Debug {
     /// Detects whether the familiar essence depleted assuming already buffed.
     fn detect_familiar_essence_depleted(&self) -> bool;
 
+    /// Detects the remaining familiar essence gauge ratio in `[0, 1]` assuming already buffed.
+    fn detect_familiar_essence_gauge(&self) -> Result<f32>;
+
     /// Detects whether the change channel menu is opened.
     fn detect_change_channel_menu_opened(&self) -> bool;
 
     /// Detects whether the change channel menu is opened.
     fn detect_arrow_spam_open(&self) -> bool;
+
+    /// Detects the server maintenance countdown banner and returns the remaining duration.
+    fn detect_maintenance_countdown(&self) -> Result<Duration>;
+
+    /// Detects the EXP percentage of the current level from the EXP bar.
+    fn detect_exp_percent(&self) -> Result<f32>;
+
+    /// Detects the meso counter inside `region`.
+    fn detect_mesos(&self, region: Rect) -> Result<u64>;
+
+    /// Detects whether the inventory full message is shown.
+    fn detect_inventory_full(&self) -> bool;
+
+    /// Detects the latest whisper line in the chat box.
+    fn detect_whisper(&self) -> Result<String>;
+
+    /// Detects whether the lie detector window is opened.
+    fn detect_lie_detector(&self) -> bool;
+
+    /// Detects whether there is any GM announcement or GM chat in the chat box.
+    fn detect_gm_chat(&self) -> bool;
+
+    /// Detects whether a boss HP bar is shown at the top of the screen.
+    fn detect_boss_health_bar(&self) -> bool;
+
+    /// Detects whether there are any damage numbers shown on the screen.
+    fn detect_damage_numbers(&self) -> bool;
 }
 
 #[cfg(test)]
@@ -242,6 +325,7 @@ mock! {
         fn mat(&self) -> &OwnedMat;
         fn grayscale_mat(&self) -> &Mat;
         fn detect_mobs(&self, minimap: Rect, bound: Rect, player: Point) -> Result<Vec<Point>>;
+        fn detect_mob_bboxes(&self) -> Vec<Rect>;
         fn detect_esc_settings(&self) -> bool;
         fn detect_esc_confirm_button(&self) -> Result<Rect>;
         fn detect_tomb_ok_button(&self) -> Result<Rect>;
@@ -257,6 +341,7 @@ mock! {
         ) -> Result<f64>;
         fn detect_minimap_portals(&self, minimap: Rect) -> Vec<Rect>;
         fn detect_minimap_rune(&self, minimap: Rect) -> Result<Rect>;
+        fn detect_minimap_platforms(&self, minimap: Rect) -> Vec<Rect>;
         fn detect_player(&self, minimap: Rect) -> Result<Rect>;
         fn detect_player_kind(&self, minimap: Rect, kind: OtherPlayerKind) -> bool;
         fn detect_player_is_dead(&self) -> bool;
@@ -280,8 +365,18 @@ mock! {
         fn detect_familiar_scrollbar(&self) -> Result<Rect>;
         fn detect_familiar_menu_opened(&self) -> bool;
         fn detect_familiar_essence_depleted(&self) -> bool;
+        fn detect_familiar_essence_gauge(&self) -> Result<f32>;
         fn detect_change_channel_menu_opened(&self) -> bool;
         fn detect_arrow_spam_open(&self) -> bool;
+        fn detect_maintenance_countdown(&self) -> Result<Duration>;
+        fn detect_exp_percent(&self) -> Result<f32>;
+        fn detect_mesos(&self, region: Rect) -> Result<u64>;
+        fn detect_inventory_full(&self) -> bool;
+        fn detect_whisper(&self) -> Result<String>;
+        fn detect_lie_detector(&self) -> bool;
+        fn detect_gm_chat(&self) -> bool;
+        fn detect_boss_health_bar(&self) -> bool;
+        fn detect_damage_numbers(&self) -> bool;
     }
 
     impl Debug for Detector {
@@ -306,6 +401,7 @@ pub struct CachedDetector {
     mat: Arc<OwnedMat>,
     grayscale: Arc<LazyLock<Mat, MatFn>>,
     buffs_grayscale: Arc<LazyLock<Mat, MatFn>>,
+    cache: Option<Arc<Mutex<DetectionCache>>>,
 }
 
 impl CachedDetector {
@@ -323,7 +419,74 @@ impl CachedDetector {
             mat,
             grayscale,
             buffs_grayscale,
+            cache: None,
+        }
+    }
+
+    /// Creates a detector that reuses the results in `cache` for the regions unchanged since the
+    /// frame the results were detected from.
+    pub fn new_with_cache(mat: OwnedMat, cache: Arc<Mutex<DetectionCache>>) -> CachedDetector {
+        Self {
+            cache: Some(cache),
+            ..Self::new(mat)
+        }
+    }
+
+    fn detect_player_buff_uncached(&self, kind: BuffKind) -> bool {
+        let mat = match kind {
+            BuffKind::Rune
+            | BuffKind::Familiar
+            | BuffKind::SayramElixir
+            | BuffKind::AureliaElixir
+            | BuffKind::ExpCouponX3
+            | BuffKind::BonusExpCoupon => &**self.buffs_grayscale,
+            BuffKind::LegionWealth
+            | BuffKind::LegionLuck
+            | BuffKind::WealthAcquisitionPotion
+            | BuffKind::ExpAccumulationPotion
+            | BuffKind::ExtremeRedPotion
+            | BuffKind::ExtremeBluePotion
+            | BuffKind::ExtremeGreenPotion
+            | BuffKind::ExtremeGoldPotion => &to_bgr(&crop_to_buffs_region(&*self.mat)),
+        };
+        detect_player_buff(mat, kind)
+    }
+}
+
+/// Caches the results of the detections on mostly static screen regions across frames.
+#[derive(Debug, Default)]
+pub struct DetectionCache {
+    /// Whether each buff kind is detected, [`None`] if not yet detected.
+    buffs: RegionCache<[Option<bool>; BuffKind::COUNT]>,
+    /// The detected current and max health, [`None`] if not yet detected.
+    health: RegionCache<Option<Option<(u32, u32)>>>,
+}
+
+#[derive(Debug, Default)]
+struct RegionCache<T> {
+    /// The grayscale region and its bounding box that [`Self::results`] are detected from.
+    region: Option<(Rect, Mat)>,
+    results: T,
+}
+
+impl<T: Default> RegionCache<T> {
+    /// Gets the cached results for the region at `bbox` of `grayscale`.
+    ///
+    /// The results are reset if the region changed since they were detected.
+    fn results(&mut self, grayscale: &impl MatTraitConst, bbox: Rect) -> Option<&mut T> {
+        let region = grayscale.roi(bbox).ok()?;
+        let unchanged = self
+            .region
+            .as_ref()
+            .is_some_and(|(last_bbox, last_region)| {
+                *last_bbox == bbox && is_region_unchanged(last_region, &region)
+            });
+        if !unchanged {
+            self.region = Some((bbox, region.clone_pointee()));
+            self.results = T::default();
         }
+
+        Some(&mut self.results)
     }
 }
 
@@ -340,6 +503,10 @@ impl Detector for CachedDetector {
         detect_mobs(&*self.mat, minimap, bound, player)
     }
 
+    fn detect_mob_bboxes(&self) -> Vec<Rect> {
+        detect_mob_bboxes(&*self.mat)
+    }
+
     fn detect_esc_settings(&self) -> bool {
         detect_esc_settings(&**self.grayscale)
     }
@@ -391,6 +558,13 @@ impl Detector for CachedDetector {
         detect_minimap_rune(&minimap_color)
     }
 
+    fn detect_minimap_platforms(&self, minimap: Rect) -> Vec<Rect> {
+        let Ok(minimap) = self.mat.roi(minimap) else {
+            return vec![];
+        };
+        detect_minimap_platforms(&to_bgr(&minimap))
+    }
+
     fn detect_player(&self, minimap: Rect) -> Result<Rect> {
         let minimap_color = to_bgr(&self.mat.roi(minimap)?);
         detect_player(&minimap_color)
@@ -418,27 +592,33 @@ impl Detector for CachedDetector {
     }
 
     fn detect_player_health(&self, current_bar: Rect, max_bar: Rect) -> Result<(u32, u32)> {
-        detect_player_health(&*self.mat, current_bar, max_bar)
+        let Some(cache) = self.cache.as_ref() else {
+            return detect_player_health(&*self.mat, current_bar, max_bar);
+        };
+        let mut cache = cache.lock().unwrap();
+        let Some(health) = cache
+            .health
+            .results(&**self.grayscale, current_bar | max_bar)
+        else {
+            return detect_player_health(&*self.mat, current_bar, max_bar);
+        };
+
+        health
+            .get_or_insert_with(|| detect_player_health(&*self.mat, current_bar, max_bar).ok())
+            .ok_or(anyhow!("player health not found"))
     }
 
     fn detect_player_buff(&self, kind: BuffKind) -> bool {
-        let mat = match kind {
-            BuffKind::Rune
-            | BuffKind::Familiar
-            | BuffKind::SayramElixir
-            | BuffKind::AureliaElixir
-            | BuffKind::ExpCouponX3
-            | BuffKind::BonusExpCoupon => &**self.buffs_grayscale,
-            BuffKind::LegionWealth
-            | BuffKind::LegionLuck
-            | BuffKind::WealthAcquisitionPotion
-            | BuffKind::ExpAccumulationPotion
-            | BuffKind::ExtremeRedPotion
-            | BuffKind::ExtremeBluePotion
-            | BuffKind::ExtremeGreenPotion
-            | BuffKind::ExtremeGoldPotion => &to_bgr(&crop_to_buffs_region(&*self.mat)),
+        let Some(cache) = self.cache.as_ref() else {
+            return self.detect_player_buff_uncached(kind);
         };
-        detect_player_buff(mat, kind)
+        let mut cache = cache.lock().unwrap();
+        let bbox = buffs_region_bbox(self.grayscale.size().unwrap());
+        let Some(buffs) = cache.buffs.results(&**self.grayscale, bbox) else {
+            return self.detect_player_buff_uncached(kind);
+        };
+
+        *buffs[kind as usize].get_or_insert_with(|| self.detect_player_buff_uncached(kind))
     }
 
     fn detect_rune_arrows(&self, calibrating: ArrowsCalibrating) -> Result<ArrowsState> {
@@ -489,21 +669,77 @@ impl Detector for CachedDetector {
         detect_familiar_essence_depleted(&**self.buffs_grayscale)
     }
 
+    fn detect_familiar_essence_gauge(&self) -> Result<f32> {
+        detect_familiar_essence_gauge(&**self.buffs_grayscale)
+    }
+
     fn detect_change_channel_menu_opened(&self) -> bool {
         detect_change_channel_menu_opened(&**self.grayscale)
     }
     fn detect_arrow_spam_open(&self) -> bool {
         detect_arrow_spam_open(&**self.grayscale)
     }
+
+    fn detect_maintenance_countdown(&self) -> Result<Duration> {
+        detect_maintenance_countdown(&*self.mat)
+    }
+
+    fn detect_exp_percent(&self) -> Result<f32> {
+        detect_exp_percent(&*self.mat)
+    }
+
+    fn detect_mesos(&self, region: Rect) -> Result<u64> {
+        detect_mesos(&*self.mat, region)
+    }
+
+    fn detect_inventory_full(&self) -> bool {
+        detect_inventory_full(&*self.mat)
+    }
+
+    fn detect_whisper(&self) -> Result<String> {
+        detect_whisper(&*self.mat)
+    }
+
+    fn detect_lie_detector(&self) -> bool {
+        detect_lie_detector(&*self.mat)
+    }
+
+    fn detect_gm_chat(&self) -> bool {
+        detect_gm_chat(&*self.mat)
+    }
+
+    fn detect_boss_health_bar(&self) -> bool {
+        detect_boss_health_bar(&*self.mat)
+    }
+
+    fn detect_damage_numbers(&self) -> bool {
+        detect_damage_numbers(&*self.mat)
+    }
 }
 
 fn crop_to_buffs_region(mat: &impl MatTraitConst) -> BoxedRef<'_, Mat> {
-    let size = mat.size().unwrap();
+    mat.roi(buffs_region_bbox(mat.size().unwrap())).unwrap()
+}
+
+#[inline]
+fn buffs_region_bbox(size: Size) -> Rect {
     // crop to top right of the image for buffs region
     let crop_x = size.width / 3;
     let crop_y = size.height / 4;
-    let crop_bbox = Rect::new(size.width - crop_x, 0, crop_x, crop_y);
-    mat.roi(crop_bbox).unwrap()
+    Rect::new(size.width - crop_x, 0, crop_x, crop_y)
+}
+
+/// Checks whether `region` is the same as `last_region` ignoring small differences (e.g. noise).
+fn is_region_unchanged(last_region: &impl MatTraitConst, region: &impl MatTraitConst) -> bool {
+    const MAX_PIXEL_DIFF: f64 = 8.0;
+
+    let mut diff = Mat::default();
+    if absdiff(last_region, region, &mut diff).is_err() {
+        return false;
+    }
+    let mut max_diff = 0.0;
+    min_max_loc(&diff, None, Some(&mut max_diff), None, None, &no_array()).is_ok()
+        && max_diff <= MAX_PIXEL_DIFF
 }
 
 fn expand_bbox(mat: &impl MatTraitConst, bbox: Rect, size: i32) -> Rect {
@@ -522,13 +758,6 @@ fn detect_mobs(
     bound: Rect,
     player: Point,
 ) -> Result<Vec<Point>> {
-    static MOB_MODEL: LazyLock<Mutex<Session>> = LazyLock::new(|| {
-        Mutex::new(
-            build_session(include_bytes!(env!("MOB_MODEL")))
-                .expect("build mob detection session successfully"),
-        )
-    });
-
     /// Approximates the mob coordinate on screen to mob coordinate on minimap.
     ///
     /// This function tries to approximate the delta (dx, dy) that the player needs to move
@@ -599,63 +828,46 @@ fn detect_mobs(
         }
     }
 
+    let size = mat.size().unwrap();
+    let points = detect_mob_bboxes(mat)
+        .into_iter()
+        .filter_map(|bbox| to_minimap_coordinate(bbox, minimap, bound, player, size))
+        .collect::<Vec<_>>();
+    Ok(points)
+}
+
+fn detect_mob_bboxes(mat: &impl MatTraitConst) -> Vec<Rect> {
     let size = mat.size().unwrap();
     let (mat_in, w_ratio, h_ratio, left, top) = preprocess_for_yolo(mat);
     let mut model = MOB_MODEL.lock().unwrap();
-    let result = model.run([norm_rgb_to_input_value(&mat_in)]).unwrap();
+    let threshold = model.threshold;
+    let result = model
+        .session()
+        .run([norm_rgb_to_input_value(&mat_in)])
+        .unwrap();
     let result = from_output_value(&result);
     // SAFETY: 0..result.rows() is within Mat bounds
-    let points = (0..result.rows())
+    (0..result.rows())
         .map(|i| unsafe { result.at_row_unchecked::<f32>(i).unwrap() })
-        .filter(|pred| pred[4] >= 0.5)
+        .filter(|pred| pred[4] >= threshold)
         .map(|pred| remap_from_yolo(pred, size, w_ratio, h_ratio, left, top))
-        .filter_map(|bbox| to_minimap_coordinate(bbox, minimap, bound, player, size))
-        .collect::<Vec<_>>();
-    Ok(points)
+        .collect::<Vec<_>>()
 }
 
 /// TODO: Support default ratio
 static ESC_SETTINGS: LazyLock<[Mat; 11]> = LazyLock::new(|| {
     [
-        imgcodecs::imdecode(
-            include_bytes!(env!("ESC_SETTING_TEMPLATE")),
-            IMREAD_GRAYSCALE,
-        )
-        .unwrap(),
-        imgcodecs::imdecode(include_bytes!(env!("ESC_MENU_TEMPLATE")), IMREAD_GRAYSCALE).unwrap(),
-        imgcodecs::imdecode(include_bytes!(env!("ESC_EVENT_TEMPLATE")), IMREAD_GRAYSCALE).unwrap(),
-        imgcodecs::imdecode(
-            include_bytes!(env!("ESC_COMMUNITY_TEMPLATE")),
-            IMREAD_GRAYSCALE,
-        )
-        .unwrap(),
-        imgcodecs::imdecode(
-            include_bytes!(env!("ESC_CHARACTER_TEMPLATE")),
-            IMREAD_GRAYSCALE,
-        )
-        .unwrap(),
-        imgcodecs::imdecode(include_bytes!(env!("ESC_OK_TEMPLATE")), IMREAD_GRAYSCALE).unwrap(),
-        imgcodecs::imdecode(
-            include_bytes!(env!("ESC_CONFIRM_TEMPLATE")),
-            IMREAD_GRAYSCALE,
-        )
-        .unwrap(),
-        imgcodecs::imdecode(
-            include_bytes!(env!("ESC_CANCEL_TEMPLATE")),
-            IMREAD_GRAYSCALE,
-        )
-        .unwrap(),
-        imgcodecs::imdecode(
-            include_bytes!(env!("ESC_CANCEL_NEW_TEMPLATE")),
-            IMREAD_GRAYSCALE,
-        )
-        .unwrap(),
-        imgcodecs::imdecode(
-            include_bytes!(env!("ESC_END_CHAT_TEMPLATE")),
-            IMREAD_GRAYSCALE,
-        )
-        .unwrap(),
-        imgcodecs::imdecode(include_bytes!(env!("ESC_NEXT_TEMPLATE")), IMREAD_GRAYSCALE).unwrap(),
+        template!("ESC_SETTING_TEMPLATE", IMREAD_GRAYSCALE),
+        template!("ESC_MENU_TEMPLATE", IMREAD_GRAYSCALE),
+        template!("ESC_EVENT_TEMPLATE", IMREAD_GRAYSCALE),
+        template!("ESC_COMMUNITY_TEMPLATE", IMREAD_GRAYSCALE),
+        template!("ESC_CHARACTER_TEMPLATE", IMREAD_GRAYSCALE),
+        template!("ESC_OK_TEMPLATE", IMREAD_GRAYSCALE),
+        template!("ESC_CONFIRM_TEMPLATE", IMREAD_GRAYSCALE),
+        template!("ESC_CANCEL_TEMPLATE", IMREAD_GRAYSCALE),
+        template!("ESC_CANCEL_NEW_TEMPLATE", IMREAD_GRAYSCALE),
+        template!("ESC_END_CHAT_TEMPLATE", IMREAD_GRAYSCALE),
+        template!("ESC_NEXT_TEMPLATE", IMREAD_GRAYSCALE),
     ]
 });
 
@@ -673,33 +885,18 @@ fn detect_esc_confirm_button(mat: &impl ToInputArray) -> Result<Rect> {
 }
 
 fn detect_tomb_ok_button(mat: &impl ToInputArray) -> Result<Rect> {
-    static TEMPLATE: LazyLock<Mat> = LazyLock::new(|| {
-        imgcodecs::imdecode(
-            include_bytes!(env!("TOMB_BUTTON_OK_TEMPLATE")),
-            IMREAD_GRAYSCALE,
-        )
-        .unwrap()
-    });
+    static TEMPLATE: LazyLock<Mat> =
+        LazyLock::new(|| template!("TOMB_BUTTON_OK_TEMPLATE", IMREAD_GRAYSCALE));
 
     detect_template(mat, &*TEMPLATE, Point::default(), 0.75)
 }
 
 fn detect_elite_boss_bar(mat: &impl MatTraitConst) -> bool {
     /// TODO: Support default ratio
-    static TEMPLATE_1: LazyLock<Mat> = LazyLock::new(|| {
-        imgcodecs::imdecode(
-            include_bytes!(env!("ELITE_BOSS_BAR_1_TEMPLATE")),
-            IMREAD_GRAYSCALE,
-        )
-        .unwrap()
-    });
-    static TEMPLATE_2: LazyLock<Mat> = LazyLock::new(|| {
-        imgcodecs::imdecode(
-            include_bytes!(env!("ELITE_BOSS_BAR_2_TEMPLATE")),
-            IMREAD_GRAYSCALE,
-        )
-        .unwrap()
-    });
+    static TEMPLATE_1: LazyLock<Mat> =
+        LazyLock::new(|| template!("ELITE_BOSS_BAR_1_TEMPLATE", IMREAD_GRAYSCALE));
+    static TEMPLATE_2: LazyLock<Mat> =
+        LazyLock::new(|| template!("ELITE_BOSS_BAR_2_TEMPLATE", IMREAD_GRAYSCALE));
 
     let size = mat.size().unwrap();
     // crop to top part of the image for boss bar
@@ -923,9 +1120,7 @@ fn detect_minimap_match<T: ToInputArray + MatTraitConst>(
 
 fn detect_minimap_portals<T: MatTraitConst + ToInputArray>(minimap: T) -> Vec<Rect> {
     /// TODO: Support default ratio
-    static TEMPLATE: LazyLock<Mat> = LazyLock::new(|| {
-        imgcodecs::imdecode(include_bytes!(env!("PORTAL_TEMPLATE")), IMREAD_COLOR).unwrap()
-    });
+    static TEMPLATE: LazyLock<Mat> = LazyLock::new(|| template!("PORTAL_TEMPLATE", IMREAD_COLOR));
     const PORTAL_EXPAND_SIZE: i32 = 5;
 
     detect_template_multiple(&minimap, &*TEMPLATE, no_array(), Point::default(), 16, 0.7)
@@ -937,12 +1132,9 @@ fn detect_minimap_portals<T: MatTraitConst + ToInputArray>(minimap: T) -> Vec<Re
 
 fn detect_minimap_rune(minimap: &impl ToInputArray) -> Result<Rect> {
     /// TODO: Support default ratio
-    static TEMPLATE: LazyLock<Mat> = LazyLock::new(|| {
-        imgcodecs::imdecode(include_bytes!(env!("RUNE_TEMPLATE")), IMREAD_COLOR).unwrap()
-    });
-    static TEMPLATE_MASK: LazyLock<Mat> = LazyLock::new(|| {
-        imgcodecs::imdecode(include_bytes!(env!("RUNE_MASK_TEMPLATE")), IMREAD_GRAYSCALE).unwrap()
-    });
+    static TEMPLATE: LazyLock<Mat> = LazyLock::new(|| template!("RUNE_TEMPLATE", IMREAD_COLOR));
+    static TEMPLATE_MASK: LazyLock<Mat> =
+        LazyLock::new(|| template!("RUNE_MASK_TEMPLATE", IMREAD_GRAYSCALE));
 
     // Expands by 2 pixels to preserve previous position calculation. Previous template is 11x11
     // while the current template is 9x9
@@ -950,11 +1142,50 @@ fn detect_minimap_rune(minimap: &impl ToInputArray) -> Result<Rect> {
         .map(|(rect, _)| Rect::new(rect.x - 1, rect.y - 1, rect.width + 2, rect.height + 2))
 }
 
+fn detect_minimap_platforms(minimap: &impl MatTraitConst) -> Vec<Rect> {
+    /// Minimum value of all BGR channels for a pixel to be part of a walkable line.
+    const LINE_MIN_VALUE: u8 = 150;
+    /// Maximum difference between BGR channels for a pixel to be part of a walkable line.
+    const LINE_MAX_CHROMA: u8 = 30;
+    /// Number of pixels from the edges to ignore because of the minimap border.
+    const BORDER_MARGIN: i32 = 2;
+    /// Minimum width of a detected platform.
+    const MIN_PLATFORM_WIDTH: i32 = 5;
+
+    let is_line = |row: i32, col: i32| {
+        let pixel = minimap.at_2d::<Vec3b>(row, col).unwrap();
+        let min = pixel.into_iter().min().unwrap();
+        let max = pixel.into_iter().max().unwrap();
+        min >= LINE_MIN_VALUE && max - min <= LINE_MAX_CHROMA
+    };
+    // Only the top-most pixel of a line is the surface the player can stand on
+    let is_surface = |row: i32, col: i32| is_line(row, col) && !is_line(row - 1, col);
+
+    let mut platforms = vec![];
+    let col_end = minimap.cols() - BORDER_MARGIN;
+    for row in BORDER_MARGIN..minimap.rows() - BORDER_MARGIN {
+        let mut col = BORDER_MARGIN;
+        while col < col_end {
+            if !is_surface(row, col) {
+                col += 1;
+                continue;
+            }
+
+            let start = col;
+            while col < col_end && is_surface(row, col) {
+                col += 1;
+            }
+            if col - start >= MIN_PLATFORM_WIDTH {
+                platforms.push(Rect::new(start, row, col - start, 1));
+            }
+        }
+    }
+    platforms
+}
+
 fn detect_player(mat: &impl ToInputArray) -> Result<Rect> {
     /// TODO: Support default ratio
-    static TEMPLATE: LazyLock<Mat> = LazyLock::new(|| {
-        imgcodecs::imdecode(include_bytes!(env!("PLAYER_TEMPLATE")), IMREAD_COLOR).unwrap()
-    });
+    static TEMPLATE: LazyLock<Mat> = LazyLock::new(|| template!("PLAYER_TEMPLATE", IMREAD_COLOR));
 
     // Expands by 2 pixels to preserve previous position calculation. Previous template is 10x10
     // while the current template is 8x8.
@@ -964,23 +1195,12 @@ fn detect_player(mat: &impl ToInputArray) -> Result<Rect> {
 
 fn detect_player_kind(mat: &impl ToInputArray, kind: OtherPlayerKind) -> bool {
     /// TODO: Support default ratio
-    static STRANGER_TEMPLATE: LazyLock<Mat> = LazyLock::new(|| {
-        imgcodecs::imdecode(
-            include_bytes!(env!("PLAYER_STRANGER_TEMPLATE")),
-            IMREAD_COLOR,
-        )
-        .unwrap()
-    });
-    static GUILDIE_TEMPLATE: LazyLock<Mat> = LazyLock::new(|| {
-        imgcodecs::imdecode(
-            include_bytes!(env!("PLAYER_GUILDIE_TEMPLATE")),
-            IMREAD_COLOR,
-        )
-        .unwrap()
-    });
-    static FRIEND_TEMPLATE: LazyLock<Mat> = LazyLock::new(|| {
-        imgcodecs::imdecode(include_bytes!(env!("PLAYER_FRIEND_TEMPLATE")), IMREAD_COLOR).unwrap()
-    });
+    static STRANGER_TEMPLATE: LazyLock<Mat> =
+        LazyLock::new(|| template!("PLAYER_STRANGER_TEMPLATE", IMREAD_COLOR));
+    static GUILDIE_TEMPLATE: LazyLock<Mat> =
+        LazyLock::new(|| template!("PLAYER_GUILDIE_TEMPLATE", IMREAD_COLOR));
+    static FRIEND_TEMPLATE: LazyLock<Mat> =
+        LazyLock::new(|| template!("PLAYER_FRIEND_TEMPLATE", IMREAD_COLOR));
 
     match kind {
         OtherPlayerKind::Stranger => {
@@ -997,30 +1217,24 @@ fn detect_player_kind(mat: &impl ToInputArray, kind: OtherPlayerKind) -> bool {
 
 fn detect_player_is_dead(mat: &impl ToInputArray) -> bool {
     /// TODO: Support default ratio
-    static TEMPLATE: LazyLock<Mat> = LazyLock::new(|| {
-        imgcodecs::imdecode(include_bytes!(env!("TOMB_TEMPLATE")), IMREAD_GRAYSCALE).unwrap()
-    });
+    static TEMPLATE: LazyLock<Mat> = LazyLock::new(|| template!("TOMB_TEMPLATE", IMREAD_GRAYSCALE));
 
     detect_template(mat, &*TEMPLATE, Point::default(), 0.8).is_ok()
 }
 
 fn detect_player_in_cash_shop(mat: &impl ToInputArray) -> bool {
     /// TODO: Support default ratio
-    static CASH_SHOP: LazyLock<Mat> = LazyLock::new(|| {
-        imgcodecs::imdecode(include_bytes!(env!("CASH_SHOP_TEMPLATE")), IMREAD_GRAYSCALE).unwrap()
-    });
+    static CASH_SHOP: LazyLock<Mat> =
+        LazyLock::new(|| template!("CASH_SHOP_TEMPLATE", IMREAD_GRAYSCALE));
 
     detect_template(mat, &*CASH_SHOP, Point::default(), 0.7).is_ok()
 }
 
 fn detect_player_health_bar(mat: &impl ToInputArray) -> Result<Rect> {
     /// TODO: Support default ratio
-    static HP_START: LazyLock<Mat> = LazyLock::new(|| {
-        imgcodecs::imdecode(include_bytes!(env!("HP_START_TEMPLATE")), IMREAD_GRAYSCALE).unwrap()
-    });
-    static HP_END: LazyLock<Mat> = LazyLock::new(|| {
-        imgcodecs::imdecode(include_bytes!(env!("HP_END_TEMPLATE")), IMREAD_GRAYSCALE).unwrap()
-    });
+    static HP_START: LazyLock<Mat> =
+        LazyLock::new(|| template!("HP_START_TEMPLATE", IMREAD_GRAYSCALE));
+    static HP_END: LazyLock<Mat> = LazyLock::new(|| template!("HP_END_TEMPLATE", IMREAD_GRAYSCALE));
 
     let hp_start = detect_template(mat, &*HP_START, Point::default(), 0.8)?;
     let hp_start_to_edge_x = hp_start.x + hp_start.width;
@@ -1039,23 +1253,12 @@ fn detect_player_current_max_health_bars(
     hp_bar: Rect,
 ) -> Result<(Rect, Rect)> {
     /// TODO: Support default ratio
-    static HP_SEPARATOR_1: LazyLock<Mat> = LazyLock::new(|| {
-        imgcodecs::imdecode(
-            include_bytes!(env!("HP_SEPARATOR_1_TEMPLATE")),
-            IMREAD_GRAYSCALE,
-        )
-        .unwrap()
-    });
-    static HP_SEPARATOR_2: LazyLock<Mat> = LazyLock::new(|| {
-        imgcodecs::imdecode(
-            include_bytes!(env!("HP_SEPARATOR_2_TEMPLATE")),
-            IMREAD_GRAYSCALE,
-        )
-        .unwrap()
-    });
-    static HP_SHIELD: LazyLock<Mat> = LazyLock::new(|| {
-        imgcodecs::imdecode(include_bytes!(env!("HP_SHIELD_TEMPLATE")), IMREAD_GRAYSCALE).unwrap()
-    });
+    static HP_SEPARATOR_1: LazyLock<Mat> =
+        LazyLock::new(|| template!("HP_SEPARATOR_1_TEMPLATE", IMREAD_GRAYSCALE));
+    static HP_SEPARATOR_2: LazyLock<Mat> =
+        LazyLock::new(|| template!("HP_SEPARATOR_2_TEMPLATE", IMREAD_GRAYSCALE));
+    static HP_SHIELD: LazyLock<Mat> =
+        LazyLock::new(|| template!("HP_SHIELD_TEMPLATE", IMREAD_GRAYSCALE));
     static HP_SEPARATOR_TYPE_1: AtomicBool = AtomicBool::new(true);
 
     let hp_separator_type_1 = HP_SEPARATOR_TYPE_1.load(Ordering::Relaxed);
@@ -1144,78 +1347,28 @@ fn detect_player_health(
 
 fn detect_player_buff<T: MatTraitConst + ToInputArray>(mat: &T, kind: BuffKind) -> bool {
     /// TODO: Support default ratio
-    static RUNE_BUFF: LazyLock<Mat> = LazyLock::new(|| {
-        imgcodecs::imdecode(include_bytes!(env!("RUNE_BUFF_TEMPLATE")), IMREAD_GRAYSCALE).unwrap()
-    });
-    static FAMILIAR_BUFF: LazyLock<Mat> = LazyLock::new(|| {
-        imgcodecs::imdecode(
-            include_bytes!(env!("FAMILIAR_BUFF_TEMPLATE")),
-            IMREAD_GRAYSCALE,
-        )
-        .unwrap()
-    });
-    static SAYRAM_ELIXIR_BUFF: LazyLock<Mat> = LazyLock::new(|| {
-        imgcodecs::imdecode(
-            include_bytes!(env!("SAYRAM_ELIXIR_BUFF_TEMPLATE")),
-            IMREAD_GRAYSCALE,
-        )
-        .unwrap()
-    });
-    static AURELIA_ELIXIR_BUFF: LazyLock<Mat> = LazyLock::new(|| {
-        imgcodecs::imdecode(
-            include_bytes!(env!("AURELIA_ELIXIR_BUFF_TEMPLATE")),
-            IMREAD_GRAYSCALE,
-        )
-        .unwrap()
-    });
-    static EXP_COUPON_X3_BUFF: LazyLock<Mat> = LazyLock::new(|| {
-        imgcodecs::imdecode(
-            include_bytes!(env!("EXP_COUPON_X3_BUFF_TEMPLATE")),
-            IMREAD_GRAYSCALE,
-        )
-        .unwrap()
-    });
-    static BONUS_EXP_COUPON_BUFF: LazyLock<Mat> = LazyLock::new(|| {
-        imgcodecs::imdecode(
-            include_bytes!(env!("BONUS_EXP_COUPON_BUFF_TEMPLATE")),
-            IMREAD_GRAYSCALE,
-        )
-        .unwrap()
-    });
-    static LEGION_WEALTH_BUFF: LazyLock<Mat> = LazyLock::new(|| {
-        imgcodecs::imdecode(
-            include_bytes!(env!("LEGION_WEALTH_BUFF_TEMPLATE")),
-            IMREAD_COLOR,
-        )
-        .unwrap()
-    });
-    static LEGION_WEALTH_BUFF_2: LazyLock<Mat> = LazyLock::new(|| {
-        imgcodecs::imdecode(
-            include_bytes!(env!("LEGION_WEALTH_BUFF_2_TEMPLATE")),
-            IMREAD_COLOR,
-        )
-        .unwrap()
-    });
-    static LEGION_LUCK_BUFF: LazyLock<Mat> = LazyLock::new(|| {
-        imgcodecs::imdecode(
-            include_bytes!(env!("LEGION_LUCK_BUFF_TEMPLATE")),
-            IMREAD_COLOR,
-        )
-        .unwrap()
-    });
-    static LEGION_LUCK_BUFF_MASK: LazyLock<Mat> = LazyLock::new(|| {
-        imgcodecs::imdecode(
-            include_bytes!(env!("LEGION_LUCK_BUFF_MASK_TEMPLATE")),
-            IMREAD_GRAYSCALE,
-        )
-        .unwrap()
-    });
+    static RUNE_BUFF: LazyLock<Mat> =
+        LazyLock::new(|| template!("RUNE_BUFF_TEMPLATE", IMREAD_GRAYSCALE));
+    static FAMILIAR_BUFF: LazyLock<Mat> =
+        LazyLock::new(|| template!("FAMILIAR_BUFF_TEMPLATE", IMREAD_GRAYSCALE));
+    static SAYRAM_ELIXIR_BUFF: LazyLock<Mat> =
+        LazyLock::new(|| template!("SAYRAM_ELIXIR_BUFF_TEMPLATE", IMREAD_GRAYSCALE));
+    static AURELIA_ELIXIR_BUFF: LazyLock<Mat> =
+        LazyLock::new(|| template!("AURELIA_ELIXIR_BUFF_TEMPLATE", IMREAD_GRAYSCALE));
+    static EXP_COUPON_X3_BUFF: LazyLock<Mat> =
+        LazyLock::new(|| template!("EXP_COUPON_X3_BUFF_TEMPLATE", IMREAD_GRAYSCALE));
+    static BONUS_EXP_COUPON_BUFF: LazyLock<Mat> =
+        LazyLock::new(|| template!("BONUS_EXP_COUPON_BUFF_TEMPLATE", IMREAD_GRAYSCALE));
+    static LEGION_WEALTH_BUFF: LazyLock<Mat> =
+        LazyLock::new(|| template!("LEGION_WEALTH_BUFF_TEMPLATE", IMREAD_COLOR));
+    static LEGION_WEALTH_BUFF_2: LazyLock<Mat> =
+        LazyLock::new(|| template!("LEGION_WEALTH_BUFF_2_TEMPLATE", IMREAD_COLOR));
+    static LEGION_LUCK_BUFF: LazyLock<Mat> =
+        LazyLock::new(|| template!("LEGION_LUCK_BUFF_TEMPLATE", IMREAD_COLOR));
+    static LEGION_LUCK_BUFF_MASK: LazyLock<Mat> =
+        LazyLock::new(|| template!("LEGION_LUCK_BUFF_MASK_TEMPLATE", IMREAD_GRAYSCALE));
     static WEALTH_EXP_POTION_MASK: LazyLock<Mat> = LazyLock::new(|| {
-        let mut mat = imgcodecs::imdecode(
-            include_bytes!(env!("WEALTH_EXP_POTION_MASK_TEMPLATE")),
-            IMREAD_GRAYSCALE,
-        )
-        .unwrap();
+        let mut mat = template!("WEALTH_EXP_POTION_MASK_TEMPLATE", IMREAD_GRAYSCALE);
         unsafe {
             mat.modify_inplace(|mat, mat_mut| {
                 mat.convert_to(mat_mut, CV_32FC3, 1.0 / 255.0, 0.0).unwrap();
@@ -1223,48 +1376,18 @@ fn detect_player_buff<T: MatTraitConst + ToInputArray>(mat: &T, kind: BuffKind)
         }
         mat
     });
-    static WEALTH_ACQUISITION_POTION_BUFF: LazyLock<Mat> = LazyLock::new(|| {
-        imgcodecs::imdecode(
-            include_bytes!(env!("WEALTH_ACQUISITION_POTION_BUFF_TEMPLATE")),
-            IMREAD_COLOR,
-        )
-        .unwrap()
-    });
-    static EXP_ACCUMULATION_POTION_BUFF: LazyLock<Mat> = LazyLock::new(|| {
-        imgcodecs::imdecode(
-            include_bytes!(env!("EXP_ACCUMULATION_POTION_BUFF_TEMPLATE")),
-            IMREAD_COLOR,
-        )
-        .unwrap()
-    });
-    static EXTREME_RED_POTION_BUFF: LazyLock<Mat> = LazyLock::new(|| {
-        imgcodecs::imdecode(
-            include_bytes!(env!("EXTREME_RED_POTION_BUFF_TEMPLATE")),
-            IMREAD_COLOR,
-        )
-        .unwrap()
-    });
-    static EXTREME_BLUE_POTION_BUFF: LazyLock<Mat> = LazyLock::new(|| {
-        imgcodecs::imdecode(
-            include_bytes!(env!("EXTREME_BLUE_POTION_BUFF_TEMPLATE")),
-            IMREAD_COLOR,
-        )
-        .unwrap()
-    });
-    static EXTREME_GREEN_POTION_BUFF: LazyLock<Mat> = LazyLock::new(|| {
-        imgcodecs::imdecode(
-            include_bytes!(env!("EXTREME_GREEN_POTION_BUFF_TEMPLATE")),
-            IMREAD_COLOR,
-        )
-        .unwrap()
-    });
-    static EXTREME_GOLD_POTION_BUFF: LazyLock<Mat> = LazyLock::new(|| {
-        imgcodecs::imdecode(
-            include_bytes!(env!("EXTREME_GOLD_POTION_BUFF_TEMPLATE")),
-            IMREAD_COLOR,
-        )
-        .unwrap()
-    });
+    static WEALTH_ACQUISITION_POTION_BUFF: LazyLock<Mat> =
+        LazyLock::new(|| template!("WEALTH_ACQUISITION_POTION_BUFF_TEMPLATE", IMREAD_COLOR));
+    static EXP_ACCUMULATION_POTION_BUFF: LazyLock<Mat> =
+        LazyLock::new(|| template!("EXP_ACCUMULATION_POTION_BUFF_TEMPLATE", IMREAD_COLOR));
+    static EXTREME_RED_POTION_BUFF: LazyLock<Mat> =
+        LazyLock::new(|| template!("EXTREME_RED_POTION_BUFF_TEMPLATE", IMREAD_COLOR));
+    static EXTREME_BLUE_POTION_BUFF: LazyLock<Mat> =
+        LazyLock::new(|| template!("EXTREME_BLUE_POTION_BUFF_TEMPLATE", IMREAD_COLOR));
+    static EXTREME_GREEN_POTION_BUFF: LazyLock<Mat> =
+        LazyLock::new(|| template!("EXTREME_GREEN_POTION_BUFF_TEMPLATE", IMREAD_COLOR));
+    static EXTREME_GOLD_POTION_BUFF: LazyLock<Mat> =
+        LazyLock::new(|| template!("EXTREME_GOLD_POTION_BUFF_TEMPLATE", IMREAD_COLOR));
 
     let threshold = match kind {
         BuffKind::AureliaElixir => 0.8,
@@ -1366,13 +1489,6 @@ fn detect_player_buff<T: MatTraitConst + ToInputArray>(mat: &T, kind: BuffKind)
 }
 
 fn detect_rune_arrows_with_scores_regions(mat: &impl MatTraitConst) -> Vec<(Rect, KeyKind, f32)> {
-    static RUNE_MODEL: LazyLock<Mutex<Session>> = LazyLock::new(|| {
-        Mutex::new(
-            build_session(include_bytes!(env!("RUNE_MODEL")))
-                .expect("build rune detection session successfully"),
-        )
-    });
-
     fn map_arrow(pred: &[f32]) -> KeyKind {
         match pred[5] as i32 {
             0 => KeyKind::Up,
@@ -1386,12 +1502,16 @@ fn detect_rune_arrows_with_scores_regions(mat: &impl MatTraitConst) -> Vec<(Rect
     let size = mat.size().unwrap();
     let (mat_in, w_ratio, h_ratio, left, top) = preprocess_for_yolo(mat);
     let mut model = RUNE_MODEL.lock().unwrap();
-    let result = model.run([norm_rgb_to_input_value(&mat_in)]).unwrap();
+    let threshold = model.threshold;
+    let result = model
+        .session()
+        .run([norm_rgb_to_input_value(&mat_in)])
+        .unwrap();
     let mat_out = from_output_value(&result);
     let mut vec = (0..mat_out.rows())
         // SAFETY: 0..outputs.rows() is within Mat bounds
         .map(|i| unsafe { mat_out.at_row_unchecked::<f32>(i).unwrap() })
-        .filter(|pred| pred[4] >= 0.2)
+        .filter(|pred| pred[4] >= threshold)
         .map(|pred| {
             (
                 remap_from_yolo(pred, size, w_ratio, h_ratio, left, top),
@@ -1509,6 +1629,7 @@ fn detect_rune_arrows(
     }
 
     // Normal detection path
+    let full_mat = mat;
     let mut mat = mat.roi(rune_region)?;
     if calibrating.spin_arrows.is_some() {
         //  Set all spin arrow regions to black pixels
@@ -1551,10 +1672,42 @@ fn detect_rune_arrows(
     }
 
     if result.len() == MAX_ARROWS {
-        Ok(ArrowsState::Complete(extract_rune_arrows_to_slice(result)))
-    } else {
-        Err(anyhow!("no rune arrow detected"))
+        return Ok(ArrowsState::Complete(extract_rune_arrows_to_slice(result)));
+    }
+
+    if calibrating.enlarged_crop_fallback {
+        debug!(target: "rune", "retrying with enlarged and denoised crop");
+        let result = detect_rune_arrows_enlarged_denoised(full_mat, rune_region)?;
+        if result.len() == MAX_ARROWS {
+            return Ok(ArrowsState::Complete(extract_rune_arrows_to_slice(result)));
+        }
     }
+
+    Err(anyhow!("no rune arrow detected"))
+}
+
+/// Detects the rune arrows using an enlarged and denoised crop of `rune_region`.
+///
+/// This is a fallback for when the arrows are partially outside of the detected region or
+/// the region is too noisy (e.g. skill effects) for the normal detection.
+fn detect_rune_arrows_enlarged_denoised(
+    mat: &impl MatTraitConst,
+    rune_region: Rect,
+) -> Result<Vec<(Rect, KeyKind)>> {
+    const RUNE_REGION_PAD: i32 = 20;
+    const SCORE_THRESHOLD: f32 = 0.6;
+
+    let x = (rune_region.x - RUNE_REGION_PAD).max(0);
+    let y = (rune_region.y - RUNE_REGION_PAD).max(0);
+    let width = (rune_region.x + rune_region.width + RUNE_REGION_PAD).min(mat.cols()) - x;
+    let height = (rune_region.y + rune_region.height + RUNE_REGION_PAD).min(mat.rows()) - y;
+    let mut denoised = Mat::default();
+    median_blur(&mat.roi(Rect::new(x, y, width, height))?, &mut denoised, 3)?;
+
+    Ok(detect_rune_arrows_with_scores_regions(&denoised)
+        .into_iter()
+        .filter_map(|(rect, arrow, score)| (score >= SCORE_THRESHOLD).then_some((rect, arrow)))
+        .collect())
 }
 
 fn calibrate_for_spin_arrows(
@@ -1815,13 +1968,8 @@ fn extract_rune_arrows_to_slice(vec: Vec<(Rect, KeyKind)>) -> [KeyKind; MAX_ARRO
 
 fn detect_erda_shower(mat: &impl MatTraitConst) -> Result<Rect> {
     /// TODO: Support default ratio
-    static ERDA_SHOWER: LazyLock<Mat> = LazyLock::new(|| {
-        imgcodecs::imdecode(
-            include_bytes!(env!("ERDA_SHOWER_TEMPLATE")),
-            IMREAD_GRAYSCALE,
-        )
-        .unwrap()
-    });
+    static ERDA_SHOWER: LazyLock<Mat> =
+        LazyLock::new(|| template!("ERDA_SHOWER_TEMPLATE", IMREAD_GRAYSCALE));
 
     let size = mat.size().unwrap();
     // crop to bottom right of the image for skill bar
@@ -1833,62 +1981,32 @@ fn detect_erda_shower(mat: &impl MatTraitConst) -> Result<Rect> {
 }
 
 fn detect_familiar_save_button(mat: &impl ToInputArray) -> Result<Rect> {
-    static TEMPLATE: LazyLock<Mat> = LazyLock::new(|| {
-        imgcodecs::imdecode(
-            include_bytes!(env!("FAMILIAR_BUTTON_SAVE_TEMPLATE")),
-            IMREAD_COLOR,
-        )
-        .unwrap()
-    });
+    static TEMPLATE: LazyLock<Mat> =
+        LazyLock::new(|| template!("FAMILIAR_BUTTON_SAVE_TEMPLATE", IMREAD_COLOR));
 
     detect_template(mat, &*TEMPLATE, Point::default(), 0.75)
 }
 
 fn detect_familiar_setup_button(mat: &impl ToInputArray) -> Result<Rect> {
-    static TEMPLATE: LazyLock<Mat> = LazyLock::new(|| {
-        imgcodecs::imdecode(
-            include_bytes!(env!("FAMILIAR_BUTTON_SETUP_TEMPLATE")),
-            IMREAD_COLOR,
-        )
-        .unwrap()
-    });
+    static TEMPLATE: LazyLock<Mat> =
+        LazyLock::new(|| template!("FAMILIAR_BUTTON_SETUP_TEMPLATE", IMREAD_COLOR));
 
     detect_template(mat, &*TEMPLATE, Point::default(), 0.75)
 }
 
 fn detect_familiar_level_button(mat: &impl ToInputArray) -> Result<Rect> {
-    static TEMPLATE: LazyLock<Mat> = LazyLock::new(|| {
-        imgcodecs::imdecode(
-            include_bytes!(env!("FAMILIAR_BUTTON_LEVEL_TEMPLATE")),
-            IMREAD_COLOR,
-        )
-        .unwrap()
-    });
+    static TEMPLATE: LazyLock<Mat> =
+        LazyLock::new(|| template!("FAMILIAR_BUTTON_LEVEL_TEMPLATE", IMREAD_COLOR));
 
     detect_template(mat, &*TEMPLATE, Point::default(), 0.75)
 }
 
-static FAMILIAR_SLOT_FREE: LazyLock<Mat> = LazyLock::new(|| {
-    imgcodecs::imdecode(
-        include_bytes!(env!("FAMILIAR_SLOT_FREE_TEMPLATE")),
-        IMREAD_COLOR,
-    )
-    .unwrap()
-});
-static FAMILIAR_SLOT_OCCUPIED: LazyLock<Mat> = LazyLock::new(|| {
-    imgcodecs::imdecode(
-        include_bytes!(env!("FAMILIAR_SLOT_OCCUPIED_TEMPLATE")),
-        IMREAD_COLOR,
-    )
-    .unwrap()
-});
-static FAMILIAR_SLOT_OCCUPIED_MASK: LazyLock<Mat> = LazyLock::new(|| {
-    imgcodecs::imdecode(
-        include_bytes!(env!("FAMILIAR_SLOT_OCCUPIED_MASK_TEMPLATE")),
-        IMREAD_GRAYSCALE,
-    )
-    .unwrap()
-});
+static FAMILIAR_SLOT_FREE: LazyLock<Mat> =
+    LazyLock::new(|| template!("FAMILIAR_SLOT_FREE_TEMPLATE", IMREAD_COLOR));
+static FAMILIAR_SLOT_OCCUPIED: LazyLock<Mat> =
+    LazyLock::new(|| template!("FAMILIAR_SLOT_OCCUPIED_TEMPLATE", IMREAD_COLOR));
+static FAMILIAR_SLOT_OCCUPIED_MASK: LazyLock<Mat> =
+    LazyLock::new(|| template!("FAMILIAR_SLOT_OCCUPIED_MASK_TEMPLATE", IMREAD_GRAYSCALE));
 
 fn detect_familiar_slots(mat: &impl ToInputArray) -> Vec<(Rect, bool)> {
     let first = detect_template_multiple(
@@ -1925,20 +2043,10 @@ fn detect_familiar_slot_is_free(mat: &impl ToInputArray) -> bool {
 }
 
 fn detect_familiar_hover_level<T: ToInputArray + MatTraitConst>(mat: &T) -> Result<FamiliarLevel> {
-    static TEMPLATE: LazyLock<Mat> = LazyLock::new(|| {
-        imgcodecs::imdecode(
-            include_bytes!(env!("FAMILIAR_LEVEL_5_TEMPLATE")),
-            IMREAD_COLOR,
-        )
-        .unwrap()
-    });
-    static TEMPLATE_MASK: LazyLock<Mat> = LazyLock::new(|| {
-        imgcodecs::imdecode(
-            include_bytes!(env!("FAMILIAR_LEVEL_5_MASK_TEMPLATE")),
-            IMREAD_GRAYSCALE,
-        )
-        .unwrap()
-    });
+    static TEMPLATE: LazyLock<Mat> =
+        LazyLock::new(|| template!("FAMILIAR_LEVEL_5_TEMPLATE", IMREAD_COLOR));
+    static TEMPLATE_MASK: LazyLock<Mat> =
+        LazyLock::new(|| template!("FAMILIAR_LEVEL_5_MASK_TEMPLATE", IMREAD_GRAYSCALE));
 
     let level_bbox = detect_template(mat, &*TEMPLATE, Point::default(), 0.75)?;
     let level = mat.roi(level_bbox)?;
@@ -1950,27 +2058,12 @@ fn detect_familiar_hover_level<T: ToInputArray + MatTraitConst>(mat: &T) -> Resu
 }
 
 fn detect_familiar_cards<T: MatTraitConst + ToInputArray>(mat: &T) -> Vec<(Rect, FamiliarRank)> {
-    static TEMPLATE_RARE: LazyLock<Mat> = LazyLock::new(|| {
-        imgcodecs::imdecode(
-            include_bytes!(env!("FAMILIAR_CARD_RARE_TEMPLATE")),
-            IMREAD_COLOR,
-        )
-        .unwrap()
-    });
-    static TEMPLATE_EPIC: LazyLock<Mat> = LazyLock::new(|| {
-        imgcodecs::imdecode(
-            include_bytes!(env!("FAMILIAR_CARD_EPIC_TEMPLATE")),
-            IMREAD_COLOR,
-        )
-        .unwrap()
-    });
-    static TEMPLATE_MASK: LazyLock<Mat> = LazyLock::new(|| {
-        imgcodecs::imdecode(
-            include_bytes!(env!("FAMILIAR_CARD_MASK_TEMPLATE")),
-            IMREAD_GRAYSCALE,
-        )
-        .unwrap()
-    });
+    static TEMPLATE_RARE: LazyLock<Mat> =
+        LazyLock::new(|| template!("FAMILIAR_CARD_RARE_TEMPLATE", IMREAD_COLOR));
+    static TEMPLATE_EPIC: LazyLock<Mat> =
+        LazyLock::new(|| template!("FAMILIAR_CARD_EPIC_TEMPLATE", IMREAD_COLOR));
+    static TEMPLATE_MASK: LazyLock<Mat> =
+        LazyLock::new(|| template!("FAMILIAR_CARD_MASK_TEMPLATE", IMREAD_GRAYSCALE));
 
     #[inline]
     fn match_template_score(
@@ -2024,65 +2117,347 @@ fn detect_familiar_cards<T: MatTraitConst + ToInputArray>(mat: &T) -> Vec<(Rect,
 }
 
 fn detect_familiar_scrollbar(mat: &impl ToInputArray) -> Result<Rect> {
-    static TEMPLATE: LazyLock<Mat> = LazyLock::new(|| {
-        imgcodecs::imdecode(
-            include_bytes!(env!("FAMILIAR_SCROLLBAR_TEMPLATE")),
-            IMREAD_GRAYSCALE,
-        )
-        .unwrap()
-    });
+    static TEMPLATE: LazyLock<Mat> =
+        LazyLock::new(|| template!("FAMILIAR_SCROLLBAR_TEMPLATE", IMREAD_GRAYSCALE));
 
     detect_template(mat, &*TEMPLATE, Point::default(), 0.6)
 }
 
 fn detect_familiar_menu_opened(mat: &impl ToInputArray) -> bool {
-    static TEMPLATE: LazyLock<Mat> = LazyLock::new(|| {
-        imgcodecs::imdecode(
-            include_bytes!(env!("FAMILIAR_MENU_TEMPLATE")),
-            IMREAD_GRAYSCALE,
-        )
-        .unwrap()
-    });
+    static TEMPLATE: LazyLock<Mat> =
+        LazyLock::new(|| template!("FAMILIAR_MENU_TEMPLATE", IMREAD_GRAYSCALE));
 
     detect_template(mat, &*TEMPLATE, Point::default(), 0.75).is_ok()
 }
 
 fn detect_familiar_essence_depleted(mat: &impl ToInputArray) -> bool {
-    static TEMPLATE: LazyLock<Mat> = LazyLock::new(|| {
-        imgcodecs::imdecode(
-            include_bytes!(env!("FAMILIAR_ESSENCE_DEPLETE_TEMPLATE")),
-            IMREAD_GRAYSCALE,
-        )
-        .unwrap()
-    });
+    static TEMPLATE: LazyLock<Mat> =
+        LazyLock::new(|| template!("FAMILIAR_ESSENCE_DEPLETE_TEMPLATE", IMREAD_GRAYSCALE));
 
     detect_template(mat, &*TEMPLATE, Point::default(), 0.8).is_ok()
 }
 
+fn detect_familiar_essence_gauge(mat: &Mat) -> Result<f32> {
+    /// The brightness ratio of a row compared to the template below which it is considered
+    /// drained.
+    const DRAINED_ROW_RATIO: f64 = 0.6;
+
+    static TEMPLATE: LazyLock<Mat> =
+        LazyLock::new(|| template!("FAMILIAR_BUFF_TEMPLATE", IMREAD_GRAYSCALE));
+
+    // The familiar buff icon is dimmed from the top as the essence drains
+    let bbox = detect_template(mat, &*TEMPLATE, Point::default(), 0.75)?;
+    let icon = mat.roi(bbox)?;
+    let mut filled_rows = 0;
+    for row in 0..bbox.height {
+        let row_bbox = Rect::new(0, row, bbox.width, 1);
+        let icon_mean = mean_def(&icon.roi(row_bbox)?)?[0];
+        let template_mean = mean_def(&TEMPLATE.roi(row_bbox)?)?[0];
+        if template_mean <= 0.0 || icon_mean / template_mean >= DRAINED_ROW_RATIO {
+            filled_rows += 1;
+        }
+    }
+
+    Ok(filled_rows as f32 / bbox.height as f32)
+}
+
 fn detect_change_channel_menu_opened(mat: &impl ToInputArray) -> bool {
-    static TEMPLATE: LazyLock<Mat> = LazyLock::new(|| {
-        imgcodecs::imdecode(
-            include_bytes!(env!("CHANGE_CHANNEL_MENU_TEMPLATE")),
-            IMREAD_GRAYSCALE,
-        )
-        .unwrap()
-    });
+    static TEMPLATE: LazyLock<Mat> =
+        LazyLock::new(|| template!("CHANGE_CHANNEL_MENU_TEMPLATE", IMREAD_GRAYSCALE));
 
     detect_template(mat, &*TEMPLATE, Point::default(), 0.75).is_ok()
 }
 
 fn detect_arrow_spam_open(mat: &impl ToInputArray) -> bool {
-    static TEMPLATE: LazyLock<Mat> = LazyLock::new(|| {
-        imgcodecs::imdecode(
-            include_bytes!(env!("DETECT_ARROW_SPAM_TEMPLATE")),
-            IMREAD_GRAYSCALE,
-        )
-        .unwrap()
-    });
+    static TEMPLATE: LazyLock<Mat> =
+        LazyLock::new(|| template!("DETECT_ARROW_SPAM_TEMPLATE", IMREAD_GRAYSCALE));
 
     detect_template(mat, &*TEMPLATE, Point::default(), 0.75).is_ok()
 }
 
+fn detect_maintenance_countdown(mat: &impl MatTraitConst) -> Result<Duration> {
+    let size = mat.size().unwrap();
+    // crop to the top of the image where the server notice scrolls
+    let crop_bbox = Rect::new(0, 0, size.width, size.height / 20);
+    let banner = mat.roi(crop_bbox).unwrap();
+    let (banner_in, w_ratio, h_ratio) = preprocess_for_text_bboxes(&banner);
+    let bboxes = extract_text_bboxes(&banner_in, w_ratio, h_ratio, 0, 0);
+    let words = extract_texts(mat, &bboxes)
+        .into_iter()
+        .map(|word| word.to_lowercase())
+        .collect::<Vec<_>>();
+    if !words.iter().any(|word| word.contains("maintenance")) {
+        bail!("maintenance countdown not found");
+    }
+
+    // The recognized minutes can either be separated (e.g. `5 minutes`) or
+    // merged (e.g. `5minutes`) with its unit
+    words
+        .iter()
+        .enumerate()
+        .find_map(|(i, word)| {
+            let digits_end = word
+                .find(|c: char| !c.is_ascii_digit())
+                .unwrap_or(word.len());
+            let minutes = word[..digits_end].parse::<u64>().ok()?;
+            let unit = if digits_end < word.len() {
+                &word[digits_end..]
+            } else {
+                words.get(i + 1)?.as_str()
+            };
+            unit.starts_with("min")
+                .then_some(Duration::from_secs(minutes * 60))
+        })
+        .ok_or(anyhow!("cannot detect maintenance countdown minutes"))
+}
+
+fn detect_exp_percent(mat: &impl MatTraitConst) -> Result<f32> {
+    let size = mat.size().unwrap();
+    // crop to the bottom of the image where the EXP bar is
+    let crop_height = size.height / 25;
+    let crop_bbox = Rect::new(0, size.height - crop_height, size.width, crop_height);
+    let bar = mat.roi(crop_bbox).unwrap();
+    let (bar_in, w_ratio, h_ratio) = preprocess_for_text_bboxes(&bar);
+    let bboxes = extract_text_bboxes(&bar_in, w_ratio, h_ratio, crop_bbox.x, crop_bbox.y);
+
+    // The percentage is recognized with its surrounding brackets (e.g. `[12.345%]`)
+    extract_texts(mat, &bboxes)
+        .into_iter()
+        .find_map(|word| {
+            let end = word.find('%')?;
+            let start = word[..end]
+                .rfind(|c: char| !c.is_ascii_digit() && c != '.')
+                .map(|index| index + 1)
+                .unwrap_or_default();
+            word[start..end]
+                .parse::<f32>()
+                .ok()
+                .filter(|percent| (0.0..=100.0).contains(percent))
+        })
+        .ok_or(anyhow!("cannot detect EXP percentage"))
+}
+
+fn detect_mesos(mat: &impl MatTraitConst, region: Rect) -> Result<u64> {
+    let size = mat.size().unwrap();
+    let region = region & Rect::new(0, 0, size.width, size.height);
+    if region.empty() {
+        bail!("meso counter region is outside of the image");
+    }
+    let counter = mat.roi(region).unwrap();
+    let (counter_in, w_ratio, h_ratio) = preprocess_for_text_bboxes(&counter);
+    let bboxes = extract_text_bboxes(&counter_in, w_ratio, h_ratio, region.x, region.y);
+
+    // The counter is separated by commas (e.g. `1,234,567`) and may be recognized as
+    // multiple words, so the longest number is used
+    extract_texts(mat, &bboxes)
+        .into_iter()
+        .map(|word| word.replace(',', ""))
+        .filter(|word| !word.is_empty() && word.chars().all(|c| c.is_ascii_digit()))
+        .max_by_key(|word| word.len())
+        .and_then(|word| word.parse::<u64>().ok())
+        .ok_or(anyhow!("cannot detect meso counter"))
+}
+
+fn detect_inventory_full(mat: &impl MatTraitConst) -> bool {
+    let size = mat.size().unwrap();
+    // crop to the bottom right of the image above the quick slots where the pick up
+    // messages are shown
+    let crop_x = size.width / 4;
+    let crop_y = size.height / 4;
+    let crop_bbox = Rect::new(
+        size.width - crop_x,
+        size.height - crop_y - size.height / 10,
+        crop_x,
+        crop_y,
+    );
+    let messages = mat.roi(crop_bbox).unwrap();
+    let (messages_in, w_ratio, h_ratio) = preprocess_for_text_bboxes(&messages);
+    let bboxes = extract_text_bboxes(&messages_in, w_ratio, h_ratio, crop_bbox.x, crop_bbox.y);
+    let words = extract_texts(mat, &bboxes)
+        .into_iter()
+        .map(|word| word.to_lowercase())
+        .collect::<Vec<_>>();
+
+    // Either `Your inventory is full` or `You cannot pick up any more items`
+    let has_word = |prefix: &str| words.iter().any(|word| word.starts_with(prefix));
+    (has_word("inventory") && has_word("full")) || (has_word("pick") && has_word("more"))
+}
+
+fn detect_whisper(mat: &impl MatTraitConst) -> Result<String> {
+    let words = extract_chat_box_text_bboxes(mat)
+        .into_iter()
+        .filter_map(|bbox| {
+            extract_texts(mat, &[bbox])
+                .into_iter()
+                .next()
+                .map(|word| (bbox, word))
+        })
+        .collect::<Vec<_>>();
+
+    // A received whisper is shown as `Name >> message` so the latest whisper is the bottom
+    // most line containing `>>`
+    let (marker, _) = words
+        .iter()
+        .filter(|(_, word)| word.contains(">>"))
+        .max_by_key(|(bbox, _)| bbox.y)
+        .ok_or(anyhow!("whisper not found"))?;
+    let mut line = words
+        .iter()
+        .filter(|(bbox, _)| {
+            let center_y = bbox.y + bbox.height / 2;
+            (marker.y..marker.y + marker.height).contains(&center_y)
+        })
+        .collect::<Vec<_>>();
+    line.sort_by_key(|(bbox, _)| bbox.x);
+
+    Ok(line
+        .into_iter()
+        .map(|(_, word)| word.as_str())
+        .collect::<Vec<_>>()
+        .join(" "))
+}
+
+fn detect_gm_chat(mat: &impl MatTraitConst) -> bool {
+    let words = extract_texts(mat, &extract_chat_box_text_bboxes(mat))
+        .into_iter()
+        .map(|word| word.to_lowercase())
+        .collect::<Vec<_>>();
+
+    // Only the explicit GM tags are matched since `gm` alone is commonly used by players
+    words.iter().enumerate().any(|(i, word)| {
+        word.starts_with("[gm")
+            || word.starts_with("<gm")
+            || word.contains("gamemaster")
+            || (word == "game"
+                && words
+                    .get(i + 1)
+                    .is_some_and(|next| next.starts_with("master")))
+    })
+}
+
+/// Extracts the text bounding boxes inside the chat box at the bottom left of the image.
+fn extract_chat_box_text_bboxes(mat: &impl MatTraitConst) -> Vec<Rect> {
+    let size = mat.size().unwrap();
+    // crop to the bottom left of the image where the chat box is, excluding the EXP bar
+    let crop_x = size.width / 3;
+    let crop_y = size.height / 4;
+    let crop_bbox = Rect::new(0, size.height - crop_y, crop_x, crop_y - size.height / 25);
+    let chat = mat.roi(crop_bbox).unwrap();
+    let (chat_in, w_ratio, h_ratio) = preprocess_for_text_bboxes(&chat);
+    extract_text_bboxes(&chat_in, w_ratio, h_ratio, crop_bbox.x, crop_bbox.y)
+}
+
+fn detect_lie_detector(mat: &impl MatTraitConst) -> bool {
+    let size = mat.size().unwrap();
+    // crop to the center of the image where the lie detector window opens
+    let crop_x = size.width / 3;
+    let crop_y = size.height / 3;
+    let crop_bbox = Rect::new(crop_x, crop_y, crop_x, crop_y);
+    let window = mat.roi(crop_bbox).unwrap();
+    let (window_in, w_ratio, h_ratio) = preprocess_for_text_bboxes(&window);
+    let bboxes = extract_text_bboxes(&window_in, w_ratio, h_ratio, crop_bbox.x, crop_bbox.y);
+    let words = extract_texts(mat, &bboxes)
+        .into_iter()
+        .map(|word| word.to_lowercase())
+        .collect::<Vec<_>>();
+
+    // The window title can be recognized as separated (e.g. `lie detector`) or
+    // merged (e.g. `liedetector`)
+    words
+        .iter()
+        .any(|word| word.contains("liedetector") || word.contains("captcha"))
+        || (words.iter().any(|word| word == "lie")
+            && words.iter().any(|word| word.starts_with("detector")))
+}
+
+fn detect_boss_health_bar(mat: &impl MatTraitConst) -> bool {
+    const MIN_WIDTH_RATIO: i32 = 10;
+    const MIN_ASPECT_RATIO: i32 = 10;
+
+    let size = mat.size().unwrap();
+    // crop to the top of the image where the boss HP bar is
+    let crop_bbox = Rect::new(0, 0, size.width, size.height / 8);
+    let bar = to_hsv(&mat.roi(crop_bbox).unwrap());
+
+    // The red hue wraps around so both ends of the hue range are thresholded
+    let mut low_red = Mat::default();
+    let mut high_red = Mat::default();
+    let mut red = Mat::default();
+    in_range(
+        &bar,
+        &Scalar::new(0.0, 150.0, 120.0, 0.0),
+        &Scalar::new(12.0, 255.0, 255.0, 0.0),
+        &mut low_red,
+    )
+    .unwrap();
+    in_range(
+        &bar,
+        &Scalar::new(240.0, 150.0, 120.0, 0.0),
+        &Scalar::new(255.0, 255.0, 255.0, 0.0),
+        &mut high_red,
+    )
+    .unwrap();
+    bitwise_or_def(&low_red, &high_red, &mut red).unwrap();
+
+    let mut contours = Vector::<Vector<Point>>::new();
+    find_contours_def(&red, &mut contours, RETR_EXTERNAL, CHAIN_APPROX_SIMPLE).unwrap();
+
+    // The bar is a long and thin red strip that shrinks as the boss is damaged, so only a small
+    // part of the screen width is required
+    contours
+        .into_iter()
+        .map(|contour| bounding_rect(&contour).unwrap())
+        .any(|bbox| {
+            bbox.width * MIN_WIDTH_RATIO >= size.width
+                && bbox.width >= bbox.height.max(1) * MIN_ASPECT_RATIO
+        })
+}
+
+fn detect_damage_numbers(mat: &impl MatTraitConst) -> bool {
+    const MIN_DIGITS_COUNT: usize = 3;
+
+    let size = mat.size().unwrap();
+    // crop out the top and bottom of the image where most of the UIs are
+    let crop_y = size.height / 8;
+    let crop_bbox = Rect::new(0, crop_y, size.width, size.height - crop_y * 2);
+    let field = to_hsv(&mat.roi(crop_bbox).unwrap());
+
+    // Normal damage numbers are orange to yellow and critical ones are pink to purple
+    let mut normal = Mat::default();
+    let mut critical = Mat::default();
+    let mut damage = Mat::default();
+    in_range(
+        &field,
+        &Scalar::new(15.0, 180.0, 200.0, 0.0),
+        &Scalar::new(45.0, 255.0, 255.0, 0.0),
+        &mut normal,
+    )
+    .unwrap();
+    in_range(
+        &field,
+        &Scalar::new(205.0, 120.0, 200.0, 0.0),
+        &Scalar::new(235.0, 255.0, 255.0, 0.0),
+        &mut critical,
+    )
+    .unwrap();
+    bitwise_or_def(&normal, &critical, &mut damage).unwrap();
+
+    let mut contours = Vector::<Vector<Point>>::new();
+    find_contours_def(&damage, &mut contours, RETR_EXTERNAL, CHAIN_APPROX_SIMPLE).unwrap();
+
+    // Each digit is a separated blob that is taller than wide
+    let min_height = size.height / 40;
+    let max_height = size.height / 10;
+    contours
+        .into_iter()
+        .map(|contour| bounding_rect(&contour).unwrap())
+        .filter(|bbox| {
+            (min_height..=max_height).contains(&bbox.height) && bbox.width <= bbox.height
+        })
+        .count()
+        >= MIN_DIGITS_COUNT
+}
+
 /// Detects a single match from `template` with the given BGR image `Mat`.
 #[inline]
 fn detect_template<T: ToInputArray + MatTraitConst>(
@@ -2121,14 +2496,16 @@ fn detect_template_multiple<T: ToInputArray + MatTraitConst>(
     threshold: f64,
 ) -> Vec<Result<(Rect, f64)>> {
     #[inline]
-    fn clear_result(result: &mut Mat, rect: Rect, offset: Point) -> Result<()> {
-        let x = (rect.x - offset.x).max(0);
-        let y = (rect.y - offset.y).max(0);
+    fn clear_result(result: &mut Mat, rect: Rect, offset: Point, scale: f64) -> Result<()> {
+        let x = (((rect.x - offset.x) as f64 * scale) as i32).max(0);
+        let y = (((rect.y - offset.y) as f64 * scale) as i32).max(0);
+        let width = (rect.width as f64 * scale).ceil() as i32;
+        let height = (rect.height as f64 * scale).ceil() as i32;
         let roi_rect = Rect::new(
             x,
             y,
-            rect.width.min(result.cols() - x),
-            rect.height.min(result.rows() - y),
+            width.min(result.cols() - x),
+            height.min(result.rows() - y),
         );
         result.roi_mut(roi_rect)?.set_scalar(Scalar::default())?;
         Ok(())
@@ -2139,6 +2516,7 @@ fn detect_template_multiple<T: ToInputArray + MatTraitConst>(
         result: &Mat,
         offset: Point,
         template_size: Size,
+        scale: f64,
         threshold: f64,
     ) -> (Rect, Result<(Rect, f64)>) {
         let mut score = 0f64;
@@ -2152,6 +2530,11 @@ fn detect_template_multiple<T: ToInputArray + MatTraitConst>(
             &no_array(),
         )
         .unwrap();
+        // Scales the location on the downscaled frame back to the full size frame
+        let loc = Point::new(
+            (loc.x as f64 / scale).round() as i32,
+            (loc.y as f64 / scale).round() as i32,
+        );
         let tl = loc + offset;
         let br = tl + Point::from_size(template_size);
         let rect = Rect::from_points(tl, br);
@@ -2162,24 +2545,30 @@ fn detect_template_multiple<T: ToInputArray + MatTraitConst>(
         }
     }
 
+    let template_size = template.size().unwrap();
+    let scale = template_match_scale(template_size);
     let mut result = Mat::default();
-    if let Err(err) = match_template(mat, template, &mut result, TM_CCOEFF_NORMED, &mask) {
+    let matched = if scale < 1.0 {
+        match_template_downscaled(mat, template, &mask, scale, &mut result)
+    } else {
+        match_template(mat, template, &mut result, TM_CCOEFF_NORMED, &mask)
+    };
+    if let Err(err) = matched {
         error!(target: "detect", "template detection error {err}");
         return vec![];
     }
 
-    let template_size = template.size().unwrap();
     let max_matches = max_matches.max(1);
     if max_matches == 1 {
         // Weird INFINITY values when match template with mask
         // https://github.com/opencv/opencv/issues/23257
         loop {
-            let (rect, match_result) = match_one(&result, offset, template_size, threshold);
+            let (rect, match_result) = match_one(&result, offset, template_size, scale, threshold);
             if match_result
                 .as_ref()
                 .is_ok_and(|(_, score)| *score == f64::INFINITY)
             {
-                if clear_result(&mut result, rect, offset).is_err() {
+                if clear_result(&mut result, rect, offset, scale).is_err() {
                     return vec![];
                 }
                 continue;
@@ -2191,8 +2580,8 @@ fn detect_template_multiple<T: ToInputArray + MatTraitConst>(
     let mut filter = Vec::new();
     for _ in 0..max_matches {
         loop {
-            let (rect, match_result) = match_one(&result, offset, template_size, threshold);
-            if clear_result(&mut result, rect, offset).is_err() {
+            let (rect, match_result) = match_one(&result, offset, template_size, scale, threshold);
+            if clear_result(&mut result, rect, offset, scale).is_err() {
                 return vec![];
             }
             // Weird INFINITY values when match template with mask
@@ -2211,6 +2600,75 @@ fn detect_template_multiple<T: ToInputArray + MatTraitConst>(
     filter
 }
 
+/// Matches `template` on `mat` downscaled by `scale`.
+///
+/// The locations in `result` are relative to the downscaled `mat`.
+fn match_template_downscaled(
+    mat: &impl ToInputArray,
+    template: &impl ToInputArray,
+    mask: &impl ToInputArray,
+    scale: f64,
+    result: &mut Mat,
+) -> opencv::Result<()> {
+    let mut mat_downscaled = Mat::default();
+    resize(
+        mat,
+        &mut mat_downscaled,
+        Size::default(),
+        scale,
+        scale,
+        INTER_AREA,
+    )?;
+    let mut template_downscaled = Mat::default();
+    resize(
+        template,
+        &mut template_downscaled,
+        Size::default(),
+        scale,
+        scale,
+        INTER_AREA,
+    )?;
+    if mask.input_array()?.empty()? {
+        return match_template(
+            &mat_downscaled,
+            &template_downscaled,
+            result,
+            TM_CCOEFF_NORMED,
+            &no_array(),
+        );
+    }
+
+    // Keeps the mask binary
+    let mut mask_downscaled = Mat::default();
+    resize(
+        mask,
+        &mut mask_downscaled,
+        Size::default(),
+        scale,
+        scale,
+        INTER_NEAREST,
+    )?;
+    match_template(
+        &mat_downscaled,
+        &template_downscaled,
+        result,
+        TM_CCOEFF_NORMED,
+        &mask_downscaled,
+    )
+}
+
+/// Gets the scale to match a template of `template_size` at.
+#[inline]
+fn template_match_scale(template_size: Size) -> f64 {
+    let scale = DETECTION_SCALE_PERCENT.load(Ordering::Relaxed) as f64 / 100.0;
+    let min_downscaled_size = (template_size.width.min(template_size.height) as f64 * scale) as i32;
+    if min_downscaled_size < MIN_DOWNSCALED_TEMPLATE_SIZE {
+        1.0
+    } else {
+        scale
+    }
+}
+
 /// Extracts texts from the non-preprocessed `Mat` and detected text bounding boxes.
 fn extract_texts(mat: &impl MatTraitConst, bboxes: &[Rect]) -> Vec<String> {
     static TEXT_RECOGNITION_MODEL: LazyLock<Mutex<TextRecognitionModel>> = LazyLock::new(|| {
@@ -2620,15 +3078,113 @@ fn norm_rgb_to_input_value(mat: &impl MatTraitConst) -> SessionInputValue<'_> {
     SessionInputValue::Owned(tensor.clone().into_dyn())
 }
 
+/// Decodes the template `bytes` compiled-in from `path` with `flags`.
+///
+/// If an image with the same file name as `path` exists inside [`USER_TEMPLATES_DIR`], that image
+/// is used instead so that templates can be replaced (e.g. for regional clients with different UI)
+/// without recompiling.
+fn decode_template(path: &str, bytes: &[u8], flags: i32) -> Mat {
+    let user_template = Path::new(path).file_name().and_then(|name| {
+        let path = env::current_exe()
+            .ok()?
+            .parent()?
+            .join(USER_TEMPLATES_DIR)
+            .join(name);
+        let template = imgcodecs::imread(path.to_str()?, flags).ok()?;
+        (!template.empty()).then(|| {
+            info!(target: "detect", "using user template {path:?}");
+            template
+        })
+    });
+
+    user_template
+        .unwrap_or_else(|| imgcodecs::imdecode(&Vector::<u8>::from_slice(bytes), flags).unwrap())
+}
+
+/// Sets the scale of the frame used for template matching.
+pub fn set_detection_scale(scale: DetectionScale) {
+    DETECTION_SCALE_PERCENT.store(scale.percent(), Ordering::Relaxed);
+}
+
+/// Updates the rune arrows and mob detection models from `models`.
+///
+/// A model is only reloaded when its path changed or `reload` is true. The compiled-in model is
+/// used if the user-provided model cannot be loaded.
+pub fn update_models(models: &Models, reload: bool) -> Result<()> {
+    let mut rune_model = RUNE_MODEL.lock().unwrap();
+    let rune = rune_model.update(&models.rune_model_path, models.rune_threshold, reload);
+    let mut mob_model = MOB_MODEL.lock().unwrap();
+    let mob = mob_model.update(&models.mob_model_path, models.mob_threshold, reload);
+
+    rune.and(mob)
+}
+
+/// An ONNX model that can be replaced by a user-provided model at runtime.
+struct Model {
+    /// The compiled-in model.
+    bytes: &'static [u8],
+    /// The session of the model, lazily built from the compiled-in model if [`None`].
+    session: Option<Session>,
+    /// The path to the user-provided model or empty to use the compiled-in model.
+    path: String,
+    /// The minimum confidence for a prediction to be accepted.
+    threshold: f32,
+}
+
+impl Model {
+    const fn new(bytes: &'static [u8], threshold: f32) -> Self {
+        Self {
+            bytes,
+            session: None,
+            path: String::new(),
+            threshold,
+        }
+    }
+
+    fn update(&mut self, path: &str, threshold: f32, reload: bool) -> Result<()> {
+        self.threshold = threshold;
+        if !reload && self.path == path {
+            return Ok(());
+        }
+
+        self.path = path.to_string();
+        self.session = None;
+        if path.is_empty() {
+            return Ok(());
+        }
+        let session = fs::read(path)
+            .map_err(anyhow::Error::from)
+            .and_then(|model| build_session(&model))
+            .inspect_err(|err| error!(target: "detect", "failed to load model {path}: {err}"))?;
+        info!(target: "detect", "using user model {path}");
+        self.session = Some(session);
+        Ok(())
+    }
+
+    fn session(&mut self) -> &mut Session {
+        self.session.get_or_insert_with(|| {
+            build_session(self.bytes).expect("build compiled-in model session successfully")
+        })
+    }
+}
+
+static RUNE_MODEL: Mutex<Model> = Mutex::new(Model::new(include_bytes!(env!("RUNE_MODEL")), 0.2));
+
+static MOB_MODEL: Mutex<Model> = Mutex::new(Model::new(include_bytes!(env!("MOB_MODEL")), 0.5));
+
+/// Maps `provider` to the execution providers registered for all ONNX sessions.
+///
+/// The CPU execution provider is always used as the fallback when the other execution providers
+/// fail to register (e.g. missing GPU or onnxruntime dlls).
+pub fn execution_providers(provider: ExecutionProvider) -> Vec<ExecutionProviderDispatch> {
+    match provider {
+        ExecutionProvider::Cpu => vec![],
+        ExecutionProvider::Cuda => vec![CUDAExecutionProvider::default().build()],
+        ExecutionProvider::DirectMl => vec![DirectMLExecutionProvider::default().build()],
+    }
+}
+
 #[inline]
 fn build_session(model: &[u8]) -> Result<Session> {
-    // TODO: ort supports fallback to CPU if GPU is not found. Check if missing GPU-related
-    // TODO: onnxruntime dlls affect this.
-    if cfg!(feature = "gpu") {
-        Ok(Session::builder()?
-            .with_execution_providers([CUDAExecutionProvider::default().build()])?
-            .commit_from_memory(model)?)
-    } else {
-        Ok(Session::builder()?.commit_from_memory(model)?)
-    }
+    Ok(Session::builder()?.commit_from_memory(model)?)
 }
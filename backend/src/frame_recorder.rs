@@ -0,0 +1,150 @@
+use std::{
+    collections::VecDeque,
+    fs,
+    path::PathBuf,
+    sync::{
+        LazyLock,
+        mpsc::{self, Receiver, SyncSender},
+    },
+    thread,
+};
+
+use log::debug;
+use opencv::{
+    core::{MatTraitConst, MatTraitConstManual, ModifyInplace, Vector},
+    imgcodecs::{IMREAD_COLOR, IMWRITE_PNG_COMPRESSION, imdecode, imencode},
+    imgproc::{COLOR_BGR2BGRA, cvt_color_def},
+};
+use platforms::windows::Frame;
+
+use crate::{mat::OwnedMat, recorder::RECORDINGS_DIR};
+
+/// Maximum number of frames kept on disk before the oldest frame is removed.
+///
+/// At 30 ticks per second, this is roughly 20 seconds of frames.
+const MAX_FRAMES: usize = 600;
+
+/// Maximum number of frames waiting to be written before new frames are dropped.
+const MAX_PENDING_FRAMES: usize = 30;
+
+/// A fast PNG compression level since frames are written every tick.
+const PNG_COMPRESSION: i32 = 1;
+
+static FRAMES_DIR: LazyLock<PathBuf> = LazyLock::new(|| RECORDINGS_DIR.join("frames"));
+
+/// A recorder that writes the captured frames to disk as a ring buffer of PNG files.
+///
+/// Frames are encoded and written on a separate thread so recording does not slow down the
+/// update loop. A frame is dropped instead if the writer thread falls behind.
+#[derive(Debug, Default)]
+pub struct FrameRecorder {
+    sender: Option<SyncSender<Frame>>,
+}
+
+impl FrameRecorder {
+    /// Starts or stops recording.
+    ///
+    /// Starting a new recording discards the frames from the last recording.
+    pub fn set_enabled(&mut self, enabled: bool) {
+        if enabled == self.sender.is_some() {
+            return;
+        }
+        if enabled {
+            let _ = fs::remove_dir_all(&*FRAMES_DIR);
+            if fs::create_dir_all(&*FRAMES_DIR).is_err() {
+                return;
+            }
+            let (sender, receiver) = mpsc::sync_channel(MAX_PENDING_FRAMES);
+            thread::spawn(move || write_frames(receiver));
+            self.sender = Some(sender);
+            debug!(target: "frame_recorder", "started recording to {:?}", *FRAMES_DIR);
+        } else {
+            // Dropping the sender stops the writer thread after the pending frames are written
+            self.sender = None;
+            debug!(target: "frame_recorder", "stopped recording");
+        }
+    }
+
+    /// Records `frame` if recording is enabled.
+    #[inline]
+    pub fn record(&self, frame: &Frame) {
+        if let Some(sender) = self.sender.as_ref() {
+            let _ = sender.try_send(frame.clone());
+        }
+    }
+}
+
+fn write_frames(receiver: Receiver<Frame>) {
+    let mut paths = VecDeque::with_capacity(MAX_FRAMES);
+    let params = Vector::from_slice(&[IMWRITE_PNG_COMPRESSION, PNG_COMPRESSION]);
+
+    for (i, frame) in receiver.into_iter().enumerate() {
+        let mat = OwnedMat::new_from_frame(frame);
+        let mut bytes = Vector::new();
+        if imencode(".png", &mat, &mut bytes, &params).is_err() {
+            continue;
+        }
+        // Zero-padded so that the files are sorted in the recorded order
+        let path = FRAMES_DIR.join(format!("{i:010}.png"));
+        if fs::write(&path, bytes.as_slice()).is_err() {
+            continue;
+        }
+
+        paths.push_back(path);
+        if paths.len() > MAX_FRAMES
+            && let Some(oldest) = paths.pop_front()
+        {
+            let _ = fs::remove_file(oldest);
+        }
+    }
+}
+
+/// A fake capture that replays the frames recorded by [`FrameRecorder`] in order.
+///
+/// The frames are replayed from the start again after the last frame.
+#[derive(Debug)]
+pub struct FrameReplay {
+    paths: Vec<PathBuf>,
+    index: usize,
+}
+
+impl FrameReplay {
+    /// Loads a replay from the frames currently recorded on disk.
+    pub fn load() -> Self {
+        let mut paths = fs::read_dir(&*FRAMES_DIR)
+            .map(|entries| {
+                entries
+                    .filter_map(|entry| entry.ok())
+                    .map(|entry| entry.path())
+                    .filter(|path| path.extension().is_some_and(|ext| ext == "png"))
+                    .collect::<Vec<_>>()
+            })
+            .unwrap_or_default();
+        paths.sort_unstable();
+        debug!(target: "frame_recorder", "replaying {} recorded frames", paths.len());
+
+        Self { paths, index: 0 }
+    }
+
+    /// Grabs the next recorded frame.
+    pub fn grab(&mut self) -> Option<Frame> {
+        if self.paths.is_empty() {
+            return None;
+        }
+        let path = &self.paths[self.index];
+        self.index = (self.index + 1) % self.paths.len();
+
+        let bytes = Vector::<u8>::from_iter(fs::read(path).ok()?);
+        let mut mat = imdecode(&bytes, IMREAD_COLOR).ok()?;
+        unsafe {
+            mat.modify_inplace(|mat, mat_mut| cvt_color_def(mat, mat_mut, COLOR_BGR2BGRA))
+                .ok()?;
+        }
+
+        Some(Frame {
+            width: mat.cols(),
+            height: mat.rows(),
+            data: mat.data_bytes().ok()?.to_vec(),
+        })
+    }
+}
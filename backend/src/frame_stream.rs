@@ -0,0 +1,332 @@
+use std::{
+    io::{self, ErrorKind, Read, Write},
+    net::{TcpStream, UdpSocket},
+    sync::{
+        Arc, Mutex,
+        atomic::{AtomicBool, Ordering},
+    },
+    thread,
+    time::{Duration, Instant},
+};
+
+use log::{debug, info};
+use opencv::{
+    core::{Mat, MatTraitConst, MatTraitConstManual, ModifyInplace, Vector},
+    imgcodecs::{IMREAD_COLOR, imdecode},
+    imgproc::{COLOR_BGR2BGRA, cvt_color_def},
+    videoio::{CAP_ANY, VideoCapture, VideoCaptureTrait, VideoCaptureTraitConst},
+};
+use platforms::windows::Frame;
+
+/// The delay before reconnecting to the source after it failed or closed.
+const RECONNECT_DELAY: Duration = Duration::from_secs(1);
+
+/// The read timeout so that the receiving thread can notice when the stream is dropped.
+const READ_TIMEOUT: Duration = Duration::from_millis(500);
+
+/// The duration after which the last received frame is considered stale and not used.
+const STALE_DURATION: Duration = Duration::from_secs(2);
+
+/// Maximum number of buffered bytes without a complete JPEG image before the buffer is cleared.
+const MAX_BUFFER_BYTES: usize = 16 * 1024 * 1024;
+
+/// JPEG start of image marker.
+const JPEG_SOI: [u8; 2] = [0xFF, 0xD8];
+
+/// JPEG end of image marker.
+const JPEG_EOI: [u8; 2] = [0xFF, 0xD9];
+
+/// The external sources [`FrameStream`] can receive frames from.
+#[derive(Clone, PartialEq, Eq, Debug)]
+enum StreamSource {
+    /// A camera device index such as the OBS virtual camera (e.g. `camera://0`).
+    Camera(i32),
+    /// JPEG frames sent to the bound UDP address (e.g. `udp://0.0.0.0:5000`).
+    Udp(String),
+    /// An MJPEG stream from a TCP server (e.g. `tcp://192.168.1.2:5000`).
+    Tcp(String),
+    /// An MJPEG over HTTP stream (e.g. `http://192.168.1.2:8080/stream`).
+    Http { address: String, path: String },
+}
+
+impl StreamSource {
+    fn parse(url: &str) -> Option<StreamSource> {
+        let (scheme, rest) = url.trim().split_once("://")?;
+        if rest.is_empty() {
+            return None;
+        }
+
+        match scheme.to_ascii_lowercase().as_str() {
+            "camera" => rest.parse::<i32>().ok().map(StreamSource::Camera),
+            "udp" => Some(StreamSource::Udp(rest.to_string())),
+            "tcp" => Some(StreamSource::Tcp(rest.to_string())),
+            "http" => {
+                let (host, path) = rest
+                    .find('/')
+                    .map(|index| rest.split_at(index))
+                    .unwrap_or((rest, "/"));
+                let address = if host.contains(':') {
+                    host.to_string()
+                } else {
+                    format!("{host}:80")
+                };
+                Some(StreamSource::Http {
+                    address,
+                    path: path.to_string(),
+                })
+            }
+            _ => None,
+        }
+    }
+}
+
+/// A capture that receives frames from an external source instead of capturing a window.
+///
+/// This allows the bot to run on a different machine than the game, with the keys sent through
+/// the RPC input method. Frames are received on a separate thread and [`Self::grab`] returns the
+/// latest received frame.
+#[derive(Debug)]
+pub struct FrameStream {
+    latest: Arc<Mutex<Option<(Frame, Instant)>>>,
+    stopped: Arc<AtomicBool>,
+}
+
+impl FrameStream {
+    /// Starts receiving frames from `url`.
+    ///
+    /// No frame is received if `url` is not a supported source.
+    pub fn new(url: &str) -> Self {
+        let latest = Arc::new(Mutex::new(None));
+        let stopped = Arc::new(AtomicBool::new(false));
+        if let Some(source) = StreamSource::parse(url) {
+            let latest = latest.clone();
+            let stopped = stopped.clone();
+            thread::spawn(move || receive_frames(source, latest, stopped));
+        } else {
+            info!(target: "frame_stream", "unsupported stream source {url:?}");
+        }
+
+        Self { latest, stopped }
+    }
+
+    /// Grabs the latest received frame if it is not stale.
+    pub fn grab(&mut self) -> Option<Frame> {
+        self.latest
+            .lock()
+            .unwrap()
+            .as_ref()
+            .filter(|(_, received_at)| received_at.elapsed() < STALE_DURATION)
+            .map(|(frame, _)| frame.clone())
+    }
+}
+
+impl Drop for FrameStream {
+    fn drop(&mut self) {
+        self.stopped.store(true, Ordering::Relaxed);
+    }
+}
+
+fn receive_frames(
+    source: StreamSource,
+    latest: Arc<Mutex<Option<(Frame, Instant)>>>,
+    stopped: Arc<AtomicBool>,
+) {
+    let on_frame = |frame: Frame| {
+        *latest.lock().unwrap() = Some((frame, Instant::now()));
+    };
+
+    while !stopped.load(Ordering::Relaxed) {
+        debug!(target: "frame_stream", "connecting to {source:?}");
+        let result = match &source {
+            StreamSource::Camera(index) => receive_camera(*index, &stopped, on_frame),
+            StreamSource::Udp(address) => UdpSocket::bind(address).and_then(|socket| {
+                socket.set_read_timeout(Some(READ_TIMEOUT))?;
+                receive_jpegs(|buf| socket.recv(buf), &stopped, on_frame)
+            }),
+            StreamSource::Tcp(address) => TcpStream::connect(address).and_then(|mut stream| {
+                stream.set_read_timeout(Some(READ_TIMEOUT))?;
+                receive_jpegs(|buf| stream.read(buf), &stopped, on_frame)
+            }),
+            StreamSource::Http { address, path } => {
+                TcpStream::connect(address).and_then(|mut stream| {
+                    stream.set_read_timeout(Some(READ_TIMEOUT))?;
+                    write!(
+                        stream,
+                        "GET {path} HTTP/1.1\r\nHost: {address}\r\nConnection: keep-alive\r\n\r\n"
+                    )?;
+                    // The response and multipart headers are skipped when looking for the images
+                    receive_jpegs(|buf| stream.read(buf), &stopped, on_frame)
+                })
+            }
+        };
+        if let Err(error) = result {
+            debug!(target: "frame_stream", "stream {source:?} failed {error}");
+        }
+        if !stopped.load(Ordering::Relaxed) {
+            thread::sleep(RECONNECT_DELAY);
+        }
+    }
+}
+
+fn receive_camera(index: i32, stopped: &AtomicBool, on_frame: impl Fn(Frame)) -> io::Result<()> {
+    let mut capture = VideoCapture::new(index, CAP_ANY).map_err(io::Error::other)?;
+    if !capture.is_opened().unwrap_or_default() {
+        return Err(io::Error::new(ErrorKind::NotFound, "camera not opened"));
+    }
+
+    let mut mat = Mat::default();
+    while !stopped.load(Ordering::Relaxed) {
+        if !capture.read(&mut mat).map_err(io::Error::other)? {
+            return Err(io::Error::new(ErrorKind::UnexpectedEof, "camera closed"));
+        }
+        if let Some(frame) = to_frame(mat.try_clone().map_err(io::Error::other)?) {
+            on_frame(frame);
+        }
+    }
+    Ok(())
+}
+
+fn receive_jpegs(
+    mut read: impl FnMut(&mut [u8]) -> io::Result<usize>,
+    stopped: &AtomicBool,
+    on_frame: impl Fn(Frame),
+) -> io::Result<()> {
+    let mut splitter = JpegSplitter::default();
+    let mut buf = vec![0; 65536];
+    while !stopped.load(Ordering::Relaxed) {
+        let len = match read(&mut buf) {
+            Ok(0) => return Err(io::Error::new(ErrorKind::UnexpectedEof, "stream closed")),
+            Ok(len) => len,
+            Err(error) if matches!(error.kind(), ErrorKind::WouldBlock | ErrorKind::TimedOut) => {
+                continue;
+            }
+            Err(error) => return Err(error),
+        };
+        // Only the latest image is decoded if more than one arrived at once
+        if let Some(jpeg) = splitter.push(&buf[..len])
+            && let Ok(mat) = imdecode(&Vector::<u8>::from_slice(&jpeg), IMREAD_COLOR)
+            && let Some(frame) = to_frame(mat)
+        {
+            on_frame(frame);
+        }
+    }
+    Ok(())
+}
+
+#[inline]
+fn to_frame(mut mat: Mat) -> Option<Frame> {
+    if mat.empty() {
+        return None;
+    }
+    unsafe {
+        mat.modify_inplace(|mat, mat_mut| cvt_color_def(mat, mat_mut, COLOR_BGR2BGRA))
+            .ok()?;
+    }
+
+    Some(Frame {
+        width: mat.cols(),
+        height: mat.rows(),
+        data: mat.data_bytes().ok()?.to_vec(),
+    })
+}
+
+/// Splits a byte stream into JPEG images using the start and end of image markers.
+///
+/// This works for both raw concatenated JPEG images (e.g. `ffmpeg -f mjpeg`) and multipart MJPEG
+/// over HTTP since any bytes outside of the markers are skipped.
+#[derive(Debug, Default)]
+struct JpegSplitter {
+    buffer: Vec<u8>,
+}
+
+impl JpegSplitter {
+    /// Appends `bytes` and returns the last complete JPEG image found.
+    fn push(&mut self, bytes: &[u8]) -> Option<Vec<u8>> {
+        self.buffer.extend_from_slice(bytes);
+
+        let mut latest = None;
+        loop {
+            let Some(start) = find_marker(&self.buffer, JPEG_SOI) else {
+                // Keeps the last byte in case it is the first half of a marker
+                let len = self.buffer.len();
+                self.buffer.drain(..len.saturating_sub(1));
+                break;
+            };
+            let Some(end) =
+                find_marker(&self.buffer[start + 2..], JPEG_EOI).map(|index| start + 2 + index + 2)
+            else {
+                self.buffer.drain(..start);
+                break;
+            };
+            latest = Some(self.buffer[start..end].to_vec());
+            self.buffer.drain(..end);
+        }
+        if self.buffer.len() > MAX_BUFFER_BYTES {
+            self.buffer.clear();
+        }
+
+        latest
+    }
+}
+
+#[inline]
+fn find_marker(bytes: &[u8], marker: [u8; 2]) -> Option<usize> {
+    bytes.windows(2).position(|window| window == marker)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn stream_source_parse() {
+        assert_eq!(
+            StreamSource::parse("camera://1"),
+            Some(StreamSource::Camera(1))
+        );
+        assert_eq!(
+            StreamSource::parse("udp://0.0.0.0:5000"),
+            Some(StreamSource::Udp("0.0.0.0:5000".to_string()))
+        );
+        assert_eq!(
+            StreamSource::parse("http://192.168.1.2/stream"),
+            Some(StreamSource::Http {
+                address: "192.168.1.2:80".to_string(),
+                path: "/stream".to_string(),
+            })
+        );
+        assert_eq!(
+            StreamSource::parse("http://192.168.1.2:8080"),
+            Some(StreamSource::Http {
+                address: "192.168.1.2:8080".to_string(),
+                path: "/".to_string(),
+            })
+        );
+        assert_eq!(StreamSource::parse("camera://obs"), None);
+        assert_eq!(StreamSource::parse("rtsp://192.168.1.2"), None);
+        assert_eq!(StreamSource::parse(""), None);
+    }
+
+    #[test]
+    fn jpeg_splitter_push_returns_latest_complete_image() {
+        let mut splitter = JpegSplitter::default();
+        let first = [0xFF, 0xD8, 1, 2, 0xFF, 0xD9];
+        let second = [0xFF, 0xD8, 3, 0xFF, 0xD9];
+
+        // Split across pushes with multipart headers in between
+        assert_eq!(splitter.push(b"--boundary\r\n\r\n"), None);
+        assert_eq!(splitter.push(&first[..3]), None);
+        assert_eq!(splitter.push(&first[3..]), Some(first.to_vec()));
+        assert!(splitter.buffer.is_empty());
+
+        // Split in the middle of the end marker
+        assert_eq!(splitter.push(&second[..4]), None);
+        assert_eq!(splitter.push(&second[4..]), Some(second.to_vec()));
+
+        // Only the latest when more than one arrived at once
+        assert_eq!(
+            splitter.push(&[first.as_slice(), &second].concat()),
+            Some(second.to_vec())
+        );
+    }
+}
@@ -0,0 +1,89 @@
+use anyhow::{Result, anyhow};
+use log::info;
+
+use crate::{
+    context::Context,
+    network::NotificationKind,
+    task::{Task, Update, update_detection_task},
+};
+
+/// The delay between each GM detection.
+const DETECT_DELAY_MILLIS: u64 = 2000;
+
+/// The number of consecutive detections required before a GM is considered present.
+///
+/// This keeps a single misread from triggering the response.
+const CONFIRM_COUNT: u32 = 2;
+
+/// Watches for GM presence while the actions are running.
+#[derive(Debug, Default)]
+pub struct GmWatcher {
+    task: Option<Task<Result<()>>>,
+    /// The number of consecutive detections.
+    detected_count: u32,
+}
+
+impl GmWatcher {
+    /// Detects GM presence if `enabled` and sends a notification once it is confirmed.
+    ///
+    /// Returns `true` if the GM presence was just confirmed and the response should be run.
+    pub fn update(&mut self, context: &Context, enabled: bool) -> bool {
+        if !enabled
+            || context.detector.is_none()
+            || context.operation.halting()
+            || context.operation.paused()
+        {
+            self.task = None;
+            self.detected_count = 0;
+            return false;
+        }
+
+        let update =
+            update_detection_task(context, DETECT_DELAY_MILLIS, &mut self.task, |detector| {
+                if detector.detect_gm_chat() {
+                    Ok(())
+                } else {
+                    Err(anyhow!("GM not found"))
+                }
+            });
+        let confirmed = match update {
+            Update::Ok(_) => self.track(true),
+            Update::Err(_) => self.track(false),
+            Update::Pending => false,
+        };
+        if confirmed {
+            info!(target: "gm", "sending GM notification...");
+            let _ = context
+                .notification
+                .schedule_notification(NotificationKind::GmAppear);
+        }
+        confirmed
+    }
+
+    /// Tracks whether a GM is `detected` and returns `true` when the presence is confirmed.
+    fn track(&mut self, detected: bool) -> bool {
+        if !detected {
+            self.detected_count = 0;
+            return false;
+        }
+        self.detected_count += 1;
+        self.detected_count == CONFIRM_COUNT
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn track_confirm_after_consecutive_detections() {
+        let mut watcher = GmWatcher::default();
+
+        assert!(!watcher.track(true));
+        assert!(!watcher.track(false));
+        assert!(!watcher.track(true));
+        assert!(watcher.track(true));
+        // Only confirmed once while still detected
+        assert!(!watcher.track(true));
+    }
+}
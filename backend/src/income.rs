@@ -0,0 +1,157 @@
+use std::time::{Duration, Instant};
+
+use anyhow::Result;
+use opencv::core::Rect;
+
+use crate::{
+    context::Context,
+    task::{Task, Update, update_inference_task},
+};
+
+/// The delay between each EXP and meso detection.
+const DETECT_DELAY_MILLIS: u64 = 10000;
+
+/// The minimum time since the first reading before the per hour rates are estimated.
+const MIN_ESTIMATE_DURATION: Duration = Duration::from_secs(60);
+
+/// The minimum drop in EXP percentage to be considered a level up instead of a misread.
+const LEVEL_UP_MIN_DROP_PERCENT: f32 = 50.0;
+
+/// Estimates the EXP and mesos gained per hour by reading the EXP bar and the meso counter
+/// periodically.
+#[derive(Debug, Default)]
+pub struct IncomeTracker {
+    exp_task: Option<Task<Result<f32>>>,
+    mesos_task: Option<Task<Result<u64>>>,
+    /// The instant the EXP percentage was first read.
+    exp_started_at: Option<Instant>,
+    last_exp_percent: Option<f32>,
+    gained_exp_percent: f32,
+    /// The instant the meso counter was first read.
+    mesos_started_at: Option<Instant>,
+    last_mesos: Option<u64>,
+    gained_mesos: u64,
+}
+
+impl IncomeTracker {
+    /// Reads the EXP bar and the meso counter inside `mesos_region` if provided.
+    pub fn update(&mut self, context: &Context, mesos_region: Option<Rect>) {
+        if context.detector.is_none() {
+            return;
+        }
+
+        if let Update::Ok(percent) = update_inference_task(
+            context,
+            DETECT_DELAY_MILLIS,
+            &mut self.exp_task,
+            |detector| detector.detect_exp_percent(),
+        ) {
+            self.track_exp(percent, Instant::now());
+        }
+        if let Some(region) = mesos_region
+            && let Update::Ok(mesos) = update_inference_task(
+                context,
+                DETECT_DELAY_MILLIS,
+                &mut self.mesos_task,
+                move |detector| detector.detect_mesos(region),
+            )
+        {
+            self.track_mesos(mesos, Instant::now());
+        }
+    }
+
+    /// The estimated EXP percentage gained per hour.
+    pub fn exp_percent_per_hour(&self, now: Instant) -> Option<f32> {
+        per_hour(self.exp_started_at?, now, self.gained_exp_percent)
+    }
+
+    /// The estimated mesos gained per hour.
+    pub fn mesos_per_hour(&self, now: Instant) -> Option<f32> {
+        per_hour(self.mesos_started_at?, now, self.gained_mesos as f32)
+    }
+
+    fn track_exp(&mut self, percent: f32, now: Instant) {
+        match self.last_exp_percent {
+            None => self.exp_started_at = Some(now),
+            Some(last) if percent >= last => self.gained_exp_percent += percent - last,
+            Some(last) if last - percent >= LEVEL_UP_MIN_DROP_PERCENT => {
+                self.gained_exp_percent += 100.0 - last + percent;
+            }
+            // A small drop is most likely a misread
+            Some(_) => return,
+        }
+        self.last_exp_percent = Some(percent);
+    }
+
+    fn track_mesos(&mut self, mesos: u64, now: Instant) {
+        let Some(last) = self.last_mesos else {
+            self.mesos_started_at = Some(now);
+            self.last_mesos = Some(mesos);
+            return;
+        };
+        // A missing or an extra digit is most likely a misread
+        let digits = mesos.checked_ilog10().unwrap_or_default();
+        let last_digits = last.checked_ilog10().unwrap_or_default();
+        if digits.abs_diff(last_digits) > 1 {
+            return;
+        }
+
+        // Spent mesos (e.g. buying potions) are not counted
+        self.gained_mesos += mesos.saturating_sub(last);
+        self.last_mesos = Some(mesos);
+    }
+}
+
+#[inline]
+fn per_hour(started_at: Instant, now: Instant, gained: f32) -> Option<f32> {
+    let elapsed = now.saturating_duration_since(started_at);
+    (elapsed >= MIN_ESTIMATE_DURATION).then(|| gained / elapsed.as_secs_f32() * 3600.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn track_exp_across_level_up() {
+        let mut tracker = IncomeTracker::default();
+        let now = Instant::now();
+
+        tracker.track_exp(90.0, now);
+        tracker.track_exp(95.0, now);
+        tracker.track_exp(5.0, now);
+        assert_eq!(tracker.gained_exp_percent, 15.0);
+
+        // Ignores a small drop as a misread
+        tracker.track_exp(4.0, now);
+        tracker.track_exp(6.0, now);
+        assert_eq!(tracker.gained_exp_percent, 16.0);
+    }
+
+    #[test]
+    fn track_mesos_ignore_spent_and_misread() {
+        let mut tracker = IncomeTracker::default();
+        let now = Instant::now();
+
+        tracker.track_mesos(1_000_000, now);
+        tracker.track_mesos(1_500_000, now);
+        tracker.track_mesos(1_200_000, now);
+        tracker.track_mesos(120_000_000, now);
+        tracker.track_mesos(1_300_000, now);
+        assert_eq!(tracker.gained_mesos, 600_000);
+    }
+
+    #[test]
+    fn exp_percent_per_hour_after_min_duration() {
+        let mut tracker = IncomeTracker::default();
+        let now = Instant::now();
+
+        tracker.track_exp(10.0, now);
+        tracker.track_exp(12.0, now);
+        assert_eq!(tracker.exp_percent_per_hour(now), None);
+        assert_eq!(
+            tracker.exp_percent_per_hour(now + Duration::from_secs(1800)),
+            Some(4.0)
+        );
+    }
+}
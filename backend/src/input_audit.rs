@@ -0,0 +1,91 @@
+use std::{
+    collections::VecDeque,
+    fs::File,
+    io::{BufWriter, Write},
+    path::PathBuf,
+};
+
+use anyhow::Result;
+
+use crate::{InputEvent, recorder::RECORDINGS_DIR};
+
+/// Maximum number of events kept in [`InputAuditLog`].
+///
+/// This is usually several minutes of inputs.
+const MAX_EVENTS: usize = 5000;
+
+/// A ring buffer of the recent inputs sent by the bot.
+///
+/// Unlike the flight recorder, this is always enabled and kept in memory so that the inputs
+/// leading to an issue can be inspected after it happened.
+#[derive(Debug, Default)]
+pub struct InputAuditLog {
+    events: VecDeque<InputEvent>,
+}
+
+impl InputAuditLog {
+    /// Records `events` and drops the oldest events if full.
+    pub fn record(&mut self, events: impl IntoIterator<Item = InputEvent>) {
+        for event in events {
+            if self.events.len() >= MAX_EVENTS {
+                self.events.pop_front();
+            }
+            self.events.push_back(event);
+        }
+    }
+
+    /// Gets the recorded events from the oldest to the newest.
+    pub fn events(&self) -> Vec<InputEvent> {
+        self.events.iter().cloned().collect()
+    }
+
+    /// Writes the recorded events as JSON lines to a new file in [`RECORDINGS_DIR`].
+    pub fn dump(&self) -> Result<PathBuf> {
+        let millis = self
+            .events
+            .back()
+            .map(|event| event.millis)
+            .unwrap_or_default();
+        let path = RECORDINGS_DIR.join(format!("input_events_{millis}.jsonl"));
+        let mut writer = BufWriter::new(File::create(&path)?);
+        for event in &self.events {
+            serde_json::to_writer(&mut writer, event)?;
+            writer.write_all(b"\n")?;
+        }
+        writer.flush()?;
+
+        Ok(path)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::InputEventKind;
+
+    fn event(millis: u64) -> InputEvent {
+        InputEvent {
+            millis,
+            tick: 0,
+            kind: InputEventKind::KeyPress,
+            input: "A".to_string(),
+            state: "Idle".to_string(),
+            action_id: None,
+            action: None,
+        }
+    }
+
+    #[test]
+    fn record_drop_oldest_when_full() {
+        let mut log = InputAuditLog::default();
+        log.record((0..MAX_EVENTS as u64 + 2).map(event));
+
+        let events = log.events();
+        assert_eq!(events.len(), MAX_EVENTS);
+        assert_eq!(events.first().map(|event| event.millis), Some(2));
+        assert_eq!(
+            events.last().map(|event| event.millis),
+            Some(MAX_EVENTS as u64 + 1)
+        );
+    }
+}
@@ -0,0 +1,130 @@
+use std::{
+    collections::HashSet,
+    time::{Duration, Instant},
+};
+
+use log::info;
+
+use crate::{
+    context::Context,
+    network::NotificationKind,
+    player::{Player, PlayerState},
+};
+
+/// Maximum velocity in both axes for the player to be considered not moving.
+const STALLED_VELOCITY_THRESHOLD: f32 = 0.1;
+
+/// Minimum duration movement keys are sent without the player moving before notifying.
+const STALLED_DURATION: Duration = Duration::from_secs(60);
+
+/// Minimum number of distinct actions attempted while stalled before notifying.
+const STALLED_ACTION_COUNT: usize = 2;
+
+/// Monitors whether dispatched movement keys result in any player movement.
+///
+/// Unlike [`Player::Unstucking`] that handles a single stuck action, this detects when the player
+/// does not move at all across multiple actions. This usually means the captured frames are
+/// frozen or the input method is not reaching the game.
+#[derive(Debug, Default)]
+pub struct InputMonitor {
+    /// The instant the player started not moving while movement keys are being sent.
+    stalled_since: Option<Instant>,
+    /// The distinct action ids attempted since [`Self::stalled_since`].
+    stalled_action_ids: HashSet<u32>,
+    /// Whether a notification has been scheduled for the current stall.
+    notified: bool,
+}
+
+impl InputMonitor {
+    /// Updates the monitor from the current player contextual state and velocity.
+    ///
+    /// Monitoring resets while actions are halting or paused.
+    pub fn update(&mut self, context: &Context, player: &PlayerState) {
+        if context.operation.halting() || context.operation.paused() {
+            *self = InputMonitor::default();
+            return;
+        }
+
+        let (vx, vy) = player.velocity();
+        let stalled = vx <= STALLED_VELOCITY_THRESHOLD && vy <= STALLED_VELOCITY_THRESHOLD;
+        let action_id = player.priority_action_id().or(player.normal_action_id());
+        if self.track(
+            Instant::now(),
+            is_sending_movement_keys(context.player),
+            stalled,
+            action_id,
+        ) {
+            info!(target: "input_monitor", "player has not moved while sending movement keys");
+            let _ = context
+                .notification
+                .schedule_notification(NotificationKind::CaptureOrInputBroken);
+        }
+    }
+
+    /// Tracks the stall at `now`.
+    ///
+    /// Returns `true` when the stall should be notified.
+    fn track(
+        &mut self,
+        now: Instant,
+        sending_keys: bool,
+        stalled: bool,
+        action_id: Option<u32>,
+    ) -> bool {
+        if !stalled {
+            *self = InputMonitor::default();
+            return false;
+        }
+        if !sending_keys {
+            return false;
+        }
+
+        let stalled_since = *self.stalled_since.get_or_insert(now);
+        if let Some(id) = action_id {
+            self.stalled_action_ids.insert(id);
+        }
+        if self.notified
+            || now.duration_since(stalled_since) < STALLED_DURATION
+            || self.stalled_action_ids.len() < STALLED_ACTION_COUNT
+        {
+            return false;
+        }
+
+        self.notified = true;
+        true
+    }
+}
+
+#[inline]
+fn is_sending_movement_keys(player: Player) -> bool {
+    matches!(
+        player,
+        Player::Moving(_, _, _)
+            | Player::Adjusting(_)
+            | Player::DoubleJumping(_)
+            | Player::Grappling(_)
+            | Player::Jumping(_)
+            | Player::UpJumping(_)
+            | Player::Falling { .. }
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn track_notify_once_after_stalled_across_actions() {
+        let mut monitor = InputMonitor::default();
+        let now = Instant::now();
+
+        assert!(!monitor.track(now, true, true, Some(1)));
+        assert!(!monitor.track(now + STALLED_DURATION, true, true, Some(1)));
+        assert!(monitor.track(now + STALLED_DURATION, true, true, Some(2)));
+        assert!(!monitor.track(now + STALLED_DURATION * 2, true, true, Some(3)));
+
+        assert!(!monitor.track(now + STALLED_DURATION * 2, true, false, Some(3)));
+        assert!(monitor.stalled_since.is_none());
+        assert!(!monitor.notified);
+    }
+}
@@ -0,0 +1,134 @@
+use std::{mem, time::Instant};
+
+use log::debug;
+
+use crate::{
+    KeyBinding,
+    database::{Action, ActionCondition, ActionKey},
+};
+
+/// The maximum milliseconds to wait between two recorded keys.
+///
+/// Longer pauses are usually the user stopping to think rather than part of the combo.
+const MAX_WAIT_MILLIS: u64 = 5000;
+
+/// Records keys pressed by the user while demonstrating a combo.
+///
+/// The recording is converted to a chain of linked actions where each action waits for the time
+/// between the key and the next key.
+#[derive(Debug, Default)]
+pub struct KeyMacroRecorder {
+    recording: bool,
+    /// The recorded keys with the instants they are released.
+    keys: Vec<(KeyBinding, Instant)>,
+}
+
+impl KeyMacroRecorder {
+    /// Starts recording and discards previously recorded keys.
+    pub fn start(&mut self) {
+        *self = KeyMacroRecorder {
+            recording: true,
+            ..KeyMacroRecorder::default()
+        };
+        debug!(target: "key_macro_recorder", "started");
+    }
+
+    /// Stops recording and returns the recorded keys as linked actions.
+    pub fn stop(&mut self) -> Vec<Action> {
+        let keys = mem::take(&mut self.keys);
+        *self = KeyMacroRecorder::default();
+        debug!(target: "key_macro_recorder", "stopped with {} keys", keys.len());
+        linked_actions_from_keys(&keys)
+    }
+
+    #[inline]
+    pub fn is_recording(&self) -> bool {
+        self.recording
+    }
+
+    /// Records `key` pressed by the user if recording.
+    pub fn record_key(&mut self, key: KeyBinding) {
+        self.record_key_at(key, Instant::now());
+    }
+
+    fn record_key_at(&mut self, key: KeyBinding, instant: Instant) {
+        if self.recording {
+            self.keys.push((key, instant));
+        }
+    }
+}
+
+/// Converts the recorded `keys` to an action followed by linked actions.
+///
+/// The wait after each action is the time until the next key rounded to 10 milliseconds. Since
+/// keys are received when released, the time includes how long the next key is held.
+fn linked_actions_from_keys(keys: &[(KeyBinding, Instant)]) -> Vec<Action> {
+    keys.iter()
+        .enumerate()
+        .map(|(i, (key, instant))| {
+            let wait_after_use_millis = keys
+                .get(i + 1)
+                .map(|(_, next)| {
+                    let millis = next.duration_since(*instant).as_millis() as u64;
+                    (millis.div_ceil(10) * 10).min(MAX_WAIT_MILLIS)
+                })
+                .unwrap_or_default();
+            let condition = if i == 0 {
+                ActionCondition::Any
+            } else {
+                ActionCondition::Linked
+            };
+
+            Action::Key(ActionKey {
+                key: *key,
+                condition,
+                wait_after_use_millis,
+                ..ActionKey::default()
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+
+    #[test]
+    fn stop_convert_keys_to_linked_actions() {
+        let mut recorder = KeyMacroRecorder::default();
+        let now = Instant::now();
+        recorder.record_key_at(KeyBinding::A, now);
+        recorder.start();
+        recorder.record_key_at(KeyBinding::A, now);
+        recorder.record_key_at(KeyBinding::B, now + Duration::from_millis(155));
+        recorder.record_key_at(KeyBinding::C, now + Duration::from_secs(20));
+
+        let actions = recorder.stop();
+        assert!(!recorder.is_recording());
+        assert_eq!(
+            actions,
+            vec![
+                Action::Key(ActionKey {
+                    key: KeyBinding::A,
+                    condition: ActionCondition::Any,
+                    wait_after_use_millis: 160,
+                    ..ActionKey::default()
+                }),
+                Action::Key(ActionKey {
+                    key: KeyBinding::B,
+                    condition: ActionCondition::Linked,
+                    wait_after_use_millis: MAX_WAIT_MILLIS,
+                    ..ActionKey::default()
+                }),
+                Action::Key(ActionKey {
+                    key: KeyBinding::C,
+                    condition: ActionCondition::Linked,
+                    wait_after_use_millis: 0,
+                    ..ActionKey::default()
+                }),
+            ]
+        );
+    }
+}
@@ -0,0 +1,77 @@
+use log::debug;
+
+use crate::context::Context;
+
+/// Force-releases all held down keys when the actions halt or the game window loses focus.
+///
+/// Keys held down while the game window is not focused will not be released by the game itself,
+/// which can cause the player to keep moving after the bot has stopped sending keys.
+#[derive(Debug)]
+pub struct KeyWatchdog {
+    was_halting: bool,
+    was_foreground: bool,
+}
+
+impl Default for KeyWatchdog {
+    fn default() -> Self {
+        Self {
+            was_halting: true,
+            was_foreground: false,
+        }
+    }
+}
+
+impl KeyWatchdog {
+    /// Updates the watchdog from the current operation and the game window focus.
+    pub fn update(&mut self, context: &Context) {
+        let halting = context.operation.halting() || context.operation.paused();
+        if self.track(halting, context.keys.is_foreground()) {
+            debug!(target: "key_watchdog", "releasing all held down keys");
+            let _ = context.keys.release_all();
+        }
+    }
+
+    /// Tracks whether actions are `halting` and the game window is in the `foreground`.
+    ///
+    /// Returns `true` if the held down keys should be released.
+    fn track(&mut self, halting: bool, foreground: bool) -> bool {
+        let halted = halting && !self.was_halting;
+        let lost_focus = !foreground && self.was_foreground;
+        self.was_halting = halting;
+        self.was_foreground = foreground;
+
+        halted || lost_focus
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn track_release_on_halt() {
+        let mut watchdog = KeyWatchdog::default();
+
+        assert!(!watchdog.track(false, true));
+        assert!(watchdog.track(true, true));
+        assert!(!watchdog.track(true, true));
+    }
+
+    #[test]
+    fn track_release_on_focus_lost() {
+        let mut watchdog = KeyWatchdog::default();
+
+        assert!(!watchdog.track(false, true));
+        assert!(watchdog.track(false, false));
+        assert!(!watchdog.track(false, false));
+        assert!(!watchdog.track(false, true));
+    }
+
+    #[test]
+    fn track_no_release_when_starting() {
+        let mut watchdog = KeyWatchdog::default();
+
+        assert!(!watchdog.track(true, false));
+        assert!(!watchdog.track(false, true));
+    }
+}
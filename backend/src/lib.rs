@@ -7,10 +7,12 @@
 #![feature(assert_matches)]
 
 use std::{
+    collections::HashMap,
     sync::{LazyLock, Mutex},
-    time::Instant,
+    time::{Duration, Instant},
 };
 
+use serde::{Deserialize, Serialize};
 use strum::Display;
 use tokio::{
     sync::{
@@ -18,39 +20,75 @@ use tokio::{
         oneshot::{self, Sender},
     },
     task::spawn_blocking,
+    time::sleep,
 };
 
 mod array;
 mod bridge;
 mod buff;
+mod capture_monitor;
+mod checkpoint;
 mod context;
+mod control_server;
 mod database;
+mod death_recovery;
 #[cfg(debug_assertions)]
 mod debug;
 mod detect;
+mod frame_recorder;
+mod frame_stream;
+mod gm;
+mod income;
+mod input_audit;
+mod input_monitor;
+mod key_macro_recorder;
+mod key_watchdog;
+mod lie_detector;
 mod mat;
 mod minimap;
+mod minimap_selector;
+mod mouse_path;
+mod movement_test;
 mod navigation;
+mod navigation_recorder;
 mod network;
+mod overlay;
 mod pathing;
+mod platform_recorder;
 mod player;
+mod profiler;
+mod recorder;
+mod remote_control;
 mod request_handler;
 mod rng;
 mod rotator;
 mod rpc;
+mod scheduler;
+mod screenshot;
+mod session;
 mod skill;
 mod task;
+mod whisper;
+mod window_tracker;
 
 pub use {
     context::init,
     database::{
         Action, ActionCondition, ActionConfiguration, ActionConfigurationCondition, ActionKey,
-        ActionKeyDirection, ActionKeyWith, ActionMove, Bound, CaptureMode, Character, Class,
-        DatabaseEvent, EliteBossBehavior, FamiliarRarity, Familiars, InputMethod, KeyBinding,
-        KeyBindingConfiguration, LinkKeyBinding, Minimap, MobbingKey, NavigationPath,
-        NavigationPoint, NavigationTransition, Notifications, Platform, Position, PotionMode,
-        RotationMode, Settings, SwappableFamiliars, database_event_receiver,
+        ActionKeyDirection, ActionKeyWith, ActionMove, Bound, BuffDuration, CaptureMode,
+        ChannelSelection, Character, Checkpoint, Class, DatabaseEvent, DetectionScale,
+        DoubleJumpKeyTiming, EliteBossBehavior, ExecutionProvider, FamiliarRarity, Familiars,
+        GlobalHotkey, GmBehavior, IncomeSample, InputHumanization, InputMethod,
+        InventoryFullBehavior, JitterDistribution, KeyBinding, KeyBindingConfiguration,
+        KeyHoldOverride, KeyRemap, LinkKeyBinding, MAX_CHANNEL_COUNT, MAX_KEY_HOLD_OVERRIDES,
+        MAX_KEY_REMAPS, MAX_MOBBING_LINKED_KEYS, MAX_UNSTUCK_STRATEGIES, Minimap,
+        MobbingCooldownKey, MobbingKey, MobbingLinkedKey, Models, NavigationPath, NavigationPoint,
+        NavigationTransition, NotificationKindOptions, Notifications, Platform, Position,
+        PotionMode, PriorityActionPreemption, RequiredBuff, RotationMode, Schedule, ScheduleDay,
+        SessionStats, Settings, StrangerChannelHop, SwappableFamiliars, UnstuckStrategy,
+        UpJumpStyle, database_event_receiver,
     },
+    network::NotificationKind,
     pathing::MAX_PLATFORMS_COUNT,
     rotator::RotatorMode,
     strum::{EnumMessage, IntoEnumIterator, ParseError},
@@ -66,6 +104,15 @@ static REQUESTS: LazyLock<(
     (tx, Mutex::new(rx))
 });
 
+/// The delay to wait for more writes to the same minimap before persisting.
+const MINIMAP_WRITE_DEBOUNCE: Duration = Duration::from_millis(250);
+
+/// Pending minimap writes keyed by minimap id.
+///
+/// Each entry stores the generation of the latest write and the minimap to be written.
+static PENDING_MINIMAP_WRITES: LazyLock<Mutex<HashMap<i64, (u64, Minimap)>>> =
+    LazyLock::new(|| Mutex::new(HashMap::new()));
+
 macro_rules! expect_unit_variant {
     ($e:expr, $p:path) => {
         match $e {
@@ -88,16 +135,33 @@ macro_rules! expect_value_variant {
 #[derive(Debug)]
 enum Request {
     RotateActions(bool),
+    PauseActions(bool),
     CreateMinimap(String),
     UpdateMinimap(Option<String>, Option<Minimap>),
     CreateNavigationPath,
     RecaptureNavigationPath(NavigationPath),
     UpdateCharacter(Option<Character>),
     RedetectMinimap,
+    TestMovement(bool),
+    RecordPlatforms(bool),
+    ClearRecordedPlatforms,
+    DetectPlatforms,
+    RecordNavigation(bool),
+    RecordKeyMacro(bool),
     GameStateReceiver,
     KeyReceiver,
     QueryCaptureHandles,
     SelectCaptureHandle(Option<usize>),
+    QueryCaptureFrame,
+    QueryCaptureWindowFrame,
+    QueryDetectionFrame,
+    QueryTickStats,
+    QueryCheckpoint,
+    ResumeCheckpoint(bool),
+    ReloadModels,
+    QueryCurrentSessionStats,
+    QueryInputEvents,
+    DumpInputEvents,
     #[cfg(debug_assertions)]
     CaptureImage(bool),
     #[cfg(debug_assertions)]
@@ -108,6 +172,8 @@ enum Request {
     RecordImages(bool),
     #[cfg(debug_assertions)]
     TestSpinRune,
+    #[cfg(debug_assertions)]
+    PlayerStateSnapshot,
 }
 
 /// Represents response to UI [`Request`].
@@ -117,16 +183,33 @@ enum Request {
 #[derive(Debug)]
 enum Response {
     RotateActions,
+    PauseActions,
     CreateMinimap(Option<Minimap>),
     UpdateMinimap,
     CreateNavigationPath(Option<NavigationPath>),
     RecaptureNavigationPath(NavigationPath),
     UpdateCharacter,
     RedetectMinimap,
+    TestMovement,
+    RecordPlatforms,
+    ClearRecordedPlatforms,
+    DetectPlatforms,
+    RecordNavigation,
+    RecordKeyMacro(Vec<Action>),
     GameStateReceiver(broadcast::Receiver<GameState>),
     KeyReceiver(broadcast::Receiver<KeyBinding>),
     QueryCaptureHandles((Vec<String>, Option<usize>)),
     SelectCaptureHandle,
+    QueryCaptureFrame(Option<(String, i32, i32)>),
+    QueryCaptureWindowFrame(Option<(String, i32, i32)>),
+    QueryDetectionFrame(Option<String>),
+    QueryTickStats(TickStats),
+    QueryCheckpoint(Option<Checkpoint>),
+    ResumeCheckpoint,
+    ReloadModels(bool),
+    QueryCurrentSessionStats(Option<SessionStats>),
+    QueryInputEvents(Vec<InputEvent>),
+    DumpInputEvents(Option<String>),
     #[cfg(debug_assertions)]
     CaptureImage,
     #[cfg(debug_assertions)]
@@ -137,12 +220,16 @@ enum Response {
     RecordImages,
     #[cfg(debug_assertions)]
     TestSpinRune,
+    #[cfg(debug_assertions)]
+    PlayerStateSnapshot(PlayerStateSnapshot),
 }
 
 /// Request handler of incoming requests from UI.
 pub(crate) trait RequestHandler {
     fn on_rotate_actions(&mut self, halting: bool);
 
+    fn on_pause_actions(&mut self, paused: bool);
+
     fn on_create_minimap(&self, name: String) -> Option<Minimap>;
 
     fn on_update_minimap(&mut self, preset: Option<String>, minimap: Option<Minimap>);
@@ -155,6 +242,18 @@ pub(crate) trait RequestHandler {
 
     fn on_redetect_minimap(&mut self);
 
+    fn on_test_movement(&mut self, start: bool);
+
+    fn on_record_platforms(&mut self, start: bool);
+
+    fn on_clear_recorded_platforms(&mut self);
+
+    fn on_detect_platforms(&mut self);
+
+    fn on_record_navigation(&mut self, start: bool);
+
+    fn on_record_key_macro(&mut self, start: bool) -> Vec<Action>;
+
     fn on_game_state_receiver(&self) -> broadcast::Receiver<GameState>;
 
     fn on_key_receiver(&self) -> broadcast::Receiver<KeyBinding>;
@@ -163,6 +262,26 @@ pub(crate) trait RequestHandler {
 
     fn on_select_capture_handle(&mut self, index: Option<usize>);
 
+    fn on_query_capture_frame(&mut self) -> Option<(String, i32, i32)>;
+
+    fn on_query_capture_window_frame(&mut self) -> Option<(String, i32, i32)>;
+
+    fn on_query_detection_frame(&self) -> Option<String>;
+
+    fn on_query_tick_stats(&self) -> TickStats;
+
+    fn on_query_checkpoint(&self) -> Option<Checkpoint>;
+
+    fn on_resume_checkpoint(&mut self, resume: bool);
+
+    fn on_reload_models(&self) -> bool;
+
+    fn on_query_current_session_stats(&self) -> Option<SessionStats>;
+
+    fn on_query_input_events(&self) -> Vec<InputEvent>;
+
+    fn on_dump_input_events(&self) -> Option<String>;
+
     #[cfg(debug_assertions)]
     fn on_capture_image(&self, is_grayscale: bool);
 
@@ -177,6 +296,9 @@ pub(crate) trait RequestHandler {
 
     #[cfg(debug_assertions)]
     fn on_test_spin_rune(&self);
+
+    #[cfg(debug_assertions)]
+    fn on_player_state_snapshot(&self) -> PlayerStateSnapshot;
 }
 
 /// The four quads of a bound.
@@ -199,10 +321,192 @@ pub struct GameState {
     pub erda_shower_state: String,
     pub destinations: Vec<(i32, i32)>,
     pub operation: GameOperation,
+    /// The measured number of ticks per second.
+    pub fps: f32,
     pub frame: Option<(Vec<u8>, usize, usize)>,
     pub platforms_bound: Option<Bound>,
     pub portals: Vec<Bound>,
+    /// The rune position in player-relative coordinate.
+    pub rune: Option<(i32, i32)>,
     pub auto_mob_quadrant: Option<BoundQuadrant>,
+    /// Whether the panic key was recently triggered.
+    pub panic_key_triggered: bool,
+    /// Transitions of the last or currently running movement test.
+    pub movement_test: Vec<MovementTestTransition>,
+    /// Whether platforms are being recorded from the player movement.
+    pub recording_platforms: bool,
+    /// Platforms proposed from the last or currently running platforms recording.
+    pub recorded_platforms: Vec<Platform>,
+    /// Whether a navigation route is being recorded from the player moving through maps.
+    pub recording_navigation: bool,
+    /// Whether the keys pressed by the user are being recorded as a combo.
+    pub recording_key_macro: bool,
+    /// Statistics of the current preset's actions keyed by the action index.
+    pub action_stats: HashMap<usize, ActionStats>,
+    /// The current preset's required buffs found missing when the actions were last started.
+    pub missing_required_buffs: Vec<String>,
+    /// The estimated remaining durations of the buffs with known durations.
+    pub buffs_remaining_millis: Vec<(String, u64)>,
+    /// The queued priority actions in order followed by the scheduled ones by their ETA.
+    pub queued_actions: Vec<QueuedAction>,
+    /// The id of the currently used minimap, which can be changed by auto-selection.
+    pub minimap_id: Option<i64>,
+    /// The currently used minimap preset.
+    pub minimap_preset: Option<String>,
+}
+
+/// The reason an action was aborted by the player.
+#[derive(PartialEq, Eq, Clone, Copy, Debug, Display)]
+pub enum ActionAbortReason {
+    #[strum(to_string = "Movement repeated too many times")]
+    MovementRepeated,
+    #[strum(to_string = "Position is unreachable")]
+    UnreachablePosition,
+    #[strum(to_string = "Interfered by a portal")]
+    PortalInterference,
+    #[strum(to_string = "Position is outside the map or too far from platforms")]
+    InvalidPosition,
+}
+
+/// Statistics of an action since the actions were last built.
+#[derive(PartialEq, Eq, Clone, Copy, Debug, Default)]
+pub struct ActionStats {
+    /// Number of times the action was aborted.
+    pub abort_count: u32,
+    /// The reason the action was most recently aborted.
+    pub last_abort_reason: Option<ActionAbortReason>,
+}
+
+/// A priority action queued or scheduled to be queued by the rotator.
+#[derive(PartialEq, Clone, Debug)]
+pub struct QueuedAction {
+    /// The rotator id of the action.
+    pub id: u32,
+    pub name: String,
+    /// The condition of the action if it is a user action.
+    pub condition: Option<ActionCondition>,
+    /// The estimated milliseconds until the action is queued.
+    ///
+    /// This is `Some(0)` if the action is already queued and `None` if it cannot be estimated.
+    pub eta_millis: Option<u64>,
+}
+
+/// Timings of the update loop over the recent ticks.
+#[derive(PartialEq, Clone, Debug, Default)]
+pub struct TickStats {
+    /// Timings of each stage in the order they run in a tick.
+    pub stages: Vec<TickStageStats>,
+    /// The average milliseconds taken by a tick.
+    pub average_millis: f32,
+    /// The maximum milliseconds taken by a tick.
+    pub max_millis: f32,
+    /// The milliseconds a tick can take before it runs late.
+    pub budget_millis: f32,
+    /// Number of recent ticks the statistics are computed from.
+    pub ticks: u32,
+    /// Number of recent ticks that took longer than [`Self::budget_millis`].
+    pub late_ticks: u32,
+}
+
+/// Timings of a single stage in the update loop.
+#[derive(PartialEq, Clone, Debug, Default)]
+pub struct TickStageStats {
+    pub name: String,
+    /// The average milliseconds taken by the stage in a tick.
+    pub average_millis: f32,
+    /// The maximum milliseconds taken by the stage in a tick.
+    pub max_millis: f32,
+}
+
+/// The movement expected to be performed by a [`MovementTestTransition`].
+#[derive(PartialEq, Eq, Clone, Copy, Debug, Display)]
+pub enum MovementTestKind {
+    Walk,
+    DoubleJump,
+    UpJump,
+    Grapple,
+    Drop,
+}
+
+/// The result of a [`MovementTestTransition`].
+#[derive(PartialEq, Eq, Clone, Copy, Debug, Display)]
+pub enum MovementTestResult {
+    Pending,
+    Testing,
+    Succeeded,
+    Failed,
+    /// The player could not reach the start of the transition.
+    Skipped,
+}
+
+/// A transition between two platforms tested by the movement test.
+#[derive(PartialEq, Eq, Clone, Debug)]
+pub struct MovementTestTransition {
+    pub from: (i32, i32),
+    pub to: (i32, i32),
+    pub kind: MovementTestKind,
+    pub result: MovementTestResult,
+}
+
+/// A compact snapshot of a single tick recorded by the flight recorder.
+#[derive(PartialEq, Clone, Debug, Serialize, Deserialize)]
+pub struct StateSnapshot {
+    pub tick: u64,
+    /// Milliseconds since Unix epoch when this snapshot was recorded.
+    pub millis: u64,
+    pub operation: String,
+    pub state: String,
+    pub position: Option<(i32, i32)>,
+    pub normal_action: Option<String>,
+    pub priority_action: Option<String>,
+    /// Keys sent or held down during this tick.
+    pub keys: Vec<String>,
+}
+
+/// The kind of an [`InputEvent`].
+#[derive(PartialEq, Clone, Copy, Debug, Serialize, Deserialize, Display)]
+pub enum InputEventKind {
+    /// A key pressed and released after a random delay.
+    #[strum(to_string = "Press")]
+    KeyPress,
+    #[strum(to_string = "Down")]
+    KeyDown,
+    #[strum(to_string = "Up")]
+    KeyUp,
+    Mouse,
+}
+
+/// A key or mouse input sent by the bot.
+#[derive(PartialEq, Clone, Debug, Serialize, Deserialize)]
+pub struct InputEvent {
+    /// Milliseconds since Unix epoch when the input was sent.
+    pub millis: u64,
+    pub tick: u64,
+    pub kind: InputEventKind,
+    /// The key or the mouse action and position.
+    pub input: String,
+    /// The player state when the input was sent.
+    pub state: String,
+    /// The id of the action the player was doing, priority action first.
+    pub action_id: Option<u32>,
+    pub action: Option<String>,
+}
+
+/// A snapshot of the player internal states for debugging.
+#[derive(PartialEq, Clone, Debug, Default, Serialize, Deserialize)]
+pub struct PlayerStateSnapshot {
+    /// The current contextual state.
+    pub state: String,
+    pub last_movement: Option<String>,
+    pub normal_action_id: Option<u32>,
+    pub priority_action_id: Option<u32>,
+    pub velocity: (f32, f32),
+    pub unstuck_count: u32,
+    pub unstuck_transitioned_count: u32,
+    /// Auto-mobbing reachable y to the number of times it has been reached.
+    pub reachable_y_map: HashMap<i32, u32>,
+    /// Auto-mobbing reachable y to the ignored x ranges and their ignored counts.
+    pub ignore_xs_map: HashMap<i32, Vec<((i32, i32), u32)>>,
 }
 
 #[derive(PartialEq, Clone, Copy, Debug)]
@@ -211,6 +515,7 @@ pub enum GameOperation {
     HaltUntil(Instant),
     Running,
     RunUntil(Instant),
+    Paused,
 }
 
 /// Starts or stops rotating the actions.
@@ -221,6 +526,17 @@ pub async fn rotate_actions(halting: bool) {
     )
 }
 
+/// Pauses or resumes the running actions.
+///
+/// Unlike [`rotate_actions`], the queued actions and the player's current action are kept while
+/// paused and continued on resume.
+pub async fn pause_actions(paused: bool) {
+    expect_unit_variant!(
+        request(Request::PauseActions(paused)).await,
+        Response::PauseActions
+    )
+}
+
 /// Queries settings from the database.
 pub async fn query_settings() -> Settings {
     spawn_blocking(database::query_settings).await.unwrap()
@@ -238,6 +554,11 @@ pub async fn upsert_settings(mut settings: Settings) -> Settings {
     .unwrap()
 }
 
+/// Loads the snapshots recorded by the flight recorder from the oldest to the newest.
+pub async fn query_state_snapshots() -> Vec<StateSnapshot> {
+    spawn_blocking(recorder::load_snapshots).await.unwrap()
+}
+
 /// Queries minimaps from the database.
 pub async fn query_minimaps() -> Option<Vec<Minimap>> {
     spawn_blocking(database::query_minimaps).await.unwrap().ok()
@@ -258,8 +579,32 @@ pub async fn create_minimap(name: String) -> Option<Minimap> {
 /// If `minimap` does not previously exist, a new one will be created and its `id` will
 /// be updated.
 ///
+/// Writes to an existing minimap are coalesced. Each write waits for [`MINIMAP_WRITE_DEBOUNCE`]
+/// and only the last write within that window is persisted. Earlier writes are superseded and
+/// return the last written minimap instead.
+///
 /// Returns the updated [`Minimap`] on success.
 pub async fn upsert_minimap(mut minimap: Minimap) -> Option<Minimap> {
+    if let Some(id) = minimap.id {
+        let generation = {
+            let mut writes = PENDING_MINIMAP_WRITES.lock().unwrap();
+            let generation = writes.get(&id).map(|(generation, _)| generation + 1);
+            let generation = generation.unwrap_or_default();
+            writes.insert(id, (generation, minimap));
+            generation
+        };
+        sleep(MINIMAP_WRITE_DEBOUNCE).await;
+
+        let mut writes = PENDING_MINIMAP_WRITES.lock().unwrap();
+        let (latest, latest_minimap) = writes.get(&id).cloned()?;
+        if latest != generation {
+            return Some(latest_minimap);
+        }
+        writes.remove(&id);
+        drop(writes);
+        minimap = latest_minimap;
+    }
+
     spawn_blocking(move || {
         database::upsert_minimap(&mut minimap)
             .is_ok()
@@ -387,6 +732,66 @@ pub async fn redetect_minimap() {
     )
 }
 
+/// Starts or stops testing the movement between platforms of the current map.
+///
+/// The test only runs while actions are halting.
+pub async fn test_movement(start: bool) {
+    expect_unit_variant!(
+        request(Request::TestMovement(start)).await,
+        Response::TestMovement
+    )
+}
+
+/// Starts or stops recording platforms from the player movement.
+///
+/// Starting discards the previously recorded platforms. The recorded platforms are kept after
+/// stopping until cleared.
+pub async fn record_platforms(start: bool) {
+    expect_unit_variant!(
+        request(Request::RecordPlatforms(start)).await,
+        Response::RecordPlatforms
+    )
+}
+
+/// Discards the recorded platforms.
+pub async fn clear_recorded_platforms() {
+    expect_unit_variant!(
+        request(Request::ClearRecordedPlatforms).await,
+        Response::ClearRecordedPlatforms
+    )
+}
+
+/// Detects platforms from the current minimap image.
+///
+/// The detected platforms are added to the recorded platforms for review.
+pub async fn detect_platforms() {
+    expect_unit_variant!(
+        request(Request::DetectPlatforms).await,
+        Response::DetectPlatforms
+    )
+}
+
+/// Starts or stops recording a navigation route from the player moving through maps.
+///
+/// Stopping saves the recorded route as navigation paths linked in the visited order.
+pub async fn record_navigation(start: bool) {
+    expect_unit_variant!(
+        request(Request::RecordNavigation(start)).await,
+        Response::RecordNavigation
+    )
+}
+
+/// Starts or stops recording the keys pressed by the user as a combo.
+///
+/// Stopping returns the recorded keys as an action followed by linked actions with the waits
+/// between the keys. Starting returns no action.
+pub async fn record_key_macro(start: bool) -> Vec<Action> {
+    expect_value_variant!(
+        request(Request::RecordKeyMacro(start)).await,
+        Response::RecordKeyMacro
+    )
+}
+
 pub async fn game_state_receiver() -> broadcast::Receiver<GameState> {
     expect_value_variant!(
         request(Request::GameStateReceiver).await,
@@ -412,6 +817,115 @@ pub async fn select_capture_handle(index: Option<usize>) {
     )
 }
 
+/// Queries the uncropped frame currently captured as a base64 PNG along with its width and height.
+pub async fn query_capture_frame() -> Option<(String, i32, i32)> {
+    expect_value_variant!(
+        request(Request::QueryCaptureFrame).await,
+        Response::QueryCaptureFrame
+    )
+}
+
+/// Queries the client area of the selected handle as seen on screen as a base64 PNG along with
+/// its width and height.
+///
+/// This is used for selecting the area to capture in [`CaptureMode::BitBltArea`].
+pub async fn query_capture_window_frame() -> Option<(String, i32, i32)> {
+    expect_value_variant!(
+        request(Request::QueryCaptureWindowFrame).await,
+        Response::QueryCaptureWindowFrame
+    )
+}
+
+/// Queries the last frame used for detection annotated with the detected bounding boxes as a
+/// base64 PNG.
+///
+/// Returns [`None`] if no frame has been captured yet.
+pub async fn query_detection_frame() -> Option<String> {
+    expect_value_variant!(
+        request(Request::QueryDetectionFrame).await,
+        Response::QueryDetectionFrame
+    )
+}
+
+/// Queries the timings of the update loop stages over the recent ticks.
+pub async fn query_tick_stats() -> TickStats {
+    expect_value_variant!(
+        request(Request::QueryTickStats).await,
+        Response::QueryTickStats
+    )
+}
+
+/// Queries the recent inputs sent by the bot from the oldest to the newest.
+pub async fn query_input_events() -> Vec<InputEvent> {
+    expect_value_variant!(
+        request(Request::QueryInputEvents).await,
+        Response::QueryInputEvents
+    )
+}
+
+/// Dumps the recent inputs sent by the bot to a file in the `recordings` folder.
+///
+/// Returns the path of the file or [`None`] if writing fails.
+pub async fn dump_input_events() -> Option<String> {
+    expect_value_variant!(
+        request(Request::DumpInputEvents).await,
+        Response::DumpInputEvents
+    )
+}
+
+/// Queries the checkpoint left from the last run that has not been resumed or discarded.
+///
+/// A checkpoint is left only if the last run exited while the actions were running (e.g. a
+/// crash or restart).
+pub async fn query_checkpoint() -> Option<Checkpoint> {
+    expect_value_variant!(
+        request(Request::QueryCheckpoint).await,
+        Response::QueryCheckpoint
+    )
+}
+
+/// Resumes the actions from the checkpoint left from the last run or discards it.
+///
+/// The minimap and character of the checkpoint must already be in use for resuming.
+pub async fn resume_checkpoint(resume: bool) {
+    expect_unit_variant!(
+        request(Request::ResumeCheckpoint(resume)).await,
+        Response::ResumeCheckpoint
+    )
+}
+
+/// Reloads the rune arrows and mob detection models from the current [`Settings::models`].
+///
+/// Returns `true` if all the user-provided models were loaded.
+pub async fn reload_models() -> bool {
+    expect_value_variant!(request(Request::ReloadModels).await, Response::ReloadModels)
+}
+
+/// Queries the statistics of the current session if the actions are running.
+pub async fn query_current_session_stats() -> Option<SessionStats> {
+    expect_value_variant!(
+        request(Request::QueryCurrentSessionStats).await,
+        Response::QueryCurrentSessionStats
+    )
+}
+
+/// Queries the statistics of the recently completed sessions from the database.
+pub async fn query_session_stats() -> Option<Vec<SessionStats>> {
+    spawn_blocking(database::query_session_stats)
+        .await
+        .unwrap()
+        .ok()
+}
+
+/// Deletes the statistics of all completed sessions from the database.
+///
+/// Returns `true` if the statistics were deleted.
+pub async fn delete_session_stats() -> bool {
+    spawn_blocking(|| database::delete_session_stats().is_ok())
+        .await
+        .unwrap()
+}
+
 #[cfg(debug_assertions)]
 pub async fn capture_image(is_grayscale: bool) {
     expect_unit_variant!(
@@ -443,6 +957,15 @@ pub async fn test_spin_rune() {
     expect_unit_variant!(request(Request::TestSpinRune).await, Response::TestSpinRune)
 }
 
+/// Takes a snapshot of the player internal states.
+#[cfg(debug_assertions)]
+pub async fn player_state_snapshot() -> PlayerStateSnapshot {
+    expect_value_variant!(
+        request(Request::PlayerStateSnapshot).await,
+        Response::PlayerStateSnapshot
+    )
+}
+
 pub(crate) fn poll_request(handler: &mut dyn RequestHandler) {
     if let Ok((request, sender)) = LazyLock::force(&REQUESTS).1.lock().unwrap().try_recv() {
         let result = match request {
@@ -450,6 +973,10 @@ pub(crate) fn poll_request(handler: &mut dyn RequestHandler) {
                 handler.on_rotate_actions(halting);
                 Response::RotateActions
             }
+            Request::PauseActions(paused) => {
+                handler.on_pause_actions(paused);
+                Response::PauseActions
+            }
             Request::CreateMinimap(name) => {
                 Response::CreateMinimap(handler.on_create_minimap(name))
             }
@@ -471,6 +998,29 @@ pub(crate) fn poll_request(handler: &mut dyn RequestHandler) {
                 handler.on_redetect_minimap();
                 Response::RedetectMinimap
             }
+            Request::TestMovement(start) => {
+                handler.on_test_movement(start);
+                Response::TestMovement
+            }
+            Request::RecordPlatforms(start) => {
+                handler.on_record_platforms(start);
+                Response::RecordPlatforms
+            }
+            Request::ClearRecordedPlatforms => {
+                handler.on_clear_recorded_platforms();
+                Response::ClearRecordedPlatforms
+            }
+            Request::DetectPlatforms => {
+                handler.on_detect_platforms();
+                Response::DetectPlatforms
+            }
+            Request::RecordNavigation(start) => {
+                handler.on_record_navigation(start);
+                Response::RecordNavigation
+            }
+            Request::RecordKeyMacro(start) => {
+                Response::RecordKeyMacro(handler.on_record_key_macro(start))
+            }
             Request::GameStateReceiver => {
                 Response::GameStateReceiver(handler.on_game_state_receiver())
             }
@@ -482,6 +1032,29 @@ pub(crate) fn poll_request(handler: &mut dyn RequestHandler) {
                 handler.on_select_capture_handle(index);
                 Response::SelectCaptureHandle
             }
+            Request::QueryCaptureFrame => {
+                Response::QueryCaptureFrame(handler.on_query_capture_frame())
+            }
+            Request::QueryCaptureWindowFrame => {
+                Response::QueryCaptureWindowFrame(handler.on_query_capture_window_frame())
+            }
+            Request::QueryDetectionFrame => {
+                Response::QueryDetectionFrame(handler.on_query_detection_frame())
+            }
+            Request::QueryTickStats => Response::QueryTickStats(handler.on_query_tick_stats()),
+            Request::QueryCheckpoint => Response::QueryCheckpoint(handler.on_query_checkpoint()),
+            Request::ResumeCheckpoint(resume) => {
+                handler.on_resume_checkpoint(resume);
+                Response::ResumeCheckpoint
+            }
+            Request::ReloadModels => Response::ReloadModels(handler.on_reload_models()),
+            Request::QueryCurrentSessionStats => {
+                Response::QueryCurrentSessionStats(handler.on_query_current_session_stats())
+            }
+            Request::QueryInputEvents => {
+                Response::QueryInputEvents(handler.on_query_input_events())
+            }
+            Request::DumpInputEvents => Response::DumpInputEvents(handler.on_dump_input_events()),
             #[cfg(debug_assertions)]
             Request::CaptureImage(is_grayscale) => {
                 handler.on_capture_image(is_grayscale);
@@ -507,6 +1080,10 @@ pub(crate) fn poll_request(handler: &mut dyn RequestHandler) {
                 handler.on_test_spin_rune();
                 Response::TestSpinRune
             }
+            #[cfg(debug_assertions)]
+            Request::PlayerStateSnapshot => {
+                Response::PlayerStateSnapshot(handler.on_player_state_snapshot())
+            }
         };
         let _ = sender.send(result);
     }
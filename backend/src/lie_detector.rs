@@ -0,0 +1,77 @@
+use anyhow::{Result, anyhow};
+use log::info;
+
+use crate::{
+    context::Context,
+    network::NotificationKind,
+    task::{Task, Update, update_detection_task},
+};
+
+/// The delay between each lie detector detection.
+const DETECT_DELAY_MILLIS: u64 = 1000;
+
+/// Watches for the lie detector window so that the actions can be paused while it is answered
+/// manually.
+#[derive(Debug, Default)]
+pub struct LieDetectorWatcher {
+    task: Option<Task<Result<()>>>,
+    /// Whether the lie detector window was visible in the last detection.
+    visible: bool,
+}
+
+impl LieDetectorWatcher {
+    /// Detects the lie detector window and sends a notification when it appears.
+    ///
+    /// Returns `true` if the running actions should be paused.
+    pub fn update(&mut self, context: &Context, enabled: bool) -> bool {
+        if !enabled || context.detector.is_none() {
+            self.visible = false;
+            return false;
+        }
+
+        match update_detection_task(context, DETECT_DELAY_MILLIS, &mut self.task, |detector| {
+            if detector.detect_lie_detector() {
+                Ok(())
+            } else {
+                Err(anyhow!("lie detector not found"))
+            }
+        }) {
+            Update::Ok(_) => {
+                if self.track(true) {
+                    info!(target: "lie_detector", "sending lie detector notification...");
+                    let _ = context
+                        .notification
+                        .schedule_notification(NotificationKind::LieDetectorAppear);
+                }
+            }
+            Update::Err(_) => {
+                self.track(false);
+            }
+            Update::Pending => (),
+        }
+
+        self.visible && !context.operation.halting() && !context.operation.paused()
+    }
+
+    /// Tracks whether the lie detector window is `visible` and returns `true` if it just appeared.
+    fn track(&mut self, visible: bool) -> bool {
+        let appeared = visible && !self.visible;
+        self.visible = visible;
+        appeared
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn track_appear_once() {
+        let mut watcher = LieDetectorWatcher::default();
+
+        assert!(watcher.track(true));
+        assert!(!watcher.track(true));
+        assert!(!watcher.track(false));
+        assert!(watcher.track(true));
+    }
+}
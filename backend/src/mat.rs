@@ -2,7 +2,7 @@ use std::ffi::c_void;
 
 use opencv::{
     boxed_ref::BoxedRef,
-    core::{_InputArray, CV_8UC4, Mat, MatTraitConst, ToInputArray},
+    core::{_InputArray, CV_8UC4, Mat, MatTraitConst, Rect, ToInputArray},
 };
 use platforms::windows::Frame;
 
@@ -20,6 +20,33 @@ impl OwnedMat {
         Self::new_from_bytes(frame.data, frame.width, frame.height, CV_8UC4)
     }
 
+    /// Creates a new mat from `frame` cropped to `crop`.
+    ///
+    /// The `crop` is clamped to the frame size. The whole frame is used if the clamped `crop` is
+    /// empty.
+    pub fn new_from_frame_cropped(frame: Frame, crop: Rect) -> Self {
+        let frame_rect = Rect::new(0, 0, frame.width, frame.height);
+        let crop = crop & frame_rect;
+        if crop.empty() || crop == frame_rect {
+            return Self::new_from_frame(frame);
+        }
+
+        let stride = frame.width as usize * 4;
+        let row_start = crop.x as usize * 4;
+        let row_len = crop.width as usize * 4;
+        let mut data = Vec::with_capacity(row_len * crop.height as usize);
+        for row in frame
+            .data
+            .chunks_exact(stride)
+            .skip(crop.y as usize)
+            .take(crop.height as usize)
+        {
+            data.extend_from_slice(&row[row_start..row_start + row_len]);
+        }
+
+        Self::new_from_bytes(data, crop.width, crop.height, CV_8UC4)
+    }
+
     #[inline]
     fn new_from_bytes(data: Vec<u8>, width: i32, height: i32, cv_type: i32) -> Self {
         let mat = BoxedRef::from(unsafe {
@@ -1,7 +1,7 @@
 use std::{
     collections::{HashMap, HashSet},
     fmt,
-    hash::{Hash, Hasher},
+    hash::{DefaultHasher, Hash, Hasher},
 };
 
 use anyhow::{Result, anyhow};
@@ -17,6 +17,7 @@ use crate::{
         MAX_PLATFORMS_COUNT, Platform, PlatformWithNeighbors, find_neighbors, find_platforms_bound,
     },
     player::{DOUBLE_JUMP_THRESHOLD, GRAPPLING_MAX_THRESHOLD, JUMP_THRESHOLD, Player},
+    screenshot::ScreenshotEvent,
     task::{Task, Update, update_detection_task},
 };
 
@@ -54,18 +55,50 @@ pub struct MinimapState {
     portals_invalidate_map: HashMap<HashedRect, u32>,
     /// Task to detect elite boss.
     has_elite_boss_task: Option<Task<Result<()>>>,
+    /// Task to detect the inventory full message.
+    has_inventory_full_task: Option<Task<Result<()>>>,
     /// Task to detect guildie player(s) in the minimap.
     has_guildie_player_task: Option<Task<Result<()>>>,
     /// Task to detect stranger player(s) in the minimap.
     has_stranger_player_task: Option<Task<Result<()>>>,
     /// Task to detect firend player(s) in the minimap.
     has_friend_player_task: Option<Task<Result<()>>>,
+    /// Whether guildie player(s) are ignored.
+    ///
+    /// Ignored players are not detected and therefore neither notified nor counted in
+    /// [`MinimapIdle::has_any_other_player`].
+    ignore_guildie_player: bool,
+    /// Whether friend player(s) are ignored.
+    ignore_friend_player: bool,
+
+    /// Portals saved in the minimap data.
+    ///
+    /// When not empty, these are used as [`MinimapIdle::portals`] and portals are no longer
+    /// detected. This keeps manually deleted false-positive portals from being detected again.
+    saved_portals: Array<Rect, MAX_PORTALS_COUNT>,
+    /// Whether to update the [`MinimapIdle::portals`] from [`Self::saved_portals`].
+    ///
+    /// This is set to true each time [`Self::saved_portals`] is updated.
+    portals_dirty: bool,
 
     platforms: Vec<Platform>,
     /// Whether to update the [`MinimapIdle::platforms`].
     ///
     /// This is set to true each time [`Self::data`] is updated.
     platforms_dirty: bool,
+    /// Cached [`MinimapIdle::platforms`] and [`MinimapIdle::platforms_bound`] keyed by the hash
+    /// of [`Self::platforms`] and the minimap bounding box.
+    ///
+    /// This avoids rebuilding the platforms graph when the minimap is re-detected (e.g. after
+    /// being occluded by other UIs) but neither the platforms nor the bounding box has changed.
+    platforms_cache: Option<PlatformsCache>,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct PlatformsCache {
+    key: u64,
+    platforms: Array<PlatformWithNeighbors, MAX_PLATFORMS_COUNT>,
+    bound: Option<Rect>,
 }
 
 impl MinimapState {
@@ -73,6 +106,18 @@ impl MinimapState {
         self.platforms = platforms;
         self.platforms_dirty = true;
     }
+
+    /// Sets the saved `portals` in player-relative coordinate.
+    pub fn set_portals(&mut self, portals: Vec<Rect>) {
+        self.saved_portals = portals.into_iter().take(MAX_PORTALS_COUNT).collect();
+        self.portals_dirty = true;
+    }
+
+    /// Sets whether guildie and friend player(s) are ignored (e.g. known alts or mules).
+    pub fn set_ignored_other_players(&mut self, guildie: bool, friend: bool) {
+        self.ignore_guildie_player = guildie;
+        self.ignore_friend_player = friend;
+    }
 }
 
 #[derive(Clone, Copy, Debug)]
@@ -124,6 +169,10 @@ pub struct MinimapIdle {
     ///
     /// TODO: This does not belong to minimap.
     has_elite_boss: Threshold<()>,
+    /// Whether the inventory is full.
+    ///
+    /// TODO: This does not belong to minimap.
+    has_inventory_full: Threshold<()>,
     /// Whether there is a guildie.
     has_guildie_player: Threshold<()>,
     /// Whether there is a stranger.
@@ -155,6 +204,11 @@ impl MinimapIdle {
         self.rune.value = Some(rune);
     }
 
+    #[cfg(test)]
+    pub fn set_has_stranger_player(&mut self) {
+        self.has_stranger_player.value = Some(());
+    }
+
     #[inline]
     pub fn portals(&self) -> Array<Rect, MAX_PORTALS_COUNT> {
         self.portals
@@ -165,6 +219,21 @@ impl MinimapIdle {
         self.has_elite_boss.value.is_some()
     }
 
+    #[inline]
+    pub fn has_inventory_full(&self) -> bool {
+        self.has_inventory_full.value.is_some()
+    }
+
+    #[cfg(test)]
+    pub fn set_has_inventory_full(&mut self) {
+        self.has_inventory_full.value = Some(());
+    }
+
+    #[inline]
+    pub fn has_stranger_player(&self) -> bool {
+        self.has_stranger_player.value.is_some()
+    }
+
     #[inline]
     pub fn has_any_other_player(&self) -> bool {
         self.has_guildie_player.value.is_some()
@@ -227,12 +296,14 @@ fn update_detecting_context(context: &Context, state: &mut MinimapState) -> Mini
         return Minimap::Detecting;
     };
 
-    let (platforms, platforms_bound) = platforms_and_bound(bbox, &state.platforms);
+    let (platforms, platforms_bound) = platforms_and_bound(state, bbox);
     state.platforms_dirty = false;
+    state.portals_dirty = false;
     state.rune_task = None;
     state.portals_task = None;
     state.portals_invalidate_map.clear();
     state.has_elite_boss_task = None;
+    state.has_inventory_full_task = None;
     state.has_guildie_player_task = None;
     state.has_stranger_player_task = None;
     state.has_friend_player_task = None;
@@ -243,10 +314,11 @@ fn update_detecting_context(context: &Context, state: &mut MinimapState) -> Mini
         partially_overlapping: false,
         rune: Threshold::new(3),
         has_elite_boss: Threshold::new(2),
+        has_inventory_full: Threshold::new(2),
         has_guildie_player: Threshold::new(2),
         has_stranger_player: Threshold::new(2),
         has_friend_player: Threshold::new(2),
-        portals: Array::new(),
+        portals: state.saved_portals,
         platforms,
         platforms_bound,
     })
@@ -266,6 +338,7 @@ fn update_idle_context(
         bbox,
         rune,
         has_elite_boss,
+        has_inventory_full,
         has_guildie_player,
         has_stranger_player,
         has_friend_player,
@@ -292,12 +365,18 @@ fn update_idle_context(
     let rune = update_rune_task(context, &mut state.rune_task, bbox, rune);
     let has_elite_boss =
         update_elite_boss_task(context, &mut state.has_elite_boss_task, has_elite_boss);
+    let has_inventory_full = update_inventory_full_task(
+        context,
+        &mut state.has_inventory_full_task,
+        has_inventory_full,
+    );
     let has_guildie_player = update_other_player_task(
         context,
         &mut state.has_guildie_player_task,
         bbox,
         has_guildie_player,
         OtherPlayerKind::Guildie,
+        state.ignore_guildie_player,
     );
     let has_stranger_player = update_other_player_task(
         context,
@@ -305,6 +384,7 @@ fn update_idle_context(
         bbox,
         has_stranger_player,
         OtherPlayerKind::Stranger,
+        false,
     );
     let has_friend_player = update_other_player_task(
         context,
@@ -312,17 +392,27 @@ fn update_idle_context(
         bbox,
         has_friend_player,
         OtherPlayerKind::Friend,
+        state.ignore_friend_player,
     );
-    let portals = update_portals_task(
-        context,
-        &mut state.portals_task,
-        &mut state.portals_invalidate_map,
-        portals,
-        bbox,
-    );
+    let portals = if state.portals_dirty {
+        state.portals_dirty = false;
+        state.portals_task = None;
+        state.portals_invalidate_map.clear();
+        state.saved_portals
+    } else if state.saved_portals.is_empty() {
+        update_portals_task(
+            context,
+            &mut state.portals_task,
+            &mut state.portals_invalidate_map,
+            portals,
+            bbox,
+        )
+    } else {
+        portals
+    };
 
     if state.platforms_dirty {
-        let (updated_platforms, updated_bound) = platforms_and_bound(bbox, &state.platforms);
+        let (updated_platforms, updated_bound) = platforms_and_bound(state, bbox);
         platforms = updated_platforms;
         platforms_bound = updated_bound;
         state.platforms_dirty = false;
@@ -332,6 +422,7 @@ fn update_idle_context(
         partially_overlapping,
         rune,
         has_elite_boss,
+        has_inventory_full,
         has_guildie_player,
         has_stranger_player,
         has_friend_player,
@@ -371,7 +462,11 @@ fn update_rune_task(
             .map(|rune| center_of_bbox(rune, minimap))
     });
 
-    if was_none && rune.value.is_some() && !context.operation.halting() {
+    if was_none
+        && rune.value.is_some()
+        && !context.operation.halting()
+        && !context.operation.paused()
+    {
         info!(target: "minimap", "sending notification for rune...");
         let _ = context
             .notification
@@ -396,7 +491,11 @@ fn update_elite_boss_task(
             }
         });
 
-    if !context.operation.halting() && !did_have_elite_boss && has_elite_boss.value.is_some() {
+    if !context.operation.halting()
+        && !context.operation.paused()
+        && !did_have_elite_boss
+        && has_elite_boss.value.is_some()
+    {
         info!(target: "minimap", "sending elite boss notification...");
         let _ = context
             .notification
@@ -405,6 +504,35 @@ fn update_elite_boss_task(
     has_elite_boss
 }
 
+#[inline]
+fn update_inventory_full_task(
+    context: &Context,
+    task: &mut Option<Task<Result<()>>>,
+    has_inventory_full: Threshold<()>,
+) -> Threshold<()> {
+    let did_have_inventory_full = has_inventory_full.value.is_some();
+    let has_inventory_full =
+        update_threshold_detection(context, 5000, has_inventory_full, task, move |detector| {
+            if detector.detect_inventory_full() {
+                Ok(())
+            } else {
+                Err(anyhow!("no inventory full detected"))
+            }
+        });
+
+    if !context.operation.halting()
+        && !context.operation.paused()
+        && !did_have_inventory_full
+        && has_inventory_full.value.is_some()
+    {
+        info!(target: "minimap", "sending inventory full notification...");
+        let _ = context
+            .notification
+            .schedule_notification(NotificationKind::InventoryFull);
+    }
+    has_inventory_full
+}
+
 #[inline]
 fn update_other_player_task(
     context: &Context,
@@ -412,7 +540,13 @@ fn update_other_player_task(
     minimap: Rect,
     threshold: Threshold<()>,
     kind: OtherPlayerKind,
+    ignored: bool,
 ) -> Threshold<()> {
+    if ignored {
+        *task = None;
+        return Threshold::new(threshold.max_fail_count);
+    }
+
     let has_player = threshold.value.is_some();
     let threshold = update_threshold_detection(context, 3000, threshold, task, move |detector| {
         if detector.detect_player_kind(minimap, kind) {
@@ -421,7 +555,11 @@ fn update_other_player_task(
             Err(anyhow!("player not found"))
         }
     });
-    if !context.operation.halting() && !has_player && threshold.value.is_some() {
+    if !context.operation.halting()
+        && !context.operation.paused()
+        && !has_player
+        && threshold.value.is_some()
+    {
         info!(target: "minimap", "sending {kind:?} notification...");
         let notification = match kind {
             OtherPlayerKind::Guildie => NotificationKind::PlayerGuildieAppear,
@@ -429,6 +567,11 @@ fn update_other_player_task(
             OtherPlayerKind::Friend => NotificationKind::PlayerFriendAppear,
         };
         let _ = context.notification.schedule_notification(notification);
+        if matches!(kind, OtherPlayerKind::Stranger) {
+            context
+                .screenshots
+                .schedule(ScreenshotEvent::StrangerAppeared);
+        }
     }
     threshold
 }
@@ -507,17 +650,36 @@ fn merge_portals_and_invalidate_if_needed(
     Array::from_iter(merged_portals.into_iter().map(|portal| portal.inner))
 }
 
+/// Builds the platforms graph and bound from [`MinimapState::platforms`].
+///
+/// Returns the cached result if both the platforms and `bbox` are unchanged since the last build.
 fn platforms_and_bound(
+    state: &mut MinimapState,
     bbox: Rect,
-    platforms: &[Platform],
 ) -> (Array<PlatformWithNeighbors, 24>, Option<Rect>) {
+    let mut hasher = DefaultHasher::new();
+    state.platforms.hash(&mut hasher);
+    HashedRect { inner: bbox }.hash(&mut hasher);
+    let key = hasher.finish();
+    if let Some(cache) = state.platforms_cache
+        && cache.key == key
+    {
+        return (cache.platforms, cache.bound);
+    }
+
     let platforms = Array::from_iter(find_neighbors(
-        platforms,
+        &state.platforms,
         DOUBLE_JUMP_THRESHOLD,
         JUMP_THRESHOLD,
         GRAPPLING_MAX_THRESHOLD,
     ));
     let bound = find_platforms_bound(bbox, &platforms);
+    debug!(target: "minimap", "rebuilt platforms graph with {} platforms", platforms.len());
+    state.platforms_cache = Some(PlatformsCache {
+        key,
+        platforms,
+        bound,
+    });
     (platforms, bound)
 }
 
@@ -689,6 +851,7 @@ mod tests {
                 assert_matches!(state.minimap_task, Some(_));
                 assert_matches!(state.rune_task, None);
                 assert_matches!(state.has_elite_boss_task, None);
+                assert_matches!(state.has_inventory_full_task, None);
                 assert_matches!(state.has_guildie_player_task, None);
                 assert_matches!(state.has_stranger_player_task, None);
                 assert_matches!(state.has_friend_player_task, None);
@@ -699,6 +862,24 @@ mod tests {
         }
     }
 
+    #[tokio::test(start_paused = true)]
+    async fn minimap_detecting_to_idle_seeds_saved_portals() {
+        let mut state = MinimapState::default();
+        let portal = Rect::new(10, 10, 5, 5);
+        state.set_portals(vec![portal]);
+        let (detector, _, _, _) = create_mock_detector();
+
+        let minimap = advance_task(Minimap::Detecting, detector, &mut state).await;
+        assert_matches!(minimap, Minimap::Idle(_));
+        match minimap {
+            Minimap::Idle(idle) => {
+                assert_eq!(idle.portals, Array::from_iter([portal]));
+                assert!(!state.portals_dirty);
+            }
+            _ => unreachable!(),
+        }
+    }
+
     #[tokio::test(start_paused = true)]
     async fn minimap_idle_rune_detection() {
         let mut state = MinimapState::default();
@@ -710,6 +891,7 @@ mod tests {
             partially_overlapping: false,
             rune: Threshold::new(3),
             has_elite_boss: Threshold::default(),
+            has_inventory_full: Threshold::default(),
             has_guildie_player: Threshold::default(),
             has_stranger_player: Threshold::default(),
             has_friend_player: Threshold::default(),
@@ -728,6 +910,27 @@ mod tests {
         }
     }
 
+    #[test]
+    fn platforms_and_bound_cached_until_changed() {
+        let mut state = MinimapState::default();
+        state.set_platforms(vec![Platform::new(0..10, 10), Platform::new(20..30, 15)]);
+        let bbox = Rect::new(0, 0, 100, 100);
+
+        let (platforms, bound) = platforms_and_bound(&mut state, bbox);
+        let key = state.platforms_cache.unwrap().key;
+        assert_eq!(platforms.len(), 2);
+        assert_eq!(platforms_and_bound(&mut state, bbox), (platforms, bound));
+        assert_eq!(state.platforms_cache.unwrap().key, key);
+
+        let (_, resized_bound) = platforms_and_bound(&mut state, Rect::new(0, 0, 100, 120));
+        assert_ne!(state.platforms_cache.unwrap().key, key);
+        assert_ne!(resized_bound, bound);
+
+        state.set_platforms(vec![Platform::new(0..10, 10)]);
+        let (platforms, _) = platforms_and_bound(&mut state, bbox);
+        assert_eq!(platforms.len(), 1);
+    }
+
     fn rect(x: i32, y: i32, w: i32, h: i32) -> Rect {
         Rect::new(x, y, w, h)
     }
@@ -885,4 +1088,26 @@ mod tests {
         assert_eq!(threshold.value, None);
         assert_eq!(threshold.fail_count, 0);
     }
+
+    #[test]
+    fn update_other_player_task_ignored_clears_detection() {
+        let mut threshold = Threshold::new(2);
+        threshold.value = Some(());
+        threshold.fail_count = 1;
+        let mut task = None;
+        let context = Context::new(None, None);
+
+        let threshold = update_other_player_task(
+            &context,
+            &mut task,
+            Rect::default(),
+            threshold,
+            OtherPlayerKind::Friend,
+            true,
+        );
+
+        assert_eq!(threshold.value, None);
+        assert_eq!(threshold.fail_count, 0);
+        assert_matches!(task, None);
+    }
 }
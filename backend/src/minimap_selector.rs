@@ -0,0 +1,171 @@
+use std::time::{Duration, Instant};
+
+use log::info;
+
+use crate::{Minimap as MinimapData, context::Context, database::query_minimaps, minimap::Minimap};
+
+/// Maximum difference in width or height between the detected minimap and a saved minimap for
+/// the saved minimap to be a candidate.
+const SIZE_TOLERANCE: i32 = 2;
+
+/// Maximum duration to wait for the navigator to determine the current path when there are
+/// multiple candidates.
+const PATH_WAIT_DURATION: Duration = Duration::from_secs(10);
+
+/// Selects the saved minimap matching the detected minimap after the map changes.
+///
+/// Saved minimaps are matched by their size with the detected minimap. When multiple saved
+/// minimaps have the same size, the one with the navigation path the player is currently in is
+/// selected.
+#[derive(Debug)]
+pub struct MinimapSelector {
+    /// Whether the map has changed and a minimap is pending for selection.
+    pending: bool,
+    /// The instant the candidates were queried and the candidates of the pending selection.
+    candidates: Option<(Instant, Vec<MinimapData>)>,
+}
+
+impl Default for MinimapSelector {
+    fn default() -> Self {
+        Self {
+            pending: true,
+            candidates: None,
+        }
+    }
+}
+
+impl MinimapSelector {
+    /// Updates the pending selection and returns the saved minimap to select if any.
+    ///
+    /// The returned minimap is always different from `current`. Selection is skipped while the
+    /// actions are running and `current` has a navigation path so that the navigator can move the
+    /// player through other maps.
+    pub fn update(
+        &mut self,
+        context: &Context,
+        enabled: bool,
+        current: Option<&MinimapData>,
+        current_path_id: Option<i64>,
+    ) -> Option<MinimapData> {
+        if context.did_minimap_changed {
+            self.pending = true;
+            self.candidates = None;
+        }
+        let Minimap::Idle(idle) = context.minimap else {
+            return None;
+        };
+        if !self.pending {
+            return None;
+        }
+
+        let navigating = !context.operation.halting()
+            && current.is_some_and(|minimap| minimap.path_id.is_some());
+        if !enabled || navigating {
+            self.pending = false;
+            return None;
+        }
+
+        let (queried_at, candidates) = self.candidates.get_or_insert_with(|| {
+            let minimaps = query_minimaps().unwrap_or_default();
+            let candidates = size_candidates(minimaps, idle.bbox.width, idle.bbox.height);
+
+            (Instant::now(), candidates)
+        });
+        let index = select_candidate(candidates, current_path_id);
+        if index.is_none() && candidates.len() > 1 && queried_at.elapsed() < PATH_WAIT_DURATION {
+            return None;
+        }
+
+        let (_, mut candidates) = self.candidates.take().expect("has value");
+        self.pending = false;
+        let selected = candidates.swap_remove(index?);
+        if current.is_some_and(|minimap| minimap.id == selected.id) {
+            return None;
+        }
+        info!(target: "minimap_selector", "selected minimap {}", selected.name);
+
+        Some(selected)
+    }
+}
+
+/// Filters `minimaps` to the ones closest in size to the detected `width` and `height`.
+fn size_candidates(minimaps: Vec<MinimapData>, width: i32, height: i32) -> Vec<MinimapData> {
+    let size_diff = |minimap: &MinimapData| {
+        (minimap.width - width)
+            .abs()
+            .max((minimap.height - height).abs())
+    };
+    let min_size_diff = minimaps
+        .iter()
+        .map(size_diff)
+        .filter(|diff| *diff <= SIZE_TOLERANCE)
+        .min();
+
+    minimaps
+        .into_iter()
+        .filter(|minimap| Some(size_diff(minimap)) == min_size_diff)
+        .collect()
+}
+
+/// Selects the index of the candidate in `candidates` to select.
+///
+/// The candidate with the navigation path `current_path_id` is preferred. Otherwise, a candidate
+/// is only selected when it is the only one.
+fn select_candidate(candidates: &[MinimapData], current_path_id: Option<i64>) -> Option<usize> {
+    if let Some(path_id) = current_path_id
+        && let Some(index) = candidates
+            .iter()
+            .position(|minimap| minimap.path_id == Some(path_id))
+    {
+        return Some(index);
+    }
+
+    (candidates.len() == 1).then_some(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn mock_minimap(id: i64, width: i32, height: i32, path_id: Option<i64>) -> MinimapData {
+        MinimapData {
+            id: Some(id),
+            width,
+            height,
+            path_id,
+            ..MinimapData::default()
+        }
+    }
+
+    #[test]
+    fn size_candidates_closest_within_tolerance() {
+        let minimaps = vec![
+            mock_minimap(1, 200, 100, None),
+            mock_minimap(2, 201, 100, None),
+            mock_minimap(3, 201, 101, None),
+            mock_minimap(4, 210, 100, None),
+        ];
+
+        let candidates = size_candidates(minimaps, 201, 101)
+            .into_iter()
+            .map(|minimap| minimap.id)
+            .collect::<Vec<_>>();
+        assert_eq!(candidates, vec![Some(3)]);
+
+        let candidates = size_candidates(vec![mock_minimap(1, 200, 100, None)], 205, 100);
+        assert!(candidates.is_empty());
+    }
+
+    #[test]
+    fn select_candidate_prefer_current_path() {
+        let candidates = vec![
+            mock_minimap(1, 200, 100, Some(10)),
+            mock_minimap(2, 200, 100, Some(20)),
+        ];
+
+        assert_eq!(select_candidate(&candidates, Some(20)), Some(1));
+        assert_eq!(select_candidate(&candidates, Some(30)), None);
+        assert_eq!(select_candidate(&candidates, None), None);
+        assert_eq!(select_candidate(&candidates[..1], None), Some(0));
+    }
+}
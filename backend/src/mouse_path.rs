@@ -0,0 +1,132 @@
+use std::{f32::consts::PI, time::Duration};
+
+use crate::rng::Rng;
+
+/// The minimum and maximum milliseconds a [`MousePath`] takes.
+const MIN_MILLIS: f32 = 80.0;
+const MAX_MILLIS: f32 = 400.0;
+
+/// The milliseconds per pixel added to [`MIN_MILLIS`] so that farther targets take longer.
+const MILLIS_PER_PIXEL: f32 = 0.4;
+
+/// The maximum distance of the curve control points from the straight line relative to the
+/// distance.
+const MAX_CURVE_RATIO: f32 = 0.25;
+
+/// The maximum jitter in pixels in the middle of the path.
+const MAX_JITTER: f32 = 2.0;
+
+/// A human-like mouse path from the current position to a target.
+#[derive(Debug)]
+pub struct MousePath {
+    /// The points of the path ending exactly at the target.
+    pub points: Vec<(i32, i32)>,
+}
+
+impl MousePath {
+    /// Generates a path from `from` to `to` following a cubic Bezier curve.
+    ///
+    /// The curve bends to a random side with small jitter along the way. Points are sampled
+    /// every `step` with a minimum-jerk speed profile so the cursor accelerates at the start
+    /// and slows down when approaching the target.
+    pub fn generate(rng: &Rng, from: (i32, i32), to: (i32, i32), step: Duration) -> MousePath {
+        let start = (from.0 as f32, from.1 as f32);
+        let end = (to.0 as f32, to.1 as f32);
+        let (dx, dy) = (end.0 - start.0, end.1 - start.1);
+        let distance = (dx * dx + dy * dy).sqrt();
+        if distance < 1.0 {
+            return MousePath { points: vec![to] };
+        }
+
+        // Control points at random distances along and away from the straight line
+        let normal = (-dy / distance, dx / distance);
+        let control_point = |along: f32| {
+            let offset = rng.random_range(-MAX_CURVE_RATIO..=MAX_CURVE_RATIO) * distance;
+            (
+                start.0 + dx * along + normal.0 * offset,
+                start.1 + dy * along + normal.1 * offset,
+            )
+        };
+        let control_1 = control_point(rng.random_range(0.2..0.4));
+        let control_2 = control_point(rng.random_range(0.6..0.8));
+
+        let millis = (MIN_MILLIS + distance * MILLIS_PER_PIXEL).min(MAX_MILLIS);
+        let count = (millis / (step.as_secs_f32() * 1000.0)).ceil().max(1.0) as usize;
+        let mut points = (1..count)
+            .map(|i| {
+                let t = minimum_jerk(i as f32 / count as f32);
+                let (x, y) = cubic_bezier(start, control_1, control_2, end, t);
+                // Jitter fades out at both ends so the path starts and ends on the points
+                let jitter = MAX_JITTER * (PI * t).sin();
+                let x = x + rng.random_range(-1.0..=1.0) * jitter;
+                let y = y + rng.random_range(-1.0..=1.0) * jitter;
+                (x.round() as i32, y.round() as i32)
+            })
+            .collect::<Vec<_>>();
+        points.dedup();
+        points.push(to);
+
+        MousePath { points }
+    }
+}
+
+/// Maps the linear progress `t` in `0.0..=1.0` to the minimum-jerk progress.
+#[inline]
+fn minimum_jerk(t: f32) -> f32 {
+    t * t * t * (10.0 - 15.0 * t + 6.0 * t * t)
+}
+
+#[inline]
+fn cubic_bezier(
+    p0: (f32, f32),
+    p1: (f32, f32),
+    p2: (f32, f32),
+    p3: (f32, f32),
+    t: f32,
+) -> (f32, f32) {
+    let u = 1.0 - t;
+    let b0 = u * u * u;
+    let b1 = 3.0 * u * u * t;
+    let b2 = 3.0 * u * t * t;
+    let b3 = t * t * t;
+    (
+        b0 * p0.0 + b1 * p1.0 + b2 * p2.0 + b3 * p3.0,
+        b0 * p0.1 + b1 * p1.1 + b2 * p2.1 + b3 * p3.1,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SEED: [u8; 32] = [
+        64, 241, 206, 219, 49, 21, 218, 145, 254, 152, 68, 176, 242, 238, 152, 14, 176, 241, 153,
+        64, 44, 192, 172, 191, 191, 157, 107, 206, 193, 55, 115, 68,
+    ];
+
+    #[test]
+    fn generate_end_at_target_with_speed_profile() {
+        let rng = Rng::new(SEED);
+        let path = MousePath::generate(&rng, (0, 0), (500, 300), Duration::from_millis(10));
+
+        assert_eq!(path.points.last(), Some(&(500, 300)));
+        assert!(path.points.len() > 2);
+        // Slower at the start than in the middle
+        let step_distance = |a: (i32, i32), b: (i32, i32)| {
+            (((b.0 - a.0).pow(2) + (b.1 - a.1).pow(2)) as f32).sqrt()
+        };
+        let middle = path.points.len() / 2;
+        assert!(
+            step_distance((0, 0), path.points[0])
+                < step_distance(path.points[middle - 1], path.points[middle])
+        );
+    }
+
+    #[test]
+    fn generate_same_position() {
+        let rng = Rng::new(SEED);
+        let path = MousePath::generate(&rng, (100, 100), (100, 100), Duration::from_millis(10));
+
+        assert_eq!(path.points, vec![(100, 100)]);
+    }
+}
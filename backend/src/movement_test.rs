@@ -0,0 +1,239 @@
+use core::range::Range;
+use std::time::{Duration, Instant};
+
+use log::debug;
+use opencv::core::Point;
+
+use crate::{
+    MovementTestKind, MovementTestResult, MovementTestTransition, Position,
+    context::Context,
+    pathing::PlatformWithNeighbors,
+    player::{
+        DOUBLE_JUMP_THRESHOLD, GRAPPLING_THRESHOLD, JUMP_THRESHOLD, PlayerAction, PlayerActionMove,
+        PlayerState,
+    },
+};
+
+/// Maximum duration to wait for the player to reach a transition's start or end point.
+const TRANSITION_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Maximum x distance from a point to be considered reached.
+const REACHED_X_THRESHOLD: i32 = 3;
+
+/// Internal representation of a [`MovementTestTransition`].
+#[derive(Debug, Clone, Copy)]
+struct Transition {
+    from: Point,
+    to: Point,
+    kind: MovementTestKind,
+    result: MovementTestResult,
+}
+
+/// The current stage of the transition being tested.
+#[derive(Debug, Clone, Copy)]
+enum Stage {
+    /// The next transition is ready to be tested.
+    Pending,
+    /// The player is moving to the start point of the transition.
+    MovingToStart(Instant),
+    /// The player is moving to the end point of the transition.
+    Testing(Instant),
+}
+
+/// Tests the movement between each pair of reachable platforms.
+///
+/// For each transition, the player first moves to the middle of the source platform and then to
+/// the middle of the destination platform. A transition fails if the player does not end up at
+/// the destination or times out. If the player cannot reach the source platform, the transition
+/// is skipped.
+#[derive(Debug, Default)]
+pub struct MovementTester {
+    transitions: Vec<Transition>,
+    index: usize,
+    stage: Option<Stage>,
+}
+
+impl MovementTester {
+    /// Starts testing all transitions between `platforms` and their neighbors.
+    pub fn start<'a>(&mut self, platforms: impl IntoIterator<Item = &'a PlatformWithNeighbors>) {
+        self.transitions = platforms
+            .into_iter()
+            .flat_map(|platform| {
+                let from = Point::new(middle_of(platform.xs()), platform.y());
+                platform.neighbors().map(move |(xs, y)| {
+                    let to = Point::new(middle_of(xs), y);
+                    Transition {
+                        from,
+                        to,
+                        kind: movement_kind(from, to),
+                        result: MovementTestResult::Pending,
+                    }
+                })
+            })
+            .collect();
+        self.index = 0;
+        self.stage = (!self.transitions.is_empty()).then_some(Stage::Pending);
+        debug!(target: "movement_test", "started with {} transitions", self.transitions.len());
+    }
+
+    /// Stops testing and marks all untested transitions as skipped.
+    pub fn stop(&mut self) {
+        if self.stage.take().is_none() {
+            return;
+        }
+        self.transitions
+            .iter_mut()
+            .skip(self.index)
+            .for_each(|transition| transition.result = MovementTestResult::Skipped);
+    }
+
+    #[inline]
+    pub fn is_testing(&self) -> bool {
+        self.stage.is_some()
+    }
+
+    /// The transitions and their results for displaying to UI.
+    pub fn transitions(&self) -> Vec<MovementTestTransition> {
+        self.transitions
+            .iter()
+            .map(|transition| MovementTestTransition {
+                from: (transition.from.x, transition.from.y),
+                to: (transition.to.x, transition.to.y),
+                kind: transition.kind,
+                result: transition.result,
+            })
+            .collect()
+    }
+
+    /// Updates the current transition being tested.
+    ///
+    /// Testing stops if the actions are started.
+    pub fn update(&mut self, context: &Context, player: &mut PlayerState) {
+        let Some(stage) = self.stage else {
+            return;
+        };
+        if !context.operation.halting() {
+            player.reset_normal_action();
+            self.stop();
+            return;
+        }
+
+        let transition = &mut self.transitions[self.index];
+        let next = match stage {
+            Stage::Pending => {
+                transition.result = MovementTestResult::Testing;
+                player.set_normal_action(None, move_action(transition.from));
+                Some(Stage::MovingToStart(Instant::now()))
+            }
+            Stage::MovingToStart(instant) => {
+                if player.has_normal_action() {
+                    if instant.elapsed() < TRANSITION_TIMEOUT {
+                        return;
+                    }
+                    player.reset_normal_action();
+                }
+                if has_reached(player, transition.from) {
+                    player.set_normal_action(None, move_action(transition.to));
+                    Some(Stage::Testing(Instant::now()))
+                } else {
+                    transition.result = MovementTestResult::Skipped;
+                    None
+                }
+            }
+            Stage::Testing(instant) => {
+                if player.has_normal_action() {
+                    if instant.elapsed() < TRANSITION_TIMEOUT {
+                        return;
+                    }
+                    player.reset_normal_action();
+                }
+                transition.result = if has_reached(player, transition.to) {
+                    MovementTestResult::Succeeded
+                } else {
+                    MovementTestResult::Failed
+                };
+                None
+            }
+        };
+        if next.is_none() {
+            debug!(target: "movement_test", "transition {transition:?} completed");
+            self.index += 1;
+        }
+        self.stage = next.or((self.index < self.transitions.len()).then_some(Stage::Pending));
+    }
+}
+
+#[inline]
+fn middle_of(xs: Range<i32>) -> i32 {
+    xs.start + (xs.end - xs.start) / 2
+}
+
+/// Determines the movement the player is expected to perform from `from` to `to`.
+fn movement_kind(from: Point, to: Point) -> MovementTestKind {
+    let x_distance = (to.x - from.x).abs();
+    let y_direction = to.y - from.y;
+    if y_direction.abs() < JUMP_THRESHOLD {
+        if x_distance >= DOUBLE_JUMP_THRESHOLD {
+            MovementTestKind::DoubleJump
+        } else {
+            MovementTestKind::Walk
+        }
+    } else if y_direction >= GRAPPLING_THRESHOLD {
+        MovementTestKind::Grapple
+    } else if y_direction > 0 {
+        MovementTestKind::UpJump
+    } else {
+        MovementTestKind::Drop
+    }
+}
+
+#[inline]
+fn move_action(point: Point) -> PlayerAction {
+    PlayerAction::Move(PlayerActionMove {
+        position: Position {
+            x: point.x,
+            y: point.y,
+            x_random_range: 0,
+            allow_adjusting: true,
+        },
+        wait_after_move_ticks: 0,
+        home: false,
+    })
+}
+
+#[inline]
+fn has_reached(player: &PlayerState, point: Point) -> bool {
+    player
+        .last_known_pos
+        .is_some_and(|pos| (pos.x - point.x).abs() <= REACHED_X_THRESHOLD && pos.y == point.y)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn movement_kind_by_distance() {
+        let from = Point::new(50, 50);
+        assert_eq!(
+            movement_kind(from, Point::new(55, 50)),
+            MovementTestKind::Walk
+        );
+        assert_eq!(
+            movement_kind(from, Point::new(50 + DOUBLE_JUMP_THRESHOLD, 52)),
+            MovementTestKind::DoubleJump
+        );
+        assert_eq!(
+            movement_kind(from, Point::new(50, 50 + JUMP_THRESHOLD)),
+            MovementTestKind::UpJump
+        );
+        assert_eq!(
+            movement_kind(from, Point::new(50, 50 + GRAPPLING_THRESHOLD)),
+            MovementTestKind::Grapple
+        );
+        assert_eq!(
+            movement_kind(from, Point::new(50, 50 - JUMP_THRESHOLD)),
+            MovementTestKind::Drop
+        );
+    }
+}
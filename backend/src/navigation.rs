@@ -22,9 +22,12 @@ use crate::{
     database::{NavigationPath, NavigationTransition, query_navigation_paths},
     detect::Detector,
     minimap::Minimap,
-    player::{PlayerAction, PlayerActionKey, PlayerState},
+    player::{PlayerAction, PlayerActionKey, PlayerState, scaled_ticks},
 };
 
+/// Number of ticks to wait for the NPC dialog before using the next transition key.
+const NPC_DIALOG_WAIT_TICKS: u32 = 15;
+
 /// Internal representation of [`NavigationPath`].
 ///
 /// This is used for eagerly resolving all of a path's referenced ids.
@@ -53,6 +56,8 @@ struct Point {
     x: i32,
     y: i32,
     transition: NavigationTransition,
+    key: KeyBinding,
+    npc_option: u32,
 }
 
 /// Next point computation state to navigate the player to [`Navigator::destination_path_id`].
@@ -61,7 +66,7 @@ enum PointState {
     Dirty,
     Completed,
     Unreachable,
-    Next(i32, i32, NavigationTransition, Option<Rc<RefCell<Path>>>),
+    Next(Point),
 }
 
 /// Update state when [`Navigator::path_dirty`] is `true`.
@@ -110,6 +115,8 @@ pub struct Navigator {
     path_last_update: Instant,
     /// Cached next point navigation computation.
     last_point_state: Option<PointState>,
+    /// Number of transition keys used so far for the point in [`Self::last_point_state`].
+    transition_step: u32,
     destination_path_id: Option<i64>,
 }
 
@@ -129,6 +136,7 @@ impl Navigator {
             path_dirty_retry_count: 0,
             path_last_update: Instant::now(),
             last_point_state: None,
+            transition_step: 0,
             destination_path_id: None,
         }
     }
@@ -137,12 +145,13 @@ impl Navigator {
     ///
     /// Returns `true` if the player has reached the destination.
     pub fn navigate_player(&mut self, context: &Context, player: &mut PlayerState) -> bool {
-        if context.operation.halting() {
+        if context.operation.halting() || context.operation.paused() {
             return false;
         }
 
-        self.last_point_state = Some(self.compute_next_point());
-        match self.last_point_state.as_ref().expect("has value") {
+        let point_state = self.compute_next_point();
+        self.last_point_state = Some(point_state.clone());
+        match point_state {
             PointState::Dirty => {
                 if context.did_minimap_changed {
                     player.take_priority_action();
@@ -150,31 +159,41 @@ impl Navigator {
                 false
             }
             PointState::Completed | PointState::Unreachable => true,
-            PointState::Next(x, y, transition, _) => {
-                match transition {
-                    NavigationTransition::Portal => {
-                        if !player.has_priority_action() {
-                            let position = Position {
-                                x: *x,
-                                y: *y,
-                                x_random_range: 0,
-                                allow_adjusting: true,
-                            };
-                            let key = PlayerActionKey {
-                                key: KeyBinding::Up,
-                                link_key: None,
-                                count: 1,
-                                position: Some(position),
-                                direction: ActionKeyDirection::Any,
-                                with: ActionKeyWith::Stationary,
-                                wait_before_use_ticks: 5,
-                                wait_before_use_ticks_random_range: 0,
-                                wait_after_use_ticks: 0,
-                                wait_after_use_ticks_random_range: 0,
-                            };
-                            player.set_priority_action(None, PlayerAction::Key(key));
-                        }
-                    }
+            PointState::Next(point) => {
+                if !player.has_priority_action() {
+                    let keys = transition_keys(&point);
+                    let index = self.transition_step as usize % keys.len();
+                    let (key, count) = keys[index];
+                    // Only the first key needs the player to be at the point
+                    let (position, with, wait_before_use_ticks) = if index == 0 {
+                        let position = Position {
+                            x: point.x,
+                            y: point.y,
+                            x_random_range: 0,
+                            allow_adjusting: true,
+                        };
+                        (Some(position), ActionKeyWith::Stationary, scaled_ticks(5))
+                    } else {
+                        (
+                            None,
+                            ActionKeyWith::Any,
+                            scaled_ticks(NPC_DIALOG_WAIT_TICKS),
+                        )
+                    };
+                    let key = PlayerActionKey {
+                        key,
+                        link_key: None,
+                        count,
+                        position,
+                        direction: ActionKeyDirection::Any,
+                        with,
+                        wait_before_use_ticks,
+                        wait_before_use_ticks_random_range: 0,
+                        wait_after_use_ticks: 0,
+                        wait_after_use_ticks_random_range: 0,
+                    };
+                    player.set_priority_action(None, PlayerAction::Key(key));
+                    self.transition_step += 1;
                 }
 
                 false
@@ -187,7 +206,7 @@ impl Navigator {
     pub fn was_last_point_available_or_completed(&self) -> bool {
         matches!(
             self.last_point_state,
-            Some(PointState::Next(_, _, _, _) | PointState::Completed)
+            Some(PointState::Next(_) | PointState::Completed)
         )
     }
 
@@ -233,7 +252,7 @@ impl Navigator {
         // Re-use cached point
         if matches!(
             self.last_point_state,
-            Some(PointState::Next(_, _, _, _) | PointState::Completed | PointState::Unreachable)
+            Some(PointState::Next(_) | PointState::Completed | PointState::Unreachable)
         ) {
             return self.last_point_state.clone().expect("has value");
         }
@@ -256,12 +275,23 @@ impl Navigator {
             .clone()
             .and_then(|path| search_point(path, path_id))
         {
-            return PointState::Next(point.x, point.y, point.transition, point.next_path.clone());
+            return PointState::Next(point);
         }
 
         PointState::Unreachable
     }
 
+    /// The id of the path the player is currently in.
+    ///
+    /// Returns `None` if the current path has not been determined after the paths became dirty.
+    #[inline]
+    pub fn current_path_id(&self) -> Option<i64> {
+        if self.path_dirty {
+            return None;
+        }
+        self.current_path.as_ref().map(|path| path.borrow().id)
+    }
+
     #[inline]
     pub fn mark_dirty(&mut self) {
         // Do not reset `base_path` and `current_path` here so that
@@ -269,6 +299,7 @@ impl Navigator {
         self.path_dirty = true;
         self.path_dirty_retry_count = 0;
         self.last_point_state = None;
+        self.transition_step = 0;
     }
 
     #[inline]
@@ -324,7 +355,10 @@ impl Navigator {
         };
 
         // Try from next_path if previously exists due to player navigating
-        if let Some(PointState::Next(_, _, _, Some(next_path))) = self.last_point_state.take()
+        if let Some(PointState::Next(Point {
+            next_path: Some(next_path),
+            ..
+        })) = self.last_point_state.take()
             && let Ok(current_path) =
                 find_current_from_base_path(next_path, detector, minimap_bbox, minimap_name_bbox)
         {
@@ -435,6 +469,8 @@ fn build_base_path_from(
                 x: point.x,
                 y: point.y,
                 transition: point.transition,
+                key: point.key,
+                npc_option: point.npc_option,
             });
 
             if let Some(id) = point.next_path_id {
@@ -487,6 +523,24 @@ fn find_current_from_base_path(
         .ok_or(anyhow!("unable to determine current path"))
 }
 
+/// The keys and their counts to use in order for the transition of `point`.
+fn transition_keys(point: &Point) -> Vec<(KeyBinding, u32)> {
+    match point.transition {
+        NavigationTransition::Portal => vec![(KeyBinding::Up, 1)],
+        NavigationTransition::Key => vec![(point.key, 1)],
+        NavigationTransition::Npc => {
+            let mut keys = vec![(point.key, 1)];
+            if point.npc_option > 0 {
+                keys.push((KeyBinding::Down, point.npc_option));
+            }
+            // Selects the destination and then confirms
+            keys.push((KeyBinding::Enter, 1));
+            keys.push((KeyBinding::Enter, 1));
+            keys
+        }
+    }
+}
+
 fn decode_base64_to_mat(base64: &str, grayscale: bool) -> Result<Mat> {
     let flag = if grayscale {
         IMREAD_GRAYSCALE
@@ -534,6 +588,7 @@ mod tests {
                 x: 30,
                 y: 30,
                 transition: NavigationTransition::Portal,
+                ..NavigationPoint::default()
             }],
         );
 
@@ -548,12 +603,14 @@ mod tests {
                     x: 20,
                     y: 20,
                     transition: NavigationTransition::Portal,
+                    ..NavigationPoint::default()
                 },
                 NavigationPoint {
                     next_path_id: Some(path_a_id),
                     x: 10,
                     y: 10,
                     transition: NavigationTransition::Portal,
+                    ..NavigationPoint::default()
                 },
             ],
         );
@@ -567,12 +624,14 @@ mod tests {
                     x: 11,
                     y: 10,
                     transition: NavigationTransition::Portal,
+                    ..NavigationPoint::default()
                 },
                 NavigationPoint {
                     next_path_id: Some(path_b_id),
                     x: 10,
                     y: 10,
                     transition: NavigationTransition::Portal,
+                    ..NavigationPoint::default()
                 },
             ],
         );
@@ -692,6 +751,8 @@ mod tests {
             x: 100,
             y: 200,
             transition: NavigationTransition::Portal,
+            key: KeyBinding::default(),
+            npc_option: 0,
             next_path: Some(Rc::new(RefCell::new(target_path.clone()))),
         };
         let path = Path {
@@ -707,7 +768,13 @@ mod tests {
         let result = navigator.compute_next_point();
 
         match result {
-            PointState::Next(x, y, transition, Some(next_path)) => {
+            PointState::Next(Point {
+                x,
+                y,
+                transition,
+                next_path: Some(next_path),
+                ..
+            }) => {
                 assert_eq!(x, 100);
                 assert_eq!(y, 200);
                 assert_eq!(transition, NavigationTransition::Portal);
@@ -758,6 +825,7 @@ mod tests {
             x: 5,
             y: 5,
             transition: NavigationTransition::Portal,
+            ..NavigationPoint::default()
         };
 
         let mock_path = mock_navigation_path(Some(1), vec![point]);
@@ -778,4 +846,26 @@ mod tests {
         assert!(navigator.current_path.is_some());
         assert!(navigator.base_path.is_some());
     }
+
+    #[test]
+    fn transition_keys_npc_talk_select_and_confirm() {
+        let point = Point {
+            next_path: None,
+            x: 0,
+            y: 0,
+            transition: NavigationTransition::Npc,
+            key: KeyBinding::Space,
+            npc_option: 2,
+        };
+
+        assert_eq!(
+            transition_keys(&point),
+            vec![
+                (KeyBinding::Space, 1),
+                (KeyBinding::Down, 2),
+                (KeyBinding::Enter, 1),
+                (KeyBinding::Enter, 1)
+            ]
+        );
+    }
 }
@@ -0,0 +1,193 @@
+use std::mem;
+
+use log::debug;
+use opencv::core::Point;
+
+use crate::{
+    KeyBinding,
+    database::{NavigationPath, NavigationPoint, NavigationTransition},
+};
+
+/// Records a navigation route from the player moving through maps manually.
+///
+/// A path is recorded for each visited map. When the map changes, a point is added to the
+/// previous map's path at the position the player last stood still, with the transition inferred
+/// from the keys pressed there.
+#[derive(Debug, Default)]
+pub struct NavigationRecorder {
+    recording: bool,
+    /// The recorded paths in the visited order with the last one being the current map.
+    paths: Vec<NavigationPath>,
+    /// Whether the path of the current map has not been recorded yet.
+    path_pending: bool,
+    /// The last player position in the current map.
+    last_pos: Option<Point>,
+    /// The keys pressed by the user since the player last moved.
+    keys: Vec<KeyBinding>,
+}
+
+impl NavigationRecorder {
+    /// Starts recording and discards previously recorded paths.
+    pub fn start(&mut self) {
+        *self = NavigationRecorder {
+            recording: true,
+            path_pending: true,
+            ..NavigationRecorder::default()
+        };
+        debug!(target: "navigation_recorder", "started");
+    }
+
+    /// Stops recording and returns the recorded paths in the visited order.
+    pub fn stop(&mut self) -> Vec<NavigationPath> {
+        let paths = mem::take(&mut self.paths);
+        *self = NavigationRecorder::default();
+        paths
+    }
+
+    #[inline]
+    pub fn is_recording(&self) -> bool {
+        self.recording
+    }
+
+    /// Records `key` pressed by the user if recording.
+    pub fn record_key(&mut self, key: KeyBinding) {
+        if self.recording && self.last_pos.is_some() {
+            self.keys.push(key);
+        }
+    }
+
+    /// Updates the recording from the player position `pos`.
+    ///
+    /// `create_path` creates the path of the current map and is called after the map changes
+    /// until it succeeds.
+    pub fn update(
+        &mut self,
+        did_minimap_changed: bool,
+        pos: Option<Point>,
+        create_path: impl FnOnce() -> Option<NavigationPath>,
+    ) {
+        if !self.recording {
+            return;
+        }
+        if did_minimap_changed {
+            self.add_transition_point();
+            return;
+        }
+        if self.path_pending {
+            if let Some(path) = create_path() {
+                self.paths.push(path);
+                self.path_pending = false;
+            }
+            return;
+        }
+        if let Some(pos) = pos
+            && self.last_pos != Some(pos)
+        {
+            self.last_pos = Some(pos);
+            self.keys.clear();
+        }
+    }
+
+    fn add_transition_point(&mut self) {
+        let last_pos = self.last_pos.take();
+        let keys = mem::take(&mut self.keys);
+        self.path_pending = true;
+
+        let (Some(path), Some(pos)) = (self.paths.last_mut(), last_pos) else {
+            return;
+        };
+        let (transition, key, npc_option) = transition_from_keys(&keys);
+        debug!(target: "navigation_recorder", "recorded {transition} at {pos:?}");
+        path.points.push(NavigationPoint {
+            next_path_id: None,
+            x: pos.x,
+            y: pos.y,
+            transition,
+            key,
+            npc_option,
+        });
+    }
+}
+
+/// Infers the transition from `keys` pressed while the player stood still before the map
+/// changed.
+///
+/// Talking to an NPC is inferred when a dialog is confirmed with enter after using a key other
+/// than the arrow keys. Otherwise, the last key is used unless it is up for entering a portal.
+fn transition_from_keys(keys: &[KeyBinding]) -> (NavigationTransition, KeyBinding, u32) {
+    if keys.contains(&KeyBinding::Enter)
+        && let Some(key) = keys
+            .iter()
+            .copied()
+            .find(|key| !matches!(key, KeyBinding::Up | KeyBinding::Down | KeyBinding::Enter))
+    {
+        let npc_option = keys.iter().filter(|key| **key == KeyBinding::Down).count() as u32;
+        return (NavigationTransition::Npc, key, npc_option);
+    }
+
+    match keys.last().copied() {
+        None | Some(KeyBinding::Up) => (NavigationTransition::Portal, KeyBinding::default(), 0),
+        Some(key) => (NavigationTransition::Key, key, 0),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn transition_from_keys_infer_portal_key_and_npc() {
+        assert_eq!(
+            transition_from_keys(&[]),
+            (NavigationTransition::Portal, KeyBinding::default(), 0)
+        );
+        assert_eq!(
+            transition_from_keys(&[KeyBinding::A, KeyBinding::Up]),
+            (NavigationTransition::Portal, KeyBinding::default(), 0)
+        );
+        assert_eq!(
+            transition_from_keys(&[KeyBinding::Up, KeyBinding::F1]),
+            (NavigationTransition::Key, KeyBinding::F1, 0)
+        );
+        assert_eq!(
+            transition_from_keys(&[
+                KeyBinding::Space,
+                KeyBinding::Down,
+                KeyBinding::Down,
+                KeyBinding::Enter,
+                KeyBinding::Enter
+            ]),
+            (NavigationTransition::Npc, KeyBinding::Space, 2)
+        );
+    }
+
+    #[test]
+    fn update_add_point_to_previous_path_on_map_changed() {
+        let mut recorder = NavigationRecorder::default();
+        recorder.start();
+
+        recorder.update(false, None, || Some(NavigationPath::default()));
+        recorder.update(false, Some(Point::new(10, 20)), || unreachable!());
+        recorder.record_key(KeyBinding::Up);
+        recorder.update(false, Some(Point::new(10, 20)), || unreachable!());
+        recorder.update(true, None, || unreachable!());
+        recorder.update(false, None, || None);
+        recorder.update(false, None, || Some(NavigationPath::default()));
+
+        let paths = recorder.stop();
+        assert_eq!(paths.len(), 2);
+        assert_eq!(
+            paths[0].points,
+            vec![NavigationPoint {
+                next_path_id: None,
+                x: 10,
+                y: 20,
+                transition: NavigationTransition::Portal,
+                key: KeyBinding::default(),
+                npc_option: 0,
+            }]
+        );
+        assert!(paths[1].points.is_empty());
+        assert!(!recorder.is_recording());
+    }
+}
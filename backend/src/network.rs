@@ -1,7 +1,10 @@
 use std::{
     cell::RefCell,
+    collections::HashMap,
+    fmt::Debug,
     mem,
     ops::{Index, Not},
+    pin::Pin,
     rc::Rc,
     sync::{Arc, Mutex},
     time::Duration,
@@ -11,31 +14,69 @@ use anyhow::{Error, Ok, bail};
 use bit_vec::BitVec;
 use log::{debug, error};
 use reqwest::{
-    Client, Url,
+    Client, RequestBuilder, Response, Url,
+    header::CONTENT_TYPE,
     multipart::{Form, Part},
 };
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
+use strum::{Display, EnumIter};
 use tokio::{
     spawn,
     time::{Instant, sleep},
 };
 
-use crate::Settings;
+use crate::{GmBehavior, Notifications, Settings};
 
 static TRUE: bool = true;
 static FALSE: bool = false;
 
-#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+#[derive(PartialEq, Eq, Hash, Clone, Copy, Debug, Serialize, Deserialize, EnumIter, Display)]
 #[repr(usize)]
 pub enum NotificationKind {
+    #[strum(to_string = "Detection fails or map changes")]
     FailOrMapChange,
+    #[strum(to_string = "Rune spawns")]
     RuneAppear,
+    #[strum(to_string = "Elite boss spawns")]
     EliteBossAppear,
+    #[strum(to_string = "Guildie appears")]
     PlayerGuildieAppear,
+    #[strum(to_string = "Stranger appears")]
     PlayerStrangerAppear,
+    #[strum(to_string = "Friend appears")]
     PlayerFriendAppear,
+    #[strum(to_string = "Player dies")]
     PlayerIsDead,
+    #[strum(to_string = "Arrow spam event")]
     ArrowSpam,
+    #[strum(to_string = "Rune solving fails")]
+    RuneFailed,
+    #[strum(to_string = "Rune arrows requested")]
+    RuneArrowsRequested,
+    #[strum(to_string = "Familiar essence low")]
+    FamiliarEssenceLow,
+    #[strum(to_string = "Maintenance countdown")]
+    MaintenanceCountdown,
+    #[strum(to_string = "Invalid action position")]
+    InvalidActionPosition,
+    #[strum(to_string = "Capture or input broken")]
+    CaptureOrInputBroken,
+    #[strum(to_string = "Capture lost")]
+    CaptureLost,
+    #[strum(to_string = "Session summary")]
+    SessionSummary,
+    #[strum(to_string = "Required buffs missing")]
+    RequiredBuffsMissing,
+    #[strum(to_string = "Inventory full")]
+    InventoryFull,
+    #[strum(to_string = "Whisper received")]
+    WhisperReceived,
+    #[strum(to_string = "Lie detector appears")]
+    LieDetectorAppear,
+    #[strum(to_string = "GM detected")]
+    GmAppear,
+    #[strum(to_string = "Potion has no effect")]
+    PotionNoEffect,
 }
 
 impl From<NotificationKind> for usize {
@@ -61,11 +102,15 @@ struct ScheduledNotification {
     /// The instant it was scheduled
     instant: Instant,
     kind: NotificationKind,
-    url: String,
-    body: DiscordWebhookBody,
+    /// Whether the notification kind is marked as critical
+    critical: bool,
+    /// The notification content in Discord markdown without any mention
+    content: String,
+    /// The backends to post the notification to
+    notifiers: Vec<Box<dyn Notifier>>,
     /// Stores fixed size tuples of frame and frame deadline in seconds
     ///
-    /// During each [`DiscordNotification::update_schedule`], the first frame not passing the
+    /// During each [`Notification::update_schedule`], the first frame not passing the
     /// deadline will try to capture the image from current game state. This is useful for showing
     /// `before and after` whnen map changes. So frame that cannot capture when the deadline is
     /// reached will be skipped.
@@ -73,7 +118,7 @@ struct ScheduledNotification {
 }
 
 #[derive(Debug)]
-pub struct DiscordNotification {
+pub struct Notification {
     client: Client,
     settings: Rc<RefCell<Settings>>,
     scheduled: Arc<Mutex<Vec<ScheduledNotification>>>,
@@ -81,9 +126,11 @@ pub struct DiscordNotification {
     ///
     /// There can only be one unique [`NotificationKind`] scheduled at a time.
     pending: Arc<Mutex<BitVec>>,
+    /// The instant each [`NotificationKind`] was last scheduled for cooldown
+    last_scheduled: RefCell<HashMap<NotificationKind, Instant>>,
 }
 
-impl DiscordNotification {
+impl Notification {
     pub fn new(settings: Rc<RefCell<Settings>>) -> Self {
         Self {
             client: Client::new(),
@@ -93,10 +140,29 @@ impl DiscordNotification {
                 mem::variant_count::<NotificationKind>(),
                 false,
             ))),
+            last_scheduled: RefCell::new(HashMap::new()),
         }
     }
 
     pub fn schedule_notification(&self, kind: NotificationKind) -> Result<(), Error> {
+        self.schedule_notification_inner(kind, None)
+    }
+
+    /// Same as [`Self::schedule_notification`] but appends `details` as a new line to the
+    /// notification content.
+    pub fn schedule_notification_with_details(
+        &self,
+        kind: NotificationKind,
+        details: String,
+    ) -> Result<(), Error> {
+        self.schedule_notification_inner(kind, Some(details))
+    }
+
+    fn schedule_notification_inner(
+        &self,
+        kind: NotificationKind,
+        details: Option<String>,
+    ) -> Result<(), Error> {
         let settings = self.settings.borrow();
         let is_enabled = match kind {
             NotificationKind::FailOrMapChange => {
@@ -106,6 +172,32 @@ impl DiscordNotification {
             NotificationKind::RuneAppear => settings.notifications.notify_on_rune_appear,
             NotificationKind::EliteBossAppear => settings.notifications.notify_on_elite_boss_appear,
             NotificationKind::PlayerIsDead => settings.notifications.notify_on_player_die,
+            NotificationKind::RuneFailed | NotificationKind::RuneArrowsRequested => {
+                settings.notifications.notify_on_rune_fail
+            }
+            NotificationKind::FamiliarEssenceLow => {
+                settings.notifications.notify_on_familiar_essence_low
+            }
+            NotificationKind::MaintenanceCountdown => {
+                settings.notifications.notify_on_maintenance_countdown
+            }
+            NotificationKind::InvalidActionPosition => {
+                settings.notifications.notify_on_invalid_action_position
+            }
+            NotificationKind::CaptureOrInputBroken | NotificationKind::CaptureLost => {
+                settings.notifications.notify_on_capture_or_input_broken
+            }
+            NotificationKind::SessionSummary => settings.notifications.notify_on_session_summary,
+            NotificationKind::RequiredBuffsMissing => {
+                settings.notifications.notify_on_required_buffs_missing
+            }
+            NotificationKind::InventoryFull => settings.notifications.notify_on_inventory_full,
+            NotificationKind::WhisperReceived => settings.notifications.notify_on_whisper_received,
+            NotificationKind::LieDetectorAppear => {
+                settings.notifications.notify_on_lie_detector_appear
+            }
+            NotificationKind::GmAppear => settings.notifications.notify_on_gm_appear,
+            NotificationKind::PotionNoEffect => settings.notifications.notify_on_potion_no_effect,
             NotificationKind::PlayerGuildieAppear => {
                 settings.notifications.notify_on_player_guildie_appear
             }
@@ -119,8 +211,21 @@ impl DiscordNotification {
         if !is_enabled {
             bail!("notification not enabled");
         }
-        if settings.notifications.discord_webhook_url.is_empty() {
-            bail!("webhook url not provided");
+        let options = settings
+            .notifications
+            .kind_options
+            .get(&kind)
+            .copied()
+            .unwrap_or_default();
+        if options.cooldown_secs > 0
+            && let Some(instant) = self.last_scheduled.borrow().get(&kind)
+            && instant.elapsed() < Duration::from_secs(options.cooldown_secs as u64)
+        {
+            bail!("notification is on cooldown");
+        }
+        let notifiers = notifiers(&settings.notifications, options.critical)?;
+        if notifiers.is_empty() {
+            bail!("no notification backend provided");
         }
 
         let mut pending = self.pending.lock().unwrap();
@@ -128,74 +233,133 @@ impl DiscordNotification {
             bail!("notification is already sending");
         }
 
-        let url = settings.notifications.discord_webhook_url.clone();
-        if Url::try_from(url.as_str()).is_err() {
-            bail!("failed to parse webhook url");
-        }
-
-        let user_id = settings
-            .notifications
-            .discord_user_id
-            .is_empty()
-            .not()
-            .then_some(format!("<@{}> ", settings.notifications.discord_user_id))
-            .unwrap_or_default();
         let content = match kind {
             NotificationKind::FailOrMapChange => {
                 if self.settings.borrow().stop_on_fail_or_change_map {
-                    format!(
-                        "{user_id}Bot stopped because it has failed to detect or the map has changed"
+                    String::from(
+                        "Bot stopped because it has failed to detect or the map has changed",
                     )
                 } else {
-                    format!("{user_id}Bot has failed to detect or the map has changed")
+                    String::from("Bot has failed to detect or the map has changed")
                 }
             }
-            NotificationKind::RuneAppear => {
-                format!("{user_id}Bot has detected a rune on map")
-            }
+            NotificationKind::RuneAppear => String::from("Bot has detected a rune on map"),
 
-            NotificationKind::ArrowSpam => {
-                format!("{user_id}Bot has detected a Arrow Spam Event")
-            }
-            NotificationKind::EliteBossAppear => {
-                format!("{user_id}Elite boss spawned")
-            }
-            NotificationKind::PlayerIsDead => {
-                format!("{user_id}The player is dead")
-            }
+            NotificationKind::ArrowSpam => String::from("Bot has detected a Arrow Spam Event"),
+            NotificationKind::EliteBossAppear => String::from("Elite boss spawned"),
+            NotificationKind::PlayerIsDead => String::from("The player is dead"),
+            NotificationKind::RuneFailed => String::from(
+                "Bot stopped because it has failed to solve rune too many times, please solve it and start the bot again",
+            ),
+            NotificationKind::RuneArrowsRequested => String::from(
+                "**URGENT** Bot has failed to detect the rune arrows, please reply with `!rune <arrows>` (e.g. `!rune ULDR`) now",
+            ),
             NotificationKind::PlayerGuildieAppear => {
-                format!("{user_id}Bot has detected guildie player(s)")
+                String::from("Bot has detected guildie player(s)")
             }
             NotificationKind::PlayerStrangerAppear => {
-                format!("{user_id}Bot has detected stranger player(s)")
+                String::from("Bot has detected stranger player(s)")
             }
             NotificationKind::PlayerFriendAppear => {
-                format!("{user_id}Bot has detected friend player(s)")
+                String::from("Bot has detected friend player(s)")
+            }
+            NotificationKind::FamiliarEssenceLow => String::from("Familiar essence is running low"),
+            NotificationKind::MaintenanceCountdown => String::from(
+                "Bot has detected a server maintenance countdown and will stop in town before it ends",
+            ),
+            NotificationKind::InvalidActionPosition => String::from(
+                "Bot has skipped an action because its position is outside the map or too far from platforms",
+            ),
+            NotificationKind::CaptureOrInputBroken => String::from(
+                "Bot is sending movement keys but the player has not moved for a while, the capture or input method may not be reaching the game",
+            ),
+            NotificationKind::CaptureLost => String::from(
+                "Bot has not captured the game for a while and restarted the capture, the game may be minimized or the capture device was lost",
+            ),
+            NotificationKind::SessionSummary => String::from("Bot has stopped"),
+            NotificationKind::RequiredBuffsMissing => {
+                String::from("Bot has started without some of the preset's required buffs")
             }
+            NotificationKind::InventoryFull => {
+                String::from("Bot has detected that the inventory is full")
+            }
+            NotificationKind::WhisperReceived => {
+                String::from("Bot has detected a whisper in the chat")
+            }
+            NotificationKind::LieDetectorAppear => {
+                if self.settings.borrow().enable_lie_detector_pause {
+                    String::from(
+                        "**URGENT** Bot has paused the actions because a lie detector appeared, please answer it now",
+                    )
+                } else {
+                    String::from("**URGENT** Bot has detected a lie detector, please answer it now")
+                }
+            }
+            NotificationKind::GmAppear => {
+                let behavior = match self.settings.borrow().gm_behavior {
+                    GmBehavior::Halt => "stopped",
+                    GmBehavior::CycleChannel => "changed channel",
+                    GmBehavior::CashShop => "entered the cash shop",
+                };
+                format!("**URGENT** Bot has {behavior} because a GM may be present")
+            }
+            NotificationKind::PotionNoEffect => String::from(
+                "Bot stopped in town because using potion did not restore health, potions may have run out",
+            ),
         };
-        let body = DiscordWebhookBody {
-            content,
-            username: "maple-bot",
-            attachments: vec![],
+        let content = match details {
+            Some(details) => format!("{content}\n{details}"),
+            None => content,
         };
         let frames = match kind {
+            _ if !options.attach_snapshots => vec![],
             NotificationKind::FailOrMapChange => vec![(None, 2), (None, 4)],
+            // Captured as soon as possible since the window can be answered or timed out quickly
+            NotificationKind::LieDetectorAppear | NotificationKind::RuneArrowsRequested => {
+                vec![(None, 1)]
+            }
+            // There is no frame to attach
+            NotificationKind::CaptureLost => vec![],
             NotificationKind::EliteBossAppear
             | NotificationKind::PlayerIsDead
             | NotificationKind::PlayerGuildieAppear
             | NotificationKind::PlayerStrangerAppear
             | NotificationKind::PlayerFriendAppear
             | NotificationKind::ArrowSpam
+            | NotificationKind::RuneFailed
+            | NotificationKind::FamiliarEssenceLow
+            | NotificationKind::MaintenanceCountdown
+            | NotificationKind::InvalidActionPosition
+            | NotificationKind::CaptureOrInputBroken
+            | NotificationKind::SessionSummary
+            | NotificationKind::RequiredBuffsMissing
+            | NotificationKind::InventoryFull
+            | NotificationKind::WhisperReceived
+            | NotificationKind::GmAppear
+            | NotificationKind::PotionNoEffect
             | NotificationKind::RuneAppear => vec![(None, 2)],
         };
         let delay = match kind {
             NotificationKind::FailOrMapChange => 5,
+            NotificationKind::LieDetectorAppear | NotificationKind::RuneArrowsRequested => 1,
             NotificationKind::EliteBossAppear
             | NotificationKind::PlayerIsDead
             | NotificationKind::PlayerGuildieAppear
             | NotificationKind::PlayerStrangerAppear
             | NotificationKind::PlayerFriendAppear
             | NotificationKind::ArrowSpam
+            | NotificationKind::RuneFailed
+            | NotificationKind::FamiliarEssenceLow
+            | NotificationKind::MaintenanceCountdown
+            | NotificationKind::InvalidActionPosition
+            | NotificationKind::CaptureOrInputBroken
+            | NotificationKind::CaptureLost
+            | NotificationKind::SessionSummary
+            | NotificationKind::RequiredBuffsMissing
+            | NotificationKind::InventoryFull
+            | NotificationKind::WhisperReceived
+            | NotificationKind::GmAppear
+            | NotificationKind::PotionNoEffect
             | NotificationKind::RuneAppear => 3,
         };
 
@@ -203,11 +367,15 @@ impl DiscordNotification {
         scheduled.push(ScheduledNotification {
             instant: Instant::now(),
             kind,
-            url,
+            critical: options.critical,
+            content,
+            notifiers,
             frames,
-            body,
         });
         pending.set(kind.into(), true);
+        self.last_scheduled
+            .borrow_mut()
+            .insert(kind, Instant::now());
 
         let client = self.client.clone();
         let pending = self.pending.clone();
@@ -262,55 +430,398 @@ impl DiscordNotification {
     }
 }
 
+/// The default JSON body of the generic webhook when no template is provided.
+const DEFAULT_WEBHOOK_TEMPLATE: &str = r#"{"kind": "{kind}", "message": "{message}"}"#;
+
+/// A notification ready to be posted by a [`Notifier`].
+#[derive(Debug)]
+struct NotificationMessage {
+    kind: NotificationKind,
+    critical: bool,
+    /// The content in Discord markdown without any mention
+    content: String,
+    /// The captured PNG frames in the order they are captured
+    frames: Vec<Vec<u8>>,
+}
+
+impl NotificationMessage {
+    /// The content without markdown for backends that do not support it.
+    fn text(&self) -> String {
+        self.content.replace("**", "")
+    }
+
+    /// Whether the notification requires the user to act immediately.
+    fn is_urgent(&self) -> bool {
+        self.critical
+            || matches!(
+                self.kind,
+                NotificationKind::LieDetectorAppear
+                    | NotificationKind::GmAppear
+                    | NotificationKind::RuneArrowsRequested
+            )
+    }
+}
+
+type NotifyFuture<'a> = Pin<Box<dyn Future<Output = Result<(), Error>> + Send + 'a>>;
+
+/// A backend that a [`NotificationMessage`] can be posted to.
+trait Notifier: Debug + Send + Sync {
+    /// The name of this backend used for logging.
+    fn name(&self) -> &'static str;
+
+    fn notify<'a>(
+        &'a self,
+        client: &'a Client,
+        message: &'a NotificationMessage,
+    ) -> NotifyFuture<'a>;
+}
+
+/// Creates a [`Notifier`] for each configured backend in `notifications`.
+///
+/// When `critical` is true, the critical Discord webhook is used if provided. Returns error if a
+/// configured backend has an invalid URL.
+fn notifiers(
+    notifications: &Notifications,
+    critical: bool,
+) -> Result<Vec<Box<dyn Notifier>>, Error> {
+    let mut notifiers = Vec::<Box<dyn Notifier>>::new();
+
+    let critical_url = notifications.discord_critical_webhook_url.trim();
+    let discord_url = if critical && !critical_url.is_empty() {
+        critical_url
+    } else {
+        notifications.discord_webhook_url.trim()
+    };
+    if !discord_url.is_empty() {
+        if Url::parse(discord_url).is_err() {
+            bail!("failed to parse webhook url");
+        }
+        let user_id = if notifications.discord_ping_critical_only && !critical {
+            String::new()
+        } else {
+            notifications.discord_user_id.trim().to_string()
+        };
+        notifiers.push(Box::new(DiscordNotifier {
+            url: discord_url.to_string(),
+            user_id,
+        }));
+    }
+
+    let bot_token = notifications.telegram_bot_token.trim();
+    let chat_id = notifications.telegram_chat_id.trim();
+    if !bot_token.is_empty() && !chat_id.is_empty() {
+        notifiers.push(Box::new(TelegramNotifier {
+            bot_token: bot_token.to_string(),
+            chat_id: chat_id.to_string(),
+        }));
+    }
+
+    let webhook_url = notifications.webhook_url.trim();
+    if !webhook_url.is_empty() {
+        if Url::parse(webhook_url).is_err() {
+            bail!("failed to parse generic webhook url");
+        }
+        let template = notifications.webhook_template.trim();
+        notifiers.push(Box::new(WebhookNotifier {
+            url: webhook_url.to_string(),
+            template: template
+                .is_empty()
+                .not()
+                .then_some(template)
+                .unwrap_or(DEFAULT_WEBHOOK_TEMPLATE)
+                .to_string(),
+        }));
+    }
+
+    let ntfy_url = notifications.ntfy_url.trim();
+    if !ntfy_url.is_empty() {
+        if Url::parse(ntfy_url).is_err() {
+            bail!("failed to parse ntfy topic url");
+        }
+        let access_token = notifications.ntfy_access_token.trim();
+        notifiers.push(Box::new(NtfyNotifier {
+            url: ntfy_url.to_string(),
+            access_token: access_token
+                .is_empty()
+                .not()
+                .then(|| access_token.to_string()),
+        }));
+    }
+
+    Ok(notifiers)
+}
+
 async fn post_notification(
     client: Client,
-    mut notification: ScheduledNotification,
+    notification: ScheduledNotification,
 ) -> Result<(), Error> {
-    for i in 0..notification
-        .frames
-        .iter()
-        .filter(|(frame, _)| frame.is_some())
-        .count()
-    {
-        notification.body.attachments.push(Attachment {
-            id: i,
-            description: format!("Game snapshot #{i}"),
-            filename: format!("image_{i}.png"),
-        });
+    let message = NotificationMessage {
+        kind: notification.kind,
+        critical: notification.critical,
+        content: notification.content,
+        frames: notification
+            .frames
+            .into_iter()
+            .filter_map(|(frame, _)| frame)
+            .collect(),
+    };
+    for notifier in notification.notifiers {
+        let name = notifier.name();
+        let kind = message.kind;
+        let _ = notifier
+            .notify(&client, &message)
+            .await
+            .inspect(|_| {
+                debug!(target: "notification", "calling {name} API {kind:?} succeeded");
+            })
+            .inspect_err(|err| {
+                error!(target: "notification", "calling {name} API failed {err}");
+            });
     }
 
-    let mut form = Form::new().text(
-        "payload_json",
-        serde_json::to_string(&notification.body).unwrap(),
-    );
-    for (i, frame) in notification
-        .frames
-        .into_iter()
-        .filter_map(|(frame, _)| frame)
-        .enumerate()
-    {
-        form = form.part(
-            format!("files[{i}]"),
-            Part::bytes(frame)
-                .mime_str("image/png")
-                .unwrap()
-                .file_name(format!("image_{i}.png")),
-        );
-    }
+    Ok(())
+}
 
-    let _ = client
-        .post(notification.url)
-        .multipart(form)
+/// Sends `request` and fails on an error status.
+///
+/// The URL is removed from the error since it can contain a secret (e.g. the Telegram bot token or
+/// the Discord webhook token) that would otherwise be written to the log.
+async fn send_request(request: RequestBuilder) -> Result<(), Error> {
+    request
         .send()
         .await
-        .inspect(|_| {
-            debug!(target: "notification", "calling Webhook API {:?} succeeded", notification.kind);
+        .and_then(Response::error_for_status)
+        .map_err(reqwest::Error::without_url)?;
+    Ok(())
+}
+
+/// Posts to a Discord webhook with the frames as attachments.
+#[derive(Debug)]
+struct DiscordNotifier {
+    url: String,
+    /// The user to mention, empty if none
+    user_id: String,
+}
+
+impl Notifier for DiscordNotifier {
+    fn name(&self) -> &'static str {
+        "Discord"
+    }
+
+    fn notify<'a>(
+        &'a self,
+        client: &'a Client,
+        message: &'a NotificationMessage,
+    ) -> NotifyFuture<'a> {
+        Box::pin(async move {
+            let content = if self.user_id.is_empty() {
+                message.content.clone()
+            } else {
+                format!("<@{}> {}", self.user_id, message.content)
+            };
+            let mut body = DiscordWebhookBody {
+                content,
+                username: "maple-bot",
+                attachments: vec![],
+            };
+            for i in 0..message.frames.len() {
+                body.attachments.push(Attachment {
+                    id: i,
+                    description: format!("Game snapshot #{i}"),
+                    filename: format!("image_{i}.png"),
+                });
+            }
+
+            let mut form = Form::new().text("payload_json", serde_json::to_string(&body)?);
+            for (i, frame) in message.frames.iter().enumerate() {
+                form = form.part(format!("files[{i}]"), png_part(frame, i));
+            }
+
+            send_request(client.post(&self.url).multipart(form)).await
         })
-        .inspect_err(|err| {
-            error!(target: "notification", "calling Webhook API failed {err}");
-        });
+    }
+}
 
-    Ok(())
+/// Posts through the Telegram Bot API.
+///
+/// A single frame is sent as a photo with the text as the caption and multiple frames are sent
+/// as a media group since a photo can only have one image.
+#[derive(Debug)]
+struct TelegramNotifier {
+    bot_token: String,
+    chat_id: String,
+}
+
+impl Notifier for TelegramNotifier {
+    fn name(&self) -> &'static str {
+        "Telegram"
+    }
+
+    fn notify<'a>(
+        &'a self,
+        client: &'a Client,
+        message: &'a NotificationMessage,
+    ) -> NotifyFuture<'a> {
+        Box::pin(async move {
+            let base_url = format!("https://api.telegram.org/bot{}", self.bot_token);
+            let chat_id = self.chat_id.clone();
+            let text = message.text();
+            let request = match message.frames.as_slice() {
+                [] => client
+                    .post(format!("{base_url}/sendMessage"))
+                    .form(&[("chat_id", chat_id), ("text", text)]),
+                [frame] => {
+                    let form = Form::new()
+                        .text("chat_id", chat_id)
+                        .text("caption", text)
+                        .part("photo", png_part(frame, 0));
+                    client.post(format!("{base_url}/sendPhoto")).multipart(form)
+                }
+                frames => {
+                    let media = frames
+                        .iter()
+                        .enumerate()
+                        .map(|(i, _)| TelegramInputMedia {
+                            kind: "photo",
+                            media: format!("attach://image_{i}"),
+                            caption: (i == 0).then(|| text.clone()),
+                        })
+                        .collect::<Vec<_>>();
+                    let mut form = Form::new()
+                        .text("chat_id", chat_id)
+                        .text("media", serde_json::to_string(&media)?);
+                    for (i, frame) in frames.iter().enumerate() {
+                        form = form.part(format!("image_{i}"), png_part(frame, i));
+                    }
+                    client
+                        .post(format!("{base_url}/sendMediaGroup"))
+                        .multipart(form)
+                }
+            };
+
+            send_request(request).await
+        })
+    }
+}
+
+/// Posts a JSON body rendered from a template to any HTTP endpoint.
+///
+/// Frames are not sent since there is no common format for attachments.
+#[derive(Debug)]
+struct WebhookNotifier {
+    url: String,
+    /// The JSON body with `{message}` and `{kind}` placeholders
+    template: String,
+}
+
+impl WebhookNotifier {
+    /// Renders the template with the placeholders replaced by JSON-escaped values.
+    ///
+    /// Returns error if the rendered body is not a valid JSON.
+    fn render(&self, message: &NotificationMessage) -> Result<String, Error> {
+        let escape = |value: &str| {
+            let escaped = serde_json::to_string(value).unwrap();
+            escaped[1..escaped.len() - 1].to_string()
+        };
+        let body = self
+            .template
+            .replace("{message}", &escape(&message.text()))
+            .replace("{kind}", &escape(&format!("{:?}", message.kind)));
+        serde_json::from_str::<serde_json::Value>(&body)?;
+        Ok(body)
+    }
+}
+
+impl Notifier for WebhookNotifier {
+    fn name(&self) -> &'static str {
+        "Webhook"
+    }
+
+    fn notify<'a>(
+        &'a self,
+        client: &'a Client,
+        message: &'a NotificationMessage,
+    ) -> NotifyFuture<'a> {
+        Box::pin(async move {
+            let body = self.render(message)?;
+            send_request(
+                client
+                    .post(&self.url)
+                    .header(CONTENT_TYPE, "application/json")
+                    .body(body),
+            )
+            .await
+        })
+    }
+}
+
+/// Publishes to a ntfy topic for push notifications on phones.
+///
+/// Since a ntfy message can only have one attachment, only the last frame is attached.
+#[derive(Debug)]
+struct NtfyNotifier {
+    /// The topic URL (e.g. `https://ntfy.sh/<topic>`)
+    url: String,
+    /// The access token for protected topics
+    access_token: Option<String>,
+}
+
+impl Notifier for NtfyNotifier {
+    fn name(&self) -> &'static str {
+        "ntfy"
+    }
+
+    fn notify<'a>(
+        &'a self,
+        client: &'a Client,
+        message: &'a NotificationMessage,
+    ) -> NotifyFuture<'a> {
+        Box::pin(async move {
+            let text = message.text();
+            let priority = if message.is_urgent() {
+                "urgent"
+            } else {
+                "default"
+            };
+            // Passed as query parameters because headers do not support non-ASCII texts
+            let mut request = match message.frames.last() {
+                Some(frame) => client
+                    .put(&self.url)
+                    .query(&[
+                        ("message", text.as_str()),
+                        ("filename", "image.png"),
+                        ("priority", priority),
+                    ])
+                    .body(frame.clone()),
+                None => client
+                    .post(&self.url)
+                    .query(&[("priority", priority)])
+                    .body(text),
+            };
+            request = request.query(&[("title", "maple-bot")]);
+            if let Some(token) = self.access_token.as_ref() {
+                request = request.bearer_auth(token);
+            }
+
+            send_request(request).await
+        })
+    }
+}
+
+fn png_part(frame: &[u8], i: usize) -> Part {
+    Part::bytes(frame.to_vec())
+        .mime_str("image/png")
+        .unwrap()
+        .file_name(format!("image_{i}.png"))
+}
+
+#[derive(Serialize, Debug)]
+struct TelegramInputMedia {
+    #[serde(rename = "type")]
+    kind: &'static str,
+    media: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    caption: Option<String>,
 }
 
 #[derive(Serialize, Debug)]
@@ -329,16 +840,19 @@ struct Attachment {
 
 #[cfg(test)]
 mod test {
-    use std::{cell::RefCell, rc::Rc, time::Duration};
+    use std::{cell::RefCell, collections::HashMap, rc::Rc, time::Duration};
 
     use tokio::time::{Instant, advance};
 
-    use super::{DiscordNotification, DiscordWebhookBody, NotificationKind, ScheduledNotification};
-    use crate::{Notifications, Settings};
+    use super::{
+        DEFAULT_WEBHOOK_TEMPLATE, Notification, NotificationKind, NotificationMessage,
+        ScheduledNotification, WebhookNotifier, notifiers,
+    };
+    use crate::{NotificationKindOptions, Notifications, Settings};
 
     #[tokio::test(start_paused = true)]
     async fn schedule_kind_unique() {
-        let noti = DiscordNotification::new(Rc::new(RefCell::new(Settings {
+        let noti = Notification::new(Rc::new(RefCell::new(Settings {
             notifications: Notifications {
                 discord_webhook_url: "https://discord.com/api/webhooks/foo/bar".to_string(),
                 notify_on_fail_or_change_map: true,
@@ -370,9 +884,158 @@ mod test {
         );
     }
 
+    #[tokio::test(start_paused = true)]
+    async fn schedule_kind_cooldown() {
+        let noti = Notification::new(Rc::new(RefCell::new(Settings {
+            notifications: Notifications {
+                discord_webhook_url: "https://discord.com/api/webhooks/foo/bar".to_string(),
+                notify_on_rune_appear: true,
+                kind_options: HashMap::from([(
+                    NotificationKind::RuneAppear,
+                    NotificationKindOptions {
+                        cooldown_secs: 60,
+                        attach_snapshots: false,
+                        critical: false,
+                    },
+                )]),
+                ..Default::default()
+            },
+            ..Default::default()
+        })));
+
+        assert!(
+            noti.schedule_notification(NotificationKind::RuneAppear)
+                .is_ok()
+        );
+        assert!(noti.scheduled.lock().unwrap()[0].frames.is_empty());
+
+        // Sent after the delay but still on cooldown
+        advance(Duration::from_secs(10)).await;
+        assert!(
+            noti.schedule_notification(NotificationKind::RuneAppear)
+                .is_err()
+        );
+
+        advance(Duration::from_secs(51)).await;
+        assert!(
+            noti.schedule_notification(NotificationKind::RuneAppear)
+                .is_ok()
+        );
+    }
+
+    #[test]
+    fn notifiers_critical_discord() {
+        let notifications = Notifications {
+            discord_webhook_url: "https://discord.com/api/webhooks/foo/bar".to_string(),
+            discord_critical_webhook_url: "https://discord.com/api/webhooks/foo/baz".to_string(),
+            discord_user_id: "1".to_string(),
+            discord_ping_critical_only: true,
+            ..Default::default()
+        };
+
+        let notifier = format!("{:?}", notifiers(&notifications, false).unwrap());
+        assert!(notifier.contains("foo/bar"));
+        assert!(notifier.contains(r#"user_id: """#));
+
+        let notifier = format!("{:?}", notifiers(&notifications, true).unwrap());
+        assert!(notifier.contains("foo/baz"));
+        assert!(notifier.contains(r#"user_id: "1""#));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn schedule_telegram_without_webhook_url() {
+        let noti = Notification::new(Rc::new(RefCell::new(Settings {
+            notifications: Notifications {
+                telegram_bot_token: "123456:token".to_string(),
+                telegram_chat_id: "42".to_string(),
+                discord_user_id: "1".to_string(),
+                notify_on_lie_detector_appear: true,
+                ..Default::default()
+            },
+            enable_lie_detector_pause: false,
+            ..Default::default()
+        })));
+
+        assert!(
+            noti.schedule_notification(NotificationKind::LieDetectorAppear)
+                .is_ok()
+        );
+        let scheduled = noti.scheduled.lock().unwrap();
+        let scheduled = scheduled.first().unwrap();
+        assert_eq!(
+            scheduled
+                .notifiers
+                .iter()
+                .map(|notifier| notifier.name())
+                .collect::<Vec<_>>(),
+            vec!["Telegram"]
+        );
+        assert_eq!(
+            scheduled.content,
+            "**URGENT** Bot has detected a lie detector, please answer it now"
+        );
+    }
+
+    #[test]
+    fn notifiers_combined() {
+        let notifiers = notifiers(
+            &Notifications {
+                discord_webhook_url: "https://discord.com/api/webhooks/foo/bar".to_string(),
+                webhook_url: "https://example.com/hook".to_string(),
+                ntfy_url: "https://ntfy.sh/komari".to_string(),
+                ..Default::default()
+            },
+            false,
+        )
+        .unwrap();
+
+        assert_eq!(
+            notifiers
+                .iter()
+                .map(|notifier| notifier.name())
+                .collect::<Vec<_>>(),
+            vec!["Discord", "Webhook", "ntfy"]
+        );
+        assert!(
+            notifiers(
+                &Notifications {
+                    ntfy_url: "ntfy.sh/komari".to_string(),
+                    ..Default::default()
+                },
+                false
+            )
+            .is_err()
+        );
+    }
+
+    #[test]
+    fn webhook_render_template() {
+        let message = NotificationMessage {
+            kind: NotificationKind::GmAppear,
+            critical: false,
+            content: "**URGENT** \"GM\"\nline".to_string(),
+            frames: vec![],
+        };
+        let notifier = WebhookNotifier {
+            url: "https://example.com/hook".to_string(),
+            template: DEFAULT_WEBHOOK_TEMPLATE.to_string(),
+        };
+
+        assert_eq!(
+            notifier.render(&message).unwrap(),
+            r#"{"kind": "GmAppear", "message": "URGENT \"GM\"\nline"}"#
+        );
+
+        let notifier = WebhookNotifier {
+            url: "https://example.com/hook".to_string(),
+            template: "{message}".to_string(),
+        };
+        assert!(notifier.render(&message).is_err());
+    }
+
     #[tokio::test(start_paused = true)]
     async fn schedule_invalid_url() {
-        let noti = DiscordNotification::new(Rc::new(RefCell::new(Settings {
+        let noti = Notification::new(Rc::new(RefCell::new(Settings {
             notifications: Notifications {
                 notify_on_fail_or_change_map: true,
                 ..Default::default()
@@ -389,17 +1052,14 @@ mod test {
     #[tokio::test(start_paused = true)]
     #[allow(clippy::await_holding_lock)]
     async fn update_scheduled_frames_deadline() {
-        let noti = DiscordNotification::new(Rc::new(RefCell::new(Settings::default())));
+        let noti = Notification::new(Rc::new(RefCell::new(Settings::default())));
         noti.scheduled.lock().unwrap().push(ScheduledNotification {
             instant: Instant::now(),
             kind: NotificationKind::FailOrMapChange,
-            url: "https://example.com".into(),
+            critical: false,
+            content: "content".into(),
+            notifiers: vec![],
             frames: vec![(None, 3), (None, 6), (None, 9)],
-            body: DiscordWebhookBody {
-                content: "content".into(),
-                username: "username",
-                attachments: vec![],
-            },
         });
 
         advance(Duration::from_secs(4)).await;
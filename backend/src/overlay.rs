@@ -0,0 +1,74 @@
+use opencv::{
+    core::{Mat, MatTraitConst, Point, Rect, Scalar},
+    imgproc::{FONT_HERSHEY_SIMPLEX, LINE_8, put_text_def, rectangle},
+};
+
+use crate::{detect::Detector, minimap::Minimap};
+
+/// Draws the bounding boxes of the detections on a copy of the frame in `detector`.
+///
+/// The minimap, player, rune and portals are only drawn when `minimap` is detected. The
+/// detections are run again on the frame, so this is slow and only meant for debugging.
+pub fn draw_detection_overlay(detector: &dyn Detector, minimap: Minimap) -> Mat {
+    let mut mat = detector.mat().try_clone().unwrap();
+
+    if let Minimap::Idle(idle) = minimap {
+        let bbox = idle.bbox;
+        draw_bbox(
+            &mut mat,
+            bbox,
+            "Minimap",
+            Scalar::new(255.0, 255.0, 255.0, 255.0),
+        );
+        if let Ok(player) = detector.detect_player(bbox) {
+            draw_bbox(
+                &mut mat,
+                player + bbox.tl(),
+                "Player",
+                Scalar::new(0.0, 255.0, 255.0, 255.0),
+            );
+        }
+        if let Ok(rune) = detector.detect_minimap_rune(bbox) {
+            draw_bbox(
+                &mut mat,
+                rune + bbox.tl(),
+                "Rune",
+                Scalar::new(255.0, 0.0, 255.0, 255.0),
+            );
+        }
+        for portal in detector.detect_minimap_portals(bbox) {
+            draw_bbox(
+                &mut mat,
+                portal + bbox.tl(),
+                "Portal",
+                Scalar::new(255.0, 255.0, 0.0, 255.0),
+            );
+        }
+    }
+    if let Ok(health_bar) = detector.detect_player_health_bar() {
+        draw_bbox(
+            &mut mat,
+            health_bar,
+            "HP",
+            Scalar::new(0.0, 255.0, 0.0, 255.0),
+        );
+    }
+    for mob in detector.detect_mob_bboxes() {
+        draw_bbox(&mut mat, mob, "Mob", Scalar::new(0.0, 0.0, 255.0, 255.0));
+    }
+
+    mat
+}
+
+#[inline]
+fn draw_bbox(mat: &mut Mat, bbox: Rect, label: &str, color: Scalar) {
+    let _ = rectangle(mat, bbox, color, 1, LINE_8, 0);
+    let _ = put_text_def(
+        mat,
+        label,
+        bbox.tl() - Point::new(0, 3),
+        FONT_HERSHEY_SIMPLEX,
+        0.4,
+        color,
+    );
+}
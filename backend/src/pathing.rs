@@ -10,6 +10,11 @@ use crate::array::Array;
 
 pub const MAX_PLATFORMS_COUNT: usize = 24;
 
+/// The weight score of moving through a portal.
+///
+/// This is kept low so that portals are preferred over long jump chains.
+const PORTAL_WEIGHT_SCORE: u32 = 1;
+
 /// The kind of movement the player should perform.
 #[derive(Debug, Clone, Copy)]
 #[cfg_attr(test, derive(PartialEq, Eq))]
@@ -18,6 +23,8 @@ pub enum MovementHint {
     Infer,
     /// Performs a walk and then jump.
     WalkAndJump,
+    /// Uses the portal at the current point.
+    Portal,
 }
 
 /// A platform where player can stand on.
@@ -25,11 +32,50 @@ pub enum MovementHint {
 pub struct Platform {
     xs: Range<i32>,
     y: i32,
+    /// The maximum x displacement from [`Self::xs`] if this platform is moving.
+    moving_range: i32,
+    /// The duration of one full movement cycle if this platform is moving.
+    moving_period_millis: u64,
 }
 
 impl Platform {
     pub fn new<R: Into<Range<i32>>>(xs: R, y: i32) -> Self {
-        Self { xs: xs.into(), y }
+        Self {
+            xs: xs.into(),
+            y,
+            moving_range: 0,
+            moving_period_millis: 0,
+        }
+    }
+
+    /// Marks this platform as moving up to `range` in both x directions every `period_millis`.
+    pub fn moving(self, range: i32, period_millis: u64) -> Self {
+        Self {
+            moving_range: range,
+            moving_period_millis: period_millis,
+            ..self
+        }
+    }
+
+    /// The x range the platform can occupy at any point during its movement.
+    #[inline]
+    fn displaced_xs(&self) -> Range<i32> {
+        (self.xs.start - self.moving_range..self.xs.end + self.moving_range).into()
+    }
+
+    /// The x range that stays on the platform regardless of its movement.
+    ///
+    /// Falls back to the middle of the platform if the platform moves more than its width.
+    #[inline]
+    fn landing_xs(&self) -> Range<i32> {
+        let xs: Range<i32> =
+            (self.xs.start + self.moving_range..self.xs.end - self.moving_range).into();
+        if xs.is_empty() && !self.xs.is_empty() {
+            let middle = self.xs.start + (self.xs.end - self.xs.start) / 2;
+            (middle..middle + 1).into()
+        } else {
+            xs
+        }
     }
 }
 
@@ -50,6 +96,26 @@ impl PlatformWithNeighbors {
     pub fn y(&self) -> i32 {
         self.inner.y
     }
+
+    /// The maximum x displacement of this platform if it is moving.
+    #[inline]
+    pub fn moving_range(&self) -> i32 {
+        self.inner.moving_range
+    }
+
+    /// The duration of one full movement cycle of this platform if it is moving.
+    #[inline]
+    pub fn moving_period_millis(&self) -> u64 {
+        self.inner.moving_period_millis
+    }
+
+    /// Iterates over the `(xs, y)` of platforms reachable from this platform.
+    #[inline]
+    pub fn neighbors(&self) -> impl Iterator<Item = (Range<i32>, i32)> {
+        self.neighbors
+            .iter()
+            .map(|platform| (platform.xs, platform.y))
+    }
 }
 
 /// The platform being visited during path finding.
@@ -95,6 +161,23 @@ pub fn find_platforms_bound(
         })
 }
 
+/// Finds the moving platform that `point` is standing on.
+///
+/// The platform's x displacement window is taken into account when checking for `point`.
+pub fn find_moving_platform(
+    platforms: &Array<PlatformWithNeighbors, MAX_PLATFORMS_COUNT>,
+    point: Point,
+) -> Option<PlatformWithNeighbors> {
+    platforms
+        .iter()
+        .find(|platform| {
+            platform.inner.moving_range > 0
+                && platform.inner.y == point.y
+                && platform.inner.displaced_xs().contains(&point.x)
+        })
+        .copied()
+}
+
 /// Builds a list of `PlatformWithNeighbors` from  `&[Platforms]` by determining which platforms
 /// are reachable from each other.
 ///
@@ -143,11 +226,16 @@ pub fn find_neighbors(
 /// Finds a sequence of points representing a path from `from` to `to`, using the given
 /// platform map.
 ///
+/// `portals` are pairs of entry and exit points of in-map portals. A portal connects the
+/// platform of its entry point to the platform of its exit point.
+///
 /// `vertical_threshold` represents maximum y distance between two connected platforms to perform
 /// a grappling. This is used as weight score to help prioritize vertical movement over
 /// horizontal movement. If `enable_hint` is true, provides movement hints like `WalkAndJump`.
+#[allow(clippy::too_many_arguments)]
 pub fn find_points_with(
     platforms: &Array<PlatformWithNeighbors, MAX_PLATFORMS_COUNT>,
+    portals: &[(Point, Point)],
     from: Point,
     to: Point,
     enable_hint: bool,
@@ -161,7 +249,21 @@ pub fn find_points_with(
         .collect::<HashMap<_, _>>();
     let from_platform = find_platform(&platforms, from, None)?; // Clamp `from` to nearest platform
     let to_platform = find_platform(&platforms, to, Some(jump_threshold))?;
+    let portals = portals
+        .iter()
+        .filter_map(|&(entry, exit)| {
+            let entry_platform = find_platform(&platforms, entry, Some(jump_threshold))?;
+            let exit_platform = find_platform(&platforms, exit, Some(jump_threshold))?;
+            (entry_platform != exit_platform).then_some((
+                entry_platform,
+                entry,
+                exit_platform,
+                exit,
+            ))
+        })
+        .collect::<Vec<_>>();
     let mut came_from = HashMap::<Platform, Platform>::new();
+    let mut came_from_portal = HashMap::<Platform, (Point, Point)>::new();
     let mut visiting = BinaryHeap::new();
     let mut score = HashMap::<Platform, u32>::new();
 
@@ -177,6 +279,7 @@ pub fn find_points_with(
         if current.platform == to_platform {
             return points_from(
                 &came_from,
+                &came_from_portal,
                 from,
                 from_platform,
                 to_platform,
@@ -187,16 +290,31 @@ pub fn find_points_with(
             );
         }
 
-        let neighbors = platforms[&current.platform].neighbors;
-        for neighbor in neighbors {
-            let tentative_score = current_score.saturating_add(weight_score(
-                current.platform,
-                neighbor,
-                vertical_threshold,
-            ));
+        let neighbors = platforms[&current.platform]
+            .neighbors
+            .into_iter()
+            .map(|neighbor| {
+                let score = weight_score(current.platform, neighbor, vertical_threshold);
+                (neighbor, score, None)
+            })
+            .chain(
+                portals
+                    .iter()
+                    .filter(|(entry_platform, _, _, _)| *entry_platform == current.platform)
+                    .map(|&(_, entry, exit_platform, exit)| {
+                        (exit_platform, PORTAL_WEIGHT_SCORE, Some((entry, exit)))
+                    }),
+            )
+            .collect::<Vec<_>>();
+        for (neighbor, weight, portal) in neighbors {
+            let tentative_score = current_score.saturating_add(weight);
             let neighbor_score = score.get(&neighbor).copied().unwrap_or(u32::MAX);
             if tentative_score < neighbor_score {
                 came_from.insert(neighbor, current.platform);
+                match portal {
+                    Some(portal) => came_from_portal.insert(neighbor, portal),
+                    None => came_from_portal.remove(&neighbor),
+                };
                 score.insert(neighbor, tentative_score);
                 if !visiting
                     .iter()
@@ -216,10 +334,12 @@ pub fn find_points_with(
 /// Converts a path from the `came_from` graph into a list of `(Point, MovementHint)` pairs
 /// indicating how to move from `from` to `to`.
 ///
-/// Adds offsets to handle jump and landing safety margins.
+/// Adds offsets to handle jump and landing safety margins. Platforms reached through a portal in
+/// `came_from_portal` are moved to by using the portal at its entry point.
 #[allow(clippy::too_many_arguments)]
 fn points_from(
     came_from: &HashMap<Platform, Platform>,
+    came_from_portal: &HashMap<Platform, (Point, Point)>,
     from: Point,
     from_platform: Platform,
     to_platform: Platform,
@@ -252,11 +372,21 @@ fn points_from(
     let double_jump_offset = double_jump_threshold / 2 + DOUBLE_JUMP_EXTRA_OFFSET;
     while went_to.contains_key(&current) {
         let next = went_to[&current];
-        let start_max = max(next.xs.start, current.xs.start);
-        let end_min = min(next.xs.end, current.xs.end);
+        if let Some((entry, exit)) = came_from_portal.get(&next).copied() {
+            points.push((Point::new(entry.x, current.y), MovementHint::Portal));
+            last_point = Point::new(exit.x, next.y);
+            current = next;
+            continue;
+        }
+
+        // Moving platforms only use the range that stays on the platform during movement
+        let current_xs = current.landing_xs();
+        let next_xs = next.landing_xs();
+        let start_max = max(next_xs.start, current_xs.start);
+        let end_min = min(next_xs.end, current_xs.end);
 
         // Check if the current platform overlap with the next platform
-        if ranges_overlap(next.xs, current.xs) {
+        if ranges_overlap(next_xs, current_xs) {
             if (start_max..end_min).contains(&last_point.x) {
                 if last_point.y <= next.y {
                     // Already inside intersection range, add a point to move up.
@@ -273,12 +403,12 @@ fn points_from(
                 points.push((Point::new(x, next.y), MovementHint::Infer));
             }
         } else {
-            let is_ltr = current.xs.start < next.xs.start;
+            let is_ltr = current_xs.start < next_xs.start;
             // Check if can double jump from last_point
             let can_double_jump_last_point = if is_ltr {
-                current.xs.end - last_point.x > double_jump_threshold
+                current_xs.end - last_point.x > double_jump_threshold
             } else {
-                last_point.x - current.xs.start >= double_jump_threshold
+                last_point.x - current_xs.start >= double_jump_threshold
             };
             // Ignore initial point as it has the same platform as the current
             let can_double_jump_last_point = can_double_jump_last_point && points.len() > 1;
@@ -295,9 +425,9 @@ fn points_from(
             };
 
             let from_edge = if is_ltr {
-                (current.xs.end - 1 - offset).clamp(current.xs.start, current.xs.end - 1)
+                (current_xs.end - 1 - offset).clamp(current_xs.start, current_xs.end - 1)
             } else {
-                (current.xs.start + offset).clamp(current.xs.start, current.xs.end - 1)
+                (current_xs.start + offset).clamp(current_xs.start, current_xs.end - 1)
             };
             let from_point = Point::new(from_edge, current.y);
             points.push((from_point, hint));
@@ -327,7 +457,7 @@ fn find_platform(
 ) -> Option<Platform> {
     platforms
         .keys()
-        .filter(|platform| platform.xs.contains(&point.x))
+        .filter(|platform| platform.displaced_xs().contains(&point.x))
         .min_by_key(|platform| (platform.y - point.y).abs())
         .filter(|platform| {
             jump_threshold.is_none() || (platform.y - point.y).abs() < jump_threshold.unwrap()
@@ -428,7 +558,7 @@ mod tests {
         let from = Point::new(10, 50);
         let to = Point::new(20, 60);
 
-        let points = find_points_with(&platforms, from, to, true, 25, 7, 41).unwrap();
+        let points = find_points_with(&platforms, &[], from, to, true, 25, 7, 41).unwrap();
 
         let expected = vec![
             (Point::new(10, 60), MovementHint::Infer),
@@ -449,7 +579,7 @@ mod tests {
         let from = Point::new(25, 50);
         let to = Point::new(65, 55);
 
-        let points = find_points_with(&platforms, from, to, true, 25, 7, 41).unwrap();
+        let points = find_points_with(&platforms, &[], from, to, true, 25, 7, 41).unwrap();
 
         assert_eq!(points.first().unwrap().0.y, 50);
         assert_eq!(points.last().unwrap().0.y, 55);
@@ -468,7 +598,7 @@ mod tests {
         let from = Point::new(10, 50);
         let to = Point::new(20, 131);
 
-        let points = find_points_with(&platforms, from, to, true, 25, 7, 41).unwrap();
+        let points = find_points_with(&platforms, &[], from, to, true, 25, 7, 41).unwrap();
 
         // Check that y-values ascend (multi-hop upward movement)
         let ys: Vec<_> = points.iter().map(|(p, _)| p.y).collect();
@@ -492,10 +622,31 @@ mod tests {
         let from = Point::new(25, 50);
         let to = Point::new(125, 55);
 
-        let points = find_points_with(&platforms, from, to, true, 25, 7, 41);
+        let points = find_points_with(&platforms, &[], from, to, true, 25, 7, 41);
         assert!(points.is_none());
     }
 
+    #[test]
+    fn find_points_with_portal() {
+        let platforms = [
+            Platform::new(0..50, 50),
+            Platform::new(100..150, 55), // Too far
+        ];
+        let platforms = make_platforms_with_neighbors(&platforms);
+        let portals = [(Point::new(40, 50), Point::new(110, 55))];
+
+        let from = Point::new(25, 50);
+        let to = Point::new(125, 55);
+
+        let points = find_points_with(&platforms, &portals, from, to, true, 25, 7, 41).unwrap();
+
+        let expected = vec![
+            (Point::new(40, 50), MovementHint::Portal),
+            (Point::new(125, 55), MovementHint::Infer),
+        ];
+        assert_eq!(points, expected);
+    }
+
     #[test]
     fn find_points_with_walk_and_jump_hint() {
         let platforms = [
@@ -507,7 +658,7 @@ mod tests {
         let from = Point::new(45, 50); // Near right edge of first platform
         let to = Point::new(60, 52); // Near left edge of second platform
 
-        let points = find_points_with(&platforms, from, to, true, 25, 7, 41).unwrap();
+        let points = find_points_with(&platforms, &[], from, to, true, 25, 7, 41).unwrap();
 
         let has_walk_and_jump = points
             .iter()
@@ -520,4 +671,37 @@ mod tests {
         assert_eq!(points.first().unwrap().0.y, 50);
         assert_eq!(points.last().unwrap().0.y, 52);
     }
+
+    #[test]
+    fn find_points_with_moving_platform_landing_xs() {
+        let platforms = [
+            Platform::new(0..100, 50),
+            Platform::new(40..60, 60).moving(5, 2000),
+        ];
+        let platforms = make_platforms_with_neighbors(&platforms);
+
+        let from = Point::new(10, 50);
+        let to = Point::new(50, 60);
+
+        let points = find_points_with(&platforms, &[], from, to, true, 25, 7, 41).unwrap();
+
+        // The point to move up must stay within the range unaffected by the movement
+        let (point, _) = points[0];
+        assert_eq!(point.y, 50);
+        assert!((45..55).contains(&point.x), "got {point:?}");
+    }
+
+    #[test]
+    fn find_moving_platform_within_displacement() {
+        let platforms = [
+            Platform::new(0..100, 50),
+            Platform::new(40..60, 60).moving(5, 2000),
+        ];
+        let platforms = make_platforms_with_neighbors(&platforms);
+
+        assert!(find_moving_platform(&platforms, Point::new(50, 50)).is_none());
+        assert!(find_moving_platform(&platforms, Point::new(36, 60)).is_some());
+        assert!(find_moving_platform(&platforms, Point::new(64, 60)).is_some());
+        assert!(find_moving_platform(&platforms, Point::new(65, 60)).is_none());
+    }
 }
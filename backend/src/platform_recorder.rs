@@ -0,0 +1,167 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use log::debug;
+use opencv::core::Point;
+
+use crate::{
+    database::Platform,
+    player::{PlayerState, scaled_ticks},
+};
+
+/// Number of consecutive ticks the player y must stay the same to be considered on a platform.
+const GROUNDED_TICKS: u32 = 5;
+
+/// Maximum y difference between samples to be merged into the same platform.
+const Y_MERGE_THRESHOLD: i32 = 1;
+
+/// Maximum x gap between samples of the same y to be considered the same platform.
+const X_GAP_THRESHOLD: i32 = 4;
+
+/// Minimum x width of a recorded platform to be proposed.
+const MIN_PLATFORM_WIDTH: i32 = 3;
+
+/// Records platforms from the player positions while the player is moved manually.
+///
+/// A position is sampled when the player is stationary or when the player y has stayed the same
+/// for [`GROUNDED_TICKS`] while walking. The samples are then clustered into platforms by y and
+/// contiguous x ranges.
+#[derive(Debug, Default)]
+pub struct PlatformRecorder {
+    recording: bool,
+    /// Sampled x positions keyed by y.
+    samples: BTreeMap<i32, BTreeSet<i32>>,
+    /// The last player position and the number of consecutive ticks its y stayed the same.
+    last_pos: Option<(Point, u32)>,
+}
+
+impl PlatformRecorder {
+    /// Starts recording and discards previously recorded samples.
+    pub fn start(&mut self) {
+        self.recording = true;
+        self.samples.clear();
+        self.last_pos = None;
+        debug!(target: "platform_recorder", "started");
+    }
+
+    /// Stops recording while keeping the recorded samples.
+    pub fn stop(&mut self) {
+        self.recording = false;
+        self.last_pos = None;
+    }
+
+    /// Discards the recorded samples.
+    pub fn clear(&mut self) {
+        self.samples.clear();
+    }
+
+    /// Adds `platforms` detected by other means (e.g. from the minimap image) as samples.
+    pub fn add_platforms(&mut self, platforms: impl IntoIterator<Item = Platform>) {
+        for platform in platforms {
+            self.samples
+                .entry(platform.y)
+                .or_default()
+                .extend(platform.x_start..=platform.x_end);
+        }
+    }
+
+    #[inline]
+    pub fn is_recording(&self) -> bool {
+        self.recording
+    }
+
+    /// The proposed platforms clustered from the recorded samples for displaying to UI.
+    pub fn platforms(&self) -> Vec<Platform> {
+        cluster_platforms(&self.samples)
+    }
+
+    /// Samples the current player position if recording.
+    pub fn update(&mut self, player: &PlayerState) {
+        if !self.recording {
+            return;
+        }
+        let Some(pos) = player.last_known_pos else {
+            self.last_pos = None;
+            return;
+        };
+
+        let grounded_ticks = match self.last_pos {
+            Some((last_pos, ticks)) if last_pos.y == pos.y => ticks + 1,
+            _ => 0,
+        };
+        self.last_pos = Some((pos, grounded_ticks));
+        if player.is_stationary() || grounded_ticks >= scaled_ticks(GROUNDED_TICKS) {
+            self.samples.entry(pos.y).or_default().insert(pos.x);
+        }
+    }
+}
+
+/// Clusters `samples` into platforms.
+///
+/// Samples with y within [`Y_MERGE_THRESHOLD`] of each other are merged using the y with the most
+/// samples. Each merged group is then split into contiguous x ranges separated by more than
+/// [`X_GAP_THRESHOLD`].
+fn cluster_platforms(samples: &BTreeMap<i32, BTreeSet<i32>>) -> Vec<Platform> {
+    let mut groups = Vec::<(i32, Vec<i32>)>::new();
+    let mut last_y = None;
+    for (&y, xs) in samples {
+        if last_y.is_none_or(|last_y| y - last_y > Y_MERGE_THRESHOLD) {
+            groups.push((y, vec![]));
+        }
+        let (group_y, group_xs) = groups.last_mut().expect("pushed");
+        if xs.len() > samples[&*group_y].len() {
+            *group_y = y;
+        }
+        group_xs.extend(xs);
+        last_y = Some(y);
+    }
+
+    groups
+        .into_iter()
+        .flat_map(|(y, mut xs)| {
+            xs.sort_unstable();
+            xs.dedup();
+            xs.chunk_by(|a, b| b - a <= X_GAP_THRESHOLD)
+                .map(|xs| (xs[0], xs[xs.len() - 1]))
+                .filter(|(x_start, x_end)| x_end - x_start >= MIN_PLATFORM_WIDTH)
+                .map(|(x_start, x_end)| Platform {
+                    x_start,
+                    x_end,
+                    y,
+                    ..Platform::default()
+                })
+                .collect::<Vec<_>>()
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cluster_platforms_merge_y_and_split_x() {
+        let samples = BTreeMap::from([
+            (50, BTreeSet::from([10, 11, 12, 13, 14, 15, 40, 42, 44, 46])),
+            (51, BTreeSet::from([16])),
+            (80, BTreeSet::from([20, 21])),
+        ]);
+
+        assert_eq!(
+            cluster_platforms(&samples),
+            vec![
+                Platform {
+                    x_start: 10,
+                    x_end: 16,
+                    y: 50,
+                    ..Platform::default()
+                },
+                Platform {
+                    x_start: 40,
+                    x_end: 46,
+                    y: 50,
+                    ..Platform::default()
+                },
+            ]
+        );
+    }
+}
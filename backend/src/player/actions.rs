@@ -2,12 +2,12 @@ use opencv::core::{Point, Rect};
 use platforms::windows::KeyKind;
 use strum::Display;
 
-use super::{Player, PlayerState, use_key::UseKey};
+use super::{Player, PlayerState, adjust::ADJUSTING_MEDIUM_THRESHOLD, use_key::UseKey};
 use crate::{
     Action, ActionKey, ActionKeyDirection, ActionKeyWith, ActionMove, FamiliarRarity, KeyBinding,
     Position, SwappableFamiliars,
     array::Array,
-    context::{Context, MS_PER_TICK},
+    context::{Context, millis_to_ticks},
     database::LinkKeyBinding,
     minimap::Minimap,
 };
@@ -18,6 +18,12 @@ const AUTO_MOB_USE_KEY_X_THRESHOLD: i32 = 16;
 /// The minimum y distance required to transition to [`Player::UseKey`] in auto mob action.
 const AUTO_MOB_USE_KEY_Y_THRESHOLD: i32 = 8;
 
+/// The maximum number of detected mobs kept in [`PlayerActionAutoMob::mobs`].
+pub const AUTO_MOB_MAX_MOBS_COUNT: usize = 16;
+
+/// The x distance to walk before using the key in walk-only ping pong action.
+const PING_PONG_WALK_STEP: i32 = 10;
+
 /// Represents the fixed key action.
 ///
 /// Converted from [`ActionKey`] without fields used by [`Rotator`]
@@ -58,12 +64,12 @@ impl From<ActionKey> for PlayerActionKey {
             position,
             direction,
             with,
-            wait_before_use_ticks: (wait_before_use_millis / MS_PER_TICK) as u32,
-            wait_before_use_ticks_random_range: (wait_before_use_millis_random_range / MS_PER_TICK)
-                as u32,
-            wait_after_use_ticks: (wait_after_use_millis / MS_PER_TICK) as u32,
-            wait_after_use_ticks_random_range: (wait_after_use_millis_random_range / MS_PER_TICK)
-                as u32,
+            wait_before_use_ticks: millis_to_ticks(wait_before_use_millis),
+            wait_before_use_ticks_random_range: millis_to_ticks(
+                wait_before_use_millis_random_range,
+            ),
+            wait_after_use_ticks: millis_to_ticks(wait_after_use_millis),
+            wait_after_use_ticks_random_range: millis_to_ticks(wait_after_use_millis_random_range),
         }
     }
 }
@@ -75,6 +81,10 @@ impl From<ActionKey> for PlayerActionKey {
 pub struct PlayerActionMove {
     pub position: Position,
     pub wait_after_move_ticks: u32,
+    /// Whether this action moves the player back to the preset home position.
+    ///
+    /// Home position uses its own platforms pathing configuration.
+    pub home: bool,
 }
 
 impl From<ActionMove> for PlayerActionMove {
@@ -87,7 +97,8 @@ impl From<ActionMove> for PlayerActionMove {
     ) -> Self {
         Self {
             position,
-            wait_after_move_ticks: (wait_after_move_millis / MS_PER_TICK) as u32,
+            wait_after_move_ticks: millis_to_ticks(wait_after_move_millis),
+            home: false,
         }
     }
 }
@@ -103,7 +114,14 @@ pub struct PlayerActionAutoMob {
     pub wait_before_ticks_random_range: u32,
     pub wait_after_ticks: u32,
     pub wait_after_ticks_random_range: u32,
+    /// Number of ticks to hold down the key instead of pressing [`Self::count`] times.
+    pub hold_ticks: Option<u32>,
     pub position: Position,
+    /// The detected mobs positions when this action is created.
+    ///
+    /// These positions are in player relative coordinate and used to determine the direction
+    /// to use the key toward.
+    pub mobs: Array<Point, AUTO_MOB_MAX_MOBS_COUNT>,
 }
 
 impl std::fmt::Display for PlayerActionAutoMob {
@@ -130,11 +148,17 @@ pub struct PlayerActionPingPong {
     pub wait_before_ticks_random_range: u32,
     pub wait_after_ticks: u32,
     pub wait_after_ticks_random_range: u32,
+    /// Number of ticks to hold down the key instead of pressing [`Self::count`] times.
+    pub hold_ticks: Option<u32>,
     /// Bound of ping pong action.
     ///
     /// This bound is in player relative coordinate.
     pub bound: Rect,
     pub direction: PingPongDirection,
+    /// Whether to only walk within the bound instead of double jumping.
+    ///
+    /// Useful for maps where double jumping can move the player into hazards.
+    pub walk_only: bool,
 }
 
 #[derive(Clone, Copy, Debug)]
@@ -161,6 +185,12 @@ pub struct PlayerActionPanic {
     pub to: PanicTo,
 }
 
+#[derive(Clone, Copy, Debug)]
+pub struct PlayerActionCashShop {
+    /// Number of ticks to stay inside the cash shop before exiting.
+    pub stay_ticks: u32,
+}
+
 #[derive(Clone, Copy, Debug)]
 pub enum PanicTo {
     Town,
@@ -185,6 +215,8 @@ pub enum PlayerAction {
     FamiliarsSwapping(PlayerActionFamiliarsSwapping),
     /// Panicking to town or another channel action.
     Panic(PlayerActionPanic),
+    /// Entering the cash shop then exiting action.
+    CashShop(PlayerActionCashShop),
 }
 
 impl From<Action> for PlayerAction {
@@ -227,6 +259,34 @@ pub fn on_ping_pong_double_jump_action(
     (moving, false)
 }
 
+/// Walks a step toward `direction` in walk-only [`PlayerAction::PingPong`].
+///
+/// The step stops slightly past the bound edge so that the edge is considered hit once the step
+/// is reached. Returns `(Player::Idle, true)` when the bound edge is hit.
+#[inline]
+pub fn on_ping_pong_walk_action(
+    cur_pos: Point,
+    bound: Rect,
+    direction: PingPongDirection,
+) -> (Player, bool) {
+    let (hit_x_bound_edge, x) = match direction {
+        PingPongDirection::Left => (
+            cur_pos.x - bound.x <= 0,
+            (cur_pos.x - PING_PONG_WALK_STEP).max(bound.x - ADJUSTING_MEDIUM_THRESHOLD),
+        ),
+        PingPongDirection::Right => (
+            cur_pos.x - bound.x - bound.width >= 0,
+            (cur_pos.x + PING_PONG_WALK_STEP)
+                .min(bound.x + bound.width + ADJUSTING_MEDIUM_THRESHOLD),
+        ),
+    };
+    if hit_x_bound_edge {
+        return (Player::Idle, true);
+    }
+
+    (Player::Moving(Point::new(x, cur_pos.y), false, None), false)
+}
+
 /// Checks proximity in [`PlayerAction::AutoMob`] for transitioning to [`Player::UseKey`].
 ///
 /// This is common logics shared with other contextual states when there is auto mob action.
@@ -317,12 +377,14 @@ pub fn on_action_state_mut(
                     state.clear_unstucking(false);
                 }
                 PlayerAction::Panic(_)
+                | PlayerAction::CashShop(_)
                 | PlayerAction::FamiliarsSwapping(_)
                 | PlayerAction::AutoMob(_)
                 | PlayerAction::Key(PlayerActionKey { position: None, .. }) => (),
             }
             // FIXME: clear only when has position?
             state.clear_action_completed();
+            state.completed_action_count += 1;
         }
         return next;
     }
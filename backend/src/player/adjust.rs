@@ -3,9 +3,9 @@ use std::cmp::Ordering;
 use platforms::windows::KeyKind;
 
 use super::{
-    PlayerAction, PlayerActionKey, PlayerState,
+    PlayerAction, PlayerActionKey, PlayerActionPingPong, PlayerState,
     moving::Moving,
-    timeout::{Lifecycle, next_timeout_lifecycle},
+    timeout::{Lifecycle, next_timeout_lifecycle, scaled_ticks},
     use_key::UseKey,
 };
 use crate::{
@@ -51,16 +51,18 @@ impl Adjusting {
     }
 
     fn update_adjusting(&mut self, context: &Context, up_key: KeyKind, down_key: KeyKind) {
-        self.adjust_timeout =
-            match next_timeout_lifecycle(self.adjust_timeout, ADJUSTING_SHORT_TIMEOUT) {
-                Lifecycle::Started(timeout) => {
-                    let _ = context.keys.send_up(up_key);
-                    let _ = context.keys.send(down_key);
-                    timeout
-                }
-                Lifecycle::Ended => Timeout::default(),
-                Lifecycle::Updated(timeout) => timeout,
-            };
+        self.adjust_timeout = match next_timeout_lifecycle(
+            self.adjust_timeout,
+            scaled_ticks(ADJUSTING_SHORT_TIMEOUT),
+        ) {
+            Lifecycle::Started(timeout) => {
+                let _ = context.keys.send_up(up_key);
+                let _ = context.keys.send(down_key);
+                timeout
+            }
+            Lifecycle::Ended => Timeout::default(),
+            Lifecycle::Updated(timeout) => timeout,
+        };
     }
 }
 
@@ -78,7 +80,12 @@ pub fn update_adjusting_context(
     let (x_distance, x_direction) = moving.x_distance_direction_from(true, cur_pos);
     let is_intermediate = moving.is_destination_intermediate();
 
-    match next_moving_lifecycle_with_axis(moving, cur_pos, MOVE_TIMEOUT, ChangeAxis::Both) {
+    match next_moving_lifecycle_with_axis(
+        moving,
+        cur_pos,
+        scaled_ticks(MOVE_TIMEOUT),
+        ChangeAxis::Both,
+    ) {
         MovingLifecycle::Started(moving) => {
             // Check to perform a fall and returns to walk
             if !is_intermediate
@@ -170,7 +177,9 @@ pub fn update_adjusting_context(
                         );
                     }
 
-                    Player::Adjusting(adjusting.moving(moving.timeout_current(MOVE_TIMEOUT)))
+                    Player::Adjusting(
+                        adjusting.moving(moving.timeout_current(scaled_ticks(MOVE_TIMEOUT))),
+                    )
                 },
             )
         }
@@ -232,7 +241,15 @@ fn on_player_action(
         })
         | PlayerAction::SolveRune
         | PlayerAction::Move(_) => None,
-        PlayerAction::PingPong(_) | PlayerAction::Panic(_) | PlayerAction::FamiliarsSwapping(_) => {
+        PlayerAction::PingPong(PlayerActionPingPong {
+            walk_only: true, ..
+        }) => moving
+            .completed
+            .then_some((Player::UseKey(UseKey::from_action(action)), false)),
+        PlayerAction::PingPong(_)
+        | PlayerAction::Panic(_)
+        | PlayerAction::CashShop(_)
+        | PlayerAction::FamiliarsSwapping(_) => {
             unreachable!()
         }
     }
@@ -1,77 +1,313 @@
+use log::{debug, info};
 use opencv::core::MatTraitConst;
 use platforms::windows::KeyKind;
 
 use super::{
     Player, PlayerState,
-    timeout::{Lifecycle, Timeout, next_timeout_lifecycle},
+    actions::{PlayerAction, on_action},
+    timeout::{Lifecycle, Timeout, next_timeout_lifecycle, scaled_ticks},
 };
 use crate::{bridge::MouseAction, context::Context};
 
+/// The default number of ticks to stay inside the cash shop (about 10 seconds).
+pub const CASH_SHOP_DEFAULT_STAY_TICKS: u32 = 305;
+
+/// Maximum number of times to retry entering the cash shop before giving up.
+const MAX_ENTER_RETRY: u32 = 3;
+
+/// Number of ticks to wait for the cash shop to load after pressing the cash shop key.
+const ENTERING_TIMEOUT: u32 = 150;
+
+/// Number of ticks to wait for the cash shop to close after sending the exit keys.
+const EXITING_TIMEOUT: u32 = 150;
+
+/// Number of ticks to wait for the player to be back in game before checking the cash shop again.
+const EXITED_TIMEOUT: u32 = 300;
+
+/// Number of ticks to wait after the player is back in game (about 3 seconds).
+const STALLING_TIMEOUT: u32 = 90;
+
+/// The stages of entering and exiting the cash shop.
 #[derive(Clone, Copy, Debug)]
-pub enum CashShop {
-    Entering,
+enum CashShopStage {
+    /// Presses the cash shop key and waits for the cash shop to load with the retry count.
+    Entering(u32),
+    /// Stays inside the cash shop for [`CashShop::stay_ticks`].
     Entered,
-    Exitting,
-    Exitted,
+    /// Sends the exit and confirmation keys and waits for the cash shop to close with the
+    /// retry count.
+    Exiting(u32),
+    /// Waits for the player to be detected back in game.
+    Exited,
+    /// Waits a bit after the player is back in game.
     Stalling,
 }
 
-// TODO: Improve this?
+#[derive(Clone, Copy, Debug)]
+pub struct CashShop {
+    stage: CashShopStage,
+    /// Number of ticks to stay inside the cash shop before exiting.
+    stay_ticks: u32,
+}
+
+impl CashShop {
+    pub fn new(stay_ticks: u32) -> Self {
+        Self {
+            stage: CashShopStage::Entering(0),
+            stay_ticks: stay_ticks.max(1),
+        }
+    }
+
+    #[inline]
+    fn stage(self, stage: CashShopStage) -> CashShop {
+        CashShop { stage, ..self }
+    }
+}
+
+/// Updates the [`Player::CashShopThenExit`] contextual state.
+///
+/// Each stage is confirmed by detecting whether the player is inside the cash shop and is
+/// retried when it times out. When the cash shop cannot be entered after [`MAX_ENTER_RETRY`]
+/// retries, this state gives up and returns to [`Player::Idle`].
 pub fn update_cash_shop_context(
     context: &Context,
-    state: &PlayerState,
+    state: &mut PlayerState,
     timeout: Timeout,
     cash_shop: CashShop,
     failed_to_detect_player: bool,
 ) -> Player {
-    match cash_shop {
-        CashShop::Entering => {
-            let _ = context.keys.send(state.config.cash_shop_key);
-            let next = if context.detector_unwrap().detect_player_in_cash_shop() {
-                CashShop::Entered
-            } else {
-                CashShop::Entering
-            };
-            Player::CashShopThenExit(timeout, next)
+    let next = match cash_shop.stage {
+        CashShopStage::Entering(retry_count) => {
+            update_entering(context, state, timeout, cash_shop, retry_count)
+        }
+        CashShopStage::Entered => match next_timeout_lifecycle(timeout, cash_shop.stay_ticks) {
+            Lifecycle::Ended => Player::CashShopThenExit(
+                Timeout::default(),
+                cash_shop.stage(CashShopStage::Exiting(0)),
+            ),
+            Lifecycle::Started(timeout) | Lifecycle::Updated(timeout) => {
+                Player::CashShopThenExit(timeout, cash_shop)
+            }
+        },
+        CashShopStage::Exiting(retry_count) => {
+            update_exiting(context, timeout, cash_shop, retry_count)
+        }
+        CashShopStage::Exited => {
+            update_exited(context, timeout, cash_shop, failed_to_detect_player)
         }
-        CashShop::Entered => {
-            // Exit after 10 secs
-            match next_timeout_lifecycle(timeout, 305) {
-                Lifecycle::Ended => Player::CashShopThenExit(timeout, CashShop::Exitting),
+        CashShopStage::Stalling => {
+            match next_timeout_lifecycle(timeout, scaled_ticks(STALLING_TIMEOUT)) {
+                Lifecycle::Ended => Player::Idle,
                 Lifecycle::Started(timeout) | Lifecycle::Updated(timeout) => {
                     Player::CashShopThenExit(timeout, cash_shop)
                 }
             }
         }
-        CashShop::Exitting => {
-            let next = if context.detector_unwrap().detect_player_in_cash_shop() {
-                CashShop::Exitting
+    };
+
+    on_action(
+        state,
+        |action| match action {
+            PlayerAction::CashShop(_) => Some((next, matches!(next, Player::Idle))),
+            _ => None,
+        },
+        || next,
+    )
+}
+
+fn update_entering(
+    context: &Context,
+    state: &PlayerState,
+    timeout: Timeout,
+    cash_shop: CashShop,
+    retry_count: u32,
+) -> Player {
+    if context.detector_unwrap().detect_player_in_cash_shop() {
+        debug!(target: "player", "cash shop entered");
+        return Player::CashShopThenExit(
+            Timeout::default(),
+            cash_shop.stage(CashShopStage::Entered),
+        );
+    }
+
+    match next_timeout_lifecycle(timeout, scaled_ticks(ENTERING_TIMEOUT)) {
+        Lifecycle::Started(timeout) => {
+            let _ = context.keys.send(state.config.cash_shop_key);
+            Player::CashShopThenExit(timeout, cash_shop)
+        }
+        Lifecycle::Ended => {
+            if retry_count < MAX_ENTER_RETRY {
+                debug!(target: "player", "cash shop not loaded, retrying {retry_count}");
+                Player::CashShopThenExit(
+                    Timeout::default(),
+                    cash_shop.stage(CashShopStage::Entering(retry_count + 1)),
+                )
             } else {
-                CashShop::Exitted
-            };
+                info!(target: "player", "failed to enter cash shop, giving up");
+                Player::Idle
+            }
+        }
+        Lifecycle::Updated(timeout) => Player::CashShopThenExit(timeout, cash_shop),
+    }
+}
+
+fn update_exiting(
+    context: &Context,
+    timeout: Timeout,
+    cash_shop: CashShop,
+    retry_count: u32,
+) -> Player {
+    if !context.detector_unwrap().detect_player_in_cash_shop() {
+        debug!(target: "player", "cash shop exited");
+        return Player::CashShopThenExit(
+            Timeout::default(),
+            cash_shop.stage(CashShopStage::Exited),
+        );
+    }
+
+    match next_timeout_lifecycle(timeout, scaled_ticks(EXITING_TIMEOUT)) {
+        Lifecycle::Started(timeout) => {
+            // Focuses the cash shop, opens the exit confirmation and confirms it
             let size = context.detector_unwrap().mat().size().unwrap();
             let _ = context
                 .keys
                 .send_mouse(size.width / 2, size.height / 2, MouseAction::Click);
             let _ = context.keys.send(KeyKind::Esc);
             let _ = context.keys.send(KeyKind::Enter);
-            Player::CashShopThenExit(timeout, next)
+            Player::CashShopThenExit(timeout, cash_shop)
         }
-        CashShop::Exitted => {
-            if failed_to_detect_player {
-                Player::CashShopThenExit(timeout, cash_shop)
+        // Keeps retrying since the player cannot do anything else inside the cash shop
+        Lifecycle::Ended => {
+            debug!(target: "player", "cash shop still opened, retrying {retry_count}");
+            Player::CashShopThenExit(
+                Timeout::default(),
+                cash_shop.stage(CashShopStage::Exiting(retry_count + 1)),
+            )
+        }
+        Lifecycle::Updated(timeout) => Player::CashShopThenExit(timeout, cash_shop),
+    }
+}
+
+fn update_exited(
+    context: &Context,
+    timeout: Timeout,
+    cash_shop: CashShop,
+    failed_to_detect_player: bool,
+) -> Player {
+    if !failed_to_detect_player {
+        return Player::CashShopThenExit(
+            Timeout::default(),
+            cash_shop.stage(CashShopStage::Stalling),
+        );
+    }
+
+    match next_timeout_lifecycle(timeout, scaled_ticks(EXITED_TIMEOUT)) {
+        Lifecycle::Ended => {
+            // The exit confirmation may have been dismissed instead
+            let stage = if context.detector_unwrap().detect_player_in_cash_shop() {
+                CashShopStage::Exiting(0)
             } else {
-                Player::CashShopThenExit(Timeout::default(), CashShop::Stalling)
-            }
+                CashShopStage::Exited
+            };
+            Player::CashShopThenExit(Timeout::default(), cash_shop.stage(stage))
         }
-        CashShop::Stalling => {
-            // Return after 3 secs
-            match next_timeout_lifecycle(timeout, 90) {
-                Lifecycle::Ended => Player::Idle,
-                Lifecycle::Started(timeout) | Lifecycle::Updated(timeout) => {
-                    Player::CashShopThenExit(timeout, cash_shop)
-                }
-            }
+        Lifecycle::Started(timeout) | Lifecycle::Updated(timeout) => {
+            Player::CashShopThenExit(timeout, cash_shop)
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::assert_matches::assert_matches;
+
+    use anyhow::Ok;
+
+    use super::*;
+    use crate::{bridge::MockKeySender, detect::MockDetector};
+
+    #[test]
+    fn update_entering_retry_then_give_up() {
+        let mut keys = MockKeySender::default();
+        let mut detector = MockDetector::default();
+        detector
+            .expect_detect_player_in_cash_shop()
+            .return_const(false);
+        keys.expect_send().times(1).returning(|_| Ok(()));
+        let context = Context::new(Some(keys), Some(detector));
+        let state = PlayerState::default();
+        let cash_shop = CashShop::new(CASH_SHOP_DEFAULT_STAY_TICKS);
+
+        // Presses the cash shop key once per attempt
+        let player = update_entering(&context, &state, Timeout::default(), cash_shop, 0);
+        assert_matches!(
+            player,
+            Player::CashShopThenExit(
+                Timeout { started: true, .. },
+                CashShop {
+                    stage: CashShopStage::Entering(0),
+                    ..
+                }
+            )
+        );
+
+        let timeout = Timeout {
+            current: ENTERING_TIMEOUT,
+            started: true,
+            ..Default::default()
+        };
+        let player = update_entering(&context, &state, timeout, cash_shop, 0);
+        assert_matches!(
+            player,
+            Player::CashShopThenExit(
+                Timeout { started: false, .. },
+                CashShop {
+                    stage: CashShopStage::Entering(1),
+                    ..
+                }
+            )
+        );
+
+        let player = update_entering(&context, &state, timeout, cash_shop, MAX_ENTER_RETRY);
+        assert_matches!(player, Player::Idle);
+    }
+
+    #[test]
+    fn update_exited_back_to_exiting_if_still_in_cash_shop() {
+        let mut detector = MockDetector::default();
+        detector
+            .expect_detect_player_in_cash_shop()
+            .return_const(true);
+        let context = Context::new(None, Some(detector));
+        let cash_shop = CashShop::new(CASH_SHOP_DEFAULT_STAY_TICKS);
+        let timeout = Timeout {
+            current: EXITED_TIMEOUT,
+            started: true,
+            ..Default::default()
+        };
+
+        let player = update_exited(&context, timeout, cash_shop, true);
+        assert_matches!(
+            player,
+            Player::CashShopThenExit(
+                _,
+                CashShop {
+                    stage: CashShopStage::Exiting(0),
+                    ..
+                }
+            )
+        );
+
+        let player = update_exited(&context, timeout, cash_shop, false);
+        assert_matches!(
+            player,
+            Player::CashShopThenExit(
+                _,
+                CashShop {
+                    stage: CashShopStage::Stalling,
+                    ..
+                }
+            )
+        );
+    }
+}
@@ -10,6 +10,7 @@ use super::{
     moving::Moving,
     timeout::{
         Lifecycle, MovingLifecycle, next_moving_lifecycle_with_axis, next_timeout_lifecycle,
+        scaled_ticks,
     },
     up_jump::UpJumping,
     use_key::UseKey,
@@ -95,7 +96,7 @@ impl DoubleJumping {
     #[inline]
     fn update_jump_cooldown(&mut self) {
         self.cooldown_timeout =
-            match next_timeout_lifecycle(self.cooldown_timeout, COOLDOWN_TIMEOUT) {
+            match next_timeout_lifecycle(self.cooldown_timeout, scaled_ticks(COOLDOWN_TIMEOUT)) {
                 Lifecycle::Started(timeout) => timeout,
                 Lifecycle::Ended => Timeout::default(),
                 Lifecycle::Updated(timeout) => timeout,
@@ -126,11 +127,11 @@ pub fn update_double_jumping_context(
     let moving = double_jumping.moving;
     let ignore_grappling = double_jumping.forced || state.should_disable_grappling();
     let is_intermediate = moving.is_destination_intermediate();
-    let timeout = if double_jumping.forced {
+    let timeout = scaled_ticks(if double_jumping.forced {
         TIMEOUT_FORCED
     } else {
         TIMEOUT
-    };
+    });
     let axis = if double_jumping.forced {
         // This ensures it won't double jump forever when jumping towards either
         // edges of the map.
@@ -260,7 +261,7 @@ pub fn update_double_jumping_context(
 
                     if moving.completed {
                         Player::DoubleJumping(
-                            double_jumping.moving(moving.timeout_current(TIMEOUT)),
+                            double_jumping.moving(moving.timeout_current(scaled_ticks(TIMEOUT))),
                         )
                     } else {
                         Player::DoubleJumping(double_jumping.moving(moving))
@@ -328,7 +329,9 @@ fn on_player_action(
         })
         | PlayerAction::SolveRune
         | PlayerAction::Move { .. } => None,
-        PlayerAction::Panic(_) | PlayerAction::FamiliarsSwapping(_) => unreachable!(),
+        PlayerAction::Panic(_) | PlayerAction::CashShop(_) | PlayerAction::FamiliarsSwapping(_) => {
+            unreachable!()
+        }
     }
 }
 
@@ -5,7 +5,7 @@ use super::{
     Player, PlayerActionKey, PlayerState,
     actions::on_action_state,
     moving::Moving,
-    timeout::{MovingLifecycle, next_moving_lifecycle_with_axis},
+    timeout::{MovingLifecycle, next_moving_lifecycle_with_axis, scaled_ticks},
     use_key::UseKey,
 };
 use crate::{
@@ -54,7 +54,7 @@ pub fn update_falling_context(
     match next_moving_lifecycle_with_axis(
         moving,
         state.last_known_pos.expect("in positional context"),
-        TIMEOUT,
+        scaled_ticks(TIMEOUT),
         ChangeAxis::Vertical,
     ) {
         MovingLifecycle::Started(moving) => {
@@ -95,7 +95,7 @@ pub fn update_falling_context(
             Player::Moving(moving.dest, moving.exact, moving.intermediates)
         }
         MovingLifecycle::Updated(mut moving) => {
-            if moving.timeout.total == STOP_DOWN_KEY_TICK {
+            if moving.timeout.total == scaled_ticks(STOP_DOWN_KEY_TICK) {
                 let _ = context.keys.send_up(KeyKind::Down);
             }
 
@@ -105,7 +105,7 @@ pub fn update_falling_context(
                     moving = moving.completed(true);
                 }
             } else if timeout_on_complete {
-                moving = moving.timeout_current(TIMEOUT);
+                moving = moving.timeout_current(scaled_ticks(TIMEOUT));
             }
 
             on_action_state(
@@ -167,7 +167,7 @@ fn on_player_action(
         | PlayerAction::PingPong(_)
         | PlayerAction::Move(_)
         | PlayerAction::SolveRune => None,
-        PlayerAction::Panic(_) | PlayerAction::FamiliarsSwapping(_) => {
+        PlayerAction::Panic(_) | PlayerAction::CashShop(_) | PlayerAction::FamiliarsSwapping(_) => {
             unreachable!()
         }
     }
@@ -7,7 +7,7 @@ use platforms::windows::KeyKind;
 use super::{
     Player, PlayerState,
     actions::on_action,
-    timeout::{Lifecycle, Timeout, next_timeout_lifecycle},
+    timeout::{Lifecycle, Timeout, next_timeout_lifecycle, scaled_ticks},
 };
 use crate::{
     array::Array,
@@ -214,7 +214,7 @@ fn update_open_menu(
     timeout: Timeout,
     retry_count: u32,
 ) -> FamiliarsSwapping {
-    match next_timeout_lifecycle(timeout, 10) {
+    match next_timeout_lifecycle(timeout, scaled_ticks(10)) {
         Lifecycle::Started(timeout) => {
             let rest = swapping.mouse_rest;
             let _ = context.keys.send_mouse(rest.x, rest.y, MouseAction::Move);
@@ -240,7 +240,7 @@ fn open_setup(
 ) -> FamiliarsSwapping {
     const OPEN_SETUP_TIMEOUT: u32 = 10;
 
-    match next_timeout_lifecycle(timeout, OPEN_SETUP_TIMEOUT) {
+    match next_timeout_lifecycle(timeout, scaled_ticks(OPEN_SETUP_TIMEOUT)) {
         Lifecycle::Started(timeout) => {
             let mut swapping = swapping;
 
@@ -364,7 +364,7 @@ fn update_free_slot(
     const FAMILIAR_CHECK_FREE_TICK: u32 = FAMILIAR_FREE_SLOTS_TIMEOUT;
     const FAMILIAR_CHECK_LVL_5_TICK: u32 = 5;
 
-    match next_timeout_lifecycle(timeout, FAMILIAR_FREE_SLOTS_TIMEOUT) {
+    match next_timeout_lifecycle(timeout, scaled_ticks(FAMILIAR_FREE_SLOTS_TIMEOUT)) {
         Lifecycle::Started(timeout) => {
             // On start, move mouse to hover over the familiar slot to check level
             let bbox = swapping.slots[index].0;
@@ -380,7 +380,7 @@ fn update_free_slot(
             let detector = context.detector_unwrap();
 
             match timeout.current {
-                FAMILIAR_CHECK_LVL_5_TICK => {
+                current if current == scaled_ticks(FAMILIAR_CHECK_LVL_5_TICK) => {
                     match detector.detect_familiar_hover_level() {
                         Ok(FamiliarLevel::Level5) => {
                             // Double click to free
@@ -408,10 +408,10 @@ fn update_free_slot(
                         Err(_) => return swapping.stage_free_slots(index, true),
                     }
                 }
-                FAMILIAR_CHECK_FREE_TICK => {
+                current if current == scaled_ticks(FAMILIAR_CHECK_FREE_TICK) => {
                     if detector.detect_familiar_slot_is_free(bbox) {
                         // If familiar is free, timeout and set flag
-                        timeout.current = FAMILIAR_FREE_SLOTS_TIMEOUT;
+                        timeout.current = scaled_ticks(FAMILIAR_FREE_SLOTS_TIMEOUT);
                         swapping.slots[index].1 = true;
                     } else {
                         // After double clicking, previous slots will move forward so this loop
@@ -468,7 +468,7 @@ fn update_swapping(
     const SWAPPING_TIMEOUT: u32 = 10;
     const SWAPPING_DETECT_LEVEL_TICK: u32 = 5;
 
-    match next_timeout_lifecycle(timeout, SWAPPING_TIMEOUT) {
+    match next_timeout_lifecycle(timeout, scaled_ticks(SWAPPING_TIMEOUT)) {
         Lifecycle::Started(timeout) => {
             let (x, y) = bbox_click_point(swapping.cards[index]);
             let _ = context.keys.send_mouse(x, y, MouseAction::Move);
@@ -498,7 +498,7 @@ fn update_swapping(
             }
         }
         Lifecycle::Updated(timeout) => {
-            if timeout.current == SWAPPING_DETECT_LEVEL_TICK {
+            if timeout.current == scaled_ticks(SWAPPING_DETECT_LEVEL_TICK) {
                 let rest = swapping.mouse_rest;
 
                 match context.detector_unwrap().detect_familiar_hover_level() {
@@ -545,7 +545,7 @@ fn update_scrolling(
     /// Y distance difference indicating the scrollbar has scrolled.
     const SCROLLBAR_SCROLLED_THRESHOLD: i32 = 10;
 
-    match next_timeout_lifecycle(timeout, SCROLLING_TIMEOUT) {
+    match next_timeout_lifecycle(timeout, scaled_ticks(SCROLLING_TIMEOUT)) {
         Lifecycle::Started(timeout) => {
             let Ok(scrollbar) = context.detector_unwrap().detect_familiar_scrollbar() else {
                 // TODO: recoverable?
@@ -578,7 +578,7 @@ fn update_scrolling(
             swapping.stage_completing(Timeout::default(), false)
         }
         Lifecycle::Updated(timeout) => {
-            if timeout.current == SCROLLING_REST_TICK {
+            if timeout.current == scaled_ticks(SCROLLING_REST_TICK) {
                 let (x, y) = bbox_click_point(scrollbar.unwrap());
                 let _ = context.keys.send_mouse(x + 70, y, MouseAction::Move);
             }
@@ -600,7 +600,7 @@ fn update_saving(
     const PRESS_OK_AT: u32 = 15;
     const PRESS_ESC_AT: u32 = 20;
 
-    match next_timeout_lifecycle(timeout, SAVING_TIMEOUT) {
+    match next_timeout_lifecycle(timeout, scaled_ticks(SAVING_TIMEOUT)) {
         Lifecycle::Started(timeout) => {
             let Ok(button) = context.detector_unwrap().detect_familiar_save_button() else {
                 // TODO: recoverable?
@@ -621,13 +621,13 @@ fn update_saving(
         }
         Lifecycle::Updated(timeout) => {
             match timeout.current {
-                PRESS_OK_AT => {
+                current if current == scaled_ticks(PRESS_OK_AT) => {
                     if let Ok(button) = context.detector_unwrap().detect_esc_confirm_button() {
                         let (x, y) = bbox_click_point(button);
                         let _ = context.keys.send_mouse(x, y, MouseAction::Click);
                     }
                 }
-                PRESS_ESC_AT => {
+                current if current == scaled_ticks(PRESS_ESC_AT) => {
                     let _ = context.keys.send(KeyKind::Esc);
                 }
                 _ => (),
@@ -645,7 +645,7 @@ fn update_completing(
     timeout: Timeout,
     completed: bool,
 ) -> FamiliarsSwapping {
-    match next_timeout_lifecycle(timeout, 10) {
+    match next_timeout_lifecycle(timeout, scaled_ticks(10)) {
         Lifecycle::Started(timeout) => {
             let has_menu = context.detector_unwrap().detect_familiar_menu_opened();
             if has_menu {
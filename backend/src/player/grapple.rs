@@ -3,7 +3,7 @@ use super::{
     actions::{on_action_state, on_auto_mob_use_key_action, on_ping_pong_double_jump_action},
     moving::Moving,
     state::LastMovement,
-    timeout::{MovingLifecycle, next_moving_lifecycle_with_axis},
+    timeout::{MovingLifecycle, next_moving_lifecycle_with_axis, scaled_ticks},
 };
 use crate::{
     context::Context,
@@ -45,7 +45,7 @@ pub fn update_grappling_context(
     match next_moving_lifecycle_with_axis(
         moving,
         state.last_known_pos.expect("in positional context"),
-        TIMEOUT,
+        scaled_ticks(TIMEOUT),
         ChangeAxis::Vertical,
     ) {
         MovingLifecycle::Started(moving) => {
@@ -61,17 +61,19 @@ pub fn update_grappling_context(
             let (y_distance, y_direction) = moving.y_distance_direction_from(true, cur_pos);
             let x_changed = prev_pos.x != cur_pos.x;
 
-            if moving.timeout.current >= MOVE_TIMEOUT && x_changed {
+            if moving.timeout.current >= scaled_ticks(MOVE_TIMEOUT) && x_changed {
                 // During double jump and grappling failed
-                moving = moving.timeout_current(TIMEOUT).completed(true);
+                moving = moving
+                    .timeout_current(scaled_ticks(TIMEOUT))
+                    .completed(true);
             }
             if !moving.completed {
                 if y_direction <= 0 || y_distance <= stopping_threshold(state.velocity.1) {
                     let _ = context.keys.send(key);
                     moving = moving.completed(true);
                 }
-            } else if moving.timeout.current >= STOPPING_TIMEOUT {
-                moving = moving.timeout_current(TIMEOUT);
+            } else if moving.timeout.current >= scaled_ticks(STOPPING_TIMEOUT) {
+                moving = moving.timeout_current(scaled_ticks(TIMEOUT));
             }
 
             on_action_state(
@@ -114,7 +116,9 @@ pub fn update_grappling_context(
                         }
                     }
                     PlayerAction::Key(_) | PlayerAction::Move(_) | PlayerAction::SolveRune => None,
-                    PlayerAction::Panic(_) | PlayerAction::FamiliarsSwapping(_) => unreachable!(),
+                    PlayerAction::Panic(_)
+                    | PlayerAction::CashShop(_)
+                    | PlayerAction::FamiliarsSwapping(_) => unreachable!(),
                 },
                 || Player::Grappling(moving),
             )
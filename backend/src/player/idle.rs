@@ -1,18 +1,24 @@
-use log::debug;
+use log::{debug, info};
 use opencv::core::Point;
 use platforms::windows::KeyKind;
 
 use super::{
     Player, PlayerAction, PlayerActionAutoMob, PlayerActionKey, PlayerActionMove, PlayerState,
-    actions::{PlayerActionPingPong, on_action_state_mut, on_ping_pong_double_jump_action},
+    actions::{
+        PlayerActionPingPong, on_action_state_mut, on_ping_pong_double_jump_action,
+        on_ping_pong_walk_action,
+    },
+    cash_shop::CashShop,
     double_jump::DoubleJumping,
     familiars_swap::FamiliarsSwapping,
     moving::{Moving, find_intermediate_points},
     panic::Panicking,
+    timeout::Timeout,
     use_key::UseKey,
 };
 use crate::{
-    ActionKeyDirection, ActionKeyWith, Position, context::Context, minimap::Minimap, rng::Rng,
+    ActionAbortReason, ActionKeyDirection, ActionKeyWith, Position, context::Context,
+    minimap::Minimap, rng::Rng,
 };
 
 /// Updates [`Player::Idle`] contextual state.
@@ -28,6 +34,21 @@ pub fn update_idle_context(context: &Context, state: &mut PlayerState) -> Player
     let _ = context.keys.send_up(KeyKind::Left);
     let _ = context.keys.send_up(KeyKind::Right);
 
+    if let Some(position) = state
+        .priority_action
+        .or(state.normal_action)
+        .and_then(action_position)
+        && !is_valid_action_position(
+            context,
+            position,
+            state.config.action_position_max_platform_distance,
+        )
+    {
+        info!(target: "player", "skipped action with invalid position {position:?}");
+        state.abort_action(ActionAbortReason::InvalidPosition);
+        return Player::Idle;
+    }
+
     on_action_state_mut(
         state,
         |state, action| on_player_action(context, state, action),
@@ -48,6 +69,7 @@ fn on_player_action(
                 match context.minimap {
                     Minimap::Idle(idle) => find_intermediate_points(
                         &idle.platforms,
+                        &state.portal_links,
                         state.last_known_pos.unwrap(),
                         point,
                         position.allow_adjusting,
@@ -77,6 +99,44 @@ fn on_player_action(
                 .or(Some(vec![point]));
             Some((next, false))
         }
+        PlayerAction::Move(PlayerActionMove {
+            position,
+            home: true,
+            ..
+        }) if state.config.home_platforms_pathing => {
+            let x = get_x_destination(&context.rng, position);
+            let point = Point::new(x, position.y);
+            debug!(target: "player", "handling home move: {} {}", x, position.y);
+            let intermediates = match context.minimap {
+                Minimap::Idle(idle) => find_intermediate_points(
+                    &idle.platforms,
+                    &state.portal_links,
+                    cur_pos,
+                    point,
+                    position.allow_adjusting,
+                    state.config.home_platforms_pathing_up_jump_only,
+                    true,
+                ),
+                _ => unreachable!(),
+            };
+            let next = intermediates
+                .map(|mut intermediates| {
+                    let (point, exact) = intermediates.next().unwrap();
+                    Player::Moving(point, exact, Some(intermediates))
+                })
+                .unwrap_or(Player::Moving(point, position.allow_adjusting, None));
+
+            state.last_destinations = intermediates
+                .map(|intermediates| {
+                    intermediates
+                        .inner()
+                        .into_iter()
+                        .map(|(point, _, _)| point)
+                        .collect::<Vec<_>>()
+                })
+                .or(Some(vec![point]));
+            Some((next, false))
+        }
         PlayerAction::Move(PlayerActionMove { position, .. }) => {
             let x = get_x_destination(&context.rng, position);
             debug!(target: "player", "handling move: {} {}", x, position.y);
@@ -132,6 +192,7 @@ fn on_player_action(
                     }
                     let intermediates = find_intermediate_points(
                         &idle.platforms,
+                        &state.portal_links,
                         cur_pos,
                         rune,
                         true,
@@ -155,6 +216,12 @@ fn on_player_action(
             }
             Some((Player::Idle, true))
         }
+        PlayerAction::PingPong(PlayerActionPingPong {
+            bound,
+            direction,
+            walk_only: true,
+            ..
+        }) => Some(on_ping_pong_walk_action(cur_pos, bound, direction)),
         PlayerAction::PingPong(PlayerActionPingPong {
             bound, direction, ..
         }) => Some(on_ping_pong_double_jump_action(
@@ -168,7 +235,65 @@ fn on_player_action(
             false,
         )),
         PlayerAction::Panic(panic) => Some((Player::Panicking(Panicking::new(panic.to)), false)),
+        PlayerAction::CashShop(cash_shop) => Some((
+            Player::CashShopThenExit(Timeout::default(), CashShop::new(cash_shop.stay_ticks)),
+            false,
+        )),
+    }
+}
+
+/// Retrieves the [`Position`] of a positioned [`PlayerAction`] provided by the user.
+#[inline]
+fn action_position(action: PlayerAction) -> Option<Position> {
+    match action {
+        PlayerAction::Move(PlayerActionMove { position, .. })
+        | PlayerAction::Key(PlayerActionKey {
+            position: Some(position),
+            ..
+        }) => Some(position),
+        PlayerAction::Key(_)
+        | PlayerAction::AutoMob(_)
+        | PlayerAction::SolveRune
+        | PlayerAction::PingPong(_)
+        | PlayerAction::FamiliarsSwapping(_)
+        | PlayerAction::Panic(_)
+        | PlayerAction::CashShop(_) => None,
+    }
+}
+
+/// Checks whether `position` is inside the minimap and is not farther than
+/// `max_platform_distance` from the nearest platform.
+///
+/// The platforms check is skipped when `max_platform_distance` is `0` or there is no platform.
+fn is_valid_action_position(
+    context: &Context,
+    position: Position,
+    max_platform_distance: u32,
+) -> bool {
+    let Minimap::Idle(idle) = context.minimap else {
+        return true;
+    };
+    let bbox = idle.bbox;
+    if position.x < 0 || position.x > bbox.width || position.y < 0 || position.y > bbox.height {
+        return false;
     }
+    if max_platform_distance == 0 || idle.platforms.is_empty() {
+        return true;
+    }
+
+    idle.platforms.iter().any(|platform| {
+        let xs = platform.xs();
+        let x_distance = if position.x < xs.start {
+            xs.start - position.x
+        } else if position.x >= xs.end {
+            position.x - (xs.end - 1)
+        } else {
+            0
+        };
+        let y_distance = position.y - platform.y();
+        let distance = ((x_distance.pow(2) + y_distance.pow(2)) as f32).sqrt();
+        distance <= max_platform_distance as f32
+    })
 }
 
 fn get_x_destination(rng: &Rng, position: Position) -> i32 {
@@ -2,7 +2,7 @@ use super::{
     Player, PlayerState,
     moving::{MOVE_TIMEOUT, Moving},
     state::LastMovement,
-    timeout::{ChangeAxis, MovingLifecycle, next_moving_lifecycle_with_axis},
+    timeout::{ChangeAxis, MovingLifecycle, next_moving_lifecycle_with_axis, scaled_ticks},
 };
 use crate::context::Context;
 
@@ -16,7 +16,7 @@ pub fn update_jumping_context(
     match next_moving_lifecycle_with_axis(
         moving,
         state.last_known_pos.expect("in positional context"),
-        TIMEOUT,
+        scaled_ticks(TIMEOUT),
         ChangeAxis::Vertical,
     ) {
         MovingLifecycle::Started(moving) => {
@@ -1,6 +1,6 @@
 use actions::{on_action, on_action_state_mut};
 use adjust::{Adjusting, update_adjusting_context};
-use cash_shop::{CashShop, update_cash_shop_context};
+use cash_shop::{CASH_SHOP_DEFAULT_STAY_TICKS, CashShop, update_cash_shop_context};
 use double_jump::{DoubleJumping, update_double_jumping_context};
 use fall::update_falling_context;
 use familiars_swap::{FamiliarsSwapping, update_familiars_swapping_context};
@@ -46,11 +46,12 @@ mod up_jump;
 mod use_key;
 
 pub use {
-    actions::PanicTo, actions::PingPongDirection, actions::PlayerAction,
-    actions::PlayerActionAutoMob, actions::PlayerActionFamiliarsSwapping, actions::PlayerActionKey,
-    actions::PlayerActionMove, actions::PlayerActionPanic, actions::PlayerActionPingPong,
-    double_jump::DOUBLE_JUMP_THRESHOLD, grapple::GRAPPLING_MAX_THRESHOLD,
-    grapple::GRAPPLING_THRESHOLD, panic::Panicking, state::PlayerState, state::Quadrant,
+    actions::AUTO_MOB_MAX_MOBS_COUNT, actions::PanicTo, actions::PingPongDirection,
+    actions::PlayerAction, actions::PlayerActionAutoMob, actions::PlayerActionCashShop,
+    actions::PlayerActionFamiliarsSwapping, actions::PlayerActionKey, actions::PlayerActionMove,
+    actions::PlayerActionPanic, actions::PlayerActionPingPong, double_jump::DOUBLE_JUMP_THRESHOLD,
+    grapple::GRAPPLING_MAX_THRESHOLD, grapple::GRAPPLING_THRESHOLD, panic::Panicking,
+    solve_rune::parse_rune_arrows, state::PlayerState, state::Quadrant, timeout::scaled_ticks,
 };
 
 /// Minimum y distance from the destination required to perform a jump.
@@ -92,7 +93,7 @@ pub enum Player {
     Stalling(Timeout, u32),
     /// Tries to solve a rune.
     SolvingRune(SolvingRune),
-    /// Enters the cash shop then exit after 10 seconds.
+    /// Enters the cash shop then exits after staying for a while.
     CashShopThenExit(Timeout, CashShop),
     #[strum(to_string = "FamiliarsSwapping({0})")]
     FamiliarsSwapping(FamiliarsSwapping),
@@ -148,16 +149,16 @@ impl Contextual for Player {
 
     // TODO: Detect if a point is reachable after number of retries?
     fn update(self, context: &Context, state: &mut PlayerState) -> ControlFlow<Self> {
-        if state.rune_cash_shop {
+        if state.cash_shop_then_exit {
             let _ = context.keys.send_up(KeyKind::Up);
             let _ = context.keys.send_up(KeyKind::Down);
             let _ = context.keys.send_up(KeyKind::Left);
             let _ = context.keys.send_up(KeyKind::Right);
-            state.rune_cash_shop = false;
+            state.cash_shop_then_exit = false;
             state.reset_to_idle_next_update = false;
             return ControlFlow::Next(Player::CashShopThenExit(
                 Timeout::default(),
-                CashShop::Entering,
+                CashShop::new(scaled_ticks(CASH_SHOP_DEFAULT_STAY_TICKS)),
             ));
         }
 
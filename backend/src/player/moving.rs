@@ -6,17 +6,18 @@ use platforms::windows::KeyKind;
 
 use super::{
     GRAPPLING_MAX_THRESHOLD, JUMP_THRESHOLD, Player, PlayerState,
-    actions::{PlayerAction, PlayerActionKey, PlayerActionMove},
+    actions::{PlayerAction, PlayerActionKey, PlayerActionMove, PlayerActionPingPong},
     double_jump::{DOUBLE_JUMP_THRESHOLD, DoubleJumping},
     state::LastMovement,
-    timeout::Timeout,
+    timeout::{Timeout, scaled_ticks},
     up_jump::UpJumping,
 };
 use crate::{
-    ActionKeyDirection, ActionKeyWith, MAX_PLATFORMS_COUNT,
+    ActionAbortReason, ActionKeyDirection, ActionKeyWith, MAX_PLATFORMS_COUNT,
     array::Array,
     context::Context,
-    pathing::{MovementHint, PlatformWithNeighbors, find_points_with},
+    minimap::Minimap,
+    pathing::{MovementHint, PlatformWithNeighbors, find_moving_platform, find_points_with},
     player::{
         adjust::{ADJUSTING_MEDIUM_THRESHOLD, ADJUSTING_SHORT_THRESHOLD, Adjusting},
         grapple::GRAPPLING_THRESHOLD,
@@ -33,6 +34,9 @@ pub const MOVE_TIMEOUT: u32 = 5;
 const JUMPABLE_RANGE: Range<i32> = 4..JUMP_THRESHOLD;
 const UP_JUMP_THRESHOLD: i32 = 10;
 
+/// Number of ticks to wait for the player to be teleported after using a portal.
+const PORTAL_STALL_TICKS: u32 = 15;
+
 /// Intermediate points to move by.
 ///
 /// The last point is the destination.
@@ -268,7 +272,17 @@ pub fn update_moving_context(
     intermediates: Option<MovingIntermediates>,
 ) -> Player {
     state.use_immediate_control_flow = true;
-    if state.track_unstucking() {
+    let cur_pos = state.last_known_pos.unwrap();
+    let (cur_moving_platform, dest_moving_platform) = match context.minimap {
+        Minimap::Idle(idle) => (
+            find_moving_platform(&idle.platforms, cur_pos),
+            find_moving_platform(&idle.platforms, dest),
+        ),
+        _ => (None, None),
+    };
+    if state.track_unstucking_moving_platform(
+        cur_moving_platform.map(|platform| platform.moving_period_millis()),
+    ) {
         return Player::Unstucking(
             Timeout::default(),
             None,
@@ -276,15 +290,22 @@ pub fn update_moving_context(
         );
     }
 
-    let cur_pos = state.last_known_pos.unwrap();
     let moving = Moving::new(cur_pos, dest, exact, intermediates);
     let is_intermediate = moving.is_destination_intermediate();
     let skip_destination = moving.auto_mob_can_skip_current_destination(state);
 
+    // The destination on a moving platform is considered reached anywhere within its
+    // displacement window
+    let x_tolerance = dest_moving_platform
+        .map(|platform| platform.moving_range())
+        .unwrap_or_default();
     let (x_distance, _) = moving.x_distance_direction_from(true, cur_pos);
+    let x_distance = (x_distance - x_tolerance).max(0);
     let (y_distance, y_direction) = moving.y_distance_direction_from(true, cur_pos);
 
-    let disable_adjusting = state.config.disable_adjusting;
+    // Walk-only ping pong can only move by adjusting
+    let disable_adjusting =
+        state.config.disable_adjusting && !state.has_ping_pong_walk_only_action();
 
     // Check to double jump
     if !skip_destination && x_distance >= state.double_jump_threshold(is_intermediate) {
@@ -332,7 +353,7 @@ pub fn update_moving_context(
             && y_distance >= GRAPPLING_THRESHOLD
         {
             debug!(target: "player", "auto mob aborted because distance for up jump only is too big");
-            state.clear_action_completed();
+            state.abort_action(ActionAbortReason::UnreachablePosition);
             return Player::Idle;
         }
 
@@ -386,7 +407,13 @@ pub fn update_moving_context(
                 KeyKind::Left
             };
             let _ = context.keys.send_down(key);
-            return Player::Stalling(Timeout::default(), 3);
+            return Player::Stalling(Timeout::default(), scaled_ticks(3));
+        }
+        if matches!(moving.intermediate_hint(), Some(MovementHint::Portal)) {
+            debug!(target: "player", "using portal at {cur_pos:?}");
+            state.stalling_timeout_state = Some(Player::Moving(dest, exact, Some(intermediates)));
+            let _ = context.keys.send(KeyKind::Up);
+            return Player::Stalling(Timeout::default(), scaled_ticks(PORTAL_STALL_TICKS));
         }
 
         return Player::Moving(dest, exact, Some(intermediates));
@@ -412,7 +439,7 @@ fn abort_action_on_state_repeat(
     if state.track_last_movement_repeated() {
         info!(target: "player", "abort action due to repeated state");
         state.auto_mob_track_ignore_xs(context, true);
-        state.clear_action_completed();
+        state.abort_action(ActionAbortReason::MovementRepeated);
         return Player::Idle;
     }
     next
@@ -460,14 +487,20 @@ fn on_player_action(
             false,
         )),
         PlayerAction::SolveRune => Some((Player::SolvingRune(SolvingRune::default()), false)),
+        PlayerAction::PingPong(PlayerActionPingPong {
+            walk_only: true, ..
+        }) => Some((Player::UseKey(UseKey::from_action(action)), false)),
         PlayerAction::PingPong(_) => Some((Player::Idle, true)),
-        PlayerAction::Panic(_) | PlayerAction::FamiliarsSwapping(_) => unreachable!(),
+        PlayerAction::Panic(_) | PlayerAction::CashShop(_) | PlayerAction::FamiliarsSwapping(_) => {
+            unreachable!()
+        }
     }
 }
 
 #[inline]
 pub fn find_intermediate_points(
     platforms: &Array<PlatformWithNeighbors, MAX_PLATFORMS_COUNT>,
+    portals: &[(Point, Point)],
     cur_pos: Point,
     dest: Point,
     exact: bool,
@@ -481,6 +514,7 @@ pub fn find_intermediate_points(
     };
     let vec = find_points_with(
         platforms,
+        portals,
         cur_pos,
         dest,
         enable_hint,
@@ -489,11 +523,15 @@ pub fn find_intermediate_points(
         vertical_threshold,
     )?;
     let len = vec.len();
-    let array = Array::from_iter(
-        vec.into_iter()
-            .enumerate()
-            .map(|(i, (point, hint))| (point, hint, if i == len - 1 { exact } else { false })),
-    );
+    // Portal points must be exact for the player to be inside the portal
+    let array = Array::from_iter(vec.into_iter().enumerate().map(|(i, (point, hint))| {
+        let exact = if i == len - 1 {
+            exact
+        } else {
+            matches!(hint, MovementHint::Portal)
+        };
+        (point, hint, exact)
+    }));
     Some(MovingIntermediates {
         current: 0,
         inner: array,
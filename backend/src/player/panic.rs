@@ -3,12 +3,15 @@ use platforms::windows::KeyKind;
 use super::{
     Player, PlayerState,
     actions::{PanicTo, on_action},
+    state::PlayerConfiguration,
     timeout::Timeout,
 };
 use crate::{
+    ChannelSelection,
     context::Context,
     minimap::Minimap,
-    player::timeout::{Lifecycle, next_timeout_lifecycle},
+    player::timeout::{Lifecycle, next_timeout_lifecycle, scaled_ticks},
+    rng::Rng,
 };
 
 const MAX_RETRY: u32 = 3;
@@ -26,6 +29,10 @@ enum PanickingStage {
 #[derive(Debug, Clone, Copy)]
 pub struct Panicking {
     stage: PanickingStage,
+    /// Number of times right is pressed in the change channel menu for the current attempt.
+    channel_presses: u32,
+    /// The channel changed to in the current attempt if the current channel is known.
+    channel_target: Option<u32>,
     pub to: PanicTo,
 }
 
@@ -36,6 +43,8 @@ impl Panicking {
                 PanicTo::Channel => PanickingStage::ChangingChannel(Timeout::default(), 0),
                 PanicTo::Town => PanickingStage::GoingToTown(Timeout::default(), 0),
             },
+            channel_presses: 1,
+            channel_target: None,
             to,
         }
     }
@@ -72,13 +81,33 @@ pub fn update_panicking_context(
     panicking: Panicking,
 ) -> Player {
     let panicking = match panicking.stage {
-        PanickingStage::ChangingChannel(timeout, retry_count) => update_changing_channel(
-            context,
-            state.config.change_channel_key,
-            panicking,
-            timeout,
-            retry_count,
-        ),
+        PanickingStage::ChangingChannel(timeout, retry_count) => {
+            let panicking = if timeout.started {
+                panicking
+            } else {
+                let (channel_presses, channel_target) =
+                    select_channel(&context.rng, &state.config, state.channel);
+                Panicking {
+                    channel_presses,
+                    channel_target,
+                    ..panicking
+                }
+            };
+            let panicking = update_changing_channel(
+                context,
+                state.config.change_channel_key,
+                panicking,
+                timeout,
+                retry_count,
+            );
+            // Changing channel is assumed succeeded when the minimap is no longer idle
+            if matches!(panicking.stage, PanickingStage::Completing(_, false)) {
+                state.channel = panicking.channel_target;
+                state.changed_channel_count += 1;
+            }
+
+            panicking
+        }
         PanickingStage::GoingToTown(timeout, retry_count) => update_going_to_town(
             context,
             state.config.to_town_key,
@@ -126,11 +155,13 @@ fn update_changing_channel(
     const PRESS_RIGHT_AT_INITIAL: u32 = 170;
     const PRESS_ENTER_AT_INITIAL: u32 = 200;
 
-    let max_timeout = if retry_count == 0 {
+    // Each additional right press delays pressing enter by one tick
+    let extra_ticks = panicking.channel_presses.max(1) - 1;
+    let max_timeout = scaled_ticks(if retry_count == 0 {
         TIMEOUT_INITIAL
     } else {
         TIMEOUT_AFTER
-    };
+    }) + extra_ticks;
     match next_timeout_lifecycle(timeout, max_timeout) {
         Lifecycle::Started(timeout) => {
             if !context
@@ -159,8 +190,10 @@ fn update_changing_channel(
             } else {
                 (PRESS_RIGHT_AT_AFTER, PRESS_ENTER_AT_AFTER)
             };
+            let press_right_at = scaled_ticks(press_right_at);
+            let press_enter_at = scaled_ticks(press_enter_at) + extra_ticks;
             match timeout.current {
-                tick if tick == press_right_at => {
+                tick if (press_right_at..=press_right_at + extra_ticks).contains(&tick) => {
                     if context
                         .detector_unwrap()
                         .detect_change_channel_menu_opened()
@@ -184,6 +217,62 @@ fn update_changing_channel(
     }
 }
 
+/// Selects the channel to change to from the `current` channel.
+///
+/// Returns the number of right presses in the change channel menu and the channel changed to if
+/// `current` is known. Blacklisted channels are skipped and whitelisted channels are preferred
+/// when `current` is known. Otherwise, only the selection kind is used.
+fn select_channel(
+    rng: &Rng,
+    config: &PlayerConfiguration,
+    current: Option<u32>,
+) -> (u32, Option<u32>) {
+    let count = config.channel_count;
+    if count <= 1 {
+        return (1, None);
+    }
+    let Some(current) = current.filter(|channel| (1..=count).contains(channel)) else {
+        let presses = match config.channel_selection {
+            ChannelSelection::Next => 1,
+            ChannelSelection::Random => rng.random_range(1..count),
+        };
+        return (presses, None);
+    };
+
+    // Channels in the order of right presses from the current channel
+    let channels = (1..count)
+        .map(|offset| (current - 1 + offset) % count + 1)
+        .filter(|channel| {
+            !config
+                .channel_blacklist
+                .iter()
+                .any(|black| black == channel)
+        })
+        .collect::<Vec<_>>();
+    let whitelisted = channels
+        .iter()
+        .copied()
+        .filter(|channel| {
+            config
+                .channel_whitelist
+                .iter()
+                .any(|white| white == channel)
+        })
+        .collect::<Vec<_>>();
+    let channels = if whitelisted.is_empty() {
+        channels
+    } else {
+        whitelisted
+    };
+    let channel = match config.channel_selection {
+        ChannelSelection::Next => channels.first().copied(),
+        ChannelSelection::Random => rng.random_choose(channels.into_iter()),
+    }
+    .unwrap_or(current % count + 1);
+
+    ((channel + count - current) % count, Some(channel))
+}
+
 fn update_going_to_town(
     context: &Context,
     key: KeyKind,
@@ -191,7 +280,7 @@ fn update_going_to_town(
     timeout: Timeout,
     retry_count: u32,
 ) -> Panicking {
-    match next_timeout_lifecycle(timeout, 90) {
+    match next_timeout_lifecycle(timeout, scaled_ticks(90)) {
         Lifecycle::Started(timeout) => {
             let _ = context.keys.send(key);
             panicking.stage_going_to_town(timeout, retry_count)
@@ -225,7 +314,7 @@ fn update_completing(
         return panicking.stage_completing(timeout, true);
     }
 
-    match next_timeout_lifecycle(timeout, 245) {
+    match next_timeout_lifecycle(timeout, scaled_ticks(245)) {
         Lifecycle::Ended => {
             if let Minimap::Idle(idle) = context.minimap {
                 if idle.has_any_other_player() {
@@ -414,6 +503,28 @@ mod tests {
         );
     }
 
+    #[test]
+    fn select_channel_skip_blacklist_and_prefer_whitelist() {
+        let rng = Rng::new([0; 32]);
+        let mut config = PlayerConfiguration {
+            channel_selection: ChannelSelection::Next,
+            channel_count: 5,
+            channel_blacklist: [5, 1].into_iter().collect(),
+            ..PlayerConfiguration::default()
+        };
+
+        assert_eq!(select_channel(&rng, &config, None), (1, None));
+        assert_eq!(select_channel(&rng, &config, Some(4)), (3, Some(2)));
+        assert_eq!(select_channel(&rng, &config, Some(2)), (1, Some(3)));
+
+        config.channel_whitelist = [3, 5].into_iter().collect();
+        assert_eq!(select_channel(&rng, &config, Some(3)), (1, Some(4)));
+        assert_eq!(select_channel(&rng, &config, Some(4)), (4, Some(3)));
+
+        config.channel_selection = ChannelSelection::Random;
+        assert_eq!(select_channel(&rng, &config, Some(4)), (4, Some(3)));
+    }
+
     #[test]
     fn update_completing_for_town_immediately_complete() {
         let context = Context::new(None, None);
@@ -1,18 +1,26 @@
+use log::debug;
 use platforms::windows::KeyKind;
 
 use super::{
     Player, PlayerState,
     actions::PlayerAction,
-    timeout::{Lifecycle, next_timeout_lifecycle},
+    timeout::{Lifecycle, next_timeout_lifecycle, scaled_ticks},
 };
 use crate::{
     context::Context,
     detect::{ArrowsCalibrating, ArrowsState},
+    network::NotificationKind,
     player::{on_action_state_mut, timeout::Timeout},
 };
 
 const MAX_RETRY_COUNT: u32 = 2;
 
+/// Maximum number of ticks to wait for the rune arrows to be detected consistently.
+const VERIFY_TIMEOUT: u32 = 45;
+
+/// Maximum number of ticks to wait for the rune arrows to be typed remotely.
+const REMOTE_ARROWS_TIMEOUT: u32 = 900;
+
 /// Representing the current stage of rune solving.
 #[derive(Debug, Default, Clone, Copy)]
 pub enum RuneStage {
@@ -23,6 +31,12 @@ pub enum RuneStage {
     FindRegion(ArrowsCalibrating, Timeout, Option<Timeout>, u32),
     // Solves for the rune arrows that possibly include spinning arrows.
     Solving(ArrowsCalibrating, Timeout),
+    // Verifies the solved arrows are the same across consecutive frames.
+    //
+    // The last `u32` is the number of consecutive frames the arrows have been the same.
+    Verifying(ArrowsCalibrating, Timeout, [KeyKind; 4], u32),
+    // Waits for the arrows to be typed remotely after failing to detect them.
+    WaitingRemoteArrows(Timeout),
     // Presses the keys.
     PressKeys(Timeout, [KeyKind; 4], usize),
     // Terminal stage.
@@ -62,6 +76,26 @@ impl SolvingRune {
         }
     }
 
+    #[inline]
+    fn stage_verifying(
+        self,
+        calibrating: ArrowsCalibrating,
+        timeout: Timeout,
+        keys: [KeyKind; 4],
+        count: u32,
+    ) -> SolvingRune {
+        SolvingRune {
+            stage: RuneStage::Verifying(calibrating, timeout, keys, count),
+        }
+    }
+
+    #[inline]
+    fn stage_waiting_remote_arrows(self, timeout: Timeout) -> SolvingRune {
+        SolvingRune {
+            stage: RuneStage::WaitingRemoteArrows(timeout),
+        }
+    }
+
     #[inline]
     fn stage_press_keys(
         self,
@@ -80,6 +114,55 @@ impl SolvingRune {
             stage: RuneStage::Completed,
         }
     }
+
+    /// Waits for the arrows to be typed remotely if `remote_arrows` is true and the user is
+    /// notified or completes otherwise.
+    #[inline]
+    fn stage_failed(self, context: &Context, remote_arrows: bool) -> SolvingRune {
+        if remote_arrows
+            && context
+                .notification
+                .schedule_notification(NotificationKind::RuneArrowsRequested)
+                .is_ok()
+        {
+            self.stage_waiting_remote_arrows(Timeout::default())
+        } else {
+            self.stage_completed()
+        }
+    }
+
+    /// Whether the arrows failed to be detected and are waiting to be typed remotely.
+    #[inline]
+    pub fn is_waiting_remote_arrows(&self) -> bool {
+        matches!(self.stage, RuneStage::WaitingRemoteArrows(_))
+    }
+}
+
+/// Parses the four rune arrows typed remotely (e.g. `ULDR`, `up left down right` or `↑←↓→`).
+pub fn parse_rune_arrows(arrows: &str) -> Option<[KeyKind; 4]> {
+    let arrows = arrows.to_ascii_lowercase();
+    let mut keys = Vec::with_capacity(4);
+    for word in arrows.split(|c: char| c.is_whitespace() || c == ',') {
+        match word {
+            "" => (),
+            "up" => keys.push(KeyKind::Up),
+            "down" => keys.push(KeyKind::Down),
+            "left" => keys.push(KeyKind::Left),
+            "right" => keys.push(KeyKind::Right),
+            _ => {
+                for c in word.chars() {
+                    keys.push(match c {
+                        'u' | '↑' => KeyKind::Up,
+                        'd' | '↓' => KeyKind::Down,
+                        'l' | '←' => KeyKind::Left,
+                        'r' | '→' => KeyKind::Right,
+                        _ => return None,
+                    });
+                }
+            }
+        }
+    }
+    keys.try_into().ok()
 }
 
 /// Updates the [`Player::SolvingRune`] contextual state.
@@ -96,12 +179,11 @@ pub fn update_solving_rune_context(
             if !state.is_stationary || !context.keys.all_keys_cleared() {
                 solving_rune.stage_precondition()
             } else {
-                solving_rune.stage_find_region(
-                    ArrowsCalibrating::default(),
-                    Timeout::default(),
-                    None,
-                    0,
-                )
+                let mut calibrating = ArrowsCalibrating::default();
+                if state.config.rune_fallback_enlarged_crop {
+                    calibrating.enable_enlarged_crop_fallback();
+                }
+                solving_rune.stage_find_region(calibrating, Timeout::default(), None, 0)
             }
         }
         RuneStage::FindRegion(calibrating, timeout, cooldown_timeout, retry_count) => {
@@ -115,8 +197,24 @@ pub fn update_solving_rune_context(
                 retry_count,
             )
         }
-        RuneStage::Solving(calibrating, timeout) => {
-            update_solving(context, solving_rune, calibrating, timeout)
+        RuneStage::Solving(calibrating, timeout) => update_solving(
+            context,
+            solving_rune,
+            calibrating,
+            timeout,
+            SolvingConfig::new(state),
+        ),
+        RuneStage::Verifying(calibrating, timeout, keys, count) => update_verifying(
+            context,
+            solving_rune,
+            calibrating,
+            timeout,
+            keys,
+            count,
+            SolvingConfig::new(state),
+        ),
+        RuneStage::WaitingRemoteArrows(timeout) => {
+            update_waiting_remote_arrows(state, solving_rune, timeout)
         }
         RuneStage::PressKeys(timeout, keys, key_index) => {
             update_press_keys(context, solving_rune, timeout, keys, key_index)
@@ -142,6 +240,7 @@ pub fn update_solving_rune_context(
             PlayerAction::PingPong(_)
             | PlayerAction::AutoMob(_)
             | PlayerAction::Panic(_)
+            | PlayerAction::CashShop(_)
             | PlayerAction::Key(_)
             | PlayerAction::FamiliarsSwapping(_)
             | PlayerAction::Move(_) => {
@@ -164,7 +263,7 @@ fn update_find_region(
     // cooldown_timeout is used to wait for rune cooldown around ~4 secs before hitting interact
     // key again.
     if let Some(cooldown_timeout) = cooldown_timeout {
-        return match next_timeout_lifecycle(cooldown_timeout, 125) {
+        return match next_timeout_lifecycle(cooldown_timeout, scaled_ticks(125)) {
             Lifecycle::Updated(cooldown_timeout) | Lifecycle::Started(cooldown_timeout) => {
                 solving_rune.stage_find_region(
                     calibrating,
@@ -180,7 +279,7 @@ fn update_find_region(
     }
 
     debug_assert!(cooldown_timeout.is_none());
-    match next_timeout_lifecycle(timeout, 35) {
+    match next_timeout_lifecycle(timeout, scaled_ticks(35)) {
         Lifecycle::Started(timeout) => {
             let _ = context.keys.send(interact_key);
             solving_rune.stage_find_region(calibrating, timeout, cooldown_timeout, retry_count)
@@ -194,7 +293,7 @@ fn update_find_region(
                 if retry_count < MAX_RETRY_COUNT {
                     // Retry possibly because mis-pressing the interact key
                     solving_rune.stage_find_region(
-                        ArrowsCalibrating::default(),
+                        calibrating,
                         Timeout::default(),
                         Some(Timeout::default()),
                         retry_count + 1,
@@ -210,29 +309,116 @@ fn update_find_region(
     }
 }
 
+/// Configuration for detecting the rune arrows.
+#[derive(Clone, Copy, Debug)]
+struct SolvingConfig {
+    /// Number of consecutive frames the arrows must match before being pressed.
+    verify_frame_count: u32,
+    /// Whether to wait for the arrows to be typed remotely when detection fails.
+    remote_arrows: bool,
+}
+
+impl SolvingConfig {
+    fn new(state: &PlayerState) -> Self {
+        Self {
+            verify_frame_count: state.config.rune_verify_frame_count,
+            remote_arrows: state.should_wait_remote_rune_arrows(),
+        }
+    }
+}
+
 fn update_solving(
     context: &Context,
     solving_rune: SolvingRune,
     calibrating: ArrowsCalibrating,
     timeout: Timeout,
+    config: SolvingConfig,
 ) -> SolvingRune {
-    match next_timeout_lifecycle(timeout, 150) {
+    match next_timeout_lifecycle(timeout, scaled_ticks(150)) {
         Lifecycle::Started(timeout) => solving_rune.stage_solving(calibrating, timeout),
-        Lifecycle::Ended => solving_rune.stage_completed(),
+        Lifecycle::Ended => solving_rune.stage_failed(context, config.remote_arrows),
         Lifecycle::Updated(timeout) => {
             match context.detector_unwrap().detect_rune_arrows(calibrating) {
                 Ok(ArrowsState::Calibrating(calibrating)) => {
                     solving_rune.stage_solving(calibrating, timeout)
                 }
                 Ok(ArrowsState::Complete(keys)) => {
-                    solving_rune.stage_press_keys(Timeout::default(), keys, 0)
+                    if config.verify_frame_count > 1 {
+                        let mut calibrating = calibrating;
+                        calibrating.clear_cached_arrows();
+                        solving_rune.stage_verifying(calibrating, Timeout::default(), keys, 1)
+                    } else {
+                        solving_rune.stage_press_keys(Timeout::default(), keys, 0)
+                    }
                 }
-                Err(_) => solving_rune.stage_completed(),
+                Err(_) => solving_rune.stage_failed(context, config.remote_arrows),
             }
         }
     }
 }
 
+fn update_verifying(
+    context: &Context,
+    solving_rune: SolvingRune,
+    calibrating: ArrowsCalibrating,
+    timeout: Timeout,
+    keys: [KeyKind; 4],
+    count: u32,
+    config: SolvingConfig,
+) -> SolvingRune {
+    if count >= config.verify_frame_count {
+        debug!(target: "rune", "arrows {keys:?} verified across {count} frames");
+        return solving_rune.stage_press_keys(Timeout::default(), keys, 0);
+    }
+
+    match next_timeout_lifecycle(timeout, scaled_ticks(VERIFY_TIMEOUT)) {
+        Lifecycle::Started(timeout) => {
+            solving_rune.stage_verifying(calibrating, timeout, keys, count)
+        }
+        Lifecycle::Ended => {
+            debug!(target: "rune", "arrows are not detected consistently");
+            solving_rune.stage_failed(context, config.remote_arrows)
+        }
+        Lifecycle::Updated(timeout) => {
+            match context.detector_unwrap().detect_rune_arrows(calibrating) {
+                Ok(ArrowsState::Calibrating(calibrating)) => {
+                    solving_rune.stage_verifying(calibrating, timeout, keys, count)
+                }
+                Ok(ArrowsState::Complete(new_keys)) => {
+                    let count = if new_keys == keys { count + 1 } else { 1 };
+                    solving_rune.stage_verifying(calibrating, timeout, new_keys, count)
+                }
+                Err(_) => solving_rune.stage_failed(context, config.remote_arrows),
+            }
+        }
+    }
+}
+
+fn update_waiting_remote_arrows(
+    state: &mut PlayerState,
+    solving_rune: SolvingRune,
+    timeout: Timeout,
+) -> SolvingRune {
+    match next_timeout_lifecycle(timeout, scaled_ticks(REMOTE_ARROWS_TIMEOUT)) {
+        Lifecycle::Started(timeout) => {
+            // Ignores the arrows typed before being asked
+            state.remote_rune_arrows = None;
+            solving_rune.stage_waiting_remote_arrows(timeout)
+        }
+        Lifecycle::Ended => {
+            debug!(target: "rune", "arrows are not typed remotely in time");
+            solving_rune.stage_completed()
+        }
+        Lifecycle::Updated(timeout) => match state.remote_rune_arrows.take() {
+            Some(keys) => {
+                debug!(target: "rune", "arrows {keys:?} typed remotely");
+                solving_rune.stage_press_keys(Timeout::default(), keys, 0)
+            }
+            None => solving_rune.stage_waiting_remote_arrows(timeout),
+        },
+    }
+}
+
 fn update_press_keys(
     context: &Context,
     solving_rune: SolvingRune,
@@ -242,7 +428,7 @@ fn update_press_keys(
 ) -> SolvingRune {
     const PRESS_KEY_INTERVAL: u32 = 8;
 
-    match next_timeout_lifecycle(timeout, PRESS_KEY_INTERVAL) {
+    match next_timeout_lifecycle(timeout, scaled_ticks(PRESS_KEY_INTERVAL)) {
         Lifecycle::Started(timeout) => {
             let _ = context.keys.send(keys[key_index]);
             solving_rune.stage_press_keys(timeout, keys, key_index)
@@ -426,6 +612,10 @@ mod tests {
                 started: true,
                 ..Default::default()
             },
+            SolvingConfig {
+                verify_frame_count: 1,
+                remote_arrows: false,
+            },
         );
 
         assert_matches!(
@@ -454,6 +644,10 @@ mod tests {
                 started: true,
                 ..Default::default()
             },
+            SolvingConfig {
+                verify_frame_count: 1,
+                remote_arrows: false,
+            },
         );
 
         assert_matches!(
@@ -483,6 +677,10 @@ mod tests {
                 started: true,
                 ..Default::default()
             },
+            SolvingConfig {
+                verify_frame_count: 1,
+                remote_arrows: false,
+            },
         );
 
         assert_matches!(
@@ -501,6 +699,179 @@ mod tests {
         );
     }
 
+    #[test]
+    fn update_solving_to_verifying_on_complete_with_verify_frames() {
+        let expected_keys = [KeyKind::A, KeyKind::S, KeyKind::D, KeyKind::F];
+        let mut detector = MockDetector::default();
+        detector
+            .expect_detect_rune_arrows()
+            .return_once(move |_| Ok(ArrowsState::Complete(expected_keys)));
+        let context = Context::new(None, Some(detector));
+        let solving_rune =
+            SolvingRune::default().stage_solving(ArrowsCalibrating::default(), Timeout::default());
+
+        let result = update_solving(
+            &context,
+            solving_rune,
+            ArrowsCalibrating::default(),
+            Timeout {
+                started: true,
+                ..Default::default()
+            },
+            SolvingConfig {
+                verify_frame_count: 3,
+                remote_arrows: false,
+            },
+        );
+
+        assert_matches!(
+            result,
+            SolvingRune {
+                stage: RuneStage::Verifying(
+                    _,
+                    Timeout { started: false, .. },
+                    [KeyKind::A, KeyKind::S, KeyKind::D, KeyKind::F],
+                    1
+                )
+            }
+        );
+    }
+
+    #[test]
+    fn update_verifying_reset_count_on_different_arrows() {
+        let keys = [KeyKind::A, KeyKind::S, KeyKind::D, KeyKind::F];
+        let new_keys = [KeyKind::F, KeyKind::D, KeyKind::S, KeyKind::A];
+        let mut detector = MockDetector::default();
+        detector
+            .expect_detect_rune_arrows()
+            .return_once(move |_| Ok(ArrowsState::Complete(new_keys)));
+        let context = Context::new(None, Some(detector));
+
+        let result = update_verifying(
+            &context,
+            SolvingRune::default(),
+            ArrowsCalibrating::default(),
+            Timeout {
+                started: true,
+                ..Default::default()
+            },
+            keys,
+            2,
+            SolvingConfig {
+                verify_frame_count: 3,
+                remote_arrows: false,
+            },
+        );
+
+        assert_matches!(
+            result,
+            SolvingRune {
+                stage: RuneStage::Verifying(
+                    _,
+                    _,
+                    [KeyKind::F, KeyKind::D, KeyKind::S, KeyKind::A],
+                    1
+                )
+            }
+        );
+    }
+
+    #[test]
+    fn update_verifying_keeps_calibration_progress() {
+        let keys = [KeyKind::A, KeyKind::S, KeyKind::D, KeyKind::F];
+        let mut detector = MockDetector::default();
+        // Completes only when the calibration from the previous frame is passed back
+        detector
+            .expect_detect_rune_arrows()
+            .times(2)
+            .returning(move |mut calibrating| {
+                if calibrating.is_enlarged_crop_fallback_enabled() {
+                    Ok(ArrowsState::Complete(keys))
+                } else {
+                    calibrating.enable_enlarged_crop_fallback();
+                    Ok(ArrowsState::Calibrating(calibrating))
+                }
+            });
+        let context = Context::new(None, Some(detector));
+        let timeout = Timeout {
+            started: true,
+            ..Default::default()
+        };
+
+        let result = update_verifying(
+            &context,
+            SolvingRune::default(),
+            ArrowsCalibrating::default(),
+            timeout,
+            keys,
+            1,
+            SolvingConfig {
+                verify_frame_count: 3,
+                remote_arrows: false,
+            },
+        );
+        let RuneStage::Verifying(calibrating, timeout, _, 1) = result.stage else {
+            panic!("expected verifying stage with unchanged count");
+        };
+        let result = update_verifying(
+            &context,
+            SolvingRune::default(),
+            calibrating,
+            timeout,
+            keys,
+            1,
+            SolvingConfig {
+                verify_frame_count: 3,
+                remote_arrows: false,
+            },
+        );
+
+        assert_matches!(
+            result,
+            SolvingRune {
+                stage: RuneStage::Verifying(
+                    _,
+                    _,
+                    [KeyKind::A, KeyKind::S, KeyKind::D, KeyKind::F],
+                    2
+                )
+            }
+        );
+    }
+
+    #[test]
+    fn update_verifying_to_press_keys_when_verified() {
+        let keys = [KeyKind::A, KeyKind::S, KeyKind::D, KeyKind::F];
+        let context = Context::new(None, None);
+
+        let result = update_verifying(
+            &context,
+            SolvingRune::default(),
+            ArrowsCalibrating::default(),
+            Timeout {
+                started: true,
+                ..Default::default()
+            },
+            keys,
+            3,
+            SolvingConfig {
+                verify_frame_count: 3,
+                remote_arrows: false,
+            },
+        );
+
+        assert_matches!(
+            result,
+            SolvingRune {
+                stage: RuneStage::PressKeys(
+                    Timeout { started: false, .. },
+                    [KeyKind::A, KeyKind::S, KeyKind::D, KeyKind::F],
+                    0
+                )
+            }
+        );
+    }
+
     #[test]
     fn update_press_keys_to_completed_after_all_keys_sent() {
         let expected_keys = [KeyKind::A, KeyKind::S, KeyKind::D, KeyKind::F];
@@ -550,4 +921,76 @@ mod tests {
             }
         }
     }
+
+    #[test]
+    fn update_solving_to_completed_on_error_with_remote_arrows_not_notified() {
+        let mut detector = MockDetector::default();
+        detector
+            .expect_detect_rune_arrows()
+            .returning(|_| Err(anyhow::anyhow!("fail")));
+        let context = Context::new(None, Some(detector));
+        let solving_rune =
+            SolvingRune::default().stage_solving(ArrowsCalibrating::default(), Timeout::default());
+
+        let result = update_solving(
+            &context,
+            solving_rune,
+            ArrowsCalibrating::default(),
+            Timeout {
+                started: true,
+                ..Default::default()
+            },
+            SolvingConfig {
+                verify_frame_count: 1,
+                remote_arrows: true,
+            },
+        );
+
+        assert_matches!(
+            result,
+            SolvingRune {
+                stage: RuneStage::Completed
+            }
+        );
+    }
+
+    #[test]
+    fn update_waiting_remote_arrows_to_press_keys_when_typed() {
+        let keys = [KeyKind::Up, KeyKind::Left, KeyKind::Down, KeyKind::Right];
+        let mut state = PlayerState::default();
+        let timeout = Timeout {
+            started: true,
+            ..Default::default()
+        };
+
+        let result = update_waiting_remote_arrows(&mut state, SolvingRune::default(), timeout);
+        assert!(result.is_waiting_remote_arrows());
+
+        state.remote_rune_arrows = Some(keys);
+        let result = update_waiting_remote_arrows(&mut state, SolvingRune::default(), timeout);
+
+        assert_matches!(
+            result,
+            SolvingRune {
+                stage: RuneStage::PressKeys(
+                    _,
+                    [KeyKind::Up, KeyKind::Left, KeyKind::Down, KeyKind::Right],
+                    0
+                )
+            }
+        );
+        assert!(state.remote_rune_arrows.is_none());
+    }
+
+    #[test]
+    fn parse_rune_arrows_formats() {
+        let keys = Some([KeyKind::Up, KeyKind::Left, KeyKind::Down, KeyKind::Right]);
+
+        assert_eq!(parse_rune_arrows("ULDR"), keys);
+        assert_eq!(parse_rune_arrows(" up, left down Right "), keys);
+        assert_eq!(parse_rune_arrows("↑←↓→"), keys);
+        assert_eq!(parse_rune_arrows("ULD"), None);
+        assert_eq!(parse_rune_arrows("ULDRU"), None);
+        assert_eq!(parse_rune_arrows("ULDX"), None);
+    }
 }
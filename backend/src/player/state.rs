@@ -1,25 +1,40 @@
-use std::{collections::HashMap, range::Range};
+use std::{
+    collections::HashMap,
+    mem,
+    range::Range,
+    time::{Duration, Instant},
+};
 
-use anyhow::Result;
-use log::debug;
+use anyhow::{Result, anyhow};
+use log::{debug, info};
 use opencv::core::{Point, Rect};
 use platforms::windows::KeyKind;
 
 use super::{
     DOUBLE_JUMP_THRESHOLD, JUMP_THRESHOLD, MOVE_TIMEOUT, Player, PlayerAction,
+    PlayerActionPingPong,
     double_jump::DOUBLE_JUMP_AUTO_MOB_THRESHOLD,
     fall::FALLING_THRESHOLD,
-    timeout::{Lifecycle, Timeout, next_timeout_lifecycle},
+    timeout::{Lifecycle, Timeout, next_timeout_lifecycle, scaled_ticks, tuned_ticks},
 };
+#[cfg(debug_assertions)]
+use crate::PlayerStateSnapshot;
 use crate::{
-    ActionKeyDirection, Class,
+    ActionAbortReason, ActionKeyDirection, ChannelSelection, Class, DoubleJumpKeyTiming,
+    MAX_UNSTUCK_STRATEGIES, PriorityActionPreemption, UnstuckStrategy, UpJumpStyle,
     array::Array,
     bridge::MouseAction,
     buff::{Buff, BuffKind},
     context::Context,
+    database::{AutoMobData, MAX_CHANNEL_COUNT},
     minimap::Minimap,
     network::NotificationKind,
-    task::{Task, Update, update_detection_task},
+    pathing::{MAX_PLATFORMS_COUNT, PlatformWithNeighbors},
+    screenshot::ScreenshotEvent,
+    task::{
+        Task, Update, update_detection_task, update_detection_task_with_budget,
+        update_inference_task,
+    },
 };
 
 /// The maximum number of times rune solving can fail before transition to
@@ -49,6 +64,11 @@ const AUTO_MOB_IGNORE_XS_SOLIDIFY_COUNT: u32 = 3;
 /// If an auto-mob x position is 5, then the range is [2, 8].
 const AUTO_MOB_IGNORE_XS_RANGE: i32 = 3;
 
+/// The interval at which solidified ignored auto-mob x ranges lose a count.
+///
+/// This allows x ranges that become reachable (e.g. platforms changed) to be retried.
+const AUTO_MOB_IGNORE_XS_DECAY_INTERVAL: Duration = Duration::from_secs(600);
+
 /// The acceptable y range above and below the detected mob position when matched
 /// with a reachable y.
 const AUTO_MOB_REACHABLE_Y_THRESHOLD: i32 = 10;
@@ -61,6 +81,15 @@ const AUTO_MOB_HORIZONTAL_MOVEMENT_REPEAT_COUNT: u32 = 4;
 /// auto-mob before aborting.
 const AUTO_MOB_VERTICAL_MOVEMENT_REPEAT_COUNT: u32 = 3;
 
+/// The factor each auto-mob quadrant mob density is multiplied by before adding the newly
+/// detected mobs.
+///
+/// This keeps mobs detected while passing by a quadrant for a while after they are off-screen.
+const AUTO_MOB_QUADRANT_DENSITY_DECAY: f32 = 0.9;
+
+/// The minimum auto-mob quadrant mob density to be preferred over the clockwise quadrant.
+const AUTO_MOB_QUADRANT_DENSITY_THRESHOLD: f32 = 1.0;
+
 /// Maximum number of times [`Player::Moving`] state can be transitioned to
 /// without changing position.
 const UNSTUCK_COUNT_THRESHOLD: u32 = 6;
@@ -68,6 +97,33 @@ const UNSTUCK_COUNT_THRESHOLD: u32 = 6;
 /// The number of times [`Player::Unstucking`] can be transitioned to before entering GAMBA MODE.
 const UNSTUCK_GAMBA_MODE_COUNT: u32 = 3;
 
+/// The number of mobbing attacks in a row without detected damage numbers before transitioning to
+/// [`Player::Unstucking`].
+const MOBBING_NO_DAMAGE_COUNT_THRESHOLD: u32 = 5;
+
+/// The delay between each damage numbers detection while mobbing.
+const MOBBING_DAMAGE_DETECTION_DELAY_MILLIS: u64 = 500;
+
+/// The maximum duration of a health detection before its result is considered outdated.
+const HEALTH_DETECTION_BUDGET: Duration = Duration::from_millis(1000);
+
+/// The maximum duration of a damage numbers detection before its result is considered outdated.
+const MOBBING_DAMAGE_DETECTION_BUDGET: Duration = Duration::from_millis(300);
+
+/// The number of health updates failing in a row before the health bar is detected again.
+const HEALTH_UPDATE_MAX_FAIL_COUNT: u32 = 3;
+
+/// The delay between each health bar revalidation once it is detected.
+///
+/// The health bar is detected again in case the game UI has been moved or rescaled.
+const HEALTH_BAR_REVALIDATE_DELAY_MILLIS: u64 = 30000;
+
+/// The number of potion uses in a row not increasing health before the actions are halted.
+///
+/// This is not one since health can be detected from a frame captured before the potion takes
+/// effect or the player can take more damage than the potion heals.
+const POTION_NO_EFFECT_MAX_COUNT: u32 = 5;
+
 /// The number of samples to store for approximating velocity.
 const VELOCITY_SAMPLES: usize = MOVE_TIMEOUT as usize;
 
@@ -80,6 +136,19 @@ pub enum Quadrant {
 }
 
 impl Quadrant {
+    /// Determines the quadrant of `bound` containing `point` where both are relative to the
+    /// minimap top-left coordinate.
+    fn from_point(point: Point, bound: Rect) -> Quadrant {
+        let bound_x_mid = bound.x + bound.width / 2;
+        let bound_y_mid = bound.y + bound.height / 2;
+        match (point.x < bound_x_mid, point.y < bound_y_mid) {
+            (true, true) => Quadrant::TopLeft,
+            (false, true) => Quadrant::TopRight,
+            (false, false) => Quadrant::BottomRight,
+            (true, false) => Quadrant::BottomLeft,
+        }
+    }
+
     fn next_clockwise(self) -> Quadrant {
         match self {
             Quadrant::TopLeft => Quadrant::TopRight,
@@ -109,6 +178,11 @@ pub struct PlayerConfiguration {
     pub class: Class,
     /// Whether to disable [`Player::Adjusting`].
     pub disable_adjusting: bool,
+    /// When to use the key of an action with [`crate::ActionKeyWith::DoubleJump`] after double jumped.
+    pub double_jump_key_timing: DoubleJumpKeyTiming,
+    /// Number of ticks to wait after double jumped before using the key of an action with
+    /// [`crate::ActionKeyWith::DoubleJump`].
+    pub double_jump_key_delay_ticks: u32,
     /// Enables platform pathing for rune.
     pub rune_platforms_pathing: bool,
     /// Uses only up jump(s) in rune platform pathing.
@@ -121,6 +195,36 @@ pub struct PlayerConfiguration {
     ///
     /// TODO: This shouldn't be here...
     pub auto_mob_platforms_bound: bool,
+    /// Prefers the auto mob quadrant with more detected mobs over the clockwise quadrant.
+    pub auto_mob_density_pathing: bool,
+    /// Transitions to [`Player::Unstucking`] when mobbing attacks produce no damage numbers.
+    pub mobbing_damage_check: bool,
+    /// Retries rune detection with an enlarged and denoised crop when it fails.
+    pub rune_fallback_enlarged_crop: bool,
+    /// Halts instead of entering cash shop when failing to solve rune too many times.
+    pub rune_fallback_halt: bool,
+    /// Waits for the arrows to be typed remotely when failing to detect them.
+    pub rune_fallback_remote: bool,
+    /// Number of consecutive frames the rune arrows must be detected the same before pressing.
+    pub rune_verify_frame_count: u32,
+    /// Halts in town when a server maintenance countdown is detected.
+    pub maintenance_halt: bool,
+    /// Number of milliseconds before the server maintenance countdown ends to halt at the latest.
+    pub maintenance_halt_margin_millis: u64,
+    /// The maximum distance a positioned action can be from the nearest platform.
+    ///
+    /// A value of `0` disables the check.
+    pub action_position_max_platform_distance: u32,
+    /// What happens when a priority action is queued during a normal action.
+    pub priority_action_preemption: PriorityActionPreemption,
+    /// Strategies to try in order when stuck mid-air in GAMBA mode.
+    pub unstuck_strategies_mid_air: Array<UnstuckStrategy, MAX_UNSTUCK_STRATEGIES>,
+    /// Strategies to try in order when stuck on ground in GAMBA mode.
+    pub unstuck_strategies_on_ground: Array<UnstuckStrategy, MAX_UNSTUCK_STRATEGIES>,
+    /// Enables platform pathing for returning to home position.
+    pub home_platforms_pathing: bool,
+    /// Uses only up jump(s) in home position platform pathing.
+    pub home_platforms_pathing_up_jump_only: bool,
     /// The interact key.
     pub interact_key: KeyKind,
     /// The `Rope Lift` skill key.
@@ -133,6 +237,8 @@ pub struct PlayerConfiguration {
     pub jump_key: KeyKind,
     /// The up jump key with [`None`] indicating composite jump (Up arrow + Double Space).
     pub upjump_key: Option<KeyKind>,
+    /// The key combination used to up jump.
+    pub up_jump_style: UpJumpStyle,
     /// The cash shop key.
     pub cash_shop_key: KeyKind,
     /// The familiar key.
@@ -141,12 +247,29 @@ pub struct PlayerConfiguration {
     pub to_town_key: KeyKind,
     /// The change channel key.
     pub change_channel_key: KeyKind,
+    /// How the channel to change to is selected.
+    pub channel_selection: ChannelSelection,
+    /// Number of channels in the change channel menu.
+    pub channel_count: u32,
+    /// Channels to never change to.
+    pub channel_blacklist: Array<u32, MAX_CHANNEL_COUNT>,
+    /// Channels to prefer when changing channel.
+    pub channel_whitelist: Array<u32, MAX_CHANNEL_COUNT>,
     /// The potion key.
     pub potion_key: KeyKind,
     /// Uses potion when health is below a percentage.
     pub use_potion_below_percent: Option<f32>,
     /// Milliseconds interval to update current health.
     pub update_health_millis: Option<u64>,
+    /// The emergency key (e.g. a town return scroll).
+    pub emergency_key: KeyKind,
+    /// Uses the emergency key when health is below a percentage.
+    ///
+    /// Only used when [`Self::use_potion_below_percent`] is set.
+    pub use_emergency_key_below_percent: Option<f32>,
+    /// Halts the actions when using potion does not increase health
+    /// [`POTION_NO_EFFECT_MAX_COUNT`] times in a row.
+    pub potion_no_effect_halt: bool,
 }
 
 /// The player persistent states.
@@ -167,6 +290,10 @@ pub struct PlayerState {
     ///
     /// This action will override the normal action if it is in the middle of executing.
     pub(super) priority_action: Option<PlayerAction>,
+    /// The id and reason of the last action aborted by the player.
+    ///
+    /// This is taken by [`Rotator`] to track the action statistics.
+    aborted_action: Option<(u32, ActionAbortReason)>,
     /// The player current health and max health.
     health: Option<(u32, u32)>,
     /// The task to update health.
@@ -174,7 +301,19 @@ pub struct PlayerState {
     /// The rectangular health bar region.
     health_bar: Option<Rect>,
     /// The task for the health bar.
+    ///
+    /// This task is also used to revalidate [`Self::health_bar`] once it is detected.
     health_bar_task: Option<Task<Result<Rect>>>,
+    /// The number of health updates failing in a row.
+    health_fail_count: u32,
+    /// The current health when the potion key was last used.
+    potion_used_at_health: Option<u32>,
+    /// The number of potion uses in a row not increasing health.
+    potion_no_effect_count: u32,
+    /// Indicates the actions should be halted because using potion does not increase health.
+    ///
+    /// Only set when [`PlayerConfiguration::potion_no_effect_halt`] is enabled.
+    potion_halt: bool,
     /// Track if the player moved within a specified ticks to determine if the player is
     /// stationary.
     is_stationary_timeout: Timeout,
@@ -230,12 +369,25 @@ pub struct PlayerState {
     ///
     /// This will help auto-mobbing ignores positions that are known to be not reachable.
     auto_mob_ignore_xs_map: HashMap<i32, Vec<(Range<i32>, u32)>>,
+    /// The last time solidified x ranges in [`Self::auto_mob_ignore_xs_map`] were decayed.
+    auto_mob_ignore_xs_last_decay: Option<Instant>,
+    /// Pairs of entry and exit positions of the in-map portals learned from teleports.
+    ///
+    /// A pair is learned when the player moves from inside one portal to inside another
+    /// in a single update.
+    pub(super) portal_links: Vec<(Point, Point)>,
+    /// The channel the player is in if known.
+    ///
+    /// This is tracked after each channel change and kept when resetting.
+    pub(super) channel: Option<u32>,
     /// The last auto-mobbing quadrant kind.
     auto_mob_last_quadrant: Option<Quadrant>,
     /// The last auto-mobbing bound's quadrant relative to bottom-left player coordinate.
     auto_mob_last_quadrant_bound: Option<Rect>,
     /// The next auto-mobbing bound's quadrant relative to bottom-left player coordinate.
     auto_mob_next_quadrant_bound: Option<Rect>,
+    /// The decayed number of detected mobs in each auto-mobbing quadrant indexed by [`Quadrant`].
+    auto_mob_quadrant_densities: [f32; 4],
     /// Tracks whether movement-related actions do not change the player position after a while.
     ///
     /// Resets when a limit is reached (for unstucking) or position did change.
@@ -244,12 +396,51 @@ pub struct PlayerState {
     ///
     /// Resets when threshold reached or position changed.
     unstuck_transitioned_count: u32,
+    /// The last time [`Self::unstuck_count`] was incremented while on a moving platform.
+    unstuck_moving_platform_last_tracked: Option<Instant>,
+    /// The index of the next configured unstuck strategy to use.
+    ///
+    /// Resets when position changed.
+    unstuck_strategy_index: usize,
+    /// The configured unstuck strategy currently used by [`Player::Unstucking`].
+    pub(super) unstuck_strategy: Option<UnstuckStrategy>,
+    /// The number of mobbing attacks in a row without any detected damage numbers.
+    ///
+    /// Resets when damage numbers are detected or a limit is reached (for unstucking).
+    mobbing_no_damage_count: u32,
+    /// The task for detecting damage numbers while mobbing.
+    mobbing_damage_task: Option<Task<Result<()>>>,
     /// Unstuck task for detecting settings when mis-pressing ESC key.
     pub(super) unstuck_task: Option<Task<Result<bool>>>,
     /// The number of times [`Player::SolvingRune`] failed.
     rune_failed_count: u32,
+    /// The number of runes solved since last taken by [`Self::take_solved_rune_count`].
+    solved_rune_count: u32,
+    /// The number of runes failed since last taken by [`Self::take_failed_rune_count`].
+    failed_rune_count: u32,
+    /// The number of channel changes since last taken by
+    /// [`Self::take_changed_channel_count`].
+    pub(super) changed_channel_count: u32,
+    /// The number of times the player needed unstucking since last taken by
+    /// [`Self::take_unstuck_event_count`].
+    unstuck_event_count: u32,
+    /// The number of actions completed since last taken by
+    /// [`Self::take_completed_action_count`].
+    pub(super) completed_action_count: u32,
     /// Indicates the state will be transitioned to [`Player::CashShopThenExit`] in the next tick.
-    pub(super) rune_cash_shop: bool,
+    pub(super) cash_shop_then_exit: bool,
+    /// Indicates the actions should be halted because of failing to solve rune too many times.
+    ///
+    /// Only set when [`PlayerConfiguration::rune_fallback_halt`] is enabled.
+    rune_halt: bool,
+    /// The rune arrows typed remotely to be pressed by [`Player::SolvingRune`].
+    pub(super) remote_rune_arrows: Option<[KeyKind; 4]>,
+    /// The task for detecting the server maintenance countdown.
+    maintenance_task: Option<Task<Result<Duration>>>,
+    /// The latest instant to halt because of the detected server maintenance countdown.
+    ///
+    /// Only set when [`PlayerConfiguration::maintenance_halt`] is enabled.
+    maintenance_halt_at: Option<Instant>,
     /// [`Timeout`] for validating whether the rune is solved.
     ///
     /// This is [`Some`] when [`Player::SolvingRune`] successfully detects the rune
@@ -268,26 +459,182 @@ pub struct PlayerState {
 impl PlayerState {
     /// Resets the player state except for configuration.
     ///
-    /// Used whenever minimap data or configuration changes.
+    /// Used whenever minimap data or configuration changes. The auto-mobbing learned positions
+    /// are reloaded from `auto_mob_data` if provided.
     #[inline]
-    pub fn reset(&mut self) {
+    pub fn reset(&mut self, auto_mob_data: Option<AutoMobData>) {
+        let AutoMobData {
+            reachable_y_map,
+            ignore_xs_map,
+            portal_links,
+            ..
+        } = auto_mob_data.unwrap_or_default();
+        let ignore_xs_map = ignore_xs_map
+            .into_iter()
+            .map(|(y, xs)| {
+                let xs = xs
+                    .into_iter()
+                    .map(|((start, end), count)| ((start..end).into(), count))
+                    .collect();
+                (y, xs)
+            })
+            .collect();
+        let portal_links = portal_links
+            .into_iter()
+            .map(|((entry_x, entry_y), (exit_x, exit_y))| {
+                (Point::new(entry_x, entry_y), Point::new(exit_x, exit_y))
+            })
+            .collect();
         *self = PlayerState {
             config: self.config,
             reset_to_idle_next_update: true,
+            auto_mob_reachable_y_map: reachable_y_map,
+            auto_mob_ignore_xs_map: ignore_xs_map,
+            portal_links,
+            channel: self.channel,
             ..PlayerState::default()
         };
     }
 
+    /// Sets the `channel` the player is currently in.
+    #[inline]
+    pub fn set_channel(&mut self, channel: Option<u32>) {
+        self.channel = channel;
+    }
+
+    /// The auto-mobbing learned positions for persisting to the [`Minimap`] with `minimap_id`.
+    pub fn auto_mob_data(&self, minimap_id: i64) -> AutoMobData {
+        AutoMobData {
+            id: Some(minimap_id),
+            reachable_y_map: self.auto_mob_reachable_y_map.clone(),
+            ignore_xs_map: self.auto_mob_ignore_xs_pairs(),
+            portal_links: self
+                .portal_links
+                .iter()
+                .map(|(entry, exit)| ((entry.x, entry.y), (exit.x, exit.y)))
+                .collect(),
+        }
+    }
+
+    #[inline]
+    fn auto_mob_ignore_xs_pairs(&self) -> HashMap<i32, Vec<((i32, i32), u32)>> {
+        self.auto_mob_ignore_xs_map
+            .iter()
+            .map(|(y, xs)| {
+                let xs = xs
+                    .iter()
+                    .map(|(range, count)| ((range.start, range.end), *count))
+                    .collect();
+                (*y, xs)
+            })
+            .collect()
+    }
+
     #[inline]
     pub fn health(&self) -> Option<(u32, u32)> {
         self.health
     }
 
+    /// Takes the number of runes solved since last taken.
+    #[inline]
+    pub fn take_solved_rune_count(&mut self) -> u32 {
+        mem::take(&mut self.solved_rune_count)
+    }
+
+    /// Takes the number of actions completed since last taken.
+    #[inline]
+    pub fn take_completed_action_count(&mut self) -> u32 {
+        mem::take(&mut self.completed_action_count)
+    }
+
+    /// Takes the number of runes failed since last taken.
+    #[inline]
+    pub fn take_failed_rune_count(&mut self) -> u32 {
+        mem::take(&mut self.failed_rune_count)
+    }
+
+    /// Takes the number of channel changes since last taken.
+    #[inline]
+    pub fn take_changed_channel_count(&mut self) -> u32 {
+        mem::take(&mut self.changed_channel_count)
+    }
+
+    /// Takes the number of times the player needed unstucking since last taken.
+    #[inline]
+    pub fn take_unstuck_event_count(&mut self) -> u32 {
+        mem::take(&mut self.unstuck_event_count)
+    }
+
+    /// Whether the player has not moved for a while.
+    #[inline]
+    pub fn is_stationary(&self) -> bool {
+        self.is_stationary
+    }
+
+    /// The approximated absolute player velocity in pixels per tick.
+    #[inline]
+    pub fn velocity(&self) -> (f32, f32) {
+        self.velocity
+    }
+
     #[inline]
     pub fn is_dead(&self) -> bool {
         self.is_dead
     }
 
+    /// Transitions the player to [`Player::CashShopThenExit`] in the next tick.
+    #[inline]
+    pub fn set_cash_shop_then_exit(&mut self) {
+        self.cash_shop_then_exit = true;
+    }
+
+    /// Whether the actions should be halted because of failing to solve rune too many times.
+    ///
+    /// The flag is reset after calling.
+    #[inline]
+    pub fn take_rune_halt(&mut self) -> bool {
+        mem::take(&mut self.rune_halt)
+    }
+
+    /// Sets the rune arrows typed remotely.
+    ///
+    /// The arrows are only pressed when [`Player::SolvingRune`] is waiting for them.
+    #[inline]
+    pub fn set_remote_rune_arrows(&mut self, keys: [KeyKind; 4]) {
+        self.remote_rune_arrows = Some(keys);
+    }
+
+    /// Whether the actions should be halted because using potion does not increase health.
+    ///
+    /// The flag is reset after calling.
+    #[inline]
+    pub fn take_potion_halt(&mut self) -> bool {
+        mem::take(&mut self.potion_halt)
+    }
+
+    /// Whether there is a pending halt because of the server maintenance countdown.
+    ///
+    /// When pending, no new action should be queued so that the current action can finish.
+    #[inline]
+    pub fn has_maintenance_halt(&self) -> bool {
+        self.maintenance_halt_at.is_some()
+    }
+
+    /// Whether the actions should be halted because of the server maintenance countdown.
+    ///
+    /// Returns `true` once the current actions are completed or the latest halt instant is
+    /// reached. The flag is reset after returning `true`.
+    pub fn take_maintenance_halt(&mut self) -> bool {
+        let Some(halt_at) = self.maintenance_halt_at else {
+            return false;
+        };
+        if (self.has_normal_action() || self.has_priority_action()) && Instant::now() < halt_at {
+            return false;
+        }
+        self.maintenance_halt_at = None;
+        true
+    }
+
     #[cfg(test)]
     pub fn normal_action(&self) -> Option<PlayerAction> {
         self.normal_action
@@ -309,6 +656,23 @@ impl PlayerState {
         }
     }
 
+    /// Takes a snapshot of the internal states for debugging with `player` as the current
+    /// contextual state.
+    #[cfg(debug_assertions)]
+    pub fn debug_snapshot(&self, player: Player) -> PlayerStateSnapshot {
+        PlayerStateSnapshot {
+            state: player.to_string(),
+            last_movement: self.last_movement.map(|movement| format!("{movement:?}")),
+            normal_action_id: self.normal_action_id(),
+            priority_action_id: self.priority_action_id(),
+            velocity: self.velocity,
+            unstuck_count: self.unstuck_count,
+            unstuck_transitioned_count: self.unstuck_transitioned_count,
+            reachable_y_map: self.auto_mob_reachable_y_map.clone(),
+            ignore_xs_map: self.auto_mob_ignore_xs_pairs(),
+        }
+    }
+
     /// Whether is a normal action.
     #[inline]
     pub fn has_normal_action(&self) -> bool {
@@ -413,6 +777,19 @@ impl PlayerState {
         !self.has_priority_action() && matches!(self.normal_action, Some(PlayerAction::PingPong(_)))
     }
 
+    /// Whether there is only ping pong action that walks instead of double jumping.
+    #[inline]
+    pub(super) fn has_ping_pong_walk_only_action(&self) -> bool {
+        !self.has_priority_action()
+            && matches!(
+                self.normal_action,
+                Some(PlayerAction::PingPong(PlayerActionPingPong {
+                    walk_only: true,
+                    ..
+                }))
+            )
+    }
+
     /// Clears both on-going normal and priority actions due to being aborted and whether to reset
     /// the player to [`Player::Idle`].
     #[inline]
@@ -422,6 +799,27 @@ impl PlayerState {
         self.normal_action = None;
     }
 
+    /// Clears either normal or priority action due to being aborted with `reason`.
+    ///
+    /// Unlike [`Self::clear_actions_aborted`], this is aborted by the player itself and is
+    /// recorded for [`Self::take_aborted_action`].
+    #[inline]
+    pub(super) fn abort_action(&mut self, reason: ActionAbortReason) {
+        let id = if self.has_priority_action() {
+            self.priority_action_id
+        } else {
+            self.normal_action_id
+        };
+        self.aborted_action = id.map(|id| (id, reason));
+        self.clear_action_completed();
+    }
+
+    /// Takes the id and reason of the last action aborted by the player if there is one.
+    #[inline]
+    pub fn take_aborted_action(&mut self) -> Option<(u32, ActionAbortReason)> {
+        self.aborted_action.take()
+    }
+
     /// Clears either normal or priority due to completion.
     #[inline]
     pub(super) fn clear_action_completed(&mut self) {
@@ -448,17 +846,73 @@ impl PlayerState {
         self.unstuck_count = 0;
         if include_transitioned_count {
             self.unstuck_transitioned_count = 0;
+            self.unstuck_strategy_index = 0;
+        }
+    }
+
+    /// Picks the next configured unstuck strategy in order and cycles back once exhausted.
+    ///
+    /// Returns [`None`] when no strategy is configured for `mid_air`.
+    #[inline]
+    pub(super) fn next_unstuck_strategy(&mut self, mid_air: bool) -> Option<UnstuckStrategy> {
+        let strategies = if mid_air {
+            self.config.unstuck_strategies_mid_air
+        } else {
+            self.config.unstuck_strategies_on_ground
+        };
+        if strategies.is_empty() {
+            return None;
         }
+
+        let strategy = strategies[self.unstuck_strategy_index % strategies.len()];
+        self.unstuck_strategy_index = (self.unstuck_strategy_index + 1) % strategies.len();
+        Some(strategy)
+    }
+
+    /// Whether the player at `pos` is not standing on any of `platforms`.
+    ///
+    /// Falls back to the auto-mobbing reachable y map when there is no platform.
+    #[inline]
+    pub(super) fn is_mid_air(
+        &self,
+        platforms: &Array<PlatformWithNeighbors, MAX_PLATFORMS_COUNT>,
+        pos: Point,
+    ) -> bool {
+        if platforms.is_empty() {
+            return !self.auto_mob_reachable_y_map.contains_key(&pos.y);
+        }
+
+        !platforms
+            .iter()
+            .any(|platform| platform.y() == pos.y && platform.xs().contains(&pos.x))
+    }
+
+    /// Whether to wait for the rune arrows to be typed remotely when detection fails.
+    ///
+    /// Only waits on the last attempt before [`PlayerState::cash_shop_then_exit`] or
+    /// [`PlayerState::rune_halt`] is set.
+    #[inline]
+    pub(super) fn should_wait_remote_rune_arrows(&self) -> bool {
+        self.config.rune_fallback_remote && self.rune_failed_count + 1 >= MAX_RUNE_FAILED_COUNT
     }
 
-    /// Increments the rune validation fail count and sets [`PlayerState::rune_cash_shop`]
-    /// if needed.
+    /// Increments the rune validation fail count and sets [`PlayerState::cash_shop_then_exit`]
+    /// or [`PlayerState::rune_halt`] if needed.
     #[inline]
-    fn track_rune_fail_count(&mut self) {
+    fn track_rune_fail_count(&mut self, context: &Context) {
         self.rune_failed_count += 1;
+        self.failed_rune_count += 1;
+        context.screenshots.schedule(ScreenshotEvent::RuneFailed);
         if self.rune_failed_count >= MAX_RUNE_FAILED_COUNT {
             self.rune_failed_count = 0;
-            self.rune_cash_shop = true;
+            if self.config.rune_fallback_halt {
+                self.rune_halt = true;
+                let _ = context
+                    .notification
+                    .schedule_notification(NotificationKind::RuneFailed);
+            } else {
+                self.cash_shop_then_exit = true;
+            }
         }
     }
 
@@ -484,12 +938,59 @@ impl PlayerState {
         self.unstuck_count += 1;
         if self.unstuck_count >= UNSTUCK_COUNT_THRESHOLD {
             self.unstuck_count = 0;
+            self.unstuck_event_count += 1;
             true
         } else {
             false
         }
     }
 
+    /// Increments the number of mobbing attacks without damage numbers if
+    /// [`PlayerConfiguration::mobbing_damage_check`] is enabled.
+    ///
+    /// Returns `true` when the player should transition to [`Player::Unstucking`].
+    #[inline]
+    pub(super) fn track_mobbing_no_damage(&mut self) -> bool {
+        if !self.config.mobbing_damage_check {
+            return false;
+        }
+        self.mobbing_no_damage_count += 1;
+        if self.mobbing_no_damage_count >= MOBBING_NO_DAMAGE_COUNT_THRESHOLD {
+            self.mobbing_no_damage_count = 0;
+            self.unstuck_event_count += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Increments the unstucking counter at most once per `period_millis` while the player is on
+    /// a moving platform.
+    ///
+    /// The player position keeps changing while standing on a moving platform, so re-entering
+    /// movement states within one movement cycle is expected and should not count toward
+    /// unstucking. When `period_millis` is [`None`], this is the same as
+    /// [`Self::track_unstucking`].
+    ///
+    /// Returns `true` when the player should transition to [`Player::Unstucking`].
+    #[inline]
+    pub(super) fn track_unstucking_moving_platform(&mut self, period_millis: Option<u64>) -> bool {
+        let Some(period_millis) = period_millis else {
+            self.unstuck_moving_platform_last_tracked = None;
+            return self.track_unstucking();
+        };
+        let now = Instant::now();
+        if self
+            .unstuck_moving_platform_last_tracked
+            .is_some_and(|instant| now.duration_since(instant).as_millis() < period_millis as u128)
+        {
+            return false;
+        }
+
+        self.unstuck_moving_platform_last_tracked = Some(now);
+        self.track_unstucking()
+    }
+
     /// Tracks the last movement to determine whether the state has repeated passing a threshold.
     #[inline]
     pub(super) fn track_last_movement_repeated(&mut self) -> bool {
@@ -553,6 +1054,8 @@ impl PlayerState {
     pub(super) fn double_jump_threshold(&self, is_intermediate: bool) -> i32 {
         if self.has_auto_mob_action_only() && !is_intermediate {
             DOUBLE_JUMP_AUTO_MOB_THRESHOLD
+        } else if self.has_ping_pong_walk_only_action() {
+            i32::MAX // Walk-only ping pong never double jumps
         } else if self.has_ping_pong_action_only() {
             0 // Ping pong double jumps forever
         } else if self.config.teleport_key.is_some() {
@@ -579,11 +1082,25 @@ impl PlayerState {
         self.auto_mob_last_quadrant
     }
 
+    /// Tracks the detected `mobs` for estimating the mob density of each quadrant of `bound`.
+    ///
+    /// Both `mobs` and `bound` are relative to the minimap top-left coordinate.
+    pub fn auto_mob_track_quadrant_mobs(&mut self, bound: Rect, mobs: &[Point]) {
+        let mut counts = [0; 4];
+        for mob in mobs {
+            counts[Quadrant::from_point(*mob, bound) as usize] += 1;
+        }
+        for (density, count) in self.auto_mob_quadrant_densities.iter_mut().zip(counts) {
+            *density = *density * AUTO_MOB_QUADRANT_DENSITY_DECAY + count as f32;
+        }
+    }
+
     /// Picks a pathing point in auto mobbing to move to where `bound` is relative to the minimap
     /// top-left coordinate.
     ///
     /// The current implementation chooses a pathing point going clockwise order in the four
-    /// quadrant of `bound`.
+    /// quadrant of `bound`. If [`PlayerConfiguration::auto_mob_density_pathing`] is enabled, the
+    /// other quadrant with the highest mob density is chosen instead if any.
     ///
     /// The returned [`Point`] is in player coordinate relative to bottom-left.
     #[inline]
@@ -623,22 +1140,16 @@ impl PlayerState {
         } else {
             // Determine the player current quadrant inside the auto-mobbing bound
             // Convert current position to top-left coordinate first
-            let bound_width_half = bound.width / 2;
-            let bound_height_half = bound.height / 2;
-            let bound_x_mid = bound.x + bound_width_half;
-            let bound_y_mid = bound.y + bound_height_half;
             let pos = self.last_known_pos.expect("inside positional context");
-            let pos = Point::new(pos.x, bbox.height - pos.y);
-            match (pos.x < bound_x_mid, pos.y < bound_y_mid) {
-                (true, true) => Quadrant::TopLeft,
-                (false, true) => Quadrant::TopRight,
-                (false, false) => Quadrant::BottomRight,
-                (true, false) => Quadrant::BottomLeft,
-            }
+            Quadrant::from_point(Point::new(pos.x, bbox.height - pos.y), bound)
         };
 
-        // Retrieve the next quadrant in clockwise order relative to current
-        let next_quadrant = current_quadrant.next_clockwise();
+        // Retrieve the next quadrant in clockwise order relative to current or the densest
+        let next_quadrant = if self.config.auto_mob_density_pathing {
+            self.auto_mob_densest_quadrant(current_quadrant)
+        } else {
+            current_quadrant.next_clockwise()
+        };
         let next_quadrant_bound = quadrant_bound(next_quadrant, bound);
         let next_next_quadrant_bound = quadrant_bound(next_quadrant.next_clockwise(), bound);
 
@@ -698,6 +1209,28 @@ impl PlayerState {
         Point::new(x, y)
     }
 
+    /// Picks the quadrant other than `current_quadrant` with the highest mob density.
+    ///
+    /// Falls back to the next clockwise quadrant if no quadrant is dense enough. Ties are broken
+    /// in clockwise order.
+    fn auto_mob_densest_quadrant(&self, current_quadrant: Quadrant) -> Quadrant {
+        let mut quadrant = current_quadrant.next_clockwise();
+        let mut densest = None::<(Quadrant, f32)>;
+        while quadrant != current_quadrant {
+            let density = self.auto_mob_quadrant_densities[quadrant as usize];
+            if density >= AUTO_MOB_QUADRANT_DENSITY_THRESHOLD
+                && densest.is_none_or(|(_, densest)| density > densest)
+            {
+                densest = Some((quadrant, density));
+            }
+            quadrant = quadrant.next_clockwise();
+        }
+
+        densest
+            .map(|(quadrant, _)| quadrant)
+            .unwrap_or(current_quadrant.next_clockwise())
+    }
+
     /// Whether the auto mob reachable y requires "solidifying".
     #[inline]
     pub(super) fn auto_mob_reachable_y_require_update(&self, y: i32) -> bool {
@@ -816,6 +1349,7 @@ impl PlayerState {
         if self.auto_mob_ignore_xs_map.is_empty() {
             self.auto_mob_populate_ignore_xs(context);
         }
+        self.auto_mob_decay_ignore_xs();
 
         let (x, y) = match self.normal_action.unwrap() {
             PlayerAction::AutoMob(mob) => (mob.position.x, mob.position.y),
@@ -824,6 +1358,7 @@ impl PlayerState {
             | PlayerAction::Key(_)
             | PlayerAction::Move(_)
             | PlayerAction::Panic(_)
+            | PlayerAction::CashShop(_)
             | PlayerAction::SolveRune => {
                 unreachable!()
             }
@@ -896,6 +1431,28 @@ impl PlayerState {
         }
     }
 
+    /// Decays solidified x ranges by one count every [`AUTO_MOB_IGNORE_XS_DECAY_INTERVAL`].
+    ///
+    /// A decayed x range is no longer ignored until it is aborted again.
+    fn auto_mob_decay_ignore_xs(&mut self) {
+        let now = Instant::now();
+        let Some(last_decay) = self.auto_mob_ignore_xs_last_decay else {
+            self.auto_mob_ignore_xs_last_decay = Some(now);
+            return;
+        };
+        if now.duration_since(last_decay) < AUTO_MOB_IGNORE_XS_DECAY_INTERVAL {
+            return;
+        }
+
+        self.auto_mob_ignore_xs_last_decay = Some(now);
+        self.auto_mob_ignore_xs_map
+            .values_mut()
+            .flatten()
+            .filter(|(_, count)| *count >= AUTO_MOB_IGNORE_XS_SOLIDIFY_COUNT)
+            .for_each(|(_, count)| *count -= 1);
+        debug!(target: "player", "auto mob decayed ignore xs {:?}", self.auto_mob_ignore_xs_map);
+    }
+
     pub(super) fn auto_mob_populate_ignore_xs(&mut self, context: &Context) {
         let (platforms, minimap_width) = match context.minimap {
             Minimap::Idle(idle) => (idle.platforms, idle.bbox.width),
@@ -953,6 +1510,8 @@ impl PlayerState {
             self.update_rune_validating_state(context);
             self.update_is_dead_state(context);
             self.update_is_arrow_spam_state(context);
+            self.update_maintenance_state(context);
+            self.update_mobbing_damage_state(context);
             true
         } else {
             false
@@ -983,16 +1542,22 @@ impl PlayerState {
         // TODO: Should keep original coordinate? And flips before passing to UI?
         let y = minimap_bbox.height - br.y;
         let pos = Point::new(x, y);
+        if let Minimap::Idle(idle) = context.minimap
+            && let Some(last_known_pos) = self.last_known_pos
+        {
+            self.update_portal_links(idle.portals(), last_known_pos, pos);
+        }
         let last_known_pos = self.last_known_pos.unwrap_or(pos);
         if last_known_pos != pos {
             self.unstuck_count = 0;
             self.unstuck_transitioned_count = 0;
+            self.unstuck_strategy_index = 0;
             self.is_stationary_timeout = Timeout::default();
         }
         self.update_velocity(pos, context.tick);
 
         let (is_stationary, is_stationary_timeout) =
-            match next_timeout_lifecycle(self.is_stationary_timeout, MOVE_TIMEOUT) {
+            match next_timeout_lifecycle(self.is_stationary_timeout, scaled_ticks(MOVE_TIMEOUT)) {
                 Lifecycle::Started(timeout) => (false, timeout),
                 Lifecycle::Ended => (true, self.is_stationary_timeout),
                 Lifecycle::Updated(timeout) => (false, timeout),
@@ -1003,6 +1568,38 @@ impl PlayerState {
         true
     }
 
+    /// Learns a portal link if the player is teleported from inside one portal to another.
+    ///
+    /// The existing link with the same entry portal is replaced.
+    #[inline]
+    fn update_portal_links(
+        &mut self,
+        portals: impl IntoIterator<Item = Rect> + Copy,
+        last_known_pos: Point,
+        pos: Point,
+    ) {
+        let portal_of = |pos: Point| {
+            portals.into_iter().find(|portal| {
+                (portal.x..portal.x + portal.width).contains(&pos.x)
+                    && (portal.y..portal.y + portal.height).contains(&pos.y)
+            })
+        };
+        let Some(entry_portal) = portal_of(last_known_pos) else {
+            return;
+        };
+        let Some(exit_portal) = portal_of(pos) else {
+            return;
+        };
+        if entry_portal == exit_portal {
+            return;
+        }
+
+        self.portal_links
+            .retain(|(entry, _)| portal_of(*entry) != Some(entry_portal));
+        self.portal_links.push((last_known_pos, pos));
+        debug!(target: "player", "learned portal link {last_known_pos:?} -> {pos:?}");
+    }
+
     /// Approximates the player velocity.
     #[inline]
     fn update_velocity(&mut self, pos: Point, tick: u64) {
@@ -1024,10 +1621,12 @@ impl PlayerState {
                     if dt == 0 {
                         return (acc_sum, acc_weight);
                     }
+                    // Velocity thresholds are tuned in pixels per tick at 30 ticks per second
+                    let dt = tuned_ticks(dt);
 
                     let weight = (i + 1) as f32;
-                    let dx = (b.0.x - a.0.x) as f32 / dt as f32;
-                    let dy = (b.0.y - a.0.y) as f32 / dt as f32;
+                    let dx = (b.0.x - a.0.x) as f32 / dt;
+                    let dy = (b.0.y - a.0.y) as f32 / dt;
                     (
                         (acc_sum.0 + weight * dx, acc_sum.1 + weight * dy),
                         acc_weight + weight,
@@ -1056,14 +1655,15 @@ impl PlayerState {
         const VALIDATE_TIMEOUT: u32 = 375;
 
         debug_assert!(self.rune_failed_count < MAX_RUNE_FAILED_COUNT);
-        debug_assert!(!self.rune_cash_shop);
+        debug_assert!(!self.cash_shop_then_exit);
         self.rune_validate_timeout = self.rune_validate_timeout.and_then(|timeout| {
-            match next_timeout_lifecycle(timeout, VALIDATE_TIMEOUT) {
+            match next_timeout_lifecycle(timeout, scaled_ticks(VALIDATE_TIMEOUT)) {
                 Lifecycle::Ended => {
                     if matches!(context.buffs[BuffKind::Rune], Buff::No) {
-                        self.track_rune_fail_count();
+                        self.track_rune_fail_count(context);
                     } else {
                         self.rune_failed_count = 0;
+                        self.solved_rune_count += 1;
                     }
                     None
                 }
@@ -1084,13 +1684,7 @@ impl PlayerState {
             return;
         }
         if self.config.use_potion_below_percent.is_none() {
-            {
-                let this = &mut *self;
-                this.health = None;
-                this.health_task = None;
-                this.health_bar = None;
-                this.health_bar_task = None;
-            };
+            self.reset_health_bar();
             return;
         }
 
@@ -1105,9 +1699,31 @@ impl PlayerState {
             return;
         };
 
-        let Update::Ok(health) = update_detection_task(
+        match update_detection_task(
+            context,
+            HEALTH_BAR_REVALIDATE_DELAY_MILLIS,
+            &mut self.health_bar_task,
+            move |detector| detector.detect_player_health_bar(),
+        ) {
+            Update::Ok(bar) if bar != health_bar => {
+                debug!(target: "player", "health bar moved from {health_bar:?} to {bar:?}");
+                self.health_bar = Some(bar);
+                self.health_task = None;
+                self.health_fail_count = 0;
+                return;
+            }
+            Update::Err(_) => {
+                debug!(target: "player", "health bar no longer detected");
+                self.reset_health_bar();
+                return;
+            }
+            Update::Ok(_) | Update::Pending => (),
+        }
+
+        let health = match update_detection_task_with_budget(
             context,
             self.config.update_health_millis.unwrap_or(1000),
+            HEALTH_DETECTION_BUDGET,
             &mut self.health_task,
             move |detector| {
                 let (current_bar, max_bar) =
@@ -1116,8 +1732,16 @@ impl PlayerState {
                 debug!(target: "player", "health updated {health:?}");
                 Ok(health)
             },
-        ) else {
-            return;
+        ) {
+            Update::Ok(health) => {
+                self.health_fail_count = 0;
+                health
+            }
+            Update::Err(_) => {
+                self.track_health_update_fail();
+                return;
+            }
+            Update::Pending => return,
         };
 
         let percentage = self.config.use_potion_below_percent.unwrap();
@@ -1125,11 +1749,74 @@ impl PlayerState {
         let ratio = current as f32 / max as f32;
 
         self.health = Some(health);
+        if self.track_potion_effect(current) {
+            info!(target: "player", "potion did not restore health, halting...");
+            self.potion_halt = true;
+            let _ = context
+                .notification
+                .schedule_notification(NotificationKind::PotionNoEffect);
+            return;
+        }
+        if self
+            .config
+            .use_emergency_key_below_percent
+            .is_some_and(|percentage| ratio <= percentage)
+        {
+            let _ = context.keys.send(self.config.emergency_key);
+        }
         if ratio <= percentage {
             let _ = context.keys.send(self.config.potion_key);
+            self.potion_used_at_health = Some(current);
         }
     }
 
+    /// Tracks whether the last potion use increased health to `current` health.
+    ///
+    /// Returns `true` if the actions should be halted because using potion has not increased
+    /// health [`POTION_NO_EFFECT_MAX_COUNT`] times in a row.
+    fn track_potion_effect(&mut self, current: u32) -> bool {
+        let Some(used_at_health) = self.potion_used_at_health.take() else {
+            return false;
+        };
+        if !self.config.potion_no_effect_halt || current > used_at_health {
+            self.potion_no_effect_count = 0;
+            return false;
+        }
+
+        self.potion_no_effect_count += 1;
+        if self.potion_no_effect_count < POTION_NO_EFFECT_MAX_COUNT {
+            return false;
+        }
+        self.potion_no_effect_count = 0;
+        true
+    }
+
+    /// Increments the health update fail count and resets the health bar for detecting again
+    /// once it reaches [`HEALTH_UPDATE_MAX_FAIL_COUNT`].
+    ///
+    /// Returns `true` if the health bar is reset.
+    fn track_health_update_fail(&mut self) -> bool {
+        self.health_fail_count += 1;
+        if self.health_fail_count < HEALTH_UPDATE_MAX_FAIL_COUNT {
+            return false;
+        }
+        debug!(target: "player", "health update failed too many times, detecting health bar again");
+        self.reset_health_bar();
+        true
+    }
+
+    /// Resets the health bar so that it is detected again.
+    #[inline]
+    pub fn reset_health_bar(&mut self) {
+        self.health = None;
+        self.health_task = None;
+        self.health_bar = None;
+        self.health_bar_task = None;
+        self.health_fail_count = 0;
+        self.potion_used_at_health = None;
+        self.potion_no_effect_count = 0;
+    }
+
     /// Updates whether the player is dead.
     ///
     /// Upon being dead, a notification will be scheduled to notify the user.
@@ -1146,6 +1833,7 @@ impl PlayerState {
             let _ = context
                 .notification
                 .schedule_notification(NotificationKind::PlayerIsDead);
+            context.screenshots.schedule(ScreenshotEvent::PlayerDied);
         }
         if is_dead {
             let update =
@@ -1193,6 +1881,68 @@ impl PlayerState {
         }
         self.is_arrow_spam = is_arrow_spam;
     }
+
+    /// Updates the server maintenance countdown state.
+    ///
+    /// Schedules a halt before the countdown ends if [`PlayerConfiguration::maintenance_halt`]
+    /// is enabled and the actions are running. The pending halt is discarded when halted.
+    #[inline]
+    fn update_maintenance_state(&mut self, context: &Context) {
+        if context.operation.halting() {
+            self.maintenance_halt_at = None;
+            return;
+        }
+        if !self.config.maintenance_halt || self.maintenance_halt_at.is_some() {
+            return;
+        }
+        let Update::Ok(countdown) =
+            update_inference_task(context, 10000, &mut self.maintenance_task, |detector| {
+                detector.detect_maintenance_countdown()
+            })
+        else {
+            return;
+        };
+
+        let margin = Duration::from_millis(self.config.maintenance_halt_margin_millis);
+        let halt_at = Instant::now() + countdown.saturating_sub(margin);
+        debug!(target: "player", "maintenance countdown {countdown:?} detected, halting at {halt_at:?}");
+        self.maintenance_halt_at = Some(halt_at);
+        let _ = context
+            .notification
+            .schedule_notification(NotificationKind::MaintenanceCountdown);
+    }
+
+    /// Updates whether damage numbers are visible while a mobbing action is running.
+    ///
+    /// Any detected damage numbers reset the count tracked by
+    /// [`Self::track_mobbing_no_damage`].
+    #[inline]
+    fn update_mobbing_damage_state(&mut self, context: &Context) {
+        if !self.config.mobbing_damage_check
+            || !matches!(
+                self.normal_action,
+                Some(PlayerAction::AutoMob(_) | PlayerAction::PingPong(_))
+            )
+        {
+            self.mobbing_no_damage_count = 0;
+            return;
+        }
+        if let Update::Ok(_) = update_detection_task_with_budget(
+            context,
+            MOBBING_DAMAGE_DETECTION_DELAY_MILLIS,
+            MOBBING_DAMAGE_DETECTION_BUDGET,
+            &mut self.mobbing_damage_task,
+            |detector| {
+                if detector.detect_damage_numbers() {
+                    Ok(())
+                } else {
+                    Err(anyhow!("damage numbers not found"))
+                }
+            },
+        ) {
+            self.mobbing_no_damage_count = 0;
+        }
+    }
 }
 
 #[inline]
@@ -1205,10 +1955,19 @@ fn auto_mob_ignore_xs_range_value(x: i32) -> (Range<i32>, u32) {
 
 #[cfg(test)]
 mod tests {
-    use std::{assert_matches::assert_matches, collections::HashMap};
+    use std::{
+        assert_matches::assert_matches,
+        collections::HashMap,
+        time::{Duration, Instant},
+    };
 
     use opencv::core::{Point, Rect};
 
+    use super::{
+        AUTO_MOB_IGNORE_XS_DECAY_INTERVAL, AUTO_MOB_IGNORE_XS_SOLIDIFY_COUNT,
+        HEALTH_UPDATE_MAX_FAIL_COUNT, MOBBING_NO_DAMAGE_COUNT_THRESHOLD,
+        POTION_NO_EFFECT_MAX_COUNT,
+    };
     use crate::{
         Position,
         array::Array,
@@ -1346,6 +2105,83 @@ mod tests {
         assert_eq!(ranges, &vec![((55..65).into(), 2), ((63..75).into(), 1)])
     }
 
+    #[test]
+    fn auto_mob_decay_ignore_xs_solidified_only() {
+        let y = 100;
+        let mut player = PlayerState {
+            auto_mob_ignore_xs_map: HashMap::from([(
+                y,
+                vec![
+                    ((45..55).into(), AUTO_MOB_IGNORE_XS_SOLIDIFY_COUNT),
+                    ((60..70).into(), 1),
+                ],
+            )]),
+            auto_mob_ignore_xs_last_decay: Some(Instant::now()),
+            ..Default::default()
+        };
+
+        player.auto_mob_decay_ignore_xs();
+        assert_eq!(
+            player.auto_mob_ignore_xs_map[&y],
+            vec![
+                ((45..55).into(), AUTO_MOB_IGNORE_XS_SOLIDIFY_COUNT),
+                ((60..70).into(), 1)
+            ]
+        );
+
+        player.auto_mob_ignore_xs_last_decay =
+            Some(Instant::now() - AUTO_MOB_IGNORE_XS_DECAY_INTERVAL);
+        player.auto_mob_decay_ignore_xs();
+        assert_eq!(
+            player.auto_mob_ignore_xs_map[&y],
+            vec![
+                ((45..55).into(), AUTO_MOB_IGNORE_XS_SOLIDIFY_COUNT - 1),
+                ((60..70).into(), 1)
+            ]
+        );
+    }
+
+    #[test]
+    fn take_maintenance_halt_waits_for_action_or_deadline() {
+        let mut player = PlayerState {
+            normal_action: Some(PlayerAction::AutoMob(PlayerActionAutoMob::default())),
+            maintenance_halt_at: Some(Instant::now() + Duration::from_secs(60)),
+            ..Default::default()
+        };
+        assert!(!player.take_maintenance_halt());
+        assert!(player.has_maintenance_halt());
+
+        player.maintenance_halt_at = Some(Instant::now());
+        assert!(player.take_maintenance_halt());
+        assert!(!player.has_maintenance_halt());
+        assert!(!player.take_maintenance_halt());
+
+        player.normal_action = None;
+        player.maintenance_halt_at = Some(Instant::now() + Duration::from_secs(60));
+        assert!(player.take_maintenance_halt());
+    }
+
+    #[test]
+    fn update_portal_links_learns_teleport_between_portals() {
+        let portals = [Rect::new(10, 10, 5, 5), Rect::new(50, 50, 5, 5)];
+        let mut player = PlayerState::default();
+
+        player.update_portal_links(portals, Point::new(12, 12), Point::new(30, 12));
+        assert!(player.portal_links.is_empty());
+
+        player.update_portal_links(portals, Point::new(12, 12), Point::new(52, 52));
+        assert_eq!(
+            player.portal_links,
+            vec![(Point::new(12, 12), Point::new(52, 52))]
+        );
+
+        player.update_portal_links(portals, Point::new(13, 11), Point::new(51, 50));
+        assert_eq!(
+            player.portal_links,
+            vec![(Point::new(13, 11), Point::new(51, 50))]
+        );
+    }
+
     #[test]
     fn auto_mob_populate_ignore_xs_detects_gaps_correctly() {
         let platforms = vec![
@@ -1439,4 +2275,96 @@ mod tests {
         assert_eq!(point.y, 20); // 100 - 80
         assert_matches!(state.auto_mob_last_quadrant, Some(Quadrant::BottomLeft));
     }
+
+    #[test]
+    fn auto_mob_pathing_point_prefers_densest_quadrant() {
+        let mut state = PlayerState {
+            auto_mob_last_quadrant: Some(Quadrant::TopLeft),
+            ..Default::default()
+        };
+        state.config.auto_mob_density_pathing = true;
+        let bound = Rect::new(0, 0, 100, 100);
+        state.auto_mob_track_quadrant_mobs(
+            bound,
+            &[
+                Point::new(10, 90), // Bottom-left
+                Point::new(20, 80), // Bottom-left
+                Point::new(90, 10), // Top-right
+            ],
+        );
+
+        let mut idle = MinimapIdle::default();
+        idle.bbox = Rect::new(0, 0, 100, 100);
+        let context = Context {
+            minimap: Minimap::Idle(idle),
+            rng: Rng::new(SEED),
+            ..Context::new(None, None)
+        };
+
+        state.auto_mob_pathing_point(&context, bound);
+        assert_matches!(state.auto_mob_last_quadrant, Some(Quadrant::BottomLeft));
+
+        // Falls back to clockwise when disabled
+        state.config.auto_mob_density_pathing = false;
+        state.auto_mob_pathing_point(&context, bound);
+        assert_matches!(state.auto_mob_last_quadrant, Some(Quadrant::TopLeft));
+    }
+
+    #[test]
+    fn track_mobbing_no_damage_unstuck_after_threshold() {
+        let mut state = PlayerState::default();
+        assert!(!state.track_mobbing_no_damage());
+        assert_eq!(state.mobbing_no_damage_count, 0);
+
+        state.config.mobbing_damage_check = true;
+        for _ in 0..MOBBING_NO_DAMAGE_COUNT_THRESHOLD - 1 {
+            assert!(!state.track_mobbing_no_damage());
+        }
+        assert!(state.track_mobbing_no_damage());
+        assert_eq!(state.mobbing_no_damage_count, 0);
+        assert_eq!(state.take_unstuck_event_count(), 1);
+    }
+
+    #[test]
+    fn track_health_update_fail_reset_health_bar_after_max_count() {
+        let mut state = PlayerState {
+            health: Some((100, 100)),
+            health_bar: Some(Rect::new(10, 10, 100, 10)),
+            ..Default::default()
+        };
+
+        for _ in 0..HEALTH_UPDATE_MAX_FAIL_COUNT - 1 {
+            assert!(!state.track_health_update_fail());
+        }
+        assert!(state.health_bar.is_some());
+        assert!(state.track_health_update_fail());
+        assert!(state.health_bar.is_none());
+        assert!(state.health.is_none());
+        assert_eq!(state.health_fail_count, 0);
+    }
+
+    #[test]
+    fn track_potion_effect_halt_after_max_count() {
+        let mut state = PlayerState::default();
+        state.config.potion_no_effect_halt = true;
+
+        // Not tracked without using potion
+        assert!(!state.track_potion_effect(50));
+
+        for _ in 0..POTION_NO_EFFECT_MAX_COUNT - 1 {
+            state.potion_used_at_health = Some(50);
+            assert!(!state.track_potion_effect(50));
+        }
+        // Resets when health increased
+        state.potion_used_at_health = Some(50);
+        assert!(!state.track_potion_effect(60));
+        assert_eq!(state.potion_no_effect_count, 0);
+
+        for _ in 0..POTION_NO_EFFECT_MAX_COUNT - 1 {
+            state.potion_used_at_health = Some(50);
+            assert!(!state.track_potion_effect(40));
+        }
+        state.potion_used_at_health = Some(50);
+        assert!(state.track_potion_effect(40));
+    }
 }
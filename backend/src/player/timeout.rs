@@ -1,6 +1,10 @@
 use opencv::core::Point;
 
 use super::Moving;
+use crate::context::fps;
+
+/// The number of ticks per second the tick counts of the contextual states are tuned at.
+const TUNED_FPS: u32 = 30;
 
 /// The axis to which the change in position should be detected.
 #[derive(Debug)]
@@ -50,6 +54,29 @@ pub struct Timeout {
     pub started: bool,
 }
 
+/// Scales `ticks` tuned at [`TUNED_FPS`] to the current tick rate so that it lasts the same
+/// duration.
+///
+/// Non-zero `ticks` is scaled to at least one tick.
+#[inline]
+pub fn scaled_ticks(ticks: u32) -> u32 {
+    scale_ticks(ticks, fps())
+}
+
+/// Converts `ticks` at the current tick rate to the number of ticks at [`TUNED_FPS`].
+#[inline]
+pub fn tuned_ticks(ticks: u64) -> f32 {
+    ticks as f32 * TUNED_FPS as f32 / fps() as f32
+}
+
+#[inline]
+fn scale_ticks(ticks: u32, fps: u32) -> u32 {
+    if ticks == 0 {
+        return 0;
+    }
+    ((ticks * fps + TUNED_FPS / 2) / TUNED_FPS).max(1)
+}
+
 /// Gets the next [`Timeout`] lifecycle.
 ///
 /// This is basic building block for contextual states that can
@@ -137,6 +164,16 @@ mod tests {
         }
     }
 
+    #[test]
+    fn scale_ticks_to_fps() {
+        assert_eq!(scale_ticks(45, 30), 45);
+        assert_eq!(scale_ticks(45, 60), 90);
+        assert_eq!(scale_ticks(45, 10), 15);
+        assert_eq!(scale_ticks(5, 15), 3);
+        assert_eq!(scale_ticks(1, 10), 1);
+        assert_eq!(scale_ticks(0, 60), 0);
+    }
+
     #[test]
     fn timeout_lifecycle_started() {
         let timeout = Timeout::default();
@@ -3,12 +3,16 @@ use platforms::windows::KeyKind;
 
 use super::{
     PlayerState,
-    timeout::{Lifecycle, Timeout, next_timeout_lifecycle},
+    actions::PanicTo,
+    panic::Panicking,
+    timeout::{Lifecycle, Timeout, next_timeout_lifecycle, scaled_ticks},
 };
 use crate::{
+    UnstuckStrategy,
     context::Context,
-    minimap::Minimap,
+    minimap::{Minimap, MinimapIdle},
     player::{MOVE_TIMEOUT, Player},
+    screenshot::ScreenshotEvent,
     task::{Update, update_detection_task},
 };
 
@@ -29,6 +33,9 @@ const Y_IGNORE_THRESHOLD: i32 = 18;
 /// Each initial transition to [`Player::Unstucking`] increases
 /// the [`PlayerState::unstuck_consecutive_counter`] by one. If the threshold is reached, this
 /// state will enter GAMBA mode. And by definition, it means `random bullsh*t go`.
+///
+/// In GAMBA mode, the configured [`UnstuckStrategy`] list for whether the player is mid-air or on
+/// ground is used in order instead when it is not empty.
 pub fn update_unstucking_context(
     context: &Context,
     state: &mut PlayerState,
@@ -44,8 +51,37 @@ pub fn update_unstucking_context(
         .map(|pos| Point::new(pos.x, idle.bbox.height - pos.y));
     let gamba_mode = gamba_mode || pos.is_none();
 
-    match next_timeout_lifecycle(timeout, MOVE_TIMEOUT) {
+    match next_timeout_lifecycle(timeout, scaled_ticks(MOVE_TIMEOUT)) {
         Lifecycle::Started(timeout) => {
+            if gamba_mode {
+                context.screenshots.schedule(ScreenshotEvent::UnstuckGamba);
+            }
+            state.unstuck_strategy = if gamba_mode {
+                next_unstuck_strategy(state, &idle)
+            } else {
+                None
+            };
+            match state.unstuck_strategy {
+                Some(UnstuckStrategy::UseTownScroll) => {
+                    state.unstuck_strategy = None;
+                    return Player::Panicking(Panicking::new(PanicTo::Town));
+                }
+                Some(UnstuckStrategy::ChangeChannel) => {
+                    state.unstuck_strategy = None;
+                    return Player::Panicking(Panicking::new(PanicTo::Channel));
+                }
+                Some(UnstuckStrategy::UpJump) => {
+                    if state.config.upjump_key.is_none() {
+                        let _ = context.keys.send_down(KeyKind::Up);
+                    }
+                    return Player::Unstucking(timeout, has_settings, gamba_mode);
+                }
+                Some(UnstuckStrategy::Jump | UnstuckStrategy::RandomArrowSpam) => {
+                    return Player::Unstucking(timeout, has_settings, gamba_mode);
+                }
+                None => (),
+            }
+
             let has_settings = if !gamba_mode && has_settings.is_none() {
                 match update_detection_task(context, 0, &mut state.unstuck_task, move |detector| {
                     Ok(detector.detect_esc_settings())
@@ -82,10 +118,18 @@ pub fn update_unstucking_context(
         Lifecycle::Ended => {
             let _ = context.keys.send_up(KeyKind::Right);
             let _ = context.keys.send_up(KeyKind::Left);
+            if state.unstuck_strategy.take().is_some() {
+                let _ = context.keys.send_up(KeyKind::Up);
+            }
 
             Player::Detecting
         }
         Lifecycle::Updated(timeout) => {
+            if let Some(strategy) = state.unstuck_strategy {
+                update_unstuck_strategy(context, state, strategy);
+                return Player::Unstucking(timeout, has_settings, gamba_mode);
+            }
+
             let send_space = match (gamba_mode, pos) {
                 (true, _) => true,
                 (_, Some(pos)) if pos.y > Y_IGNORE_THRESHOLD => true,
@@ -99,3 +143,34 @@ pub fn update_unstucking_context(
         }
     }
 }
+
+/// Picks the next configured [`UnstuckStrategy`] depending on whether the player is mid-air.
+fn next_unstuck_strategy(state: &mut PlayerState, idle: &MinimapIdle) -> Option<UnstuckStrategy> {
+    let pos = state.last_known_pos?;
+    let mid_air = state.is_mid_air(&idle.platforms, pos);
+
+    state.next_unstuck_strategy(mid_air)
+}
+
+/// Presses the key(s) of the current `strategy` for each [`Lifecycle::Updated`] tick.
+fn update_unstuck_strategy(context: &Context, state: &PlayerState, strategy: UnstuckStrategy) {
+    match strategy {
+        UnstuckStrategy::Jump => {
+            let _ = context.keys.send(state.config.jump_key);
+        }
+        UnstuckStrategy::UpJump => {
+            let key = state.config.upjump_key.unwrap_or(state.config.jump_key);
+            let _ = context.keys.send(key);
+        }
+        UnstuckStrategy::RandomArrowSpam => {
+            let key = context
+                .rng
+                .random_choose(
+                    [KeyKind::Left, KeyKind::Right, KeyKind::Up, KeyKind::Down].into_iter(),
+                )
+                .expect("not empty");
+            let _ = context.keys.send(key);
+        }
+        UnstuckStrategy::UseTownScroll | UnstuckStrategy::ChangeChannel => unreachable!(),
+    }
+}
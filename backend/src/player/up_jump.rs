@@ -4,11 +4,12 @@ use super::{
     Player, PlayerActionKey, PlayerActionPingPong, PlayerState,
     actions::on_ping_pong_double_jump_action,
     moving::Moving,
-    timeout::{MovingLifecycle, next_moving_lifecycle_with_axis},
+    state::PlayerConfiguration,
+    timeout::{MovingLifecycle, next_moving_lifecycle_with_axis, scaled_ticks},
     use_key::UseKey,
 };
 use crate::{
-    ActionKeyWith,
+    ActionAbortReason, ActionKeyWith, UpJumpStyle,
     context::Context,
     minimap::Minimap,
     player::{
@@ -41,11 +42,11 @@ pub struct UpJumping {
 impl UpJumping {
     pub fn new(moving: Moving) -> Self {
         let (y_distance, _) = moving.y_distance_direction_from(true, moving.pos);
-        let spam_delay = if y_distance <= SOFT_UP_JUMP_THRESHOLD {
+        let spam_delay = scaled_ticks(if y_distance <= SOFT_UP_JUMP_THRESHOLD {
             SOFT_SPAM_DELAY
         } else {
             SPAM_DELAY
-        };
+        });
         Self {
             moving,
             spam_delay,
@@ -73,7 +74,7 @@ impl UpJumping {
 /// player has reached the destination x-wise. Before performing an up jump, it will check for
 /// stationary state and whether the player is currently near a portal. If the player is near
 /// a portal, this action is aborted. The up jump action is made to be adapted for various classes
-/// that has different up jump key combination.
+/// that has different up jump key combination through [`UpJumpStyle`].
 pub fn update_up_jumping_context(
     context: &Context,
     state: &mut PlayerState,
@@ -81,12 +82,12 @@ pub fn update_up_jumping_context(
 ) -> Player {
     let up_jump_key = state.config.upjump_key;
     let jump_key = state.config.jump_key;
-    let has_teleport_key = state.config.teleport_key.is_some();
+    let (style, key) = up_jump_style_key(&state.config);
 
     match next_moving_lifecycle_with_axis(
         up_jumping.moving,
         state.last_known_pos.expect("in positional context"),
-        TIMEOUT,
+        scaled_ticks(TIMEOUT),
         ChangeAxis::Vertical,
     ) {
         MovingLifecycle::Started(moving) => {
@@ -98,7 +99,7 @@ pub fn update_up_jumping_context(
             if let Minimap::Idle(idle) = context.minimap
                 && idle.is_position_inside_portal(moving.pos)
             {
-                state.clear_action_completed();
+                state.abort_action(ActionAbortReason::PortalInterference);
                 return Player::Idle;
             }
             state.last_movement = Some(LastMovement::UpJumping);
@@ -107,17 +108,24 @@ pub fn update_up_jumping_context(
             if !matches!(up_jump_key, Some(KeyKind::Up)) {
                 let _ = context.keys.send_down(KeyKind::Up);
             }
-            match (up_jump_key, has_teleport_key) {
-                // This is a generic class, a mage or a Demon Slayer
-                (None, _) | (Some(_), true) | (Some(KeyKind::Up), false) => {
-                    // This if is for mage. It means if the player is a mage and the y distance
-                    // is less than `TELEPORT_UP_JUMP_THRESHOLD`, do not send jump key.
+            match style {
+                // This is a generic class or a Demon Slayer
+                UpJumpStyle::DoubleJumpUp => {
+                    let _ = context.keys.send(jump_key);
+                }
+                // This is for mage. It means if the y distance is less than
+                // `TELEPORT_UP_JUMP_THRESHOLD`, do not send jump key.
+                UpJumpStyle::TeleportUp => {
                     let (y_distance, _) = moving.y_distance_direction_from(true, moving.pos);
-                    if !can_mage_skip_jump_key(up_jump_key, has_teleport_key, y_distance) {
+                    if y_distance > TELEPORT_UP_JUMP_THRESHOLD {
                         let _ = context.keys.send(jump_key);
                     }
                 }
-                _ => (),
+                UpJumpStyle::HoldUpJump => {
+                    let _ = context.keys.send_down(jump_key);
+                }
+                UpJumpStyle::SingleKey => (),
+                UpJumpStyle::Auto => unreachable!(),
             }
 
             // TODO: Should be fine to not check auto-mob action only?
@@ -129,43 +137,53 @@ pub fn update_up_jumping_context(
         }
         MovingLifecycle::Ended(moving) => {
             let _ = context.keys.send_up(KeyKind::Up);
+            if matches!(style, UpJumpStyle::HoldUpJump) {
+                let _ = context.keys.send_up(jump_key);
+            }
             Player::Moving(moving.dest, moving.exact, moving.intermediates)
         }
         MovingLifecycle::Updated(mut moving) => {
             let cur_pos = moving.pos;
             let (y_distance, y_direction) = moving.y_distance_direction_from(true, moving.pos);
 
-            match (moving.completed, up_jump_key, has_teleport_key) {
-                (false, None, true) | (false, Some(KeyKind::Up), false) | (false, None, false) => {
+            match (moving.completed, style) {
+                (false, UpJumpStyle::DoubleJumpUp) => {
                     if state.velocity.1 <= UP_JUMPED_Y_VELOCITY_THRESHOLD {
                         // Spam jump key until the player y changes
                         // above a threshold as sending jump key twice
                         // doesn't work
                         if moving.timeout.total >= up_jumping.spam_delay {
-                            // This up jump key is Up for Demon Slayer
-                            if let Some(key) = up_jump_key {
-                                let _ = context.keys.send(key);
-                            } else {
-                                let _ = context.keys.send(jump_key);
-                            }
+                            // This key is Up for Demon Slayer
+                            let _ = context.keys.send(key);
                         }
                     } else {
                         moving = moving.completed(true);
                     }
                 }
-                (false, Some(key), _) => {
+                (false, UpJumpStyle::HoldUpJump) => {
+                    // Keep holding jump key until the player y changes above a threshold
+                    if state.velocity.1 > UP_JUMPED_Y_VELOCITY_THRESHOLD {
+                        let _ = context.keys.send_up(key);
+                        moving = moving.completed(true);
+                    }
+                }
+                (false, UpJumpStyle::SingleKey) => {
                     // TODO: Support soft up jump?
-                    // If the player is a mage and y distance is less
-                    // than `TELEPORT_UP_JUMP_THRESHOLD`, send the teleport key immediately.
-                    if !has_teleport_key
-                        || (y_distance <= TELEPORT_UP_JUMP_THRESHOLD
-                            || moving.timeout.total >= SPAM_DELAY)
+                    let _ = context.keys.send(key);
+                    moving = moving.completed(true);
+                }
+                (false, UpJumpStyle::TeleportUp) => {
+                    // If y distance is less than `TELEPORT_UP_JUMP_THRESHOLD`, send the teleport
+                    // key immediately.
+                    if y_distance <= TELEPORT_UP_JUMP_THRESHOLD
+                        || moving.timeout.total >= scaled_ticks(SPAM_DELAY)
                     {
                         let _ = context.keys.send(key);
                         moving = moving.completed(true);
                     }
                 }
-                (true, _, _) => {
+                (false, UpJumpStyle::Auto) => unreachable!(),
+                (true, _) => {
                     let _ = context.keys.send_up(KeyKind::Up);
                 }
             }
@@ -225,7 +243,9 @@ pub fn update_up_jumping_context(
                     })
                     | PlayerAction::Move(_)
                     | PlayerAction::SolveRune => None,
-                    PlayerAction::Panic(_) | PlayerAction::FamiliarsSwapping(_) => unreachable!(),
+                    PlayerAction::Panic(_)
+                    | PlayerAction::CashShop(_)
+                    | PlayerAction::FamiliarsSwapping(_) => unreachable!(),
                 },
                 || Player::UpJumping(up_jumping.moving(moving)),
             )
@@ -233,15 +253,37 @@ pub fn update_up_jumping_context(
     }
 }
 
-#[inline]
-fn can_mage_skip_jump_key(
-    up_jump_key: Option<KeyKind>,
-    has_teleport_key: bool,
-    y_distance: i32,
-) -> bool {
-    // It means if the player is a mage and the y distance
-    // is less than `TELEPORT_UP_JUMP_THRESHOLD`, do not send jump key or wait for stationary.
-    up_jump_key.is_some() && has_teleport_key && y_distance <= TELEPORT_UP_JUMP_THRESHOLD
+/// Resolves the [`UpJumpStyle`] and the key to send for the style.
+///
+/// [`UpJumpStyle::Auto`] is inferred from the up jump and teleport keys. An explicit style falls
+/// back to [`UpJumpStyle::DoubleJumpUp`] if the key it requires is not set.
+fn up_jump_style_key(config: &PlayerConfiguration) -> (UpJumpStyle, KeyKind) {
+    let up_jump_key = config.upjump_key;
+    let style = match config.up_jump_style {
+        UpJumpStyle::Auto => match (up_jump_key, config.teleport_key.is_some()) {
+            (None, _) | (Some(KeyKind::Up), false) => UpJumpStyle::DoubleJumpUp,
+            (Some(_), true) => UpJumpStyle::TeleportUp,
+            (Some(_), false) => UpJumpStyle::SingleKey,
+        },
+        style => style,
+    };
+    let key = match style {
+        UpJumpStyle::SingleKey => up_jump_key,
+        UpJumpStyle::TeleportUp => up_jump_key.or(config.teleport_key),
+        // Demon Slayer uses Up instead of jump key
+        UpJumpStyle::DoubleJumpUp => Some(
+            up_jump_key
+                .filter(|key| *key == KeyKind::Up)
+                .unwrap_or(config.jump_key),
+        ),
+        UpJumpStyle::HoldUpJump => Some(config.jump_key),
+        UpJumpStyle::Auto => unreachable!(),
+    };
+
+    match key {
+        Some(key) => (style, key),
+        None => (UpJumpStyle::DoubleJumpUp, config.jump_key),
+    }
 }
 
 #[cfg(test)]
@@ -253,6 +295,7 @@ mod tests {
 
     use super::{Moving, PlayerState, UpJumping, update_up_jumping_context};
     use crate::{
+        UpJumpStyle,
         bridge::MockKeySender,
         context::Context,
         player::{Player, Timeout},
@@ -472,4 +515,57 @@ mod tests {
         );
         let _ = context.keys;
     }
+
+    #[test]
+    fn up_jump_hold_up_jump() {
+        let pos = Point::new(10, 10);
+        let mut moving = Moving {
+            pos,
+            dest: Point::new(10, 30),
+            ..Default::default()
+        };
+        let mut state = PlayerState::default();
+        state.config.up_jump_style = UpJumpStyle::HoldUpJump;
+        state.config.jump_key = KeyKind::Space;
+        state.last_known_pos = Some(pos);
+        state.is_stationary = true;
+
+        let mut keys = MockKeySender::new();
+        keys.expect_send_down()
+            .withf(|key| *key == KeyKind::Up)
+            .once()
+            .returning(|_| Ok(()));
+        keys.expect_send_down()
+            .withf(|key| *key == KeyKind::Space)
+            .once()
+            .returning(|_| Ok(()));
+        keys.expect_send().never();
+        let mut context = Context::new(None, None);
+        context.keys = Box::new(keys);
+
+        // Start by holding Up and Space
+        update_up_jumping_context(&context, &mut state, UpJumping::new(moving));
+        let _ = context.keys;
+
+        // Release Space once up jumped
+        moving.timeout.started = true;
+        state.velocity = (0.0, 1.36);
+        let mut keys = MockKeySender::new();
+        keys.expect_send_up()
+            .withf(|key| *key == KeyKind::Space)
+            .once()
+            .returning(|_| Ok(()));
+        context.keys = Box::new(keys);
+        assert_matches!(
+            update_up_jumping_context(&context, &mut state, UpJumping::new(moving)),
+            Player::UpJumping(UpJumping {
+                moving: Moving {
+                    completed: true,
+                    ..
+                },
+                ..
+            })
+        );
+        let _ = context.keys;
+    }
 }
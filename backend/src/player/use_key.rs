@@ -4,15 +4,18 @@ use opencv::core::Point;
 use platforms::windows::KeyKind;
 
 use super::{
-    PingPongDirection, PlayerActionAutoMob, PlayerState, Timeout,
+    AUTO_MOB_MAX_MOBS_COUNT, PingPongDirection, PlayerActionAutoMob, PlayerState, Timeout,
     actions::{
         PlayerAction, PlayerActionKey, PlayerActionPingPong, on_ping_pong_double_jump_action,
+        on_ping_pong_walk_action,
     },
     double_jump::DoubleJumping,
-    timeout::{Lifecycle, next_timeout_lifecycle},
+    timeout::{Lifecycle, next_timeout_lifecycle, scaled_ticks},
 };
 use crate::{
-    ActionKeyDirection, ActionKeyWith, Class, KeyBinding, LinkKeyBinding, Position,
+    ActionKeyDirection, ActionKeyWith, Class, DoubleJumpKeyTiming, KeyBinding, LinkKeyBinding,
+    Position,
+    array::Array,
     context::Context,
     player::{LastMovement, MOVE_TIMEOUT, Moving, Player, on_action_state_mut},
 };
@@ -23,6 +26,12 @@ const CHANGE_DIRECTION_TIMEOUT: u32 = 3;
 /// The tick to which the actual key will be pressed for [`LinkKeyBinding::Along`].
 const LINK_ALONG_PRESS_TICK: u32 = 2;
 
+/// The maximum y distance from the player for a mob to be considered when picking direction.
+const MOBS_DIRECTION_Y_THRESHOLD: i32 = 8;
+
+/// Maximum number of ticks to wait for the player to start descending after double jumped.
+const DOUBLE_JUMP_DESCENT_TIMEOUT: u32 = 30;
+
 /// The different stages of using key.
 #[derive(Clone, Copy, Debug)]
 pub enum UseKeyStage {
@@ -38,9 +47,16 @@ pub enum UseKeyStage {
     /// Returns to [`UseKeyStage::Precondition`] if player is stationary or
     /// transfers to [`Player::DoubleJumping`].
     EnsuringUseWith,
+    /// Waits for the configured [`DoubleJumpKeyTiming`] and delay after double jumped.
+    ///
+    /// Stores the highest y reached during the double jump to detect descending.
+    DoubleJumpTiming(Timeout, i32),
     /// Uses the actual key with optional [`LinkKeyBinding`] and stalls
     /// for [`UseKey::wait_after_use_ticks`].
     Using(Timeout, bool),
+    /// Holds down the actual key for [`UseKey::hold_ticks`] and stalls
+    /// for [`UseKey::wait_after_use_ticks`].
+    Holding(Timeout),
     /// Ensures all [`UseKey::count`] times executed.
     Postcondition,
 }
@@ -55,6 +71,10 @@ pub struct UseKey {
     with: ActionKeyWith,
     wait_before_use_ticks: u32,
     wait_after_use_ticks: u32,
+    /// Number of ticks to hold down the key instead of pressing.
+    ///
+    /// Only applies when there is no [`LinkKeyBinding`] or it is [`LinkKeyBinding::Before`].
+    hold_ticks: Option<u32>,
     stage: UseKeyStage,
 }
 
@@ -92,6 +112,7 @@ impl UseKey {
                     with,
                     wait_before_use_ticks: wait_before,
                     wait_after_use_ticks: wait_after,
+                    hold_ticks: None,
                     stage: UseKeyStage::Precondition,
                 }
             }
@@ -104,19 +125,22 @@ impl UseKey {
                 Self {
                     key: mob.key,
                     link_key: mob.link_key,
-                    count: mob.count,
+                    count: mob.hold_ticks.map_or(mob.count, |_| 1),
                     current_count: 0,
                     direction: match pos {
-                        Some(pos) => match pos.x.cmp(&mob.position.x) {
-                            Ordering::Less => ActionKeyDirection::Right,
-                            Ordering::Equal => ActionKeyDirection::Any,
-                            Ordering::Greater => ActionKeyDirection::Left,
-                        },
+                        Some(pos) => mobs_direction(pos, &mob.mobs).unwrap_or(
+                            match pos.x.cmp(&mob.position.x) {
+                                Ordering::Less => ActionKeyDirection::Right,
+                                Ordering::Equal => ActionKeyDirection::Any,
+                                Ordering::Greater => ActionKeyDirection::Left,
+                            },
+                        ),
                         None => unreachable!(),
                     },
                     with: mob.with,
                     wait_before_use_ticks: wait_before,
                     wait_after_use_ticks: wait_after,
+                    hold_ticks: mob.hold_ticks,
                     stage: UseKeyStage::Precondition,
                 }
             }
@@ -133,7 +157,7 @@ impl UseKey {
                 Self {
                     key: ping_pong.key,
                     link_key: ping_pong.link_key,
-                    count: ping_pong.count,
+                    count: ping_pong.hold_ticks.map_or(ping_pong.count, |_| 1),
                     current_count: 0,
                     direction: if matches!(ping_pong.direction, PingPongDirection::Left) {
                         ActionKeyDirection::Left
@@ -143,12 +167,14 @@ impl UseKey {
                     with: ping_pong.with,
                     wait_before_use_ticks: wait_before,
                     wait_after_use_ticks: wait_after,
+                    hold_ticks: ping_pong.hold_ticks,
                     stage: UseKeyStage::Precondition,
                 }
             }
             PlayerAction::FamiliarsSwapping(_)
             | PlayerAction::SolveRune
             | PlayerAction::Panic(_)
+            | PlayerAction::CashShop(_)
             | PlayerAction::Move { .. } => {
                 unreachable!()
             }
@@ -156,6 +182,44 @@ impl UseKey {
     }
 }
 
+/// Transitions to [`UseKeyStage::Using`] after stalling for [`UseKey::wait_before_use_ticks`].
+#[inline]
+fn update_before_using(state: &mut PlayerState, use_key: UseKey) -> Player {
+    let next = Player::UseKey(UseKey {
+        stage: UseKeyStage::Using(Timeout::default(), false),
+        ..use_key
+    });
+    if use_key.wait_before_use_ticks > 0 {
+        state.stalling_timeout_state = Some(next);
+        Player::Stalling(Timeout::default(), use_key.wait_before_use_ticks)
+    } else {
+        state.use_immediate_control_flow = true;
+        next
+    }
+}
+
+/// Picks the direction toward the side of `pos` with more `mobs` near the same y.
+///
+/// Returns [`None`] if both sides have the same number of mobs.
+fn mobs_direction(
+    pos: Point,
+    mobs: &Array<Point, AUTO_MOB_MAX_MOBS_COUNT>,
+) -> Option<ActionKeyDirection> {
+    let (left, right) = mobs
+        .iter()
+        .filter(|mob| (mob.y - pos.y).abs() <= MOBS_DIRECTION_Y_THRESHOLD)
+        .fold((0, 0), |(left, right), mob| match mob.x.cmp(&pos.x) {
+            Ordering::Less => (left + 1, right),
+            Ordering::Equal => (left, right),
+            Ordering::Greater => (left, right + 1),
+        });
+    match left.cmp(&right) {
+        Ordering::Less => Some(ActionKeyDirection::Right),
+        Ordering::Equal => None,
+        Ordering::Greater => Some(ActionKeyDirection::Left),
+    }
+}
+
 /// Updates the [`Player::UseKey`] contextual state.
 ///
 /// Like [`Player::SolvingRune`], this state can only be transitioned via a [`PlayerAction`]. It
@@ -194,16 +258,42 @@ pub fn update_use_key_context(
                     || (matches!(use_key.with, ActionKeyWith::DoubleJump)
                         && matches!(state.last_movement, Some(LastMovement::DoubleJumping)))
             );
-            let next = Player::UseKey(UseKey {
-                stage: UseKeyStage::Using(Timeout::default(), false),
-                ..use_key
-            });
-            if use_key.wait_before_use_ticks > 0 {
-                state.stalling_timeout_state = Some(next);
-                Player::Stalling(Timeout::default(), use_key.wait_before_use_ticks)
-            } else {
-                state.use_immediate_control_flow = true;
-                next
+            if matches!(use_key.with, ActionKeyWith::DoubleJump)
+                && use_key.current_count == 0
+                && (state.config.double_jump_key_delay_ticks > 0
+                    || matches!(
+                        state.config.double_jump_key_timing,
+                        DoubleJumpKeyTiming::Descent
+                    ))
+                && let Some(Point { y, .. }) = state.last_known_pos
+            {
+                return Player::UseKey(UseKey {
+                    stage: UseKeyStage::DoubleJumpTiming(Timeout::default(), y),
+                    ..use_key
+                });
+            }
+            update_before_using(state, use_key)
+        }
+        UseKeyStage::DoubleJumpTiming(timeout, peak_y) => {
+            let y = state.last_known_pos.map_or(peak_y, |pos| pos.y);
+            let delay = state.config.double_jump_key_delay_ticks;
+            match next_timeout_lifecycle(timeout, delay + scaled_ticks(DOUBLE_JUMP_DESCENT_TIMEOUT))
+            {
+                Lifecycle::Started(timeout) | Lifecycle::Updated(timeout) => {
+                    let ready = match state.config.double_jump_key_timing {
+                        DoubleJumpKeyTiming::Ascent => true,
+                        DoubleJumpKeyTiming::Descent => y < peak_y,
+                    };
+                    if ready && timeout.total >= delay {
+                        update_before_using(state, use_key)
+                    } else {
+                        Player::UseKey(UseKey {
+                            stage: UseKeyStage::DoubleJumpTiming(timeout, peak_y.max(y)),
+                            ..use_key
+                        })
+                    }
+                }
+                Lifecycle::Ended => update_before_using(state, use_key),
             }
         }
         UseKeyStage::ChangingDirection(timeout) => {
@@ -212,7 +302,7 @@ pub fn update_use_key_context(
                 ActionKeyDirection::Right => KeyKind::Right,
                 ActionKeyDirection::Any => unreachable!(),
             };
-            match next_timeout_lifecycle(timeout, CHANGE_DIRECTION_TIMEOUT) {
+            match next_timeout_lifecycle(timeout, scaled_ticks(CHANGE_DIRECTION_TIMEOUT)) {
                 Lifecycle::Started(timeout) => {
                     let _ = context.keys.send_down(key);
                     Player::UseKey(UseKey {
@@ -300,18 +390,35 @@ pub fn update_use_key_context(
                         );
                     }
                     debug_assert!(use_key.link_key.is_none() || completed);
+                    if use_key.hold_ticks.is_some() {
+                        return Player::UseKey(UseKey {
+                            stage: UseKeyStage::Holding(Timeout::default()),
+                            ..use_key
+                        });
+                    }
                     let _ = context.keys.send(use_key.key.into());
                 }
             }
-            let next = Player::UseKey(UseKey {
-                stage: UseKeyStage::Postcondition,
-                ..use_key
-            });
-            if use_key.wait_after_use_ticks > 0 {
-                state.stalling_timeout_state = Some(next);
-                Player::Stalling(Timeout::default(), use_key.wait_after_use_ticks)
-            } else {
-                next
+            update_postcondition_stalling(state, use_key)
+        }
+        UseKeyStage::Holding(timeout) => {
+            let key = use_key.key.into();
+            match next_timeout_lifecycle(timeout, use_key.hold_ticks.unwrap()) {
+                Lifecycle::Started(timeout) => {
+                    let _ = context.keys.send_down(key);
+                    Player::UseKey(UseKey {
+                        stage: UseKeyStage::Holding(timeout),
+                        ..use_key
+                    })
+                }
+                Lifecycle::Ended => {
+                    let _ = context.keys.send_up(key);
+                    update_postcondition_stalling(state, use_key)
+                }
+                Lifecycle::Updated(timeout) => Player::UseKey(UseKey {
+                    stage: UseKeyStage::Holding(timeout),
+                    ..use_key
+                }),
             }
         }
         UseKeyStage::Postcondition => {
@@ -338,23 +445,37 @@ pub fn update_use_key_context(
                 let is_terminal = matches!(next, Player::Idle);
                 if is_terminal {
                     state.auto_mob_track_ignore_xs(context, false);
+                    if state.track_mobbing_no_damage() {
+                        return Some((mobbing_no_damage_unstucking(state), true));
+                    }
                     if state.auto_mob_reachable_y_require_update(y) {
-                        return Some((Player::Stalling(Timeout::default(), MOVE_TIMEOUT), false));
+                        return Some((
+                            Player::Stalling(Timeout::default(), scaled_ticks(MOVE_TIMEOUT)),
+                            false,
+                        ));
                     }
                 }
                 Some((next, is_terminal))
             }
             PlayerAction::PingPong(PlayerActionPingPong {
-                bound, direction, ..
+                bound,
+                direction,
+                walk_only,
+                ..
             }) => {
                 if matches!(next, Player::Idle) {
+                    if state.track_mobbing_no_damage() {
+                        return Some((mobbing_no_damage_unstucking(state), false));
+                    }
                     state.clear_unstucking(true);
-                    Some(on_ping_pong_double_jump_action(
-                        context,
-                        state.last_known_pos.unwrap(),
-                        bound,
-                        direction,
-                    ))
+                    let cur_pos = state.last_known_pos.unwrap();
+                    if walk_only {
+                        Some(on_ping_pong_walk_action(cur_pos, bound, direction))
+                    } else {
+                        Some(on_ping_pong_double_jump_action(
+                            context, cur_pos, bound, direction,
+                        ))
+                    }
                 } else {
                     None
                 }
@@ -364,12 +485,42 @@ pub fn update_use_key_context(
             PlayerAction::Move(_) => None,
             PlayerAction::FamiliarsSwapping(_)
             | PlayerAction::SolveRune
-            | PlayerAction::Panic(_) => unreachable!(),
+            | PlayerAction::Panic(_)
+            | PlayerAction::CashShop(_) => unreachable!(),
         },
         || next,
     )
 }
 
+/// Transitions to [`Player::Unstucking`] after mobbing attacks produced no damage numbers.
+///
+/// The direction is reset so that it is pressed again before the next attack.
+#[inline]
+fn mobbing_no_damage_unstucking(state: &mut PlayerState) -> Player {
+    state.last_known_direction = ActionKeyDirection::Any;
+    Player::Unstucking(
+        Timeout::default(),
+        None,
+        state.track_unstucking_transitioned(),
+    )
+}
+
+/// Transitions to [`UseKeyStage::Postcondition`] after stalling for
+/// [`UseKey::wait_after_use_ticks`] if any.
+#[inline]
+fn update_postcondition_stalling(state: &mut PlayerState, use_key: UseKey) -> Player {
+    let next = Player::UseKey(UseKey {
+        stage: UseKeyStage::Postcondition,
+        ..use_key
+    });
+    if use_key.wait_after_use_ticks > 0 {
+        state.stalling_timeout_state = Some(next);
+        Player::Stalling(Timeout::default(), use_key.wait_after_use_ticks)
+    } else {
+        next
+    }
+}
+
 #[inline]
 fn ensure_direction(state: &PlayerState, direction: ActionKeyDirection) -> bool {
     match direction {
@@ -413,7 +564,7 @@ fn update_link_key(
         }
     };
 
-    match next_timeout_lifecycle(timeout, link_key_timeout) {
+    match next_timeout_lifecycle(timeout, scaled_ticks(link_key_timeout)) {
         Lifecycle::Started(timeout) => {
             if let LinkKeyBinding::Before(key) = link_key {
                 let _ = context.keys.send(key.into());
@@ -441,7 +592,7 @@ fn update_link_key(
         }
         Lifecycle::Updated(timeout) => {
             if matches!(link_key, LinkKeyBinding::Along(_))
-                && timeout.total == LINK_ALONG_PRESS_TICK
+                && timeout.total == scaled_ticks(LINK_ALONG_PRESS_TICK)
             {
                 let _ = context.keys.send(use_key.key.into());
             }
@@ -465,14 +616,18 @@ fn random_wait_ticks(wait_base_ticks: u32, wait_random_range: u32) -> u32 {
 mod tests {
     use std::assert_matches::assert_matches;
 
+    use opencv::core::Point;
     use platforms::windows::KeyKind;
 
     use crate::{
-        ActionKeyDirection, ActionKeyWith, KeyBinding, LinkKeyBinding,
+        ActionKeyDirection, ActionKeyWith, DoubleJumpKeyTiming, KeyBinding, LinkKeyBinding,
+        Position,
+        array::Array,
         bridge::MockKeySender,
         context::Context,
         player::{
-            Player, PlayerState, Timeout, update_non_positional_context,
+            LastMovement, Player, PlayerAction, PlayerActionAutoMob, PlayerState, Timeout,
+            update_non_positional_context,
             use_key::{UseKey, UseKeyStage, update_use_key_context},
         },
     };
@@ -490,6 +645,7 @@ mod tests {
             with: ActionKeyWith::Stationary,
             wait_before_use_ticks: 0,
             wait_after_use_ticks: 0,
+            hold_ticks: None,
             stage: UseKeyStage::Precondition,
         };
 
@@ -516,6 +672,57 @@ mod tests {
         );
     }
 
+    #[test]
+    fn use_key_double_jump_descent_timing() {
+        let mut state = PlayerState::default();
+        let context = Context::new(None, None);
+        let use_key = UseKey {
+            key: KeyBinding::A,
+            link_key: None,
+            count: 1,
+            current_count: 0,
+            direction: ActionKeyDirection::Any,
+            with: ActionKeyWith::DoubleJump,
+            wait_before_use_ticks: 0,
+            wait_after_use_ticks: 0,
+            hold_ticks: None,
+            stage: UseKeyStage::Precondition,
+        };
+        state.config.double_jump_key_timing = DoubleJumpKeyTiming::Descent;
+        state.last_movement = Some(LastMovement::DoubleJumping);
+        state.last_known_pos = Some(Point::new(50, 50));
+
+        // Waits for descending
+        let mut player = update_use_key_context(&context, &mut state, use_key);
+        assert_matches!(
+            player,
+            Player::UseKey(UseKey {
+                stage: UseKeyStage::DoubleJumpTiming(_, 50),
+                ..
+            })
+        );
+        state.last_known_pos = Some(Point::new(50, 55));
+        player = update_non_positional_context(player, &context, &mut state, false).unwrap();
+        assert_matches!(
+            player,
+            Player::UseKey(UseKey {
+                stage: UseKeyStage::DoubleJumpTiming(_, 55),
+                ..
+            })
+        );
+
+        // Uses key once descending
+        state.last_known_pos = Some(Point::new(50, 53));
+        player = update_non_positional_context(player, &context, &mut state, false).unwrap();
+        assert_matches!(
+            player,
+            Player::UseKey(UseKey {
+                stage: UseKeyStage::Using(_, false),
+                ..
+            })
+        );
+    }
+
     #[test]
     fn use_key_change_direction() {
         let mut keys = MockKeySender::new();
@@ -536,6 +743,7 @@ mod tests {
             with: ActionKeyWith::Any,
             wait_before_use_ticks: 0,
             wait_after_use_ticks: 0,
+            hold_ticks: None,
             stage: UseKeyStage::Precondition,
         };
 
@@ -600,6 +808,7 @@ mod tests {
             with: ActionKeyWith::Any,
             wait_before_use_ticks: 0,
             wait_after_use_ticks: 0,
+            hold_ticks: None,
             stage: UseKeyStage::Precondition,
         };
 
@@ -636,6 +845,60 @@ mod tests {
         }
     }
 
+    #[test]
+    fn use_key_holding() {
+        let mut keys = MockKeySender::new();
+        keys.expect_send().never();
+        keys.expect_send_down()
+            .once()
+            .withf(|key| matches!(key, KeyKind::A))
+            .returning(|_| Ok(()));
+        keys.expect_send_up()
+            .once()
+            .withf(|key| matches!(key, KeyKind::A))
+            .returning(|_| Ok(()));
+        let mut state = PlayerState::default();
+        let context = Context::new(Some(keys), None);
+        let use_key = UseKey {
+            key: KeyBinding::A,
+            link_key: None,
+            count: 1,
+            current_count: 0,
+            direction: ActionKeyDirection::Any,
+            with: ActionKeyWith::Any,
+            wait_before_use_ticks: 0,
+            wait_after_use_ticks: 0,
+            hold_ticks: Some(3),
+            stage: UseKeyStage::Using(Timeout::default(), false),
+        };
+
+        let mut player = Player::UseKey(use_key);
+        player = update_non_positional_context(player, &context, &mut state, false).unwrap();
+        assert_matches!(
+            player,
+            Player::UseKey(UseKey {
+                stage: UseKeyStage::Holding(Timeout { started: false, .. }),
+                ..
+            })
+        );
+        while matches!(
+            player,
+            Player::UseKey(UseKey {
+                stage: UseKeyStage::Holding(_),
+                ..
+            })
+        ) {
+            player = update_non_positional_context(player, &context, &mut state, false).unwrap();
+        }
+        assert_matches!(
+            player,
+            Player::UseKey(UseKey {
+                stage: UseKeyStage::Postcondition,
+                ..
+            })
+        );
+    }
+
     #[test]
     fn use_key_stalling() {
         let mut keys = MockKeySender::new();
@@ -653,6 +916,7 @@ mod tests {
             with: ActionKeyWith::Any,
             wait_before_use_ticks: 10,
             wait_after_use_ticks: 20,
+            hold_ticks: None,
             stage: UseKeyStage::Precondition,
         };
 
@@ -700,6 +964,42 @@ mod tests {
         );
     }
 
+    #[test]
+    fn use_key_auto_mob_direction_toward_mobs() {
+        let pos = Point::new(50, 50);
+        let action = |mobs| {
+            PlayerAction::AutoMob(PlayerActionAutoMob {
+                position: Position {
+                    x: 60,
+                    y: 50,
+                    ..Default::default()
+                },
+                mobs,
+                ..Default::default()
+            })
+        };
+
+        // More mobs on the left despite destination on the right
+        let mobs = Array::from_iter([Point::new(40, 50), Point::new(45, 52), Point::new(55, 50)]);
+        assert_matches!(
+            UseKey::from_action_pos(action(mobs), Some(pos)),
+            UseKey {
+                direction: ActionKeyDirection::Left,
+                ..
+            }
+        );
+
+        // Mobs too far in y are ignored and fall back to destination direction
+        let mobs = Array::from_iter([Point::new(40, 80), Point::new(45, 20)]);
+        assert_matches!(
+            UseKey::from_action_pos(action(mobs), Some(pos)),
+            UseKey {
+                direction: ActionKeyDirection::Right,
+                ..
+            }
+        );
+    }
+
     #[test]
     fn use_key_link_along() {
         let mut state = PlayerState::default();
@@ -713,6 +1013,7 @@ mod tests {
             with: ActionKeyWith::Any,
             wait_before_use_ticks: 0,
             wait_after_use_ticks: 0,
+            hold_ticks: None,
             stage: UseKeyStage::Using(Timeout::default(), false),
         };
 
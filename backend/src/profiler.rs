@@ -0,0 +1,210 @@
+use std::{
+    collections::VecDeque,
+    mem,
+    time::{Duration, Instant},
+};
+
+use strum::{Display, EnumIter, IntoEnumIterator};
+
+use crate::{TickStageStats, TickStats, context::ms_per_tick_f32};
+
+/// Maximum number of recent ticks the statistics are computed from.
+///
+/// At 30 ticks per second, this is roughly 5 seconds of ticks.
+const MAX_SAMPLES: usize = 150;
+
+/// A stage of the update loop that is timed on each tick.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Display, EnumIter)]
+pub enum TickStage {
+    Capture,
+    /// Skills and buffs detection.
+    Detection,
+    Minimap,
+    Player,
+    /// Navigator and rotator updates.
+    Rotator,
+    #[strum(to_string = "Request handling")]
+    RequestHandling,
+}
+
+impl TickStage {
+    pub const COUNT: usize = mem::variant_count::<TickStage>();
+}
+
+/// Timings of a single tick.
+#[derive(Clone, Copy, Debug, Default)]
+struct TickSample {
+    stages: [Duration; TickStage::COUNT],
+    total: Duration,
+}
+
+/// Times each [`TickStage`] of the update loop over the recent ticks.
+#[derive(Debug, Default)]
+pub struct TickProfiler {
+    /// The instant the current tick started.
+    started_at: Option<Instant>,
+    /// The timings of the current tick.
+    current: TickSample,
+    /// The timings of the recent completed ticks from the oldest to the newest.
+    samples: VecDeque<TickSample>,
+}
+
+impl TickProfiler {
+    /// Starts timing a new tick.
+    #[inline]
+    pub fn begin_tick(&mut self) {
+        self.started_at = Some(Instant::now());
+        self.current = TickSample::default();
+    }
+
+    /// Completes timing the current tick started by [`Self::begin_tick`].
+    pub fn end_tick(&mut self) {
+        let Some(started_at) = self.started_at.take() else {
+            return;
+        };
+        self.current.total = started_at.elapsed();
+        self.push(self.current);
+    }
+
+    /// Runs `f` and adds the time it takes to `stage` of the current tick.
+    #[inline]
+    pub fn measure<T>(&mut self, stage: TickStage, f: impl FnOnce() -> T) -> T {
+        let start = Instant::now();
+        let value = f();
+        self.record(stage, start.elapsed());
+        value
+    }
+
+    /// Adds `duration` to `stage` of the current tick.
+    #[inline]
+    pub fn record(&mut self, stage: TickStage, duration: Duration) {
+        self.current.stages[stage as usize] += duration;
+    }
+
+    /// Computes the statistics of the recent ticks.
+    pub fn stats(&self) -> TickStats {
+        let ticks = self.samples.len() as u32;
+        let stages = TickStage::iter()
+            .map(|stage| {
+                let durations = self
+                    .samples
+                    .iter()
+                    .map(|sample| sample.stages[stage as usize]);
+                let (average_millis, max_millis) = average_and_max_millis(durations);
+                TickStageStats {
+                    name: stage.to_string(),
+                    average_millis,
+                    max_millis,
+                }
+            })
+            .collect();
+        let (average_millis, max_millis) =
+            average_and_max_millis(self.samples.iter().map(|sample| sample.total));
+        let late_ticks = self
+            .samples
+            .iter()
+            .filter(|sample| sample.total.as_secs_f32() * 1000.0 > ms_per_tick_f32())
+            .count() as u32;
+
+        TickStats {
+            stages,
+            average_millis,
+            max_millis,
+            budget_millis: ms_per_tick_f32(),
+            ticks,
+            late_ticks,
+        }
+    }
+
+    #[inline]
+    fn push(&mut self, sample: TickSample) {
+        if self.samples.len() >= MAX_SAMPLES {
+            self.samples.pop_front();
+        }
+        self.samples.push_back(sample);
+    }
+}
+
+fn average_and_max_millis(durations: impl Iterator<Item = Duration>) -> (f32, f32) {
+    let mut count = 0;
+    let mut total = Duration::ZERO;
+    let mut max = Duration::ZERO;
+    for duration in durations {
+        count += 1;
+        total += duration;
+        max = max.max(duration);
+    }
+    if count == 0 {
+        return (0.0, 0.0);
+    }
+
+    (
+        total.as_secs_f32() * 1000.0 / count as f32,
+        max.as_secs_f32() * 1000.0,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample_with(stage: TickStage, millis: u64, total_millis: u64) -> TickSample {
+        let mut sample = TickSample::default();
+        sample.stages[stage as usize] = Duration::from_millis(millis);
+        sample.total = Duration::from_millis(total_millis);
+        sample
+    }
+
+    #[test]
+    fn stats_empty() {
+        let stats = TickProfiler::default().stats();
+
+        assert_eq!(stats.ticks, 0);
+        assert_eq!(stats.late_ticks, 0);
+        assert_eq!(stats.average_millis, 0.0);
+        assert_eq!(stats.stages.len(), TickStage::COUNT);
+    }
+
+    #[test]
+    fn stats_average_max_and_late_ticks() {
+        let mut profiler = TickProfiler::default();
+        profiler.push(sample_with(TickStage::Player, 10, 20));
+        profiler.push(sample_with(TickStage::Player, 30, 50));
+
+        let stats = profiler.stats();
+        let player = &stats.stages[TickStage::Player as usize];
+
+        assert_eq!(stats.ticks, 2);
+        assert_eq!(stats.late_ticks, 1);
+        assert_eq!(stats.average_millis, 35.0);
+        assert_eq!(stats.max_millis, 50.0);
+        assert_eq!(player.name, "Player");
+        assert_eq!(player.average_millis, 20.0);
+        assert_eq!(player.max_millis, 30.0);
+    }
+
+    #[test]
+    fn push_keeps_recent_samples() {
+        let mut profiler = TickProfiler::default();
+        for _ in 0..MAX_SAMPLES + 10 {
+            profiler.push(TickSample::default());
+        }
+
+        assert_eq!(profiler.samples.len(), MAX_SAMPLES);
+    }
+
+    #[test]
+    fn record_accumulates_stage() {
+        let mut profiler = TickProfiler::default();
+        profiler.begin_tick();
+        profiler.record(TickStage::Capture, Duration::from_millis(2));
+        profiler.record(TickStage::Capture, Duration::from_millis(3));
+
+        assert_eq!(
+            profiler.current.stages[TickStage::Capture as usize],
+            Duration::from_millis(5)
+        );
+        profiler.end_tick();
+        assert_eq!(profiler.samples.len(), 1);
+    }
+}
@@ -0,0 +1,120 @@
+use std::{
+    env,
+    fs::{self, File},
+    io::{BufRead, BufReader, BufWriter, Write},
+    path::PathBuf,
+    sync::LazyLock,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use anyhow::Result;
+use log::debug;
+
+use crate::StateSnapshot;
+
+/// Maximum number of snapshots written to a single recording file before rotating.
+///
+/// At 30 ticks per second, this is roughly 30 minutes of snapshots per file.
+const MAX_SNAPSHOTS_PER_FILE: usize = 54_000;
+
+/// Number of snapshots between each flush so that the file can be loaded while recording.
+const FLUSH_INTERVAL: usize = 30;
+
+pub(crate) static RECORDINGS_DIR: LazyLock<PathBuf> = LazyLock::new(|| {
+    let dir = env::current_exe()
+        .unwrap()
+        .parent()
+        .unwrap()
+        .join("recordings");
+    fs::create_dir_all(dir.clone()).unwrap();
+    dir
+});
+
+/// The file currently being written to.
+static CURRENT_FILE: LazyLock<PathBuf> = LazyLock::new(|| RECORDINGS_DIR.join("snapshots.jsonl"));
+
+/// The file containing snapshots prior to [`CURRENT_FILE`].
+static PREVIOUS_FILE: LazyLock<PathBuf> =
+    LazyLock::new(|| RECORDINGS_DIR.join("snapshots.1.jsonl"));
+
+/// A flight recorder that writes per-tick [`StateSnapshot`] to disk.
+///
+/// Snapshots are written as JSON lines into a ring of two files. Once the current file reaches
+/// [`MAX_SNAPSHOTS_PER_FILE`], it replaces the previous file and a new current file is started.
+/// This bounds the disk usage while still keeping the most recent snapshots for a long session.
+#[derive(Debug, Default)]
+pub struct FlightRecorder {
+    writer: Option<BufWriter<File>>,
+    count: usize,
+}
+
+impl FlightRecorder {
+    /// Starts or stops recording.
+    ///
+    /// Starting a new recording discards the snapshots from the last recording.
+    pub fn set_enabled(&mut self, enabled: bool) {
+        if enabled == self.writer.is_some() {
+            return;
+        }
+        if enabled {
+            let _ = fs::remove_file(&*PREVIOUS_FILE);
+            self.writer = File::create(&*CURRENT_FILE).ok().map(BufWriter::new);
+            self.count = 0;
+            debug!(target: "recorder", "started recording to {:?}", *CURRENT_FILE);
+        } else if let Some(mut writer) = self.writer.take() {
+            let _ = writer.flush();
+            debug!(target: "recorder", "stopped recording");
+        }
+    }
+
+    /// Records `snapshot` if recording is enabled.
+    ///
+    /// `snapshot` is a function because building the snapshot is not free and should be avoided
+    /// when not recording.
+    pub fn record(&mut self, snapshot: impl FnOnce(u64) -> StateSnapshot) {
+        let Some(writer) = self.writer.as_mut() else {
+            return;
+        };
+        let millis = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|duration| duration.as_millis() as u64)
+            .unwrap_or_default();
+        if write_snapshot(writer, &snapshot(millis)).is_err() {
+            self.writer = None;
+            return;
+        }
+
+        self.count += 1;
+        if self.count.is_multiple_of(FLUSH_INTERVAL) {
+            let _ = writer.flush();
+        }
+        if self.count >= MAX_SNAPSHOTS_PER_FILE {
+            // Drop to close the file first because an opened file cannot be renamed on Windows
+            drop(self.writer.take());
+            self.writer = fs::rename(&*CURRENT_FILE, &*PREVIOUS_FILE)
+                .and_then(|_| File::create(&*CURRENT_FILE))
+                .ok()
+                .map(BufWriter::new);
+            self.count = 0;
+        }
+    }
+}
+
+#[inline]
+fn write_snapshot(writer: &mut BufWriter<File>, snapshot: &StateSnapshot) -> Result<()> {
+    serde_json::to_writer(&mut *writer, snapshot)?;
+    writer.write_all(b"\n")?;
+    Ok(())
+}
+
+/// Loads all recorded snapshots from the oldest to the newest.
+///
+/// Lines that fail to parse (e.g. partially written last line) are skipped.
+pub fn load_snapshots() -> Vec<StateSnapshot> {
+    [&*PREVIOUS_FILE, &*CURRENT_FILE]
+        .into_iter()
+        .filter_map(|path| File::open(path).ok())
+        .flat_map(|file| BufReader::new(file).lines().map_while(|line| line.ok()))
+        .filter_map(|line| serde_json::from_str::<StateSnapshot>(&line).ok())
+        .collect()
+}
@@ -0,0 +1,377 @@
+use std::time::Duration;
+
+use anyhow::Error;
+use log::{debug, error};
+use platforms::windows::KeyKind;
+use reqwest::{
+    Client, RequestBuilder,
+    multipart::{Form, Part},
+};
+use serde::{Deserialize, Serialize};
+use tokio::{
+    spawn,
+    sync::mpsc::{UnboundedReceiver, UnboundedSender, unbounded_channel},
+    task::JoinHandle,
+    time::sleep,
+};
+
+use crate::{Settings, player::parse_rune_arrows};
+
+const DISCORD_API_URL: &str = "https://discord.com/api/v10";
+
+/// The interval between polling the channel for new messages.
+const POLL_INTERVAL: Duration = Duration::from_secs(3);
+
+/// The prefix of a message to be parsed as a [`RemoteCommand`].
+const COMMAND_PREFIX: char = '!';
+
+/// A command received from the remote control channel.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum RemoteCommand {
+    Help,
+    Status,
+    Screenshot,
+    Pause,
+    Resume,
+    ChangeChannel,
+    Stop,
+    /// The rune arrows typed remotely, [`None`] if they cannot be parsed.
+    RuneArrows(Option<[KeyKind; 4]>),
+}
+
+impl RemoteCommand {
+    /// Parses `content` in the form of `!<command>`, case-insensitive.
+    fn parse(content: &str) -> Option<RemoteCommand> {
+        let content = content.trim().strip_prefix(COMMAND_PREFIX)?;
+        let (command, args) = content
+            .split_once(char::is_whitespace)
+            .unwrap_or((content, ""));
+        match command.to_ascii_lowercase().as_str() {
+            "help" => Some(RemoteCommand::Help),
+            "status" => Some(RemoteCommand::Status),
+            "screenshot" => Some(RemoteCommand::Screenshot),
+            "pause" => Some(RemoteCommand::Pause),
+            "resume" => Some(RemoteCommand::Resume),
+            "channel" => Some(RemoteCommand::ChangeChannel),
+            "stop" => Some(RemoteCommand::Stop),
+            "rune" => Some(RemoteCommand::RuneArrows(parse_rune_arrows(args))),
+            _ => None,
+        }
+    }
+
+    /// The usage text of all commands.
+    pub fn help() -> &'static str {
+        "`!status` shows the bot status\n\
+        `!screenshot` sends a game snapshot\n\
+        `!pause` pauses the actions\n\
+        `!resume` resumes the actions\n\
+        `!channel` changes to a different channel\n\
+        `!stop` stops the actions\n\
+        `!rune <arrows>` types the rune arrows (e.g. `!rune ULDR`) when asked"
+    }
+}
+
+#[derive(Clone, PartialEq, Debug)]
+struct RemoteConfig {
+    bot_token: String,
+    channel_id: String,
+    /// The only user allowed to send commands
+    user_id: String,
+}
+
+impl RemoteConfig {
+    fn from_settings(settings: &Settings) -> Option<RemoteConfig> {
+        let notifications = &settings.notifications;
+        let bot_token = notifications.discord_bot_token.trim();
+        let channel_id = notifications.discord_channel_id.trim();
+        let user_id = notifications.discord_user_id.trim();
+        if !notifications.enable_discord_remote_control
+            || bot_token.is_empty()
+            || channel_id.is_empty()
+            || user_id.is_empty()
+        {
+            return None;
+        }
+
+        Some(RemoteConfig {
+            bot_token: bot_token.to_string(),
+            channel_id: channel_id.to_string(),
+            user_id: user_id.to_string(),
+        })
+    }
+
+    fn authorize(&self, request: RequestBuilder) -> RequestBuilder {
+        request.header("Authorization", format!("Bot {}", self.bot_token))
+    }
+
+    fn messages_url(&self) -> String {
+        format!("{DISCORD_API_URL}/channels/{}/messages", self.channel_id)
+    }
+}
+
+/// Listens for [`RemoteCommand`]s sent by the Discord user in a channel through a Discord bot.
+///
+/// The channel is polled through the REST API so there is no need for a gateway connection or a
+/// public endpoint for slash commands.
+#[derive(Debug, Default)]
+pub struct RemoteControl {
+    client: Client,
+    config: Option<RemoteConfig>,
+    task: Option<JoinHandle<()>>,
+    receiver: Option<UnboundedReceiver<RemoteCommand>>,
+}
+
+impl RemoteControl {
+    pub fn new(settings: &Settings) -> Self {
+        let mut remote_control = Self::default();
+        remote_control.update(settings);
+        remote_control
+    }
+
+    /// Starts, restarts or stops listening for commands if the remote control settings changed.
+    pub fn update(&mut self, settings: &Settings) {
+        let config = RemoteConfig::from_settings(settings);
+        if config == self.config {
+            return;
+        }
+        if let Some(task) = self.task.take() {
+            task.abort();
+        }
+        self.receiver = None;
+        self.config = config.clone();
+
+        if let Some(config) = config {
+            let (sender, receiver) = unbounded_channel();
+            self.task = Some(spawn(poll_commands(self.client.clone(), config, sender)));
+            self.receiver = Some(receiver);
+        }
+    }
+
+    pub fn try_recv(&mut self) -> Option<RemoteCommand> {
+        self.receiver.as_mut()?.try_recv().ok()
+    }
+
+    /// Replies to the channel with `content` and an optional PNG `frame` attached.
+    pub fn reply(&self, content: impl Into<String>, frame: Option<Vec<u8>>) {
+        let Some(config) = self.config.clone() else {
+            return;
+        };
+        let client = self.client.clone();
+        let content = content.into();
+        spawn(async move {
+            let _ = post_message(&client, &config, content, frame)
+                .await
+                .inspect_err(|err| {
+                    error!(target: "remote_control", "replying to command failed {err}");
+                });
+        });
+    }
+}
+
+impl Drop for RemoteControl {
+    fn drop(&mut self) {
+        if let Some(task) = self.task.take() {
+            task.abort();
+        }
+    }
+}
+
+#[derive(Deserialize, Debug)]
+struct DiscordMessage {
+    id: String,
+    content: String,
+    author: DiscordUser,
+}
+
+#[derive(Deserialize, Debug)]
+struct DiscordUser {
+    id: String,
+}
+
+#[derive(Serialize, Debug)]
+struct DiscordMessageBody {
+    content: String,
+    attachments: Vec<DiscordAttachment>,
+}
+
+#[derive(Serialize, Debug)]
+struct DiscordAttachment {
+    id: usize,
+    filename: &'static str,
+}
+
+async fn poll_commands(
+    client: Client,
+    config: RemoteConfig,
+    sender: UnboundedSender<RemoteCommand>,
+) {
+    // Messages sent before listening are skipped by only fetching the latest one first
+    let mut last_id = None;
+    let mut initialized = false;
+    loop {
+        match fetch_messages(&client, &config, last_id).await {
+            Ok(messages) => {
+                for (id, command) in parse_commands(messages, &config.user_id) {
+                    last_id = last_id.max(Some(id));
+                    if initialized && let Some(command) = command {
+                        debug!(target: "remote_control", "received command {command:?}");
+                        if sender.send(command).is_err() {
+                            return;
+                        }
+                    }
+                }
+                // An empty channel has no latest message, so every message from now on is new
+                last_id = last_id.or(Some(0));
+                initialized = true;
+            }
+            Err(err) => {
+                error!(target: "remote_control", "fetching channel messages failed {err}");
+            }
+        }
+        sleep(POLL_INTERVAL).await;
+    }
+}
+
+async fn fetch_messages(
+    client: &Client,
+    config: &RemoteConfig,
+    after: Option<u64>,
+) -> Result<Vec<DiscordMessage>, Error> {
+    let mut request = client.get(config.messages_url());
+    request = match after {
+        Some(id) => request.query(&[("after", id.to_string()), ("limit", "50".to_string())]),
+        None => request.query(&[("limit", "1")]),
+    };
+    let body = config
+        .authorize(request)
+        .send()
+        .await?
+        .error_for_status()?
+        .text()
+        .await?;
+
+    Ok(serde_json::from_str(&body)?)
+}
+
+/// Parses `messages` into tuples of message ID and command sorted from oldest to newest.
+///
+/// Messages not sent by `user_id` or not a command have [`None`] command.
+fn parse_commands(
+    messages: Vec<DiscordMessage>,
+    user_id: &str,
+) -> Vec<(u64, Option<RemoteCommand>)> {
+    let mut commands = messages
+        .into_iter()
+        .filter_map(|message| {
+            let id = message.id.parse::<u64>().ok()?;
+            let command = (message.author.id == user_id)
+                .then(|| RemoteCommand::parse(&message.content))
+                .flatten();
+            Some((id, command))
+        })
+        .collect::<Vec<_>>();
+    commands.sort_by_key(|(id, _)| *id);
+    commands
+}
+
+async fn post_message(
+    client: &Client,
+    config: &RemoteConfig,
+    content: String,
+    frame: Option<Vec<u8>>,
+) -> Result<(), Error> {
+    let body = DiscordMessageBody {
+        content,
+        attachments: frame
+            .is_some()
+            .then_some(DiscordAttachment {
+                id: 0,
+                filename: "image.png",
+            })
+            .into_iter()
+            .collect(),
+    };
+    let mut form = Form::new().text("payload_json", serde_json::to_string(&body)?);
+    if let Some(frame) = frame {
+        form = form.part(
+            "files[0]",
+            Part::bytes(frame)
+                .mime_str("image/png")
+                .unwrap()
+                .file_name("image.png"),
+        );
+    }
+
+    config
+        .authorize(client.post(config.messages_url()))
+        .multipart(form)
+        .send()
+        .await?
+        .error_for_status()?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use platforms::windows::KeyKind;
+
+    use super::{DiscordMessage, DiscordUser, RemoteCommand, parse_commands};
+
+    fn message(id: &str, author: &str, content: &str) -> DiscordMessage {
+        DiscordMessage {
+            id: id.to_string(),
+            content: content.to_string(),
+            author: DiscordUser {
+                id: author.to_string(),
+            },
+        }
+    }
+
+    #[test]
+    fn parse_command() {
+        assert_eq!(
+            RemoteCommand::parse(" !Status please"),
+            Some(RemoteCommand::Status)
+        );
+        assert_eq!(
+            RemoteCommand::parse("!channel"),
+            Some(RemoteCommand::ChangeChannel)
+        );
+        assert_eq!(
+            RemoteCommand::parse("!rune up left down right"),
+            Some(RemoteCommand::RuneArrows(Some([
+                KeyKind::Up,
+                KeyKind::Left,
+                KeyKind::Down,
+                KeyKind::Right
+            ])))
+        );
+        assert_eq!(
+            RemoteCommand::parse("!rune"),
+            Some(RemoteCommand::RuneArrows(None))
+        );
+        assert_eq!(RemoteCommand::parse("stop"), None);
+        assert_eq!(RemoteCommand::parse("!unknown"), None);
+    }
+
+    #[test]
+    fn parse_commands_sorted_and_filtered_by_user() {
+        let commands = parse_commands(
+            vec![
+                message("30", "1", "!stop"),
+                message("20", "2", "!pause"),
+                message("10", "1", "!pause"),
+            ],
+            "1",
+        );
+
+        assert_eq!(
+            commands,
+            vec![
+                (10, Some(RemoteCommand::Pause)),
+                (20, None),
+                (30, Some(RemoteCommand::Stop)),
+            ]
+        );
+    }
+}
@@ -1,10 +1,10 @@
 use std::time::Instant;
-use std::{sync::LazyLock, time::Duration};
+use std::{collections::HashMap, sync::LazyLock, time::Duration};
 
 use base64::{Engine, prelude::BASE64_STANDARD};
 #[cfg(debug_assertions)]
 use include_dir::{Dir, include_dir};
-use log::debug;
+use log::{debug, info};
 use opencv::core::Vector;
 #[cfg(debug_assertions)]
 use opencv::{
@@ -16,7 +16,10 @@ use opencv::{
     core::{MatTraitConst, MatTraitConstManual, Rect, Vec4b},
     imgcodecs::imencode_def,
 };
-use platforms::windows::{Handle, KeyInputKind, KeyKind, KeyReceiver, query_capture_handles};
+use platforms::windows::{
+    BitBltCapture, Handle, Hotkey, HotkeyReceiver, KeyInputKind, KeyKind, KeyReceiver,
+    query_capture_handles, register_hotkeys,
+};
 #[cfg(debug_assertions)]
 use rand::distr::{Alphanumeric, SampleString};
 use strum::IntoEnumIterator;
@@ -24,34 +27,60 @@ use tokio::sync::broadcast;
 
 use crate::DatabaseEvent;
 #[cfg(debug_assertions)]
+use crate::PlayerStateSnapshot;
+#[cfg(debug_assertions)]
 use crate::debug::{
     save_image_for_training, save_image_for_training_to, save_minimap_for_training,
 };
 #[cfg(debug_assertions)]
 use crate::detect::{ArrowsCalibrating, ArrowsState, CachedDetector, Detector};
-#[cfg(debug_assertions)]
 use crate::mat::OwnedMat;
 use crate::pathing::Platform;
 use crate::{
-    Action, ActionCondition, ActionConfigurationCondition, ActionKey, BoundQuadrant, CaptureMode,
-    Character, GameOperation, GameState, KeyBinding, KeyBindingConfiguration,
-    Minimap as MinimapData, NavigationPath, PotionMode, RequestHandler, RotationMode, RotatorMode,
-    Settings,
-    bridge::{ImageCapture, ImageCaptureKind, KeySenderMethod},
+    Action, ActionCondition, ActionConfigurationCondition, ActionKey, ActionStats, Bound,
+    BoundQuadrant, CaptureMode, Character, GameOperation, GameState, GlobalHotkey, InputEvent,
+    KeyBinding, KeyBindingConfiguration, MAX_UNSTUCK_STRATEGIES, Minimap as MinimapData,
+    NavigationPath, Platform as PlatformData, PotionMode, RequestHandler, RotationMode,
+    RotatorMode, Schedule, SessionStats, Settings, StrangerChannelHop, TickStats,
+    bridge::{DefaultKeySender, ImageCapture, ImageCaptureKind, KeySenderMethod},
     buff::{BuffKind, BuffState},
-    context::{Context, Operation},
-    database::InputMethod,
+    capture_monitor::{CaptureMonitor, CaptureRecovery},
+    checkpoint::CheckpointTracker,
+    context::{Context, Operation, cycle_duration, measured_fps, millis_to_ticks, set_fps, to_png},
+    control_server::{ControlCommand, ControlServer, ControlStatus},
+    database::{
+        Checkpoint, MAX_CHANNEL_COUNT, query_auto_mob_data, query_minimap, upsert_auto_mob_data,
+        upsert_navigation_path, upsert_settings,
+    },
+    detect::{set_detection_scale, update_models},
+    input_audit::InputAuditLog,
+    key_macro_recorder::KeyMacroRecorder,
     minimap::{Minimap, MinimapState},
+    movement_test::MovementTester,
     navigation::Navigator,
-    player::{PlayerState, Quadrant},
+    navigation_recorder::NavigationRecorder,
+    network::NotificationKind,
+    overlay::draw_detection_overlay,
+    platform_recorder::PlatformRecorder,
+    player::{PanicTo, Panicking, Player, PlayerState, Quadrant, parse_rune_arrows},
     poll_request,
+    profiler::TickProfiler,
+    remote_control::{RemoteCommand, RemoteControl},
     rotator::{Rotator, RotatorBuildArgs},
+    session::{SessionStopReason, SessionTracker},
     skill::SkillKind,
 };
 
 static GAME_STATE: LazyLock<broadcast::Sender<GameState>> =
     LazyLock::new(|| broadcast::channel(1).0);
 
+/// How long the UI is notified about the panic key being triggered.
+const PANIC_KEY_NOTIFY_DURATION: Duration = Duration::from_secs(3);
+
+const TOGGLE_ACTIONS_HOTKEY_ID: i32 = 1;
+const PAUSE_ACTIONS_HOTKEY_ID: i32 = 2;
+const EMERGENCY_HALT_HOTKEY_ID: i32 = 3;
+
 // TODO: Add unit tests
 pub struct DefaultRequestHandler<'a> {
     pub context: &'a mut Context,
@@ -68,10 +97,23 @@ pub struct DefaultRequestHandler<'a> {
     pub minimap_data_preset: &'a mut Option<String>,
     pub key_sender: &'a broadcast::Sender<KeyBinding>,
     pub key_receiver: &'a mut KeyReceiver,
+    pub hotkey_receiver: &'a mut HotkeyReceiver,
+    pub remote_control: &'a mut RemoteControl,
+    pub control_server: &'a mut ControlServer,
     pub image_capture: &'a mut ImageCapture,
     pub capture_handles: &'a mut Vec<(String, Handle)>,
     pub selected_capture_handle: &'a mut Option<Handle>,
     pub database_event_receiver: &'a mut broadcast::Receiver<DatabaseEvent>,
+    pub panic_key_triggered_at: &'a mut Option<Instant>,
+    pub movement_tester: &'a mut MovementTester,
+    pub platform_recorder: &'a mut PlatformRecorder,
+    pub navigation_recorder: &'a mut NavigationRecorder,
+    pub key_macro_recorder: &'a mut KeyMacroRecorder,
+    pub input_audit_log: &'a mut InputAuditLog,
+    pub session_tracker: &'a mut SessionTracker,
+    pub checkpoint_tracker: &'a mut CheckpointTracker,
+    pub capture_monitor: &'a mut CaptureMonitor,
+    pub tick_profiler: &'a TickProfiler,
     #[cfg(debug_assertions)]
     pub recording_images_id: &'a mut Option<String>,
     #[cfg(debug_assertions)]
@@ -82,6 +124,10 @@ impl DefaultRequestHandler<'_> {
     pub fn poll_request(&mut self) {
         poll_request(self);
         poll_key(self);
+        poll_hotkey(self);
+        poll_remote_command(self);
+        poll_control_command(self);
+        update_control_server(self);
         poll_database_event(self);
         #[cfg(debug_assertions)]
         poll_debug(self);
@@ -111,7 +157,9 @@ impl DefaultRequestHandler<'_> {
                     Operation::Halting => GameOperation::Halting,
                     Operation::Running => GameOperation::Running,
                     Operation::RunUntil(instant) => GameOperation::RunUntil(instant),
+                    Operation::Paused(_) => GameOperation::Paused,
                 },
+                fps: measured_fps(),
                 frame: self
                     .context
                     .detector
@@ -136,6 +184,11 @@ impl DefaultRequestHandler<'_> {
                 } else {
                     vec![]
                 },
+                rune: if let Minimap::Idle(idle) = self.context.minimap {
+                    idle.rune().map(|rune| (rune.x, rune.y))
+                } else {
+                    None
+                },
                 auto_mob_quadrant: self.player.auto_mob_last_quadrant().map(|quadrant| {
                     match quadrant {
                         Quadrant::TopLeft => BoundQuadrant::TopLeft,
@@ -144,6 +197,33 @@ impl DefaultRequestHandler<'_> {
                         Quadrant::BottomLeft => BoundQuadrant::BottomLeft,
                     }
                 }),
+                panic_key_triggered: self
+                    .panic_key_triggered_at
+                    .is_some_and(|instant| instant.elapsed() < PANIC_KEY_NOTIFY_DURATION),
+                movement_test: self.movement_tester.transitions(),
+                recording_platforms: self.platform_recorder.is_recording(),
+                recorded_platforms: self.platform_recorder.platforms(),
+                recording_navigation: self.navigation_recorder.is_recording(),
+                recording_key_macro: self.key_macro_recorder.is_recording(),
+                action_stats: self.preset_action_stats(),
+                missing_required_buffs: self
+                    .rotator
+                    .missing_required_buffs()
+                    .iter()
+                    .map(|kind| kind.to_string())
+                    .collect(),
+                buffs_remaining_millis: self
+                    .buff_states
+                    .iter()
+                    .filter_map(|state| {
+                        state
+                            .remaining_millis()
+                            .map(|millis| (state.kind().to_string(), millis))
+                    })
+                    .collect(),
+                queued_actions: self.rotator.queued_actions(),
+                minimap_id: self.minimap_data.as_ref().and_then(|minimap| minimap.id),
+                minimap_preset: self.minimap_data_preset.clone(),
             };
             let _ = GAME_STATE.send(game_state);
         }
@@ -163,14 +243,31 @@ impl DefaultRequestHandler<'_> {
                 RotationMode::PingPong => RotatorMode::PingPong(
                     minimap.rotation_mobbing_key,
                     minimap.rotation_ping_pong_bound,
+                    minimap.rotation_ping_pong_walk_only,
                 ),
+                RotationMode::Bossing => RotatorMode::Bossing,
             })
             .unwrap_or_default();
+        let stranger_channel_hop = self
+            .minimap_data
+            .as_ref()
+            .map(|minimap| minimap.stranger_channel_hop)
+            .unwrap_or_default();
+        let enable_stranger_channel_hop = match stranger_channel_hop {
+            StrangerChannelHop::Settings => self.settings.enable_stranger_channel_hop,
+            StrangerChannelHop::Enabled => true,
+            StrangerChannelHop::Disabled => false,
+        };
         let reset_on_erda = self
             .minimap_data
             .as_ref()
             .map(|minimap| minimap.actions_any_reset_on_erda_condition)
             .unwrap_or_default();
+        let home_position = self
+            .minimap_data
+            .as_ref()
+            .zip(self.minimap_data_preset.as_ref())
+            .and_then(|(minimap, preset)| minimap.home_positions.get(preset).copied());
         let actions = self
             .character
             .as_ref()
@@ -181,10 +278,34 @@ impl DefaultRequestHandler<'_> {
                     .collect::<Vec<_>>()
             })
             .unwrap_or_default();
+        let mobbing_cooldown_keys = self
+            .minimap_data
+            .as_ref()
+            .map(|minimap| minimap.rotation_mobbing_cooldown_keys.as_slice())
+            .unwrap_or_default();
+        let required_buffs = self
+            .minimap_data
+            .as_ref()
+            .zip(self.minimap_data_preset.as_ref())
+            .and_then(|(minimap, preset)| minimap.required_buffs.get(preset))
+            .map(|buffs| {
+                buffs
+                    .iter()
+                    .copied()
+                    .map(BuffKind::from)
+                    .collect::<Vec<_>>()
+            })
+            .unwrap_or_default();
+        for state in self.buff_states.iter_mut() {
+            state.set_required(required_buffs.contains(&state.kind()));
+        }
         let args = RotatorBuildArgs {
             mode,
+            mobbing_cooldown_keys,
             actions: actions.as_slice(),
+            home_position,
             buffs: self.buffs,
+            required_buffs: &required_buffs,
             familiar_essence_key: self
                 .character
                 .as_ref()
@@ -193,6 +314,7 @@ impl DefaultRequestHandler<'_> {
             familiar_swappable_slots: self.settings.familiars.swappable_familiars,
             familiar_swappable_rarities: &self.settings.familiars.swappable_rarities,
             familiar_swap_check_millis: self.settings.familiars.swap_check_millis,
+            familiar_essence_low_percentage: self.settings.familiars.essence_low_percentage,
             elite_boss_behavior: self.character.as_ref().and_then(|character| {
                 character
                     .elite_boss_behavior_enabled
@@ -203,22 +325,59 @@ impl DefaultRequestHandler<'_> {
                 .as_ref()
                 .map(|character| character.elite_boss_behavior_key)
                 .unwrap_or_default(),
+            inventory_full_behavior: self
+                .character
+                .as_ref()
+                .map(|character| character.inventory_full_behavior)
+                .unwrap_or_default(),
             enable_panic_mode: self.settings.enable_panic_mode,
+            enable_stranger_channel_hop,
+            stranger_channel_hop_after_millis: self.settings.stranger_channel_hop_after_millis,
+            stranger_channel_hop_cooldown_millis: self
+                .settings
+                .stranger_channel_hop_cooldown_millis,
+            enable_cash_shop_anti_idle: self.settings.enable_cash_shop_anti_idle,
+            cash_shop_anti_idle_every_millis: self.settings.cash_shop_anti_idle_every_millis,
+            cash_shop_anti_idle_stay_millis: self.settings.cash_shop_anti_idle_stay_millis,
             enable_rune_solving: self.settings.enable_rune_solving,
+            enable_rune_solving_only_when_safe: self.settings.rune_solving_only_when_safe,
             enable_familiars_swapping: self.settings.familiars.enable_familiars_swapping,
+            enable_familiars_resummon_on_essence_low: self
+                .settings
+                .familiars
+                .resummon_on_essence_low,
             enable_reset_normal_actions_on_erda: reset_on_erda,
         };
 
         self.rotator.build_actions(args);
     }
 
+    /// Maps the [`Rotator`] action statistics to the index of the current preset's actions.
+    ///
+    /// The built actions are prefixed with the character's configuration actions so they are
+    /// excluded.
+    fn preset_action_stats(&self) -> HashMap<usize, ActionStats> {
+        let offset = self
+            .character
+            .as_ref()
+            .map(|character| config_actions(character).len())
+            .unwrap_or_default();
+        self.rotator
+            .action_stats()
+            .iter()
+            .filter_map(|(index, stats)| index.checked_sub(offset).map(|index| (index, *stats)))
+            .collect()
+    }
+
     pub fn update_context_halting(&mut self, halting: bool, reset_player_to_idle: bool) {
         if self.minimap_data.as_ref().is_some() && self.character.is_some() {
             self.context.operation = match (halting, self.settings.cycle_run_stop) {
                 (true, _) => Operation::Halting,
                 (false, true) => Instant::now()
-                    .checked_add(Duration::from_millis(
+                    .checked_add(cycle_duration(
+                        &self.context.rng,
                         self.settings.cycle_run_duration_millis,
+                        self.settings.cycle_run_duration_millis_random_range,
                     ))
                     .map(Operation::RunUntil)
                     .unwrap_or(Operation::Running),
@@ -227,73 +386,419 @@ impl DefaultRequestHandler<'_> {
             if halting {
                 self.rotator.reset_queue();
                 self.player.clear_actions_aborted(reset_player_to_idle);
+                self.save_auto_mob_data();
             }
         }
     }
 
-    fn update_settings(&mut self, settings: Settings) {
-        let mut handle_or_default = self.selected_capture_handle.unwrap_or(self.context.handle);
+    /// Pauses or resumes the running actions.
+    ///
+    /// Unlike [`Self::update_context_halting`], the rotator queue and the player's current action
+    /// are kept. The player is reset to [`Player::Idle`] with all keys released so the current
+    /// action starts over on resume.
+    pub fn update_context_paused(&mut self, paused: bool) {
+        let now = Instant::now();
+        self.context.operation = match (paused, &self.context.operation) {
+            (true, Operation::Running) => Operation::Paused(None),
+            (true, Operation::RunUntil(instant)) => {
+                Operation::Paused(Some(instant.saturating_duration_since(now)))
+            }
+            (false, Operation::Paused(remaining)) => remaining
+                .map(|remaining| Operation::RunUntil(now + remaining))
+                .unwrap_or(Operation::Running),
+            _ => return,
+        };
+        if paused {
+            self.context.player = Player::Idle;
+            let _ = self.context.keys.release_all();
+        }
+    }
+
+    /// Selects `minimap` automatically detected as the current map.
+    ///
+    /// The current preset is kept if `minimap` also has it. Otherwise, the first preset is used.
+    pub fn select_minimap(&mut self, minimap: MinimapData) {
+        let preset = self
+            .minimap_data_preset
+            .clone()
+            .filter(|preset| minimap.actions.contains_key(preset))
+            .or_else(|| minimap.actions.keys().next().cloned());
+        self.on_update_minimap(preset, Some(minimap));
+    }
 
-        if settings.capture_mode != self.settings.capture_mode {
-            self.image_capture
-                .set_mode(handle_or_default, settings.capture_mode);
+    /// Starts the actions when the time window of `schedule` starts.
+    ///
+    /// The minimap and preset of `schedule` are selected first if any.
+    pub fn start_by_schedule(&mut self, schedule: Schedule) {
+        if let Some(minimap) = schedule.minimap_id.and_then(query_minimap) {
+            let preset = schedule
+                .minimap_preset
+                .filter(|preset| minimap.actions.contains_key(preset))
+                .or_else(|| minimap.actions.keys().next().cloned());
+            self.on_update_minimap(preset, Some(minimap));
         }
+        self.update_context_halting(false, false);
+    }
 
-        if settings.input_method != self.settings.input_method
-            || settings.input_method_rpc_server_url != self.settings.input_method_rpc_server_url
+    /// Halts the actions when the time window of the previous schedule ends.
+    pub fn halt_by_schedule(&mut self) {
+        if matches!(self.context.operation, Operation::Halting) {
+            return;
+        }
+        self.session_tracker
+            .set_stop_reason(SessionStopReason::Schedule);
+        self.update_context_halting(true, true);
+    }
+
+    /// Updates the navigation recorder from the current map and player position.
+    pub fn update_navigation_recorder(&mut self) {
+        let context = &*self.context;
+        self.navigation_recorder.update(
+            context.did_minimap_changed,
+            self.player.last_known_pos,
+            || create_navigation_path(context),
+        );
+    }
+
+    /// Saves a checkpoint of the running actions periodically along with the auto-mobbing data.
+    pub fn update_checkpoint(&mut self) {
+        let running = !matches!(self.context.operation, Operation::Halting);
+        if !self.checkpoint_tracker.should_save(running) {
+            return;
+        }
+
+        let now = Instant::now();
+        let (cycle_remaining, cycle_stopping) = match self.context.operation {
+            Operation::HaltUntil(instant) => (Some(instant.saturating_duration_since(now)), true),
+            Operation::RunUntil(instant) => (Some(instant.saturating_duration_since(now)), false),
+            Operation::Paused(remaining) => (remaining, false),
+            Operation::Halting | Operation::Running => (None, false),
+        };
+        let (normal_action_index, normal_actions_backward) = self.rotator.normal_position();
+        self.checkpoint_tracker.save(Checkpoint {
+            character_id: self.character.as_ref().and_then(|character| character.id),
+            minimap_id: self.minimap_data.as_ref().and_then(|minimap| minimap.id),
+            minimap_preset: self.minimap_data_preset.clone(),
+            cycle_remaining_millis: cycle_remaining.map(|duration| duration.as_millis() as u64),
+            cycle_stopping,
+            normal_action_index,
+            normal_actions_backward,
+            ..Checkpoint::default()
+        });
+        self.save_auto_mob_data();
+    }
+
+    /// Recovers the capture when no frame has been captured for
+    /// [`Settings::capture_recovery_timeout_millis`].
+    ///
+    /// The capture is recreated first and then falls back to the next capture mode if
+    /// [`Settings::capture_recovery_fallback`] is enabled and frames are still not captured.
+    pub fn update_capture_monitor(&mut self, captured: bool) {
+        // Replayed frames are expected to run out
+        if matches!(self.settings.capture_mode, CaptureMode::Replay)
+            || self.settings.capture_recovery_timeout_millis == 0
         {
-            if let ImageCaptureKind::BitBltArea(capture) = self.image_capture.kind() {
-                handle_or_default = capture.handle();
-                *self.key_receiver = KeyReceiver::new(handle_or_default, KeyInputKind::Foreground);
+            return;
+        }
+
+        let timeout = Duration::from_millis(self.settings.capture_recovery_timeout_millis);
+        let fallback_mode = fallback_capture_mode(self.settings.capture_mode).filter(|_| {
+            self.settings.capture_recovery_fallback
+                && self.selected_capture_area(self.settings).is_none()
+        });
+        let Some(recovery) =
+            self.capture_monitor
+                .update(captured, timeout, fallback_mode.is_some())
+        else {
+            return;
+        };
+
+        let details = match recovery {
+            CaptureRecovery::Reinitialize => {
+                info!(target: "capture", "no frame captured for {timeout:?}, recreating capture");
+                let settings = self.settings.clone();
+                self.update_image_capture(&settings);
+                None
             }
-            match settings.input_method {
-                InputMethod::Default => {
-                    let kind = if matches!(settings.capture_mode, CaptureMode::BitBltArea) {
-                        KeyInputKind::Foreground
-                    } else {
-                        KeyInputKind::Fixed
-                    };
-                    self.context
-                        .keys
-                        .set_method(KeySenderMethod::Default(handle_or_default, kind));
-                }
-                InputMethod::Rpc => {
-                    self.context.keys.set_method(KeySenderMethod::Rpc(
-                        handle_or_default,
-                        settings.input_method_rpc_server_url.clone(),
-                    ));
-                }
+            CaptureRecovery::Fallback => {
+                let mode = fallback_mode.expect("has fallback mode");
+                info!(target: "capture", "no frame captured, falling back to {mode}");
+                // The capture is recreated on the settings updated event
+                let mut settings = self.settings.clone();
+                settings.capture_mode = mode;
+                let _ = upsert_settings(&mut settings);
+                Some(format!("Capture mode changed to {mode}"))
             }
+        };
+        if self.capture_monitor.is_first_recovery() {
+            let _ = match details {
+                Some(details) => self
+                    .context
+                    .notification
+                    .schedule_notification_with_details(NotificationKind::CaptureLost, details),
+                None => self
+                    .context
+                    .notification
+                    .schedule_notification(NotificationKind::CaptureLost),
+            };
+        }
+    }
+
+    /// Resets the player and reloads the auto-mobbing data learned on the current minimap.
+    fn reset_player(&mut self) {
+        let data = self
+            .minimap_data
+            .as_ref()
+            .and_then(|minimap| minimap.id)
+            .and_then(query_auto_mob_data);
+        self.player.reset(data);
+    }
+
+    /// Persists the auto-mobbing data learned by the player on the current minimap.
+    fn save_auto_mob_data(&self) {
+        if let Some(id) = self.minimap_data.as_ref().and_then(|minimap| minimap.id) {
+            let _ = upsert_auto_mob_data(&mut self.player.auto_mob_data(id));
+        }
+    }
+
+    /// Stops all automation immediately regardless of the current state.
+    ///
+    /// Unlike [`Self::update_context_halting`], this does not require a minimap or character
+    /// to be selected and also releases all held down keys.
+    fn halt_by_panic_key(&mut self) {
+        self.session_tracker
+            .set_stop_reason(SessionStopReason::PanicKey);
+        self.context.operation = Operation::Halting;
+        self.context.player = Player::Idle;
+        self.rotator.reset_queue();
+        self.player.clear_actions_aborted(true);
+        let _ = self.context.keys.release_all();
+        *self.panic_key_triggered_at = Some(Instant::now());
+    }
+
+    /// Gets the area of the selected handle in [`Settings::capture_areas`] if `settings` is in
+    /// [`CaptureMode::BitBltArea`].
+    fn selected_capture_area(&self, settings: &Settings) -> Option<Bound> {
+        if !matches!(settings.capture_mode, CaptureMode::BitBltArea) {
+            return None;
+        }
+        let selected_handle = (*self.selected_capture_handle)?;
+        let (name, _) = self
+            .capture_handles
+            .iter()
+            .find(|(_, handle)| *handle == selected_handle)?;
+
+        settings.capture_areas.get(name).copied()
+    }
+
+    fn update_image_capture(&mut self, settings: &Settings) {
+        let handle_or_default = self.selected_capture_handle.unwrap_or(self.context.handle);
+        if let Some(area) = self.selected_capture_area(settings) {
+            self.image_capture.set_area(handle_or_default, area);
+        } else {
+            self.image_capture.set_mode(
+                handle_or_default,
+                settings.capture_mode,
+                &settings.capture_stream_url,
+            );
+        }
+    }
+
+    fn update_key_input(&mut self, settings: &Settings) {
+        let (handle, kind) =
+            if let ImageCaptureKind::BitBltArea(capture) = self.image_capture.kind() {
+                (capture.handle(), KeyInputKind::Foreground)
+            } else {
+                (
+                    self.selected_capture_handle.unwrap_or(self.context.handle),
+                    KeyInputKind::Fixed,
+                )
+            };
+
+        *self.key_receiver = KeyReceiver::new(handle, kind);
+        self.context
+            .keys
+            .set_method(KeySenderMethod::from_settings(settings, handle, kind));
+    }
+
+    /// Guards inputs with the selected game window if [`Settings::input_focus_guard`] is enabled.
+    fn update_focus_guard(&mut self, settings: &Settings) {
+        let handle = settings
+            .input_focus_guard
+            .then_some(self.selected_capture_handle.unwrap_or(self.context.handle));
+        if let Some(keys) = self
+            .context
+            .keys
+            .as_any_mut()
+            .downcast_mut::<DefaultKeySender>()
+        {
+            keys.set_focus_guard(handle);
+        }
+    }
+
+    /// Sets how keys are pressed from the current character's [`Character::input_humanization`]
+    /// or [`Settings::input_humanization`] if not overridden.
+    fn update_input_humanization(&mut self) {
+        let humanization = self
+            .character
+            .as_ref()
+            .and_then(|character| character.input_humanization.clone())
+            .unwrap_or_else(|| self.settings.input_humanization.clone());
+        if let Some(keys) = self
+            .context
+            .keys
+            .as_any_mut()
+            .downcast_mut::<DefaultKeySender>()
+        {
+            keys.set_humanization(humanization);
+        }
+    }
+
+    fn update_settings(&mut self, settings: Settings) {
+        let fps_changed = settings.fps != self.settings.fps;
+        if fps_changed {
+            set_fps(settings.fps);
+        }
+        // Some captures are created with the tick duration as the minimum frame interval
+        let capture_changed = fps_changed
+            || settings.capture_mode != self.settings.capture_mode
+            || (matches!(settings.capture_mode, CaptureMode::Stream)
+                && settings.capture_stream_url != self.settings.capture_stream_url)
+            || self.selected_capture_area(&settings) != self.selected_capture_area(self.settings);
+        if capture_changed {
+            self.update_image_capture(&settings);
+        }
+
+        if capture_changed
+            || settings.input_method != self.settings.input_method
+            || settings.input_method_rpc_server_url != self.settings.input_method_rpc_server_url
+            || settings.input_method_serial_port != self.settings.input_method_serial_port
+            || settings.input_method_serial_baud_rate != self.settings.input_method_serial_baud_rate
+            || settings.input_method_kmbox_address != self.settings.input_method_kmbox_address
+            || settings.input_method_kmbox_uuid != self.settings.input_method_kmbox_uuid
+        {
+            self.update_key_input(&settings);
+        }
+        if settings.input_focus_guard != self.settings.input_focus_guard {
+            self.update_focus_guard(&settings);
         }
         self.context.operation = match self.context.operation {
             Operation::HaltUntil(_) => {
                 if settings.cycle_run_stop {
                     Operation::HaltUntil(
-                        Instant::now() + Duration::from_millis(settings.cycle_stop_duration_millis),
+                        Instant::now()
+                            + cycle_duration(
+                                &self.context.rng,
+                                settings.cycle_stop_duration_millis,
+                                settings.cycle_stop_duration_millis_random_range,
+                            ),
                     )
                 } else {
                     Operation::Halting
                 }
             }
             Operation::Halting => Operation::Halting,
+            Operation::Paused(_) => Operation::Paused(settings.cycle_run_stop.then(|| {
+                cycle_duration(
+                    &self.context.rng,
+                    settings.cycle_run_duration_millis,
+                    settings.cycle_run_duration_millis_random_range,
+                )
+            })),
             Operation::Running | Operation::RunUntil(_) => {
                 if settings.cycle_run_stop {
                     Operation::RunUntil(
-                        Instant::now() + Duration::from_millis(settings.cycle_run_duration_millis),
+                        Instant::now()
+                            + cycle_duration(
+                                &self.context.rng,
+                                settings.cycle_run_duration_millis,
+                                settings.cycle_run_duration_millis_random_range,
+                            ),
                     )
                 } else {
                     Operation::Running
                 }
             }
         };
+        if settings.global_toggle_actions_hotkey != self.settings.global_toggle_actions_hotkey
+            || settings.global_pause_actions_hotkey != self.settings.global_pause_actions_hotkey
+            || settings.global_emergency_halt_hotkey != self.settings.global_emergency_halt_hotkey
+        {
+            register_global_hotkeys(&settings);
+        }
+        if settings.models != self.settings.models {
+            let _ = update_models(&settings.models, false);
+        }
+        if settings.detection_scale != self.settings.detection_scale {
+            set_detection_scale(settings.detection_scale);
+        }
+        if settings.current_channel != self.settings.current_channel {
+            self.player
+                .set_channel(Some(settings.current_channel).filter(|channel| *channel > 0));
+        }
+        self.remote_control.update(&settings);
+        self.control_server.update(&settings);
+        let humanization_changed = settings.input_humanization != self.settings.input_humanization;
         *self.settings = settings;
+        if humanization_changed {
+            self.update_input_humanization();
+        }
+        self.minimap.set_ignored_other_players(
+            self.settings.ignore_guildie_players,
+            self.settings.ignore_friend_players,
+        );
+        self.player.config.rune_fallback_enlarged_crop = self.settings.rune_fallback_enlarged_crop;
+        self.player.config.rune_fallback_halt = self.settings.rune_fallback_halt;
+        self.player.config.rune_fallback_remote = self.settings.rune_fallback_remote;
+        self.player.config.rune_verify_frame_count = self.settings.rune_verify_frame_count;
+        self.player.config.maintenance_halt = self.settings.enable_maintenance_halt;
+        self.player.config.maintenance_halt_margin_millis =
+            self.settings.maintenance_halt_margin_millis;
+        self.player.config.action_position_max_platform_distance =
+            self.settings.action_position_max_platform_distance;
+        self.player.config.priority_action_preemption = self.settings.priority_action_preemption;
+        self.player.config.unstuck_strategies_mid_air = self
+            .settings
+            .unstuck_strategies_mid_air
+            .iter()
+            .copied()
+            .take(MAX_UNSTUCK_STRATEGIES)
+            .collect();
+        self.player.config.unstuck_strategies_on_ground = self
+            .settings
+            .unstuck_strategies_on_ground
+            .iter()
+            .copied()
+            .take(MAX_UNSTUCK_STRATEGIES)
+            .collect();
+        self.player.config.channel_selection = self.settings.channel_selection;
+        self.player.config.channel_count = self.settings.channel_count;
+        self.player.config.channel_blacklist = self
+            .settings
+            .channel_blacklist
+            .iter()
+            .copied()
+            .take(MAX_CHANNEL_COUNT)
+            .collect();
+        self.player.config.channel_whitelist = self
+            .settings
+            .channel_whitelist
+            .iter()
+            .copied()
+            .take(MAX_CHANNEL_COUNT)
+            .collect();
 
         let Some(character) = self.character else {
             return;
         };
+        if fps_changed {
+            self.player.config.double_jump_key_delay_ticks =
+                millis_to_ticks(character.double_jump_key_delay_millis);
+        }
         self.buff_states.iter_mut().for_each(|state| {
             state.update_enabled_state(character, self.settings);
         });
+        // Also converts the actions milliseconds to ticks again if the tick rate changed
         self.update_rotator_actions();
     }
 }
@@ -303,6 +808,10 @@ impl RequestHandler for DefaultRequestHandler<'_> {
         self.update_context_halting(halting, true);
     }
 
+    fn on_pause_actions(&mut self, paused: bool) {
+        self.update_context_paused(paused);
+    }
+
     fn on_create_minimap(&self, name: String) -> Option<MinimapData> {
         if let Minimap::Idle(idle) = self.context.minimap {
             Some(MinimapData {
@@ -317,6 +826,7 @@ impl RequestHandler for DefaultRequestHandler<'_> {
     }
 
     fn on_update_minimap(&mut self, preset: Option<String>, minimap: Option<MinimapData>) {
+        self.save_auto_mob_data();
         *self.minimap_data = minimap;
         *self.minimap_data_preset = preset.clone();
         self.minimap.set_platforms(
@@ -331,7 +841,19 @@ impl RequestHandler for DefaultRequestHandler<'_> {
                 })
                 .unwrap_or_default(),
         );
-        self.player.reset();
+        self.minimap.set_portals(
+            self.minimap_data
+                .as_ref()
+                .map(|data| {
+                    data.portals
+                        .iter()
+                        .copied()
+                        .map(Rect::from)
+                        .collect::<Vec<_>>()
+                })
+                .unwrap_or_default(),
+        );
+        self.reset_player();
 
         let Some(minimap) = self.minimap_data.as_ref() else {
             *self.actions = Vec::new();
@@ -346,6 +868,11 @@ impl RequestHandler for DefaultRequestHandler<'_> {
         self.player.config.auto_mob_platforms_pathing_up_jump_only =
             minimap.auto_mob_platforms_pathing_up_jump_only;
         self.player.config.auto_mob_platforms_bound = minimap.auto_mob_platforms_bound;
+        self.player.config.auto_mob_density_pathing = minimap.rotation_auto_mob_density_pathing;
+        self.player.config.mobbing_damage_check = minimap.rotation_mobbing_damage_check;
+        self.player.config.home_platforms_pathing = minimap.home_platforms_pathing;
+        self.player.config.home_platforms_pathing_up_jump_only =
+            minimap.home_platforms_pathing_up_jump_only;
         *self.actions = preset
             .and_then(|preset| minimap.actions.get(&preset).cloned())
             .unwrap_or_default();
@@ -353,21 +880,9 @@ impl RequestHandler for DefaultRequestHandler<'_> {
         self.update_rotator_actions();
     }
 
+    #[inline]
     fn on_create_navigation_path(&self) -> Option<NavigationPath> {
-        if let Some((minimap_base64, name_base64, name_bbox)) =
-            extract_minimap_and_name_base64(self.context)
-        {
-            Some(NavigationPath {
-                id: None,
-                minimap_snapshot_base64: minimap_base64,
-                name_snapshot_base64: name_base64,
-                name_snapshot_width: name_bbox.width,
-                name_snapshot_height: name_bbox.height,
-                points: vec![],
-            })
-        } else {
-            None
-        }
+        create_navigation_path(self.context)
     }
 
     fn on_recapture_navigation_path(&self, mut path: NavigationPath) -> NavigationPath {
@@ -385,19 +900,38 @@ impl RequestHandler for DefaultRequestHandler<'_> {
 
     fn on_update_character(&mut self, character: Option<Character>) {
         *self.character = character;
+        if let Some(keys) = self
+            .context
+            .keys
+            .as_any_mut()
+            .downcast_mut::<DefaultKeySender>()
+        {
+            let remaps = self
+                .character
+                .as_ref()
+                .map(|character| character.key_remaps.as_slice())
+                .unwrap_or_default();
+            keys.set_key_remaps(remaps);
+        }
+        self.update_input_humanization();
 
         let Some(character) = self.character else {
             return;
         };
         *self.buffs = config_buffs(character);
-        self.player.reset();
+        self.save_auto_mob_data();
+        self.reset_player();
         self.player.config.class = character.class;
         self.player.config.disable_adjusting = character.disable_adjusting;
+        self.player.config.double_jump_key_timing = character.double_jump_key_timing;
+        self.player.config.double_jump_key_delay_ticks =
+            millis_to_ticks(character.double_jump_key_delay_millis);
         self.player.config.interact_key = character.interact_key.key.into();
         self.player.config.grappling_key = character.ropelift_key.map(|key| key.key.into());
         self.player.config.teleport_key = character.teleport_key.map(|key| key.key.into());
         self.player.config.jump_key = character.jump_key.key.into();
         self.player.config.upjump_key = character.up_jump_key.map(|key| key.key.into());
+        self.player.config.up_jump_style = character.up_jump_style;
         self.player.config.cash_shop_key = character.cash_shop_key.key.into();
         self.player.config.familiar_key = character.familiar_menu_key.key.into();
         self.player.config.to_town_key = character.to_town_key.key.into();
@@ -409,6 +943,12 @@ impl RequestHandler for DefaultRequestHandler<'_> {
                 (_, PotionMode::Percentage(percent)) => Some(percent / 100.0),
             };
         self.player.config.update_health_millis = Some(character.health_update_millis);
+        self.player.config.emergency_key = character.emergency_key.key.into();
+        self.player.config.use_emergency_key_below_percent = character
+            .emergency_key
+            .enabled
+            .then_some(character.emergency_health_percent / 100.0);
+        self.player.config.potion_no_effect_halt = character.halt_on_potion_no_effect;
         self.buff_states.iter_mut().for_each(|state| {
             state.update_enabled_state(character, self.settings);
         });
@@ -420,6 +960,80 @@ impl RequestHandler for DefaultRequestHandler<'_> {
         self.context.minimap = Minimap::Detecting;
     }
 
+    fn on_test_movement(&mut self, start: bool) {
+        if self.movement_tester.is_testing() {
+            self.player.reset_normal_action();
+            self.movement_tester.stop();
+        }
+        if start
+            && self.context.operation.halting()
+            && let Minimap::Idle(idle) = self.context.minimap
+        {
+            self.movement_tester.start(&idle.platforms);
+        }
+    }
+
+    fn on_record_platforms(&mut self, start: bool) {
+        if start {
+            self.platform_recorder.start();
+        } else {
+            self.platform_recorder.stop();
+        }
+    }
+
+    #[inline]
+    fn on_clear_recorded_platforms(&mut self) {
+        self.platform_recorder.clear();
+    }
+
+    fn on_detect_platforms(&mut self) {
+        let Minimap::Idle(idle) = self.context.minimap else {
+            return;
+        };
+        let Some(detector) = self.context.detector.as_ref() else {
+            return;
+        };
+
+        let platforms = detector
+            .detect_minimap_platforms(idle.bbox)
+            .into_iter()
+            .map(|platform| PlatformData {
+                x_start: platform.x,
+                x_end: platform.x + platform.width - 1,
+                y: idle.bbox.height - platform.y,
+                ..PlatformData::default()
+            });
+        self.platform_recorder.add_platforms(platforms);
+    }
+
+    fn on_record_navigation(&mut self, start: bool) {
+        if start {
+            self.navigation_recorder.start();
+            return;
+        }
+
+        // Saves from the last visited path so each path can link to the next path id
+        let mut next_path_id = None;
+        for mut path in self.navigation_recorder.stop().into_iter().rev() {
+            if let Some(point) = path.points.last_mut() {
+                point.next_path_id = next_path_id;
+            }
+            if upsert_navigation_path(&mut path).is_err() {
+                break;
+            }
+            next_path_id = path.id;
+        }
+    }
+
+    fn on_record_key_macro(&mut self, start: bool) -> Vec<Action> {
+        if start {
+            self.key_macro_recorder.start();
+            vec![]
+        } else {
+            self.key_macro_recorder.stop()
+        }
+    }
+
     #[inline]
     fn on_game_state_receiver(&self) -> broadcast::Receiver<GameState> {
         GAME_STATE.subscribe()
@@ -452,33 +1066,110 @@ impl RequestHandler for DefaultRequestHandler<'_> {
     }
 
     fn on_select_capture_handle(&mut self, index: Option<usize>) {
-        if matches!(self.settings.capture_mode, CaptureMode::BitBltArea) {
-            return;
-        }
-
         let handle = index
             .and_then(|index| self.capture_handles.get(index))
             .map(|(_, handle)| *handle);
-        let handle_or_default = handle.unwrap_or(self.context.handle);
+        let had_area = self.selected_capture_area(self.settings).is_some();
 
         *self.selected_capture_handle = handle;
-        self.image_capture
-            .set_mode(handle_or_default, self.settings.capture_mode);
-        *self.key_receiver = KeyReceiver::new(handle_or_default, KeyInputKind::Fixed);
-        match self.settings.input_method {
-            InputMethod::Default => {
-                self.context.keys.set_method(KeySenderMethod::Default(
-                    handle_or_default,
-                    KeyInputKind::Fixed,
-                ));
-            }
-            InputMethod::Rpc => {
-                self.context.keys.set_method(KeySenderMethod::Rpc(
-                    handle_or_default,
-                    self.settings.input_method_rpc_server_url.clone(),
-                ));
-            }
+        let settings = self.settings.clone();
+        self.update_focus_guard(&settings);
+        // Keeps the capture area window unless an area is saved for either handle
+        if matches!(self.settings.capture_mode, CaptureMode::BitBltArea)
+            && !had_area
+            && self.selected_capture_area(self.settings).is_none()
+        {
+            return;
+        }
+
+        self.update_image_capture(&settings);
+        self.update_key_input(&settings);
+    }
+
+    fn on_query_capture_frame(&mut self) -> Option<(String, i32, i32)> {
+        let frame = self.image_capture.grab()?;
+        let (width, height) = (frame.width, frame.height);
+        let mat = OwnedMat::new_from_frame(frame);
+        let mut bytes = Vector::new();
+        imencode_def(".png", &mat, &mut bytes).ok()?;
+
+        Some((BASE64_STANDARD.encode(bytes), width, height))
+    }
+
+    fn on_query_capture_window_frame(&mut self) -> Option<(String, i32, i32)> {
+        let handle = self.selected_capture_handle.unwrap_or(self.context.handle);
+        let frame = BitBltCapture::new(handle, true).grab().ok()?;
+        let (width, height) = (frame.width, frame.height);
+        let mat = OwnedMat::new_from_frame(frame);
+        let mut bytes = Vector::new();
+        imencode_def(".png", &mat, &mut bytes).ok()?;
+
+        Some((BASE64_STANDARD.encode(bytes), width, height))
+    }
+
+    fn on_query_detection_frame(&self) -> Option<String> {
+        let detector = self.context.detector.as_ref()?;
+        let mat = draw_detection_overlay(detector.as_ref(), self.context.minimap);
+        let mut bytes = Vector::new();
+        imencode_def(".png", &mat, &mut bytes).ok()?;
+
+        Some(BASE64_STANDARD.encode(bytes))
+    }
+
+    fn on_query_tick_stats(&self) -> TickStats {
+        self.tick_profiler.stats()
+    }
+
+    fn on_query_checkpoint(&self) -> Option<Checkpoint> {
+        self.checkpoint_tracker.resumable().cloned()
+    }
+
+    fn on_reload_models(&self) -> bool {
+        update_models(&self.settings.models, true).is_ok()
+    }
+
+    fn on_resume_checkpoint(&mut self, resume: bool) {
+        let Some(checkpoint) = self.checkpoint_tracker.take_resumable() else {
+            return;
+        };
+        if !resume || self.minimap_data.is_none() || self.character.is_none() {
+            return;
         }
+
+        self.rotator.set_normal_position(
+            checkpoint.normal_action_index,
+            checkpoint.normal_actions_backward,
+        );
+        self.context.operation = match checkpoint
+            .cycle_remaining_millis
+            .filter(|_| self.settings.cycle_run_stop)
+        {
+            Some(millis) => {
+                let instant = Instant::now() + Duration::from_millis(millis);
+                if checkpoint.cycle_stopping {
+                    Operation::HaltUntil(instant)
+                } else {
+                    Operation::RunUntil(instant)
+                }
+            }
+            None => Operation::Running,
+        };
+        debug!(target: "checkpoint", "resumed from checkpoint {checkpoint:?}");
+    }
+
+    fn on_query_current_session_stats(&self) -> Option<SessionStats> {
+        self.session_tracker.current_stats()
+    }
+
+    fn on_query_input_events(&self) -> Vec<InputEvent> {
+        self.input_audit_log.events()
+    }
+
+    fn on_dump_input_events(&self) -> Option<String> {
+        self.input_audit_log
+            .dump()
+            .ok()
+            .map(|path| path.to_string_lossy().into_owned())
     }
 
     #[cfg(debug_assertions)]
@@ -551,23 +1242,288 @@ impl RequestHandler for DefaultRequestHandler<'_> {
             }
         }
     }
+
+    #[cfg(debug_assertions)]
+    fn on_player_state_snapshot(&self) -> PlayerStateSnapshot {
+        self.player.debug_snapshot(self.context.player)
+    }
 }
 
 // TODO: should only handle a single matched key binding
 #[inline]
 fn poll_key(handler: &mut DefaultRequestHandler) {
-    let Some(received_key) = handler.key_receiver.try_recv() else {
+    let Some((received_key, can_process)) = handler.key_receiver.try_recv_with_foreground() else {
         return;
     };
+    // Panic key works in any window
+    if let KeyBindingConfiguration { key, enabled: true } = handler.settings.panic_key
+        && KeyKind::from(key) == received_key
+    {
+        debug!(target: "handler", "received panic key {received_key:?}");
+        handler.halt_by_panic_key();
+        return;
+    }
+    if !can_process {
+        return;
+    }
     debug!(target: "handler", "received key {received_key:?}");
     if let KeyBindingConfiguration { key, enabled: true } = handler.settings.toggle_actions_key
         && KeyKind::from(key) == received_key
     {
         handler.on_rotate_actions(!handler.context.operation.halting());
     }
+    if let KeyBindingConfiguration { key, enabled: true } = handler.settings.pause_actions_key
+        && KeyKind::from(key) == received_key
+    {
+        handler.on_pause_actions(!handler.context.operation.paused());
+    }
+    handler.navigation_recorder.record_key(received_key.into());
+    handler.key_macro_recorder.record_key(received_key.into());
     let _ = handler.key_sender.send(received_key.into());
 }
 
+#[inline]
+fn poll_hotkey(handler: &mut DefaultRequestHandler) {
+    let Some(id) = handler.hotkey_receiver.try_recv() else {
+        return;
+    };
+    debug!(target: "handler", "received global hotkey {id}");
+    match id {
+        TOGGLE_ACTIONS_HOTKEY_ID => {
+            handler.on_rotate_actions(!handler.context.operation.halting());
+        }
+        PAUSE_ACTIONS_HOTKEY_ID => {
+            handler.on_pause_actions(!handler.context.operation.paused());
+        }
+        EMERGENCY_HALT_HOTKEY_ID => handler.halt_by_panic_key(),
+        _ => (),
+    }
+}
+
+#[inline]
+fn poll_remote_command(handler: &mut DefaultRequestHandler) {
+    let Some(command) = handler.remote_control.try_recv() else {
+        return;
+    };
+    debug!(target: "handler", "received remote command {command:?}");
+    let reply = match command {
+        RemoteCommand::Help => RemoteCommand::help().to_string(),
+        RemoteCommand::Status => remote_status(handler),
+        RemoteCommand::Screenshot => {
+            let frame = to_png(
+                handler
+                    .context
+                    .detector
+                    .as_ref()
+                    .map(|detector| detector.mat()),
+            );
+            let reply = if frame.is_some() {
+                "Game snapshot"
+            } else {
+                "The game is not captured"
+            };
+            handler.remote_control.reply(reply, frame);
+            return;
+        }
+        RemoteCommand::Pause => {
+            handler.on_pause_actions(true);
+            format!("Actions are {}", handler.context.operation)
+        }
+        RemoteCommand::Resume => {
+            handler.on_pause_actions(false);
+            format!("Actions are {}", handler.context.operation)
+        }
+        RemoteCommand::ChangeChannel => {
+            if handler.context.operation.halting() || handler.context.operation.paused() {
+                "Actions must be running to change channel".to_string()
+            } else {
+                handler.rotator.reset_queue();
+                handler.player.clear_actions_aborted(false);
+                handler.context.player = Player::Panicking(Panicking::new(PanicTo::Channel));
+                "Changing channel".to_string()
+            }
+        }
+        RemoteCommand::Stop => {
+            handler
+                .session_tracker
+                .set_stop_reason(SessionStopReason::DiscordCommand);
+            handler.on_rotate_actions(true);
+            format!("Actions are {}", handler.context.operation)
+        }
+        RemoteCommand::RuneArrows(Some(keys)) => match type_remote_rune_arrows(handler, keys) {
+            Ok(()) => "Typing the rune arrows".to_string(),
+            Err(err) => err,
+        },
+        RemoteCommand::RuneArrows(None) => {
+            "Rune arrows must be four of U, D, L and R (e.g. `!rune ULDR`)".to_string()
+        }
+    };
+    handler.remote_control.reply(reply, None);
+}
+
+/// Types the rune arrows `keys` if [`Player::SolvingRune`] is waiting for them.
+fn type_remote_rune_arrows(
+    handler: &mut DefaultRequestHandler,
+    keys: [KeyKind; 4],
+) -> Result<(), String> {
+    if !matches!(
+        handler.context.player,
+        Player::SolvingRune(solving_rune) if solving_rune.is_waiting_remote_arrows()
+    ) {
+        return Err("The rune arrows are not being asked".to_string());
+    }
+    handler.player.set_remote_rune_arrows(keys);
+    Ok(())
+}
+
+fn remote_status(handler: &DefaultRequestHandler) -> String {
+    let map = handler
+        .minimap_data
+        .as_ref()
+        .map(|minimap| minimap.name.as_str())
+        .unwrap_or("None");
+    let preset = handler.minimap_data_preset.as_deref().unwrap_or("None");
+    let character = handler
+        .character
+        .as_ref()
+        .map(|character| character.name.as_str())
+        .unwrap_or("None");
+    let position = handler
+        .player
+        .last_known_pos
+        .map(|pos| format!("({}, {})", pos.x, pos.y))
+        .unwrap_or_else(|| "Unknown".to_string());
+
+    format!(
+        "Operation: {}\nState: {}\nMap: {map} ({preset})\nCharacter: {character}\nPosition: {position}",
+        handler.context.operation, handler.context.player
+    )
+}
+
+#[inline]
+fn poll_control_command(handler: &mut DefaultRequestHandler) {
+    let Some((command, reply)) = handler.control_server.try_recv() else {
+        return;
+    };
+    debug!(target: "handler", "received control command {command:?}");
+    let result = match command {
+        ControlCommand::Start => {
+            if handler.minimap_data.is_none() || handler.character.is_none() {
+                Err("A map and a character must be selected".to_string())
+            } else {
+                handler.on_rotate_actions(false);
+                Ok(())
+            }
+        }
+        ControlCommand::Halt => {
+            handler.on_rotate_actions(true);
+            Ok(())
+        }
+        ControlCommand::Pause => {
+            handler.on_pause_actions(true);
+            Ok(())
+        }
+        ControlCommand::Resume => {
+            handler.on_pause_actions(false);
+            Ok(())
+        }
+        ControlCommand::SelectPreset(preset) => match handler.minimap_data.clone() {
+            Some(minimap) if minimap.actions.contains_key(&preset) => {
+                handler.on_update_minimap(Some(preset), Some(minimap));
+                Ok(())
+            }
+            Some(_) => Err(format!("Preset {preset} not found in the current map")),
+            None => Err("A map must be selected".to_string()),
+        },
+        ControlCommand::RuneArrows(arrows) => match parse_rune_arrows(&arrows) {
+            Some(keys) => type_remote_rune_arrows(handler, keys),
+            None => Err(format!("Invalid rune arrows {arrows}")),
+        },
+    };
+    let _ = reply.send(result);
+}
+
+#[inline]
+fn update_control_server(handler: &mut DefaultRequestHandler) {
+    if handler.control_server.wants_status() {
+        let minimap = handler.minimap_data.as_ref();
+        handler.control_server.update_status(ControlStatus {
+            operation: handler.context.operation.to_string(),
+            state: handler.context.player.to_string(),
+            position: handler.player.last_known_pos.map(|pos| (pos.x, pos.y)),
+            health: handler.player.health(),
+            normal_action: handler.player.normal_action_name(),
+            priority_action: handler.player.priority_action_name(),
+            minimap: minimap.map(|minimap| minimap.name.clone()),
+            preset: handler.minimap_data_preset.clone(),
+            presets: minimap
+                .map(|minimap| minimap.actions.keys().cloned().collect())
+                .unwrap_or_default(),
+            character: handler
+                .character
+                .as_ref()
+                .map(|character| character.name.clone()),
+            session: handler.session_tracker.current_stats(),
+        });
+    }
+
+    let wants_frame = handler.control_server.wants_frame();
+    let wants_minimap = handler.control_server.wants_minimap();
+    if (wants_frame || wants_minimap)
+        && let Some(detector) = handler.context.detector.as_ref()
+    {
+        let mat = draw_detection_overlay(detector.as_ref(), handler.context.minimap);
+        let mut frame_bytes = Vector::new();
+        if wants_frame && imencode_def(".png", &mat, &mut frame_bytes).is_ok() {
+            handler.control_server.update_frame(frame_bytes.to_vec());
+        }
+        let mut minimap_bytes = Vector::new();
+        if wants_minimap
+            && let Minimap::Idle(idle) = handler.context.minimap
+            && let Ok(minimap) = mat.roi(idle.bbox)
+            && imencode_def(".png", &minimap, &mut minimap_bytes).is_ok()
+        {
+            handler
+                .control_server
+                .update_minimap(minimap_bytes.to_vec());
+        }
+    }
+}
+
+/// Registers the enabled system-wide hotkeys in `settings`, replacing the previous ones.
+pub fn register_global_hotkeys(settings: &Settings) {
+    let hotkeys = [
+        (
+            TOGGLE_ACTIONS_HOTKEY_ID,
+            settings.global_toggle_actions_hotkey,
+        ),
+        (
+            PAUSE_ACTIONS_HOTKEY_ID,
+            settings.global_pause_actions_hotkey,
+        ),
+        (
+            EMERGENCY_HALT_HOTKEY_ID,
+            settings.global_emergency_halt_hotkey,
+        ),
+    ]
+    .into_iter()
+    .filter(|(_, hotkey)| hotkey.enabled)
+    .map(|(id, hotkey)| (id, to_hotkey(hotkey)))
+    .collect();
+
+    register_hotkeys(hotkeys);
+}
+
+#[inline]
+fn to_hotkey(hotkey: GlobalHotkey) -> Hotkey {
+    Hotkey {
+        key: hotkey.key.into(),
+        ctrl: hotkey.ctrl,
+        alt: hotkey.alt,
+        shift: hotkey.shift,
+    }
+}
+
 #[inline]
 fn poll_database_event(handler: &mut DefaultRequestHandler) {
     let Ok(event) = handler.database_event_receiver.try_recv() else {
@@ -585,6 +1541,8 @@ fn poll_database_event(handler: &mut DefaultRequestHandler) {
         }
         DatabaseEvent::MinimapDeleted(deleted_id) => {
             if Some(deleted_id) == handler.minimap_data.as_ref().and_then(|minimap| minimap.id) {
+                // Clears first so the deleted minimap auto-mobbing data is not saved again
+                *handler.minimap_data = None;
                 handler.on_update_minimap(None, None);
             }
         }
@@ -605,6 +1563,7 @@ fn poll_database_event(handler: &mut DefaultRequestHandler) {
                 handler.on_update_character(Some(character));
             }
         }
+        DatabaseEvent::SessionStatsUpdated => (),
         DatabaseEvent::CharacterDeleted(deleted_id) => {
             let current_id = handler
                 .character
@@ -656,6 +1615,32 @@ fn poll_debug(handler: &mut DefaultRequestHandler) {
 }
 
 // TODO: Better way?
+/// Gets the capture mode to fall back to from `mode` when no frame is captured.
+///
+/// Only the modes capturing the game window fall back to each other.
+#[inline]
+fn fallback_capture_mode(mode: CaptureMode) -> Option<CaptureMode> {
+    match mode {
+        CaptureMode::BitBlt => Some(CaptureMode::WindowsGraphicsCapture),
+        CaptureMode::WindowsGraphicsCapture => Some(CaptureMode::DesktopDuplication),
+        CaptureMode::DesktopDuplication => Some(CaptureMode::BitBlt),
+        CaptureMode::BitBltArea | CaptureMode::Replay | CaptureMode::Stream => None,
+    }
+}
+
+fn create_navigation_path(context: &Context) -> Option<NavigationPath> {
+    let (minimap_base64, name_base64, name_bbox) = extract_minimap_and_name_base64(context)?;
+
+    Some(NavigationPath {
+        id: None,
+        minimap_snapshot_base64: minimap_base64,
+        name_snapshot_base64: name_base64,
+        name_snapshot_width: name_bbox.width,
+        name_snapshot_height: name_bbox.height,
+        points: vec![],
+    })
+}
+
 fn extract_minimap_and_name_base64(context: &Context) -> Option<(String, String, Rect)> {
     if let Minimap::Idle(idle) = context.minimap
         && let Some(detector) = context.detector.as_ref()
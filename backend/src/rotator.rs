@@ -1,34 +1,51 @@
 use std::{
     assert_matches::debug_assert_matches,
-    collections::{HashSet, VecDeque},
+    cell::Cell,
+    collections::{HashMap, HashSet, VecDeque},
     sync::atomic::{AtomicU32, Ordering},
-    time::Instant,
+    time::{Duration, Instant},
 };
 
-use anyhow::Result;
+use anyhow::{Result, anyhow};
 use log::debug;
 use opencv::core::{Point, Rect};
 use ordered_hash_map::OrderedHashMap;
 
 use crate::{
-    ActionKeyDirection, ActionKeyWith, Bound, FamiliarRarity, KeyBinding, MobbingKey, Position,
+    ActionAbortReason, ActionKeyDirection, ActionKeyWith, ActionStats, Bound, FamiliarRarity,
+    KeyBinding, MobbingCooldownKey, MobbingKey, Position, PriorityActionPreemption, QueuedAction,
     SwappableFamiliars,
     array::Array,
     buff::{Buff, BuffKind},
-    context::{Context, MS_PER_TICK},
-    database::{Action, ActionCondition, ActionKey, ActionMove, EliteBossBehavior},
+    context::{Context, millis_to_ticks},
+    database::{
+        Action, ActionCondition, ActionKey, ActionMove, EliteBossBehavior, InventoryFullBehavior,
+    },
     minimap::Minimap,
+    network::NotificationKind,
     player::{
-        GRAPPLING_THRESHOLD, PanicTo, PingPongDirection, Player, PlayerAction, PlayerActionAutoMob,
-        PlayerActionFamiliarsSwapping, PlayerActionKey, PlayerActionPanic, PlayerActionPingPong,
-        PlayerState, Quadrant,
+        AUTO_MOB_MAX_MOBS_COUNT, DOUBLE_JUMP_THRESHOLD, GRAPPLING_THRESHOLD, JUMP_THRESHOLD,
+        PanicTo, PingPongDirection, Player, PlayerAction, PlayerActionAutoMob,
+        PlayerActionCashShop, PlayerActionFamiliarsSwapping, PlayerActionKey, PlayerActionMove,
+        PlayerActionPanic, PlayerActionPingPong, PlayerState, Quadrant,
     },
     skill::{Skill, SkillKind},
-    task::{Task, Update, update_detection_task},
+    task::{Task, Update, update_detection_task, update_inference_task_with_budget},
 };
 
 const COOLDOWN_BETWEEN_QUEUE_MILLIS: u128 = 20_000;
+const FAMILIAR_ESSENCE_LOW_CHECK_MILLIS: u128 = 60_000;
+/// The minimum time between each queue of an [`ActionCondition::InventoryFull`] action so that
+/// the actions have time to free up the inventory.
+const INVENTORY_FULL_COOLDOWN_MILLIS: u128 = 60_000;
 const AUTO_MOB_SAME_QUAD_THRESHOLD: u32 = 5;
+/// The maximum duration of a mobs detection before the player position it is relative to is
+/// considered outdated.
+const AUTO_MOB_DETECTION_BUDGET: Duration = Duration::from_millis(500);
+/// The maximum duration to wait for the required buffs with keys to be casted before starting.
+const REQUIRED_BUFFS_CAST_TIMEOUT_MILLIS: u128 = 15_000;
+/// The delay between each boss HP bar detection in [`RotatorMode::Bossing`].
+const BOSS_DETECTION_DELAY_MILLIS: u64 = 1000;
 
 /// [`Condition`] evaluation result.
 enum ConditionResult {
@@ -74,6 +91,10 @@ struct PriorityAction {
     inner: RotatorAction,
     /// Whether to queue this action to the front of [`Rotator::priority_actions_queue`].
     queue_to_front: bool,
+    /// Whether this action is a burst member.
+    ///
+    /// Burst members are only queued together when all of them can be queued.
+    burst: bool,
     /// Whether this action is being ignored.
     ///
     /// While ignored, [`Self::last_queued_time`] will be updated to [`Instant::now`].
@@ -110,7 +131,10 @@ pub enum RotatorMode {
     #[default]
     StartToEndThenReverse,
     AutoMobbing(MobbingKey, Bound),
-    PingPong(MobbingKey, Bound),
+    /// Ping pong with the mobbing key, bound and whether to only walk.
+    PingPong(MobbingKey, Bound, bool),
+    /// Rotates the normal actions from start to end only while a boss HP bar is detected.
+    Bossing,
 }
 
 #[derive(Default, Debug)]
@@ -124,6 +148,14 @@ pub struct Rotator {
     normal_actions_backward: bool,
     normal_actions_reset_on_erda: bool,
     normal_rotate_mode: RotatorMode,
+    /// The [`MobbingCooldownKey`]s and their last used [`Instant`].
+    ///
+    /// Used in place of the [`RotatorMode`] mobbing key when one of them is off cooldown.
+    normal_mobbing_cooldown_keys: Vec<(MobbingCooldownKey, Option<Instant>)>,
+    /// The position to return to after each normal action or when there is no normal action.
+    normal_home_position: Option<Position>,
+    /// Whether the player should return to [`Self::normal_home_position`] on next rotation.
+    normal_home_pending: bool,
     /// The [`Task`] used when [`Self::normal_rotate_mode`] is [`RotatorMode::AutoMobbing`]
     auto_mob_task: Option<Task<Result<Vec<Point>>>>,
     /// Tracks number of times a mob detection has been completed inside the same quad.
@@ -131,6 +163,10 @@ pub struct Rotator {
     /// This limits the number of detections can be done inside the same quad as to help player
     /// advances to the next quad.
     auto_mob_quadrant_consecutive_count: Option<(Quadrant, u32)>,
+    /// The [`Task`] used when [`Self::normal_rotate_mode`] is [`RotatorMode::Bossing`]
+    boss_task: Option<Task<Result<()>>>,
+    /// Whether a boss HP bar was detected in the last boss detection.
+    boss_detected: bool,
     priority_actions: OrderedHashMap<u32, PriorityAction>,
     /// The currently executing [`RotatorAction::Linked`] action
     priority_queuing_linked_action: Option<(u32, Box<LinkedAction>)>,
@@ -138,22 +174,47 @@ pub struct Rotator {
     ///
     /// Populates from [`Self::priority_actions`] when its predicate for queuing is true
     priority_actions_queue: VecDeque<u32>,
+    /// Maps action ids to the index of the built [`Action`].
+    action_indices: HashMap<u32, usize>,
+    /// Statistics of the built [`Action`]s keyed by the action index.
+    action_stats: HashMap<usize, ActionStats>,
+    /// Buffs required before rotating the first normal action and whether each has a key to cast.
+    required_buffs: Vec<(BuffKind, bool)>,
+    /// The [`Instant`] the [`Self::required_buffs`] started being verified.
+    required_buffs_verifying_since: Option<Instant>,
+    /// Whether [`Self::required_buffs`] have been verified since the queue was last reset.
+    required_buffs_verified: bool,
+    /// The required buffs missing from the last verification.
+    missing_required_buffs: Vec<BuffKind>,
 }
 
 #[derive(Debug)]
 pub struct RotatorBuildArgs<'a> {
     pub mode: RotatorMode,
+    pub mobbing_cooldown_keys: &'a [MobbingCooldownKey],
     pub actions: &'a [Action],
+    pub home_position: Option<Position>,
     pub buffs: &'a [(BuffKind, KeyBinding)],
+    pub required_buffs: &'a [BuffKind],
     pub familiar_essence_key: KeyBinding,
     pub familiar_swappable_slots: SwappableFamiliars,
     pub familiar_swappable_rarities: &'a HashSet<FamiliarRarity>,
     pub familiar_swap_check_millis: u64,
+    pub familiar_essence_low_percentage: f32,
     pub elite_boss_behavior: Option<EliteBossBehavior>,
     pub elite_boss_behavior_key: KeyBinding,
+    pub inventory_full_behavior: InventoryFullBehavior,
     pub enable_panic_mode: bool,
+    pub enable_stranger_channel_hop: bool,
+    pub stranger_channel_hop_after_millis: u64,
+    pub stranger_channel_hop_cooldown_millis: u64,
+    pub enable_cash_shop_anti_idle: bool,
+    pub cash_shop_anti_idle_every_millis: u64,
+    pub cash_shop_anti_idle_stay_millis: u64,
     pub enable_rune_solving: bool,
+    pub enable_rune_solving_only_when_safe: bool,
     pub enable_familiars_swapping: bool,
+    pub enable_familiars_resummon_on_essence_low: bool,
     pub enable_reset_normal_actions_on_erda: bool,
 }
 
@@ -162,32 +223,62 @@ impl Rotator {
         debug!(target: "rotator", "preparing actions {args:?}");
         let RotatorBuildArgs {
             mode,
+            mobbing_cooldown_keys,
             actions,
+            home_position,
             buffs,
+            required_buffs,
             familiar_essence_key,
             familiar_swappable_slots,
             familiar_swappable_rarities,
             familiar_swap_check_millis,
+            familiar_essence_low_percentage,
             elite_boss_behavior,
             elite_boss_behavior_key,
+            inventory_full_behavior,
             enable_panic_mode,
+            enable_stranger_channel_hop,
+            stranger_channel_hop_after_millis,
+            stranger_channel_hop_cooldown_millis,
+            enable_cash_shop_anti_idle,
+            cash_shop_anti_idle_every_millis,
+            cash_shop_anti_idle_stay_millis,
             enable_rune_solving,
+            enable_rune_solving_only_when_safe,
             enable_familiars_swapping,
+            enable_familiars_resummon_on_essence_low,
             enable_reset_normal_actions_on_erda,
         } = args;
         self.reset_queue();
         self.normal_actions.clear();
         self.normal_rotate_mode = mode;
+        self.normal_mobbing_cooldown_keys = mobbing_cooldown_keys
+            .iter()
+            .map(|key| (*key, None))
+            .collect();
+        self.normal_home_position = home_position;
         self.normal_actions_reset_on_erda = enable_reset_normal_actions_on_erda;
         self.priority_actions.clear();
+        self.action_indices.clear();
+        self.action_stats.clear();
+        self.required_buffs = required_buffs
+            .iter()
+            .map(|required| (*required, buffs.iter().any(|(buff, _)| buff == required)))
+            .collect();
+        self.missing_required_buffs.clear();
 
         let mut i = 0;
         while i < actions.len() {
+            let index = i;
             let action = actions[i];
             let condition = action.condition();
-            let queue_to_front = match action {
-                Action::Move(_) => false,
-                Action::Key(ActionKey { queue_to_front, .. }) => queue_to_front.unwrap_or_default(),
+            let (queue_to_front, burst) = match action {
+                Action::Move(_) => (false, false),
+                Action::Key(ActionKey {
+                    queue_to_front,
+                    burst,
+                    ..
+                }) => (queue_to_front.unwrap_or_default() || burst, burst),
             };
             let (action, offset) = rotator_action(action, i, actions);
             debug_assert!(i != 0 || !matches!(condition, ActionCondition::Linked));
@@ -195,36 +286,57 @@ impl Rotator {
             // infinite loop due to auto mobbing ignoring Any condition
             i += offset;
             match condition {
-                ActionCondition::EveryMillis(_) | ActionCondition::ErdaShowerOffCooldown => {
+                ActionCondition::InventoryFull
+                    if !matches!(inventory_full_behavior, InventoryFullBehavior::RunActions) =>
+                {
+                    continue;
+                }
+                ActionCondition::EveryMillis(_)
+                | ActionCondition::ErdaShowerOffCooldown
+                | ActionCondition::InventoryFull => {
+                    let id = self.id_counter.fetch_add(1, Ordering::Relaxed);
+                    self.action_indices.insert(id, index);
                     self.priority_actions.insert(
-                        self.id_counter.fetch_add(1, Ordering::Relaxed),
-                        priority_action(action, condition, queue_to_front),
+                        id,
+                        PriorityAction {
+                            burst,
+                            ..priority_action(action, condition, queue_to_front)
+                        },
                     );
                 }
                 ActionCondition::Any => {
                     if matches!(self.normal_rotate_mode, RotatorMode::AutoMobbing(_, _)) {
                         continue;
                     }
-                    self.normal_actions
-                        .push((self.id_counter.fetch_add(1, Ordering::Relaxed), action))
+                    let id = self.id_counter.fetch_add(1, Ordering::Relaxed);
+                    self.action_indices.insert(id, index);
+                    self.normal_actions.push((id, action))
                 }
                 ActionCondition::Linked => unreachable!(),
             }
         }
 
-        if buffs
+        if let Some((_, familiar_buff_key)) = buffs
             .iter()
-            .any(|(buff, _)| matches!(buff, BuffKind::Familiar))
+            .find(|(buff, _)| matches!(buff, BuffKind::Familiar))
         {
             self.priority_actions.insert(
                 self.id_counter.fetch_add(1, Ordering::Relaxed),
                 familiar_essence_replenish_priority_action(familiar_essence_key),
             );
+            self.priority_actions.insert(
+                self.id_counter.fetch_add(1, Ordering::Relaxed),
+                familiar_essence_low_priority_action(
+                    *familiar_buff_key,
+                    familiar_essence_low_percentage / 100.0,
+                    enable_familiars_resummon_on_essence_low,
+                ),
+            );
         }
         if enable_rune_solving {
             self.priority_actions.insert(
                 self.id_counter.fetch_add(1, Ordering::Relaxed),
-                solve_rune_priority_action(),
+                solve_rune_priority_action(enable_rune_solving_only_when_safe),
             );
         }
         if let Some(behavior) = elite_boss_behavior {
@@ -266,6 +378,24 @@ impl Rotator {
                 panic_priority_action(),
             );
         }
+        if enable_stranger_channel_hop {
+            self.priority_actions.insert(
+                self.id_counter.fetch_add(1, Ordering::Relaxed),
+                stranger_channel_hop_priority_action(
+                    stranger_channel_hop_after_millis,
+                    stranger_channel_hop_cooldown_millis,
+                ),
+            );
+        }
+        if enable_cash_shop_anti_idle {
+            self.priority_actions.insert(
+                self.id_counter.fetch_add(1, Ordering::Relaxed),
+                cash_shop_anti_idle_priority_action(
+                    cash_shop_anti_idle_every_millis,
+                    cash_shop_anti_idle_stay_millis,
+                ),
+            );
+        }
         for (i, key) in buffs.iter().copied() {
             self.priority_actions.insert(
                 self.id_counter.fetch_add(1, Ordering::Relaxed),
@@ -281,35 +411,189 @@ impl Rotator {
         self.priority_actions_queue.clear();
         self.priority_queuing_linked_action = None;
         self.auto_mob_quadrant_consecutive_count = None;
+        self.boss_detected = false;
+        self.required_buffs_verifying_since = None;
+        self.required_buffs_verified = false;
+    }
+
+    /// The index of the next normal action and whether the actions are rotated backward.
+    #[inline]
+    pub fn normal_position(&self) -> (usize, bool) {
+        (self.normal_index, self.normal_actions_backward)
+    }
+
+    /// Restores the position in the normal actions previously returned by
+    /// [`Self::normal_position`].
+    ///
+    /// The position is ignored if it is out of range for the current normal actions.
+    pub fn set_normal_position(&mut self, index: usize, backward: bool) {
+        if index < self.normal_actions.len() {
+            self.normal_index = index;
+            self.normal_actions_backward = backward;
+        }
     }
 
     #[inline]
     fn reset_normal_actions_queue(&mut self) {
         self.normal_index = 0;
         self.normal_queuing_linked_action = None;
+        self.normal_home_pending = false;
+    }
+
+    /// The priority actions in [`Self::priority_actions_queue`] followed by the not yet queued
+    /// [`ActionCondition::EveryMillis`], [`ActionCondition::ErdaShowerOffCooldown`] and
+    /// [`ActionCondition::InventoryFull`] actions.
+    ///
+    /// The not yet queued actions are sorted by their estimated time until queued.
+    pub fn queued_actions(&self) -> Vec<QueuedAction> {
+        let queued_action = |id: u32, action: &PriorityAction, eta_millis: Option<u64>| {
+            let inner = match &action.inner {
+                RotatorAction::Single(inner) => inner,
+                RotatorAction::Linked(linked) => &linked.inner,
+            };
+            let name = match inner {
+                PlayerAction::Key(PlayerActionKey { key, .. }) => format!("Key({key})"),
+                inner => inner.to_string(),
+            };
+            QueuedAction {
+                id,
+                name,
+                condition: action.condition_kind,
+                eta_millis,
+            }
+        };
+
+        let mut queued = self
+            .priority_actions_queue
+            .iter()
+            .filter_map(|id| {
+                self.priority_actions
+                    .get(id)
+                    .map(|action| queued_action(*id, action, Some(0)))
+            })
+            .collect::<Vec<_>>();
+        let mut scheduled = self
+            .priority_actions
+            .iter()
+            .filter(|(id, _)| !self.priority_actions_queue.contains(*id))
+            .filter_map(|(id, action)| {
+                let eta_millis = match action.condition_kind? {
+                    ActionCondition::EveryMillis(millis) => {
+                        let elapsed = action
+                            .last_queued_time
+                            .map(|instant| instant.elapsed().as_millis() as u64)
+                            .unwrap_or(millis);
+                        Some(millis.saturating_sub(elapsed))
+                    }
+                    ActionCondition::ErdaShowerOffCooldown | ActionCondition::InventoryFull => None,
+                    ActionCondition::Any | ActionCondition::Linked => return None,
+                };
+                Some(queued_action(*id, action, eta_millis))
+            })
+            .collect::<Vec<_>>();
+        scheduled.sort_by_key(|action| action.eta_millis.unwrap_or(u64::MAX));
+        queued.extend(scheduled);
+
+        queued
+    }
+
+    /// Statistics of the built [`Action`]s keyed by the index in the built actions.
+    #[inline]
+    pub fn action_stats(&self) -> &HashMap<usize, ActionStats> {
+        &self.action_stats
+    }
+
+    /// The required buffs found missing when the actions were last started.
+    #[inline]
+    pub fn missing_required_buffs(&self) -> &[BuffKind] {
+        &self.missing_required_buffs
     }
 
     #[inline]
     pub fn rotate_action(&mut self, context: &Context, player: &mut PlayerState) {
-        if context.operation.halting() || matches!(context.player, Player::CashShopThenExit(_, _)) {
+        if let Some((id, reason)) = player.take_aborted_action()
+            && let Some(index) = self.action_indices.get(&id).copied()
+        {
+            let stats = self.action_stats.entry(index).or_default();
+            stats.abort_count += 1;
+            stats.last_abort_reason = Some(reason);
+            // Only notifies once per action because it will keep being skipped
+            if reason == ActionAbortReason::InvalidPosition && stats.abort_count == 1 {
+                let _ = context
+                    .notification
+                    .schedule_notification(NotificationKind::InvalidActionPosition);
+            }
+        }
+        if context.operation.halting()
+            || context.operation.paused()
+            || matches!(context.player, Player::CashShopThenExit(_, _))
+        {
             return;
         }
         self.rotate_priority_actions(context, player);
         self.rotate_priority_actions_queue(context, player);
-        if !player.has_priority_action() && !player.has_normal_action() {
+        if !player.has_priority_action()
+            && !player.has_normal_action()
+            && self.verify_required_buffs(context)
+            && !self.rotate_home(player)
+        {
             match self.normal_rotate_mode {
                 RotatorMode::StartToEnd => self.rotate_start_to_end(player),
                 RotatorMode::StartToEndThenReverse => self.rotate_start_to_end_then_reverse(player),
                 RotatorMode::AutoMobbing(key, bound) => {
                     self.rotate_auto_mobbing(context, player, key, bound)
                 }
-                RotatorMode::PingPong(key, bound) => {
-                    self.rotate_ping_pong(context, player, key, bound)
+                RotatorMode::PingPong(key, bound, walk_only) => {
+                    self.rotate_ping_pong(context, player, key, bound, walk_only)
                 }
+                RotatorMode::Bossing => self.rotate_bossing(context, player),
             }
         }
     }
 
+    /// Verifies the [`Self::required_buffs`] are active before rotating the first normal action.
+    ///
+    /// Waits up to [`REQUIRED_BUFFS_CAST_TIMEOUT_MILLIS`] for the required buffs with keys to be
+    /// casted by their priority actions. Afterward, notifies the missing required buffs if any.
+    ///
+    /// Returns `true` when normal actions can be rotated.
+    fn verify_required_buffs(&mut self, context: &Context) -> bool {
+        if self.required_buffs_verified {
+            return true;
+        }
+
+        let verifying_since = *self
+            .required_buffs_verifying_since
+            .get_or_insert_with(Instant::now);
+        let missing = self
+            .required_buffs
+            .iter()
+            .copied()
+            .filter(|(kind, _)| !matches!(context.buffs[*kind], Buff::Yes | Buff::Expiring))
+            .collect::<Vec<_>>();
+        if missing.iter().any(|(_, castable)| *castable)
+            && verifying_since.elapsed().as_millis() < REQUIRED_BUFFS_CAST_TIMEOUT_MILLIS
+        {
+            return false;
+        }
+
+        self.required_buffs_verified = true;
+        self.missing_required_buffs = missing.into_iter().map(|(kind, _)| kind).collect();
+        if !self.missing_required_buffs.is_empty() {
+            let checklist = self
+                .missing_required_buffs
+                .iter()
+                .map(|kind| format!("- {kind}"))
+                .collect::<Vec<_>>()
+                .join("\n");
+            let _ = context.notification.schedule_notification_with_details(
+                NotificationKind::RequiredBuffsMissing,
+                format!("Missing:\n{checklist}"),
+            );
+        }
+        true
+    }
+
     /// Rotates the actions inside the [`Self::priority_actions`]
     ///
     /// This function does not pass the action to the player but only pushes the action to
@@ -367,6 +651,9 @@ impl Rotator {
         let has_erda_action = has_erda_action_queuing_or_executing(self, player);
         let ids = self.priority_actions.keys().copied().collect::<Vec<_>>(); // why?
         let mut did_queue_erda_action = false;
+        // Burst members that can be queued and whether all burst members can be queued
+        let mut burst_ids = Vec::new();
+        let mut burst_ready = true;
 
         for id in ids {
             // Ignores for as long as the action is a linked action that is queuing
@@ -379,7 +666,10 @@ impl Rotator {
                 Some(ActionCondition::ErdaShowerOffCooldown) => {
                     has_erda_action || has_linked_action
                 }
-                Some(ActionCondition::Linked) | Some(ActionCondition::EveryMillis(_)) | None => {
+                Some(ActionCondition::Linked)
+                | Some(ActionCondition::EveryMillis(_))
+                | Some(ActionCondition::InventoryFull)
+                | None => {
                     player // The player currently executing action
                         .priority_action_id()
                         .is_some_and(|action_id| action_id == id)
@@ -393,10 +683,22 @@ impl Rotator {
             };
             if action.ignoring {
                 action.last_queued_time = Some(Instant::now());
+                burst_ready &= !action.burst;
                 continue;
             }
 
             let result = (action.condition.0)(context, player, action.last_queued_time);
+            if action.burst {
+                match result {
+                    ConditionResult::Queue => burst_ids.push(id),
+                    ConditionResult::Skip => burst_ready = false,
+                    ConditionResult::Ignore => {
+                        action.last_queued_time = Some(Instant::now());
+                        burst_ready = false;
+                    }
+                }
+                continue;
+            }
             match result {
                 ConditionResult::Queue => {
                     if action.queue_to_front {
@@ -419,6 +721,21 @@ impl Rotator {
             }
         }
 
+        if burst_ready {
+            // Queues in reverse so that the burst members are executed in the built order
+            for id in burst_ids.into_iter().rev() {
+                let action = self.priority_actions.get_mut(&id).expect("action id exist");
+                self.priority_actions_queue.push_front(id);
+                action.last_queued_time = Some(Instant::now());
+                if !did_queue_erda_action {
+                    did_queue_erda_action = matches!(
+                        action.condition_kind,
+                        Some(ActionCondition::ErdaShowerOffCooldown)
+                    );
+                }
+            }
+        }
+
         if did_queue_erda_action && self.normal_actions_reset_on_erda {
             self.reset_normal_actions_queue();
             player.reset_normal_action();
@@ -469,9 +786,7 @@ impl Rotator {
         if self.priority_actions_queue.is_empty() && self.priority_queuing_linked_action.is_none() {
             return;
         }
-        if !context
-            .player
-            .can_action_override_current_state(player.last_known_pos)
+        if !can_priority_action_override(context, player)
             || has_normal_linked_action_queuing_or_executing(self, player)
             || has_priority_linked_action_executing(self, player)
         {
@@ -531,6 +846,9 @@ impl Rotator {
         bound: Bound,
     ) {
         debug_assert!(!player.has_normal_action() && !player.has_priority_action());
+        if self.rotate_queuing_linked_action(player, false) {
+            return;
+        }
         let Minimap::Idle(idle) = context.minimap else {
             return;
         };
@@ -543,13 +861,18 @@ impl Rotator {
             bound.into()
         };
 
-        let Update::Ok(points) =
-            update_detection_task(context, 0, &mut self.auto_mob_task, move |detector| {
-                detector.detect_mobs(idle.bbox, bound, pos)
-            })
-        else {
+        let Update::Ok(points) = update_inference_task_with_budget(
+            context,
+            0,
+            AUTO_MOB_DETECTION_BUDGET,
+            &mut self.auto_mob_task,
+            move |detector| detector.detect_mobs(idle.bbox, bound, pos),
+        ) else {
             return;
         };
+        if player.config.auto_mob_density_pathing {
+            player.auto_mob_track_quadrant_mobs(bound, &points);
+        }
         // FIXME: Collect to a Vec first because `context.rng` needs to be borrowed again.
         let points = points
             .iter()
@@ -564,6 +887,7 @@ impl Rotator {
                 point.and_then(|point| player.auto_mob_pick_reachable_y_position(context, point))
             })
             .collect::<Vec<_>>();
+        let mobs = Array::from_iter(points.iter().copied().take(AUTO_MOB_MAX_MOBS_COUNT));
         let mut use_pathing_point = false;
 
         if let Some(last_quad) = player.auto_mob_last_quadrant()
@@ -595,12 +919,12 @@ impl Rotator {
                 .random_choose(points.into_iter())
                 .unwrap_or_else(|| player.auto_mob_pathing_point(context, bound))
         };
-        let wait_before_ticks = (key.wait_before_millis / MS_PER_TICK) as u32;
-        let wait_before_ticks_random_range =
-            (key.wait_before_millis_random_range / MS_PER_TICK) as u32;
-        let wait_after_ticks = (key.wait_after_millis / MS_PER_TICK) as u32;
-        let wait_after_ticks_random_range =
-            (key.wait_after_millis_random_range / MS_PER_TICK) as u32;
+        let key = self.next_mobbing_key(key);
+        let wait_before_ticks = millis_to_ticks(key.wait_before_millis);
+        let wait_before_ticks_random_range = millis_to_ticks(key.wait_before_millis_random_range);
+        let wait_after_ticks = millis_to_ticks(key.wait_after_millis);
+        let wait_after_ticks_random_range = millis_to_ticks(key.wait_after_millis_random_range);
+        let hold_ticks = key.hold_millis.map(millis_to_ticks);
         let position = Position {
             x: point.x,
             x_random_range: 0,
@@ -619,9 +943,12 @@ impl Rotator {
                 wait_before_ticks_random_range,
                 wait_after_ticks,
                 wait_after_ticks_random_range,
+                hold_ticks,
                 position,
+                mobs,
             }),
         );
+        self.queue_mobbing_linked_action(key);
     }
 
     fn rotate_ping_pong(
@@ -630,8 +957,12 @@ impl Rotator {
         player: &mut PlayerState,
         key: MobbingKey,
         bound: Bound,
+        walk_only: bool,
     ) {
         debug_assert!(!player.has_normal_action() && !player.has_priority_action());
+        if self.rotate_queuing_linked_action(player, false) {
+            return;
+        }
         let Minimap::Idle(idle) = context.minimap else {
             return;
         };
@@ -653,6 +984,7 @@ impl Rotator {
             bound.width,
             bound.height,
         );
+        let key = self.next_mobbing_key(key);
 
         player.set_normal_action(
             None,
@@ -661,16 +993,61 @@ impl Rotator {
                 link_key: key.link_key,
                 count: key.count.max(1),
                 with: key.with,
-                wait_before_ticks: (key.wait_before_millis / MS_PER_TICK) as u32,
-                wait_before_ticks_random_range: (key.wait_before_millis_random_range / MS_PER_TICK)
-                    as u32,
-                wait_after_ticks: (key.wait_after_millis / MS_PER_TICK) as u32,
-                wait_after_ticks_random_range: (key.wait_after_millis_random_range / MS_PER_TICK)
-                    as u32,
+                wait_before_ticks: millis_to_ticks(key.wait_before_millis),
+                wait_before_ticks_random_range: millis_to_ticks(
+                    key.wait_before_millis_random_range,
+                ),
+                wait_after_ticks: millis_to_ticks(key.wait_after_millis),
+                wait_after_ticks_random_range: millis_to_ticks(key.wait_after_millis_random_range),
+                hold_ticks: key.hold_millis.map(millis_to_ticks),
                 bound,
                 direction,
+                walk_only,
             }),
         );
+        self.queue_mobbing_linked_action(key);
+    }
+
+    /// Picks the first [`Self::normal_mobbing_cooldown_keys`] that is off cooldown or `key` if
+    /// there is none.
+    #[inline]
+    fn next_mobbing_key(&mut self, key: MobbingKey) -> MobbingKey {
+        self.normal_mobbing_cooldown_keys
+            .iter_mut()
+            .find(|(cooldown_key, last_used)| {
+                last_used.is_none_or(|instant| {
+                    instant.elapsed().as_millis() >= cooldown_key.cooldown_millis as u128
+                })
+            })
+            .map(|(cooldown_key, last_used)| {
+                *last_used = Some(Instant::now());
+                cooldown_key.key
+            })
+            .unwrap_or(key)
+    }
+
+    /// Queues the [`MobbingKey::linked_keys`] to be used after the current mobbing action.
+    #[inline]
+    fn queue_mobbing_linked_action(&mut self, key: MobbingKey) {
+        let linked_action =
+            key.linked_keys
+                .into_iter()
+                .flatten()
+                .rev()
+                .fold(None, |next, linked_key| {
+                    let action = ActionKey {
+                        key: linked_key.key,
+                        count: linked_key.count,
+                        wait_after_use_millis: linked_key.wait_after_millis,
+                        ..ActionKey::default()
+                    };
+                    Some(Box::new(LinkedAction {
+                        inner: Action::Key(action).into(),
+                        next,
+                    }))
+                });
+        self.normal_queuing_linked_action =
+            linked_action.map(|action| (self.id_counter.fetch_add(1, Ordering::Relaxed), action));
     }
 
     fn rotate_start_to_end(&mut self, player: &mut PlayerState) {
@@ -678,6 +1055,7 @@ impl Rotator {
         if self.normal_actions.is_empty() {
             return;
         }
+        self.normal_home_pending = self.normal_home_position.is_some();
         if self.rotate_queuing_linked_action(player, false) {
             return;
         }
@@ -695,11 +1073,39 @@ impl Rotator {
         }
     }
 
+    /// Rotates the normal actions from start to end while a boss HP bar is detected.
+    ///
+    /// The boss detection is only updated when there is no normal action so the current action
+    /// always finishes before the rotation stops.
+    fn rotate_bossing(&mut self, context: &Context, player: &mut PlayerState) {
+        debug_assert!(!player.has_normal_action() && !player.has_priority_action());
+        match update_detection_task(
+            context,
+            BOSS_DETECTION_DELAY_MILLIS,
+            &mut self.boss_task,
+            |detector| {
+                if detector.detect_boss_health_bar() {
+                    Ok(())
+                } else {
+                    Err(anyhow!("boss health bar not found"))
+                }
+            },
+        ) {
+            Update::Ok(_) => self.boss_detected = true,
+            Update::Err(_) => self.boss_detected = false,
+            Update::Pending => (),
+        }
+        if self.boss_detected {
+            self.rotate_start_to_end(player);
+        }
+    }
+
     fn rotate_start_to_end_then_reverse(&mut self, player: &mut PlayerState) {
         debug_assert!(!player.has_normal_action() && !player.has_priority_action());
         if self.normal_actions.is_empty() {
             return;
         }
+        self.normal_home_pending = self.normal_home_position.is_some();
         if self.rotate_queuing_linked_action(player, false) {
             return;
         }
@@ -731,6 +1137,53 @@ impl Rotator {
         }
     }
 
+    /// Rotates the move action to [`Self::normal_home_position`] if there is one.
+    ///
+    /// The player returns home after every normal action (but not in the middle of a linked
+    /// action) or when there is no normal action and the player is away from home.
+    ///
+    /// Returns `true` if the home move action was set.
+    fn rotate_home(&mut self, player: &mut PlayerState) -> bool {
+        debug_assert!(!player.has_normal_action() && !player.has_priority_action());
+        let Some(position) = self.normal_home_position else {
+            return false;
+        };
+        if !matches!(
+            self.normal_rotate_mode,
+            RotatorMode::StartToEnd | RotatorMode::StartToEndThenReverse | RotatorMode::Bossing
+        ) || self.normal_queuing_linked_action.is_some()
+        {
+            return false;
+        }
+
+        let should_return = if self.normal_home_pending {
+            true
+        } else if self.normal_actions.is_empty() {
+            player.last_known_pos.is_some_and(|pos| {
+                let x_distance = (pos.x - position.x).abs();
+                let y_distance = (pos.y - position.y).abs();
+                x_distance >= DOUBLE_JUMP_THRESHOLD + position.x_random_range
+                    || y_distance >= JUMP_THRESHOLD
+            })
+        } else {
+            false
+        };
+        if !should_return {
+            return false;
+        }
+
+        self.normal_home_pending = false;
+        player.set_normal_action(
+            None,
+            PlayerAction::Move(PlayerActionMove {
+                position,
+                wait_after_move_ticks: 0,
+                home: true,
+            }),
+        );
+        true
+    }
+
     #[inline]
     fn rotate_queuing_linked_action(
         &mut self,
@@ -822,7 +1275,9 @@ fn priority_action(
 ) -> PriorityAction {
     debug_assert_matches!(
         condition,
-        ActionCondition::EveryMillis(_) | ActionCondition::ErdaShowerOffCooldown
+        ActionCondition::EveryMillis(_)
+            | ActionCondition::ErdaShowerOffCooldown
+            | ActionCondition::InventoryFull
     );
     PriorityAction {
         inner: action,
@@ -835,6 +1290,7 @@ fn priority_action(
         })),
         condition_kind: Some(condition),
         queue_to_front,
+        burst: false,
         ignoring: false,
         last_queued_time: None,
     }
@@ -880,6 +1336,69 @@ fn familiar_essence_replenish_priority_action(key: KeyBinding) -> PriorityAction
             wait_after_use_ticks_random_range: 0,
         })),
         queue_to_front: true,
+        burst: false,
+        ignoring: false,
+        last_queued_time: None,
+    }
+}
+
+/// Creates a [`PlayerAction::Key`] priority action to unsummon and resummon familiars
+/// when the familiar essence gauge is detected as low.
+///
+/// The action will only be checked if:
+/// - Enough time has passed since the last check.
+/// - The familiar buff is currently active.
+///
+/// When the gauge ratio is below `low_ratio`, [`NotificationKind::FamiliarEssenceLow`] is
+/// scheduled and the action only queues if `resummon` is `true`. Otherwise, the action will be
+/// marked as [`ConditionResult::Ignore`].
+#[inline]
+fn familiar_essence_low_priority_action(
+    key: KeyBinding,
+    low_ratio: f32,
+    resummon: bool,
+) -> PriorityAction {
+    PriorityAction {
+        condition: Condition(Box::new(move |context, _, last_queued_time| {
+            if !at_least_millis_passed_since(last_queued_time, FAMILIAR_ESSENCE_LOW_CHECK_MILLIS) {
+                return ConditionResult::Skip;
+            }
+            if !matches!(context.buffs[BuffKind::Familiar], Buff::Yes) {
+                return ConditionResult::Skip;
+            }
+            let is_low = context
+                .detector_unwrap()
+                .detect_familiar_essence_gauge()
+                .is_ok_and(|ratio| ratio < low_ratio);
+            if !is_low {
+                return ConditionResult::Ignore;
+            }
+
+            let _ = context
+                .notification
+                .schedule_notification(NotificationKind::FamiliarEssenceLow);
+            if resummon {
+                ConditionResult::Queue
+            } else {
+                ConditionResult::Ignore
+            }
+        })),
+        condition_kind: None,
+        // Pressing the familiar buff key twice to unsummon and then resummon
+        inner: RotatorAction::Single(PlayerAction::Key(PlayerActionKey {
+            key,
+            link_key: None,
+            count: 2,
+            position: None,
+            direction: ActionKeyDirection::Any,
+            with: ActionKeyWith::Stationary,
+            wait_before_use_ticks: 5,
+            wait_before_use_ticks_random_range: 0,
+            wait_after_use_ticks: 10,
+            wait_after_use_ticks_random_range: 0,
+        })),
+        queue_to_front: true,
+        burst: false,
         ignoring: false,
         last_queued_time: None,
     }
@@ -893,10 +1412,11 @@ fn familiar_essence_replenish_priority_action(key: KeyBinding) -> PriorityAction
 /// - The minimap is in the [`Minimap::Idle`] state.
 /// - A rune is present on the minimap.
 /// - The player currently has no rune buff.
+/// - If `only_when_safe` is true, there is no other player or elite boss in the map.
 #[inline]
-fn solve_rune_priority_action() -> PriorityAction {
+fn solve_rune_priority_action(only_when_safe: bool) -> PriorityAction {
     PriorityAction {
-        condition: Condition(Box::new(|context, player, last_queued_time| {
+        condition: Condition(Box::new(move |context, player, last_queued_time| {
             if player.is_validating_rune() {
                 return ConditionResult::Skip;
             }
@@ -907,6 +1427,9 @@ fn solve_rune_priority_action() -> PriorityAction {
                 && idle.rune().is_some()
                 && matches!(context.buffs[BuffKind::Rune], Buff::No)
             {
+                if only_when_safe && (idle.has_any_other_player() || idle.has_elite_boss()) {
+                    return ConditionResult::Skip;
+                }
                 return ConditionResult::Queue;
             }
             ConditionResult::Skip
@@ -914,6 +1437,7 @@ fn solve_rune_priority_action() -> PriorityAction {
         condition_kind: None,
         inner: RotatorAction::Single(PlayerAction::SolveRune),
         queue_to_front: true,
+        burst: false,
         ignoring: false,
         last_queued_time: None,
     }
@@ -924,7 +1448,7 @@ fn solve_rune_priority_action() -> PriorityAction {
 /// The action queues if:
 /// - Enough time has passed since the last queue attempt.
 /// - The minimap is in the [`Minimap::Idle`] state.
-/// - The specified buff is currently missing.
+/// - The specified buff is currently missing or expiring.
 #[inline]
 fn buff_priority_action(buff: BuffKind, key: KeyBinding) -> PriorityAction {
     PriorityAction {
@@ -935,7 +1459,7 @@ fn buff_priority_action(buff: BuffKind, key: KeyBinding) -> PriorityAction {
             if !matches!(context.minimap, Minimap::Idle(_)) {
                 return ConditionResult::Skip;
             }
-            if matches!(context.buffs[buff], Buff::No) {
+            if matches!(context.buffs[buff], Buff::No | Buff::Expiring) {
                 ConditionResult::Queue
             } else {
                 ConditionResult::Skip
@@ -955,6 +1479,7 @@ fn buff_priority_action(buff: BuffKind, key: KeyBinding) -> PriorityAction {
             wait_after_use_ticks_random_range: 0,
         })),
         queue_to_front: true,
+        burst: false,
         ignoring: false,
         last_queued_time: None,
     }
@@ -983,6 +1508,70 @@ fn panic_priority_action() -> PriorityAction {
             to: PanicTo::Channel,
         })),
         queue_to_front: true,
+        burst: false,
+        ignoring: false,
+        last_queued_time: None,
+    }
+}
+
+/// Changes channel when a stranger stays in the map for at least `after_millis`.
+///
+/// Similar to panic mode, `last_queued_time` is kept updated while there is no stranger so it is
+/// the time the stranger appeared. Channel changes are at least `cooldown_millis` apart.
+#[inline]
+fn stranger_channel_hop_priority_action(after_millis: u64, cooldown_millis: u64) -> PriorityAction {
+    let last_hop_time = Cell::new(None);
+
+    PriorityAction {
+        condition: Condition(Box::new(move |context, _, last_queued_time| {
+            let Minimap::Idle(idle) = context.minimap else {
+                return ConditionResult::Skip;
+            };
+            if !idle.has_stranger_player() || last_queued_time.is_none() {
+                return ConditionResult::Ignore;
+            }
+            if !at_least_millis_passed_since(last_queued_time, after_millis as u128)
+                || !at_least_millis_passed_since(last_hop_time.get(), cooldown_millis as u128)
+            {
+                return ConditionResult::Skip;
+            }
+
+            last_hop_time.set(Some(Instant::now()));
+            ConditionResult::Queue
+        })),
+        condition_kind: None,
+        inner: RotatorAction::Single(PlayerAction::Panic(PlayerActionPanic {
+            to: PanicTo::Channel,
+        })),
+        queue_to_front: true,
+        burst: false,
+        ignoring: false,
+        last_queued_time: None,
+    }
+}
+
+/// Enters the cash shop for `stay_millis` every `every_millis` to avoid being idle.
+///
+/// The first entering is only after `every_millis` since the actions started.
+#[inline]
+fn cash_shop_anti_idle_priority_action(every_millis: u64, stay_millis: u64) -> PriorityAction {
+    PriorityAction {
+        condition: Condition(Box::new(move |_, _, last_queued_time| {
+            if last_queued_time.is_none() {
+                return ConditionResult::Ignore;
+            }
+            if at_least_millis_passed_since(last_queued_time, every_millis as u128) {
+                ConditionResult::Queue
+            } else {
+                ConditionResult::Skip
+            }
+        })),
+        condition_kind: None,
+        inner: RotatorAction::Single(PlayerAction::CashShop(PlayerActionCashShop {
+            stay_ticks: millis_to_ticks(stay_millis),
+        })),
+        queue_to_front: false,
+        burst: false,
         ignoring: false,
         last_queued_time: None,
     }
@@ -1008,6 +1597,7 @@ fn elite_boss_change_channel_priority_action() -> PriorityAction {
             to: PanicTo::Channel,
         })),
         queue_to_front: true,
+        burst: false,
         ignoring: false,
         last_queued_time: None,
     }
@@ -1042,11 +1632,37 @@ fn elite_boss_use_key_priority_action(key: KeyBinding) -> PriorityAction {
             wait_after_use_ticks_random_range: 0,
         })),
         queue_to_front: true,
+        burst: false,
         ignoring: false,
         last_queued_time: None,
     }
 }
 
+/// Whether a priority action can override the player's current state.
+///
+/// When the player is executing a normal action, the override also follows the configured
+/// [`PriorityActionPreemption`].
+#[inline]
+fn can_priority_action_override(context: &Context, player: &PlayerState) -> bool {
+    if !context
+        .player
+        .can_action_override_current_state(player.last_known_pos)
+    {
+        return false;
+    }
+    if !player.has_normal_action() || player.has_priority_action() {
+        return true;
+    }
+
+    match player.config.priority_action_preemption {
+        PriorityActionPreemption::Abort => true,
+        PriorityActionPreemption::FinishMovement => {
+            matches!(context.player, Player::Idle | Player::Detecting)
+        }
+        PriorityActionPreemption::FinishAction => false,
+    }
+}
+
 #[inline]
 fn at_least_millis_passed_since(last_queued_time: Option<Instant>, millis: u128) -> bool {
     last_queued_time
@@ -1063,17 +1679,21 @@ fn should_queue_fixed_action(
     let millis_should_passed = match condition {
         ActionCondition::EveryMillis(millis) => millis as u128,
         ActionCondition::ErdaShowerOffCooldown => COOLDOWN_BETWEEN_QUEUE_MILLIS,
+        ActionCondition::InventoryFull => INVENTORY_FULL_COOLDOWN_MILLIS,
         ActionCondition::Linked | ActionCondition::Any => unreachable!(),
     };
     if !at_least_millis_passed_since(last_queued_time, millis_should_passed) {
         return false;
     }
-    if matches!(condition, ActionCondition::ErdaShowerOffCooldown)
-        && !matches!(context.skills[SkillKind::ErdaShower], Skill::Idle(_, _))
-    {
-        return false;
+    match condition {
+        ActionCondition::ErdaShowerOffCooldown => {
+            matches!(context.skills[SkillKind::ErdaShower], Skill::Idle(_, _))
+        }
+        ActionCondition::InventoryFull => {
+            matches!(context.minimap, Minimap::Idle(idle) if idle.has_inventory_full())
+        }
+        _ => true,
     }
-    true
 }
 
 #[cfg(test)]
@@ -1086,7 +1706,9 @@ mod tests {
     use opencv::core::{Point, Vec4b};
 
     use super::*;
-    use crate::{Position, buff::BuffKind, minimap::MinimapIdle, skill::SkillKind};
+    use crate::{
+        MobbingLinkedKey, Position, buff::BuffKind, minimap::MinimapIdle, skill::SkillKind,
+    };
 
     const NORMAL_ACTION: Action = Action::Move(ActionMove {
         position: Position {
@@ -1165,6 +1787,161 @@ mod tests {
         ));
     }
 
+    #[test]
+    fn rotator_should_queue_fixed_action_inventory_full() {
+        let mut context = Context::new(None, None);
+        let mut idle = MinimapIdle::default();
+        let now = Instant::now();
+
+        context.minimap = Minimap::Idle(idle);
+        assert!(!should_queue_fixed_action(
+            &context,
+            None,
+            ActionCondition::InventoryFull
+        ));
+
+        idle.set_has_inventory_full();
+        context.minimap = Minimap::Idle(idle);
+        assert!(should_queue_fixed_action(
+            &context,
+            None,
+            ActionCondition::InventoryFull
+        ));
+        assert!(!should_queue_fixed_action(
+            &context,
+            Some(now - Duration::from_millis(INVENTORY_FULL_COOLDOWN_MILLIS as u64 - 1000)),
+            ActionCondition::InventoryFull
+        ));
+    }
+
+    #[test]
+    fn stranger_channel_hop_priority_action_after_millis_and_cooldown() {
+        let mut context = Context::new(None, None);
+        let mut player = PlayerState::default();
+        let action = stranger_channel_hop_priority_action(1000, 5000);
+        let condition = |context: &Context, player: &mut PlayerState, millis: u64| {
+            (action.condition.0)(
+                context,
+                player,
+                Some(Instant::now() - Duration::from_millis(millis)),
+            )
+        };
+
+        let mut idle = MinimapIdle::default();
+        context.minimap = Minimap::Idle(idle);
+        assert!(matches!(
+            condition(&context, &mut player, 2000),
+            ConditionResult::Ignore
+        ));
+
+        idle.set_has_stranger_player();
+        context.minimap = Minimap::Idle(idle);
+        assert!(matches!(
+            condition(&context, &mut player, 500),
+            ConditionResult::Skip
+        ));
+        assert!(matches!(
+            condition(&context, &mut player, 2000),
+            ConditionResult::Queue
+        ));
+        // Still in cooldown from the last hop
+        assert!(matches!(
+            condition(&context, &mut player, 2000),
+            ConditionResult::Skip
+        ));
+    }
+
+    #[test]
+    fn cash_shop_anti_idle_priority_action_every_millis() {
+        let context = Context::new(None, None);
+        let mut player = PlayerState::default();
+        let action = cash_shop_anti_idle_priority_action(5000, 1000);
+
+        // Not queued immediately when the actions start
+        assert!(matches!(
+            (action.condition.0)(&context, &mut player, None),
+            ConditionResult::Ignore
+        ));
+        assert!(matches!(
+            (action.condition.0)(
+                &context,
+                &mut player,
+                Some(Instant::now() - Duration::from_millis(1000))
+            ),
+            ConditionResult::Skip
+        ));
+        assert!(matches!(
+            (action.condition.0)(
+                &context,
+                &mut player,
+                Some(Instant::now() - Duration::from_millis(6000))
+            ),
+            ConditionResult::Queue
+        ));
+        assert_matches!(
+            action.inner,
+            RotatorAction::Single(PlayerAction::CashShop(PlayerActionCashShop { stay_ticks }))
+                if stay_ticks == millis_to_ticks(1000)
+        );
+    }
+
+    #[test]
+    fn can_priority_action_override_follow_preemption() {
+        let mut context = Context::new(None, None);
+        let mut player = PlayerState::default();
+        player.last_known_pos = Some(Point::new(0, 0));
+        player.set_normal_action(Some(1), PlayerAction::SolveRune);
+        context.player = Player::Moving(Point::new(100, 0), false, None);
+
+        player.config.priority_action_preemption = PriorityActionPreemption::Abort;
+        assert!(can_priority_action_override(&context, &player));
+        player.config.priority_action_preemption = PriorityActionPreemption::FinishMovement;
+        assert!(!can_priority_action_override(&context, &player));
+        player.config.priority_action_preemption = PriorityActionPreemption::FinishAction;
+        assert!(!can_priority_action_override(&context, &player));
+
+        context.player = Player::Idle;
+        player.config.priority_action_preemption = PriorityActionPreemption::FinishMovement;
+        assert!(can_priority_action_override(&context, &player));
+        player.config.priority_action_preemption = PriorityActionPreemption::FinishAction;
+        assert!(!can_priority_action_override(&context, &player));
+
+        player.reset_normal_action();
+        assert!(can_priority_action_override(&context, &player));
+    }
+
+    #[test]
+    fn rotator_queued_actions_queue_then_eta() {
+        let mut rotator = Rotator::default();
+        let every_millis_action = |millis: u64, last_queued_time: Option<Instant>| PriorityAction {
+            last_queued_time,
+            ..priority_action(
+                RotatorAction::Single(PlayerAction::SolveRune),
+                ActionCondition::EveryMillis(millis),
+                false,
+            )
+        };
+        rotator.priority_actions.insert(
+            1,
+            every_millis_action(10000, Some(Instant::now() - Duration::from_millis(4000))),
+        );
+        rotator
+            .priority_actions
+            .insert(2, every_millis_action(1000, None));
+        rotator
+            .priority_actions
+            .insert(3, every_millis_action(1000, None));
+        rotator.priority_actions_queue.push_back(3);
+
+        let queued = rotator.queued_actions();
+        let ids = queued.iter().map(|action| action.id).collect::<Vec<_>>();
+        assert_eq!(ids, vec![3, 2, 1]);
+        assert_eq!(queued[0].eta_millis, Some(0));
+        assert_eq!(queued[1].eta_millis, Some(0));
+        let eta_millis = queued[2].eta_millis.unwrap();
+        assert!(eta_millis > 5000 && eta_millis <= 6000);
+    }
+
     #[test]
     fn rotator_build_actions() {
         let mut rotator = Rotator::default();
@@ -1172,17 +1949,30 @@ mod tests {
         let buffs = vec![(BuffKind::Rune, KeyBinding::default()); 4];
         let args = RotatorBuildArgs {
             mode: RotatorMode::default(),
+            mobbing_cooldown_keys: &[],
             actions: &actions,
+            home_position: None,
             buffs: &buffs,
+            required_buffs: &[],
             familiar_essence_key: KeyBinding::default(),
             familiar_swappable_slots: SwappableFamiliars::default(),
             familiar_swappable_rarities: &HashSet::default(),
             familiar_swap_check_millis: 0,
+            familiar_essence_low_percentage: 0.0,
             elite_boss_behavior: Some(EliteBossBehavior::CycleChannel),
             elite_boss_behavior_key: KeyBinding::default(),
+            inventory_full_behavior: InventoryFullBehavior::default(),
             enable_panic_mode: true,
+            enable_stranger_channel_hop: false,
+            stranger_channel_hop_after_millis: 0,
+            stranger_channel_hop_cooldown_millis: 0,
+            enable_cash_shop_anti_idle: false,
+            cash_shop_anti_idle_every_millis: 0,
+            cash_shop_anti_idle_stay_millis: 0,
             enable_rune_solving: true,
+            enable_rune_solving_only_when_safe: false,
             enable_familiars_swapping: false,
+            enable_familiars_resummon_on_essence_low: false,
             enable_reset_normal_actions_on_erda: false,
         };
 
@@ -1191,6 +1981,28 @@ mod tests {
         assert_eq!(rotator.normal_actions.len(), 2);
     }
 
+    #[test]
+    fn rotator_rotate_action_wait_for_required_buffs() {
+        let mut rotator = Rotator::default();
+        let mut player = PlayerState::default();
+        let mut context = Context::new(None, None);
+        rotator.normal_rotate_mode = RotatorMode::StartToEnd;
+        rotator
+            .normal_actions
+            .push((0, RotatorAction::Single(NORMAL_ACTION.into())));
+        rotator.required_buffs = vec![(BuffKind::SayramElixir, true)];
+
+        rotator.rotate_action(&context, &mut player);
+        assert!(!player.has_normal_action());
+        assert!(!rotator.required_buffs_verified);
+
+        context.buffs[BuffKind::SayramElixir] = Buff::Yes;
+        rotator.rotate_action(&context, &mut player);
+        assert_eq!(player.normal_action_id(), Some(0));
+        assert!(rotator.required_buffs_verified);
+        assert!(rotator.missing_required_buffs.is_empty());
+    }
+
     #[test]
     fn rotator_rotate_action_start_to_end_then_reverse() {
         let mut rotator = Rotator::default();
@@ -1233,6 +2045,22 @@ mod tests {
         assert_eq!(rotator.normal_index, 1);
     }
 
+    #[test]
+    fn rotator_set_normal_position_in_range() {
+        let mut rotator = Rotator::default();
+        for i in 0..3 {
+            rotator
+                .normal_actions
+                .push((i, RotatorAction::Single(NORMAL_ACTION.into())));
+        }
+
+        rotator.set_normal_position(2, true);
+        assert_eq!(rotator.normal_position(), (2, true));
+
+        rotator.set_normal_position(3, false);
+        assert_eq!(rotator.normal_position(), (2, true));
+    }
+
     #[test]
     fn rotator_rotate_action_start_to_end() {
         let mut rotator = Rotator::default();
@@ -1258,6 +2086,36 @@ mod tests {
         assert_eq!(rotator.normal_index, 0);
     }
 
+    #[test]
+    fn rotator_rotate_action_home_position() {
+        let mut rotator = Rotator::default();
+        let mut player = PlayerState::default();
+        let context = Context::new(None, None);
+        rotator.normal_rotate_mode = RotatorMode::StartToEnd;
+        rotator.normal_home_position = Some(Position::default());
+        for i in 0..2 {
+            rotator
+                .normal_actions
+                .push((i, RotatorAction::Single(NORMAL_ACTION.into())));
+        }
+
+        rotator.rotate_action(&context, &mut player);
+        assert_eq!(player.normal_action_id(), Some(0));
+        assert!(rotator.normal_home_pending);
+
+        player.clear_actions_aborted(true);
+        rotator.rotate_action(&context, &mut player);
+        assert_matches!(
+            player.normal_action(),
+            Some(PlayerAction::Move(PlayerActionMove { home: true, .. }))
+        );
+        assert!(!rotator.normal_home_pending);
+
+        player.clear_actions_aborted(true);
+        rotator.rotate_action(&context, &mut player);
+        assert_eq!(player.normal_action_id(), Some(1));
+    }
+
     #[test]
     fn rotator_priority_action_queue() {
         let mut rotator = Rotator::default();
@@ -1280,6 +2138,7 @@ mod tests {
                 condition_kind: None,
                 inner: RotatorAction::Single(PlayerAction::SolveRune),
                 queue_to_front: true,
+                burst: false,
                 ignoring: false,
                 last_queued_time: None,
             },
@@ -1290,6 +2149,38 @@ mod tests {
         assert_eq!(player.priority_action_id(), Some(55));
     }
 
+    #[test]
+    fn rotator_priority_action_burst_queue_when_all_ready() {
+        let mut rotator = Rotator::default();
+        let mut player = PlayerState::default();
+        let context = Context::new(None, None);
+        let burst_action = |result: fn() -> ConditionResult| PriorityAction {
+            condition: Condition(Box::new(move |_, _, _| result())),
+            condition_kind: None,
+            inner: RotatorAction::Single(PlayerAction::SolveRune),
+            queue_to_front: true,
+            burst: true,
+            ignoring: false,
+            last_queued_time: None,
+        };
+        rotator
+            .priority_actions
+            .insert(1, burst_action(|| ConditionResult::Queue));
+        rotator
+            .priority_actions
+            .insert(2, burst_action(|| ConditionResult::Skip));
+
+        rotator.rotate_priority_actions(&context, &mut player);
+        assert!(rotator.priority_actions_queue.is_empty());
+
+        rotator
+            .priority_actions
+            .insert(2, burst_action(|| ConditionResult::Queue));
+        rotator.priority_actions_queue.push_back(3);
+        rotator.rotate_priority_actions(&context, &mut player);
+        assert_eq!(rotator.priority_actions_queue, vec![1, 2, 3]);
+    }
+
     #[test]
     fn rotator_priority_action_queue_to_front() {
         let mut rotator = Rotator::default();
@@ -1303,6 +2194,7 @@ mod tests {
                 condition_kind: None,
                 inner: RotatorAction::Single(NORMAL_ACTION.into()),
                 queue_to_front: false,
+                burst: false,
                 ignoring: false,
                 last_queued_time: None,
             },
@@ -1314,6 +2206,7 @@ mod tests {
                 condition_kind: None,
                 inner: RotatorAction::Single(NORMAL_ACTION.into()),
                 queue_to_front: false,
+                burst: false,
                 ignoring: false,
                 last_queued_time: None,
             },
@@ -1331,6 +2224,7 @@ mod tests {
                 condition_kind: None,
                 inner: RotatorAction::Single(NORMAL_ACTION.into()),
                 queue_to_front: true,
+                burst: false,
                 ignoring: false,
                 last_queued_time: None,
             },
@@ -1352,6 +2246,7 @@ mod tests {
                 condition_kind: None,
                 inner: RotatorAction::Single(NORMAL_ACTION.into()),
                 queue_to_front: true,
+                burst: false,
                 ignoring: false,
                 last_queued_time: None,
             },
@@ -1385,6 +2280,7 @@ mod tests {
                     })),
                 }),
                 queue_to_front: false,
+                burst: false,
                 ignoring: false,
                 last_queued_time: None,
             },
@@ -1404,6 +2300,7 @@ mod tests {
                 condition_kind: None,
                 inner: RotatorAction::Single(PlayerAction::SolveRune),
                 queue_to_front: true,
+                burst: false,
                 ignoring: false,
                 last_queued_time: None,
             },
@@ -1441,6 +2338,7 @@ mod tests {
             &mut player,
             MobbingKey::default(),
             Rect::new(20, 20, 80, 80).into(),
+            false,
         );
 
         assert_matches!(
@@ -1459,6 +2357,7 @@ mod tests {
             &mut player,
             MobbingKey::default(),
             Rect::new(20, 20, 80, 80).into(),
+            false,
         );
 
         assert_matches!(
@@ -1470,6 +2369,135 @@ mod tests {
         );
     }
 
+    #[test]
+    fn rotate_ping_pong_walk_only() {
+        let mut rotator = Rotator::default();
+        let mut player = PlayerState::default();
+        let mut idle = MinimapIdle::default();
+        idle.bbox = Rect::new(0, 0, 100, 100);
+
+        let mut context = Context::new(None, None);
+        context.minimap = Minimap::Idle(idle);
+
+        player.last_known_pos = Some(Point::new(80, 50));
+        rotator.rotate_ping_pong(
+            &context,
+            &mut player,
+            MobbingKey::default(),
+            Rect::new(20, 20, 80, 80).into(),
+            true,
+        );
+
+        assert_matches!(
+            player.normal_action(),
+            Some(PlayerAction::PingPong(PlayerActionPingPong {
+                walk_only: true,
+                ..
+            }))
+        );
+    }
+
+    #[test]
+    fn rotate_ping_pong_linked_keys() {
+        let mut rotator = Rotator::default();
+        let mut player = PlayerState::default();
+        let mut idle = MinimapIdle::default();
+        idle.bbox = Rect::new(0, 0, 100, 100);
+
+        let mut context = Context::new(None, None);
+        context.minimap = Minimap::Idle(idle);
+
+        let mut key = MobbingKey::default();
+        key.linked_keys[0] = Some(MobbingLinkedKey {
+            key: KeyBinding::B,
+            ..MobbingLinkedKey::default()
+        });
+        player.last_known_pos = Some(Point::new(80, 50));
+        rotator.rotate_ping_pong(
+            &context,
+            &mut player,
+            key,
+            Rect::new(20, 20, 80, 80).into(),
+            false,
+        );
+
+        assert_matches!(player.normal_action(), Some(PlayerAction::PingPong(_)));
+        assert!(rotator.normal_queuing_linked_action.is_some());
+
+        player.clear_actions_aborted(true);
+        rotator.rotate_ping_pong(
+            &context,
+            &mut player,
+            key,
+            Rect::new(20, 20, 80, 80).into(),
+            false,
+        );
+
+        assert_matches!(
+            player.normal_action(),
+            Some(PlayerAction::Key(PlayerActionKey {
+                key: KeyBinding::B,
+                ..
+            }))
+        );
+        assert!(rotator.normal_queuing_linked_action.is_none());
+    }
+
+    #[test]
+    fn rotate_ping_pong_mobbing_cooldown_keys() {
+        let mut rotator = Rotator::default();
+        let mut player = PlayerState::default();
+        let mut idle = MinimapIdle::default();
+        idle.bbox = Rect::new(0, 0, 100, 100);
+
+        let mut context = Context::new(None, None);
+        context.minimap = Minimap::Idle(idle);
+
+        let cooldown_key = MobbingCooldownKey {
+            key: MobbingKey {
+                key: KeyBinding::B,
+                ..MobbingKey::default()
+            },
+            cooldown_millis: 10000,
+        };
+        rotator.normal_mobbing_cooldown_keys = vec![(cooldown_key, None)];
+        player.last_known_pos = Some(Point::new(80, 50));
+        rotator.rotate_ping_pong(
+            &context,
+            &mut player,
+            MobbingKey::default(),
+            Rect::new(20, 20, 80, 80).into(),
+            false,
+        );
+
+        assert_matches!(
+            player.normal_action(),
+            Some(PlayerAction::PingPong(PlayerActionPingPong {
+                key: KeyBinding::B,
+                ..
+            }))
+        );
+        assert!(rotator.normal_mobbing_cooldown_keys[0].1.is_some());
+
+        // On cooldown -> Use the default key
+        player.clear_actions_aborted(true);
+        rotator.rotate_ping_pong(
+            &context,
+            &mut player,
+            MobbingKey::default(),
+            Rect::new(20, 20, 80, 80).into(),
+            false,
+        );
+
+        assert_matches!(
+            player.normal_action(),
+            Some(PlayerAction::PingPong(PlayerActionPingPong {
+                key: KeyBinding::A,
+                ..
+            }))
+        );
+    }
+
     #[test]
     fn rotator_priority_action_is_ignored_when_executing() {
         let mut rotator = Rotator::default();
@@ -1485,6 +2513,7 @@ mod tests {
                 condition_kind: None,
                 inner: RotatorAction::Single(NORMAL_ACTION.into()),
                 queue_to_front: false,
+                burst: false,
                 ignoring: false,
                 last_queued_time: None,
             },
@@ -1522,6 +2551,7 @@ mod tests {
                     next: None,
                 }),
                 queue_to_front: false,
+                burst: false,
                 ignoring: false,
                 last_queued_time: None,
             },
@@ -1555,6 +2585,7 @@ mod tests {
                 condition_kind: Some(ActionCondition::ErdaShowerOffCooldown),
                 inner: RotatorAction::Single(NORMAL_ACTION.into()),
                 queue_to_front: false,
+                burst: false,
                 ignoring: false,
                 last_queued_time: Some(Instant::now()),
             },
@@ -1567,6 +2598,7 @@ mod tests {
                 condition_kind: Some(ActionCondition::ErdaShowerOffCooldown),
                 inner: RotatorAction::Single(NORMAL_ACTION.into()),
                 queue_to_front: false,
+                burst: false,
                 ignoring: false,
                 last_queued_time: None,
             },
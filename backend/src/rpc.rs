@@ -1,30 +1,87 @@
-use std::time::Duration;
+use std::time::{Duration, Instant};
 
-use anyhow::{Error, Ok, bail};
+use anyhow::{Error, Ok, anyhow, bail};
 use bit_vec::BitVec;
 use input::key_input_client::KeyInputClient;
 pub use input::{Coordinate, MouseAction};
-use input::{Key, KeyDownRequest, KeyInitRequest, KeyRequest, KeyUpRequest, MouseRequest};
+use input::{
+    Key, KeyBatchRequest, KeyDownRequest, KeyInitRequest, KeyRequest, KeyStroke, KeyUpRequest,
+    MouseRequest, PingRequest,
+};
+use log::{debug, info};
 use platforms::windows::KeyKind;
 use tokio::runtime::Handle;
 use tokio::task::block_in_place;
 use tokio::time::timeout;
-use tonic::Request;
 use tonic::transport::{Channel, Endpoint};
+use tonic::{Code, Request, Status};
 
 mod input {
     tonic::include_proto!("input");
 }
 
+/// Maximum duration to wait for the first connection.
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(3);
+
+/// Maximum duration to wait for a reconnection.
+///
+/// Shorter than [`CONNECT_TIMEOUT`] since reconnection blocks the bot loop.
+const RECONNECT_TIMEOUT: Duration = Duration::from_millis(500);
+
+/// The initial and maximum backoff between two reconnection attempts.
+const RECONNECT_BACKOFF_MIN: Duration = Duration::from_millis(500);
+const RECONNECT_BACKOFF_MAX: Duration = Duration::from_secs(30);
+
+/// The interval between two pings while connected.
+const PING_INTERVAL: Duration = Duration::from_secs(5);
+
+/// The state of reconnecting after the server is unreachable.
+#[derive(Debug, Default)]
+struct Reconnect {
+    /// The number of consecutive failed attempts.
+    failures: u32,
+    /// The instant the next attempt can be made.
+    next_attempt: Option<Instant>,
+}
+
+impl Reconnect {
+    fn can_attempt(&self) -> bool {
+        self.next_attempt
+            .is_none_or(|instant| Instant::now() >= instant)
+    }
+
+    fn on_failure(&mut self) {
+        let backoff = RECONNECT_BACKOFF_MIN
+            .saturating_mul(2u32.saturating_pow(self.failures))
+            .min(RECONNECT_BACKOFF_MAX);
+        self.failures = self.failures.saturating_add(1);
+        self.next_attempt = Some(Instant::now() + backoff);
+    }
+}
+
 #[derive(Debug)]
 pub struct KeysService {
-    client: KeyInputClient<Channel>,
+    /// The client to the server, [`None`] if the server is unreachable.
+    client: Option<KeyInputClient<Channel>>,
+    endpoint: Endpoint,
     url: String,
+    /// The seed from the last [`Self::init`] to re-initialize the server after reconnecting.
+    seed: Vec<u8>,
     key_down: BitVec, // TODO: is a bit wrong good?
     mouse_coordinate: Coordinate,
+    /// Whether the server implements `SendBatch`.
+    ///
+    /// Servers implementing the first version of the protocol only have the single key methods.
+    supports_batch: bool,
+    reconnect: Reconnect,
+    last_ping: Instant,
 }
 
 impl KeysService {
+    /// Connects to the server at `dest`.
+    ///
+    /// Only fails if `dest` is not a valid URL. If the server is unreachable, the service is
+    /// still created and reconnects with exponential backoff on the next calls.
     pub fn connect<D>(dest: D) -> Result<Self, Error>
     where
         D: TryInto<Endpoint>,
@@ -32,43 +89,63 @@ impl KeysService {
         D::Error: std::error::Error + Send + Sync + 'static,
     {
         let endpoint = TryInto::<Endpoint>::try_into(dest.as_ref().to_string())?;
-        let client = block_future(async move {
-            timeout(Duration::from_secs(3), KeyInputClient::connect(endpoint)).await
-        })??;
-        Ok(Self {
-            client,
+        let mut service = Self {
+            client: None,
+            endpoint,
             url: dest.as_ref().to_string(),
+            seed: vec![],
             key_down: BitVec::from_elem(128, false),
             mouse_coordinate: Coordinate::Screen,
-        })
+            supports_batch: true,
+            reconnect: Reconnect::default(),
+            last_ping: Instant::now(),
+        };
+        if service.connect_client(CONNECT_TIMEOUT).is_err() {
+            service.reconnect.on_failure();
+        }
+        Ok(service)
     }
 
     pub fn url(&self) -> &String {
         &self.url
     }
 
+    #[inline]
+    pub fn is_connected(&self) -> bool {
+        self.client.is_some()
+    }
+
+    #[inline]
+    pub fn supports_batch(&self) -> bool {
+        self.supports_batch
+    }
+
     pub fn reset(&mut self) {
-        for i in 0..self.key_down.len() {
-            if Key::try_from(i as i32).is_ok() {
-                let _ = block_future(async {
-                    self.client
-                        .send_up(Request::new(KeyUpRequest { key: i as i32 }))
-                        .await
-                });
+        if let Some(client) = self.client.as_mut() {
+            for i in 0..self.key_down.len() {
+                if Key::try_from(i as i32).is_ok() {
+                    let _ = block_future(async {
+                        client
+                            .send_up(Request::new(KeyUpRequest { key: i as i32 }))
+                            .await
+                    });
+                }
             }
         }
         self.key_down.clear();
     }
 
     pub fn init(&mut self, seed: &[u8]) -> Result<(), Error> {
+        self.seed = seed.to_vec();
+        let mut client = self.client()?;
         let response = block_future(async {
-            self.client
+            client
                 .init(KeyInitRequest {
                     seed: seed.to_vec(),
                 })
                 .await
-        })?
-        .into_inner();
+        });
+        let response = self.check_status(response)?.into_inner();
         self.mouse_coordinate = response.mouse_coordinate();
         Ok(())
     }
@@ -77,6 +154,37 @@ impl KeysService {
         self.mouse_coordinate
     }
 
+    /// Pings the server and returns the round-trip duration.
+    ///
+    /// Servers not implementing `Ping` are considered healthy.
+    pub fn ping(&mut self) -> Result<Duration, Error> {
+        let mut client = self.client()?;
+        let instant = Instant::now();
+        let response = block_future(async { client.ping(Request::new(PingRequest {})).await });
+        self.last_ping = Instant::now();
+        match response {
+            Err(status) if status.code() == Code::Unimplemented => Ok(instant.elapsed()),
+            response => {
+                self.check_status(response)?;
+                Ok(instant.elapsed())
+            }
+        }
+    }
+
+    /// Checks the connection to the server.
+    ///
+    /// Pings the server every [`PING_INTERVAL`] while connected or tries to reconnect when the
+    /// backoff elapsed otherwise.
+    pub fn check_health(&mut self) {
+        if self.client.is_none() {
+            let _ = self.client();
+            return;
+        }
+        if self.last_ping.elapsed() >= PING_INTERVAL {
+            let _ = self.ping();
+        }
+    }
+
     pub fn send_mouse(
         &mut self,
         width: i32,
@@ -85,8 +193,9 @@ impl KeysService {
         y: i32,
         action: MouseAction,
     ) -> Result<(), Error> {
-        Ok(block_future(async move {
-            self.client
+        let mut client = self.client()?;
+        let response = block_future(async move {
+            client
                 .send_mouse(Request::new(MouseRequest {
                     width,
                     height,
@@ -94,24 +203,58 @@ impl KeysService {
                     y,
                     action: action.into(),
                 }))
-                .await?;
-            Ok(())
-        })?)
+                .await
+        });
+        self.check_status(response)?;
+        Ok(())
     }
 
     // TODO: Use gRPC enum instead of platforms
     pub fn send(&mut self, key: KeyKind, down_ms: f32) -> Result<(), Error> {
-        Ok(block_future(async move {
-            let kind = from_key_kind(key);
-            let request = Request::new(KeyRequest {
-                key: kind.into(),
-                down_ms,
-            });
+        let mut client = self.client()?;
+        let kind = from_key_kind(key);
+        let request = Request::new(KeyRequest {
+            key: kind.into(),
+            down_ms,
+        });
+        let response = block_future(async move { client.send(request).await });
+        self.check_status(response)?;
+        self.key_down.set(i32::from(kind) as usize, false);
+        Ok(())
+    }
 
-            self.client.send(request).await?;
-            self.key_down.set(i32::from(kind) as usize, false);
-            Ok(())
-        })?)
+    /// Sends `strokes` of `(key, down_ms, delay_ms)` in one call.
+    ///
+    /// The server presses each key for `down_ms` and waits `delay_ms` after releasing it before
+    /// the next key so the timings are not affected by the network.
+    // TODO: Use gRPC enum instead of platforms
+    pub fn send_batch(&mut self, strokes: &[(KeyKind, f32, f32)]) -> Result<(), Error> {
+        if !self.supports_batch {
+            bail!("batch not supported");
+        }
+        let mut client = self.client()?;
+        let strokes = strokes
+            .iter()
+            .map(|(key, down_ms, delay_ms)| KeyStroke {
+                key: from_key_kind(*key).into(),
+                down_ms: *down_ms,
+                delay_ms: *delay_ms,
+            })
+            .collect::<Vec<_>>();
+        let keys = strokes.iter().map(|stroke| stroke.key).collect::<Vec<_>>();
+        let request = Request::new(KeyBatchRequest { strokes });
+        let response = block_future(async move { client.send_batch(request).await });
+        if let Err(status) = &response
+            && status.code() == Code::Unimplemented
+        {
+            info!(target: "rpc", "server does not support batch, falling back to single key");
+            self.supports_batch = false;
+        }
+        self.check_status(response)?;
+        for key in keys {
+            self.key_down.set(key as usize, false);
+        }
+        Ok(())
     }
 
     // TODO: Use gRPC enum instead of platforms
@@ -119,14 +262,13 @@ impl KeysService {
         if !self.can_send_key(key, false) {
             bail!("key not sent");
         }
-        Ok(block_future(async move {
-            let kind = from_key_kind(key);
-            let request = Request::new(KeyUpRequest { key: kind.into() });
-
-            self.client.send_up(request).await?;
-            self.key_down.set(i32::from(kind) as usize, false);
-            Ok(())
-        })?)
+        let mut client = self.client()?;
+        let kind = from_key_kind(key);
+        let request = Request::new(KeyUpRequest { key: kind.into() });
+        let response = block_future(async move { client.send_up(request).await });
+        self.check_status(response)?;
+        self.key_down.set(i32::from(kind) as usize, false);
+        Ok(())
     }
 
     // TODO: Use gRPC enum instead of platforms
@@ -134,14 +276,13 @@ impl KeysService {
         if !self.can_send_key(key, true) {
             bail!("key not sent");
         }
-        Ok(block_future(async move {
-            let kind = from_key_kind(key);
-            let request = Request::new(KeyDownRequest { key: kind.into() });
-
-            self.client.send_down(request).await?;
-            self.key_down.set(i32::from(kind) as usize, true);
-            Ok(())
-        })?)
+        let mut client = self.client()?;
+        let kind = from_key_kind(key);
+        let request = Request::new(KeyDownRequest { key: kind.into() });
+        let response = block_future(async move { client.send_down(request).await });
+        self.check_status(response)?;
+        self.key_down.set(i32::from(kind) as usize, true);
+        Ok(())
     }
 
     // TODO: Use gRPC enum instead of platforms
@@ -152,6 +293,67 @@ impl KeysService {
         let was_down = self.key_down.get(key_num).unwrap();
         !matches!((was_down, is_down), (true, true) | (false, false))
     }
+
+    /// Returns the client to the server, reconnecting if the server was unreachable and the
+    /// backoff elapsed.
+    ///
+    /// The client is cheap to clone since it shares the same underlying channel.
+    fn client(&mut self) -> Result<KeyInputClient<Channel>, Error> {
+        if self.client.is_none() {
+            if !self.reconnect.can_attempt() {
+                bail!("server unreachable");
+            }
+            if let Err(err) = self.connect_client(RECONNECT_TIMEOUT) {
+                self.reconnect.on_failure();
+                debug!(target: "rpc", "reconnect failed {} times {err}", self.reconnect.failures);
+                return Err(err);
+            }
+            info!(target: "rpc", "reconnected to {}", self.url);
+        }
+
+        Ok(self.client.clone().unwrap())
+    }
+
+    /// Connects the client and re-initializes the server with the last seed if any.
+    fn connect_client(&mut self, duration: Duration) -> Result<(), Error> {
+        let endpoint = self.endpoint.clone();
+        let mut client =
+            block_future(
+                async move { timeout(duration, KeyInputClient::connect(endpoint)).await },
+            )??;
+        if !self.seed.is_empty() {
+            let seed = self.seed.clone();
+            let response = block_future(async {
+                timeout(duration, client.init(KeyInitRequest { seed })).await
+            })??
+            .into_inner();
+            self.mouse_coordinate = response.mouse_coordinate();
+        }
+
+        // Keys held before disconnected are assumed released by the server
+        self.key_down.clear();
+        self.client = Some(client);
+        self.supports_batch = true;
+        self.reconnect = Reconnect::default();
+        self.last_ping = Instant::now();
+        Ok(())
+    }
+
+    /// Drops the client when `response` failed because the server is unreachable so that the
+    /// next calls reconnect.
+    fn check_status<T>(&mut self, response: Result<T, Status>) -> Result<T, Error> {
+        response.map_err(|status| {
+            if matches!(
+                status.code(),
+                Code::Unavailable | Code::Cancelled | Code::DeadlineExceeded
+            ) {
+                info!(target: "rpc", "server unreachable {status}, reconnecting");
+                self.client = None;
+                self.reconnect.on_failure();
+            }
+            anyhow!(status)
+        })
+    }
 }
 
 #[inline]
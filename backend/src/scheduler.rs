@@ -0,0 +1,177 @@
+use log::info;
+use platforms::windows::LocalTime;
+
+use crate::{IntoEnumIterator, Schedule, ScheduleDay};
+
+const MINUTES_PER_DAY: u32 = 24 * 60;
+
+const DAYS_PER_WEEK: usize = 7;
+
+/// A change of the active schedule time window.
+#[derive(Clone, PartialEq, Debug)]
+pub enum ScheduleTransition {
+    /// A schedule time window has started and the actions should be started.
+    Start(Schedule),
+    /// The previous schedule time window has ended and the actions should be halted.
+    Halt,
+}
+
+/// Starts and halts the actions at the time windows of the configured schedules.
+///
+/// Only transitions between time windows are reported so that starting or halting manually within
+/// or outside a time window is kept until the next transition.
+#[derive(Debug, Default)]
+pub struct Scheduler {
+    /// The index of the schedule whose time window is currently active.
+    active: Option<usize>,
+}
+
+impl Scheduler {
+    /// Updates the active time window from `now` and returns the transition if any.
+    pub fn update(
+        &mut self,
+        enabled: bool,
+        schedules: &[Schedule],
+        now: LocalTime,
+    ) -> Option<ScheduleTransition> {
+        if !enabled {
+            self.active = None;
+            return None;
+        }
+
+        let day = ScheduleDay::iter().nth(now.day_of_week as usize)?;
+        let minute = now.hour * 60 + now.minute;
+        let active = schedules
+            .iter()
+            .position(|schedule| schedule.enabled && is_in_window(schedule, day, minute));
+        if active == self.active {
+            return None;
+        }
+        self.active = active;
+
+        match active {
+            Some(index) => {
+                info!(target: "scheduler", "schedule {index} started");
+                Some(ScheduleTransition::Start(schedules[index].clone()))
+            }
+            None => {
+                info!(target: "scheduler", "schedule ended");
+                Some(ScheduleTransition::Halt)
+            }
+        }
+    }
+}
+
+/// Whether `minute` of the day `day` is within the time window of `schedule`.
+fn is_in_window(schedule: &Schedule, day: ScheduleDay, minute: u32) -> bool {
+    let start = (schedule.start_hour * 60 + schedule.start_minute) % MINUTES_PER_DAY;
+    let end = (schedule.end_hour * 60 + schedule.end_minute) % MINUTES_PER_DAY;
+    let previous_day = ScheduleDay::iter()
+        .nth((day as usize + DAYS_PER_WEEK - 1) % DAYS_PER_WEEK)
+        .expect("valid day");
+
+    if start <= end {
+        schedule.days.contains(&day) && (start..end).contains(&minute)
+    } else {
+        (schedule.days.contains(&day) && minute >= start)
+            || (schedule.days.contains(&previous_day) && minute < end)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::assert_matches::assert_matches;
+
+    use super::*;
+
+    fn local_time(day_of_week: u32, hour: u32, minute: u32) -> LocalTime {
+        LocalTime {
+            day_of_week,
+            hour,
+            minute,
+        }
+    }
+
+    #[test]
+    fn update_start_and_halt_on_window_transition() {
+        let schedule = Schedule {
+            days: vec![ScheduleDay::Monday],
+            start_hour: 1,
+            end_hour: 6,
+            ..Schedule::default()
+        };
+        let schedules = vec![schedule.clone()];
+        let mut scheduler = Scheduler::default();
+
+        assert_eq!(
+            scheduler.update(true, &schedules, local_time(1, 0, 59)),
+            None
+        );
+        assert_eq!(
+            scheduler.update(true, &schedules, local_time(1, 1, 0)),
+            Some(ScheduleTransition::Start(schedule))
+        );
+        assert_eq!(
+            scheduler.update(true, &schedules, local_time(1, 5, 59)),
+            None
+        );
+        assert_eq!(
+            scheduler.update(true, &schedules, local_time(1, 6, 0)),
+            Some(ScheduleTransition::Halt)
+        );
+        // Tuesday is not scheduled
+        assert_eq!(
+            scheduler.update(true, &schedules, local_time(2, 1, 0)),
+            None
+        );
+    }
+
+    #[test]
+    fn update_overnight_window_spans_to_next_day() {
+        let schedules = vec![Schedule {
+            days: vec![ScheduleDay::Saturday],
+            start_hour: 22,
+            end_hour: 2,
+            ..Schedule::default()
+        }];
+        let mut scheduler = Scheduler::default();
+
+        assert_matches!(
+            scheduler.update(true, &schedules, local_time(6, 23, 0)),
+            Some(ScheduleTransition::Start(_))
+        );
+        // Sunday after midnight
+        assert_eq!(
+            scheduler.update(true, &schedules, local_time(0, 1, 30)),
+            None
+        );
+        assert_eq!(
+            scheduler.update(true, &schedules, local_time(0, 2, 0)),
+            Some(ScheduleTransition::Halt)
+        );
+    }
+
+    #[test]
+    fn update_disabled_resets_active() {
+        let schedules = vec![Schedule {
+            end_hour: 6,
+            ..Schedule::default()
+        }];
+        let mut scheduler = Scheduler::default();
+
+        assert!(
+            scheduler
+                .update(true, &schedules, local_time(3, 1, 0))
+                .is_some()
+        );
+        assert_eq!(
+            scheduler.update(false, &schedules, local_time(3, 1, 0)),
+            None
+        );
+        assert!(
+            scheduler
+                .update(true, &schedules, local_time(3, 1, 0))
+                .is_some()
+        );
+    }
+}
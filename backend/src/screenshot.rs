@@ -0,0 +1,272 @@
+use std::{
+    cell::RefCell,
+    fs, mem,
+    path::{Path, PathBuf},
+    rc::Rc,
+    sync::{
+        LazyLock,
+        mpsc::{self, Receiver, SyncSender},
+    },
+    thread,
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+};
+
+use log::debug;
+use opencv::{
+    core::{MatTraitConst, Point, Scalar, Vector},
+    imgcodecs::imencode_def,
+    imgproc::{FONT_HERSHEY_SIMPLEX, put_text_def},
+};
+use strum::Display;
+
+use crate::{
+    Settings, detect::Detector, minimap::Minimap, overlay::draw_detection_overlay,
+    recorder::RECORDINGS_DIR,
+};
+
+/// Minimum duration between two screenshots of the same event.
+///
+/// Some events (e.g. unstucking) can happen many times in a short period.
+const MIN_EVENT_INTERVAL: Duration = Duration::from_secs(10);
+
+/// Maximum number of screenshots waiting to be written before new screenshots are dropped.
+const MAX_PENDING_SCREENSHOTS: usize = 8;
+
+static DEFAULT_SCREENSHOTS_DIR: LazyLock<PathBuf> =
+    LazyLock::new(|| RECORDINGS_DIR.join("screenshots"));
+
+/// A notable event to take a screenshot of.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Display)]
+pub enum ScreenshotEvent {
+    #[strum(to_string = "rune_failed")]
+    RuneFailed,
+    #[strum(to_string = "player_died")]
+    PlayerDied,
+    #[strum(to_string = "stranger_appeared")]
+    StrangerAppeared,
+    #[strum(to_string = "unstuck_gamba")]
+    UnstuckGamba,
+    #[strum(to_string = "map_changed")]
+    MapChanged,
+}
+
+impl ScreenshotEvent {
+    pub const COUNT: usize = mem::variant_count::<ScreenshotEvent>();
+}
+
+#[derive(Debug)]
+struct PendingScreenshot {
+    /// The folder to write the screenshot to.
+    dir: PathBuf,
+    file_name: String,
+    png: Vec<u8>,
+    max_count: usize,
+    max_bytes: u64,
+}
+
+/// Writes annotated screenshots to disk when notable events occur.
+///
+/// Events are scheduled from anywhere with access to the [`crate::context::Context`] and the
+/// screenshots are taken from the current frame in [`Self::update`]. Screenshots are written on
+/// a separate thread and the oldest screenshots are removed once the folder exceeds the
+/// configured count or size.
+#[derive(Debug)]
+pub struct EventScreenshots {
+    settings: Rc<RefCell<Settings>>,
+    /// The events scheduled in the current tick.
+    scheduled: RefCell<Vec<ScreenshotEvent>>,
+    /// The last instant a screenshot was taken for each event.
+    taken_at: RefCell<[Option<Instant>; ScreenshotEvent::COUNT]>,
+    /// The sender to the writer thread, spawned on the first screenshot.
+    sender: RefCell<Option<SyncSender<PendingScreenshot>>>,
+}
+
+impl EventScreenshots {
+    pub fn new(settings: Rc<RefCell<Settings>>) -> Self {
+        Self {
+            settings,
+            scheduled: RefCell::new(vec![]),
+            taken_at: RefCell::new([None; ScreenshotEvent::COUNT]),
+            sender: RefCell::new(None),
+        }
+    }
+
+    /// Schedules a screenshot of `event` to be taken in [`Self::update`] if enabled.
+    pub fn schedule(&self, event: ScreenshotEvent) {
+        if !self.settings.borrow().enable_event_screenshots {
+            return;
+        }
+        let taken_recently = self.taken_at.borrow()[event as usize]
+            .is_some_and(|instant| instant.elapsed() < MIN_EVENT_INTERVAL);
+        let mut scheduled = self.scheduled.borrow_mut();
+        if !taken_recently && !scheduled.contains(&event) {
+            scheduled.push(event);
+        }
+    }
+
+    /// Takes the screenshots of the scheduled events from the current frame of `detector`.
+    ///
+    /// The screenshots are annotated with the detections and the event name.
+    pub fn update(&self, detector: Option<&dyn Detector>, minimap: Minimap) {
+        let events = self.scheduled.take();
+        let Some(detector) = detector else {
+            return;
+        };
+        if events.is_empty() {
+            return;
+        }
+
+        let settings = self.settings.borrow();
+        let dir = if settings.event_screenshots_dir.is_empty() {
+            DEFAULT_SCREENSHOTS_DIR.clone()
+        } else {
+            PathBuf::from(&settings.event_screenshots_dir)
+        };
+        let millis = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|duration| duration.as_millis())
+            .unwrap_or_default();
+        let mat = draw_detection_overlay(detector, minimap);
+        let mut sender = self.sender.borrow_mut();
+        let sender = sender.get_or_insert_with(|| {
+            let (sender, receiver) = mpsc::sync_channel(MAX_PENDING_SCREENSHOTS);
+            thread::spawn(move || write_screenshots(receiver));
+            sender
+        });
+
+        for event in events {
+            self.taken_at.borrow_mut()[event as usize] = Some(Instant::now());
+
+            let Ok(mut mat) = mat.try_clone() else {
+                continue;
+            };
+            let _ = put_text_def(
+                &mut mat,
+                &event.to_string(),
+                Point::new(10, 30),
+                FONT_HERSHEY_SIMPLEX,
+                0.8,
+                Scalar::new(0.0, 0.0, 255.0, 255.0),
+            );
+            let mut bytes = Vector::new();
+            if imencode_def(".png", &mat, &mut bytes).is_err() {
+                continue;
+            }
+            debug!(target: "screenshot", "taking screenshot for {event}");
+            let _ = sender.try_send(PendingScreenshot {
+                dir: dir.clone(),
+                // Prefixed with the time so that the files are sorted in the taken order
+                file_name: format!("{millis:013}_{event}.png"),
+                png: bytes.to_vec(),
+                max_count: settings.event_screenshots_max_count as usize,
+                max_bytes: settings.event_screenshots_max_size_mb as u64 * 1024 * 1024,
+            });
+        }
+    }
+}
+
+fn write_screenshots(receiver: Receiver<PendingScreenshot>) {
+    for screenshot in receiver {
+        if fs::create_dir_all(&screenshot.dir).is_err() {
+            continue;
+        }
+        if fs::write(screenshot.dir.join(&screenshot.file_name), &screenshot.png).is_err() {
+            continue;
+        }
+
+        let files = fs::read_dir(&screenshot.dir)
+            .map(|entries| {
+                entries
+                    .filter_map(|entry| entry.ok())
+                    .map(|entry| entry.path())
+                    .filter(|path| is_screenshot(path))
+                    .map(|path| {
+                        let size = fs::metadata(&path)
+                            .map(|metadata| metadata.len())
+                            .unwrap_or_default();
+                        (path, size)
+                    })
+                    .collect::<Vec<_>>()
+            })
+            .unwrap_or_default();
+        for path in screenshots_to_remove(files, screenshot.max_count, screenshot.max_bytes) {
+            let _ = fs::remove_file(path);
+        }
+    }
+}
+
+/// Whether `path` is a screenshot written by [`write_screenshots`].
+///
+/// This keeps other files from being removed if the folder is shared.
+fn is_screenshot(path: &Path) -> bool {
+    path.extension().is_some_and(|ext| ext == "png")
+        && path
+            .file_stem()
+            .and_then(|stem| stem.to_str())
+            .and_then(|stem| stem.split_once('_'))
+            .is_some_and(|(millis, _)| millis.parse::<u128>().is_ok())
+}
+
+/// Finds the oldest screenshots in `files` exceeding `max_count` or `max_bytes` in total.
+///
+/// `files` are tuples of path and size with the paths prefixed by the time taken. The newest
+/// screenshot is always kept.
+fn screenshots_to_remove(
+    mut files: Vec<(PathBuf, u64)>,
+    max_count: usize,
+    max_bytes: u64,
+) -> Vec<PathBuf> {
+    // Newest first
+    files.sort_unstable_by(|(a, _), (b, _)| b.cmp(a));
+
+    let mut total_bytes = 0;
+    files
+        .into_iter()
+        .enumerate()
+        .filter_map(|(i, (path, size))| {
+            total_bytes += size;
+            (i > 0 && (i >= max_count || total_bytes > max_bytes)).then_some(path)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn screenshots_to_remove_oldest_over_limits() {
+        let files = vec![
+            (PathBuf::from("0000000000002_map_changed.png"), 10),
+            (PathBuf::from("0000000000001_rune_failed.png"), 10),
+            (PathBuf::from("0000000000003_player_died.png"), 10),
+        ];
+
+        assert!(screenshots_to_remove(files.clone(), 3, 30).is_empty());
+        assert_eq!(
+            screenshots_to_remove(files.clone(), 2, 30),
+            vec![PathBuf::from("0000000000001_rune_failed.png")]
+        );
+        assert_eq!(
+            screenshots_to_remove(files.clone(), 3, 15),
+            vec![
+                PathBuf::from("0000000000002_map_changed.png"),
+                PathBuf::from("0000000000001_rune_failed.png"),
+            ]
+        );
+        // Always keeps the newest
+        assert_eq!(screenshots_to_remove(files, 0, 0).len(), 2);
+    }
+
+    #[test]
+    fn is_screenshot_only_matches_written_names() {
+        assert!(is_screenshot(Path::new(
+            "dir/0000000000001_rune_failed.png"
+        )));
+        assert!(!is_screenshot(Path::new(
+            "dir/0000000000001_rune_failed.jpg"
+        )));
+        assert!(!is_screenshot(Path::new("dir/holiday_photo.png")));
+        assert!(!is_screenshot(Path::new("dir/photo.png")));
+    }
+}
@@ -0,0 +1,242 @@
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use opencv::core::Rect;
+use strum::Display;
+
+use crate::{
+    Settings,
+    context::Context,
+    database::{IncomeSample, SessionStats, insert_session_stats},
+    income::IncomeTracker,
+    network::NotificationKind,
+    player::PlayerState,
+};
+
+/// The interval between each sampled EXP and mesos per hour in a session.
+const INCOME_SAMPLE_INTERVAL: Duration = Duration::from_secs(60);
+
+/// The maximum number of EXP and mesos per hour samples kept in a session.
+///
+/// At one sample per minute, this is a day of samples.
+const MAX_INCOME_SAMPLES: usize = 1440;
+
+/// The reason the actions were halted.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Display)]
+pub enum SessionStopReason {
+    #[strum(to_string = "Stopped manually")]
+    Manual,
+    #[strum(to_string = "Run/stop cycle")]
+    Cycle,
+    #[strum(to_string = "Failed to solve rune too many times")]
+    RuneFailed,
+    #[strum(to_string = "Server maintenance")]
+    Maintenance,
+    #[strum(to_string = "Player died")]
+    PlayerDied,
+    #[strum(to_string = "Failed to detect or map changed")]
+    FailOrMapChange,
+    #[strum(to_string = "Panic key")]
+    PanicKey,
+    #[strum(to_string = "Schedule ended")]
+    Schedule,
+    #[strum(to_string = "Inventory full")]
+    InventoryFull,
+    #[strum(to_string = "GM detected")]
+    GmDetected,
+    #[strum(to_string = "Potion has no effect")]
+    PotionNoEffect,
+    #[strum(to_string = "Discord command")]
+    DiscordCommand,
+}
+
+/// A single run from when the actions start until they halt.
+#[derive(Debug)]
+struct Session {
+    started_at: Instant,
+    stats: SessionStats,
+    income: IncomeTracker,
+    income_sampled_at: Instant,
+}
+
+/// Tracks the current run and sends a summary notification when the actions halt.
+///
+/// The statistics of each run are persisted to the database when the run ends.
+#[derive(Debug, Default)]
+pub struct SessionTracker {
+    session: Option<Session>,
+    /// The reason set for the halt happening in the current tick.
+    stop_reason: Option<SessionStopReason>,
+}
+
+impl SessionTracker {
+    /// Sets the `reason` for the actions halting in the current tick.
+    ///
+    /// Only the first reason set in a tick is kept.
+    #[inline]
+    pub fn set_stop_reason(&mut self, reason: SessionStopReason) {
+        self.stop_reason.get_or_insert(reason);
+    }
+
+    /// Increments the number of deaths in the current session.
+    #[inline]
+    pub fn track_death(&mut self) {
+        if let Some(session) = self.session.as_mut() {
+            session.stats.deaths += 1;
+        }
+    }
+
+    /// The statistics of the current session if the actions are running.
+    pub fn current_stats(&self) -> Option<SessionStats> {
+        self.session.as_ref().map(|session| SessionStats {
+            runtime_millis: session.started_at.elapsed().as_millis() as u64,
+            ..session.stats.clone()
+        })
+    }
+
+    /// Updates the current session from the player counters.
+    ///
+    /// A new session starts when the actions are running and ends with a summary notification
+    /// when the actions are halted. EXP and mesos are also tracked while running if enabled in
+    /// `settings`.
+    pub fn update(&mut self, context: &Context, player: &mut PlayerState, settings: &Settings) {
+        // Pausing keeps the current session
+        if context.operation.paused() {
+            return;
+        }
+        let completed_actions = player.take_completed_action_count();
+        let solved_runes = player.take_solved_rune_count();
+        let failed_runes = player.take_failed_rune_count();
+        let channel_changes = player.take_changed_channel_count();
+        let unstuck_events = player.take_unstuck_event_count();
+        let stop_reason = self.stop_reason.take();
+
+        match (self.session.as_mut(), context.operation.halting()) {
+            (None, false) => {
+                let now = Instant::now();
+                self.session = Some(Session {
+                    started_at: now,
+                    income: IncomeTracker::default(),
+                    income_sampled_at: now,
+                    stats: SessionStats {
+                        started_at_millis: SystemTime::now()
+                            .duration_since(UNIX_EPOCH)
+                            .map(|duration| duration.as_millis() as u64)
+                            .unwrap_or_default(),
+                        ..SessionStats::default()
+                    },
+                });
+            }
+            (Some(session), false) => {
+                session.stats.completed_actions += completed_actions;
+                session.stats.solved_runes += solved_runes;
+                session.stats.failed_runes += failed_runes;
+                session.stats.channel_changes += channel_changes;
+                session.stats.unstuck_events += unstuck_events;
+                if settings.enable_income_tracking {
+                    update_income(context, session, settings);
+                }
+            }
+            (Some(session), true) => {
+                let reason = stop_reason.unwrap_or(SessionStopReason::Manual);
+                let runtime = session.started_at.elapsed();
+                let summary = session_summary(&session.stats, runtime, reason);
+                let mut stats = SessionStats {
+                    runtime_millis: runtime.as_millis() as u64,
+                    stop_reason: reason.to_string(),
+                    ..session.stats.clone()
+                };
+                self.session = None;
+                let _ = insert_session_stats(&mut stats);
+                let _ = context
+                    .notification
+                    .schedule_notification_with_details(NotificationKind::SessionSummary, summary);
+            }
+            (None, true) => (),
+        }
+    }
+}
+
+fn update_income(context: &Context, session: &mut Session, settings: &Settings) {
+    // The region is relative to the captured frame before cropping
+    let mesos_region = settings.mesos_region.map(|region| {
+        let mut region = Rect::from(region);
+        if let Some(crop) = settings.capture_crop {
+            region.x -= crop.x;
+            region.y -= crop.y;
+        }
+        region
+    });
+    session.income.update(context, mesos_region);
+
+    let now = Instant::now();
+    session.stats.exp_percent_per_hour = session.income.exp_percent_per_hour(now);
+    session.stats.mesos_per_hour = session.income.mesos_per_hour(now);
+    if now.duration_since(session.income_sampled_at) < INCOME_SAMPLE_INTERVAL {
+        return;
+    }
+    session.income_sampled_at = now;
+    if session.stats.exp_percent_per_hour.is_none() && session.stats.mesos_per_hour.is_none() {
+        return;
+    }
+    if session.stats.income_samples.len() >= MAX_INCOME_SAMPLES {
+        session.stats.income_samples.remove(0);
+    }
+    session.stats.income_samples.push(IncomeSample {
+        elapsed_millis: now.duration_since(session.started_at).as_millis() as u64,
+        exp_percent_per_hour: session.stats.exp_percent_per_hour,
+        mesos_per_hour: session.stats.mesos_per_hour,
+    });
+}
+
+fn session_summary(stats: &SessionStats, runtime: Duration, reason: SessionStopReason) -> String {
+    let secs = runtime.as_secs();
+    format!(
+        "Reason: {reason}\nRuntime: {}h {}m {}s\nActions executed: {}\nRunes solved: {}\nDeaths: {}",
+        secs / 3600,
+        secs % 3600 / 60,
+        secs % 60,
+        stats.completed_actions,
+        stats.solved_runes,
+        stats.deaths
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::context::Operation;
+
+    #[test]
+    fn update_keep_session_while_paused() {
+        let mut context = Context::new(None, None);
+        let mut player = PlayerState::default();
+        let mut tracker = SessionTracker::default();
+        let settings = Settings::default();
+
+        tracker.update(&context, &mut player, &settings);
+        assert!(tracker.session.is_some());
+
+        context.operation = Operation::Paused(None);
+        tracker.update(&context, &mut player, &settings);
+        assert!(tracker.session.is_some());
+    }
+
+    #[test]
+    fn session_summary_format() {
+        let stats = SessionStats {
+            completed_actions: 42,
+            solved_runes: 3,
+            deaths: 1,
+            ..SessionStats::default()
+        };
+
+        assert_eq!(
+            session_summary(
+                &stats,
+                Duration::from_secs(3 * 3600 + 25 * 60 + 7),
+                SessionStopReason::Cycle
+            ),
+            "Reason: Run/stop cycle\nRuntime: 3h 25m 7s\nActions executed: 42\nRunes solved: 3\nDeaths: 1"
+        );
+    }
+}
@@ -1,15 +1,34 @@
-use std::{fmt, time::Duration};
+use std::{
+    fmt,
+    sync::{Arc, LazyLock, OnceLock},
+    thread,
+    time::Duration,
+};
 
 use anyhow::{Error, Result};
+use log::debug;
 use tokio::{
     spawn,
-    sync::oneshot::{self, Receiver},
+    sync::{
+        Semaphore,
+        oneshot::{self, Receiver},
+    },
     task::spawn_blocking,
-    time::sleep,
+    time::{Instant, sleep},
 };
 
 use crate::{context::Context, detect::Detector};
 
+/// Bounds the number of model inference tasks (e.g. mobs detection) running at the same time.
+///
+/// Half of the available cores are used so that slow and less urgent ONNX inferences do not
+/// starve the update loop and the other detection tasks (e.g. minimap and health), which are not
+/// bounded.
+static INFERENCE_WORKERS: LazyLock<Semaphore> = LazyLock::new(|| {
+    let count = thread::available_parallelism().map_or(1, |count| (count.get() / 2).max(1));
+    Semaphore::new(count)
+});
+
 /// An asynchronous task.
 ///
 /// This is a simple wrapper around [`tokio::task::spawn`] and [`tokio::sync::oneshot`] mainly
@@ -18,6 +37,12 @@ use crate::{context::Context, detect::Detector};
 pub struct Task<T> {
     rx: Receiver<T>,
     completed: bool,
+    /// The instant the task started running after its repeat delay and waiting for a worker.
+    ///
+    /// Unset while the task is still waiting.
+    started_at: Arc<OnceLock<Instant>>,
+    /// Whether the task was spawned again because the previous result was stale.
+    stale_retry: bool,
 }
 
 impl<T: fmt::Debug> Task<T> {
@@ -33,6 +58,8 @@ impl<T: fmt::Debug> Task<T> {
         Task {
             rx,
             completed: false,
+            started_at: Arc::new(OnceLock::new()),
+            stale_retry: false,
         }
     }
 
@@ -71,20 +98,65 @@ where
     T: fmt::Debug + Send + 'static,
     A: Send + 'static,
 {
-    let update = match task.as_mut().and_then(|task| task.poll_inner()) {
+    update_task_with_budget(repeat_delay_millis, None, None, task, task_fn_args, task_fn)
+}
+
+/// Same as [`update_task`] but discards the result completed later than `budget` since the task
+/// started running and only runs when one of `workers` is available if provided.
+///
+/// A task with a discarded result is spawned again immediately without the repeat delay. To
+/// avoid never completing when the task always takes longer than `budget`, the result of the
+/// spawned again task is not discarded.
+fn update_task_with_budget<F, T, A>(
+    repeat_delay_millis: u64,
+    budget: Option<Duration>,
+    workers: Option<&'static Semaphore>,
+    task: &mut Option<Task<Result<T>>>,
+    task_fn_args: impl FnOnce() -> A,
+    task_fn: F,
+) -> Update<T>
+where
+    F: FnOnce(A) -> Result<T> + Send + 'static,
+    T: fmt::Debug + Send + 'static,
+    A: Send + 'static,
+{
+    let result = task.as_mut().and_then(|task| task.poll_inner());
+    let stale = result.is_some()
+        && task.as_ref().is_some_and(|task| {
+            !task.stale_retry
+                && budget.is_some_and(|budget| {
+                    task.started_at
+                        .get()
+                        .is_some_and(|started_at| started_at.elapsed() > budget)
+                })
+        });
+    let update = match result {
+        Some(_) if stale => {
+            debug!(target: "task", "discarded stale task result");
+            Update::Pending
+        }
         Some(Ok(value)) => Update::Ok(value),
         Some(Err(err)) => Update::Err(err),
         None => Update::Pending,
     };
     if matches!(update, Update::Pending) && task.as_ref().is_none_or(|task| task.completed) {
-        let has_delay = task.as_ref().is_some_and(|task| task.completed);
+        let has_delay = !stale && task.as_ref().is_some_and(|task| task.completed);
         let args = task_fn_args();
-        let spawned = Task::spawn(async move {
+        let started_at = Arc::new(OnceLock::new());
+        let task_started_at = started_at.clone();
+        let mut spawned = Task::spawn(async move {
             if has_delay {
                 sleep(Duration::from_millis(repeat_delay_millis)).await;
             }
+            let _permit = match workers {
+                Some(workers) => Some(workers.acquire().await.unwrap()),
+                None => None,
+            };
+            let _ = task_started_at.set(Instant::now());
             spawn_blocking(move || task_fn(args)).await.unwrap()
         });
+        spawned.started_at = started_at;
+        spawned.stale_retry = stale;
         *task = Some(spawned);
     }
     update
@@ -109,14 +181,90 @@ where
     )
 }
 
+/// Same as [`update_detection_task`] but discards the result detected later than `budget` since
+/// the detection started.
+///
+/// This is for detections whose result quickly becomes outdated (e.g. player health).
+#[inline]
+pub fn update_detection_task_with_budget<F, T>(
+    context: &Context,
+    repeat_delay_millis: u64,
+    budget: Duration,
+    task: &mut Option<Task<Result<T>>>,
+    task_fn: F,
+) -> Update<T>
+where
+    F: FnOnce(Box<dyn Detector>) -> Result<T> + Send + 'static,
+    T: fmt::Debug + Send + 'static,
+{
+    update_task_with_budget(
+        repeat_delay_millis,
+        Some(budget),
+        None,
+        task,
+        || context.detector_cloned_unwrap(),
+        task_fn,
+    )
+}
+
+/// Same as [`update_detection_task`] but for slow detections using a model (e.g. mobs and EXP).
+///
+/// The detection waits for one of the [`INFERENCE_WORKERS`] to be available.
+#[inline]
+pub fn update_inference_task<F, T>(
+    context: &Context,
+    repeat_delay_millis: u64,
+    task: &mut Option<Task<Result<T>>>,
+    task_fn: F,
+) -> Update<T>
+where
+    F: FnOnce(Box<dyn Detector>) -> Result<T> + Send + 'static,
+    T: fmt::Debug + Send + 'static,
+{
+    update_task_with_budget(
+        repeat_delay_millis,
+        None,
+        Some(&INFERENCE_WORKERS),
+        task,
+        || context.detector_cloned_unwrap(),
+        task_fn,
+    )
+}
+
+/// Same as [`update_inference_task`] but discards the result detected later than `budget` since
+/// the detection started after waiting for a worker.
+///
+/// This is for detections whose result quickly becomes outdated (e.g. mobs position).
+#[inline]
+pub fn update_inference_task_with_budget<F, T>(
+    context: &Context,
+    repeat_delay_millis: u64,
+    budget: Duration,
+    task: &mut Option<Task<Result<T>>>,
+    task_fn: F,
+) -> Update<T>
+where
+    F: FnOnce(Box<dyn Detector>) -> Result<T> + Send + 'static,
+    T: fmt::Debug + Send + 'static,
+{
+    update_task_with_budget(
+        repeat_delay_millis,
+        Some(budget),
+        Some(&INFERENCE_WORKERS),
+        task,
+        || context.detector_cloned_unwrap(),
+        task_fn,
+    )
+}
+
 #[cfg(test)]
 mod tests {
-    use std::assert_matches::assert_matches;
+    use std::{assert_matches::assert_matches, time::Duration};
 
     use anyhow::Result;
-    use tokio::task::yield_now;
+    use tokio::{sync::Semaphore, task::yield_now, time::sleep};
 
-    use crate::task::{Task, Update, update_task};
+    use crate::task::{Task, Update, update_task, update_task_with_budget};
 
     #[tokio::test(start_paused = true)]
     async fn spawn_state() {
@@ -160,4 +308,58 @@ mod tests {
         );
         assert!(!task.as_ref().unwrap().completed());
     }
+
+    #[tokio::test(start_paused = true)]
+    async fn update_task_with_budget_discard_stale_once() {
+        let budget = Some(Duration::from_millis(100));
+        let mut task = None::<Task<Result<u32>>>;
+
+        assert_matches!(
+            update_task_with_budget(1000, budget, None, &mut task, || (), |_| Ok(0)),
+            Update::Pending
+        );
+        sleep(Duration::from_millis(200)).await;
+        // Stale result is discarded and spawned again without delay
+        assert_matches!(
+            update_task_with_budget(1000, budget, None, &mut task, || (), |_| Ok(0)),
+            Update::Pending
+        );
+        assert!(task.as_ref().unwrap().stale_retry);
+        sleep(Duration::from_millis(200)).await;
+        // Stale result of the spawned again task is kept
+        assert_matches!(
+            update_task_with_budget(1000, budget, None, &mut task, || (), |_| Ok(0)),
+            Update::Ok(0)
+        );
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn update_task_with_budget_start_after_worker_acquired() {
+        static WORKERS: Semaphore = Semaphore::const_new(1);
+        let budget = Some(Duration::from_millis(100));
+        let mut task = None::<Task<Result<u32>>>;
+        let permit = WORKERS.acquire().await.unwrap();
+
+        assert_matches!(
+            update_task_with_budget(1000, budget, Some(&WORKERS), &mut task, || (), |_| Ok(0)),
+            Update::Pending
+        );
+        sleep(Duration::from_millis(200)).await;
+        // Waiting for a worker does not count toward the budget
+        assert!(task.as_ref().unwrap().started_at.get().is_none());
+        drop(permit);
+
+        loop {
+            match update_task_with_budget(1000, budget, Some(&WORKERS), &mut task, || (), |_| Ok(0))
+            {
+                Update::Ok(value) => {
+                    assert_eq!(value, 0);
+                    break;
+                }
+                Update::Pending => yield_now().await,
+                Update::Err(_) => unreachable!(),
+            }
+        }
+        assert!(!task.as_ref().unwrap().stale_retry);
+    }
 }
@@ -0,0 +1,64 @@
+use anyhow::Result;
+use log::info;
+
+use crate::{
+    context::Context,
+    network::NotificationKind,
+    task::{Task, Update, update_detection_task},
+};
+
+/// The delay between each whisper detection.
+const DETECT_DELAY_MILLIS: u64 = 3000;
+
+/// Watches the chat box for whispers and sends a notification for each new whisper.
+#[derive(Debug, Default)]
+pub struct WhisperWatcher {
+    task: Option<Task<Result<String>>>,
+    /// The last detected whisper line.
+    last_whisper: Option<String>,
+}
+
+impl WhisperWatcher {
+    /// Detects the latest whisper in the chat box if `enabled`.
+    pub fn update(&mut self, context: &Context, enabled: bool) {
+        if !enabled || context.detector.is_none() {
+            return;
+        }
+
+        if let Update::Ok(whisper) =
+            update_detection_task(context, DETECT_DELAY_MILLIS, &mut self.task, |detector| {
+                detector.detect_whisper()
+            })
+            && self.track(&whisper)
+        {
+            info!(target: "whisper", "sending whisper notification...");
+            let _ = context
+                .notification
+                .schedule_notification_with_details(NotificationKind::WhisperReceived, whisper);
+        }
+    }
+
+    /// Tracks the detected `whisper` line and returns `true` if it is a new whisper.
+    fn track(&mut self, whisper: &str) -> bool {
+        if self.last_whisper.as_deref() == Some(whisper) {
+            return false;
+        }
+        self.last_whisper = Some(whisper.to_string());
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn track_only_new_whisper() {
+        let mut watcher = WhisperWatcher::default();
+
+        assert!(watcher.track("Someone >> hello"));
+        assert!(!watcher.track("Someone >> hello"));
+        assert!(watcher.track("Someone >> are you there"));
+        assert!(watcher.track("Someone >> hello"));
+    }
+}
@@ -0,0 +1,92 @@
+use std::time::{Duration, Instant};
+
+use platforms::windows::{ClientRect, ClientRectQuery, Handle};
+
+/// The duration the client area must stay the same after changing before it is considered
+/// settled.
+///
+/// This avoids re-anchoring on every tick while the window is being dragged or resized.
+const SETTLE_DURATION: Duration = Duration::from_millis(500);
+
+/// Tracks the client area of the captured window for moves, resizes and switching between
+/// windowed and borderless.
+#[derive(Debug, Default)]
+pub struct WindowTracker {
+    query: Option<ClientRectQuery>,
+    /// The last known client area.
+    rect: Option<ClientRect>,
+    /// The instant the client area last changed and has not settled yet.
+    changed_at: Option<Instant>,
+}
+
+impl WindowTracker {
+    /// Queries the client area of `handle` and returns `true` once it has changed and settled.
+    pub fn update(&mut self, handle: Handle) -> bool {
+        if self
+            .query
+            .as_ref()
+            .is_none_or(|query| query.handle() != handle)
+        {
+            self.query = Some(ClientRectQuery::new(handle));
+            self.rect = None;
+            self.changed_at = None;
+        }
+
+        let rect = self.query.as_ref().unwrap().query();
+        self.track(rect, Instant::now())
+    }
+
+    fn track(&mut self, rect: Option<ClientRect>, now: Instant) -> bool {
+        // Keeps the last client area while the window is minimized or not found
+        let Some(rect) = rect else {
+            return false;
+        };
+        if self.rect.is_none() {
+            self.rect = Some(rect);
+            return false;
+        }
+        if self.rect != Some(rect) {
+            self.rect = Some(rect);
+            self.changed_at = Some(now);
+            return false;
+        }
+        if self
+            .changed_at
+            .is_some_and(|changed_at| now.duration_since(changed_at) >= SETTLE_DURATION)
+        {
+            self.changed_at = None;
+            return true;
+        }
+
+        false
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn track_changed_once_after_settled() {
+        let mut tracker = WindowTracker::default();
+        let now = Instant::now();
+        let rect = ClientRect {
+            x: 0,
+            y: 0,
+            width: 1366,
+            height: 768,
+        };
+        let moved = ClientRect { x: 100, ..rect };
+
+        assert!(!tracker.track(Some(rect), now));
+        assert!(!tracker.track(Some(rect), now + SETTLE_DURATION));
+
+        // Still moving
+        assert!(!tracker.track(Some(moved), now));
+        assert!(!tracker.track(None, now + SETTLE_DURATION));
+        assert!(!tracker.track(Some(moved), now + SETTLE_DURATION / 2));
+
+        assert!(tracker.track(Some(moved), now + SETTLE_DURATION));
+        assert!(!tracker.track(Some(moved), now + SETTLE_DURATION * 2));
+    }
+}
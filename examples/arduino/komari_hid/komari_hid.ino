@@ -0,0 +1,78 @@
+// Sketch for the `Serial` input method. Requires a board with native USB (e.g. Arduino
+// Leonardo/Pro Micro) and the AbsMouse library (https://github.com/jonathanedgecombe/absmouse)
+// for absolute mouse movement.
+#include <AbsMouse.h>
+#include <Keyboard.h>
+
+const long BAUD_RATE = 115200;
+// The bot normalizes mouse coordinates to 0..=65535
+const uint32_t MOUSE_RANGE = 65536;
+// Keyboard.press() treats values from 136 as raw HID usage IDs
+const uint8_t HID_USAGE_OFFSET = 136;
+
+char LINE[32];
+size_t LINE_LEN = 0;
+bool CONNECTED = false;
+
+void setup() {
+  Serial.begin(BAUD_RATE);
+  Keyboard.begin();
+  AbsMouse.init(MOUSE_RANGE, MOUSE_RANGE);
+}
+
+void loop() {
+  // Releases all keys when the bot closes the port so no key is stuck
+  bool connected = Serial.dtr();
+  if (CONNECTED && !connected) {
+    Keyboard.releaseAll();
+    LINE_LEN = 0;
+  }
+  CONNECTED = connected;
+
+  while (Serial.available()) {
+    char c = Serial.read();
+    if (c == '\n') {
+      LINE[LINE_LEN] = '\0';
+      handleLine(LINE);
+      LINE_LEN = 0;
+    } else if (LINE_LEN < sizeof(LINE) - 1) {
+      LINE[LINE_LEN++] = c;
+    }
+  }
+}
+
+/*
+  Handles a single command line written by the bot.
+*/
+void handleLine(char *line) {
+  char command = line[0];
+  char *args = line + 1;
+
+  switch (command) {
+    case 'D':
+      Keyboard.press(atoi(args) + HID_USAGE_OFFSET);
+      break;
+    case 'U':
+      Keyboard.release(atoi(args) + HID_USAGE_OFFSET);
+      break;
+    case 'M':
+      {
+        char *end;
+        long x = strtol(args, &end, 10);
+        long y = strtol(end, nullptr, 10);
+        AbsMouse.move(x, y);
+        break;
+      }
+    case 'C':
+      AbsMouse.press(MOUSE_LEFT);
+      delay(20);
+      AbsMouse.release(MOUSE_LEFT);
+      break;
+    case 'W':
+      // AbsMouse does not report the wheel, replace it with a mouse library that does if
+      // scrolling is needed (e.g. familiars swapping)
+      break;
+    default:
+      break;
+  }
+}
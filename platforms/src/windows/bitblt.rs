@@ -4,10 +4,12 @@ use std::ptr;
 use std::slice;
 
 use windows::Win32::Foundation::HWND;
+use windows::Win32::Foundation::POINT;
 use windows::Win32::Foundation::RECT;
 use windows::Win32::Graphics::Gdi::BI_BITFIELDS;
 use windows::Win32::Graphics::Gdi::BITMAPV4HEADER;
 use windows::Win32::Graphics::Gdi::BitBlt;
+use windows::Win32::Graphics::Gdi::ClientToScreen;
 use windows::Win32::Graphics::Gdi::CreateDCW;
 use windows::Win32::Graphics::Gdi::GetMonitorInfoW;
 use windows::Win32::Graphics::Gdi::MONITOR_DEFAULTTONULL;
@@ -61,6 +63,8 @@ pub struct BitBltCapture {
     handle: HandleCell,
     bitmap: Option<Bitmap>,
     overlap: bool,
+    /// The area relative to the client area of `handle` to capture as `(x, y, width, height)`.
+    area: Option<(i32, i32, i32, i32)>,
 }
 
 impl BitBltCapture {
@@ -74,6 +78,20 @@ impl BitBltCapture {
             handle: HandleCell::new(handle),
             bitmap: None,
             overlap,
+            area: None,
+        }
+    }
+
+    /// Creates a new `BitBlt` capture of an area inside `handle`
+    ///
+    /// The area is relative to the client area of `handle` and is captured from the monitor where
+    /// `handle` is in like when `overlap` is true.
+    pub fn new_area(handle: Handle, x: i32, y: i32, width: i32, height: i32) -> Self {
+        Self {
+            handle: HandleCell::new(handle),
+            bitmap: None,
+            overlap: true,
+            area: Some((x, y, width, height)),
         }
     }
 
@@ -82,16 +100,32 @@ impl BitBltCapture {
         self.grab_inner(None)
     }
 
+    /// Gets the area relative to the client area if this is an area capture.
+    pub fn area(&self) -> Option<(i32, i32, i32, i32)> {
+        self.area
+    }
+
+    /// Drops the bitmap so that it is recreated for the current window size on the next grab.
+    pub fn reset(&mut self) {
+        self.bitmap = None;
+    }
+
     pub(crate) fn grab_inner_offset(&mut self, offset: Option<(i32, i32)>) -> Result<Frame, Error> {
         self.grab_inner(offset)
     }
 
     fn grab_inner(&mut self, mut offset: Option<(i32, i32)>) -> Result<Frame, Error> {
         let handle = self.handle.as_inner().ok_or(Error::WindowNotFound)?;
-        let rect = get_rect(handle)?;
-        let width = rect.right - rect.left;
-        let height = rect.bottom - rect.top;
-        if width == 0 || height == 0 {
+        let (width, height) = if let Some((x, y, width, height)) = self.area {
+            let mut upper_left = POINT::default();
+            unsafe { ClientToScreen(handle, &raw mut upper_left).ok()? };
+            offset = Some((upper_left.x + x, upper_left.y + y));
+            (width, height)
+        } else {
+            let rect = get_rect(handle)?;
+            (rect.right - rect.left, rect.bottom - rect.top)
+        };
+        if width <= 0 || height <= 0 {
             return Err(Error::InvalidWindowSize);
         }
 
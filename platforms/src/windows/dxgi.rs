@@ -0,0 +1,345 @@
+//! Thanks https://learn.microsoft.com/en-us/windows/win32/direct3ddxgi/desktop-dup-api
+//! Thanks https://github.com/obsproject/obs-studio/blob/cfb23a51ff8acad13dc739c31854d9f451e05298/libobs-d3d11/d3d11-duplicator.cpp
+
+use std::slice;
+
+use windows::{
+    Win32::{
+        Foundation::{HMODULE, HWND, POINT, RECT},
+        Graphics::{
+            Direct3D::{
+                D3D_DRIVER_TYPE_UNKNOWN, D3D_FEATURE_LEVEL_10_0, D3D_FEATURE_LEVEL_10_1,
+                D3D_FEATURE_LEVEL_11_0,
+            },
+            Direct3D11::{
+                D3D11_BOX, D3D11_CPU_ACCESS_READ, D3D11_CREATE_DEVICE_BGRA_SUPPORT, D3D11_MAP_READ,
+                D3D11_MAPPED_SUBRESOURCE, D3D11_SDK_VERSION, D3D11_TEXTURE2D_DESC,
+                D3D11_USAGE_STAGING, D3D11CreateDevice, ID3D11Device, ID3D11DeviceContext,
+                ID3D11Texture2D,
+            },
+            Dxgi::{
+                Common::{DXGI_FORMAT, DXGI_SAMPLE_DESC},
+                CreateDXGIFactory1, DXGI_ERROR_ACCESS_LOST, DXGI_ERROR_NOT_FOUND,
+                DXGI_ERROR_WAIT_TIMEOUT, DXGI_OUTDUPL_FRAME_INFO, IDXGIAdapter1, IDXGIFactory1,
+                IDXGIOutput1, IDXGIOutputDuplication, IDXGIResource,
+            },
+            Gdi::{ClientToScreen, HMONITOR, MONITOR_DEFAULTTONULL, MonitorFromWindow},
+        },
+        UI::WindowsAndMessaging::GetClientRect,
+    },
+    core::Interface,
+};
+
+use super::{Error, Frame, Handle, HandleCell};
+
+#[derive(Debug)]
+struct DesktopDuplicationInner {
+    monitor: HMONITOR,
+    /// The bounds of the duplicated monitor in desktop coordinates.
+    monitor_rect: RECT,
+    d3d11_context: ID3D11DeviceContext,
+    d3d11_device: ID3D11Device,
+    /// The staging texture holding the window area of the last acquired frame.
+    d3d11_texture: Option<ID3D11Texture2D>,
+    duplication: IDXGIOutputDuplication,
+}
+
+impl DesktopDuplicationInner {
+    fn grab_with_timeout(&mut self, handle: HWND, timeout: u32) -> Result<Frame, Error> {
+        let mut frame_info = DXGI_OUTDUPL_FRAME_INFO::default();
+        let mut resource = None::<IDXGIResource>;
+        let result = unsafe {
+            self.duplication
+                .AcquireNextFrame(timeout, &raw mut frame_info, &raw mut resource)
+        };
+        match result {
+            Ok(()) => {
+                let result = self.copy_window_area(handle, resource.as_ref());
+                let _ = unsafe { self.duplication.ReleaseFrame() };
+                result?;
+            }
+            // The desktop only produces a new frame when something on the monitor changed, so the
+            // last copied window area is reused
+            Err(error) if error.code() == DXGI_ERROR_WAIT_TIMEOUT => {
+                if self.d3d11_texture.is_none() {
+                    return Err(Error::FrameNotAvailable);
+                }
+            }
+            Err(error) => return Err(Error::from(error)),
+        }
+
+        self.read_texture()
+    }
+
+    fn copy_window_area(
+        &mut self,
+        handle: HWND,
+        resource: Option<&IDXGIResource>,
+    ) -> Result<(), Error> {
+        let surface_texture = resource
+            .ok_or(Error::FrameNotAvailable)?
+            .cast::<ID3D11Texture2D>()?;
+        let mut surface_desc = D3D11_TEXTURE2D_DESC::default();
+        unsafe { surface_texture.GetDesc(&raw mut surface_desc) };
+
+        let texture_rect = get_client_rect(handle, self.monitor_rect)?;
+        let texture_width = texture_rect.right - texture_rect.left;
+        let texture_height = texture_rect.bottom - texture_rect.top;
+        if self.d3d11_texture.as_ref().is_none_or(|texture| {
+            let mut texture_desc = D3D11_TEXTURE2D_DESC::default();
+            unsafe {
+                texture.GetDesc(&raw mut texture_desc);
+            };
+            texture_desc.Width != texture_width || texture_desc.Height != texture_height
+        }) {
+            self.d3d11_texture = Some(create_texture_2d(
+                &self.d3d11_device,
+                texture_width,
+                texture_height,
+                surface_desc.Format,
+            )?);
+        }
+
+        unsafe {
+            self.d3d11_context.CopySubresourceRegion(
+                self.d3d11_texture.as_ref().unwrap(),
+                0,
+                0,
+                0,
+                0,
+                &surface_texture,
+                0,
+                Some(&raw const texture_rect),
+            );
+        }
+        Ok(())
+    }
+
+    fn read_texture(&self) -> Result<Frame, Error> {
+        let texture = self.d3d11_texture.as_ref().unwrap();
+        let mut texture_desc = D3D11_TEXTURE2D_DESC::default();
+        unsafe { texture.GetDesc(&raw mut texture_desc) };
+        let texture_width = texture_desc.Width;
+        let texture_height = texture_desc.Height;
+
+        let mut resource = D3D11_MAPPED_SUBRESOURCE::default();
+        unsafe {
+            self.d3d11_context
+                .Map(texture, 0, D3D11_MAP_READ, 0, Some(&raw mut resource))?;
+        };
+        let buffer = unsafe {
+            slice::from_raw_parts::<u8>(
+                resource.pData.cast(),
+                (texture_height * resource.RowPitch) as usize,
+            )
+        };
+        let dst_stride = (texture_width * 4) as usize;
+        let vec = if dst_stride != resource.RowPitch as usize {
+            let mut vec = Vec::<u8>::with_capacity(dst_stride * texture_height as usize);
+            for i in 0..texture_height as usize {
+                let src_offset = resource.RowPitch as usize * i;
+                vec.extend_from_slice(&buffer[src_offset..src_offset + dst_stride]);
+            }
+            vec
+        } else {
+            buffer.to_vec()
+        };
+        unsafe {
+            self.d3d11_context.Unmap(texture, 0);
+        };
+
+        Ok(Frame {
+            width: texture_width as i32,
+            height: texture_height as i32,
+            data: vec,
+        })
+    }
+}
+
+/// Captures the window area from the monitor the window is on using DXGI desktop duplication.
+///
+/// Like `BitBlt` with overlap, the content of other windows on top of the window is also
+/// captured. This is useful when `BitBlt` only returns black frames and Windows Graphics Capture
+/// draws a border or stops working in fullscreen.
+#[derive(Debug)]
+pub struct DesktopDuplicationCapture {
+    handle: HandleCell,
+    frame_timeout: u32,
+    inner: Option<DesktopDuplicationInner>,
+}
+
+impl DesktopDuplicationCapture {
+    pub fn new(handle: Handle, frame_timeout: u64) -> Self {
+        Self {
+            handle: HandleCell::new(handle),
+            frame_timeout: frame_timeout as u32,
+            inner: None,
+        }
+    }
+
+    pub fn grab(&mut self) -> Result<Frame, Error> {
+        let handle = self.handle.as_inner().ok_or(Error::WindowNotFound)?;
+        let monitor = unsafe { MonitorFromWindow(handle, MONITOR_DEFAULTTONULL) };
+        if monitor.is_invalid() {
+            self.stop_capture();
+            return Err(Error::WindowNotFound);
+        }
+        // The window moved to a different monitor
+        if self
+            .inner
+            .as_ref()
+            .is_some_and(|inner| inner.monitor != monitor)
+        {
+            self.stop_capture();
+        }
+        if self.inner.is_none() {
+            self.inner = Some(start_capture(monitor)?);
+        }
+
+        let result = self
+            .inner
+            .as_mut()
+            .unwrap()
+            .grab_with_timeout(handle, self.frame_timeout);
+        // Access is lost on mode change, fullscreen switch, UAC prompt, etc. and must be recreated
+        if let Err(Error::Win32(code, _)) = result.as_ref()
+            && *code == DXGI_ERROR_ACCESS_LOST.0 as u32
+        {
+            self.stop_capture();
+            return Err(Error::FrameNotAvailable);
+        }
+        result
+    }
+
+    pub fn stop_capture(&mut self) {
+        let _ = self.inner.take();
+    }
+}
+
+fn start_capture(monitor: HMONITOR) -> Result<DesktopDuplicationInner, Error> {
+    let factory = unsafe { CreateDXGIFactory1::<IDXGIFactory1>()? };
+    let (adapter, output, monitor_rect) = find_output(&factory, monitor)?;
+    let (d3d11_device, d3d11_context) = create_d3d11_device(&adapter)?;
+    let duplication = unsafe { output.DuplicateOutput(&d3d11_device)? };
+
+    Ok(DesktopDuplicationInner {
+        monitor,
+        monitor_rect,
+        d3d11_context,
+        d3d11_device,
+        d3d11_texture: None,
+        duplication,
+    })
+}
+
+/// Finds the adapter and output of `monitor` along with its desktop coordinates.
+#[inline]
+fn find_output(
+    factory: &IDXGIFactory1,
+    monitor: HMONITOR,
+) -> Result<(IDXGIAdapter1, IDXGIOutput1, RECT), Error> {
+    let mut adapter_index = 0;
+    loop {
+        let adapter = match unsafe { factory.EnumAdapters1(adapter_index) } {
+            Ok(adapter) => adapter,
+            Err(error) if error.code() == DXGI_ERROR_NOT_FOUND => {
+                return Err(Error::WindowNotFound);
+            }
+            Err(error) => return Err(Error::from(error)),
+        };
+        let mut output_index = 0;
+        while let Ok(output) = unsafe { adapter.EnumOutputs(output_index) } {
+            let desc = unsafe { output.GetDesc()? };
+            if desc.Monitor == monitor {
+                return Ok((adapter, output.cast()?, desc.DesktopCoordinates));
+            }
+            output_index += 1;
+        }
+        adapter_index += 1;
+    }
+}
+
+/// Gets the client area of `handle` relative to and clamped inside `monitor_rect`.
+#[inline]
+fn get_client_rect(handle: HWND, monitor_rect: RECT) -> Result<D3D11_BOX, Error> {
+    let mut client_rect = RECT::default();
+    unsafe { GetClientRect(handle, &raw mut client_rect)? };
+    let mut upper_left = POINT::default();
+    unsafe { ClientToScreen(handle, &raw mut upper_left).ok()? };
+
+    let left = upper_left.x.max(monitor_rect.left);
+    let top = upper_left.y.max(monitor_rect.top);
+    let right = (upper_left.x + client_rect.right).min(monitor_rect.right);
+    let bottom = (upper_left.y + client_rect.bottom).min(monitor_rect.bottom);
+    if right <= left || bottom <= top {
+        return Err(Error::InvalidWindowSize);
+    }
+
+    Ok(D3D11_BOX {
+        left: (left - monitor_rect.left) as u32,
+        top: (top - monitor_rect.top) as u32,
+        right: (right - monitor_rect.left) as u32,
+        bottom: (bottom - monitor_rect.top) as u32,
+        front: 0,
+        back: 1,
+    })
+}
+
+#[inline]
+fn create_texture_2d(
+    device: &ID3D11Device,
+    width: u32,
+    height: u32,
+    format: DXGI_FORMAT,
+) -> Result<ID3D11Texture2D, Error> {
+    let mut texture = None;
+    let texture_desc = D3D11_TEXTURE2D_DESC {
+        Width: width,
+        Height: height,
+        MipLevels: 1,
+        ArraySize: 1,
+        Format: format,
+        SampleDesc: DXGI_SAMPLE_DESC {
+            Count: 1,
+            Quality: 0,
+        },
+        Usage: D3D11_USAGE_STAGING,
+        BindFlags: 0,
+        CPUAccessFlags: D3D11_CPU_ACCESS_READ.0 as u32,
+        MiscFlags: 0,
+    };
+    unsafe {
+        device.CreateTexture2D(&texture_desc, None, Some(&mut texture))?;
+    }
+    Ok(texture.unwrap())
+}
+
+/// Creates the device on `adapter` since the duplication must be created from the adapter the
+/// output is attached to.
+#[inline]
+fn create_d3d11_device(
+    adapter: &IDXGIAdapter1,
+) -> Result<(ID3D11Device, ID3D11DeviceContext), Error> {
+    let feature_flags = [
+        D3D_FEATURE_LEVEL_11_0,
+        D3D_FEATURE_LEVEL_10_1,
+        D3D_FEATURE_LEVEL_10_0,
+    ];
+    let mut d3d_device = None;
+    let mut feature_level = D3D_FEATURE_LEVEL_10_0;
+    let mut d3d_device_context = None;
+    unsafe {
+        D3D11CreateDevice(
+            adapter,
+            D3D_DRIVER_TYPE_UNKNOWN,
+            HMODULE::default(),
+            D3D11_CREATE_DEVICE_BGRA_SUPPORT,
+            Some(&feature_flags),
+            D3D11_SDK_VERSION,
+            Some(&mut d3d_device),
+            Some(&mut feature_level),
+            Some(&mut d3d_device_context),
+        )?
+    };
+    Ok((d3d_device.unwrap(), d3d_device_context.unwrap()))
+}
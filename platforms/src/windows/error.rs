@@ -13,6 +13,8 @@ pub enum Error {
     FrameNotAvailable,
     #[error("key not found")]
     KeyNotFound,
+    #[error("interception driver is not installed or interception.dll is not found")]
+    InterceptionNotAvailable,
     #[error("win32 API error {0}: {1}")]
     Win32(u32, String),
 }
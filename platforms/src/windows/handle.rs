@@ -2,11 +2,14 @@ use std::{cell::Cell, ffi::OsString, os::windows::ffi::OsStringExt, ptr, str};
 
 use windows::{
     Win32::{
-        Foundation::{HWND, LPARAM},
-        Graphics::Dwm::{DWMWA_CLOAKED, DwmGetWindowAttribute},
+        Foundation::{HWND, LPARAM, POINT, RECT},
+        Graphics::{
+            Dwm::{DWMWA_CLOAKED, DwmGetWindowAttribute},
+            Gdi::ClientToScreen,
+        },
         UI::WindowsAndMessaging::{
-            EnumWindows, GWL_EXSTYLE, GWL_STYLE, GetClassNameW, GetWindowLongPtrW, GetWindowTextW,
-            IsWindowVisible, WS_DISABLED, WS_EX_TOOLWINDOW,
+            EnumWindows, GWL_EXSTYLE, GWL_STYLE, GetClassNameW, GetClientRect, GetWindowLongPtrW,
+            GetWindowTextW, IsIconic, IsWindowVisible, WS_DISABLED, WS_EX_TOOLWINDOW,
         },
     },
     core::BOOL,
@@ -103,6 +106,64 @@ impl Handle {
     }
 }
 
+/// The client area of a window in screen coordinates.
+#[derive(Clone, Copy, PartialEq, Eq, Default, Debug)]
+pub struct ClientRect {
+    pub x: i32,
+    pub y: i32,
+    pub width: i32,
+    pub height: i32,
+}
+
+/// Queries the client area of a [`Handle`] for tracking when the window moves or resizes.
+#[derive(Debug)]
+pub struct ClientRectQuery {
+    handle: Handle,
+    cell: HandleCell,
+}
+
+impl ClientRectQuery {
+    pub fn new(handle: Handle) -> Self {
+        Self {
+            handle,
+            cell: HandleCell::new(handle),
+        }
+    }
+
+    pub fn handle(&self) -> Handle {
+        self.handle
+    }
+
+    /// Queries the current client area.
+    ///
+    /// Returns [`None`] if the window cannot be found, is minimized or has an empty client area.
+    pub fn query(&self) -> Option<ClientRect> {
+        let handle = self.cell.as_inner()?;
+        if unsafe { IsIconic(handle) }.as_bool() {
+            return None;
+        }
+
+        let mut rect = RECT::default();
+        unsafe { GetClientRect(handle, &raw mut rect) }.ok()?;
+        let mut upper_left = POINT::default();
+        unsafe { ClientToScreen(handle, &raw mut upper_left) }
+            .ok()
+            .ok()?;
+        let width = rect.right - rect.left;
+        let height = rect.bottom - rect.top;
+        if width <= 0 || height <= 0 {
+            return None;
+        }
+
+        Some(ClientRect {
+            x: upper_left.x,
+            y: upper_left.y,
+            width,
+            height,
+        })
+    }
+}
+
 pub fn query_capture_handles() -> Vec<(String, Handle)> {
     unsafe extern "system" fn callback(handle: HWND, params: LPARAM) -> BOOL {
         if !unsafe { IsWindowVisible(handle) }.as_bool() {
@@ -0,0 +1,142 @@
+use std::{
+    mem,
+    sync::{
+        LazyLock, Mutex,
+        atomic::{AtomicU32, Ordering},
+    },
+};
+
+use tokio::sync::broadcast::{self, Receiver, Sender};
+use windows::Win32::{
+    Foundation::{LPARAM, WPARAM},
+    System::Threading::GetCurrentThreadId,
+    UI::{
+        Input::KeyboardAndMouse::{
+            HOT_KEY_MODIFIERS, MOD_ALT, MOD_CONTROL, MOD_NOREPEAT, MOD_SHIFT, RegisterHotKey,
+            UnregisterHotKey, VIRTUAL_KEY,
+        },
+        WindowsAndMessaging::{MSG, PostThreadMessageW, WM_APP, WM_HOTKEY},
+    },
+};
+
+use super::keys::KeyKind;
+
+/// The thread message sent to the message loop thread to apply [`PENDING_HOTKEYS`].
+const WM_UPDATE_HOTKEYS: u32 = WM_APP + 1;
+
+static HOTKEY_CHANNEL: LazyLock<Sender<i32>> = LazyLock::new(|| broadcast::channel(4).0);
+
+/// The hotkeys waiting to be registered by the message loop thread.
+static PENDING_HOTKEYS: Mutex<Option<Vec<(i32, Hotkey)>>> = Mutex::new(None);
+
+/// The id of the message loop thread or `0` if it has not started.
+static MESSAGE_LOOP_THREAD_ID: AtomicU32 = AtomicU32::new(0);
+
+/// A system-wide key combination.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct Hotkey {
+    pub key: KeyKind,
+    pub ctrl: bool,
+    pub alt: bool,
+    pub shift: bool,
+}
+
+/// Receives the ids of the triggered hotkeys registered through [`register_hotkeys`].
+#[derive(Debug)]
+pub struct HotkeyReceiver {
+    rx: Receiver<i32>,
+}
+
+impl Default for HotkeyReceiver {
+    fn default() -> Self {
+        Self {
+            rx: HOTKEY_CHANNEL.subscribe(),
+        }
+    }
+}
+
+impl HotkeyReceiver {
+    pub fn try_recv(&mut self) -> Option<i32> {
+        self.rx.try_recv().ok()
+    }
+}
+
+/// Replaces all currently registered hotkeys with `hotkeys` keyed by their ids.
+///
+/// Unlike keys received through [`super::KeyReceiver`], hotkeys are triggered in any window and
+/// are not passed to the foreground window. A hotkey already registered by another application
+/// is skipped.
+pub fn register_hotkeys(hotkeys: Vec<(i32, Hotkey)>) {
+    *PENDING_HOTKEYS.lock().unwrap() = Some(hotkeys);
+    let thread_id = MESSAGE_LOOP_THREAD_ID.load(Ordering::Acquire);
+    if thread_id != 0 {
+        unsafe {
+            let _ = PostThreadMessageW(thread_id, WM_UPDATE_HOTKEYS, WPARAM(0), LPARAM(0));
+        }
+    }
+}
+
+/// Tracks the hotkeys registered on the message loop thread.
+#[derive(Debug, Default)]
+pub(crate) struct Hotkeys {
+    registered: Vec<i32>,
+}
+
+impl Hotkeys {
+    /// Initializes hotkeys on the calling message loop thread.
+    pub(crate) fn init() -> Self {
+        MESSAGE_LOOP_THREAD_ID.store(unsafe { GetCurrentThreadId() }, Ordering::Release);
+        let mut hotkeys = Hotkeys::default();
+        // Applies the hotkeys registered before the message loop thread started
+        hotkeys.update();
+        hotkeys
+    }
+
+    /// Handles `msg` if it is a hotkey message.
+    ///
+    /// Returns `true` if `msg` has been handled.
+    pub(crate) fn handle_message(&mut self, msg: &MSG) -> bool {
+        match msg.message {
+            WM_HOTKEY => {
+                let _ = HOTKEY_CHANNEL.send(msg.wParam.0 as i32);
+                true
+            }
+            WM_UPDATE_HOTKEYS => {
+                self.update();
+                true
+            }
+            _ => false,
+        }
+    }
+
+    fn update(&mut self) {
+        let Some(hotkeys) = PENDING_HOTKEYS.lock().unwrap().take() else {
+            return;
+        };
+        for id in mem::take(&mut self.registered) {
+            unsafe {
+                let _ = UnregisterHotKey(None, id);
+            }
+        }
+        for (id, hotkey) in hotkeys {
+            let key = VIRTUAL_KEY::from(hotkey.key);
+            if unsafe { RegisterHotKey(None, id, modifiers(hotkey), key.0 as u32) }.is_ok() {
+                self.registered.push(id);
+            }
+        }
+    }
+}
+
+fn modifiers(hotkey: Hotkey) -> HOT_KEY_MODIFIERS {
+    let mut modifiers = MOD_NOREPEAT;
+    if hotkey.ctrl {
+        modifiers |= MOD_CONTROL;
+    }
+    if hotkey.alt {
+        modifiers |= MOD_ALT;
+    }
+    if hotkey.shift {
+        modifiers |= MOD_SHIFT;
+    }
+    modifiers
+}
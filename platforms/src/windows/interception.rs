@@ -0,0 +1,203 @@
+use std::{ffi::c_void, mem, sync::LazyLock};
+
+use windows::{
+    Win32::System::LibraryLoader::{GetProcAddress, LoadLibraryW},
+    core::{s, w},
+};
+
+use super::error::Error;
+
+/// The first keyboard device of the Interception driver.
+const INTERCEPTION_KEYBOARD_FIRST: i32 = 1;
+
+/// Maximum number of keyboard devices of the Interception driver.
+const INTERCEPTION_MAX_KEYBOARD: i32 = 10;
+
+/// The first mouse device of the Interception driver.
+const INTERCEPTION_MOUSE_FIRST: i32 = INTERCEPTION_KEYBOARD_FIRST + INTERCEPTION_MAX_KEYBOARD;
+
+/// Maximum number of mouse devices of the Interception driver.
+const INTERCEPTION_MAX_MOUSE: i32 = 10;
+
+const INTERCEPTION_KEY_DOWN: u16 = 0x00;
+const INTERCEPTION_KEY_UP: u16 = 0x01;
+const INTERCEPTION_KEY_E0: u16 = 0x02;
+
+const INTERCEPTION_MOUSE_LEFT_BUTTON_DOWN: u16 = 0x001;
+const INTERCEPTION_MOUSE_LEFT_BUTTON_UP: u16 = 0x002;
+const INTERCEPTION_MOUSE_WHEEL: u16 = 0x400;
+
+const INTERCEPTION_MOUSE_MOVE_ABSOLUTE: u16 = 0x001;
+const INTERCEPTION_MOUSE_VIRTUAL_DESKTOP: u16 = 0x002;
+
+/// The Interception driver loaded from `interception.dll`, [`None`] if not available.
+static INTERCEPTION: LazyLock<Option<Interception>> = LazyLock::new(Interception::load);
+
+type ProcFn = unsafe extern "system" fn() -> isize;
+type CreateContextFn = unsafe extern "C" fn() -> *mut c_void;
+type SendFn = unsafe extern "C" fn(*mut c_void, i32, *const c_void, u32) -> i32;
+type GetHardwareIdFn = unsafe extern "C" fn(*mut c_void, i32, *mut c_void, u32) -> u32;
+
+#[repr(C)]
+struct InterceptionKeyStroke {
+    code: u16,
+    state: u16,
+    information: u32,
+}
+
+#[repr(C)]
+struct InterceptionMouseStroke {
+    state: u16,
+    flags: u16,
+    rolling: i16,
+    x: i32,
+    y: i32,
+    information: u32,
+}
+
+/// The mouse button state of [`send_interception_mouse`].
+#[derive(Clone, Copy, Debug)]
+pub(crate) enum InterceptionMouseState {
+    Move,
+    LeftDown,
+    LeftUp,
+    Scroll(i16),
+}
+
+/// A context of the Interception driver that sends strokes below the `SendInput` level.
+///
+/// The context is never destroyed since it lives for the whole process.
+struct Interception {
+    context: usize,
+    send: SendFn,
+    keyboard: i32,
+    mouse: i32,
+}
+
+impl Interception {
+    fn load() -> Option<Interception> {
+        let module = unsafe { LoadLibraryW(w!("interception.dll")) }.ok()?;
+        let create_context = unsafe { GetProcAddress(module, s!("interception_create_context")) }?;
+        let send = unsafe { GetProcAddress(module, s!("interception_send")) }?;
+        let get_hardware_id =
+            unsafe { GetProcAddress(module, s!("interception_get_hardware_id")) }?;
+        let (create_context, send, get_hardware_id) = unsafe {
+            (
+                mem::transmute::<ProcFn, CreateContextFn>(create_context),
+                mem::transmute::<ProcFn, SendFn>(send),
+                mem::transmute::<ProcFn, GetHardwareIdFn>(get_hardware_id),
+            )
+        };
+
+        // Null if the driver is not installed
+        let context = unsafe { create_context() };
+        if context.is_null() {
+            return None;
+        }
+        // Uses the first device that is connected or the first device if none is found
+        let find_device = |first: i32, count: i32| {
+            (first..first + count)
+                .find(|device| {
+                    let mut buf = [0u16; 500];
+                    let len = unsafe {
+                        get_hardware_id(
+                            context,
+                            *device,
+                            buf.as_mut_ptr().cast(),
+                            mem::size_of_val(&buf) as u32,
+                        )
+                    };
+                    len > 0
+                })
+                .unwrap_or(first)
+        };
+        let keyboard = find_device(INTERCEPTION_KEYBOARD_FIRST, INTERCEPTION_MAX_KEYBOARD);
+        let mouse = find_device(INTERCEPTION_MOUSE_FIRST, INTERCEPTION_MAX_MOUSE);
+
+        Some(Interception {
+            context: context as usize,
+            send,
+            keyboard,
+            mouse,
+        })
+    }
+
+    fn send<T>(&self, device: i32, stroke: &T) -> Result<(), Error> {
+        let sent = unsafe {
+            (self.send)(
+                self.context as *mut c_void,
+                device,
+                (stroke as *const T).cast(),
+                1,
+            )
+        };
+        if sent > 0 {
+            Ok(())
+        } else {
+            Err(Error::KeyNotSent)
+        }
+    }
+}
+
+/// Whether the Interception driver is installed and `interception.dll` can be loaded.
+pub fn is_interception_available() -> bool {
+    INTERCEPTION.is_some()
+}
+
+/// Sends a key stroke of `scan_code` through the Interception driver.
+pub(crate) fn send_interception_key(
+    scan_code: u16,
+    is_extended: bool,
+    is_down: bool,
+) -> Result<(), Error> {
+    let interception = INTERCEPTION
+        .as_ref()
+        .ok_or(Error::InterceptionNotAvailable)?;
+    let mut state = if is_down {
+        INTERCEPTION_KEY_DOWN
+    } else {
+        INTERCEPTION_KEY_UP
+    };
+    if is_extended {
+        state |= INTERCEPTION_KEY_E0;
+    }
+
+    interception.send(
+        interception.keyboard,
+        &InterceptionKeyStroke {
+            code: scan_code,
+            state,
+            information: 0,
+        },
+    )
+}
+
+/// Sends a mouse stroke at the normalized absolute virtual desktop coordinate `(dx, dy)` through
+/// the Interception driver.
+pub(crate) fn send_interception_mouse(
+    dx: i32,
+    dy: i32,
+    state: InterceptionMouseState,
+) -> Result<(), Error> {
+    let interception = INTERCEPTION
+        .as_ref()
+        .ok_or(Error::InterceptionNotAvailable)?;
+    let (state, rolling) = match state {
+        InterceptionMouseState::Move => (0, 0),
+        InterceptionMouseState::LeftDown => (INTERCEPTION_MOUSE_LEFT_BUTTON_DOWN, 0),
+        InterceptionMouseState::LeftUp => (INTERCEPTION_MOUSE_LEFT_BUTTON_UP, 0),
+        InterceptionMouseState::Scroll(rolling) => (INTERCEPTION_MOUSE_WHEEL, rolling),
+    };
+
+    interception.send(
+        interception.mouse,
+        &InterceptionMouseStroke {
+            state,
+            flags: INTERCEPTION_MOUSE_MOVE_ABSOLUTE | INTERCEPTION_MOUSE_VIRTUAL_DESKTOP,
+            rolling,
+            x: dx,
+            y: dy,
+            information: 0,
+        },
+    )
+}
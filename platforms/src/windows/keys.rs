@@ -1,7 +1,8 @@
 use std::{
     cell::RefCell,
     mem::{self, size_of},
-    sync::LazyLock,
+    rc::Rc,
+    sync::{LazyLock, Mutex, PoisonError},
     thread,
     time::Duration,
 };
@@ -31,21 +32,43 @@ use windows::{
                 VK_SPACE, VK_T, VK_U, VK_UP, VK_V, VK_W, VK_X, VK_Y, VK_Z,
             },
             WindowsAndMessaging::{
-                CallNextHookEx, GetForegroundWindow, GetSystemMetrics, GetWindowRect,
-                GetWindowThreadProcessId, HC_ACTION, HHOOK, KBDLLHOOKSTRUCT, LLKHF_INJECTED,
-                LLKHF_LOWER_IL_INJECTED, SM_CXVIRTUALSCREEN, SM_CYVIRTUALSCREEN, SM_XVIRTUALSCREEN,
-                SM_YVIRTUALSCREEN, SetWindowsHookExW, WH_KEYBOARD_LL, WM_KEYDOWN, WM_KEYUP,
+                CallNextHookEx, GetClientRect, GetCursorPos, GetForegroundWindow, GetSystemMetrics,
+                GetWindowRect, GetWindowThreadProcessId, HC_ACTION, HHOOK, KBDLLHOOKSTRUCT,
+                LLKHF_INJECTED, LLKHF_LOWER_IL_INJECTED, SM_CXVIRTUALSCREEN, SM_CYVIRTUALSCREEN,
+                SM_XVIRTUALSCREEN, SM_YVIRTUALSCREEN, SetWindowsHookExW, WH_KEYBOARD_LL,
+                WM_KEYDOWN, WM_KEYUP,
             },
         },
     },
     core::Owned,
 };
 
-use super::{HandleCell, error::Error, handle::Handle};
+use super::{
+    HandleCell,
+    error::Error,
+    handle::Handle,
+    interception::{
+        InterceptionMouseState, is_interception_available, send_interception_key,
+        send_interception_mouse,
+    },
+    kmbox::KmBoxNet,
+    serial::SerialPort,
+};
 
 static KEY_CHANNEL: LazyLock<Sender<KeyKind>> = LazyLock::new(|| broadcast::channel(1).0);
 static PROCESS_ID: LazyLock<u32> = LazyLock::new(|| unsafe { GetCurrentProcessId() });
 
+/// The keys currently held down by any [`Keys`] indexed by [`VIRTUAL_KEY`].
+static HELD_KEYS: LazyLock<Mutex<BitVec>> =
+    LazyLock::new(|| Mutex::new(BitVec::from_elem(256, false)));
+
+/// The scan codes of the key up strokes sent through the Interception driver or a hardware device
+/// that have not been seen by the keyboard hook yet.
+///
+/// Unlike `SendInput`, the strokes cannot be tagged with [`PROCESS_ID`] and would otherwise be
+/// received as keys pressed by the user.
+static PENDING_KEY_UPS: LazyLock<Mutex<Vec<u16>>> = LazyLock::new(|| Mutex::new(Vec::new()));
+
 pub(crate) fn init() -> Owned<HHOOK> {
     unsafe extern "system" fn keyboard_ll(code: i32, wparam: WPARAM, lparam: LPARAM) -> LRESULT {
         let msg = wparam.0 as u32;
@@ -55,7 +78,9 @@ pub(crate) fn init() -> Owned<HHOOK> {
             let vkey = unsafe { mem::transmute::<u16, VIRTUAL_KEY>(key.vkCode as u16) };
             let key_kind = KeyKind::try_from(vkey);
             let ignore = key.dwExtraInfo == *PROCESS_ID as usize;
+            let ignore_pending = !ignore && msg == WM_KEYUP && is_pending_key_up(key.scanCode);
             if !ignore
+                && !ignore_pending
                 && msg == WM_KEYUP
                 && let Ok(key) = key_kind
             {
@@ -74,6 +99,30 @@ pub(crate) fn init() -> Owned<HHOOK> {
     unsafe { Owned::new(SetWindowsHookExW(WH_KEYBOARD_LL, Some(keyboard_ll), None, 0).unwrap()) }
 }
 
+/// Releases all keys currently held down by any [`Keys`].
+///
+/// This is intended as a last resort when the process is exiting or has crashed, where the
+/// [`Keys`] holding the keys can no longer be used.
+pub fn release_held_keys() {
+    let mut held_keys = HELD_KEYS.lock().unwrap_or_else(PoisonError::into_inner);
+    let held = held_keys
+        .iter()
+        .enumerate()
+        .filter_map(|(i, down)| down.then_some(i))
+        .collect::<Vec<_>>();
+    held_keys.clear();
+    for i in held {
+        let key = VIRTUAL_KEY(i as u16);
+        let (scan_code, is_extended) = to_scan_code(key);
+        let _ = send_input(to_input(key, scan_code, is_extended, false));
+        if is_interception_available() {
+            let _ = with_pending_key_up(scan_code, false, || {
+                send_interception_key(scan_code, is_extended, false)
+            });
+        }
+    }
+}
+
 #[derive(Debug)]
 pub struct ConvertedCoordinates {
     pub width: i32,
@@ -99,10 +148,19 @@ impl KeyReceiver {
     }
 
     pub fn try_recv(&mut self) -> Option<KeyKind> {
+        self.try_recv_with_foreground()
+            .and_then(|(key, can_process)| can_process.then_some(key))
+    }
+
+    /// Receives a key regardless of the foreground window.
+    ///
+    /// The returned `bool` indicates whether the key would have been accepted by
+    /// [`Self::try_recv`]. Useful for global hotkeys that must work in any window.
+    pub fn try_recv_with_foreground(&mut self) -> Option<(KeyKind, bool)> {
         self.rx
             .try_recv()
             .ok()
-            .and_then(|key| self.can_process_key().then_some(key))
+            .map(|key| (key, self.can_process_key()))
     }
 
     // TODO: Is this good?
@@ -134,7 +192,20 @@ pub enum KeyInputKind {
 pub struct Keys {
     handle: HandleCell,
     key_input_kind: KeyInputKind,
+    /// The window that must be in the foreground for inputs to be sent, [`None`] to only check
+    /// with [`KeyInputKind`].
+    focus_guard: Option<HandleCell>,
     key_down: RefCell<BitVec>,
+    output: KeysOutput,
+}
+
+/// Where [`Keys`] sends the inputs to.
+#[derive(Debug, Clone)]
+enum KeysOutput {
+    SendInput,
+    Interception,
+    Serial(Rc<SerialPort>),
+    KmBox(Rc<KmBoxNet>),
 }
 
 #[derive(Debug)]
@@ -224,7 +295,39 @@ impl Keys {
         Self {
             handle: HandleCell::new(handle),
             key_input_kind: kind,
+            focus_guard: None,
             key_down: RefCell::new(BitVec::from_elem(256, false)),
+            output: KeysOutput::SendInput,
+        }
+    }
+
+    /// Creates a new [`Keys`] that sends keys and mouse through the Interception driver.
+    ///
+    /// This is for clients that ignore inputs sent by `SendInput`. Sending fails with
+    /// [`Error::InterceptionNotAvailable`] if the driver is not installed.
+    pub fn new_interception(handle: Handle, kind: KeyInputKind) -> Self {
+        Self {
+            output: KeysOutput::Interception,
+            ..Self::new(handle, kind)
+        }
+    }
+
+    /// Creates a new [`Keys`] that sends keys and mouse to a microcontroller acting as a USB
+    /// keyboard and mouse through `port`.
+    ///
+    /// See [`SerialPort`] for the commands written to the port.
+    pub fn new_serial(handle: Handle, kind: KeyInputKind, port: SerialPort) -> Self {
+        Self {
+            output: KeysOutput::Serial(Rc::new(port)),
+            ..Self::new(handle, kind)
+        }
+    }
+
+    /// Creates a new [`Keys`] that sends keys and mouse through a KMBox Net `device`.
+    pub fn new_kmbox(handle: Handle, kind: KeyInputKind, device: KmBoxNet) -> Self {
+        Self {
+            output: KeysOutput::KmBox(Rc::new(device)),
+            ..Self::new(handle, kind)
         }
     }
 
@@ -234,7 +337,17 @@ impl Keys {
         Ok(())
     }
 
-    pub fn send_mouse(&self, x: i32, y: i32, action: MouseAction) -> Result<(), Error> {
+    /// Sends mouse to `(x, y)` relative to the client area and performs `action`.
+    ///
+    /// If `frame_size` is provided, `(x, y)` is in a captured frame of that size and is scaled to
+    /// the client area.
+    pub fn send_mouse(
+        &self,
+        x: i32,
+        y: i32,
+        frame_size: Option<(i32, i32)>,
+        action: MouseAction,
+    ) -> Result<(), Error> {
         #[inline]
         fn mouse_input(dx: i32, dy: i32, flags: MOUSE_EVENT_FLAGS, data: i32) -> [INPUT; 1] {
             [INPUT {
@@ -252,14 +365,65 @@ impl Keys {
         }
 
         let mut handle = self.get_handle()?;
-        if !is_foreground(handle, self.key_input_kind) {
+        if !self.is_input_allowed(handle) {
             return Err(Error::WindowNotFound);
         }
-        if matches!(self.key_input_kind, KeyInputKind::Foreground) {
+        let (x, y) = if matches!(self.key_input_kind, KeyInputKind::Foreground) {
+            // The frame is captured from the capture area window instead of the foreground window
             handle = unsafe { GetForegroundWindow() };
+            (x, y)
+        } else {
+            frame_to_client_coordinate(handle, x, y, frame_size)?
+        };
+
+        if let KeysOutput::KmBox(device) = &self.output {
+            let mut point = POINT { x, y };
+            let mut cursor = POINT::default();
+            unsafe {
+                ClientToScreen(handle, &raw mut point).ok()?;
+                GetCursorPos(&raw mut cursor)?;
+            }
+            device.send_mouse_move(point.x - cursor.x, point.y - cursor.y)?;
+            return match action {
+                MouseAction::Move => Ok(()),
+                MouseAction::Click => {
+                    device.send_mouse_left(true)?;
+                    thread::sleep(Duration::from_millis(80));
+                    device.send_mouse_left(false)
+                }
+                MouseAction::Scroll => device.send_mouse_scroll(-3),
+            };
         }
 
         let (dx, dy) = client_to_absolute_coordinate_raw(handle, x, y)?;
+        match &self.output {
+            KeysOutput::SendInput => (),
+            KeysOutput::Interception => {
+                return match action {
+                    MouseAction::Move => {
+                        send_interception_mouse(dx, dy, InterceptionMouseState::Move)
+                    }
+                    MouseAction::Click => {
+                        send_interception_mouse(dx, dy, InterceptionMouseState::LeftDown)?;
+                        thread::sleep(Duration::from_millis(80));
+                        send_interception_mouse(dx, dy, InterceptionMouseState::LeftUp)
+                    }
+                    MouseAction::Scroll => {
+                        send_interception_mouse(dx, dy, InterceptionMouseState::Scroll(-300))
+                    }
+                };
+            }
+            KeysOutput::Serial(port) => {
+                port.send_mouse_move(dx, dy)?;
+                return match action {
+                    MouseAction::Move => Ok(()),
+                    MouseAction::Click => port.send_mouse_click(),
+                    MouseAction::Scroll => port.send_mouse_scroll(-3),
+                };
+            }
+            // Moved relatively above
+            KeysOutput::KmBox(_) => unreachable!(),
+        }
         let base_flags = MOUSEEVENTF_ABSOLUTE | MOUSEEVENTF_MOVE | MOUSEEVENTF_VIRTUALDESK;
 
         match action {
@@ -284,14 +448,61 @@ impl Keys {
         self.send_input(kind, true)
     }
 
+    /// Releases all keys currently held down by this [`Keys`].
+    ///
+    /// Unlike [`Self::send_up`], this does not require a valid handle.
+    pub fn release_all(&self) -> Result<(), Error> {
+        let mut key_down = self.key_down.borrow_mut();
+        let held = key_down
+            .iter()
+            .enumerate()
+            .filter_map(|(i, down)| down.then_some(i))
+            .collect::<Vec<_>>();
+        key_down.clear();
+        let mut held_keys = HELD_KEYS.lock().unwrap_or_else(PoisonError::into_inner);
+        for i in held.iter().copied() {
+            held_keys.set(i, false);
+        }
+        drop(held_keys);
+        // Keeps releasing the remaining keys even if one fails
+        held.into_iter().fold(Ok(()), |result, i| {
+            let key = VIRTUAL_KEY(i as u16);
+            result.and(self.send_key_input(key, false))
+        })
+    }
+
+    /// Only sends inputs when `handle` is the foreground window.
+    ///
+    /// With [`KeyInputKind::Foreground`], inputs are otherwise sent to any window on top of
+    /// [`Keys::handle`] (e.g. a browser when alt-tabbing).
+    pub fn set_focus_guard(&mut self, handle: Option<Handle>) {
+        self.focus_guard = handle.map(HandleCell::new);
+    }
+
+    /// Whether the handle is in the foreground so that keys can be sent.
+    pub fn is_foreground(&self) -> bool {
+        self.get_handle()
+            .map(|handle| self.is_input_allowed(handle))
+            .unwrap_or_default()
+    }
+
+    #[inline]
+    fn is_input_allowed(&self, handle: HWND) -> bool {
+        is_foreground(handle, self.key_input_kind)
+            && self.focus_guard.as_ref().is_none_or(|guard| {
+                guard
+                    .as_inner()
+                    .is_some_and(|guard| guard == unsafe { GetForegroundWindow() })
+            })
+    }
+
     #[inline]
     fn send_input(&self, kind: KeyKind, is_down: bool) -> Result<(), Error> {
         let handle = self.get_handle()?;
-        if is_down && !is_foreground(handle, self.key_input_kind) {
+        if is_down && !self.is_input_allowed(handle) {
             return Err(Error::KeyNotSent);
         }
         let key = kind.into();
-        let (scan_code, is_extended) = to_scan_code(key);
         let mut key_down = self.key_down.borrow_mut();
         // SAFETY: VIRTUAL_KEY is from range 0..254 (inclusive) and BitVec
         // was initialized with 256 elements
@@ -300,9 +511,33 @@ impl Keys {
             (true, true) | (false, false) => return Err(Error::KeyNotSent),
             _ => {
                 key_down.set(key.0 as usize, is_down);
+                HELD_KEYS
+                    .lock()
+                    .unwrap_or_else(PoisonError::into_inner)
+                    .set(key.0 as usize, is_down);
+            }
+        }
+        drop(key_down);
+        self.send_key_input(key, is_down)
+    }
+
+    #[inline]
+    fn send_key_input(&self, key: VIRTUAL_KEY, is_down: bool) -> Result<(), Error> {
+        let (scan_code, is_extended) = to_scan_code(key);
+        match &self.output {
+            KeysOutput::SendInput => send_input(to_input(key, scan_code, is_extended, is_down)),
+            KeysOutput::Interception => with_pending_key_up(scan_code, is_down, || {
+                send_interception_key(scan_code, is_extended, is_down)
+            }),
+            KeysOutput::Serial(port) => {
+                let kind = KeyKind::try_from(key)?;
+                with_pending_key_up(scan_code, is_down, || port.send_key(kind, is_down))
+            }
+            KeysOutput::KmBox(device) => {
+                let kind = KeyKind::try_from(key)?;
+                with_pending_key_up(scan_code, is_down, || device.send_key(kind, is_down))
             }
         }
-        send_input(to_input(key, scan_code, is_extended, is_down))
     }
 
     #[inline]
@@ -468,13 +703,19 @@ impl From<KeyKind> for VIRTUAL_KEY {
     }
 }
 
+/// Converts `(x, y)` relative to the client area of `handle` to the monitor or window coordinate.
+///
+/// If `frame_size` is provided, `(x, y)` is in a captured frame of that size and is scaled to the
+/// client area first.
 pub fn client_to_monitor_or_frame(
     handle: Handle,
     x: i32,
     y: i32,
+    frame_size: Option<(i32, i32)>,
     monitor_coordinate: bool,
 ) -> Result<ConvertedCoordinates, Error> {
     let handle = handle.query_handle().ok_or(Error::WindowNotFound)?;
+    let (x, y) = frame_to_client_coordinate(handle, x, y, frame_size)?;
     let mut point = POINT { x, y };
     unsafe { ClientToScreen(handle, &raw mut point).ok()? };
 
@@ -520,21 +761,53 @@ pub fn client_to_monitor_or_frame(
     })
 }
 
+/// Scales `(x, y)` in a captured frame of `frame_size` to the client area of `handle`.
+///
+/// The captured frame can have a different size than the client area in physical pixels. For
+/// example, `BitBlt` captures a DPI-unaware game at its unscaled size while the game is shown
+/// stretched on a monitor with 125% scaling.
+fn frame_to_client_coordinate(
+    handle: HWND,
+    x: i32,
+    y: i32,
+    frame_size: Option<(i32, i32)>,
+) -> Result<(i32, i32), Error> {
+    let Some((frame_width, frame_height)) =
+        frame_size.filter(|(width, height)| *width > 0 && *height > 0)
+    else {
+        return Ok((x, y));
+    };
+    let mut rect = RECT::default();
+    unsafe { GetClientRect(handle, &raw mut rect)? };
+    let client_width = rect.right - rect.left;
+    let client_height = rect.bottom - rect.top;
+    if client_width == frame_width && client_height == frame_height {
+        return Ok((x, y));
+    }
+
+    let x = x as i64 * client_width as i64 / frame_width as i64;
+    let y = y as i64 * client_height as i64 / frame_height as i64;
+    Ok((x as i32, y as i32))
+}
+
 fn client_to_absolute_coordinate_raw(handle: HWND, x: i32, y: i32) -> Result<(i32, i32), Error> {
     let mut point = POINT { x, y };
     unsafe { ClientToScreen(handle, &raw mut point).ok()? };
 
+    // The virtual screen spans all monitors and can start at negative coordinates when a
+    // monitor is on the left or above the primary monitor
     let virtual_left = unsafe { GetSystemMetrics(SM_XVIRTUALSCREEN) };
     let virtual_top = unsafe { GetSystemMetrics(SM_YVIRTUALSCREEN) };
     let virtual_width = unsafe { GetSystemMetrics(SM_CXVIRTUALSCREEN) };
     let virtual_height = unsafe { GetSystemMetrics(SM_CYVIRTUALSCREEN) };
-    if virtual_width == 0 || virtual_height == 0 {
+    if virtual_width <= 1 || virtual_height <= 1 {
         return Err(Error::InvalidWindowSize);
     }
 
-    let dx = (point.x - virtual_left) * 65536 / virtual_width;
-    let dy = (point.y - virtual_top) * 65536 / virtual_height;
-    Ok((dx, dy))
+    // Normalized to 0..=65535 where 65535 is the last pixel
+    let dx = (point.x - virtual_left) as i64 * 65535 / (virtual_width - 1) as i64;
+    let dy = (point.y - virtual_top) as i64 * 65535 / (virtual_height - 1) as i64;
+    Ok((dx as i32, dy as i32))
 }
 
 // TODO: Is this good?
@@ -588,6 +861,46 @@ fn send_input(input: [INPUT; 1]) -> Result<(), Error> {
     }
 }
 
+/// Sends the key with `send` and tracks the key up of `scan_code` for the keyboard hook.
+#[inline]
+fn with_pending_key_up(
+    scan_code: u16,
+    is_down: bool,
+    send: impl FnOnce() -> Result<(), Error>,
+) -> Result<(), Error> {
+    if !is_down {
+        PENDING_KEY_UPS
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .push(scan_code);
+    }
+    let result = send();
+    if result.is_err() && !is_down {
+        PENDING_KEY_UPS
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .pop();
+    }
+    result
+}
+
+/// Whether the key up of `scan_code` is sent by [`with_pending_key_up`].
+#[inline]
+fn is_pending_key_up(scan_code: u32) -> bool {
+    let mut pending = PENDING_KEY_UPS
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner);
+    if let Some(index) = pending
+        .iter()
+        .position(|pending| *pending as u32 == scan_code)
+    {
+        pending.remove(index);
+        true
+    } else {
+        false
+    }
+}
+
 #[inline]
 fn to_scan_code(key: VIRTUAL_KEY) -> (u16, bool) {
     let scan_code = unsafe { MapVirtualKeyW(key.0 as u32, MAPVK_VK_TO_VSC_EX) } as u16;
@@ -0,0 +1,177 @@
+use std::{
+    cell::{Cell, RefCell},
+    net::UdpSocket,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use super::{Error, KeyKind, serial::to_hid_usage};
+
+const CMD_CONNECT: u32 = 0xAF3C2828;
+const CMD_MOUSE_MOVE: u32 = 0xAEDE7345;
+const CMD_MOUSE_LEFT: u32 = 0x9823AE8D;
+const CMD_MOUSE_WHEEL: u32 = 0xFFEEAD38;
+const CMD_KEYBOARD_ALL: u32 = 0x123C2C2F;
+
+/// Maximum milliseconds to wait for the device to acknowledge a command.
+const ACK_TIMEOUT_MILLIS: u64 = 100;
+
+/// The first HID usage ID of the modifier keys (left control).
+const HID_USAGE_MODIFIER_FIRST: u8 = 0xE0;
+
+/// Maximum number of non-modifier keys the device can hold down at once.
+const MAX_KEYBOARD_BUTTONS: usize = 10;
+
+/// The keyboard report sent to the device with [`CMD_KEYBOARD_ALL`].
+#[derive(Debug, Default)]
+struct KmBoxKeyboard {
+    /// The bit mask of the held down modifier keys.
+    modifiers: u8,
+    /// The HID usage IDs of the held down keys, `0` if the slot is empty.
+    buttons: [u8; MAX_KEYBOARD_BUTTONS],
+}
+
+/// A KMBox Net device controlled through its UDP protocol.
+///
+/// The device sits between the physical keyboard/mouse and the game PC so inputs are sent as
+/// hardware inputs. The device only supports relative mouse movement, so the mouse is moved by
+/// the distance from the current cursor position.
+///
+/// The device is connected on the first command and reconnected after a command is not
+/// acknowledged so that the device can be restarted without restarting.
+#[derive(Debug)]
+pub struct KmBoxNet {
+    address: String,
+    uuid: u32,
+    socket: RefCell<Option<UdpSocket>>,
+    /// The index of the last command that must increase with each command.
+    index: Cell<u32>,
+    keyboard: RefCell<KmBoxKeyboard>,
+}
+
+impl KmBoxNet {
+    /// Creates a KMBox Net device at `address` (e.g. `192.168.2.188:8808`) with `uuid`.
+    ///
+    /// `uuid` is the 8 hexadecimal characters shown on the device screen. The device will not
+    /// respond if it does not match.
+    pub fn new(address: String, uuid: &str) -> Self {
+        Self {
+            address,
+            uuid: u32::from_str_radix(uuid.trim(), 16).unwrap_or_default(),
+            socket: RefCell::new(None),
+            index: Cell::new(0),
+            keyboard: RefCell::new(KmBoxKeyboard::default()),
+        }
+    }
+
+    pub(crate) fn send_key(&self, kind: KeyKind, is_down: bool) -> Result<(), Error> {
+        let usage = to_hid_usage(kind);
+        let mut keyboard = self.keyboard.borrow_mut();
+        if usage >= HID_USAGE_MODIFIER_FIRST {
+            let bit = 1 << (usage - HID_USAGE_MODIFIER_FIRST);
+            if is_down {
+                keyboard.modifiers |= bit;
+            } else {
+                keyboard.modifiers &= !bit;
+            }
+        } else if is_down {
+            if !keyboard.buttons.contains(&usage) {
+                let slot = keyboard
+                    .buttons
+                    .iter_mut()
+                    .find(|button| **button == 0)
+                    .ok_or(Error::KeyNotSent)?;
+                *slot = usage;
+            }
+        } else if let Some(slot) = keyboard.buttons.iter_mut().find(|button| **button == usage) {
+            *slot = 0;
+        }
+
+        let mut payload = Vec::with_capacity(2 + MAX_KEYBOARD_BUTTONS);
+        payload.extend([keyboard.modifiers, 0]);
+        payload.extend(keyboard.buttons);
+        self.send_command(CMD_KEYBOARD_ALL, &payload)
+    }
+
+    pub(crate) fn send_mouse_move(&self, dx: i32, dy: i32) -> Result<(), Error> {
+        self.send_mouse_command(CMD_MOUSE_MOVE, 0, dx, dy, 0)
+    }
+
+    pub(crate) fn send_mouse_left(&self, is_down: bool) -> Result<(), Error> {
+        self.send_mouse_command(CMD_MOUSE_LEFT, is_down as i32, 0, 0, 0)
+    }
+
+    pub(crate) fn send_mouse_scroll(&self, amount: i32) -> Result<(), Error> {
+        self.send_mouse_command(CMD_MOUSE_WHEEL, 0, 0, 0, amount)
+    }
+
+    fn send_mouse_command(
+        &self,
+        command: u32,
+        button: i32,
+        x: i32,
+        y: i32,
+        wheel: i32,
+    ) -> Result<(), Error> {
+        // The mouse report is the button, x, y, wheel and 10 unused points
+        let mut payload = Vec::with_capacity(14 * 4);
+        for value in [button, x, y, wheel] {
+            payload.extend(value.to_le_bytes());
+        }
+        payload.resize(14 * 4, 0);
+        self.send_command(command, &payload)
+    }
+
+    fn send_command(&self, command: u32, payload: &[u8]) -> Result<(), Error> {
+        let mut socket = self.socket.borrow_mut();
+        if socket.is_none() {
+            *socket = Some(self.connect()?);
+        }
+
+        let result = self.send_and_ack(socket.as_ref().unwrap(), command, payload);
+        if result.is_err() {
+            // Reconnects on the next command in case the device was restarted
+            *socket = None;
+        }
+        result
+    }
+
+    fn connect(&self) -> Result<UdpSocket, Error> {
+        let socket = UdpSocket::bind("0.0.0.0:0").map_err(|_| Error::KeyNotSent)?;
+        socket
+            .connect(&self.address)
+            .map_err(|_| Error::KeyNotSent)?;
+        socket
+            .set_read_timeout(Some(Duration::from_millis(ACK_TIMEOUT_MILLIS)))
+            .map_err(|_| Error::KeyNotSent)?;
+        self.index.set(0);
+        self.send_and_ack(&socket, CMD_CONNECT, &[])?;
+
+        Ok(socket)
+    }
+
+    fn send_and_ack(&self, socket: &UdpSocket, command: u32, payload: &[u8]) -> Result<(), Error> {
+        let index = self.index.get().wrapping_add(1);
+        self.index.set(index);
+        let rand = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|duration| duration.subsec_nanos())
+            .unwrap_or_default();
+
+        let mut packet = Vec::with_capacity(16 + payload.len());
+        for value in [self.uuid, rand, index, command] {
+            packet.extend(value.to_le_bytes());
+        }
+        packet.extend_from_slice(payload);
+        socket.send(&packet).map_err(|_| Error::KeyNotSent)?;
+
+        // The device echoes the header back once the command is executed, skips the late echoes
+        // of the previous commands until timed out
+        let mut ack = [0u8; 1024];
+        loop {
+            let len = socket.recv(&mut ack).map_err(|_| Error::KeyNotSent)?;
+            if len >= 16 && ack[8..16] == packet[8..16] {
+                return Ok(());
+            }
+        }
+    }
+}
@@ -6,18 +6,29 @@ use std::{
     thread,
 };
 
-use windows::Win32::UI::WindowsAndMessaging::{
-    DispatchMessageW, GetMessageW, MSG, TranslateMessage,
+use windows::Win32::UI::{
+    HiDpi::{DPI_AWARENESS_CONTEXT_PER_MONITOR_AWARE_V2, SetProcessDpiAwarenessContext},
+    WindowsAndMessaging::{DispatchMessageW, GetMessageW, MSG, TranslateMessage},
 };
 
 mod bitblt;
+mod dxgi;
 mod error;
 mod handle;
+mod hotkey;
+mod interception;
 mod keys;
+mod kmbox;
+mod power;
+mod serial;
+mod time;
 mod wgc;
 mod window_box;
 
-pub use {bitblt::*, error::*, handle::*, keys::*, wgc::*, window_box::*};
+pub use {
+    bitblt::*, dxgi::*, error::*, handle::*, hotkey::*, interception::is_interception_available,
+    keys::*, kmbox::*, power::*, serial::*, time::*, wgc::*, window_box::*,
+};
 
 #[derive(Clone, Debug)]
 pub struct Frame {
@@ -26,20 +37,36 @@ pub struct Frame {
     pub data: Vec<u8>,
 }
 
+unsafe extern "C" {
+    fn atexit(callback: extern "C" fn()) -> i32;
+}
+
 pub fn init() {
+    extern "C" fn release_held_keys_on_exit() {
+        keys::release_held_keys();
+    }
+
     static INITIALIZED: AtomicBool = AtomicBool::new(false);
 
     if INITIALIZED
         .compare_exchange(false, true, Ordering::SeqCst, Ordering::Acquire)
         .is_ok()
     {
+        // Window, capture and mouse coordinates must all be in physical pixels regardless of the
+        // monitor scaling. This fails if the awareness is already set by the manifest.
+        let _ =
+            unsafe { SetProcessDpiAwarenessContext(DPI_AWARENESS_CONTEXT_PER_MONITOR_AWARE_V2) };
         let barrier = Arc::new(Barrier::new(2));
         let keys_barrier = barrier.clone();
         thread::spawn(move || {
             let _hook = keys::init();
+            let mut hotkeys = hotkey::Hotkeys::init();
             let mut msg = MSG::default();
             keys_barrier.wait();
             while unsafe { GetMessageW(&raw mut msg, None, 0, 0) }.as_bool() {
+                if hotkeys.handle_message(&msg) {
+                    continue;
+                }
                 unsafe {
                     let _ = TranslateMessage(&raw const msg);
                     let _ = DispatchMessageW(&raw const msg);
@@ -47,5 +74,9 @@ pub fn init() {
             }
         });
         barrier.wait();
+        // Keys held down must not stay held after the process exits
+        unsafe {
+            atexit(release_held_keys_on_exit);
+        }
     }
 }
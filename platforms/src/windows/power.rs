@@ -0,0 +1,20 @@
+use windows::Win32::System::Power::{
+    ES_CONTINUOUS, ES_DISPLAY_REQUIRED, ES_SYSTEM_REQUIRED, SetThreadExecutionState,
+};
+
+/// Prevents the system from sleeping and the display from turning off.
+///
+/// This only applies while the calling thread is alive or until [`allow_sleep`] is called from
+/// the same thread.
+pub fn prevent_sleep() {
+    unsafe {
+        SetThreadExecutionState(ES_CONTINUOUS | ES_SYSTEM_REQUIRED | ES_DISPLAY_REQUIRED);
+    }
+}
+
+/// Restores the normal power behavior previously changed by [`prevent_sleep`].
+pub fn allow_sleep() {
+    unsafe {
+        SetThreadExecutionState(ES_CONTINUOUS);
+    }
+}
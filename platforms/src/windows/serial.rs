@@ -0,0 +1,210 @@
+use std::{cell::RefCell, mem::size_of};
+
+use windows::{
+    Win32::{
+        Devices::Communication::{
+            COMMTIMEOUTS, DCB, GetCommState, NOPARITY, ONESTOPBIT, SetCommState, SetCommTimeouts,
+        },
+        Foundation::{GENERIC_WRITE, HANDLE},
+        Storage::FileSystem::{
+            CreateFileW, FILE_ATTRIBUTE_NORMAL, FILE_SHARE_NONE, OPEN_EXISTING, WriteFile,
+        },
+    },
+    core::{HSTRING, Owned},
+};
+
+use super::{Error, KeyKind};
+
+/// Maximum milliseconds a write can take before failing.
+const WRITE_TIMEOUT_MILLIS: u32 = 100;
+
+/// `fBinary` bit of [`DCB`].
+const DCB_BINARY: u32 = 1;
+
+/// `fDtrControl` bits of [`DCB`] set to `DTR_CONTROL_ENABLE`.
+///
+/// Boards with native USB (e.g. Arduino Leonardo and Raspberry Pi Pico) only receive data while
+/// DTR is enabled.
+const DCB_DTR_CONTROL_ENABLE: u32 = 1 << 4;
+
+/// A serial port to a microcontroller acting as a USB keyboard and mouse.
+///
+/// Each command is written as a text line:
+/// - `D <usage>` and `U <usage>` to press and release the key with the HID usage ID `<usage>`
+/// - `M <x> <y>` to move the mouse to the absolute virtual desktop coordinate `(x, y)`
+///   normalized to `0..=65535`
+/// - `C` to click the left mouse button
+/// - `W <amount>` to scroll the mouse wheel by `<amount>`, negative to scroll down
+///
+/// The port is opened on the first write and reopened after a failed write so that the device
+/// can be reconnected without restarting.
+#[derive(Debug)]
+pub struct SerialPort {
+    port: String,
+    baud_rate: u32,
+    handle: RefCell<Option<Owned<HANDLE>>>,
+}
+
+impl SerialPort {
+    /// Creates a serial port to `port` (e.g. `COM3`) with `baud_rate`.
+    pub fn new(port: String, baud_rate: u32) -> Self {
+        Self {
+            port,
+            baud_rate,
+            handle: RefCell::new(None),
+        }
+    }
+
+    pub(crate) fn send_key(&self, kind: KeyKind, is_down: bool) -> Result<(), Error> {
+        let command = if is_down { "D" } else { "U" };
+        self.write_line(&format!("{command} {}", to_hid_usage(kind)))
+    }
+
+    pub(crate) fn send_mouse_move(&self, dx: i32, dy: i32) -> Result<(), Error> {
+        self.write_line(&format!("M {dx} {dy}"))
+    }
+
+    pub(crate) fn send_mouse_click(&self) -> Result<(), Error> {
+        self.write_line("C")
+    }
+
+    pub(crate) fn send_mouse_scroll(&self, amount: i32) -> Result<(), Error> {
+        self.write_line(&format!("W {amount}"))
+    }
+
+    fn write_line(&self, line: &str) -> Result<(), Error> {
+        let mut handle = self.handle.borrow_mut();
+        if handle.is_none() {
+            *handle = Some(self.open()?);
+        }
+
+        let bytes = format!("{line}\n");
+        let mut written = 0;
+        let result = unsafe {
+            WriteFile(
+                **handle.as_ref().unwrap(),
+                Some(bytes.as_bytes()),
+                Some(&raw mut written),
+                None,
+            )
+        };
+        if result.is_err() || written as usize != bytes.len() {
+            // Reopens on the next write in case the device was reconnected
+            *handle = None;
+            return Err(Error::KeyNotSent);
+        }
+
+        Ok(())
+    }
+
+    fn open(&self) -> Result<Owned<HANDLE>, Error> {
+        let path = HSTRING::from(format!(r"\\.\{}", self.port));
+        let handle = unsafe {
+            Owned::new(CreateFileW(
+                &path,
+                GENERIC_WRITE.0,
+                FILE_SHARE_NONE,
+                None,
+                OPEN_EXISTING,
+                FILE_ATTRIBUTE_NORMAL,
+                None,
+            )?)
+        };
+
+        let mut dcb = DCB {
+            DCBlength: size_of::<DCB>() as u32,
+            ..DCB::default()
+        };
+        unsafe { GetCommState(*handle, &raw mut dcb)? };
+        dcb.BaudRate = self.baud_rate;
+        dcb.ByteSize = 8;
+        dcb.Parity = NOPARITY;
+        dcb.StopBits = ONESTOPBIT;
+        dcb._bitfield = DCB_BINARY | DCB_DTR_CONTROL_ENABLE;
+        unsafe { SetCommState(*handle, &raw const dcb)? };
+
+        let timeouts = COMMTIMEOUTS {
+            WriteTotalTimeoutConstant: WRITE_TIMEOUT_MILLIS,
+            ..COMMTIMEOUTS::default()
+        };
+        unsafe { SetCommTimeouts(*handle, &raw const timeouts)? };
+
+        Ok(handle)
+    }
+}
+
+/// Converts `kind` to the USB HID keyboard usage ID.
+pub(crate) fn to_hid_usage(kind: KeyKind) -> u8 {
+    match kind {
+        KeyKind::A => 0x04,
+        KeyKind::B => 0x05,
+        KeyKind::C => 0x06,
+        KeyKind::D => 0x07,
+        KeyKind::E => 0x08,
+        KeyKind::F => 0x09,
+        KeyKind::G => 0x0A,
+        KeyKind::H => 0x0B,
+        KeyKind::I => 0x0C,
+        KeyKind::J => 0x0D,
+        KeyKind::K => 0x0E,
+        KeyKind::L => 0x0F,
+        KeyKind::M => 0x10,
+        KeyKind::N => 0x11,
+        KeyKind::O => 0x12,
+        KeyKind::P => 0x13,
+        KeyKind::Q => 0x14,
+        KeyKind::R => 0x15,
+        KeyKind::S => 0x16,
+        KeyKind::T => 0x17,
+        KeyKind::U => 0x18,
+        KeyKind::V => 0x19,
+        KeyKind::W => 0x1A,
+        KeyKind::X => 0x1B,
+        KeyKind::Y => 0x1C,
+        KeyKind::Z => 0x1D,
+        KeyKind::One => 0x1E,
+        KeyKind::Two => 0x1F,
+        KeyKind::Three => 0x20,
+        KeyKind::Four => 0x21,
+        KeyKind::Five => 0x22,
+        KeyKind::Six => 0x23,
+        KeyKind::Seven => 0x24,
+        KeyKind::Eight => 0x25,
+        KeyKind::Nine => 0x26,
+        KeyKind::Zero => 0x27,
+        KeyKind::Enter => 0x28,
+        KeyKind::Esc => 0x29,
+        KeyKind::Space => 0x2C,
+        KeyKind::Semicolon => 0x33,
+        KeyKind::Quote => 0x34,
+        KeyKind::Tilde => 0x35,
+        KeyKind::Comma => 0x36,
+        KeyKind::Period => 0x37,
+        KeyKind::Slash => 0x38,
+        KeyKind::F1 => 0x3A,
+        KeyKind::F2 => 0x3B,
+        KeyKind::F3 => 0x3C,
+        KeyKind::F4 => 0x3D,
+        KeyKind::F5 => 0x3E,
+        KeyKind::F6 => 0x3F,
+        KeyKind::F7 => 0x40,
+        KeyKind::F8 => 0x41,
+        KeyKind::F9 => 0x42,
+        KeyKind::F10 => 0x43,
+        KeyKind::F11 => 0x44,
+        KeyKind::F12 => 0x45,
+        KeyKind::Insert => 0x49,
+        KeyKind::Home => 0x4A,
+        KeyKind::PageUp => 0x4B,
+        KeyKind::Delete => 0x4C,
+        KeyKind::End => 0x4D,
+        KeyKind::PageDown => 0x4E,
+        KeyKind::Right => 0x4F,
+        KeyKind::Left => 0x50,
+        KeyKind::Down => 0x51,
+        KeyKind::Up => 0x52,
+        KeyKind::Ctrl => 0xE0,
+        KeyKind::Shift => 0xE1,
+        KeyKind::Alt => 0xE2,
+    }
+}
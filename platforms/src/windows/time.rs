@@ -0,0 +1,21 @@
+use windows::Win32::System::SystemInformation::GetLocalTime;
+
+/// The current local time of day.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct LocalTime {
+    /// Day of the week starting from 0 for Sunday.
+    pub day_of_week: u32,
+    pub hour: u32,
+    pub minute: u32,
+}
+
+/// Retrieves the current local time of day.
+pub fn local_time() -> LocalTime {
+    let time = unsafe { GetLocalTime() };
+
+    LocalTime {
+        day_of_week: time.wDayOfWeek as u32,
+        hour: time.wHour as u32,
+        minute: time.wMinute as u32,
+    }
+}
@@ -1,4 +1,5 @@
 use std::{
+    collections::HashMap,
     fmt::Display,
     fs::File,
     io::BufReader,
@@ -7,9 +8,12 @@ use std::{
 };
 
 use backend::{
-    Action, ActionCondition, ActionKey, ActionKeyDirection, ActionKeyWith, ActionMove, Bound,
-    IntoEnumIterator, KeyBinding, LinkKeyBinding, Minimap, MobbingKey, Platform, Position,
-    RotationMode, key_receiver, update_minimap, upsert_minimap,
+    Action, ActionCondition, ActionKey, ActionKeyDirection, ActionKeyWith, ActionMove, ActionStats,
+    Bound, IntoEnumIterator, KeyBinding, LinkKeyBinding, MAX_MOBBING_LINKED_KEYS, Minimap,
+    MobbingCooldownKey, MobbingKey, MobbingLinkedKey, MovementTestResult, MovementTestTransition,
+    Platform, Position, RequiredBuff, RotationMode, StrangerChannelHop, clear_recorded_platforms,
+    detect_platforms, game_state_receiver, key_receiver, record_key_macro, record_platforms,
+    test_movement, update_minimap, upsert_minimap,
 };
 use dioxus::prelude::*;
 use futures_util::StreamExt;
@@ -29,6 +33,9 @@ const ITEM_TEXT_CLASS: &str =
     "text-center inline-block pt-1 text-ellipsis overflow-hidden whitespace-nowrap";
 const ITEM_BORDER_CLASS: &str = "border-r-2 border-gray-700";
 
+/// Maximum y distance from a platform for a position to be snappable to that platform.
+const SNAP_PLATFORM_Y_THRESHOLD: i32 = 3;
+
 #[derive(Debug)]
 enum ActionUpdate {
     Set,
@@ -43,20 +50,24 @@ enum PopupInputKind {
     Action(ActionInputKind),
     Bound(Bound),
     Platform(Platform, Option<usize>),
+    Portal(Bound, Option<usize>),
+    HomePosition(Position),
 }
 
 #[derive(PartialEq, Clone, Copy, Debug)]
 enum ActionInputKind {
     Add(Action),
     Edit(Action, usize),
-    PingPongOrAutoMobbing(MobbingKey),
+    /// Modifies the mobbing key or the [`MobbingCooldownKey`] at the index.
+    PingPongOrAutoMobbing(MobbingKey, Option<usize>),
 }
 
 #[derive(Debug)]
 enum ActionInputValueKind {
     Add(Action, ActionCondition),
     Edit(Action, usize),
-    PingPongOrAutoMobbing(MobbingKey),
+    /// Modifies the mobbing key or the [`MobbingCooldownKey`] at the index.
+    PingPongOrAutoMobbing(MobbingKey, Option<usize>),
 }
 
 #[component]
@@ -90,6 +101,9 @@ pub fn Actions() -> Element {
         })
     });
 
+    // Number of minimap writes that have not landed in the database yet
+    let pending_writes = use_signal(|| 0u32);
+
     // Handles async operations for action-related
     // TODO: Split into functions
     let coroutine = use_coroutine(move |mut rx: UnboundedReceiver<ActionUpdate>| async move {
@@ -141,14 +155,10 @@ pub fn Actions() -> Element {
                     };
 
                     current_minimap.actions.insert(preset, actions);
-                    if let Some(current_minimap) = upsert_minimap(current_minimap).await {
-                        minimap.set(Some(current_minimap));
-                    }
+                    persist_minimap(minimap, pending_writes, current_minimap);
                 }
                 ActionUpdate::UpdateMinimap(new_minimap) => {
-                    if let Some(new_minimap) = upsert_minimap(new_minimap).await {
-                        minimap.set(Some(new_minimap));
-                    }
+                    persist_minimap(minimap, pending_writes, new_minimap);
                 }
             }
         }
@@ -182,23 +192,36 @@ pub fn Actions() -> Element {
         ActionInputKind::Edit(action, _) => {
             popup_input_kind.set(Some(PopupInputKind::Action(ActionInputKind::Add(action))));
         }
-        ActionInputKind::Add(_) | ActionInputKind::PingPongOrAutoMobbing(_) => {
+        ActionInputKind::Add(_) | ActionInputKind::PingPongOrAutoMobbing(_, _) => {
             unreachable!()
         }
     });
 
     // Edit mobbing key/bound callbacks
-    let edit_mobbing_key = use_callback(move |key| {
+    let edit_mobbing_key = use_callback(move |(key, index): (MobbingKey, Option<usize>)| {
         let mut minimap = minimap_view();
+        let mobbing_key = match index {
+            Some(index) => match minimap.rotation_mobbing_cooldown_keys.get_mut(index) {
+                Some(cooldown_key) => &mut cooldown_key.key,
+                None => return,
+            },
+            None => &mut minimap.rotation_mobbing_key,
+        };
 
-        minimap.rotation_mobbing_key = key;
+        *mobbing_key = MobbingKey {
+            hold_millis: mobbing_key.hold_millis,
+            linked_keys: mobbing_key.linked_keys,
+            ..key
+        };
         coroutine.send(ActionUpdate::UpdateMinimap(minimap));
     });
     let edit_mobbing_bound = use_callback(move |bound| {
         let mut minimap = minimap_view();
 
         match minimap.rotation_mode {
-            RotationMode::StartToEnd | RotationMode::StartToEndThenReverse => return,
+            RotationMode::StartToEnd
+            | RotationMode::StartToEndThenReverse
+            | RotationMode::Bossing => return,
             RotationMode::AutoMobbing => {
                 minimap.rotation_auto_mob_bound = bound;
             }
@@ -209,6 +232,21 @@ pub fn Actions() -> Element {
         coroutine.send(ActionUpdate::UpdateMinimap(minimap));
     });
 
+    // Edit home position callback
+    let edit_home_position = use_callback(move |position: Option<Position>| {
+        let mut minimap = minimap_view();
+        let Some(preset) = minimap_preset() else {
+            return;
+        };
+
+        if let Some(position) = position {
+            minimap.home_positions.insert(preset, position);
+        } else {
+            minimap.home_positions.remove(&preset);
+        }
+        coroutine.send(ActionUpdate::UpdateMinimap(minimap));
+    });
+
     //Add, edit platform callbacks
     let add_platform = use_callback(move |platform| {
         let mut minimap = minimap_view();
@@ -226,6 +264,47 @@ pub fn Actions() -> Element {
         coroutine.send(ActionUpdate::UpdateMinimap(minimap));
     });
 
+    // Portals currently detected or saved and used by the game loop
+    let mut detected_portals = use_signal(Vec::<Bound>::new);
+    // Saved portals or the detected portals if there is none saved
+    let portals_view = use_memo(move || {
+        let portals = minimap_view().portals;
+        if portals.is_empty() {
+            detected_portals()
+        } else {
+            portals
+        }
+    });
+    let edit_portal = use_callback(move |(portal, index): (Bound, Option<usize>)| {
+        let mut minimap = minimap_view();
+
+        minimap.portals = portals_view();
+        match index {
+            Some(index) => {
+                let Some(existing) = minimap.portals.get_mut(index) else {
+                    return;
+                };
+                *existing = portal;
+            }
+            None => minimap.portals.push(portal),
+        }
+        coroutine.send(ActionUpdate::UpdateMinimap(minimap));
+    });
+
+    use_future(move || async move {
+        let mut receiver = game_state_receiver().await;
+        loop {
+            let state = match receiver.recv().await {
+                Ok(value) => value,
+                Err(RecvError::Closed) => break,
+                Err(RecvError::Lagged(_)) => continue,
+            };
+            if *detected_portals.peek() != state.portals {
+                detected_portals.set(state.portals);
+            }
+        }
+    });
+
     rsx! {
         div { class: "flex flex-col pb-15 h-full gap-3 overflow-y-auto scrollbar pr-2",
             SectionRotation {
@@ -238,6 +317,29 @@ pub fn Actions() -> Element {
                 minimap_view,
                 disabled: minimap().is_none(),
             }
+            SectionPortals {
+                popup_input_kind,
+                minimap_view,
+                portals_view,
+                detected_portals,
+                disabled: minimap().is_none(),
+            }
+            SectionMovementTest { disabled: minimap().is_none() }
+            SectionHomePosition {
+                popup_input_kind,
+                minimap_view,
+                on_clear: move |_| {
+                    edit_home_position(None);
+                },
+                home_position: minimap_preset()
+                    .and_then(|preset| minimap_view().home_positions.get(&preset).copied()),
+                disabled: minimap().is_none() || minimap_preset().is_none(),
+            }
+            SectionRequiredBuffs {
+                minimap_view,
+                minimap_preset,
+                disabled: minimap().is_none() || minimap_preset().is_none(),
+            }
             SectionActions {
                 popup_input_kind,
                 minimap_preset_actions,
@@ -265,8 +367,8 @@ pub fn Actions() -> Element {
                                 ActionInputValueKind::Edit(action, index) => {
                                     edit_action((action, index));
                                 }
-                                ActionInputValueKind::PingPongOrAutoMobbing(key) => {
-                                    edit_mobbing_key(key);
+                                ActionInputValueKind::PingPongOrAutoMobbing(key, index) => {
+                                    edit_mobbing_key((key, index));
                                 }
                             }
                         },
@@ -285,6 +387,31 @@ pub fn Actions() -> Element {
                         value: bound,
                     }
                 },
+                PopupInputKind::HomePosition(position) => rsx! {
+                    PopupHomePositionInput {
+                        on_cancel: move |_| {
+                            popup_input_kind.take();
+                        },
+                        on_value: move |position| {
+                            popup_input_kind.take();
+                            edit_home_position(Some(position));
+                        },
+                        value: position,
+                    }
+                },
+                PopupInputKind::Portal(portal, index) => rsx! {
+                    PopupPortalInput {
+                        index,
+                        on_cancel: move |_| {
+                            popup_input_kind.take();
+                        },
+                        on_value: move |value: (Bound, Option<usize>)| {
+                            popup_input_kind.take();
+                            edit_portal(value);
+                        },
+                        value: portal,
+                    }
+                },
                 PopupInputKind::Platform(platform, index) => {
                     rsx! {
                         PopupPlatformInput {
@@ -325,10 +452,35 @@ pub fn Actions() -> Element {
                 },
                 selected: minimap_preset_index(),
             }
+            if pending_writes() > 0 {
+                div { class: "paragraph-xs !text-gray-400 pl-2 whitespace-nowrap", "Saving..." }
+            }
         }
     }
 }
 
+/// Sets `minimap` to `new_minimap` immediately and persists it in the background.
+///
+/// The UI reflects the change right away so that subsequent edits build on top of it. Rapid
+/// writes are coalesced by the backend and `pending_writes` tracks writes that have not landed.
+fn persist_minimap(
+    mut minimap: Signal<Option<Minimap>>,
+    mut pending_writes: Signal<u32>,
+    new_minimap: Minimap,
+) {
+    minimap.set(Some(new_minimap.clone()));
+    pending_writes += 1;
+    spawn(async move {
+        let saved_minimap = upsert_minimap(new_minimap).await;
+        pending_writes -= 1;
+        if pending_writes() == 0
+            && let Some(saved_minimap) = saved_minimap
+        {
+            minimap.set(Some(saved_minimap));
+        }
+    });
+}
+
 #[component]
 fn Section(
     name: String,
@@ -355,10 +507,27 @@ fn SectionRotation(
             RotationMode::AutoMobbing | RotationMode::PingPong
         )
     });
+    let linked_keys = use_memo(move || minimap_view().rotation_mobbing_key.linked_keys);
     let coroutine = use_coroutine_handle::<ActionUpdate>();
     let save_minimap = use_callback(move |new_minimap: Minimap| {
         coroutine.send(ActionUpdate::UpdateMinimap(new_minimap));
     });
+    let save_linked_key = use_callback(
+        move |(index, linked_key): (usize, Option<MobbingLinkedKey>)| {
+            let minimap = minimap_view.peek().clone();
+            let mut linked_keys = minimap.rotation_mobbing_key.linked_keys;
+            linked_keys[index] = linked_key;
+            save_minimap(Minimap {
+                rotation_mobbing_key: MobbingKey {
+                    linked_keys,
+                    ..minimap.rotation_mobbing_key
+                },
+                ..minimap
+            })
+        },
+    );
+    let linked_key_labels: [&'static str; MAX_MOBBING_LINKED_KEYS] =
+        ["Linked key 1", "Linked key 2"];
 
     rsx! {
         Section { name: "Rotation",
@@ -374,7 +543,17 @@ fn SectionRotation(
                     },
                     selected: minimap_view().rotation_mode,
                 }
-                div {}
+                ActionsSelect::<StrangerChannelHop> {
+                    label: "Change channel on stranger",
+                    disabled,
+                    on_select: move |stranger_channel_hop| {
+                        save_minimap(Minimap {
+                            stranger_channel_hop,
+                            ..minimap_view.peek().clone()
+                        })
+                    },
+                    selected: minimap_view().stranger_channel_hop,
+                }
                 Button {
                     text: "Update mobbing key",
                     kind: ButtonKind::Primary,
@@ -382,14 +561,14 @@ fn SectionRotation(
                     on_click: move |_| {
                         let minimap = minimap_view.peek();
                         let key = match minimap.rotation_mode {
-                            RotationMode::StartToEnd | RotationMode::StartToEndThenReverse => {
-                                unreachable!()
-                            }
+                            RotationMode::StartToEnd
+                            | RotationMode::StartToEndThenReverse
+                            | RotationMode::Bossing => unreachable!(),
                             RotationMode::AutoMobbing | RotationMode::PingPong => {
                                 minimap.rotation_mobbing_key
                             }
                         };
-                        let kind = ActionInputKind::PingPongOrAutoMobbing(key);
+                        let kind = ActionInputKind::PingPongOrAutoMobbing(key, None);
                         popup_input_kind.set(Some(PopupInputKind::Action(kind)));
                     },
                 }
@@ -400,9 +579,9 @@ fn SectionRotation(
                     on_click: move |_| {
                         let minimap = minimap_view.peek();
                         let bound = match minimap.rotation_mode {
-                            RotationMode::StartToEnd | RotationMode::StartToEndThenReverse => {
-                                unreachable!()
-                            }
+                            RotationMode::StartToEnd
+                            | RotationMode::StartToEndThenReverse
+                            | RotationMode::Bossing => unreachable!(),
                             RotationMode::AutoMobbing => minimap.rotation_auto_mob_bound,
                             RotationMode::PingPong => minimap.rotation_ping_pong_bound,
                         };
@@ -420,6 +599,184 @@ fn SectionRotation(
                     },
                     value: minimap_view().actions_any_reset_on_erda_condition,
                 }
+                ActionsCheckbox {
+                    label: "Hold mobbing key",
+                    disabled: disabled | update_mobbing_button_disabled(),
+                    on_value: move |hold: bool| {
+                        let minimap = minimap_view.peek().clone();
+                        save_minimap(Minimap {
+                            rotation_mobbing_key: MobbingKey {
+                                hold_millis: hold.then_some(1000),
+                                ..minimap.rotation_mobbing_key
+                            },
+                            ..minimap
+                        })
+                    },
+                    value: minimap_view().rotation_mobbing_key.hold_millis.is_some(),
+                }
+                if let Some(hold_millis) = minimap_view().rotation_mobbing_key.hold_millis {
+                    ActionsMillisInput {
+                        label: "Hold mobbing key duration",
+                        on_value: move |hold_millis| {
+                            let minimap = minimap_view.peek().clone();
+                            save_minimap(Minimap {
+                                rotation_mobbing_key: MobbingKey {
+                                    hold_millis: Some(hold_millis),
+                                    ..minimap.rotation_mobbing_key
+                                },
+                                ..minimap
+                            })
+                        },
+                        value: hold_millis,
+                    }
+                    div {}
+                }
+                ActionsCheckbox {
+                    label: "Ping pong walk only",
+                    disabled: disabled
+                        | !matches!(minimap_view().rotation_mode, RotationMode::PingPong),
+                    on_value: move |rotation_ping_pong_walk_only| {
+                        save_minimap(Minimap {
+                            rotation_ping_pong_walk_only,
+                            ..minimap_view.peek().clone()
+                        })
+                    },
+                    value: minimap_view().rotation_ping_pong_walk_only,
+                }
+                ActionsCheckbox {
+                    label: "Auto mobbing prefers quadrants with more mobs",
+                    disabled: disabled
+                        | !matches!(minimap_view().rotation_mode, RotationMode::AutoMobbing),
+                    on_value: move |rotation_auto_mob_density_pathing| {
+                        save_minimap(Minimap {
+                            rotation_auto_mob_density_pathing,
+                            ..minimap_view.peek().clone()
+                        })
+                    },
+                    value: minimap_view().rotation_auto_mob_density_pathing,
+                }
+                ActionsCheckbox {
+                    label: "Unstuck when mobbing deals no damage",
+                    disabled: disabled | update_mobbing_button_disabled(),
+                    on_value: move |rotation_mobbing_damage_check| {
+                        save_minimap(Minimap {
+                            rotation_mobbing_damage_check,
+                            ..minimap_view.peek().clone()
+                        })
+                    },
+                    value: minimap_view().rotation_mobbing_damage_check,
+                }
+                div {}
+                for (index , label) in linked_key_labels.into_iter().enumerate() {
+                    ActionsKeyBindingInput {
+                        label,
+                        disabled: disabled | update_mobbing_button_disabled()
+                            | linked_keys()[index].is_none(),
+                        on_value: move |key: Option<KeyBinding>| {
+                            let linked_key = linked_keys.peek()[index];
+                            if let (Some(key), Some(linked_key)) = (key, linked_key) {
+                                save_linked_key((
+                                    index,
+                                    Some(MobbingLinkedKey {
+                                        key,
+                                        ..linked_key
+                                    }),
+                                ));
+                            }
+                        },
+                        value: linked_keys()[index].map(|linked_key| linked_key.key),
+                    }
+                    ActionsCheckbox {
+                        label: "Enabled",
+                        disabled: disabled | update_mobbing_button_disabled(),
+                        on_value: move |enabled: bool| {
+                            save_linked_key((index, enabled.then(MobbingLinkedKey::default)));
+                        },
+                        value: linked_keys()[index].is_some(),
+                    }
+                    if let Some(linked_key) = linked_keys()[index] {
+                        ActionsNumberInputU32 {
+                            label: "Use count",
+                            disabled: disabled | update_mobbing_button_disabled(),
+                            on_value: move |count| {
+                                save_linked_key((
+                                    index,
+                                    Some(MobbingLinkedKey {
+                                        count,
+                                        ..linked_key
+                                    }),
+                                ));
+                            },
+                            value: linked_key.count,
+                        }
+                        ActionsMillisInput {
+                            label: "Wait after",
+                            on_value: move |wait_after_millis| {
+                                save_linked_key((
+                                    index,
+                                    Some(MobbingLinkedKey {
+                                        wait_after_millis,
+                                        ..linked_key
+                                    }),
+                                ));
+                            },
+                            value: linked_key.wait_after_millis,
+                        }
+                    }
+                }
+                for (index , cooldown_key) in minimap_view()
+                    .rotation_mobbing_cooldown_keys
+                    .into_iter()
+                    .enumerate()
+                {
+                    Button {
+                        text: format!("Update cooldown mobbing key {}", index + 1),
+                        kind: ButtonKind::Primary,
+                        disabled: disabled | update_mobbing_button_disabled(),
+                        on_click: move |_| {
+                            let kind = ActionInputKind::PingPongOrAutoMobbing(
+                                cooldown_key.key,
+                                Some(index),
+                            );
+                            popup_input_kind.set(Some(PopupInputKind::Action(kind)));
+                        },
+                    }
+                    Button {
+                        text: "Remove",
+                        kind: ButtonKind::Danger,
+                        disabled,
+                        on_click: move |_| {
+                            let mut minimap = minimap_view.peek().clone();
+                            minimap.rotation_mobbing_cooldown_keys.remove(index);
+                            save_minimap(minimap);
+                        },
+                    }
+                    ActionsMillisInput {
+                        label: "Cooldown",
+                        on_value: move |cooldown_millis| {
+                            let mut minimap = minimap_view.peek().clone();
+                            minimap.rotation_mobbing_cooldown_keys[index] = MobbingCooldownKey {
+                                cooldown_millis,
+                                ..cooldown_key
+                            };
+                            save_minimap(minimap);
+                        },
+                        value: cooldown_key.cooldown_millis,
+                    }
+                    div {}
+                }
+                Button {
+                    text: "Add cooldown mobbing key",
+                    kind: ButtonKind::Secondary,
+                    disabled: disabled | update_mobbing_button_disabled(),
+                    on_click: move |_| {
+                        let mut minimap = minimap_view.peek().clone();
+                        minimap
+                            .rotation_mobbing_cooldown_keys
+                            .push(MobbingCooldownKey::default());
+                        save_minimap(minimap);
+                    },
+                }
             }
         }
     }
@@ -487,7 +844,37 @@ fn SectionPlatforms(
     let save_minimap = use_callback(move |new_minimap: Minimap| {
         coroutine.send(ActionUpdate::UpdateMinimap(new_minimap));
     });
+    let mut recording_platforms = use_signal(|| false);
+    let mut recorded_platforms = use_signal(Vec::<Platform>::new);
+    let accept_recorded_platforms = use_callback(move |_: ()| {
+        let mut minimap = minimap_view();
+        for platform in recorded_platforms.peek().iter().copied() {
+            if !minimap.platforms.contains(&platform) {
+                minimap.platforms.push(platform);
+            }
+        }
+        coroutine.send(ActionUpdate::UpdateMinimap(minimap));
+        spawn(async move {
+            clear_recorded_platforms().await;
+        });
+    });
 
+    use_future(move || async move {
+        let mut receiver = game_state_receiver().await;
+        loop {
+            let state = match receiver.recv().await {
+                Ok(value) => value,
+                Err(RecvError::Closed) => break,
+                Err(RecvError::Lagged(_)) => continue,
+            };
+            if *recording_platforms.peek() != state.recording_platforms {
+                recording_platforms.set(state.recording_platforms);
+            }
+            if *recorded_platforms.peek() != state.recorded_platforms {
+                recorded_platforms.set(state.recorded_platforms);
+            }
+        }
+    });
     use_future(move || async move {
         let mut platform = Platform::default();
         let mut key_receiver = key_receiver().await;
@@ -598,15 +985,434 @@ fn SectionPlatforms(
                 }
             }
             Button {
-                text: "Add platform",
+                text: "Add platform",
+                kind: ButtonKind::Secondary,
+                on_click: move |_| {
+                    let kind = PopupInputKind::Platform(Platform::default(), None);
+                    popup_input_kind.set(Some(kind));
+                },
+                disabled,
+                class: "label mt-2",
+            }
+            if !recorded_platforms().is_empty() {
+                div { class: "paragraph-xs !text-gray-400 mt-2",
+                    {format!("Proposed platforms ({})", recorded_platforms().len())}
+                }
+            }
+            for platform in recorded_platforms() {
+                div { class: "grid grid-cols-2 h-6 paragraph-xs gap-2 !text-gray-400",
+                    div { class: "{ITEM_BORDER_CLASS} {ITEM_TEXT_CLASS}",
+                        {format!("X / {} - {}", platform.x_start, platform.x_end)}
+                    }
+                    div { class: "{ITEM_TEXT_CLASS}", {format!("Y / {}", platform.y)} }
+                }
+            }
+            div { class: "grid grid-cols-2 gap-3 mt-2",
+                Button {
+                    text: if recording_platforms() { "Stop recording" } else { "Record platforms" },
+                    kind: ButtonKind::Secondary,
+                    on_click: move |_| async move {
+                        record_platforms(!*recording_platforms.peek()).await;
+                    },
+                    disabled,
+                    class: "label",
+                }
+                Button {
+                    text: "Detect platforms",
+                    kind: ButtonKind::Secondary,
+                    on_click: move |_| async move {
+                        detect_platforms().await;
+                    },
+                    disabled,
+                    class: "label",
+                }
+                Button {
+                    text: "Accept proposed",
+                    kind: ButtonKind::Primary,
+                    on_click: move |_| {
+                        accept_recorded_platforms(());
+                    },
+                    disabled: disabled || recording_platforms() || recorded_platforms().is_empty(),
+                    class: "label",
+                }
+                Button {
+                    text: "Discard proposed",
+                    kind: ButtonKind::Danger,
+                    on_click: move |_| async move {
+                        clear_recorded_platforms().await;
+                    },
+                    disabled: recording_platforms() || recorded_platforms().is_empty(),
+                    class: "label",
+                }
+            }
+            p { class: "paragraph-xs !text-gray-400 mt-2",
+                "While recording, walk across each platform manually. Platforms are proposed from where the player stood or walked. Detecting proposes platforms from the minimap lines, which may need fixing up."
+            }
+        }
+    }
+}
+
+#[component]
+fn SectionPortals(
+    popup_input_kind: Signal<Option<PopupInputKind>>,
+    minimap_view: Memo<Minimap>,
+    portals_view: Memo<Vec<Bound>>,
+    detected_portals: ReadOnlySignal<Vec<Bound>>,
+    disabled: bool,
+) -> Element {
+    #[component]
+    fn PortalItem(
+        portal: Bound,
+        on_item_click: EventHandler,
+        on_item_delete: EventHandler,
+    ) -> Element {
+        const ICON_CONTAINER_CLASS: &str = "w-4 h-6 flex justify-center items-center";
+        const ICON_CLASS: &str = "w-[11px] h-[11px] fill-current";
+
+        rsx! {
+            div { class: "relative group",
+                div {
+                    class: "grid grid-cols-2 h-6 paragraph-xs gap-2 !text-gray-400 group-hover:bg-gray-900",
+                    onclick: move |e| {
+                        e.stop_propagation();
+                        on_item_click(());
+                    },
+                    div { class: "{ITEM_BORDER_CLASS} {ITEM_TEXT_CLASS}",
+                        {format!("X / {} - {}", portal.x, portal.x + portal.width)}
+                    }
+                    div { class: "{ITEM_TEXT_CLASS}",
+                        {format!("Y / {} - {}", portal.y, portal.y + portal.height)}
+                    }
+                }
+                div { class: "absolute invisible group-hover:visible top-0 right-1 flex",
+                    div {
+                        class: ICON_CONTAINER_CLASS,
+                        onclick: move |e| {
+                            e.stop_propagation();
+                            on_item_delete(());
+                        },
+                        XIcon { class: "{ICON_CLASS} text-red-500" }
+                    }
+                }
+            }
+        }
+    }
+
+    let coroutine = use_coroutine_handle::<ActionUpdate>();
+    let save_detected_portals = use_callback(move |_: ()| {
+        let mut minimap = minimap_view();
+
+        minimap.portals = detected_portals.peek().clone();
+        coroutine.send(ActionUpdate::UpdateMinimap(minimap));
+    });
+    // Deleting a detected portal also saves the other detected portals
+    let delete_portal = use_callback(move |index| {
+        let mut minimap = minimap_view();
+
+        minimap.portals = portals_view();
+        minimap.portals.remove(index);
+        coroutine.send(ActionUpdate::UpdateMinimap(minimap));
+    });
+    let clear_portals = use_callback(move |_: ()| {
+        let mut minimap = minimap_view();
+
+        minimap.portals.clear();
+        coroutine.send(ActionUpdate::UpdateMinimap(minimap));
+    });
+
+    rsx! {
+        Section { name: "Portals",
+            if !portals_view().is_empty() {
+                div { class: "paragraph-xs !text-gray-400",
+                    if minimap_view().portals.is_empty() {
+                        {format!("Detected portals ({})", portals_view().len())}
+                    } else {
+                        {format!("Saved portals ({})", portals_view().len())}
+                    }
+                }
+            }
+            for (index , portal) in portals_view().into_iter().enumerate() {
+                PortalItem {
+                    portal,
+                    on_item_click: move |_| {
+                        popup_input_kind.set(Some(PopupInputKind::Portal(portal, Some(index))));
+                    },
+                    on_item_delete: move |_| {
+                        delete_portal(index);
+                    },
+                }
+            }
+            Button {
+                text: "Add portal",
+                kind: ButtonKind::Secondary,
+                on_click: move |_| {
+                    let kind = PopupInputKind::Portal(Bound::default(), None);
+                    popup_input_kind.set(Some(kind));
+                },
+                disabled,
+                class: "label mt-2",
+            }
+            div { class: "grid grid-cols-2 gap-3 mt-2",
+                Button {
+                    text: "Save detected portals",
+                    kind: ButtonKind::Primary,
+                    on_click: move |_| {
+                        save_detected_portals(());
+                    },
+                    disabled: disabled || !minimap_view().portals.is_empty()
+                        || detected_portals().is_empty(),
+                    class: "label",
+                }
+                Button {
+                    text: "Clear portals",
+                    kind: ButtonKind::Danger,
+                    on_click: move |_| {
+                        clear_portals(());
+                    },
+                    disabled: disabled || minimap_view().portals.is_empty(),
+                    class: "label",
+                }
+            }
+            p { class: "paragraph-xs !text-gray-400 mt-2",
+                "Wrong portals can cancel up jumps. Once portals are saved or edited, they are no longer detected and deleted portals stay deleted. Clear all saved portals to detect again."
+            }
+        }
+    }
+}
+
+#[component]
+fn SectionMovementTest(disabled: bool) -> Element {
+    #[component]
+    fn TransitionItem(transition: MovementTestTransition) -> Element {
+        let MovementTestTransition {
+            from,
+            to,
+            kind,
+            result,
+        } = transition;
+        let result_class = match result {
+            MovementTestResult::Succeeded => "!text-green-500",
+            MovementTestResult::Failed => "!text-red-500",
+            MovementTestResult::Pending
+            | MovementTestResult::Testing
+            | MovementTestResult::Skipped => "",
+        };
+
+        rsx! {
+            div { class: "grid grid-cols-3 h-6 paragraph-xs gap-2 !text-gray-400",
+                div { class: "{ITEM_BORDER_CLASS} {ITEM_TEXT_CLASS}",
+                    {format!("{}, {} → {}, {}", from.0, from.1, to.0, to.1)}
+                }
+                div { class: "{ITEM_BORDER_CLASS} {ITEM_TEXT_CLASS}", "{kind}" }
+                div { class: "{ITEM_TEXT_CLASS} {result_class}", "{result}" }
+            }
+        }
+    }
+
+    let mut transitions = use_signal(Vec::<MovementTestTransition>::new);
+    let testing = use_memo(move || {
+        transitions().iter().any(|transition| {
+            matches!(
+                transition.result,
+                MovementTestResult::Pending | MovementTestResult::Testing
+            )
+        })
+    });
+
+    use_future(move || async move {
+        let mut receiver = game_state_receiver().await;
+        loop {
+            let state = match receiver.recv().await {
+                Ok(value) => value,
+                Err(RecvError::Closed) => break,
+                Err(RecvError::Lagged(_)) => continue,
+            };
+            if *transitions.peek() != state.movement_test {
+                transitions.set(state.movement_test);
+            }
+        }
+    });
+
+    rsx! {
+        Section { name: "Movement test",
+            for transition in transitions() {
+                TransitionItem { transition }
+            }
+            Button {
+                text: if testing() { "Stop test" } else { "Test movement" },
+                kind: ButtonKind::Secondary,
+                on_click: move |_| async move {
+                    test_movement(!*testing.peek()).await;
+                },
+                disabled,
+                class: "label mt-2",
+            }
+        }
+    }
+}
+
+#[component]
+fn SectionHomePosition(
+    popup_input_kind: Signal<Option<PopupInputKind>>,
+    minimap_view: Memo<Minimap>,
+    on_clear: EventHandler,
+    home_position: Option<Position>,
+    disabled: bool,
+) -> Element {
+    let coroutine = use_coroutine_handle::<ActionUpdate>();
+    let save_minimap = use_callback(move |new_minimap: Minimap| {
+        coroutine.send(ActionUpdate::UpdateMinimap(new_minimap));
+    });
+
+    rsx! {
+        Section { name: "Home position",
+            div { class: "grid grid-cols-3 gap-3",
+                ActionsCheckbox {
+                    label: "Platforms pathing",
+                    disabled,
+                    on_value: move |home_platforms_pathing| {
+                        save_minimap(Minimap {
+                            home_platforms_pathing,
+                            ..minimap_view.peek().clone()
+                        })
+                    },
+                    value: minimap_view().home_platforms_pathing,
+                }
+                ActionsCheckbox {
+                    label: "Up jump only",
+                    disabled: disabled || !minimap_view().home_platforms_pathing,
+                    on_value: move |home_platforms_pathing_up_jump_only| {
+                        save_minimap(Minimap {
+                            home_platforms_pathing_up_jump_only,
+                            ..minimap_view.peek().clone()
+                        })
+                    },
+                    value: minimap_view().home_platforms_pathing_up_jump_only,
+                }
+                div {}
+            }
+            if let Some(position) = home_position {
+                div { class: "grid grid-cols-2 h-6 paragraph-xs gap-2 !text-gray-400 mt-2",
+                    div { class: "{ITEM_BORDER_CLASS} {ITEM_TEXT_CLASS}",
+                        {format!("X / {} ~ {}", position.x, position.x_random_range)}
+                    }
+                    div { class: "{ITEM_TEXT_CLASS}", {format!("Y / {}", position.y)} }
+                }
+            }
+            div { class: "grid grid-cols-2 gap-3 mt-2",
+                Button {
+                    text: if home_position.is_some() { "Update home position" } else { "Set home position" },
+                    kind: ButtonKind::Secondary,
+                    on_click: move |_| {
+                        let kind = PopupInputKind::HomePosition(home_position.unwrap_or_default());
+                        popup_input_kind.set(Some(kind));
+                    },
+                    disabled,
+                    class: "label",
+                }
+                Button {
+                    text: "Clear home position",
+                    kind: ButtonKind::Secondary,
+                    on_click: move |_| {
+                        on_clear(());
+                    },
+                    disabled: disabled || home_position.is_none(),
+                    class: "label",
+                }
+            }
+        }
+    }
+}
+
+#[component]
+fn SectionRequiredBuffs(
+    minimap_view: Memo<Minimap>,
+    minimap_preset: ReadOnlySignal<Option<String>>,
+    disabled: bool,
+) -> Element {
+    let coroutine = use_coroutine_handle::<ActionUpdate>();
+    let required_buffs = use_memo(move || {
+        minimap_preset()
+            .and_then(|preset| minimap_view().required_buffs.get(&preset).cloned())
+            .unwrap_or_default()
+    });
+    let save_required_buffs = use_callback(move |buffs: Vec<RequiredBuff>| {
+        let mut minimap = minimap_view();
+        let Some(preset) = minimap_preset() else {
+            return;
+        };
+
+        if buffs.is_empty() {
+            minimap.required_buffs.remove(&preset);
+        } else {
+            minimap.required_buffs.insert(preset, buffs);
+        }
+        coroutine.send(ActionUpdate::UpdateMinimap(minimap));
+    });
+    let mut missing_buffs = use_signal(Vec::<String>::new);
+
+    use_future(move || async move {
+        let mut receiver = game_state_receiver().await;
+        loop {
+            let state = match receiver.recv().await {
+                Ok(value) => value,
+                Err(RecvError::Closed) => break,
+                Err(RecvError::Lagged(_)) => continue,
+            };
+            if *missing_buffs.peek() != state.missing_required_buffs {
+                missing_buffs.set(state.missing_required_buffs);
+            }
+        }
+    });
+
+    rsx! {
+        Section { name: "Required buffs",
+            div { class: "grid grid-cols-2 gap-3",
+                for (index , buff) in required_buffs().into_iter().enumerate() {
+                    ActionsSelect::<RequiredBuff> {
+                        label: "Buff",
+                        disabled,
+                        on_select: move |buff| {
+                            let mut buffs = required_buffs.peek().clone();
+                            buffs[index] = buff;
+                            save_required_buffs(buffs);
+                        },
+                        selected: buff,
+                    }
+                    div { class: "flex items-end",
+                        Button {
+                            text: "Remove",
+                            kind: ButtonKind::Danger,
+                            on_click: move |_| {
+                                let mut buffs = required_buffs.peek().clone();
+                                buffs.remove(index);
+                                save_required_buffs(buffs);
+                            },
+                            disabled,
+                            class: "label w-full",
+                        }
+                    }
+                }
+            }
+            Button {
+                text: "Add required buff",
                 kind: ButtonKind::Secondary,
                 on_click: move |_| {
-                    let kind = PopupInputKind::Platform(Platform::default(), None);
-                    popup_input_kind.set(Some(kind));
+                    let mut buffs = required_buffs.peek().clone();
+                    buffs.push(RequiredBuff::default());
+                    save_required_buffs(buffs);
                 },
                 disabled,
                 class: "label mt-2",
             }
+            if !missing_buffs().is_empty() {
+                p { class: "paragraph-xs !text-red-500 mt-2",
+                    {format!("Missing on last start: {}", missing_buffs().join(", "))}
+                }
+            }
+            p { class: "paragraph-xs !text-gray-400 mt-2",
+                "On start, required buffs with keys set in the character are casted before the first normal action. Buffs still missing afterward are notified."
+            }
         }
     }
 }
@@ -713,6 +1519,34 @@ fn SectionActions(
         coroutine.send(ActionUpdate::Update(actions));
     });
 
+    let mut recording_key_macro = use_signal(|| false);
+    let toggle_recording_key_macro = use_callback(move |_: ()| {
+        let start = !*recording_key_macro.peek();
+        spawn(async move {
+            let recorded = record_key_macro(start).await;
+            if recorded.is_empty() {
+                return;
+            }
+            let mut actions = minimap_preset_actions();
+            actions.extend(recorded);
+            coroutine.send(ActionUpdate::Update(actions));
+        });
+    });
+
+    use_future(move || async move {
+        let mut receiver = game_state_receiver().await;
+        loop {
+            let state = match receiver.recv().await {
+                Ok(value) => value,
+                Err(RecvError::Closed) => break,
+                Err(RecvError::Lagged(_)) => continue,
+            };
+            if *recording_key_macro.peek() != state.recording_key_macro {
+                recording_key_macro.set(state.recording_key_macro);
+            }
+        }
+    });
+
     let delete_action = use_callback(move |index: usize| {
         let mut actions = minimap_preset_actions();
         let Some(condition) = actions.get(index).map(|action| action.condition()) else {
@@ -894,6 +1728,43 @@ fn SectionActions(
                 actions: minimap_preset_actions(),
             }
         }
+        Section { name: "Inventory full priority actions",
+            ActionList {
+                on_add_click: move |_| {
+                    let action = Action::Key(ActionKey {
+                        condition: ActionCondition::InventoryFull,
+                        ..ActionKey::default()
+                    });
+                    popup_input(ActionInputKind::Add(action));
+                },
+                on_item_click: move |(action, index)| {
+                    popup_input(ActionInputKind::Edit(action, index));
+                },
+                on_item_move: move |(index, condition, up)| {
+                    move_action((index, condition, up));
+                },
+                on_item_delete: move |index| {
+                    delete_action(index);
+                },
+                condition_filter: ActionCondition::InventoryFull,
+                disabled,
+                actions: minimap_preset_actions(),
+            }
+        }
+        Section { name: "Record combo",
+            Button {
+                text: if recording_key_macro() { "Stop recording" } else { "Record combo" },
+                kind: ButtonKind::Secondary,
+                on_click: move |_| {
+                    toggle_recording_key_macro(());
+                },
+                disabled,
+                class: "w-full",
+            }
+            p { class: "paragraph-xs !text-gray-400 mt-2",
+                "Perform the combo in game while recording. The keys are added as an action followed by linked actions that wait for the recorded time between keys."
+            }
+        }
         Section { name: "Import/export actions",
             div { class: "flex gap-2",
                 div { class: "flex-grow",
@@ -936,6 +1807,91 @@ fn SectionActions(
     }
 }
 
+#[component]
+fn PopupPortalInput(
+    index: Option<usize>,
+    on_cancel: EventHandler,
+    on_value: EventHandler<(Bound, Option<usize>)>,
+    value: Bound,
+) -> Element {
+    const ICON_CONTAINER_CLASS: &str = "absolute invisible group-hover:visible top-5 right-1 w-4 h-6 flex justify-center items-center";
+    const ICON_CLASS: &str = "w-3 h-3 text-gray-50 fill-current";
+
+    let position = use_context::<AppState>().position;
+    let mut portal = use_signal(|| value);
+    let section_name = if index.is_some() {
+        "Modify portal"
+    } else {
+        "Add portal"
+    };
+    let button_name = if index.is_some() { "Save" } else { "Add" };
+
+    use_effect(use_reactive!(|value| portal.set(value)));
+
+    rsx! {
+        Popup {
+            title: section_name,
+            class: "max-w-108 max-h-50",
+            confirm_button: button_name,
+            on_confirm: move |_| {
+                on_value((*portal.peek(), index));
+            },
+            cancel_button: "Cancel",
+            on_cancel: move |_| {
+                on_cancel(());
+            },
+            div { class: "grid grid-cols-2 gap-3",
+                div { class: "relative group",
+                    ActionsNumberInputI32 {
+                        label: "X",
+                        on_value: move |x| {
+                            portal.write().x = x;
+                        },
+                        value: portal().x,
+                    }
+                    div {
+                        class: ICON_CONTAINER_CLASS,
+                        onclick: move |_| {
+                            portal.write().x = position.peek().0;
+                        },
+                        PositionIcon { class: ICON_CLASS }
+                    }
+                }
+                div { class: "relative group",
+                    ActionsNumberInputI32 {
+                        label: "Y",
+                        on_value: move |y| {
+                            portal.write().y = y;
+                        },
+                        value: portal().y,
+                    }
+                    div {
+                        class: ICON_CONTAINER_CLASS,
+                        onclick: move |_| {
+                            portal.write().y = position.peek().1;
+                        },
+                        PositionIcon { class: ICON_CLASS }
+                    }
+                }
+                ActionsNumberInputI32 {
+                    label: "Width",
+                    on_value: move |width| {
+                        portal.write().width = width;
+                    },
+                    value: portal().width,
+                }
+                ActionsNumberInputI32 {
+                    label: "Height",
+                    on_value: move |height| {
+                        portal.write().height = height;
+                    },
+                    value: portal().height,
+                }
+            }
+        }
+    }
+}
+
 #[component]
 fn PopupPlatformInput(
     index: Option<usize>,
@@ -960,7 +1916,7 @@ fn PopupPlatformInput(
     rsx! {
         Popup {
             title: section_name,
-            class: "max-w-104 max-h-36",
+            class: "max-w-104 max-h-52",
             confirm_button: button_name,
             on_confirm: move |_| {
                 on_value((*platform.peek(), index));
@@ -1018,6 +1974,98 @@ fn PopupPlatformInput(
                         PositionIcon { class: ICON_CLASS }
                     }
                 }
+                ActionsNumberInputI32 {
+                    label: "Moving x range",
+                    on_value: move |range| {
+                        platform.write().moving_x_range = range;
+                    },
+                    value: platform().moving_x_range,
+                }
+                ActionsMillisInput {
+                    label: "Moving period",
+                    on_value: move |millis| {
+                        platform.write().moving_period_millis = millis;
+                    },
+                    value: platform().moving_period_millis,
+                }
+            }
+        }
+    }
+}
+
+#[component]
+fn PopupHomePositionInput(
+    on_cancel: EventHandler,
+    on_value: EventHandler<Position>,
+    value: Position,
+) -> Element {
+    const ICON_CONTAINER_CLASS: &str = "absolute invisible group-hover:visible top-5 right-1 w-4 h-6 flex justify-center items-center";
+    const ICON_CLASS: &str = "w-3 h-3 text-gray-50 fill-current";
+
+    let position = use_context::<AppState>().position;
+    let mut home = use_signal(|| value);
+
+    use_effect(use_reactive!(|value| home.set(value)));
+
+    rsx! {
+        Popup {
+            title: "Modify home position",
+            class: "max-w-104 max-h-50",
+            confirm_button: "Save",
+            on_confirm: move |_| {
+                on_value(*home.peek());
+            },
+            cancel_button: "Cancel",
+            on_cancel: move |_| {
+                on_cancel(());
+            },
+            div { class: "grid grid-cols-3 gap-3",
+                div { class: "relative group",
+                    ActionsNumberInputI32 {
+                        label: "X",
+                        on_value: move |x| {
+                            home.write().x = x;
+                        },
+                        value: home().x,
+                    }
+                    div {
+                        class: ICON_CONTAINER_CLASS,
+                        onclick: move |_| {
+                            home.write().x = position.peek().0;
+                        },
+                        PositionIcon { class: ICON_CLASS }
+                    }
+                }
+                ActionsNumberInputI32 {
+                    label: "X random range",
+                    on_value: move |x| {
+                        home.write().x_random_range = x;
+                    },
+                    value: home().x_random_range,
+                }
+                div { class: "relative group",
+                    ActionsNumberInputI32 {
+                        label: "Y",
+                        on_value: move |y| {
+                            home.write().y = y;
+                        },
+                        value: home().y,
+                    }
+                    div {
+                        class: ICON_CONTAINER_CLASS,
+                        onclick: move |_| {
+                            home.write().y = position.peek().1;
+                        },
+                        PositionIcon { class: ICON_CLASS }
+                    }
+                }
+                ActionsCheckbox {
+                    label: "Adjust",
+                    on_value: move |allow_adjusting| {
+                        home.write().allow_adjusting = allow_adjusting;
+                    },
+                    value: home().allow_adjusting,
+                }
             }
         }
     }
@@ -1088,7 +2136,7 @@ fn PopupActionInput(
     kind: ActionInputKind,
 ) -> Element {
     let (action, index) = match kind {
-        ActionInputKind::PingPongOrAutoMobbing(key) => {
+        ActionInputKind::PingPongOrAutoMobbing(key, _) => {
             let key = ActionKey {
                 key: key.key,
                 link_key: key.link_key,
@@ -1107,16 +2155,17 @@ fn PopupActionInput(
         ActionInputKind::Add(action) => (action, None),
         ActionInputKind::Edit(action, index) => (action, Some(index)),
     };
-    let switchable = !matches!(kind, ActionInputKind::PingPongOrAutoMobbing(_));
+    let switchable = !matches!(kind, ActionInputKind::PingPongOrAutoMobbing(_, _));
     let modifying = matches!(
         kind,
-        ActionInputKind::Edit(_, _) | ActionInputKind::PingPongOrAutoMobbing(_)
+        ActionInputKind::Edit(_, _) | ActionInputKind::PingPongOrAutoMobbing(_, _)
     );
     let copyable = matches!(kind, ActionInputKind::Edit(_, _));
     let can_create_linked_action = match kind {
         ActionInputKind::Add(_) | ActionInputKind::Edit(_, _) => match action.condition() {
             ActionCondition::EveryMillis(_)
             | ActionCondition::ErdaShowerOffCooldown
+            | ActionCondition::InventoryFull
             | ActionCondition::Any => {
                 let actions = actions();
                 let filtered = filter_actions(actions, action.condition());
@@ -1127,7 +2176,7 @@ fn PopupActionInput(
             }
             ActionCondition::Linked => false,
         },
-        ActionInputKind::PingPongOrAutoMobbing(_) => false,
+        ActionInputKind::PingPongOrAutoMobbing(_, _) => false,
     };
     let section_text = match kind {
         ActionInputKind::Add(_) | ActionInputKind::Edit(_, _) => {
@@ -1135,6 +2184,7 @@ fn PopupActionInput(
                 backend::ActionCondition::Any => "normal",
                 backend::ActionCondition::EveryMillis(_) => "every milliseconds",
                 backend::ActionCondition::ErdaShowerOffCooldown => "Erda Shower off cooldown",
+                backend::ActionCondition::InventoryFull => "inventory full",
                 backend::ActionCondition::Linked => "linked",
             };
             if modifying {
@@ -1143,7 +2193,7 @@ fn PopupActionInput(
                 format!("Add a new {name} action")
             }
         }
-        ActionInputKind::PingPongOrAutoMobbing(_) => "Modify mobbing skill".to_string(),
+        ActionInputKind::PingPongOrAutoMobbing(_, _) => "Modify mobbing skill".to_string(),
     };
 
     rsx! {
@@ -1166,7 +2216,7 @@ fn PopupActionInput(
                         ActionInputKind::Edit(_, index) => {
                             on_value(ActionInputValueKind::Edit(action, index));
                         }
-                        ActionInputKind::PingPongOrAutoMobbing(_) => {
+                        ActionInputKind::PingPongOrAutoMobbing(_, index) => {
                             let action = match action {
                                 Action::Move(_) => unreachable!(),
                                 Action::Key(action) => action,
@@ -1182,8 +2232,10 @@ fn PopupActionInput(
                                 wait_after_millis: action.wait_after_use_millis,
                                 wait_after_millis_random_range: action
                                     .wait_after_use_millis_random_range,
+                                hold_millis: None,
+                                linked_keys: [None; MAX_MOBBING_LINKED_KEYS],
                             };
-                            on_value(ActionInputValueKind::PingPongOrAutoMobbing(key));
+                            on_value(ActionInputValueKind::PingPongOrAutoMobbing(key, index));
                         }
                     }
                 },
@@ -1371,6 +2423,13 @@ fn ActionMoveInput(
                 },
                 value: action().wait_after_move_millis,
             }
+            ActionsSnapToPlatformButton {
+                position: action().position,
+                on_snap: move |position| {
+                    let mut action = action.write();
+                    action.position = position;
+                },
+            }
             if can_create_linked_action {
                 ActionsCheckbox {
                     label: "Linked action",
@@ -1500,6 +2559,15 @@ fn ActionKeyInput(
                         }
                     }
                 }
+                if let Some(pos) = action().position {
+                    ActionsSnapToPlatformButton {
+                        position: pos,
+                        on_snap: move |position| {
+                            let mut action = action.write();
+                            action.position = Some(position);
+                        },
+                    }
+                }
             }
 
             // Key, count and link key
@@ -1531,6 +2599,7 @@ fn ActionKeyInput(
                             value.condition
                         };
                         action.queue_to_front = None;
+                        action.burst = false;
                     },
                     value: matches!(action().condition, ActionCondition::Linked),
                 }
@@ -1594,7 +2663,9 @@ fn ActionKeyInput(
             }
             if matches!(
                 action().condition,
-                ActionCondition::EveryMillis(_) | ActionCondition::ErdaShowerOffCooldown
+                ActionCondition::EveryMillis(_)
+                | ActionCondition::ErdaShowerOffCooldown
+                | ActionCondition::InventoryFull
             )
             {
                 ActionsCheckbox {
@@ -1619,6 +2690,21 @@ fn ActionKeyInput(
                 }
                 div { class: "col-span-2" }
             }
+            if matches!(
+                action().condition,
+                ActionCondition::EveryMillis(_) | ActionCondition::ErdaShowerOffCooldown
+            )
+            {
+                ActionsCheckbox {
+                    label: "Burst",
+                    on_value: move |burst: bool| {
+                        let mut action = action.write();
+                        action.burst = burst;
+                    },
+                    value: action().burst,
+                }
+                div { class: "col-span-2" }
+            }
 
             // Wait before use
             ActionsMillisInput {
@@ -1734,13 +2820,29 @@ fn ActionList(
         }
     }
 
+    let mut action_stats = use_signal(HashMap::<usize, ActionStats>::new);
     let filtered = filter_actions(actions, condition_filter);
 
+    use_future(move || async move {
+        let mut receiver = game_state_receiver().await;
+        loop {
+            let state = match receiver.recv().await {
+                Ok(value) => value,
+                Err(RecvError::Closed) => break,
+                Err(RecvError::Lagged(_)) => continue,
+            };
+            if *action_stats.peek() != state.action_stats {
+                action_stats.set(state.action_stats);
+            }
+        }
+    });
+
     rsx! {
         div { class: "flex flex-col",
             for (action , index) in filtered {
                 div {
                     class: "relative group",
+                    title: abort_reason_title(action_stats().get(&index)),
                     onclick: move |e| {
                         e.stop_propagation();
                         on_item_click((action, index));
@@ -1826,6 +2928,7 @@ fn ActionKeyItem(action: ActionKey) -> Element {
         direction,
         with,
         queue_to_front,
+        burst,
         wait_before_use_millis,
         wait_after_use_millis,
         ..
@@ -1856,6 +2959,7 @@ fn ActionKeyItem(action: ActionKey) -> Element {
     } else {
         ""
     };
+    let burst = if burst { "✸ / " } else { "" };
     let linked_action = if matches!(condition, ActionCondition::Linked) {
         ""
     } else {
@@ -1897,7 +3001,9 @@ fn ActionKeyItem(action: ActionKey) -> Element {
 
     rsx! {
         div { class: "grid grid-cols-[140px_100px_30px_auto] h-6 paragraph-xs !text-gray-400 group-hover:bg-gray-900 {linked_action}",
-            div { class: "{ITEM_BORDER_CLASS} {ITEM_TEXT_CLASS}", "{queue_to_front}{position}" }
+            div { class: "{ITEM_BORDER_CLASS} {ITEM_TEXT_CLASS}",
+                "{burst}{queue_to_front}{position}"
+            }
             div { class: "{ITEM_BORDER_CLASS} {ITEM_TEXT_CLASS}", "{link_key}{key} × {count}" }
             div { class: "{ITEM_BORDER_CLASS} {ITEM_TEXT_CLASS}",
                 match direction {
@@ -1911,6 +3017,27 @@ fn ActionKeyItem(action: ActionKey) -> Element {
     }
 }
 
+#[component]
+fn ActionsSnapToPlatformButton(position: Position, on_snap: EventHandler<Position>) -> Element {
+    let minimap = use_context::<AppState>().minimap;
+    let snapped = use_memo(use_reactive!(|position| {
+        minimap().and_then(|minimap| find_snapped_position(&minimap.platforms, position))
+    }));
+
+    rsx! {
+        if let Some(snapped) = snapped() {
+            Button {
+                class: "col-span-3 border border-gray-600",
+                text: format!("Snap to platform at {}, {}", snapped.x, snapped.y),
+                kind: ButtonKind::Secondary,
+                on_click: move |_| {
+                    on_snap(snapped);
+                },
+            }
+        }
+    }
+}
+
 #[component]
 fn ActionsSelect<T: 'static + Clone + PartialEq + Display + IntoEnumIterator>(
     label: &'static str,
@@ -2049,6 +3176,17 @@ fn find_last_linked_action_index(
     Some(last_index)
 }
 
+/// Formats the most recent abort reason in `stats` for displaying as tooltip.
+fn abort_reason_title(stats: Option<&ActionStats>) -> Option<String> {
+    let stats = stats?;
+    let reason = stats.last_abort_reason?;
+
+    Some(format!(
+        "Aborted {} time(s), last reason: {reason}",
+        stats.abort_count
+    ))
+}
+
 /// Filters `actions` to find action with condition matching `condition_filter` including linked
 /// action(s) of that matching action.
 ///
@@ -2076,6 +3214,35 @@ fn filter_actions(actions: Vec<Action>, condition_filter: ActionCondition) -> Ve
     filtered
 }
 
+/// Finds the position snapped to the platform nearest to `position` in y-axis.
+///
+/// The snapped position has y equal to the platform's y and x clamped into the platform's range.
+/// Returns [`None`] if there is no platform within [`SNAP_PLATFORM_Y_THRESHOLD`] or `position` is
+/// already on the platform.
+fn find_snapped_position(platforms: &[Platform], position: Position) -> Option<Position> {
+    let platform = platforms
+        .iter()
+        .filter(|platform| (platform.y - position.y).abs() <= SNAP_PLATFORM_Y_THRESHOLD)
+        .min_by_key(|platform| {
+            let x_last = (platform.x_end - 1).max(platform.x_start);
+            let x_distance = if position.x < platform.x_start {
+                platform.x_start - position.x
+            } else {
+                (position.x - x_last).max(0)
+            };
+            ((platform.y - position.y).abs(), x_distance)
+        })?;
+    // Platform x range is end-exclusive
+    let x_last = (platform.x_end - 1).max(platform.x_start);
+    let snapped = Position {
+        x: position.x.clamp(platform.x_start, x_last),
+        y: platform.y,
+        ..position
+    };
+
+    (snapped != position).then_some(snapped)
+}
+
 #[inline]
 fn update_valid_platform_end(platform: &mut Platform) {
     platform.x_end = if platform.x_end <= platform.x_start {
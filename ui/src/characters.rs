@@ -1,9 +1,11 @@
 use std::{fmt::Display, fs::File, io::BufReader};
 
 use backend::{
-    ActionConfiguration, ActionConfigurationCondition, ActionKeyWith, Character, Class,
-    EliteBossBehavior, IntoEnumIterator, KeyBinding, KeyBindingConfiguration, LinkKeyBinding,
-    PotionMode, delete_character, query_characters, update_character, upsert_character,
+    ActionConfiguration, ActionConfigurationCondition, ActionKeyWith, BuffDuration, Character,
+    Class, DoubleJumpKeyTiming, EliteBossBehavior, InputHumanization, IntoEnumIterator,
+    InventoryFullBehavior, KeyBinding, KeyBindingConfiguration, KeyRemap, LinkKeyBinding,
+    MAX_KEY_REMAPS, PotionMode, UpJumpStyle, delete_character, query_characters, update_character,
+    upsert_character,
 };
 use dioxus::prelude::*;
 use futures_util::StreamExt;
@@ -16,6 +18,7 @@ use crate::{
     inputs::{Checkbox, KeyBindingInput, MillisInput, NumberInputU32, PercentageInput},
     popup::Popup,
     select::{EnumSelect, TextSelect},
+    settings::InputHumanizationInputs,
 };
 
 #[derive(Debug)]
@@ -116,6 +119,8 @@ pub fn Characters() -> Element {
     rsx! {
         div { class: "flex flex-col pb-15 h-full overflow-y-auto scrollbar",
             SectionKeyBindings { character_view, save_character }
+            SectionKeyRemaps { character_view, save_character }
+            SectionInputHumanization { character_view, save_character }
             SectionBuffs { character_view, save_character }
             SectionFixedActions {
                 action_input_kind,
@@ -235,6 +240,17 @@ fn SectionKeyBindings(
                     },
                     value: character_view().up_jump_key,
                 }
+                CharactersSelect::<UpJumpStyle> {
+                    label: "Up jump style",
+                    disabled: character_view().id.is_none(),
+                    on_select: move |up_jump_style| {
+                        save_character(Character {
+                            up_jump_style,
+                            ..character_view.peek().clone()
+                        });
+                    },
+                    selected: character_view().up_jump_style,
+                }
                 KeyBindingConfigurationInput {
                     label: "Interact",
                     disabled: character_view().id.is_none(),
@@ -301,6 +317,17 @@ fn SectionKeyBindings(
                     },
                     value: character_view().potion_key,
                 }
+                KeyBindingConfigurationInput {
+                    label: "Emergency",
+                    disabled: character_view().id.is_none(),
+                    on_value: move |key_config: Option<KeyBindingConfiguration>| {
+                        save_character(Character {
+                            emergency_key: key_config.expect("not optional"),
+                            ..character_view.peek().clone()
+                        });
+                    },
+                    value: character_view().emergency_key,
+                }
                 div { class: "col-span-full grid-cols-3 grid gap-2 justify-items-stretch",
                     KeyBindingConfigurationInput {
                         label: "Familiar menu",
@@ -341,6 +368,126 @@ fn SectionKeyBindings(
     }
 }
 
+#[component]
+fn SectionKeyRemaps(
+    character_view: Memo<Character>,
+    save_character: Callback<Character>,
+) -> Element {
+    let save_remaps = move |key_remaps: Vec<KeyRemap>| {
+        save_character(Character {
+            key_remaps,
+            ..character_view.peek().clone()
+        });
+    };
+    let remaps = character_view().key_remaps;
+    let remaps_len = remaps.len();
+
+    rsx! {
+        Section { name: "Key remaps",
+            div { class: "grid grid-cols-3 gap-3",
+                for (index , remap) in remaps.clone().into_iter().enumerate() {
+                    KeyBindingInput {
+                        label: "Bot key",
+                        disabled: character_view().id.is_none(),
+                        on_value: {
+                            let remaps = remaps.clone();
+                            move |key: Option<KeyBinding>| {
+                                let mut remaps = remaps.clone();
+                                remaps[index].from = key.expect("not optional");
+                                save_remaps(remaps);
+                            }
+                        },
+                        value: Some(remap.from),
+                    }
+                    KeyBindingInput {
+                        label: "In-game key",
+                        disabled: character_view().id.is_none(),
+                        on_value: {
+                            let remaps = remaps.clone();
+                            move |key: Option<KeyBinding>| {
+                                let mut remaps = remaps.clone();
+                                remaps[index].to = key.expect("not optional");
+                                save_remaps(remaps);
+                            }
+                        },
+                        value: Some(remap.to),
+                    }
+                    div { class: "flex items-end",
+                        Button {
+                            text: "Remove",
+                            kind: ButtonKind::Danger,
+                            disabled: character_view().id.is_none(),
+                            on_click: {
+                                let remaps = remaps.clone();
+                                move |_| {
+                                    let mut remaps = remaps.clone();
+                                    remaps.remove(index);
+                                    save_remaps(remaps);
+                                }
+                            },
+                            class: "w-full",
+                        }
+                    }
+                }
+            }
+            Button {
+                text: "Add remap",
+                kind: ButtonKind::Secondary,
+                disabled: character_view().id.is_none() || remaps_len >= MAX_KEY_REMAPS,
+                on_click: move |_| {
+                    let mut remaps = remaps.clone();
+                    remaps.push(KeyRemap::default());
+                    save_remaps(remaps);
+                },
+                class: "mt-2",
+            }
+            p { class: "paragraph-xs !text-gray-400 mt-2",
+                "Sends the in-game key whenever the bot sends the bot key (e.g. arrow keys bound to other keys in-game)."
+            }
+        }
+    }
+}
+
+#[component]
+fn SectionInputHumanization(
+    character_view: Memo<Character>,
+    save_character: Callback<Character>,
+) -> Element {
+    let humanization_view =
+        use_memo(move || character_view().input_humanization.unwrap_or_default());
+    let save_humanization = move |input_humanization: Option<InputHumanization>| {
+        save_character(Character {
+            input_humanization,
+            ..character_view.peek().clone()
+        });
+    };
+
+    rsx! {
+        Section { name: "Input humanization",
+            CharactersCheckbox {
+                label: "Override global input humanization",
+                div_class: "mb-2",
+                disabled: character_view().id.is_none(),
+                on_value: move |enabled: bool| {
+                    save_humanization(enabled.then(InputHumanization::default));
+                },
+                value: character_view().input_humanization.is_some(),
+            }
+            if character_view().input_humanization.is_some() {
+                InputHumanizationInputs {
+                    humanization_view,
+                    on_humanization: move |input_humanization| {
+                        save_humanization(Some(input_humanization));
+                    },
+                }
+            }
+            p { class: "paragraph-xs !text-gray-400 mt-2",
+                "Overrides the input humanization in the Settings tab for this character."
+            }
+        }
+    }
+}
+
 #[component]
 fn SectionBuffs(character_view: Memo<Character>, save_character: Callback<Character>) -> Element {
     #[component]
@@ -349,28 +496,56 @@ fn SectionBuffs(character_view: Memo<Character>, save_character: Callback<Charac
         disabled: bool,
         on_value: EventHandler<KeyBindingConfiguration>,
         value: KeyBindingConfiguration,
+        on_duration: EventHandler<BuffDuration>,
+        duration: BuffDuration,
     ) -> Element {
         rsx! {
-            div { class: "flex gap-2",
-                KeyBindingConfigurationInput {
-                    label,
-                    div_class: "flex-1",
-                    disabled,
-                    on_value: move |config: Option<KeyBindingConfiguration>| {
-                        on_value(config.expect("not optional"));
-                    },
-                    value: Some(value),
+            div { class: "flex flex-col gap-2",
+                div { class: "flex gap-2",
+                    KeyBindingConfigurationInput {
+                        label,
+                        div_class: "flex-1",
+                        disabled,
+                        on_value: move |config: Option<KeyBindingConfiguration>| {
+                            on_value(config.expect("not optional"));
+                        },
+                        value: Some(value),
+                    }
+                    CharactersCheckbox {
+                        label: "Enabled",
+                        disabled,
+                        on_value: move |enabled| {
+                            on_value(KeyBindingConfiguration {
+                                enabled,
+                                ..value
+                            });
+                        },
+                        value: value.enabled,
+                    }
                 }
-                CharactersCheckbox {
-                    label: "Enabled",
-                    disabled,
-                    on_value: move |enabled| {
-                        on_value(KeyBindingConfiguration {
-                            enabled,
-                            ..value
-                        });
-                    },
-                    value: value.enabled,
+                div { class: "grid grid-cols-2 gap-2",
+                    CharactersMillisInput {
+                        label: "Duration",
+                        disabled,
+                        on_value: move |duration_millis| {
+                            on_duration(BuffDuration {
+                                duration_millis,
+                                ..duration
+                            });
+                        },
+                        value: duration.duration_millis,
+                    }
+                    CharactersMillisInput {
+                        label: "Re-cast under",
+                        disabled: disabled || duration.duration_millis == 0,
+                        on_value: move |recast_remaining_millis| {
+                            on_duration(BuffDuration {
+                                recast_remaining_millis,
+                                ..duration
+                            });
+                        },
+                        value: duration.recast_remaining_millis,
+                    }
                 }
             }
         }
@@ -405,6 +580,13 @@ fn SectionBuffs(character_view: Memo<Character>, save_character: Callback<Charac
                         });
                     },
                     value: character_view().sayram_elixir_key,
+                    on_duration: move |sayram_elixir_duration| {
+                        save_character(Character {
+                            sayram_elixir_duration,
+                            ..character_view.peek().clone()
+                        });
+                    },
+                    duration: character_view().sayram_elixir_duration,
                 }
                 Buff {
                     label: "Aurelia's Elixir",
@@ -416,6 +598,13 @@ fn SectionBuffs(character_view: Memo<Character>, save_character: Callback<Charac
                         });
                     },
                     value: character_view().aurelia_elixir_key,
+                    on_duration: move |aurelia_elixir_duration| {
+                        save_character(Character {
+                            aurelia_elixir_duration,
+                            ..character_view.peek().clone()
+                        });
+                    },
+                    duration: character_view().aurelia_elixir_duration,
                 }
                 Buff {
                     label: "3x EXP Coupon",
@@ -427,6 +616,13 @@ fn SectionBuffs(character_view: Memo<Character>, save_character: Callback<Charac
                         });
                     },
                     value: character_view().exp_x3_key,
+                    on_duration: move |exp_x3_duration| {
+                        save_character(Character {
+                            exp_x3_duration,
+                            ..character_view.peek().clone()
+                        });
+                    },
+                    duration: character_view().exp_x3_duration,
                 }
                 Buff {
                     label: "50% Bonus EXP Coupon",
@@ -438,6 +634,13 @@ fn SectionBuffs(character_view: Memo<Character>, save_character: Callback<Charac
                         });
                     },
                     value: character_view().bonus_exp_key,
+                    on_duration: move |bonus_exp_duration| {
+                        save_character(Character {
+                            bonus_exp_duration,
+                            ..character_view.peek().clone()
+                        });
+                    },
+                    duration: character_view().bonus_exp_duration,
                 }
                 Buff {
                     label: "Legion's Wealth",
@@ -449,6 +652,13 @@ fn SectionBuffs(character_view: Memo<Character>, save_character: Callback<Charac
                         });
                     },
                     value: character_view().legion_wealth_key,
+                    on_duration: move |legion_wealth_duration| {
+                        save_character(Character {
+                            legion_wealth_duration,
+                            ..character_view.peek().clone()
+                        });
+                    },
+                    duration: character_view().legion_wealth_duration,
                 }
                 Buff {
                     label: "Legion's Luck",
@@ -460,6 +670,13 @@ fn SectionBuffs(character_view: Memo<Character>, save_character: Callback<Charac
                         });
                     },
                     value: character_view().legion_luck_key,
+                    on_duration: move |legion_luck_duration| {
+                        save_character(Character {
+                            legion_luck_duration,
+                            ..character_view.peek().clone()
+                        });
+                    },
+                    duration: character_view().legion_luck_duration,
                 }
                 Buff {
                     label: "Wealth Acquisition Potion",
@@ -471,6 +688,13 @@ fn SectionBuffs(character_view: Memo<Character>, save_character: Callback<Charac
                         });
                     },
                     value: character_view().wealth_acquisition_potion_key,
+                    on_duration: move |wealth_acquisition_potion_duration| {
+                        save_character(Character {
+                            wealth_acquisition_potion_duration,
+                            ..character_view.peek().clone()
+                        });
+                    },
+                    duration: character_view().wealth_acquisition_potion_duration,
                 }
                 Buff {
                     label: "EXP Accumulation Potion",
@@ -482,6 +706,13 @@ fn SectionBuffs(character_view: Memo<Character>, save_character: Callback<Charac
                         });
                     },
                     value: character_view().exp_accumulation_potion_key,
+                    on_duration: move |exp_accumulation_potion_duration| {
+                        save_character(Character {
+                            exp_accumulation_potion_duration,
+                            ..character_view.peek().clone()
+                        });
+                    },
+                    duration: character_view().exp_accumulation_potion_duration,
                 }
                 Buff {
                     label: "Extreme Red Potion",
@@ -493,6 +724,13 @@ fn SectionBuffs(character_view: Memo<Character>, save_character: Callback<Charac
                         });
                     },
                     value: character_view().extreme_red_potion_key,
+                    on_duration: move |extreme_red_potion_duration| {
+                        save_character(Character {
+                            extreme_red_potion_duration,
+                            ..character_view.peek().clone()
+                        });
+                    },
+                    duration: character_view().extreme_red_potion_duration,
                 }
                 Buff {
                     label: "Extreme Blue Potion",
@@ -504,6 +742,13 @@ fn SectionBuffs(character_view: Memo<Character>, save_character: Callback<Charac
                         });
                     },
                     value: character_view().extreme_blue_potion_key,
+                    on_duration: move |extreme_blue_potion_duration| {
+                        save_character(Character {
+                            extreme_blue_potion_duration,
+                            ..character_view.peek().clone()
+                        });
+                    },
+                    duration: character_view().extreme_blue_potion_duration,
                 }
                 Buff {
                     label: "Extreme Green Potion",
@@ -515,6 +760,13 @@ fn SectionBuffs(character_view: Memo<Character>, save_character: Callback<Charac
                         });
                     },
                     value: character_view().extreme_green_potion_key,
+                    on_duration: move |extreme_green_potion_duration| {
+                        save_character(Character {
+                            extreme_green_potion_duration,
+                            ..character_view.peek().clone()
+                        });
+                    },
+                    duration: character_view().extreme_green_potion_duration,
                 }
                 Buff {
                     label: "Extreme Gold Potion",
@@ -526,6 +778,13 @@ fn SectionBuffs(character_view: Memo<Character>, save_character: Callback<Charac
                         });
                     },
                     value: character_view().extreme_gold_potion_key,
+                    on_duration: move |extreme_gold_potion_duration| {
+                        save_character(Character {
+                            extreme_gold_potion_duration,
+                            ..character_view.peek().clone()
+                        });
+                    },
+                    duration: character_view().extreme_gold_potion_duration,
                 }
             }
         }
@@ -713,6 +972,43 @@ fn SectionOthers(character_view: Memo<Character>, save_character: Callback<Chara
                                 },
                                 value: character_view().health_update_millis,
                             }
+                            CharactersPercentageInput {
+                                label: "Use emergency below health",
+                                disabled: character_view().id.is_none(),
+                                on_value: move |emergency_health_percent| {
+                                    save_character(Character {
+                                        emergency_health_percent,
+                                        ..character_view.peek().clone()
+                                    });
+                                },
+                                value: character_view().emergency_health_percent,
+                            }
+                            CharactersCheckbox {
+                                label: "Use emergency",
+                                disabled: character_view().id.is_none(),
+                                on_value: move |enabled| {
+                                    let character = character_view.peek().clone();
+                                    save_character(Character {
+                                        emergency_key: KeyBindingConfiguration {
+                                            enabled,
+                                            ..character.emergency_key
+                                        },
+                                        ..character
+                                    });
+                                },
+                                value: character_view().emergency_key.enabled,
+                            }
+                            CharactersCheckbox {
+                                label: "Stop if potion has no effect",
+                                disabled: character_view().id.is_none(),
+                                on_value: move |halt_on_potion_no_effect| {
+                                    save_character(Character {
+                                        halt_on_potion_no_effect,
+                                        ..character_view.peek().clone()
+                                    });
+                                },
+                                value: character_view().halt_on_potion_no_effect,
+                            }
                         }
                     },
                 }
@@ -787,6 +1083,42 @@ fn SectionOthers(character_view: Memo<Character>, save_character: Callback<Chara
                     },
                     value: character_view().elite_boss_behavior_enabled,
                 }
+                CharactersSelect::<InventoryFullBehavior> {
+                    label: "Inventory full behavior",
+                    disabled: character_view().id.is_none(),
+                    on_select: move |inventory_full_behavior| {
+                        save_character(Character {
+                            inventory_full_behavior,
+                            ..character_view.peek().clone()
+                        });
+                    },
+                    selected: character_view().inventory_full_behavior,
+                }
+                div {}
+                div {}
+                CharactersSelect::<DoubleJumpKeyTiming> {
+                    label: "Double jump key timing",
+                    disabled: character_view().id.is_none(),
+                    on_select: move |double_jump_key_timing| {
+                        save_character(Character {
+                            double_jump_key_timing,
+                            ..character_view.peek().clone()
+                        });
+                    },
+                    selected: character_view().double_jump_key_timing,
+                }
+                CharactersMillisInput {
+                    label: "Double jump key delay",
+                    disabled: character_view().id.is_none(),
+                    on_value: move |double_jump_key_delay_millis| {
+                        save_character(Character {
+                            double_jump_key_delay_millis,
+                            ..character_view.peek().clone()
+                        });
+                    },
+                    value: character_view().double_jump_key_delay_millis,
+                }
+                div {}
                 div { class: "flex gap-2 col-span-3",
                     div { class: "flex-grow",
                         a {
@@ -1,11 +1,25 @@
-use backend::{capture_image, infer_minimap, infer_rune, record_images, test_spin_rune};
+use std::time::Duration;
+
+use backend::{
+    PlayerStateSnapshot, capture_image, infer_minimap, infer_rune, player_state_snapshot,
+    record_images, test_spin_rune,
+};
 use dioxus::prelude::*;
+use tokio::time::sleep;
 
 use crate::button::{Button, ButtonKind};
 
 #[component]
 pub fn Debug() -> Element {
     let mut is_recording = use_signal(|| false);
+    let mut snapshot = use_signal(PlayerStateSnapshot::default);
+
+    use_future(move || async move {
+        loop {
+            snapshot.set(player_state_snapshot().await);
+            sleep(Duration::from_millis(500)).await;
+        }
+    });
 
     rsx! {
         div { class: "flex flex-col h-full overflow-y-auto scrollbar pr-4 pb-3",
@@ -55,6 +69,77 @@ pub fn Debug() -> Element {
                     },
                 }
             }
+            PlayerStateSnapshotInfo { snapshot }
+        }
+    }
+}
+
+#[component]
+fn PlayerStateSnapshotInfo(snapshot: ReadOnlySignal<PlayerStateSnapshot>) -> Element {
+    let optional = |value: Option<String>| value.unwrap_or("None".to_string());
+    let reachable_ys = use_memo(move || {
+        let mut ys = snapshot().reachable_y_map.into_iter().collect::<Vec<_>>();
+        ys.sort_unstable();
+        ys.into_iter()
+            .map(|(y, count)| format!("{y} ({count})"))
+            .collect::<Vec<_>>()
+            .join(", ")
+    });
+    let ignore_xs = use_memo(move || {
+        let mut ys = snapshot().ignore_xs_map.into_iter().collect::<Vec<_>>();
+        ys.sort_unstable_by_key(|(y, _)| *y);
+        ys.into_iter()
+            .map(|(y, xs)| {
+                let xs = xs
+                    .into_iter()
+                    .map(|((start, end), count)| format!("{start}..{end} ({count})"))
+                    .collect::<Vec<_>>()
+                    .join(", ");
+                format!("{y}: {xs}")
+            })
+            .collect::<Vec<_>>()
+    });
+
+    rsx! {
+        div { class: "grid grid-cols-2 items-center gap-1 mt-3",
+            SnapshotItem { name: "State", value: snapshot().state }
+            SnapshotItem {
+                name: "Last movement",
+                value: optional(snapshot().last_movement),
+            }
+            SnapshotItem {
+                name: "Normal action id",
+                value: optional(snapshot().normal_action_id.map(|id| id.to_string())),
+            }
+            SnapshotItem {
+                name: "Priority action id",
+                value: optional(snapshot().priority_action_id.map(|id| id.to_string())),
+            }
+            SnapshotItem {
+                name: "Velocity",
+                value: format!("{:.2}, {:.2}", snapshot().velocity.0, snapshot().velocity.1),
+            }
+            SnapshotItem {
+                name: "Unstuck count",
+                value: snapshot().unstuck_count.to_string(),
+            }
+            SnapshotItem {
+                name: "Unstuck transitioned count",
+                value: snapshot().unstuck_transitioned_count.to_string(),
+            }
+            SnapshotItem { name: "Reachable ys", value: reachable_ys() }
+            p { class: "paragraph font-mono col-span-2", "Ignored xs" }
+            for ignore_xs in ignore_xs() {
+                p { class: "paragraph text-right font-mono col-span-2", "{ignore_xs}" }
+            }
         }
     }
 }
+
+#[component]
+fn SnapshotItem(name: String, value: String) -> Element {
+    rsx! {
+        p { class: "paragraph font-mono", "{name}" }
+        p { class: "paragraph text-right font-mono", "{value}" }
+    }
+}
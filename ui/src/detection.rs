@@ -0,0 +1,58 @@
+use std::time::Duration;
+
+use backend::query_detection_frame;
+use dioxus::prelude::*;
+use tokio::time::sleep;
+
+use crate::button::{Button, ButtonKind};
+
+/// The delay between each detection frame query.
+const REFRESH_DELAY: Duration = Duration::from_millis(1000);
+
+#[component]
+pub fn Detection() -> Element {
+    let mut is_live = use_signal(|| true);
+    let mut frame = use_signal::<Option<Option<String>>>(|| None);
+
+    use_future(move || async move {
+        loop {
+            if is_live() {
+                frame.set(Some(query_detection_frame().await));
+            }
+            sleep(REFRESH_DELAY).await;
+        }
+    });
+
+    rsx! {
+        div { class: "flex flex-col h-full overflow-y-auto scrollbar pr-4 pb-3",
+            div { class: "flex items-center justify-between h-10",
+                p { class: "title-xs", "Detection overlay" }
+                Button {
+                    class: "w-24",
+                    text: if is_live() { "Pause" } else { "Resume" },
+                    kind: ButtonKind::Secondary,
+                    on_click: move |_| {
+                        is_live.toggle();
+                    },
+                }
+            }
+            match frame() {
+                Some(Some(base64)) => rsx! {
+                    img {
+                        src: format!("data:image/png;base64,{}", base64),
+                        class: "w-full border border-gray-600",
+                    }
+                },
+                Some(None) => rsx! {
+                    p { class: "paragraph-xs !text-gray-400", "No frame has been captured yet." }
+                },
+                None => rsx! {
+                    p { class: "paragraph-xs !text-gray-400", "Capturing..." }
+                },
+            }
+            p { class: "paragraph-xs !text-gray-400 mt-2",
+                "Shows what the bot detects on the captured frame: minimap (white), player (yellow), rune (magenta), portals (cyan), health bar (green) and mobs (red). Detecting is slow, so pause the overlay when it is not needed."
+            }
+        }
+    }
+}
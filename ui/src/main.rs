@@ -6,10 +6,14 @@
 use std::{env::current_exe, io::stdout, string::ToString, sync::LazyLock};
 
 use actions::Actions;
-use backend::{Character, Minimap as MinimapData, Settings as SettingsData};
+use backend::{
+    Character, DatabaseEvent, Minimap as MinimapData, Settings as SettingsData,
+    database_event_receiver,
+};
 use characters::Characters;
 #[cfg(debug_assertions)]
 use debug::Debug;
+use detection::Detection;
 use dioxus::{
     desktop::{
         WindowBuilder,
@@ -23,27 +27,39 @@ use log::LevelFilter;
 use minimap::Minimap;
 use navigation::Navigation;
 use rand::distr::{Alphanumeric, SampleString};
+use resume::Resume;
+use schedule::Schedule;
 use settings::Settings;
+use statistics::Statistics;
+use tokio::sync::broadcast::error::RecvError;
 
 mod actions;
 mod button;
 mod characters;
 #[cfg(debug_assertions)]
 mod debug;
+mod detection;
 mod icons;
 mod inputs;
 mod minimap;
 mod navigation;
+mod performance;
 mod popup;
+mod resume;
+mod schedule;
 mod select;
 mod settings;
+mod statistics;
 
 const TAILWIND_CSS: Asset = asset!("public/tailwind.css");
 const AUTO_NUMERIC_JS: Asset = asset!("assets/autoNumeric.min.js");
 const TAB_ACTIONS: &str = "Actions";
 const TAB_CHARACTERS: &str = "Characters";
 const TAB_NAVIGATION: &str = "Navigation";
+const TAB_SCHEDULE: &str = "Schedule";
 const TAB_SETTINGS: &str = "Settings";
+const TAB_STATISTICS: &str = "Statistics";
+const TAB_DETECTION: &str = "Detection";
 #[cfg(debug_assertions)]
 const TAB_DEBUG: &str = "Debug";
 
@@ -52,7 +68,10 @@ static TABS: LazyLock<Vec<String>> = LazyLock::new(|| {
         TAB_ACTIONS.to_string(),
         TAB_CHARACTERS.to_string(),
         TAB_NAVIGATION.to_string(),
+        TAB_SCHEDULE.to_string(),
         TAB_SETTINGS.to_string(),
+        TAB_STATISTICS.to_string(),
+        TAB_DETECTION.to_string(),
         #[cfg(debug_assertions)]
         TAB_DEBUG.to_string(),
     ]
@@ -107,7 +126,7 @@ fn App() -> Element {
     let mut selected_tab = use_signal(|| TAB_CHARACTERS.to_string());
     let mut script_loaded = use_signal(|| false);
 
-    use_context_provider(|| AppState {
+    let mut app_state = use_context_provider(|| AppState {
         minimap: Signal::new(None),
         minimap_preset: Signal::new(None),
         character: Signal::new(None),
@@ -115,6 +134,21 @@ fn App() -> Element {
         position: Signal::new((0, 0)),
     });
 
+    // Keeps the settings in sync when updated by the backend (e.g. capture mode fallback)
+    use_future(move || async move {
+        let mut rx = database_event_receiver();
+        loop {
+            let event = match rx.recv().await {
+                Ok(value) => value,
+                Err(RecvError::Closed) => break,
+                Err(RecvError::Lagged(_)) => continue,
+            };
+            if let DatabaseEvent::SettingsUpdated(settings) = event {
+                app_state.settings.set(Some(settings));
+            }
+        }
+    });
+
     // Thanks dioxus
     use_future(move || async move {
         let mut eval = document::eval(
@@ -160,6 +194,15 @@ fn App() -> Element {
                             TAB_NAVIGATION => rsx! {
                                 Navigation {}
                             },
+                            TAB_SCHEDULE => rsx! {
+                                Schedule {}
+                            },
+                            TAB_STATISTICS => rsx! {
+                                Statistics {}
+                            },
+                            TAB_DETECTION => rsx! {
+                                Detection {}
+                            },
                             #[cfg(debug_assertions)]
                             TAB_DEBUG => rsx! {
                                 Debug {}
@@ -168,6 +211,7 @@ fn App() -> Element {
                         }
                     }
                 }
+                Resume {}
             }
         }
     }
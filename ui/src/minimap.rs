@@ -6,9 +6,10 @@ use std::{
 };
 
 use backend::{
-    Action, ActionKey, ActionMove, DatabaseEvent, GameOperation, Minimap as MinimapData, Position,
-    RotationMode, create_minimap, database_event_receiver, delete_minimap, game_state_receiver,
-    query_minimaps, redetect_minimap, rotate_actions, update_minimap, upsert_minimap,
+    Action, ActionKey, ActionMove, DatabaseEvent, GameOperation, Minimap as MinimapData, Platform,
+    Position, QueuedAction, RotationMode, create_minimap, database_event_receiver, delete_minimap,
+    game_state_receiver, pause_actions, query_minimaps, redetect_minimap, rotate_actions,
+    update_minimap, upsert_minimap,
 };
 use dioxus::{document::EvalError, prelude::*};
 use futures_util::StreamExt;
@@ -19,9 +20,13 @@ use tokio::{sync::broadcast::error::RecvError, time::sleep};
 use crate::{
     AppState,
     button::{Button, ButtonKind},
+    performance::Performance,
     select::TextSelect,
 };
 
+/// Maximum number of queued actions shown in the info.
+const MAX_NEXT_ACTIONS: usize = 5;
+
 const BACKGROUND: Asset = asset!(
     "assets/background.png",
     ImageAssetOptions::new().with_webp()
@@ -32,7 +37,7 @@ const MINIMAP_JS: &str = r#"
     const canvasCtx = canvas.getContext("2d");
 
     while (true) {
-        const [buffer, width, height, destinations, bound, quadrant, portals] = await dioxus.recv();
+        const [buffer, width, height, destinations, bound, quadrant, portals, rune] = await dioxus.recv();
         const data = new ImageData(new Uint8ClampedArray(buffer), width, height);
         const bitmap = await createImageBitmap(data);
 
@@ -75,6 +80,18 @@ const MINIMAP_JS: &str = r#"
             canvasCtx.strokeRect(x, y, w, h);
         }
 
+        if (rune !== null) {
+            const [runeX, runeY] = rune;
+            const x = (runeX / width) * canvas.width;
+            const y = ((height - runeY) / height) * canvas.height;
+
+            canvasCtx.setLineDash([]);
+            canvasCtx.strokeStyle = "rgb(255, 214, 10)";
+            canvasCtx.beginPath();
+            canvasCtx.arc(x, y, 4, 0, 2 * Math.PI);
+            canvasCtx.stroke();
+        }
+
         if (quadrant !== null && bound !== null) {
             canvasCtx.strokeStyle = "rgb(254, 71, 57)";
 
@@ -276,6 +293,131 @@ const MINIMAP_ACTIONS_JS: &str = r#"
     }
 "#;
 
+const MINIMAP_PLATFORMS_JS: &str = r#"
+    const canvas = document.getElementById("canvas-minimap-platforms");
+    const canvasCtx = canvas.getContext("2d");
+    const [width, height, platforms] = await dioxus.recv();
+    // Maximum distance in minimap pixels for the cursor to be considered on a platform
+    const threshold = 3;
+    let drag = null;
+
+    draw();
+
+    canvas.onmousedown = (e) => {
+        if (e.button !== 0) {
+            return;
+        }
+        const [x, y] = toMinimap(e);
+        const index = findPlatform(x, y);
+        if (index === null) {
+            platforms.push({ x_start: x, x_end: x, y: y, moving_x_range: 0, moving_period_millis: 0 });
+            drag = { index: platforms.length - 1, kind: "End" };
+            draw();
+            return;
+        }
+
+        const platform = platforms[index];
+        if (Math.abs(platform.x_start - x) <= threshold) {
+            drag = { index, kind: "Start" };
+        } else if (Math.abs(platform.x_end - x) <= threshold) {
+            drag = { index, kind: "End" };
+        } else {
+            drag = { index, kind: "Move", x, y, platform: { ...platform } };
+        }
+    };
+    canvas.onmousemove = (e) => {
+        if (drag === null) {
+            return;
+        }
+        const [x, y] = toMinimap(e);
+        const platform = platforms[drag.index];
+        switch (drag.kind) {
+            case "Start":
+                platform.x_start = x;
+                platform.y = y;
+                break;
+            case "End":
+                platform.x_end = x;
+                platform.y = y;
+                break;
+            case "Move":
+                platform.x_start = drag.platform.x_start + x - drag.x;
+                platform.x_end = drag.platform.x_end + x - drag.x;
+                platform.y = drag.platform.y + y - drag.y;
+                break;
+        }
+        draw();
+    };
+    canvas.onmouseup = () => {
+        if (drag === null) {
+            return;
+        }
+        const platform = platforms[drag.index];
+        if (platform.x_start > platform.x_end) {
+            [platform.x_start, platform.x_end] = [platform.x_end, platform.x_start];
+        }
+        if (platform.x_start === platform.x_end) {
+            platforms.splice(drag.index, 1);
+        }
+        drag = null;
+        draw();
+        dioxus.send(platforms);
+    };
+    canvas.oncontextmenu = (e) => {
+        e.preventDefault();
+        const [x, y] = toMinimap(e);
+        const index = findPlatform(x, y);
+        if (index === null) {
+            return;
+        }
+        platforms.splice(index, 1);
+        draw();
+        dioxus.send(platforms);
+    };
+
+    function toMinimap(e) {
+        const rect = canvas.getBoundingClientRect();
+        const x = Math.round(((e.clientX - rect.left) / rect.width) * width);
+        const y = Math.round(height - ((e.clientY - rect.top) / rect.height) * height);
+        return [x, y];
+    }
+
+    function findPlatform(x, y) {
+        for (let i = platforms.length - 1; i >= 0; i--) {
+            const platform = platforms[i];
+            if (
+                Math.abs(platform.y - y) <= threshold &&
+                x >= platform.x_start - threshold &&
+                x <= platform.x_end + threshold
+            ) {
+                return i;
+            }
+        }
+        return null;
+    }
+
+    function draw() {
+        const handleSize = 4;
+        const handleSizeHalf = handleSize / 2;
+
+        canvasCtx.clearRect(0, 0, canvas.width, canvas.height);
+        canvasCtx.setLineDash([]);
+        canvasCtx.fillStyle = "rgb(255, 255, 255)";
+        canvasCtx.strokeStyle = "rgb(255, 160, 37)";
+        for (const platform of platforms) {
+            const xStart = (platform.x_start / width) * canvas.width;
+            const xEnd = (platform.x_end / width) * canvas.width;
+            const y = ((height - platform.y) / height) * canvas.height;
+            canvasCtx.beginPath();
+            canvasCtx.moveTo(xStart, y);
+            canvasCtx.lineTo(xEnd, y);
+            canvasCtx.stroke();
+            canvasCtx.fillRect(xStart - handleSizeHalf, y - handleSizeHalf, handleSize, handleSize);
+            canvasCtx.fillRect(xEnd - handleSizeHalf, y - handleSizeHalf, handleSize, handleSize);
+        }
+    }
+"#;
+
 #[derive(Clone, PartialEq, Serialize)]
 struct ActionView {
     x: i32,
@@ -292,7 +434,11 @@ struct MinimapState {
     priority_action: Option<String>,
     erda_shower_state: String,
     operation: GameOperation,
+    fps: f32,
     detected_size: Option<(usize, usize)>,
+    panic_key_triggered: bool,
+    buffs_remaining_millis: Vec<(String, u64)>,
+    queued_actions: Vec<QueuedAction>,
 }
 
 #[derive(Debug)]
@@ -301,6 +447,7 @@ enum MinimapUpdate {
     Create(String),
     Import(MinimapData),
     Delete,
+    UpdatePlatforms(Vec<Platform>),
 }
 
 #[component]
@@ -330,6 +477,8 @@ pub fn Minimap() -> Element {
 
     // Game state for displaying info
     let state = use_signal::<Option<MinimapState>>(|| None);
+    // Whether platforms can be created and edited by dragging on the minimap
+    let editing_platforms = use_signal(|| false);
     // Handles async operations for minimap-related
     let coroutine = use_coroutine(move |mut rx: UnboundedReceiver<MinimapUpdate>| async move {
         while let Some(message) = rx.next().await {
@@ -360,6 +509,16 @@ pub fn Minimap() -> Element {
                         minimap_preset.set(None);
                     }
                 }
+                MinimapUpdate::UpdatePlatforms(platforms) => {
+                    let Some(mut current_minimap) = minimap() else {
+                        continue;
+                    };
+
+                    current_minimap.platforms = platforms;
+                    if let Some(current_minimap) = upsert_minimap(current_minimap).await {
+                        minimap.set(Some(current_minimap));
+                    }
+                }
             }
         }
     });
@@ -401,6 +560,43 @@ pub fn Minimap() -> Element {
             }
         }
     });
+    // Follows the minimap and preset changed by the backend (e.g. auto-selected on map changed)
+    use_future(move || async move {
+        let mut rx = game_state_receiver().await;
+        let mut last_selection = None;
+        loop {
+            let state = match rx.recv().await {
+                Ok(value) => value,
+                Err(RecvError::Closed) => break,
+                Err(RecvError::Lagged(_)) => continue,
+            };
+            let selection = (state.minimap_id, state.minimap_preset);
+            if last_selection.as_ref() == Some(&selection) {
+                continue;
+            }
+            last_selection = Some(selection.clone());
+
+            let (Some(id), preset) = selection else {
+                continue;
+            };
+            if minimap.peek().as_ref().and_then(|minimap| minimap.id) == Some(id)
+                && *minimap_preset.peek() == preset
+            {
+                continue;
+            }
+            let Some(selected) = query_minimaps()
+                .await
+                .unwrap_or_default()
+                .into_iter()
+                .find(|minimap| minimap.id == Some(id))
+            else {
+                continue;
+            };
+
+            minimap.set(Some(selected));
+            minimap_preset.set(preset);
+        }
+    });
 
     rsx! {
         div { class: "relative flex flex-col flex-none w-xs xl:w-md z-0",
@@ -413,9 +609,11 @@ pub fn Minimap() -> Element {
                 minimap,
                 minimap_preset,
                 position,
+                editing_platforms,
             }
-            Buttons { state, minimap }
+            Buttons { state, minimap, editing_platforms }
             Info { state, minimap }
+            Performance {}
             div { class: "flex-grow flex items-end px-2",
                 div { class: "flex flex-col items-end w-full",
                     ImportExport { minimap }
@@ -458,14 +656,19 @@ fn Canvas(
     minimap: ReadOnlySignal<Option<MinimapData>>,
     minimap_preset: ReadOnlySignal<Option<String>>,
     position: Signal<(i32, i32)>,
+    editing_platforms: ReadOnlySignal<bool>,
 ) -> Element {
+    let coroutine = use_coroutine_handle::<MinimapUpdate>();
+    let mut platforms_editor = use_signal::<Option<Task>>(|| None);
     let mut platforms_bound = use_signal(|| None);
     let rotation_bound_and_type = use_memo(move || {
         let platforms_bound = platforms_bound();
         let minimap = minimap()?;
 
         match minimap.rotation_mode {
-            RotationMode::StartToEnd | RotationMode::StartToEndThenReverse => None,
+            RotationMode::StartToEnd
+            | RotationMode::StartToEndThenReverse
+            | RotationMode::Bossing => None,
             RotationMode::AutoMobbing => Some((
                 platforms_bound.unwrap_or(minimap.rotation_auto_mob_bound),
                 "AutoMobbing",
@@ -514,6 +717,26 @@ fn Canvas(
             ));
         });
     });
+    // Starts the platforms editor with the current platforms and saves the edited platforms
+    use_effect(move || {
+        if let Some(task) = platforms_editor.take() {
+            task.cancel();
+        }
+        if !editing_platforms() {
+            return;
+        }
+        let Some(minimap) = minimap() else {
+            return;
+        };
+
+        platforms_editor.set(Some(spawn(async move {
+            let mut editor = document::eval(MINIMAP_PLATFORMS_JS);
+            let _ = editor.send((minimap.width, minimap.height, minimap.platforms));
+            while let Ok(platforms) = editor.recv::<Vec<Platform>>().await {
+                coroutine.send(MinimapUpdate::UpdatePlatforms(platforms));
+            }
+        })));
+    });
     // Draw minimap and update game state
     use_future(move || async move {
         let mut canvas = document::eval(MINIMAP_JS);
@@ -529,6 +752,7 @@ fn Canvas(
                 .map(|quadrant| quadrant.to_string());
             let frame = current_state.frame;
             let portals = current_state.portals;
+            let rune = current_state.rune;
             let current_state = MinimapState {
                 position: current_state.position,
                 health: current_state.health,
@@ -537,7 +761,11 @@ fn Canvas(
                 priority_action: current_state.priority_action,
                 erda_shower_state: current_state.erda_shower_state,
                 operation: current_state.operation,
+                fps: current_state.fps,
                 detected_size: frame.as_ref().map(|(_, width, height)| (*width, *height)),
+                panic_key_triggered: current_state.panic_key_triggered,
+                buffs_remaining_millis: current_state.buffs_remaining_millis,
+                queued_actions: current_state.queued_actions,
             };
 
             if *platforms_bound.peek() != bound {
@@ -556,9 +784,16 @@ fn Canvas(
             let Some((frame, width, height)) = frame else {
                 continue;
             };
-            let Err(error) =
-                canvas.send((frame, width, height, destinations, bound, quadrant, portals))
-            else {
+            let Err(error) = canvas.send((
+                frame,
+                width,
+                height,
+                destinations,
+                bound,
+                quadrant,
+                portals,
+                rune,
+            )) else {
                 continue;
             };
             if matches!(error, EvalError::Finished) {
@@ -568,8 +803,20 @@ fn Canvas(
         }
     });
 
+    // Flashes the minimap when the panic key is triggered
+    let panic_class = use_memo(move || {
+        if state().is_some_and(|state| state.panic_key_triggered) {
+            "ring-4 ring-red-500 animate-pulse"
+        } else {
+            ""
+        }
+    });
+
+    // Only shows the platforms editor canvas on top for receiving mouse events when editing
+    let platforms_editor_class = use_memo(move || if editing_platforms() { "" } else { "hidden" });
+
     rsx! {
-        div { class: "relative h-31 xl:h-38 rounded-2xl bg-gray-900",
+        div { class: "relative h-31 xl:h-38 rounded-2xl bg-gray-900 {panic_class}",
             canvas {
                 class: "absolute inset-0 rounded-2xl w-full h-full",
                 id: "canvas-minimap",
@@ -578,6 +825,10 @@ fn Canvas(
                 class: "absolute inset-0 rounded-2xl w-full h-full",
                 id: "canvas-minimap-actions",
             }
+            canvas {
+                class: "absolute inset-0 rounded-2xl w-full h-full cursor-crosshair {platforms_editor_class}",
+                id: "canvas-minimap-platforms",
+            }
         }
     }
 }
@@ -595,9 +846,12 @@ fn Info(
         normal_action: String,
         priority_action: String,
         erda_shower_state: String,
+        fps: String,
         detected_minimap_size: String,
         selected_minimap_size: String,
         cycle_duration: String,
+        buffs_remaining: Vec<(String, String)>,
+        next_actions: Vec<(String, String)>,
     }
 
     let info = use_memo(move || {
@@ -608,9 +862,12 @@ fn Info(
             normal_action: "None".to_string(),
             priority_action: "None".to_string(),
             erda_shower_state: "Unknown".to_string(),
+            fps: "Unknown".to_string(),
             detected_minimap_size: "Unknown".to_string(),
             selected_minimap_size: "Unknown".to_string(),
             cycle_duration: "None".to_string(),
+            buffs_remaining: vec![],
+            next_actions: vec![],
         };
 
         if let Some(minimap) = minimap() {
@@ -620,8 +877,11 @@ fn Info(
         if let Some(state) = state() {
             info.state = state.state;
             info.erda_shower_state = state.erda_shower_state;
+            info.fps = format!("{:.1}", state.fps);
             info.cycle_duration = match state.operation {
-                GameOperation::Halting | GameOperation::Running => "None".to_string(),
+                GameOperation::Halting | GameOperation::Running | GameOperation::Paused => {
+                    "None".to_string()
+                }
                 GameOperation::HaltUntil(instant) | GameOperation::RunUntil(instant) => {
                     let duration = instant.saturating_duration_since(Instant::now());
                     let seconds = duration.as_secs() % 60;
@@ -645,6 +905,32 @@ fn Info(
             if let Some((width, height)) = state.detected_size {
                 info.detected_minimap_size = format!("{width}px x {height}px")
             }
+            info.buffs_remaining = state
+                .buffs_remaining_millis
+                .into_iter()
+                .map(|(buff, millis)| {
+                    let seconds = (millis / 1000) % 60;
+                    let minutes = millis / 1000 / 60;
+                    (buff, format!("{minutes:0>2}:{seconds:0>2}"))
+                })
+                .collect();
+            info.next_actions = state
+                .queued_actions
+                .into_iter()
+                .take(MAX_NEXT_ACTIONS)
+                .map(|action| {
+                    let eta = match action.eta_millis {
+                        Some(0) => "Now".to_string(),
+                        Some(millis) => {
+                            let seconds = (millis / 1000) % 60;
+                            let minutes = millis / 1000 / 60;
+                            format!("{minutes:0>2}:{seconds:0>2}")
+                        }
+                        None => "Unknown".to_string(),
+                    };
+                    (format!("Next {}", action.name), eta)
+                })
+                .collect();
         }
 
         info
@@ -658,9 +944,16 @@ fn Info(
             InfoItem { name: "Priority action", value: info().priority_action }
             InfoItem { name: "Normal action", value: info().normal_action }
             InfoItem { name: "Erda Shower", value: info().erda_shower_state }
+            InfoItem { name: "FPS", value: info().fps }
             InfoItem { name: "Detected size", value: info().detected_minimap_size }
             InfoItem { name: "Selected size", value: info().selected_minimap_size }
             InfoItem { name: "Run/stop cycle", value: info().cycle_duration }
+            for (buff , remaining) in info().buffs_remaining {
+                InfoItem { name: buff, value: remaining }
+            }
+            for (action , eta) in info().next_actions {
+                InfoItem { name: action, value: eta }
+            }
         }
     }
 }
@@ -677,12 +970,18 @@ fn InfoItem(name: String, value: String) -> Element {
 fn Buttons(
     state: ReadOnlySignal<Option<MinimapState>>,
     minimap: ReadOnlySignal<Option<MinimapData>>,
+    editing_platforms: Signal<bool>,
 ) -> Element {
     let halting = use_memo(move || {
         state()
             .map(|state| matches!(state.operation, GameOperation::Halting))
             .unwrap_or_default()
     });
+    let paused = use_memo(move || {
+        state()
+            .map(|state| matches!(state.operation, GameOperation::Paused))
+            .unwrap_or_default()
+    });
     let character = use_context::<AppState>().character;
 
     rsx! {
@@ -696,6 +995,15 @@ fn Buttons(
                     rotate_actions(!*halting.peek()).await;
                 },
             }
+            Button {
+                class: "w-20",
+                text: if paused() { "Resume" } else { "Pause" },
+                kind: ButtonKind::Primary,
+                disabled: halting(),
+                on_click: move || async move {
+                    pause_actions(!*paused.peek()).await;
+                },
+            }
             Button {
                 class: "w-20",
                 text: "Re-detect",
@@ -704,6 +1012,15 @@ fn Buttons(
                     redetect_minimap().await;
                 },
             }
+            Button {
+                class: "w-20",
+                text: if editing_platforms() { "Done" } else { "Platforms" },
+                kind: ButtonKind::Primary,
+                disabled: minimap().is_none(),
+                on_click: move |_| {
+                    editing_platforms.toggle();
+                },
+            }
         }
     }
 }
@@ -1,7 +1,8 @@
 use backend::{
-    DatabaseEvent, NavigationPath, NavigationPoint, NavigationTransition, create_navigation_path,
-    database_event_receiver, delete_navigation_path, query_navigation_paths,
-    recapture_navigation_path, upsert_minimap, upsert_navigation_path,
+    DatabaseEvent, KeyBinding, NavigationPath, NavigationPoint, NavigationTransition,
+    create_navigation_path, database_event_receiver, delete_navigation_path, game_state_receiver,
+    query_navigation_paths, recapture_navigation_path, record_navigation, upsert_minimap,
+    upsert_navigation_path,
 };
 use dioxus::prelude::*;
 use futures_util::StreamExt;
@@ -11,9 +12,9 @@ use crate::{
     AppState,
     button::{Button, ButtonKind},
     icons::{DetailsIcon, PositionIcon, XIcon},
-    inputs::NumberInputI32,
+    inputs::{KeyBindingInput, NumberInputI32, NumberInputU32},
     popup::Popup,
-    select::Select,
+    select::{EnumSelect, Select},
 };
 
 #[derive(Debug, Clone, PartialEq)]
@@ -94,17 +95,15 @@ fn PopupPoint(
 
     let position = use_context::<AppState>().position;
     let value = use_memo(use_reactive!(|value| value));
-    let mut xy = use_signal(|| match value() {
-        PopupPointValue::Add(point) => (point.x, point.y),
-        PopupPointValue::Edit(point, _) => (point.x, point.y),
+    let mut point = use_signal(|| match value() {
+        PopupPointValue::Add(point) => point,
+        PopupPointValue::Edit(point, _) => point,
     });
     let on_save_click = use_callback(move |_| {
-        let (x, y) = *xy.peek();
+        let point = *point.peek();
         let value = match value.peek().clone() {
-            PopupPointValue::Add(point) => PopupPointValue::Add(NavigationPoint { x, y, ..point }),
-            PopupPointValue::Edit(point, index) => {
-                PopupPointValue::Edit(NavigationPoint { x, y, ..point }, index)
-            }
+            PopupPointValue::Add(_) => PopupPointValue::Add(point),
+            PopupPointValue::Edit(_, index) => PopupPointValue::Edit(point, index),
         };
         on_save(value);
     });
@@ -112,7 +111,7 @@ fn PopupPoint(
     rsx! {
         Popup {
             title: "Point",
-            class: "max-w-80 min-h-35 max-h-35",
+            class: "max-w-80 min-h-55 max-h-55",
             confirm_button: "Save",
             on_confirm: move |_| {
                 on_save_click(());
@@ -126,14 +125,14 @@ fn PopupPoint(
                     NumberInputI32 {
                         label: "X",
                         on_value: move |x| {
-                            xy.write().0 = x;
+                            point.write().x = x;
                         },
-                        value: xy().0,
+                        value: point().x,
                     }
                     div {
                         class: ICON_CONTAINER_CLASS,
                         onclick: move |_| {
-                            xy.write().0 = position.peek().0;
+                            point.write().x = position.peek().0;
                         },
                         PositionIcon { class: ICON_CLASS }
                     }
@@ -142,18 +141,43 @@ fn PopupPoint(
                     NumberInputI32 {
                         label: "Y",
                         on_value: move |y| {
-                            xy.write().1 = y;
+                            point.write().y = y;
                         },
-                        value: xy().1,
+                        value: point().y,
                     }
                     div {
                         class: ICON_CONTAINER_CLASS,
                         onclick: move |_| {
-                            xy.write().1 = position.peek().1;
+                            point.write().y = position.peek().1;
                         },
                         PositionIcon { class: ICON_CLASS }
                     }
                 }
+                EnumSelect::<NavigationTransition> {
+                    label: "Transition",
+                    on_select: move |transition| {
+                        point.write().transition = transition;
+                    },
+                    selected: point().transition,
+                }
+                KeyBindingInput {
+                    label: "Key",
+                    input_class: "border border-gray-600",
+                    disabled: matches!(point().transition, NavigationTransition::Portal),
+                    on_value: move |key: Option<KeyBinding>| {
+                        point.write().key = key.expect("not optional");
+                    },
+                    value: Some(point().key),
+                }
+                NumberInputU32 {
+                    label: "NPC option",
+                    minimum_value: 0,
+                    disabled: !matches!(point().transition, NavigationTransition::Npc),
+                    on_value: move |npc_option| {
+                        point.write().npc_option = npc_option;
+                    },
+                    value: point().npc_option,
+                }
             }
         }
     }
@@ -172,6 +196,7 @@ fn SectionPaths(popup: Signal<Option<NavigationPopup>>) -> Element {
             .filter_map(|path| path.id.map(|id| format!("Path {id}")))
             .collect::<Vec<_>>()
     });
+    let mut recording_navigation = use_signal(|| false);
     let minimap_attached_path_index = use_memo(move || {
         let minimap = minimap();
         let paths = paths_view();
@@ -234,6 +259,7 @@ fn SectionPaths(popup: Signal<Option<NavigationPopup>>) -> Element {
                 x: position.peek().0,
                 y: position.peek().1,
                 transition: NavigationTransition::Portal,
+                ..NavigationPoint::default()
             }),
         )));
     });
@@ -268,6 +294,19 @@ fn SectionPaths(popup: Signal<Option<NavigationPopup>>) -> Element {
             }
         }
     });
+    use_future(move || async move {
+        let mut receiver = game_state_receiver().await;
+        loop {
+            let state = match receiver.recv().await {
+                Ok(value) => value,
+                Err(RecvError::Closed) => break,
+                Err(RecvError::Lagged(_)) => continue,
+            };
+            if *recording_navigation.peek() != state.recording_navigation {
+                recording_navigation.set(state.recording_navigation);
+            }
+        }
+    });
 
     rsx! {
         Section { name: "Selected map",
@@ -319,13 +358,29 @@ fn SectionPaths(popup: Signal<Option<NavigationPopup>>) -> Element {
                     }
                 }
             }
-            Button {
-                text: "Add path",
-                kind: ButtonKind::Secondary,
-                on_click: move |_| {
-                    coroutine.send(NavigationUpdate::Create);
-                },
-                class: "label mt-4",
+            div { class: "grid grid-cols-2 gap-3 mt-4",
+                Button {
+                    text: "Add path",
+                    kind: ButtonKind::Secondary,
+                    on_click: move |_| {
+                        coroutine.send(NavigationUpdate::Create);
+                    },
+                    disabled: recording_navigation(),
+                    class: "label",
+                }
+                Button {
+                    text: if recording_navigation() { "Stop recording" } else { "Record route" },
+                    kind: ButtonKind::Secondary,
+                    on_click: move |_| async move {
+                        record_navigation(!*recording_navigation.peek()).await;
+                    },
+                    class: "label",
+                }
+            }
+            if recording_navigation() {
+                p { class: "paragraph-xs !text-gray-400 mt-2",
+                    "Move through the maps manually. The route is saved as paths when stopped."
+                }
             }
         }
         if let Some(kind) = popup() {
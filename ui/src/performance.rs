@@ -0,0 +1,69 @@
+use std::time::Duration;
+
+use backend::{TickStats, query_tick_stats};
+use dioxus::prelude::*;
+use tokio::time::sleep;
+
+use crate::button::{Button, ButtonKind};
+
+#[component]
+pub fn Performance() -> Element {
+    let mut expanded = use_signal(|| false);
+    let mut stats = use_signal(TickStats::default);
+
+    use_future(move || async move {
+        loop {
+            if *expanded.peek() {
+                stats.set(query_tick_stats().await);
+            }
+            sleep(Duration::from_millis(500)).await;
+        }
+    });
+
+    rsx! {
+        div { class: "flex flex-col px-4 gap-1",
+            Button {
+                text: if expanded() { "Hide performance" } else { "Show performance" },
+                kind: ButtonKind::Secondary,
+                on_click: move |_| {
+                    expanded.toggle();
+                },
+            }
+            if expanded() {
+                div { class: "grid grid-cols-2 items-center gap-1",
+                    PerformanceItem {
+                        name: "Tick avg / max",
+                        value: format!(
+                            "{:.1} / {:.1}ms",
+                            stats().average_millis,
+                            stats().max_millis,
+                        ),
+                    }
+                    PerformanceItem {
+                        name: "Late ticks",
+                        value: format!(
+                            "{} / {} (>{:.1}ms)",
+                            stats().late_ticks,
+                            stats().ticks,
+                            stats().budget_millis,
+                        ),
+                    }
+                    for stage in stats().stages {
+                        PerformanceItem {
+                            name: stage.name,
+                            value: format!("{:.1} / {:.1}ms", stage.average_millis, stage.max_millis),
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[component]
+fn PerformanceItem(name: String, value: String) -> Element {
+    rsx! {
+        p { class: "paragraph font-mono", "{name}" }
+        p { class: "paragraph text-right font-mono", "{value}" }
+    }
+}
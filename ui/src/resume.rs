@@ -0,0 +1,81 @@
+use std::time::{Duration, UNIX_EPOCH};
+
+use backend::{
+    Checkpoint, query_characters, query_checkpoint, query_minimaps, resume_checkpoint,
+    update_character, update_minimap,
+};
+use dioxus::prelude::*;
+
+use crate::{AppState, popup::Popup};
+
+/// Offers to resume from the checkpoint left when the last run exited while running actions.
+#[component]
+pub fn Resume() -> Element {
+    let mut character = use_context::<AppState>().character;
+    let mut minimap = use_context::<AppState>().minimap;
+    let mut minimap_preset = use_context::<AppState>().minimap_preset;
+    let checkpoint = use_resource(query_checkpoint);
+    // Whether the checkpoint has been resumed or discarded
+    let mut dismissed = use_signal(|| false);
+
+    let resume = move |checkpoint: Checkpoint| async move {
+        let selected_character = query_characters()
+            .await
+            .unwrap_or_default()
+            .into_iter()
+            .find(|character| character.id.is_some() && character.id == checkpoint.character_id);
+        let selected_minimap = query_minimaps()
+            .await
+            .unwrap_or_default()
+            .into_iter()
+            .find(|minimap| minimap.id.is_some() && minimap.id == checkpoint.minimap_id);
+        let (Some(selected_character), Some(selected_minimap)) =
+            (selected_character, selected_minimap)
+        else {
+            resume_checkpoint(false).await;
+            return;
+        };
+        let preset = checkpoint
+            .minimap_preset
+            .filter(|preset| selected_minimap.actions.contains_key(preset));
+
+        character.set(Some(selected_character));
+        minimap.set(Some(selected_minimap));
+        minimap_preset.set(preset);
+        update_character(character()).await;
+        update_minimap(minimap_preset(), minimap()).await;
+        resume_checkpoint(true).await;
+    };
+
+    let Some(Some(current_checkpoint)) = checkpoint().filter(|_| !dismissed()) else {
+        return rsx! {};
+    };
+    let saved_at = humantime::format_rfc3339_seconds(
+        UNIX_EPOCH + Duration::from_millis(current_checkpoint.saved_at_millis),
+    );
+
+    rsx! {
+        Popup {
+            title: "Resume where you left off?",
+            class: "max-w-108 max-h-50",
+            confirm_button: "Resume",
+            on_confirm: move |_| {
+                let current_checkpoint = current_checkpoint.clone();
+                dismissed.set(true);
+                spawn(async move {
+                    resume(current_checkpoint).await;
+                });
+            },
+            cancel_button: "Discard",
+            on_cancel: move |_| {
+                dismissed.set(true);
+                spawn(async move {
+                    resume_checkpoint(false).await;
+                });
+            },
+            p { class: "paragraph-xs",
+                "The actions were still running when the last session exited at {saved_at}. Resuming selects the same character, map and preset and continues the actions from the saved rotation position."
+            }
+        }
+    }
+}
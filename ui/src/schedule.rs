@@ -0,0 +1,271 @@
+use backend::{
+    IntoEnumIterator, Minimap as MinimapData, Schedule as ScheduleData, ScheduleDay,
+    Settings as SettingsData, query_minimaps, query_settings, upsert_settings,
+};
+use dioxus::prelude::*;
+use futures_util::StreamExt;
+
+use crate::{
+    AppState,
+    button::{Button, ButtonKind},
+    inputs::{Checkbox, NumberInputU32},
+    select::Select,
+};
+
+#[derive(Debug)]
+enum ScheduleUpdate {
+    Update(SettingsData),
+}
+
+#[component]
+pub fn Schedule() -> Element {
+    let mut settings = use_context::<AppState>().settings;
+    let settings_view = use_memo(move || settings().unwrap_or_default());
+    let minimaps = use_resource(async || query_minimaps().await.unwrap_or_default());
+    let minimaps_view = use_memo(move || minimaps().unwrap_or_default());
+
+    // Handles async operations for schedule-related
+    let coroutine = use_coroutine(
+        move |mut rx: UnboundedReceiver<ScheduleUpdate>| async move {
+            while let Some(message) = rx.next().await {
+                match message {
+                    ScheduleUpdate::Update(new_settings) => {
+                        settings.set(Some(upsert_settings(new_settings).await));
+                    }
+                }
+            }
+        },
+    );
+    let save_settings = use_callback(move |new_settings: SettingsData| {
+        coroutine.send(ScheduleUpdate::Update(new_settings));
+    });
+    let save_schedules = use_callback(move |schedules: Vec<ScheduleData>| {
+        save_settings(SettingsData {
+            schedules,
+            ..settings_view.peek().clone()
+        });
+    });
+
+    use_future(move || async move {
+        if settings.peek().is_none() {
+            settings.set(Some(query_settings().await));
+        }
+    });
+
+    rsx! {
+        div { class: "flex flex-col h-full overflow-y-auto scrollbar pr-4 pb-3",
+            div { class: "flex items-center title-xs h-10", "Schedule" }
+            div { class: "grid grid-cols-2 gap-3",
+                Checkbox {
+                    label: "Enabled",
+                    input_class: "w-6",
+                    on_value: move |enable_schedule| {
+                        save_settings(SettingsData {
+                            enable_schedule,
+                            ..settings_view.peek().clone()
+                        });
+                    },
+                    value: settings_view().enable_schedule,
+                }
+            }
+            p { class: "paragraph-xs !text-gray-400 mt-2",
+                "Actions start when a time window starts and halt when it ends. A character must be selected."
+            }
+            div { class: "flex flex-col gap-3 mt-3",
+                for (index , schedule) in settings_view().schedules.into_iter().enumerate() {
+                    ScheduleItem {
+                        schedule,
+                        minimaps: minimaps_view(),
+                        on_value: move |schedule| {
+                            let mut schedules = settings_view.peek().schedules.clone();
+                            schedules[index] = schedule;
+                            save_schedules(schedules);
+                        },
+                        on_delete: move |_| {
+                            let mut schedules = settings_view.peek().schedules.clone();
+                            schedules.remove(index);
+                            save_schedules(schedules);
+                        },
+                    }
+                }
+            }
+            Button {
+                text: "Add schedule",
+                kind: ButtonKind::Secondary,
+                on_click: move |_| {
+                    let mut schedules = settings_view.peek().schedules.clone();
+                    schedules.push(ScheduleData::default());
+                    save_schedules(schedules);
+                },
+                class: "mt-3",
+            }
+        }
+    }
+}
+
+#[component]
+fn ScheduleItem(
+    schedule: ScheduleData,
+    minimaps: Vec<MinimapData>,
+    on_value: EventHandler<ScheduleData>,
+    on_delete: EventHandler,
+) -> Element {
+    let schedule = use_memo(use_reactive!(|schedule| schedule));
+    let minimaps = use_memo(use_reactive!(|minimaps| minimaps));
+    let minimap_names = use_memo(move || {
+        minimaps()
+            .into_iter()
+            .map(|minimap| minimap.name)
+            .collect::<Vec<_>>()
+    });
+    let minimap_index = use_memo(move || {
+        let id = schedule().minimap_id?;
+        minimaps().iter().position(|minimap| minimap.id == Some(id))
+    });
+    let presets = use_memo(move || {
+        minimap_index()
+            .map(|index| {
+                minimaps()[index]
+                    .actions
+                    .keys()
+                    .cloned()
+                    .collect::<Vec<_>>()
+            })
+            .unwrap_or_default()
+    });
+    let preset_index = use_memo(move || {
+        let preset = schedule().minimap_preset?;
+        presets().iter().position(|name| *name == preset)
+    });
+
+    rsx! {
+        div { class: "flex flex-col gap-2 border-b border-gray-600 pb-3",
+            div { class: "grid grid-cols-4 gap-3",
+                NumberInputU32 {
+                    label: "Start hour",
+                    minimum_value: 0,
+                    maximum_value: Some(23),
+                    on_value: move |start_hour| {
+                        on_value(ScheduleData {
+                            start_hour,
+                            ..schedule.peek().clone()
+                        });
+                    },
+                    value: schedule().start_hour,
+                }
+                NumberInputU32 {
+                    label: "Start minute",
+                    minimum_value: 0,
+                    maximum_value: Some(59),
+                    on_value: move |start_minute| {
+                        on_value(ScheduleData {
+                            start_minute,
+                            ..schedule.peek().clone()
+                        });
+                    },
+                    value: schedule().start_minute,
+                }
+                NumberInputU32 {
+                    label: "End hour",
+                    minimum_value: 0,
+                    maximum_value: Some(23),
+                    on_value: move |end_hour| {
+                        on_value(ScheduleData {
+                            end_hour,
+                            ..schedule.peek().clone()
+                        });
+                    },
+                    value: schedule().end_hour,
+                }
+                NumberInputU32 {
+                    label: "End minute",
+                    minimum_value: 0,
+                    maximum_value: Some(59),
+                    on_value: move |end_minute| {
+                        on_value(ScheduleData {
+                            end_minute,
+                            ..schedule.peek().clone()
+                        });
+                    },
+                    value: schedule().end_minute,
+                }
+            }
+            div { class: "grid grid-cols-7 gap-3",
+                for day in ScheduleDay::iter() {
+                    Checkbox {
+                        label: day.to_string(),
+                        input_class: "w-6",
+                        on_value: move |checked| {
+                            let mut schedule = schedule.peek().clone();
+                            schedule.days.retain(|scheduled| *scheduled != day);
+                            if checked {
+                                schedule.days.push(day);
+                            }
+                            on_value(schedule);
+                        },
+                        value: schedule().days.contains(&day),
+                    }
+                }
+            }
+            div { class: "grid grid-cols-2 gap-3",
+                Select {
+                    label: "Start into map",
+                    options: [vec!["Current".to_string()], minimap_names()].concat(),
+                    on_select: move |(index, _)| {
+                        let minimap_id = if index == 0 {
+                            None
+                        } else {
+                            minimaps.peek().get(index - 1).and_then(|minimap| minimap.id)
+                        };
+                        on_value(ScheduleData {
+                            minimap_id,
+                            minimap_preset: None,
+                            ..schedule.peek().clone()
+                        });
+                    },
+                    selected: minimap_index().map(|index| index + 1).unwrap_or_default(),
+                }
+                Select {
+                    label: "Preset",
+                    disabled: minimap_index().is_none(),
+                    options: [vec!["First".to_string()], presets()].concat(),
+                    on_select: move |(index, _)| {
+                        let minimap_preset = if index == 0 {
+                            None
+                        } else {
+                            presets.peek().get(index - 1).cloned()
+                        };
+                        on_value(ScheduleData {
+                            minimap_preset,
+                            ..schedule.peek().clone()
+                        });
+                    },
+                    selected: preset_index().map(|index| index + 1).unwrap_or_default(),
+                }
+            }
+            div { class: "grid grid-cols-2 gap-3",
+                Checkbox {
+                    label: "Enabled",
+                    input_class: "w-6",
+                    on_value: move |enabled| {
+                        on_value(ScheduleData {
+                            enabled,
+                            ..schedule.peek().clone()
+                        });
+                    },
+                    value: schedule().enabled,
+                }
+                div { class: "flex items-end",
+                    Button {
+                        text: "Delete",
+                        kind: ButtonKind::Danger,
+                        on_click: move |_| {
+                            on_delete(());
+                        },
+                        class: "w-full",
+                    }
+                }
+            }
+        }
+    }
+}
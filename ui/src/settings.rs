@@ -1,18 +1,33 @@
-use std::{fmt::Display, fs::File, io::BufReader};
+use std::{
+    fmt::Display,
+    fs::File,
+    io::BufReader,
+    time::{Duration, UNIX_EPOCH},
+};
 
 use backend::{
-    CaptureMode, FamiliarRarity, Familiars, InputMethod, IntoEnumIterator, KeyBinding,
-    KeyBindingConfiguration, Notifications, Settings as SettingsData, SwappableFamiliars,
-    query_capture_handles, query_settings, select_capture_handle, upsert_settings,
+    Bound, CaptureMode, ChannelSelection, DetectionScale, ExecutionProvider, FamiliarRarity,
+    Familiars, GlobalHotkey, GmBehavior, InputEvent, InputHumanization, InputMethod,
+    IntoEnumIterator, JitterDistribution, KeyBinding, KeyBindingConfiguration, KeyHoldOverride,
+    MAX_CHANNEL_COUNT, MAX_KEY_HOLD_OVERRIDES, MAX_UNSTUCK_STRATEGIES, Models, NotificationKind,
+    NotificationKindOptions, Notifications, PriorityActionPreemption, Settings as SettingsData,
+    StateSnapshot, SwappableFamiliars, UnstuckStrategy, dump_input_events, query_capture_frame,
+    query_capture_handles, query_capture_window_frame, query_input_events, query_settings,
+    query_state_snapshots, reload_models, select_capture_handle, upsert_settings,
 };
 use dioxus::prelude::*;
 use futures_util::StreamExt;
 use rand::distr::{Alphanumeric, SampleString};
+use tokio::time::sleep;
 
 use crate::{
     AppState,
     button::{Button, ButtonKind},
-    inputs::{Checkbox, KeyBindingInput, MillisInput, TextInput},
+    inputs::{
+        Checkbox, KeyBindingInput, MillisInput, NumberInputI32, NumberInputU32, PercentageInput,
+        TextInput,
+    },
+    popup::Popup,
     select::{EnumSelect, Select},
 };
 
@@ -52,10 +67,26 @@ pub fn Settings() -> Element {
         div { class: "flex flex-col h-full overflow-y-auto scrollbar",
             SectionCapture { settings_view, save_settings }
             SectionInput { settings_view, save_settings }
+            SectionInputHumanization { settings_view, save_settings }
             SectionFamiliars { settings_view, save_settings }
             SectionNotifications { settings_view, save_settings }
             SectionHotkeys { settings_view, save_settings }
+            SectionGlobalHotkeys { settings_view, save_settings }
+            SectionControlServer { settings_view, save_settings }
             SectionRunStopCycle { settings_view, save_settings }
+            SectionDeathRecovery { settings_view, save_settings }
+            SectionMaintenance { settings_view, save_settings }
+            SectionLieDetector { settings_view, save_settings }
+            SectionGmDetection { settings_view, save_settings }
+            SectionCashShopAntiIdle { settings_view, save_settings }
+            SectionActionPosition { settings_view, save_settings }
+            SectionUnstuck { settings_view, save_settings }
+            SectionChannel { settings_view, save_settings }
+            SectionIncomeTracking { settings_view, save_settings }
+            SectionFlightRecorder { settings_view, save_settings }
+            SectionInputEvents {}
+            SectionEventScreenshots { settings_view, save_settings }
+            SectionModels { settings_view, save_settings }
             SectionOthers { settings_view, save_settings }
         }
     }
@@ -89,6 +120,14 @@ fn SectionCapture(
         [default, names].concat()
     });
 
+    let mut annotating = use_signal(|| false);
+    let mut picking_area = use_signal(|| false);
+    // The selected handle name for the capture area in BitBltArea
+    let selected_handle_name = use_memo(move || {
+        let index = selected_handle_index()?;
+        handle_names()?.get(index).cloned()
+    });
+
     rsx! {
         Section { name: "Capture",
             div { class: "grid grid-cols-2 gap-3",
@@ -102,6 +141,13 @@ fn SectionCapture(
                         } else {
                             selected_handle_index.set(Some(index - 1));
                             select_capture_handle(Some(index - 1)).await;
+                            let capture_mode = settings_view.peek().capture_mode;
+                            if matches!(capture_mode, CaptureMode::BitBltArea) {
+                                picking_area.set(true);
+                            } else {
+                                // Non-game windows usually need the game area annotated
+                                annotating.set(true);
+                            }
                         }
                     },
                     selected: selected_handle_index().map(|index| index + 1).unwrap_or_default(),
@@ -116,14 +162,391 @@ fn SectionCapture(
                     },
                     selected: settings_view().capture_mode,
                 }
+                NumberInputU32 {
+                    label: "FPS",
+                    minimum_value: 10,
+                    maximum_value: Some(60),
+                    on_value: move |fps| {
+                        save_settings(SettingsData {
+                            fps,
+                            ..settings_view.peek().clone()
+                        });
+                    },
+                    value: settings_view().fps,
+                }
             }
-            Button {
-                text: "Refresh handles",
-                kind: ButtonKind::Secondary,
-                on_click: move |_| {
-                    handle_names.restart();
+            if matches!(settings_view().capture_mode, CaptureMode::Stream) {
+                div { class: "grid grid-cols-2 gap-3 mt-2",
+                    SettingsTextInput {
+                        text_label: "Stream URL",
+                        button_label: "Update",
+                        on_value: move |capture_stream_url| {
+                            save_settings(SettingsData {
+                                capture_stream_url,
+                                ..settings_view.peek().clone()
+                            });
+                        },
+                        value: settings_view().capture_stream_url,
+                    }
+                }
+                p { class: "paragraph-xs !text-gray-400 mt-2",
+                    "Supports camera://<index> (e.g. OBS virtual camera), udp://<bind address>, tcp://<address> and http://<address>/<path> MJPEG streams."
+                }
+            }
+            div { class: "grid grid-cols-2 gap-3 mt-2",
+                Button {
+                    text: "Refresh handles",
+                    kind: ButtonKind::Secondary,
+                    on_click: move |_| {
+                        handle_names.restart();
+                    },
+                }
+                Button {
+                    text: "Annotate game area",
+                    kind: ButtonKind::Secondary,
+                    on_click: move |_| {
+                        annotating.set(true);
+                    },
+                }
+                MillisInput {
+                    label: "Recover capture after no frame for",
+                    on_value: move |capture_recovery_timeout_millis| {
+                        save_settings(SettingsData {
+                            capture_recovery_timeout_millis,
+                            ..settings_view.peek().clone()
+                        });
+                    },
+                    value: settings_view().capture_recovery_timeout_millis,
+                }
+                SettingsCheckbox {
+                    label: "Fall back to next capture mode",
+                    on_value: move |capture_recovery_fallback| {
+                        save_settings(SettingsData {
+                            capture_recovery_fallback,
+                            ..settings_view.peek().clone()
+                        });
+                    },
+                    value: settings_view().capture_recovery_fallback,
+                }
+            }
+            if matches!(settings_view().capture_mode, CaptureMode::BitBltArea) {
+                if let Some(name) = selected_handle_name() {
+                    div { class: "grid grid-cols-2 gap-3 mt-2",
+                        Button {
+                            text: "Select capture area",
+                            kind: ButtonKind::Secondary,
+                            on_click: move |_| {
+                                picking_area.set(true);
+                            },
+                        }
+                    }
+                    if let Some(area) = settings_view().capture_areas.get(&name) {
+                        p { class: "paragraph-xs mt-2",
+                            "Capture area: {area.width}x{area.height} at ({area.x}, {area.y})"
+                        }
+                    } else {
+                        p { class: "paragraph-xs !text-gray-400 mt-2",
+                            "No capture area selected for this handle, the capture area window is used instead."
+                        }
+                    }
+                } else {
+                    p { class: "paragraph-xs !text-gray-400 mt-2",
+                        "Select a handle to pick the capture area inside it or move the capture area window over the game."
+                    }
+                }
+            }
+            if let Some(crop) = settings_view().capture_crop {
+                p { class: "paragraph-xs mt-2",
+                    "Game area: {crop.width}x{crop.height} at ({crop.x}, {crop.y})"
+                }
+            }
+        }
+        if let Some(name) = selected_handle_name().filter(|_| picking_area()) {
+            PopupCaptureAreaPicker {
+                value: settings_view().capture_areas.get(&name).copied(),
+                on_save: move |area| {
+                    picking_area.set(false);
+                    let mut settings = settings_view.peek().clone();
+                    match area {
+                        Some(area) => {
+                            settings.capture_areas.insert(name.clone(), area);
+                        }
+                        None => {
+                            settings.capture_areas.remove(&name);
+                        }
+                    }
+                    save_settings(settings);
+                },
+                on_cancel: move |_| {
+                    picking_area.set(false);
+                },
+            }
+        }
+        if annotating() {
+            PopupCaptureAnnotator {
+                title: "Annotate game area",
+                clear_button: "Use whole frame",
+                value: settings_view().capture_crop,
+                on_save: move |capture_crop| {
+                    annotating.set(false);
+                    save_settings(SettingsData {
+                        capture_crop,
+                        ..settings_view.peek().clone()
+                    });
                 },
-                class: "mt-2",
+                on_cancel: move |_| {
+                    annotating.set(false);
+                },
+            }
+        }
+    }
+}
+
+/// Shows a live preview of the selected handle for the user to drag a capture area inside it.
+#[component]
+fn PopupCaptureAreaPicker(
+    value: Option<Bound>,
+    on_save: EventHandler<Option<Bound>>,
+    on_cancel: EventHandler,
+) -> Element {
+    const REFRESH_DELAY: Duration = Duration::from_millis(500);
+
+    let mut bound = use_signal(|| value.unwrap_or_default());
+    let mut drag_start = use_signal::<Option<(i32, i32)>>(|| None);
+    let mut frame = use_signal::<Option<Option<(String, i32, i32)>>>(|| None);
+
+    use_future(move || async move {
+        loop {
+            frame.set(Some(query_capture_window_frame().await));
+            sleep(REFRESH_DELAY).await;
+        }
+    });
+
+    let overlay_style = use_memo(move || {
+        let bound = bound();
+        format!(
+            "left: {}px; top: {}px; width: {}px; height: {}px;",
+            bound.x, bound.y, bound.width, bound.height
+        )
+    });
+
+    rsx! {
+        Popup {
+            title: "Select capture area",
+            class: "max-w-160 max-h-120",
+            confirm_button: "Save",
+            on_confirm: move |_| {
+                let bound = *bound.peek();
+                on_save((bound.width > 0 && bound.height > 0).then_some(bound));
+            },
+            cancel_button: "Cancel",
+            on_cancel: move |_| {
+                on_cancel(());
+            },
+            div { class: "flex flex-col gap-2 pr-2 overflow-y-auto scrollbar",
+                p { class: "paragraph-xs !text-gray-400",
+                    "Drag a rectangle over the game inside the preview or type the coordinates below."
+                }
+                match frame() {
+                    Some(Some((base64, _, _))) => rsx! {
+                        div { class: "overflow-auto scrollbar max-h-80",
+                            div {
+                                class: "relative w-fit select-none cursor-crosshair",
+                                onmousedown: move |e| {
+                                    let point = e.element_coordinates();
+                                    let (x, y) = (point.x as i32, point.y as i32);
+                                    drag_start.set(Some((x, y)));
+                                    bound.set(Bound { x, y, width: 0, height: 0 });
+                                },
+                                onmousemove: move |e| {
+                                    let Some((start_x, start_y)) = drag_start() else {
+                                        return;
+                                    };
+                                    let point = e.element_coordinates();
+                                    let (x, y) = (point.x as i32, point.y as i32);
+                                    bound.set(Bound {
+                                        x: start_x.min(x),
+                                        y: start_y.min(y),
+                                        width: (x - start_x).abs(),
+                                        height: (y - start_y).abs(),
+                                    });
+                                },
+                                onmouseup: move |_| {
+                                    drag_start.set(None);
+                                },
+                                onmouseleave: move |_| {
+                                    drag_start.set(None);
+                                },
+                                // Shown at the original size so that the mouse position is the
+                                // position inside the frame
+                                img {
+                                    src: format!("data:image/png;base64,{}", base64),
+                                    draggable: "false",
+                                    class: "max-w-none border border-gray-600",
+                                }
+                                div {
+                                    class: "absolute border-2 border-red-500 pointer-events-none",
+                                    style: overlay_style(),
+                                }
+                            }
+                        }
+                    },
+                    Some(None) => rsx! {
+                        p { class: "paragraph-xs", "Failed to capture the selected handle." }
+                    },
+                    None => rsx! {
+                        p { class: "paragraph-xs", "Capturing..." }
+                    },
+                }
+                div { class: "grid grid-cols-4 gap-3",
+                    NumberInputI32 {
+                        label: "X",
+                        on_value: move |x| {
+                            bound.write().x = x;
+                        },
+                        value: bound().x,
+                    }
+                    NumberInputI32 {
+                        label: "Y",
+                        on_value: move |y| {
+                            bound.write().y = y;
+                        },
+                        value: bound().y,
+                    }
+                    NumberInputI32 {
+                        label: "Width",
+                        on_value: move |width| {
+                            bound.write().width = width;
+                        },
+                        value: bound().width,
+                    }
+                    NumberInputI32 {
+                        label: "Height",
+                        on_value: move |height| {
+                            bound.write().height = height;
+                        },
+                        value: bound().height,
+                    }
+                }
+                Button {
+                    text: "Use capture area window",
+                    kind: ButtonKind::Secondary,
+                    on_click: move |_| {
+                        on_save(None);
+                    },
+                }
+            }
+        }
+    }
+}
+
+/// Shows the first captured frame for the user to confirm or adjust an area inside it.
+#[component]
+fn PopupCaptureAnnotator(
+    title: String,
+    clear_button: String,
+    value: Option<Bound>,
+    on_save: EventHandler<Option<Bound>>,
+    on_cancel: EventHandler,
+) -> Element {
+    let mut bound = use_signal(|| value.unwrap_or_default());
+    let frame = use_resource(move || async move {
+        let frame = query_capture_frame().await;
+        if value.is_none()
+            && let Some((_, width, height)) = frame
+        {
+            bound.set(Bound {
+                x: 0,
+                y: 0,
+                width,
+                height,
+            });
+        }
+        frame
+    });
+    let overlay_style = use_memo(move || {
+        let Some(Some((_, frame_width, frame_height))) = frame() else {
+            return String::default();
+        };
+        let bound = bound();
+        let left = bound.x as f32 / frame_width as f32 * 100.0;
+        let top = bound.y as f32 / frame_height as f32 * 100.0;
+        let width = bound.width as f32 / frame_width as f32 * 100.0;
+        let height = bound.height as f32 / frame_height as f32 * 100.0;
+
+        format!("left: {left}%; top: {top}%; width: {width}%; height: {height}%;")
+    });
+
+    rsx! {
+        Popup {
+            title,
+            class: "max-w-160 max-h-120",
+            confirm_button: "Save",
+            on_confirm: move |_| {
+                on_save(Some(*bound.peek()));
+            },
+            cancel_button: "Cancel",
+            on_cancel: move |_| {
+                on_cancel(());
+            },
+            div { class: "flex flex-col gap-2 pr-2 overflow-y-auto scrollbar",
+                match frame() {
+                    Some(Some((base64, _, _))) => rsx! {
+                        div { class: "relative",
+                            img {
+                                src: format!("data:image/png;base64,{}", base64),
+                                class: "w-full border border-gray-600",
+                            }
+                            div {
+                                class: "absolute border-2 border-red-500 pointer-events-none",
+                                style: overlay_style(),
+                            }
+                        }
+                    },
+                    Some(None) => rsx! {
+                        p { class: "paragraph-xs", "Failed to capture a frame." }
+                    },
+                    None => rsx! {
+                        p { class: "paragraph-xs", "Capturing..." }
+                    },
+                }
+                div { class: "grid grid-cols-4 gap-3",
+                    NumberInputI32 {
+                        label: "X",
+                        on_value: move |x| {
+                            bound.write().x = x;
+                        },
+                        value: bound().x,
+                    }
+                    NumberInputI32 {
+                        label: "Y",
+                        on_value: move |y| {
+                            bound.write().y = y;
+                        },
+                        value: bound().y,
+                    }
+                    NumberInputI32 {
+                        label: "Width",
+                        on_value: move |width| {
+                            bound.write().width = width;
+                        },
+                        value: bound().width,
+                    }
+                    NumberInputI32 {
+                        label: "Height",
+                        on_value: move |height| {
+                            bound.write().height = height;
+                        },
+                        value: bound().height,
+                    }
+                }
+                Button {
+                    text: clear_button,
+                    kind: ButtonKind::Secondary,
+                    on_click: move |_| {
+                        on_save(None);
+                    },
+                }
             }
         }
     }
@@ -158,8 +581,283 @@ fn SectionInput(
                     },
                     value: settings_view().input_method_rpc_server_url,
                 }
+                div {}
+                SettingsTextInput {
+                    text_label: "Serial port",
+                    button_label: "Update",
+                    on_value: move |input_method_serial_port| {
+                        save_settings(SettingsData {
+                            input_method_serial_port,
+                            ..settings_view.peek().clone()
+                        });
+                    },
+                    value: settings_view().input_method_serial_port,
+                }
+                NumberInputU32 {
+                    label: "Serial baud rate",
+                    minimum_value: 1,
+                    on_value: move |input_method_serial_baud_rate| {
+                        save_settings(SettingsData {
+                            input_method_serial_baud_rate,
+                            ..settings_view.peek().clone()
+                        });
+                    },
+                    value: settings_view().input_method_serial_baud_rate,
+                }
+                div {}
+                SettingsTextInput {
+                    text_label: "KMBox Net address",
+                    button_label: "Update",
+                    on_value: move |input_method_kmbox_address| {
+                        save_settings(SettingsData {
+                            input_method_kmbox_address,
+                            ..settings_view.peek().clone()
+                        });
+                    },
+                    value: settings_view().input_method_kmbox_address,
+                }
+                SettingsTextInput {
+                    text_label: "KMBox Net UUID",
+                    button_label: "Update",
+                    on_value: move |input_method_kmbox_uuid| {
+                        save_settings(SettingsData {
+                            input_method_kmbox_uuid,
+                            ..settings_view.peek().clone()
+                        });
+                    },
+                    value: settings_view().input_method_kmbox_uuid,
+                }
+                SettingsCheckbox {
+                    label: "Only send to game window",
+                    on_value: move |input_focus_guard| {
+                        save_settings(SettingsData {
+                            input_focus_guard,
+                            ..settings_view.peek().clone()
+                        });
+                    },
+                    value: settings_view().input_focus_guard,
+                }
+            }
+            if matches!(settings_view().input_method, InputMethod::Interception) {
+                p { class: "paragraph-xs !text-gray-400 mt-2",
+                    "Requires the Interception driver to be installed and interception.dll next to the executable."
+                }
+            }
+        }
+    }
+}
+
+#[component]
+fn SectionInputHumanization(
+    settings_view: Memo<SettingsData>,
+    save_settings: EventHandler<SettingsData>,
+) -> Element {
+    let humanization_view = use_memo(move || settings_view().input_humanization);
+
+    rsx! {
+        Section { name: "Input humanization",
+            InputHumanizationInputs {
+                humanization_view,
+                on_humanization: move |input_humanization| {
+                    save_settings(SettingsData {
+                        input_humanization,
+                        ..settings_view.peek().clone()
+                    });
+                },
+            }
+            p { class: "paragraph-xs !text-gray-400 mt-2",
+                "Fumbled keys are held twice as long. Key presses sent within the delay between keys are queued. Keys held down (e.g. walking) are not affected."
+            }
+        }
+    }
+}
+
+/// Inputs for [`InputHumanization`] shared with the character override.
+#[component]
+pub(crate) fn InputHumanizationInputs(
+    humanization_view: Memo<InputHumanization>,
+    on_humanization: EventHandler<InputHumanization>,
+) -> Element {
+    rsx! {
+        div { class: "grid grid-cols-2 gap-3",
+            MillisInput {
+                label: "Mean hold time",
+                on_value: move |hold_mean_millis| {
+                    on_humanization(InputHumanization {
+                        hold_mean_millis,
+                        ..humanization_view.peek().clone()
+                    });
+                },
+                value: humanization_view().hold_mean_millis,
+            }
+            MillisInput {
+                label: "Hold time jitter",
+                on_value: move |hold_std_millis| {
+                    on_humanization(InputHumanization {
+                        hold_std_millis,
+                        ..humanization_view.peek().clone()
+                    });
+                },
+                value: humanization_view().hold_std_millis,
+            }
+            MillisInput {
+                label: "Minimum hold time",
+                on_value: move |hold_min_millis| {
+                    on_humanization(InputHumanization {
+                        hold_min_millis,
+                        ..humanization_view.peek().clone()
+                    });
+                },
+                value: humanization_view().hold_min_millis,
+            }
+            MillisInput {
+                label: "Maximum hold time",
+                on_value: move |hold_max_millis| {
+                    on_humanization(InputHumanization {
+                        hold_max_millis,
+                        ..humanization_view.peek().clone()
+                    });
+                },
+                value: humanization_view().hold_max_millis,
+            }
+            SettingsEnumSelect::<JitterDistribution> {
+                label: "Jitter distribution",
+                on_select: move |jitter_distribution| {
+                    on_humanization(InputHumanization {
+                        jitter_distribution,
+                        ..humanization_view.peek().clone()
+                    });
+                },
+                selected: humanization_view().jitter_distribution,
+            }
+            PercentageInput {
+                label: "Fumble chance",
+                on_value: move |percent: f32| {
+                    on_humanization(InputHumanization {
+                        fumble_probability: percent / 100.0,
+                        ..humanization_view.peek().clone()
+                    });
+                },
+                value: humanization_view().fumble_probability * 100.0,
+            }
+            MillisInput {
+                label: "Minimum delay between keys",
+                on_value: move |inter_key_delay_min_millis| {
+                    on_humanization(InputHumanization {
+                        inter_key_delay_min_millis,
+                        ..humanization_view.peek().clone()
+                    });
+                },
+                value: humanization_view().inter_key_delay_min_millis,
+            }
+            MillisInput {
+                label: "Maximum delay between keys",
+                on_value: move |inter_key_delay_max_millis| {
+                    on_humanization(InputHumanization {
+                        inter_key_delay_max_millis,
+                        ..humanization_view.peek().clone()
+                    });
+                },
+                value: humanization_view().inter_key_delay_max_millis,
+            }
+            SettingsCheckbox {
+                label: "Humanize mouse movement",
+                on_value: move |humanize_mouse_movement| {
+                    on_humanization(InputHumanization {
+                        humanize_mouse_movement,
+                        ..humanization_view.peek().clone()
+                    });
+                },
+                value: humanization_view().humanize_mouse_movement,
+            }
+        }
+        KeyHoldOverrides {
+            on_overrides: move |key_overrides| {
+                on_humanization(InputHumanization {
+                    key_overrides,
+                    ..humanization_view.peek().clone()
+                });
+            },
+            overrides: humanization_view().key_overrides,
+        }
+    }
+}
+
+#[component]
+fn KeyHoldOverrides(
+    on_overrides: EventHandler<Vec<KeyHoldOverride>>,
+    overrides: Vec<KeyHoldOverride>,
+) -> Element {
+    let overrides_len = overrides.len();
+
+    rsx! {
+        div { class: "flex items-center paragraph-xs h-6 mt-2", "Per-key hold time" }
+        div { class: "grid grid-cols-4 gap-3",
+            for (index , key_override) in overrides.clone().into_iter().enumerate() {
+                KeyBindingInput {
+                    label: "Key",
+                    on_value: {
+                        let overrides = overrides.clone();
+                        move |key: Option<KeyBinding>| {
+                            let mut overrides = overrides.clone();
+                            overrides[index].key = key.expect("not optional");
+                            on_overrides(overrides);
+                        }
+                    },
+                    value: Some(key_override.key),
+                }
+                MillisInput {
+                    label: "Mean hold time",
+                    on_value: {
+                        let overrides = overrides.clone();
+                        move |hold_mean_millis| {
+                            let mut overrides = overrides.clone();
+                            overrides[index].hold_mean_millis = hold_mean_millis;
+                            on_overrides(overrides);
+                        }
+                    },
+                    value: key_override.hold_mean_millis,
+                }
+                MillisInput {
+                    label: "Hold time jitter",
+                    on_value: {
+                        let overrides = overrides.clone();
+                        move |hold_std_millis| {
+                            let mut overrides = overrides.clone();
+                            overrides[index].hold_std_millis = hold_std_millis;
+                            on_overrides(overrides);
+                        }
+                    },
+                    value: key_override.hold_std_millis,
+                }
+                div { class: "flex items-end",
+                    Button {
+                        text: "Remove",
+                        kind: ButtonKind::Danger,
+                        on_click: {
+                            let overrides = overrides.clone();
+                            move |_| {
+                                let mut overrides = overrides.clone();
+                                overrides.remove(index);
+                                on_overrides(overrides);
+                            }
+                        },
+                        class: "w-full",
+                    }
+                }
             }
         }
+        Button {
+            text: "Add key",
+            kind: ButtonKind::Secondary,
+            disabled: overrides_len >= MAX_KEY_HOLD_OVERRIDES,
+            on_click: move |_| {
+                let mut overrides = overrides.clone();
+                overrides.push(KeyHoldOverride::default());
+                on_overrides(overrides);
+            },
+            class: "mt-2",
+        }
     }
 }
 
@@ -256,17 +954,63 @@ fn SectionFamiliars(
                     value: familiars_view().swappable_rarities.contains(&FamiliarRarity::Epic),
                 }
             }
-        }
-    }
-}
-
-#[component]
-fn SectionNotifications(
-    settings_view: Memo<SettingsData>,
-    save_settings: EventHandler<SettingsData>,
-) -> Element {
-    let notifications_view = use_memo(move || settings_view().notifications);
-
+            div { class: "grid grid-cols-2 gap-3 mt-2",
+                PercentageInput {
+                    label: "Essence low below",
+                    on_value: move |essence_low_percentage| {
+                        save_settings(SettingsData {
+                            familiars: Familiars {
+                                essence_low_percentage,
+                                ..familiars_view.peek().clone()
+                            },
+                            ..settings_view.peek().clone()
+                        });
+                    },
+                    value: familiars_view().essence_low_percentage,
+                }
+                SettingsCheckbox {
+                    label: "Resummon on essence low",
+                    on_value: move |resummon_on_essence_low| {
+                        save_settings(SettingsData {
+                            familiars: Familiars {
+                                resummon_on_essence_low,
+                                ..familiars_view.peek().clone()
+                            },
+                            ..settings_view.peek().clone()
+                        });
+                    },
+                    value: familiars_view().resummon_on_essence_low,
+                }
+            }
+        }
+    }
+}
+
+#[component]
+fn SectionNotifications(
+    settings_view: Memo<SettingsData>,
+    save_settings: EventHandler<SettingsData>,
+) -> Element {
+    let notifications_view = use_memo(move || settings_view().notifications);
+    let mut selected_kind = use_signal(|| NotificationKind::RuneAppear);
+    let kind_options_view = use_memo(move || {
+        notifications_view()
+            .kind_options
+            .get(&selected_kind())
+            .copied()
+            .unwrap_or_default()
+    });
+    let save_kind_options = move |options: NotificationKindOptions| {
+        let mut notifications = notifications_view.peek().clone();
+        notifications
+            .kind_options
+            .insert(*selected_kind.peek(), options);
+        save_settings(SettingsData {
+            notifications,
+            ..settings_view.peek().clone()
+        });
+    };
+
     rsx! {
         Section { name: "Notifications",
             div { class: "grid grid-cols-2 gap-3 mb-2",
@@ -298,6 +1042,158 @@ fn SectionNotifications(
                     },
                     value: notifications_view().discord_user_id,
                 }
+                SettingsTextInput {
+                    text_label: "Discord critical webhook URL",
+                    button_label: "Update",
+                    on_value: move |discord_critical_webhook_url| {
+                        save_settings(SettingsData {
+                            notifications: Notifications {
+                                discord_critical_webhook_url,
+                                ..notifications_view.peek().clone()
+                            },
+                            ..settings_view.peek().clone()
+                        });
+                    },
+                    value: notifications_view().discord_critical_webhook_url,
+                }
+                SettingsCheckbox {
+                    label: "Only ping for critical notifications",
+                    on_value: move |discord_ping_critical_only| {
+                        save_settings(SettingsData {
+                            notifications: Notifications {
+                                discord_ping_critical_only,
+                                ..notifications_view.peek().clone()
+                            },
+                            ..settings_view.peek().clone()
+                        });
+                    },
+                    value: notifications_view().discord_ping_critical_only,
+                }
+                SettingsTextInput {
+                    text_label: "Discord bot token",
+                    button_label: "Update",
+                    on_value: move |discord_bot_token| {
+                        save_settings(SettingsData {
+                            notifications: Notifications {
+                                discord_bot_token,
+                                ..notifications_view.peek().clone()
+                            },
+                            ..settings_view.peek().clone()
+                        });
+                    },
+                    value: notifications_view().discord_bot_token,
+                }
+                SettingsTextInput {
+                    text_label: "Discord commands channel ID",
+                    button_label: "Update",
+                    on_value: move |discord_channel_id| {
+                        save_settings(SettingsData {
+                            notifications: Notifications {
+                                discord_channel_id,
+                                ..notifications_view.peek().clone()
+                            },
+                            ..settings_view.peek().clone()
+                        });
+                    },
+                    value: notifications_view().discord_channel_id,
+                }
+                SettingsCheckbox {
+                    label: "Discord remote control",
+                    on_value: move |enable_discord_remote_control| {
+                        save_settings(SettingsData {
+                            notifications: Notifications {
+                                enable_discord_remote_control,
+                                ..notifications_view.peek().clone()
+                            },
+                            ..settings_view.peek().clone()
+                        });
+                    },
+                    value: notifications_view().enable_discord_remote_control,
+                }
+                SettingsTextInput {
+                    text_label: "Telegram bot token",
+                    button_label: "Update",
+                    on_value: move |telegram_bot_token| {
+                        save_settings(SettingsData {
+                            notifications: Notifications {
+                                telegram_bot_token,
+                                ..notifications_view.peek().clone()
+                            },
+                            ..settings_view.peek().clone()
+                        });
+                    },
+                    value: notifications_view().telegram_bot_token,
+                }
+                SettingsTextInput {
+                    text_label: "Telegram chat ID",
+                    button_label: "Update",
+                    on_value: move |telegram_chat_id| {
+                        save_settings(SettingsData {
+                            notifications: Notifications {
+                                telegram_chat_id,
+                                ..notifications_view.peek().clone()
+                            },
+                            ..settings_view.peek().clone()
+                        });
+                    },
+                    value: notifications_view().telegram_chat_id,
+                }
+                SettingsTextInput {
+                    text_label: "Webhook URL",
+                    button_label: "Update",
+                    on_value: move |webhook_url| {
+                        save_settings(SettingsData {
+                            notifications: Notifications {
+                                webhook_url,
+                                ..notifications_view.peek().clone()
+                            },
+                            ..settings_view.peek().clone()
+                        });
+                    },
+                    value: notifications_view().webhook_url,
+                }
+                SettingsTextInput {
+                    text_label: "Webhook JSON template",
+                    button_label: "Update",
+                    on_value: move |webhook_template| {
+                        save_settings(SettingsData {
+                            notifications: Notifications {
+                                webhook_template,
+                                ..notifications_view.peek().clone()
+                            },
+                            ..settings_view.peek().clone()
+                        });
+                    },
+                    value: notifications_view().webhook_template,
+                }
+                SettingsTextInput {
+                    text_label: "ntfy topic URL",
+                    button_label: "Update",
+                    on_value: move |ntfy_url| {
+                        save_settings(SettingsData {
+                            notifications: Notifications {
+                                ntfy_url,
+                                ..notifications_view.peek().clone()
+                            },
+                            ..settings_view.peek().clone()
+                        });
+                    },
+                    value: notifications_view().ntfy_url,
+                }
+                SettingsTextInput {
+                    text_label: "ntfy access token",
+                    button_label: "Update",
+                    on_value: move |ntfy_access_token| {
+                        save_settings(SettingsData {
+                            notifications: Notifications {
+                                ntfy_access_token,
+                                ..notifications_view.peek().clone()
+                            },
+                            ..settings_view.peek().clone()
+                        });
+                    },
+                    value: notifications_view().ntfy_access_token,
+                }
             }
             div { class: "grid grid-cols-3 gap-3",
                 SettingsCheckbox {
@@ -317,208 +1213,1428 @@ fn SectionNotifications(
                     label: "Elite boss spawns",
                     on_value: move |notify_on_elite_boss_appear| {
                         save_settings(SettingsData {
-                            notifications: Notifications {
-                                notify_on_elite_boss_appear,
-                                ..notifications_view.peek().clone()
-                            },
+                            notifications: Notifications {
+                                notify_on_elite_boss_appear,
+                                ..notifications_view.peek().clone()
+                            },
+                            ..settings_view.peek().clone()
+                        });
+                    },
+                    value: notifications_view().notify_on_elite_boss_appear,
+                }
+                SettingsCheckbox {
+                    label: "Player dies",
+                    on_value: move |notify_on_player_die| {
+                        save_settings(SettingsData {
+                            notifications: Notifications {
+                                notify_on_player_die,
+                                ..notifications_view.peek().clone()
+                            },
+                            ..settings_view.peek().clone()
+                        });
+                    },
+                    value: notifications_view().notify_on_player_die,
+                }
+                SettingsCheckbox {
+                    label: "Rune fails",
+                    on_value: move |notify_on_rune_fail| {
+                        save_settings(SettingsData {
+                            notifications: Notifications {
+                                notify_on_rune_fail,
+                                ..notifications_view.peek().clone()
+                            },
+                            ..settings_view.peek().clone()
+                        });
+                    },
+                    value: notifications_view().notify_on_rune_fail,
+                }
+                SettingsCheckbox {
+                    label: "Familiar essence low",
+                    on_value: move |notify_on_familiar_essence_low| {
+                        save_settings(SettingsData {
+                            notifications: Notifications {
+                                notify_on_familiar_essence_low,
+                                ..notifications_view.peek().clone()
+                            },
+                            ..settings_view.peek().clone()
+                        });
+                    },
+                    value: notifications_view().notify_on_familiar_essence_low,
+                }
+                SettingsCheckbox {
+                    label: "Server maintenance countdown",
+                    on_value: move |notify_on_maintenance_countdown| {
+                        save_settings(SettingsData {
+                            notifications: Notifications {
+                                notify_on_maintenance_countdown,
+                                ..notifications_view.peek().clone()
+                            },
+                            ..settings_view.peek().clone()
+                        });
+                    },
+                    value: notifications_view().notify_on_maintenance_countdown,
+                }
+                SettingsCheckbox {
+                    label: "Action skipped due to invalid position",
+                    on_value: move |notify_on_invalid_action_position| {
+                        save_settings(SettingsData {
+                            notifications: Notifications {
+                                notify_on_invalid_action_position,
+                                ..notifications_view.peek().clone()
+                            },
+                            ..settings_view.peek().clone()
+                        });
+                    },
+                    value: notifications_view().notify_on_invalid_action_position,
+                }
+                SettingsCheckbox {
+                    label: "Capture or input not reaching game",
+                    on_value: move |notify_on_capture_or_input_broken| {
+                        save_settings(SettingsData {
+                            notifications: Notifications {
+                                notify_on_capture_or_input_broken,
+                                ..notifications_view.peek().clone()
+                            },
+                            ..settings_view.peek().clone()
+                        });
+                    },
+                    value: notifications_view().notify_on_capture_or_input_broken,
+                }
+                SettingsCheckbox {
+                    label: "Session summary on stop",
+                    on_value: move |notify_on_session_summary| {
+                        save_settings(SettingsData {
+                            notifications: Notifications {
+                                notify_on_session_summary,
+                                ..notifications_view.peek().clone()
+                            },
+                            ..settings_view.peek().clone()
+                        });
+                    },
+                    value: notifications_view().notify_on_session_summary,
+                }
+                SettingsCheckbox {
+                    label: "Required buffs missing on start",
+                    on_value: move |notify_on_required_buffs_missing| {
+                        save_settings(SettingsData {
+                            notifications: Notifications {
+                                notify_on_required_buffs_missing,
+                                ..notifications_view.peek().clone()
+                            },
+                            ..settings_view.peek().clone()
+                        });
+                    },
+                    value: notifications_view().notify_on_required_buffs_missing,
+                }
+                SettingsCheckbox {
+                    label: "Inventory full",
+                    on_value: move |notify_on_inventory_full| {
+                        save_settings(SettingsData {
+                            notifications: Notifications {
+                                notify_on_inventory_full,
+                                ..notifications_view.peek().clone()
+                            },
+                            ..settings_view.peek().clone()
+                        });
+                    },
+                    value: notifications_view().notify_on_inventory_full,
+                }
+                SettingsCheckbox {
+                    label: "Whisper received",
+                    on_value: move |notify_on_whisper_received| {
+                        save_settings(SettingsData {
+                            notifications: Notifications {
+                                notify_on_whisper_received,
+                                ..notifications_view.peek().clone()
+                            },
+                            ..settings_view.peek().clone()
+                        });
+                    },
+                    value: notifications_view().notify_on_whisper_received,
+                }
+                SettingsCheckbox {
+                    label: "Lie detector appears",
+                    on_value: move |notify_on_lie_detector_appear| {
+                        save_settings(SettingsData {
+                            notifications: Notifications {
+                                notify_on_lie_detector_appear,
+                                ..notifications_view.peek().clone()
+                            },
+                            ..settings_view.peek().clone()
+                        });
+                    },
+                    value: notifications_view().notify_on_lie_detector_appear,
+                }
+                SettingsCheckbox {
+                    label: "GM detected",
+                    on_value: move |notify_on_gm_appear| {
+                        save_settings(SettingsData {
+                            notifications: Notifications {
+                                notify_on_gm_appear,
+                                ..notifications_view.peek().clone()
+                            },
+                            ..settings_view.peek().clone()
+                        });
+                    },
+                    value: notifications_view().notify_on_gm_appear,
+                }
+                SettingsCheckbox {
+                    label: "Potion has no effect",
+                    on_value: move |notify_on_potion_no_effect| {
+                        save_settings(SettingsData {
+                            notifications: Notifications {
+                                notify_on_potion_no_effect,
+                                ..notifications_view.peek().clone()
+                            },
+                            ..settings_view.peek().clone()
+                        });
+                    },
+                    value: notifications_view().notify_on_potion_no_effect,
+                }
+                SettingsCheckbox {
+                    label: "Guildie appears",
+                    on_value: move |notify_on_player_guildie_appear| {
+                        save_settings(SettingsData {
+                            notifications: Notifications {
+                                notify_on_player_guildie_appear,
+                                ..notifications_view.peek().clone()
+                            },
+                            ..settings_view.peek().clone()
+                        });
+                    },
+                    value: notifications_view().notify_on_player_guildie_appear,
+                }
+                SettingsCheckbox {
+                    label: "Stranger appears",
+                    on_value: move |notify_on_player_stranger_appear| {
+                        save_settings(SettingsData {
+                            notifications: Notifications {
+                                notify_on_player_stranger_appear,
+                                ..notifications_view.peek().clone()
+                            },
+                            ..settings_view.peek().clone()
+                        });
+                    },
+                    value: notifications_view().notify_on_player_stranger_appear,
+                }
+                SettingsCheckbox {
+                    label: "Friend appears",
+                    on_value: move |notify_on_player_friend_appear| {
+                        save_settings(SettingsData {
+                            notifications: Notifications {
+                                notify_on_player_friend_appear,
+                                ..notifications_view.peek().clone()
+                            },
+                            ..settings_view.peek().clone()
+                        });
+                    },
+                    value: notifications_view().notify_on_player_friend_appear,
+                }
+                SettingsCheckbox {
+                    label: "Detection fails or map changes",
+                    on_value: move |notify_on_fail_or_change_map| {
+                        save_settings(SettingsData {
+                            notifications: Notifications {
+                                notify_on_fail_or_change_map,
+                                ..notifications_view.peek().clone()
+                            },
+                            ..settings_view.peek().clone()
+                        });
+                    },
+                    value: notifications_view().notify_on_fail_or_change_map,
+                }
+            }
+            div { class: "grid grid-cols-2 gap-3 mt-2",
+                SettingsEnumSelect::<NotificationKind> {
+                    label: "Notification",
+                    on_select: move |kind| {
+                        selected_kind.set(kind);
+                    },
+                    selected: selected_kind(),
+                }
+                NumberInputU32 {
+                    label: "Cooldown (seconds)",
+                    minimum_value: 0,
+                    on_value: move |cooldown_secs| {
+                        save_kind_options(NotificationKindOptions {
+                            cooldown_secs,
+                            ..*kind_options_view.peek()
+                        });
+                    },
+                    value: kind_options_view().cooldown_secs,
+                }
+                SettingsCheckbox {
+                    label: "Attach snapshots",
+                    on_value: move |attach_snapshots| {
+                        save_kind_options(NotificationKindOptions {
+                            attach_snapshots,
+                            ..*kind_options_view.peek()
+                        });
+                    },
+                    value: kind_options_view().attach_snapshots,
+                }
+                SettingsCheckbox {
+                    label: "Critical",
+                    on_value: move |critical| {
+                        save_kind_options(NotificationKindOptions {
+                            critical,
+                            ..*kind_options_view.peek()
+                        });
+                    },
+                    value: kind_options_view().critical,
+                }
+            }
+        }
+    }
+}
+
+#[component]
+fn SectionHotkeys(
+    settings_view: Memo<SettingsData>,
+    save_settings: EventHandler<SettingsData>,
+) -> Element {
+    #[component]
+    fn Hotkey(
+        label: &'static str,
+        on_value: EventHandler<KeyBindingConfiguration>,
+        value: KeyBindingConfiguration,
+    ) -> Element {
+        rsx! {
+            div { class: "flex gap-2",
+                KeyBindingInput {
+                    label,
+                    div_class: "flex-grow",
+                    on_value: move |new_value: Option<KeyBinding>| {
+                        on_value(KeyBindingConfiguration {
+                            key: new_value.expect("not optional"),
+                            ..value
+                        });
+                    },
+                    value: Some(value.key),
+                }
+                SettingsCheckbox {
+                    label: "Enabled",
+                    on_value: move |enabled| {
+                        on_value(KeyBindingConfiguration {
+                            enabled,
+                            ..value
+                        });
+                    },
+                    value: value.enabled,
+                }
+            }
+        }
+    }
+
+    rsx! {
+        Section { name: "Hotkeys",
+            div { class: "grid grid-cols-2 gap-3",
+                Hotkey {
+                    label: "Toggle start/stop actions",
+                    on_value: move |toggle_actions_key| {
+                        save_settings(SettingsData {
+                            toggle_actions_key,
+                            ..settings_view.peek().clone()
+                        });
+                    },
+                    value: settings_view().toggle_actions_key,
+                }
+                Hotkey {
+                    label: "Toggle pause/resume actions",
+                    on_value: move |pause_actions_key| {
+                        save_settings(SettingsData {
+                            pause_actions_key,
+                            ..settings_view.peek().clone()
+                        });
+                    },
+                    value: settings_view().pause_actions_key,
+                }
+                Hotkey {
+                    label: "Panic (stop everything)",
+                    on_value: move |panic_key| {
+                        save_settings(SettingsData {
+                            panic_key,
+                            ..settings_view.peek().clone()
+                        });
+                    },
+                    value: settings_view().panic_key,
+                }
+                Hotkey {
+                    label: "Add platform",
+                    on_value: move |platform_add_key| {
+                        save_settings(SettingsData {
+                            platform_add_key,
+                            ..settings_view.peek().clone()
+                        });
+                    },
+                    value: settings_view().platform_add_key,
+                }
+                Hotkey {
+                    label: "Mark platform start",
+                    on_value: move |platform_start_key| {
+                        save_settings(SettingsData {
+                            platform_start_key,
+                            ..settings_view.peek().clone()
+                        });
+                    },
+                    value: settings_view().platform_start_key,
+                }
+                Hotkey {
+                    label: "Mark platform end",
+                    on_value: move |platform_end_key| {
+                        save_settings(SettingsData {
+                            platform_end_key,
+                            ..settings_view.peek().clone()
+                        });
+                    },
+                    value: settings_view().platform_end_key,
+                }
+            }
+        }
+    }
+}
+
+#[component]
+fn SectionGlobalHotkeys(
+    settings_view: Memo<SettingsData>,
+    save_settings: EventHandler<SettingsData>,
+) -> Element {
+    #[component]
+    fn Hotkey(
+        label: &'static str,
+        on_value: EventHandler<GlobalHotkey>,
+        value: GlobalHotkey,
+    ) -> Element {
+        rsx! {
+            div { class: "flex gap-2",
+                KeyBindingInput {
+                    label,
+                    div_class: "flex-grow",
+                    on_value: move |new_value: Option<KeyBinding>| {
+                        on_value(GlobalHotkey {
+                            key: new_value.expect("not optional"),
+                            ..value
+                        });
+                    },
+                    value: Some(value.key),
+                }
+                SettingsCheckbox {
+                    label: "Ctrl",
+                    on_value: move |ctrl| {
+                        on_value(GlobalHotkey { ctrl, ..value });
+                    },
+                    value: value.ctrl,
+                }
+                SettingsCheckbox {
+                    label: "Alt",
+                    on_value: move |alt| {
+                        on_value(GlobalHotkey { alt, ..value });
+                    },
+                    value: value.alt,
+                }
+                SettingsCheckbox {
+                    label: "Shift",
+                    on_value: move |shift| {
+                        on_value(GlobalHotkey { shift, ..value });
+                    },
+                    value: value.shift,
+                }
+                SettingsCheckbox {
+                    label: "Enabled",
+                    on_value: move |enabled| {
+                        on_value(GlobalHotkey { enabled, ..value });
+                    },
+                    value: value.enabled,
+                }
+            }
+        }
+    }
+
+    rsx! {
+        Section { name: "Global hotkeys",
+            div { class: "flex flex-col gap-3",
+                Hotkey {
+                    label: "Toggle start/stop actions",
+                    on_value: move |global_toggle_actions_hotkey| {
+                        save_settings(SettingsData {
+                            global_toggle_actions_hotkey,
+                            ..settings_view.peek().clone()
+                        });
+                    },
+                    value: settings_view().global_toggle_actions_hotkey,
+                }
+                Hotkey {
+                    label: "Toggle pause/resume actions",
+                    on_value: move |global_pause_actions_hotkey| {
+                        save_settings(SettingsData {
+                            global_pause_actions_hotkey,
+                            ..settings_view.peek().clone()
+                        });
+                    },
+                    value: settings_view().global_pause_actions_hotkey,
+                }
+                Hotkey {
+                    label: "Emergency (release all keys and stop)",
+                    on_value: move |global_emergency_halt_hotkey| {
+                        save_settings(SettingsData {
+                            global_emergency_halt_hotkey,
+                            ..settings_view.peek().clone()
+                        });
+                    },
+                    value: settings_view().global_emergency_halt_hotkey,
+                }
+            }
+        }
+    }
+}
+
+#[component]
+fn SectionControlServer(
+    settings_view: Memo<SettingsData>,
+    save_settings: EventHandler<SettingsData>,
+) -> Element {
+    rsx! {
+        Section { name: "Control server",
+            div { class: "grid grid-cols-2 gap-3",
+                NumberInputU32 {
+                    label: "Port",
+                    minimum_value: 1,
+                    maximum_value: Some(u16::MAX as u32),
+                    on_value: move |port: u32| {
+                        save_settings(SettingsData {
+                            control_server_port: port as u16,
+                            ..settings_view.peek().clone()
+                        });
+                    },
+                    value: settings_view().control_server_port as u32,
+                }
+                SettingsCheckbox {
+                    label: "Enabled",
+                    on_value: move |enable_control_server| {
+                        save_settings(SettingsData {
+                            enable_control_server,
+                            ..settings_view.peek().clone()
+                        });
+                    },
+                    value: settings_view().enable_control_server,
+                }
+                SettingsTextInput {
+                    text_label: "Token",
+                    button_label: "Update",
+                    on_value: move |control_server_token| {
+                        save_settings(SettingsData {
+                            control_server_token,
+                            ..settings_view.peek().clone()
+                        });
+                    },
+                    value: settings_view().control_server_token,
+                }
+                SettingsCheckbox {
+                    label: "Allow LAN access",
+                    on_value: move |control_server_allow_lan| {
+                        save_settings(SettingsData {
+                            control_server_allow_lan,
+                            ..settings_view.peek().clone()
+                        });
+                    },
+                    value: settings_view().control_server_allow_lan,
+                }
+            }
+            p { class: "paragraph-xs !text-gray-400 mt-2",
+                "The dashboard is served at http://<address>:<port>/?token=<token>. LAN access requires a token."
+            }
+        }
+    }
+}
+
+#[component]
+fn SectionRunStopCycle(
+    settings_view: Memo<SettingsData>,
+    save_settings: EventHandler<SettingsData>,
+) -> Element {
+    rsx! {
+        Section { name: "Run/stop cycle",
+            div { class: "grid grid-cols-3 gap-3",
+                MillisInput {
+                    label: "Run duration",
+                    on_value: move |cycle_run_duration_millis| {
+                        save_settings(SettingsData {
+                            cycle_run_duration_millis,
+                            ..settings_view.peek().clone()
+                        });
+                    },
+                    value: settings_view().cycle_run_duration_millis,
+                }
+                MillisInput {
+                    label: "Stop duration",
+                    on_value: move |cycle_stop_duration_millis| {
+                        save_settings(SettingsData {
+                            cycle_stop_duration_millis,
+                            ..settings_view.peek().clone()
+                        });
+                    },
+                    value: settings_view().cycle_stop_duration_millis,
+                }
+                SettingsCheckbox {
+                    label: "Enabled",
+                    on_value: move |cycle_run_stop| {
+                        save_settings(SettingsData {
+                            cycle_run_stop,
+                            ..settings_view.peek().clone()
+                        });
+                    },
+                    value: settings_view().cycle_run_stop,
+                }
+                MillisInput {
+                    label: "Run random range",
+                    on_value: move |cycle_run_duration_millis_random_range| {
+                        save_settings(SettingsData {
+                            cycle_run_duration_millis_random_range,
+                            ..settings_view.peek().clone()
+                        });
+                    },
+                    value: settings_view().cycle_run_duration_millis_random_range,
+                }
+                MillisInput {
+                    label: "Stop random range",
+                    on_value: move |cycle_stop_duration_millis_random_range| {
+                        save_settings(SettingsData {
+                            cycle_stop_duration_millis_random_range,
+                            ..settings_view.peek().clone()
+                        });
+                    },
+                    value: settings_view().cycle_stop_duration_millis_random_range,
+                }
+            }
+            p { class: "paragraph-xs !text-gray-400 mt-2",
+                "Each cycle picks its duration randomly within duration plus or minus the random range."
+            }
+        }
+    }
+}
+
+#[component]
+fn SectionDeathRecovery(
+    settings_view: Memo<SettingsData>,
+    save_settings: EventHandler<SettingsData>,
+) -> Element {
+    rsx! {
+        Section { name: "Death recovery",
+            div { class: "grid grid-cols-2 gap-3",
+                NumberInputU32 {
+                    label: "Maximum recoveries",
+                    minimum_value: 1,
+                    on_value: move |death_recovery_max_count| {
+                        save_settings(SettingsData {
+                            death_recovery_max_count,
+                            ..settings_view.peek().clone()
+                        });
+                    },
+                    value: settings_view().death_recovery_max_count,
+                }
+                SettingsCheckbox {
+                    label: "Enabled",
+                    on_value: move |enable_death_recovery| {
+                        save_settings(SettingsData {
+                            enable_death_recovery,
+                            ..settings_view.peek().clone()
+                        });
+                    },
+                    value: settings_view().enable_death_recovery,
+                }
+            }
+            p { class: "paragraph-xs !text-gray-400 mt-2",
+                "Requires navigation paths from the respawn town to the selected map. Buffs are detected and re-casted before the actions resume."
+            }
+        }
+    }
+}
+
+#[component]
+fn SectionMaintenance(
+    settings_view: Memo<SettingsData>,
+    save_settings: EventHandler<SettingsData>,
+) -> Element {
+    rsx! {
+        Section { name: "Server maintenance",
+            div { class: "grid grid-cols-2 gap-3",
+                MillisInput {
+                    label: "Stop before countdown ends",
+                    on_value: move |maintenance_halt_margin_millis| {
+                        save_settings(SettingsData {
+                            maintenance_halt_margin_millis,
+                            ..settings_view.peek().clone()
+                        });
+                    },
+                    value: settings_view().maintenance_halt_margin_millis,
+                }
+                SettingsCheckbox {
+                    label: "Enabled",
+                    on_value: move |enable_maintenance_halt| {
+                        save_settings(SettingsData {
+                            enable_maintenance_halt,
+                            ..settings_view.peek().clone()
+                        });
+                    },
+                    value: settings_view().enable_maintenance_halt,
+                }
+            }
+            p { class: "paragraph-xs !text-gray-400 mt-2",
+                "Finishes the current action and stops in town when a maintenance countdown is detected."
+            }
+        }
+    }
+}
+
+#[component]
+fn SectionLieDetector(
+    settings_view: Memo<SettingsData>,
+    save_settings: EventHandler<SettingsData>,
+) -> Element {
+    rsx! {
+        Section { name: "Lie detector",
+            div { class: "grid grid-cols-2 gap-3",
+                SettingsCheckbox {
+                    label: "Pause actions",
+                    on_value: move |enable_lie_detector_pause| {
+                        save_settings(SettingsData {
+                            enable_lie_detector_pause,
+                            ..settings_view.peek().clone()
+                        });
+                    },
+                    value: settings_view().enable_lie_detector_pause,
+                }
+            }
+            p { class: "paragraph-xs !text-gray-400 mt-2",
+                "Pauses the actions and releases all keys while a lie detector is detected so that it can be answered manually. The actions must be resumed manually after answering."
+            }
+        }
+    }
+}
+
+#[component]
+fn SectionGmDetection(
+    settings_view: Memo<SettingsData>,
+    save_settings: EventHandler<SettingsData>,
+) -> Element {
+    rsx! {
+        Section { name: "GM detection",
+            div { class: "grid grid-cols-2 gap-3",
+                SettingsEnumSelect::<GmBehavior> {
+                    label: "When a GM is detected",
+                    on_select: move |gm_behavior| {
+                        save_settings(SettingsData {
+                            gm_behavior,
+                            ..settings_view.peek().clone()
+                        });
+                    },
+                    selected: settings_view().gm_behavior,
+                }
+                SettingsCheckbox {
+                    label: "Enabled",
+                    on_value: move |enable_gm_detection| {
+                        save_settings(SettingsData {
+                            enable_gm_detection,
+                            ..settings_view.peek().clone()
+                        });
+                    },
+                    value: settings_view().enable_gm_detection,
+                }
+            }
+            p { class: "paragraph-xs !text-gray-400 mt-2",
+                "Reads the chat box while the actions are running for GM tags (e.g. [GM] or Game Master). The response only runs after the tag is detected twice in a row."
+            }
+        }
+    }
+}
+
+#[component]
+fn SectionCashShopAntiIdle(
+    settings_view: Memo<SettingsData>,
+    save_settings: EventHandler<SettingsData>,
+) -> Element {
+    rsx! {
+        Section { name: "Cash shop anti-idle",
+            div { class: "grid grid-cols-3 gap-3",
+                MillisInput {
+                    label: "Enter every",
+                    on_value: move |cash_shop_anti_idle_every_millis| {
+                        save_settings(SettingsData {
+                            cash_shop_anti_idle_every_millis,
+                            ..settings_view.peek().clone()
+                        });
+                    },
+                    value: settings_view().cash_shop_anti_idle_every_millis,
+                }
+                MillisInput {
+                    label: "Stay for",
+                    on_value: move |cash_shop_anti_idle_stay_millis| {
+                        save_settings(SettingsData {
+                            cash_shop_anti_idle_stay_millis,
+                            ..settings_view.peek().clone()
+                        });
+                    },
+                    value: settings_view().cash_shop_anti_idle_stay_millis,
+                }
+                SettingsCheckbox {
+                    label: "Enabled",
+                    on_value: move |enable_cash_shop_anti_idle| {
+                        save_settings(SettingsData {
+                            enable_cash_shop_anti_idle,
+                            ..settings_view.peek().clone()
+                        });
+                    },
+                    value: settings_view().enable_cash_shop_anti_idle,
+                }
+            }
+            p { class: "paragraph-xs !text-gray-400 mt-2",
+                "Enters the cash shop periodically while the actions are running. The first entering is after the interval has passed since the actions started."
+            }
+        }
+    }
+}
+
+#[component]
+fn SectionActionPosition(
+    settings_view: Memo<SettingsData>,
+    save_settings: EventHandler<SettingsData>,
+) -> Element {
+    rsx! {
+        Section { name: "Positioned actions",
+            div { class: "grid grid-cols-2 gap-3",
+                NumberInputU32 {
+                    label: "Maximum distance from platforms",
+                    minimum_value: 0,
+                    on_value: move |action_position_max_platform_distance| {
+                        save_settings(SettingsData {
+                            action_position_max_platform_distance,
+                            ..settings_view.peek().clone()
+                        });
+                    },
+                    value: settings_view().action_position_max_platform_distance,
+                }
+                SettingsEnumSelect::<PriorityActionPreemption> {
+                    label: "When a priority action is queued",
+                    on_select: move |priority_action_preemption| {
+                        save_settings(SettingsData {
+                            priority_action_preemption,
+                            ..settings_view.peek().clone()
+                        });
+                    },
+                    selected: settings_view().priority_action_preemption,
+                }
+            }
+            p { class: "paragraph-xs !text-gray-400 mt-2",
+                "Skips actions with a position outside the map or farther than this distance from all platforms. Set to 0 to only check the map bound."
+            }
+            p { class: "paragraph-xs !text-gray-400",
+                "Finishing movement or the whole action first avoids walking again to the same position after a priority action interrupts a normal action."
+            }
+        }
+    }
+}
+
+#[component]
+fn SectionUnstuck(
+    settings_view: Memo<SettingsData>,
+    save_settings: EventHandler<SettingsData>,
+) -> Element {
+    rsx! {
+        Section { name: "Unstuck",
+            UnstuckStrategies {
+                label: "Stuck mid-air",
+                on_strategies: move |unstuck_strategies_mid_air| {
+                    save_settings(SettingsData {
+                        unstuck_strategies_mid_air,
+                        ..settings_view.peek().clone()
+                    });
+                },
+                strategies: settings_view().unstuck_strategies_mid_air,
+            }
+            UnstuckStrategies {
+                label: "Stuck on ground",
+                on_strategies: move |unstuck_strategies_on_ground| {
+                    save_settings(SettingsData {
+                        unstuck_strategies_on_ground,
+                        ..settings_view.peek().clone()
+                    });
+                },
+                strategies: settings_view().unstuck_strategies_on_ground,
+            }
+            p { class: "paragraph-xs !text-gray-400 mt-2",
+                "Strategies are tried in order after unstucking fails repeatedly. Leave empty to use the default random unstucking."
+            }
+        }
+    }
+}
+
+#[component]
+fn SectionChannel(
+    settings_view: Memo<SettingsData>,
+    save_settings: EventHandler<SettingsData>,
+) -> Element {
+    let channels_to_string = |channels: Vec<u32>| {
+        channels
+            .into_iter()
+            .map(|channel| channel.to_string())
+            .collect::<Vec<_>>()
+            .join(", ")
+    };
+    let channels_from_string = |text: String| {
+        text.split(',')
+            .filter_map(|channel| channel.trim().parse::<u32>().ok())
+            .filter(|channel| *channel > 0)
+            .take(MAX_CHANNEL_COUNT)
+            .collect::<Vec<_>>()
+    };
+
+    rsx! {
+        Section { name: "Change channel",
+            div { class: "grid grid-cols-3 gap-3",
+                SettingsEnumSelect::<ChannelSelection> {
+                    label: "Channel selection",
+                    on_select: move |channel_selection| {
+                        save_settings(SettingsData {
+                            channel_selection,
+                            ..settings_view.peek().clone()
+                        });
+                    },
+                    selected: settings_view().channel_selection,
+                }
+                NumberInputU32 {
+                    label: "Channel count",
+                    minimum_value: 1,
+                    on_value: move |channel_count: u32| {
+                        save_settings(SettingsData {
+                            channel_count: channel_count.min(MAX_CHANNEL_COUNT as u32),
+                            ..settings_view.peek().clone()
+                        });
+                    },
+                    value: settings_view().channel_count,
+                }
+                NumberInputU32 {
+                    label: "Current channel",
+                    minimum_value: 0,
+                    on_value: move |current_channel| {
+                        save_settings(SettingsData {
+                            current_channel,
+                            ..settings_view.peek().clone()
+                        });
+                    },
+                    value: settings_view().current_channel,
+                }
+            }
+            div { class: "grid grid-cols-2 gap-3",
+                SettingsTextInput {
+                    text_label: "Blacklisted channels",
+                    button_label: "Update",
+                    on_value: move |text| {
+                        save_settings(SettingsData {
+                            channel_blacklist: channels_from_string(text),
+                            ..settings_view.peek().clone()
+                        });
+                    },
+                    value: channels_to_string(settings_view().channel_blacklist),
+                }
+                SettingsTextInput {
+                    text_label: "Whitelisted channels",
+                    button_label: "Update",
+                    on_value: move |text| {
+                        save_settings(SettingsData {
+                            channel_whitelist: channels_from_string(text),
+                            ..settings_view.peek().clone()
+                        });
+                    },
+                    value: channels_to_string(settings_view().channel_whitelist),
+                }
+            }
+            div { class: "grid grid-cols-3 gap-3",
+                MillisInput {
+                    label: "Stranger stays for",
+                    on_value: move |stranger_channel_hop_after_millis| {
+                        save_settings(SettingsData {
+                            stranger_channel_hop_after_millis,
+                            ..settings_view.peek().clone()
+                        });
+                    },
+                    value: settings_view().stranger_channel_hop_after_millis,
+                }
+                MillisInput {
+                    label: "Stranger hop cooldown",
+                    on_value: move |stranger_channel_hop_cooldown_millis| {
+                        save_settings(SettingsData {
+                            stranger_channel_hop_cooldown_millis,
+                            ..settings_view.peek().clone()
+                        });
+                    },
+                    value: settings_view().stranger_channel_hop_cooldown_millis,
+                }
+                SettingsCheckbox {
+                    label: "Change channel on stranger",
+                    on_value: move |enable_stranger_channel_hop| {
+                        save_settings(SettingsData {
+                            enable_stranger_channel_hop,
+                            ..settings_view.peek().clone()
+                        });
+                    },
+                    value: settings_view().enable_stranger_channel_hop,
+                }
+            }
+            div { class: "grid grid-cols-3 gap-3",
+                SettingsCheckbox {
+                    label: "Ignore guildies",
+                    on_value: move |ignore_guildie_players| {
+                        save_settings(SettingsData {
+                            ignore_guildie_players,
+                            ..settings_view.peek().clone()
+                        });
+                    },
+                    value: settings_view().ignore_guildie_players,
+                }
+                SettingsCheckbox {
+                    label: "Ignore friends",
+                    on_value: move |ignore_friend_players| {
+                        save_settings(SettingsData {
+                            ignore_friend_players,
+                            ..settings_view.peek().clone()
+                        });
+                    },
+                    value: settings_view().ignore_friend_players,
+                }
+            }
+            p { class: "paragraph-xs !text-gray-400 mt-2",
+                "Channels are comma-separated numbers. Blacklisted channels are skipped and whitelisted channels are preferred only when the current channel is set. Changing channel on stranger can be overridden per map in the rotation section. Ignored guildies and friends (e.g. your own alts) are neither notified nor treated as other players."
+            }
+        }
+    }
+}
+
+#[component]
+fn UnstuckStrategies(
+    label: &'static str,
+    on_strategies: EventHandler<Vec<UnstuckStrategy>>,
+    strategies: Vec<UnstuckStrategy>,
+) -> Element {
+    let strategies_len = strategies.len();
+
+    rsx! {
+        div { class: "flex items-center paragraph-xs h-6 mt-2", {label} }
+        div { class: "grid grid-cols-2 gap-3",
+            for (index , strategy) in strategies.clone().into_iter().enumerate() {
+                EnumSelect {
+                    label: format!("Strategy {}", index + 1),
+                    on_select: {
+                        let strategies = strategies.clone();
+                        move |strategy| {
+                            let mut strategies = strategies.clone();
+                            strategies[index] = strategy;
+                            on_strategies(strategies);
+                        }
+                    },
+                    selected: strategy,
+                }
+                div { class: "flex items-end",
+                    Button {
+                        text: "Remove",
+                        kind: ButtonKind::Danger,
+                        on_click: {
+                            let strategies = strategies.clone();
+                            move |_| {
+                                let mut strategies = strategies.clone();
+                                strategies.remove(index);
+                                on_strategies(strategies);
+                            }
+                        },
+                        class: "w-full",
+                    }
+                }
+            }
+        }
+        Button {
+            text: "Add strategy",
+            kind: ButtonKind::Secondary,
+            disabled: strategies_len >= MAX_UNSTUCK_STRATEGIES,
+            on_click: move |_| {
+                let mut strategies = strategies.clone();
+                strategies.push(UnstuckStrategy::default());
+                on_strategies(strategies);
+            },
+            class: "mt-2",
+        }
+    }
+}
+
+#[component]
+fn SectionIncomeTracking(
+    settings_view: Memo<SettingsData>,
+    save_settings: EventHandler<SettingsData>,
+) -> Element {
+    let mut annotating = use_signal(|| false);
+
+    rsx! {
+        Section { name: "EXP and mesos tracking",
+            div { class: "grid grid-cols-2 gap-3",
+                SettingsCheckbox {
+                    label: "Enabled",
+                    on_value: move |enable_income_tracking| {
+                        save_settings(SettingsData {
+                            enable_income_tracking,
                             ..settings_view.peek().clone()
                         });
                     },
-                    value: notifications_view().notify_on_elite_boss_appear,
+                    value: settings_view().enable_income_tracking,
                 }
-                SettingsCheckbox {
-                    label: "Player dies",
-                    on_value: move |notify_on_player_die| {
-                        save_settings(SettingsData {
-                            notifications: Notifications {
-                                notify_on_player_die,
-                                ..notifications_view.peek().clone()
-                            },
-                            ..settings_view.peek().clone()
-                        });
+                Button {
+                    text: "Annotate meso counter",
+                    kind: ButtonKind::Secondary,
+                    on_click: move |_| {
+                        annotating.set(true);
                     },
-                    value: notifications_view().notify_on_player_die,
                 }
+            }
+            if let Some(region) = settings_view().mesos_region {
+                p { class: "paragraph-xs mt-2",
+                    "Meso counter: {region.width}x{region.height} at ({region.x}, {region.y})"
+                }
+            } else {
+                p { class: "paragraph-xs !text-gray-400 mt-2",
+                    "Annotate the meso counter of an opened inventory to also track mesos."
+                }
+            }
+        }
+        if annotating() {
+            PopupCaptureAnnotator {
+                title: "Annotate meso counter",
+                clear_button: "Do not track mesos",
+                value: settings_view().mesos_region,
+                on_save: move |mesos_region| {
+                    annotating.set(false);
+                    save_settings(SettingsData {
+                        mesos_region,
+                        ..settings_view.peek().clone()
+                    });
+                },
+                on_cancel: move |_| {
+                    annotating.set(false);
+                },
+            }
+        }
+    }
+}
+
+#[component]
+fn SectionFlightRecorder(
+    settings_view: Memo<SettingsData>,
+    save_settings: EventHandler<SettingsData>,
+) -> Element {
+    let mut snapshots = use_signal(Vec::<StateSnapshot>::new);
+    let mut snapshot_index = use_signal(|| 0usize);
+    let snapshot = use_memo(move || snapshots.read().get(snapshot_index()).cloned());
+
+    rsx! {
+        Section { name: "Flight recorder",
+            div { class: "grid grid-cols-2 gap-3",
                 SettingsCheckbox {
-                    label: "Guildie appears",
-                    on_value: move |notify_on_player_guildie_appear| {
+                    label: "Enabled",
+                    on_value: move |enable_flight_recorder| {
                         save_settings(SettingsData {
-                            notifications: Notifications {
-                                notify_on_player_guildie_appear,
-                                ..notifications_view.peek().clone()
-                            },
+                            enable_flight_recorder,
                             ..settings_view.peek().clone()
                         });
                     },
-                    value: notifications_view().notify_on_player_guildie_appear,
+                    value: settings_view().enable_flight_recorder,
                 }
-                SettingsCheckbox {
-                    label: "Stranger appears",
-                    on_value: move |notify_on_player_stranger_appear| {
-                        save_settings(SettingsData {
-                            notifications: Notifications {
-                                notify_on_player_stranger_appear,
-                                ..notifications_view.peek().clone()
-                            },
-                            ..settings_view.peek().clone()
-                        });
+                Button {
+                    text: "Load snapshots",
+                    kind: ButtonKind::Secondary,
+                    on_click: move |_| async move {
+                        snapshots.set(query_state_snapshots().await);
+                        snapshot_index.set(0);
                     },
-                    value: notifications_view().notify_on_player_stranger_appear,
                 }
                 SettingsCheckbox {
-                    label: "Friend appears",
-                    on_value: move |notify_on_player_friend_appear| {
+                    label: "Record frames for replay",
+                    on_value: move |enable_frame_recorder| {
                         save_settings(SettingsData {
-                            notifications: Notifications {
-                                notify_on_player_friend_appear,
-                                ..notifications_view.peek().clone()
-                            },
+                            enable_frame_recorder,
                             ..settings_view.peek().clone()
                         });
                     },
-                    value: notifications_view().notify_on_player_friend_appear,
+                    value: settings_view().enable_frame_recorder,
                 }
-                SettingsCheckbox {
-                    label: "Detection fails or map changes",
-                    on_value: move |notify_on_fail_or_change_map| {
-                        save_settings(SettingsData {
-                            notifications: Notifications {
-                                notify_on_fail_or_change_map,
-                                ..notifications_view.peek().clone()
-                            },
-                            ..settings_view.peek().clone()
-                        });
+            }
+            if !snapshots.read().is_empty() {
+                input {
+                    class: "w-full mt-2",
+                    r#type: "range",
+                    min: 0,
+                    max: snapshots.read().len() - 1,
+                    value: snapshot_index(),
+                    oninput: move |e| {
+                        if let Ok(index) = e.value().parse::<usize>() {
+                            snapshot_index.set(index);
+                        }
                     },
-                    value: notifications_view().notify_on_fail_or_change_map,
                 }
             }
+            if let Some(snapshot) = snapshot() {
+                SnapshotDetails { snapshot }
+            }
         }
     }
 }
 
 #[component]
-fn SectionHotkeys(
-    settings_view: Memo<SettingsData>,
-    save_settings: EventHandler<SettingsData>,
-) -> Element {
-    #[component]
-    fn Hotkey(
-        label: &'static str,
-        on_value: EventHandler<KeyBindingConfiguration>,
-        value: KeyBindingConfiguration,
-    ) -> Element {
-        rsx! {
-            div { class: "flex gap-2",
-                KeyBindingInput {
-                    label,
-                    div_class: "flex-grow",
-                    on_value: move |new_value: Option<KeyBinding>| {
-                        on_value(KeyBindingConfiguration {
-                            key: new_value.expect("not optional"),
-                            ..value
-                        });
+fn SectionInputEvents() -> Element {
+    let mut events = use_signal(Vec::<InputEvent>::new);
+    let mut dump_path = use_signal(|| None::<Option<String>>);
+
+    rsx! {
+        Section { name: "Input events",
+            div { class: "grid grid-cols-2 gap-3",
+                Button {
+                    text: "Load input events",
+                    kind: ButtonKind::Secondary,
+                    on_click: move |_| async move {
+                        events.set(query_input_events().await);
                     },
-                    value: Some(value.key),
                 }
-                SettingsCheckbox {
-                    label: "Enabled",
-                    on_value: move |enabled| {
-                        on_value(KeyBindingConfiguration {
-                            enabled,
-                            ..value
-                        });
+                Button {
+                    text: "Dump to file",
+                    kind: ButtonKind::Secondary,
+                    on_click: move |_| async move {
+                        dump_path.set(Some(dump_input_events().await));
                     },
-                    value: value.enabled,
                 }
             }
+            if let Some(path) = dump_path() {
+                p { class: "paragraph-xs !text-gray-400 mt-2",
+                    {path.map(|path| format!("Dumped to {path}")).unwrap_or("Failed to dump input events.".to_string())}
+                }
+            }
+            if !events.read().is_empty() {
+                div { class: "grid grid-cols-[auto_auto_auto_auto_auto] gap-x-3 gap-y-1 mt-2 paragraph-xs max-h-80 overflow-y-auto scrollbar",
+                    p { "Time" }
+                    p { "Tick" }
+                    p { "Input" }
+                    p { "State" }
+                    p { "Action" }
+                    for event in events.read().iter().rev() {
+                        InputEventRow { event: event.clone() }
+                    }
+                }
+            }
+            p { class: "paragraph-xs !text-gray-400 mt-2",
+                "The last 5000 keys and mouse inputs sent by the bot, newest first."
+            }
         }
     }
+}
+
+#[component]
+fn InputEventRow(event: InputEvent) -> Element {
+    let time = humantime::format_rfc3339_millis(UNIX_EPOCH + Duration::from_millis(event.millis));
+    let action = match (event.action_id, event.action) {
+        (Some(id), Some(action)) => format!("#{id} {action}"),
+        (None, Some(action)) => action,
+        _ => "None".to_string(),
+    };
 
     rsx! {
-        Section { name: "Hotkeys",
+        p { "{time}" }
+        p { "{event.tick}" }
+        p { "{event.kind} {event.input}" }
+        p { "{event.state}" }
+        p { "{action}" }
+    }
+}
+
+#[component]
+fn SectionEventScreenshots(
+    settings_view: Memo<SettingsData>,
+    save_settings: EventHandler<SettingsData>,
+) -> Element {
+    rsx! {
+        Section { name: "Event screenshots",
             div { class: "grid grid-cols-2 gap-3",
-                Hotkey {
-                    label: "Toggle start/stop actions",
-                    on_value: move |toggle_actions_key| {
+                SettingsTextInput {
+                    text_label: "Folder",
+                    button_label: "Update",
+                    on_value: move |event_screenshots_dir| {
                         save_settings(SettingsData {
-                            toggle_actions_key,
+                            event_screenshots_dir,
                             ..settings_view.peek().clone()
                         });
                     },
-                    value: settings_view().toggle_actions_key,
+                    value: settings_view().event_screenshots_dir,
                 }
-                Hotkey {
-                    label: "Add platform",
-                    on_value: move |platform_add_key| {
+                SettingsCheckbox {
+                    label: "Enabled",
+                    on_value: move |enable_event_screenshots| {
                         save_settings(SettingsData {
-                            platform_add_key,
+                            enable_event_screenshots,
                             ..settings_view.peek().clone()
                         });
                     },
-                    value: settings_view().platform_add_key,
+                    value: settings_view().enable_event_screenshots,
                 }
-                Hotkey {
-                    label: "Mark platform start",
-                    on_value: move |platform_start_key| {
+                NumberInputU32 {
+                    label: "Maximum screenshots",
+                    minimum_value: 1,
+                    on_value: move |event_screenshots_max_count| {
                         save_settings(SettingsData {
-                            platform_start_key,
+                            event_screenshots_max_count,
                             ..settings_view.peek().clone()
                         });
                     },
-                    value: settings_view().platform_start_key,
+                    value: settings_view().event_screenshots_max_count,
                 }
-                Hotkey {
-                    label: "Mark platform end",
-                    on_value: move |platform_end_key| {
+                NumberInputU32 {
+                    label: "Maximum size (MB)",
+                    minimum_value: 1,
+                    on_value: move |event_screenshots_max_size_mb| {
                         save_settings(SettingsData {
-                            platform_end_key,
+                            event_screenshots_max_size_mb,
                             ..settings_view.peek().clone()
                         });
                     },
-                    value: settings_view().platform_end_key,
+                    value: settings_view().event_screenshots_max_size_mb,
                 }
             }
+            p { class: "paragraph-xs !text-gray-400 mt-2",
+                "Saves annotated screenshots when a rune fails, the player dies, a stranger appears, unstucking enters GAMBA mode or the map changes. The oldest screenshots are removed once either limit is exceeded. Uses the recordings/screenshots folder next to the executable if the folder is empty."
+            }
         }
     }
 }
 
 #[component]
-fn SectionRunStopCycle(
+fn SnapshotDetails(snapshot: StateSnapshot) -> Element {
+    let time =
+        humantime::format_rfc3339_millis(UNIX_EPOCH + Duration::from_millis(snapshot.millis));
+    let position = snapshot
+        .position
+        .map(|(x, y)| format!("{x}, {y}"))
+        .unwrap_or("Unknown".to_string());
+    let priority_action = snapshot.priority_action.unwrap_or("None".to_string());
+    let normal_action = snapshot.normal_action.unwrap_or("None".to_string());
+    let keys = snapshot.keys.join(", ");
+
+    rsx! {
+        div { class: "grid grid-cols-2 gap-1 mt-2 paragraph-xs",
+            p { "Tick" }
+            p { "{snapshot.tick}" }
+            p { "Time" }
+            p { "{time}" }
+            p { "Operation" }
+            p { "{snapshot.operation}" }
+            p { "State" }
+            p { "{snapshot.state}" }
+            p { "Position" }
+            p { "{position}" }
+            p { "Priority action" }
+            p { "{priority_action}" }
+            p { "Normal action" }
+            p { "{normal_action}" }
+            p { "Keys" }
+            p { "{keys}" }
+        }
+    }
+}
+
+#[component]
+fn SectionModels(
     settings_view: Memo<SettingsData>,
     save_settings: EventHandler<SettingsData>,
 ) -> Element {
+    let models_view = use_memo(move || settings_view().models);
+    let mut reload_failed = use_signal(|| false);
+
     rsx! {
-        Section { name: "Run/stop cycle",
+        Section { name: "Models",
             div { class: "grid grid-cols-3 gap-3",
-                MillisInput {
-                    label: "Run duration",
-                    on_value: move |cycle_run_duration_millis| {
+                SettingsTextInput {
+                    text_label: "Rune arrows model path",
+                    button_label: "Update",
+                    on_value: move |rune_model_path| {
                         save_settings(SettingsData {
-                            cycle_run_duration_millis,
+                            models: Models {
+                                rune_model_path,
+                                ..models_view.peek().clone()
+                            },
                             ..settings_view.peek().clone()
                         });
                     },
-                    value: settings_view().cycle_run_duration_millis,
+                    value: models_view().rune_model_path,
                 }
-                MillisInput {
-                    label: "Stop duration",
-                    on_value: move |cycle_stop_duration_millis| {
+                PercentageInput {
+                    label: "Rune arrows confidence",
+                    on_value: move |percent: f32| {
                         save_settings(SettingsData {
-                            cycle_stop_duration_millis,
+                            models: Models {
+                                rune_threshold: percent / 100.0,
+                                ..models_view.peek().clone()
+                            },
                             ..settings_view.peek().clone()
                         });
                     },
-                    value: settings_view().cycle_stop_duration_millis,
+                    value: models_view().rune_threshold * 100.0,
                 }
-                SettingsCheckbox {
-                    label: "Enabled",
-                    on_value: move |cycle_run_stop| {
+                SettingsTextInput {
+                    text_label: "Mob detection model path",
+                    button_label: "Update",
+                    on_value: move |mob_model_path| {
                         save_settings(SettingsData {
-                            cycle_run_stop,
+                            models: Models {
+                                mob_model_path,
+                                ..models_view.peek().clone()
+                            },
                             ..settings_view.peek().clone()
                         });
                     },
-                    value: settings_view().cycle_run_stop,
+                    value: models_view().mob_model_path,
+                }
+                PercentageInput {
+                    label: "Mob detection confidence",
+                    on_value: move |percent: f32| {
+                        save_settings(SettingsData {
+                            models: Models {
+                                mob_threshold: percent / 100.0,
+                                ..models_view.peek().clone()
+                            },
+                            ..settings_view.peek().clone()
+                        });
+                    },
+                    value: models_view().mob_threshold * 100.0,
+                }
+                SettingsEnumSelect::<ExecutionProvider> {
+                    label: "Execution provider (requires restart)",
+                    on_select: move |execution_provider| async move {
+                        save_settings(SettingsData {
+                            execution_provider,
+                            ..settings_view.peek().clone()
+                        });
+                    },
+                    selected: settings_view().execution_provider,
+                }
+                div { class: "flex items-end",
+                    Button {
+                        text: "Reload models",
+                        kind: ButtonKind::Secondary,
+                        on_click: move |_| async move {
+                            reload_failed.set(!reload_models().await);
+                        },
+                        class: "w-full",
+                    }
+                }
+            }
+            if reload_failed() {
+                p { class: "paragraph-xs !text-gray-400 mt-2",
+                    "Failed to load a model, the built-in model is used instead."
+                }
+            } else {
+                p { class: "paragraph-xs !text-gray-400 mt-2",
+                    "Leave the path empty to use the built-in model."
                 }
             }
         }
@@ -595,6 +2711,57 @@ fn SectionOthers(
                     },
                     value: settings_view().enable_rune_solving,
                 }
+                SettingsCheckbox {
+                    label: "Solve rune only when no other player or elite boss",
+                    on_value: move |rune_solving_only_when_safe| {
+                        save_settings(SettingsData {
+                            rune_solving_only_when_safe,
+                            ..settings_view.peek().clone()
+                        });
+                    },
+                    value: settings_view().rune_solving_only_when_safe,
+                }
+                SettingsCheckbox {
+                    label: "Retry rune with enlarged crop",
+                    on_value: move |rune_fallback_enlarged_crop| {
+                        save_settings(SettingsData {
+                            rune_fallback_enlarged_crop,
+                            ..settings_view.peek().clone()
+                        });
+                    },
+                    value: settings_view().rune_fallback_enlarged_crop,
+                }
+                SettingsCheckbox {
+                    label: "Stop actions instead of cash shop on rune fails",
+                    on_value: move |rune_fallback_halt| {
+                        save_settings(SettingsData {
+                            rune_fallback_halt,
+                            ..settings_view.peek().clone()
+                        });
+                    },
+                    value: settings_view().rune_fallback_halt,
+                }
+                SettingsCheckbox {
+                    label: "Wait for rune arrows typed remotely",
+                    on_value: move |rune_fallback_remote| {
+                        save_settings(SettingsData {
+                            rune_fallback_remote,
+                            ..settings_view.peek().clone()
+                        });
+                    },
+                    value: settings_view().rune_fallback_remote,
+                }
+                NumberInputU32 {
+                    label: "Rune arrows verification frames",
+                    minimum_value: 1,
+                    on_value: move |rune_verify_frame_count| {
+                        save_settings(SettingsData {
+                            rune_verify_frame_count,
+                            ..settings_view.peek().clone()
+                        });
+                    },
+                    value: settings_view().rune_verify_frame_count,
+                }
                 div {}
                 SettingsCheckbox {
                     label: "Stop actions on fail or map changed",
@@ -616,6 +2783,46 @@ fn SectionOthers(
                     },
                     value: settings_view().enable_panic_mode,
                 }
+                SettingsCheckbox {
+                    label: "Prevent sleep while running",
+                    on_value: move |prevent_sleep_while_running| {
+                        save_settings(SettingsData {
+                            prevent_sleep_while_running,
+                            ..settings_view.peek().clone()
+                        });
+                    },
+                    value: settings_view().prevent_sleep_while_running,
+                }
+                SettingsCheckbox {
+                    label: "Auto-select map on map changed",
+                    on_value: move |auto_select_minimap| {
+                        save_settings(SettingsData {
+                            auto_select_minimap,
+                            ..settings_view.peek().clone()
+                        });
+                    },
+                    value: settings_view().auto_select_minimap,
+                }
+                SettingsCheckbox {
+                    label: "Skip detections on unchanged buffs and HP regions",
+                    on_value: move |enable_performance_mode| {
+                        save_settings(SettingsData {
+                            enable_performance_mode,
+                            ..settings_view.peek().clone()
+                        });
+                    },
+                    value: settings_view().enable_performance_mode,
+                }
+                SettingsEnumSelect::<DetectionScale> {
+                    label: "Detection scale",
+                    on_select: move |detection_scale| async move {
+                        save_settings(SettingsData {
+                            detection_scale,
+                            ..settings_view.peek().clone()
+                        });
+                    },
+                    selected: settings_view().detection_scale,
+                }
                 div {
                     a { id: export_element_id(), class: "w-0 h-0 invisible" }
                     Button {
@@ -0,0 +1,246 @@
+use std::time::{Duration, UNIX_EPOCH};
+
+use backend::{
+    DatabaseEvent, SessionStats, database_event_receiver, delete_session_stats,
+    query_current_session_stats, query_session_stats,
+};
+use dioxus::prelude::*;
+use tokio::{sync::broadcast::error::RecvError, time::sleep};
+
+use crate::button::{Button, ButtonKind};
+
+#[component]
+pub fn Statistics() -> Element {
+    let mut current = use_signal::<Option<SessionStats>>(|| None);
+    let mut sessions = use_resource(async || query_session_stats().await.unwrap_or_default());
+    // Most recent sessions first
+    let sessions_view = use_memo(move || {
+        let mut sessions = sessions().unwrap_or_default();
+        sessions.reverse();
+        sessions
+    });
+    let total = use_memo(move || total_stats(&sessions_view()));
+    // The current session or the most recent session if the actions are not running
+    let income_samples = use_memo(move || {
+        current()
+            .or_else(|| sessions_view().into_iter().next())
+            .map(|stats| stats.income_samples)
+            .unwrap_or_default()
+    });
+
+    use_future(move || async move {
+        loop {
+            current.set(query_current_session_stats().await);
+            sleep(Duration::from_secs(1)).await;
+        }
+    });
+    use_future(move || async move {
+        let mut rx = database_event_receiver();
+        loop {
+            let event = match rx.recv().await {
+                Ok(value) => value,
+                Err(RecvError::Closed) => break,
+                Err(RecvError::Lagged(_)) => continue,
+            };
+            if matches!(event, DatabaseEvent::SessionStatsUpdated) {
+                sessions.restart();
+            }
+        }
+    });
+
+    rsx! {
+        div { class: "flex flex-col h-full overflow-y-auto scrollbar pr-4 pb-3",
+            div { class: "flex items-center title-xs h-10", "Current session" }
+            if let Some(stats) = current() {
+                SessionStatsItem { stats }
+            } else {
+                p { class: "paragraph-xs !text-gray-400", "Actions are not running." }
+            }
+            if !income_samples().is_empty() {
+                div { class: "grid grid-cols-2 gap-3 mt-3",
+                    IncomeGraph {
+                        name: "EXP/hour",
+                        values: income_samples()
+                            .iter()
+                            .map(|sample| (sample.elapsed_millis, sample.exp_percent_per_hour))
+                            .collect::<Vec<_>>(),
+                    }
+                    IncomeGraph {
+                        name: "Mesos/hour",
+                        values: income_samples()
+                            .iter()
+                            .map(|sample| (sample.elapsed_millis, sample.mesos_per_hour))
+                            .collect::<Vec<_>>(),
+                    }
+                }
+            }
+            div { class: "flex items-center title-xs h-10 mt-3", "All sessions" }
+            SessionStatsItem { stats: total() }
+            div { class: "flex items-center justify-between h-10 mt-3",
+                p { class: "title-xs", "Recent sessions" }
+                Button {
+                    class: "w-24",
+                    text: "Clear",
+                    kind: ButtonKind::Danger,
+                    disabled: sessions_view().is_empty(),
+                    on_click: move |_| async move {
+                        delete_session_stats().await;
+                    },
+                }
+            }
+            div { class: "flex flex-col gap-3",
+                for stats in sessions_view() {
+                    div { class: "flex flex-col",
+                        p { class: "paragraph-xs !text-gray-400",
+                            {
+                                format!(
+                                    "{} - {}",
+                                    humantime::format_rfc3339_seconds(
+                                        UNIX_EPOCH + Duration::from_millis(stats.started_at_millis),
+                                    ),
+                                    stats.stop_reason,
+                                )
+                            }
+                        }
+                        SessionStatsItem { stats }
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[component]
+fn SessionStatsItem(stats: SessionStats) -> Element {
+    let secs = stats.runtime_millis / 1000;
+    let exp_per_hour = stats
+        .exp_percent_per_hour
+        .map(|exp| format!("{exp:.2}%"))
+        .unwrap_or("-".to_string());
+    let mesos_per_hour = stats
+        .mesos_per_hour
+        .map(|mesos| format!("{mesos:.0}"))
+        .unwrap_or("-".to_string());
+
+    rsx! {
+        div { class: "grid grid-cols-4 gap-x-3 gap-y-1",
+            StatsItem {
+                name: "Runtime",
+                value: format!("{}h {}m {}s", secs / 3600, secs % 3600 / 60, secs % 60),
+            }
+            StatsItem { name: "EXP/hour", value: exp_per_hour }
+            StatsItem { name: "Mesos/hour", value: mesos_per_hour }
+            StatsItem { name: "Actions executed", value: stats.completed_actions.to_string() }
+            StatsItem { name: "Deaths", value: stats.deaths.to_string() }
+            StatsItem { name: "Runes solved", value: stats.solved_runes.to_string() }
+            StatsItem { name: "Runes failed", value: stats.failed_runes.to_string() }
+            StatsItem { name: "Channel changes", value: stats.channel_changes.to_string() }
+            StatsItem { name: "Unstuck events", value: stats.unstuck_events.to_string() }
+        }
+    }
+}
+
+/// Plots `values` of elapsed milliseconds and the estimated amount per hour as a line.
+#[component]
+fn IncomeGraph(name: String, values: Vec<(u64, Option<f32>)>) -> Element {
+    const WIDTH: f32 = 200.0;
+    const HEIGHT: f32 = 60.0;
+
+    let values = values
+        .into_iter()
+        .filter_map(|(millis, value)| value.map(|value| (millis, value)))
+        .collect::<Vec<_>>();
+    let max_millis = values
+        .last()
+        .map(|(millis, _)| *millis)
+        .unwrap_or_default()
+        .max(1);
+    let max_value = values
+        .iter()
+        .map(|(_, value)| *value)
+        .fold(0.0, f32::max)
+        .max(f32::EPSILON);
+    let points = values
+        .iter()
+        .map(|(millis, value)| {
+            let x = *millis as f32 / max_millis as f32 * WIDTH;
+            let y = HEIGHT - value / max_value * HEIGHT;
+            format!("{x:.1},{y:.1}")
+        })
+        .collect::<Vec<_>>()
+        .join(" ");
+    let latest = values
+        .last()
+        .map(|(_, value)| format!("{value:.2}"))
+        .unwrap_or("-".to_string());
+
+    rsx! {
+        div { class: "flex flex-col",
+            div { class: "flex justify-between",
+                p { class: "paragraph-xs !text-gray-400", "{name}" }
+                p { class: "paragraph-xs font-mono", "{latest}" }
+            }
+            svg {
+                class: "w-full h-16 border border-gray-600",
+                view_box: "0 0 {WIDTH} {HEIGHT}",
+                preserve_aspect_ratio: "none",
+                polyline {
+                    points,
+                    fill: "none",
+                    stroke: "currentColor",
+                    stroke_width: "1",
+                    vector_effect: "non-scaling-stroke",
+                }
+            }
+        }
+    }
+}
+
+#[component]
+fn StatsItem(name: String, value: String) -> Element {
+    rsx! {
+        div { class: "flex flex-col",
+            p { class: "paragraph-xs !text-gray-400", "{name}" }
+            p { class: "paragraph font-mono", "{value}" }
+        }
+    }
+}
+
+/// Sums `sessions` into a single [`SessionStats`].
+fn total_stats(sessions: &[SessionStats]) -> SessionStats {
+    let mut total = sessions
+        .iter()
+        .fold(SessionStats::default(), |total, stats| SessionStats {
+            runtime_millis: total.runtime_millis + stats.runtime_millis,
+            completed_actions: total.completed_actions + stats.completed_actions,
+            solved_runes: total.solved_runes + stats.solved_runes,
+            failed_runes: total.failed_runes + stats.failed_runes,
+            deaths: total.deaths + stats.deaths,
+            channel_changes: total.channel_changes + stats.channel_changes,
+            unstuck_events: total.unstuck_events + stats.unstuck_events,
+            ..total
+        });
+    total.exp_percent_per_hour = weighted_per_hour(sessions, |stats| stats.exp_percent_per_hour);
+    total.mesos_per_hour = weighted_per_hour(sessions, |stats| stats.mesos_per_hour);
+    total
+}
+
+/// Averages the per hour amount from `per_hour` weighted by the runtime of the sessions that
+/// tracked it.
+fn weighted_per_hour(
+    sessions: &[SessionStats],
+    per_hour: impl Fn(&SessionStats) -> Option<f32>,
+) -> Option<f32> {
+    let (amount, millis) = sessions
+        .iter()
+        .filter_map(|stats| {
+            per_hour(stats).map(|value| (value * stats.runtime_millis as f32, stats.runtime_millis))
+        })
+        .fold(
+            (0.0, 0),
+            |(amount, millis), (session_amount, session_millis)| {
+                (amount + session_amount, millis + session_millis)
+            },
+        );
+    (millis > 0).then(|| amount / millis as f32)
+}